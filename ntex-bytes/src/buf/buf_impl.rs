@@ -1,4 +1,4 @@
-use std::{cmp, mem, ptr};
+use std::{cmp, io::IoSlice, mem, ptr};
 
 macro_rules! buf_get_impl {
     ($this:ident, $typ:tt::$conv:tt) => {{
@@ -120,6 +120,35 @@ pub trait Buf {
     /// empty slice.
     fn chunk(&self) -> &[u8];
 
+    /// Fills `dst` with potentially multiple slices starting at `self`'s
+    /// current position.
+    ///
+    /// Returns number of filled slices. Default implementation fills
+    /// single slice with `Buf::chunk()`, implementations with non-contiguous
+    /// storage could fill more slices, this allows to write all data
+    /// with single vectored io call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::IoSlice;
+    /// use ntex_bytes::Buf;
+    ///
+    /// let buf = &b"hello world"[..];
+    /// let mut dst = [IoSlice::new(&[]); 2];
+    ///
+    /// assert_eq!(buf.chunks_vectored(&mut dst), 1);
+    /// assert_eq!(&dst[0][..], &b"hello world"[..]);
+    /// ```
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        if dst.is_empty() || !self.has_remaining() {
+            0
+        } else {
+            dst[0] = IoSlice::new(self.chunk());
+            1
+        }
+    }
+
     /// Advance the internal cursor of the Buf
     ///
     /// The next call to `bytes` will return a slice starting `cnt` bytes
@@ -802,6 +831,11 @@ impl<T: Buf + ?Sized> Buf for &mut T {
         (**self).chunk()
     }
 
+    #[inline]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        (**self).chunks_vectored(dst)
+    }
+
     #[inline]
     fn advance(&mut self, cnt: usize) {
         (**self).advance(cnt)
@@ -819,6 +853,11 @@ impl<T: Buf + ?Sized> Buf for Box<T> {
         (**self).chunk()
     }
 
+    #[inline]
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        (**self).chunks_vectored(dst)
+    }
+
     fn advance(&mut self, cnt: usize) {
         (**self).advance(cnt)
     }
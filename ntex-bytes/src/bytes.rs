@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::borrow::{Borrow, BorrowMut};
 use std::ops::{Deref, DerefMut, RangeBounds};
 use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
@@ -372,6 +373,8 @@ struct Shared {
     vec: Vec<u8>,
     ref_count: AtomicUsize,
     pool: PoolRef,
+    // external owner of the data, `vec` is empty if owner is set
+    owner: Option<Box<dyn Any + Send + Sync>>,
 }
 
 struct SharedVec {
@@ -459,6 +462,34 @@ impl Bytes {
         }
     }
 
+    /// Creates a new `Bytes` from externally owned memory.
+    ///
+    /// The returned `Bytes` points directly to the memory of the owner, there
+    /// is no copying. The owner is dropped when the last `Bytes` handle
+    /// is dropped, so owner's `Drop` impl could be used to release
+    /// the memory, e.g. to unmap memory-mapped file.
+    ///
+    /// Buffer is read-only, `try_mut()` never succeeds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use ntex_bytes::Bytes;
+    ///
+    /// let data: Arc<[u8]> = Arc::from(&b"hello"[..]);
+    /// let b = Bytes::from_owner(data);
+    /// assert_eq!(&b[..], b"hello");
+    /// ```
+    pub fn from_owner<T>(owner: T) -> Bytes
+    where
+        T: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        Bytes {
+            inner: Inner::from_owner(Box::new(owner)),
+        }
+    }
+
     /// Returns the number of bytes contained in this `Bytes`.
     ///
     /// # Examples
@@ -3013,6 +3044,7 @@ impl Inner {
             vec,
             pool,
             ref_count: AtomicUsize::new(1),
+            owner: None,
         }));
 
         // The pointer should be aligned, so this assert should always succeed.
@@ -3027,6 +3059,33 @@ impl Inner {
         }
     }
 
+    #[inline]
+    fn from_owner<T>(owner: Box<T>) -> Inner
+    where
+        T: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        // owner is boxed, so data pointer stays valid while owner is alive
+        let data = (*owner).as_ref();
+        let ptr = data.as_ptr() as *mut u8;
+        let len = data.len();
+
+        // external memory is not accounted in memory pool
+        let shared = Box::into_raw(Box::new(Shared {
+            vec: Vec::new(),
+            pool: PoolId::DEFAULT.pool_ref(),
+            ref_count: AtomicUsize::new(1),
+            owner: Some(owner),
+        }));
+        debug_assert!(0 == (shared as usize & KIND_MASK));
+
+        Inner {
+            ptr,
+            len,
+            cap: len,
+            arc: unsafe { NonNull::new_unchecked(shared) },
+        }
+    }
+
     #[inline]
     fn with_capacity(capacity: usize, pool: PoolRef) -> Inner {
         Inner::from_slice(capacity, &[], pool)
@@ -3731,7 +3790,9 @@ impl Shared {
         // are ordered before the `ref_count` is decremented. As such,
         // this `Acquire` will guarantee that those mutations are
         // visible to the current thread.
-        self.ref_count.load(Acquire) == 1
+        //
+        // Externally owned memory is never mutable.
+        self.owner.is_none() && self.ref_count.load(Acquire) == 1
     }
 }
 
@@ -59,12 +59,18 @@ pub use crate::buf::{Buf, BufMut};
 mod bytes;
 mod debug;
 mod hex;
+mod list;
 mod pool;
+#[cfg(feature = "serde")]
 mod serde;
 mod string;
 
 pub use crate::bytes::{Bytes, BytesMut, BytesVec};
-pub use crate::string::ByteString;
+pub use crate::list::BytesList;
+pub use crate::string::{ByteString, ByteStringBuilder};
+
+#[cfg(feature = "serde")]
+pub use crate::serde::base64;
 
 #[doc(hidden)]
-pub use crate::pool::{Pool, PoolId, PoolRef};
+pub use crate::pool::{Pool, PoolId, PoolRef, PoolStats};
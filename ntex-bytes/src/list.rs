@@ -0,0 +1,262 @@
+use std::{collections::VecDeque, fmt, io::IoSlice};
+
+use crate::{Buf, BufMut, Bytes, BytesMut};
+
+/// A list of `Bytes` segments.
+///
+/// `BytesList` aggregates multiple `Bytes` segments without copying,
+/// it could be consumed via `Buf` trait, `chunks_vectored()` fills io
+/// slices with all segments for vectored writes.
+///
+/// # Examples
+///
+/// ```
+/// use ntex_bytes::{Buf, Bytes, BytesList};
+///
+/// let mut list = BytesList::new();
+/// list.push(Bytes::from_static(b"hello "));
+/// list.push(Bytes::from_static(b"world"));
+///
+/// assert_eq!(list.len(), 11);
+/// assert_eq!(list.chunk(), b"hello ");
+/// assert_eq!(list.to_bytes(), "hello world");
+/// ```
+#[derive(Clone, Default)]
+pub struct BytesList {
+    segments: VecDeque<Bytes>,
+    len: usize,
+}
+
+impl BytesList {
+    /// Creates a new empty `BytesList`.
+    #[inline]
+    pub fn new() -> Self {
+        BytesList {
+            segments: VecDeque::new(),
+            len: 0,
+        }
+    }
+
+    /// Creates a new empty `BytesList` with space for `cap` segments.
+    #[inline]
+    pub fn with_capacity(cap: usize) -> Self {
+        BytesList {
+            segments: VecDeque::with_capacity(cap),
+            len: 0,
+        }
+    }
+
+    /// Returns the total number of bytes in all segments.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the list has no bytes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of segments.
+    #[inline]
+    pub fn segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Appends segment to the end of the list.
+    ///
+    /// Empty segments are ignored.
+    pub fn push(&mut self, segment: Bytes) {
+        if !segment.is_empty() {
+            self.len += segment.len();
+            self.segments.push_back(segment);
+        }
+    }
+
+    /// Prepends segment to the start of the list.
+    ///
+    /// Empty segments are ignored.
+    pub fn push_front(&mut self, segment: Bytes) {
+        if !segment.is_empty() {
+            self.len += segment.len();
+            self.segments.push_front(segment);
+        }
+    }
+
+    /// Removes the first segment from the list.
+    pub fn pop_front(&mut self) -> Option<Bytes> {
+        let segment = self.segments.pop_front()?;
+        self.len -= segment.len();
+        Some(segment)
+    }
+
+    /// Removes all segments.
+    pub fn clear(&mut self) {
+        self.segments.clear();
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the segments.
+    pub fn iter(&self) -> impl Iterator<Item = &Bytes> {
+        self.segments.iter()
+    }
+
+    /// Splits the list into two at the given index.
+    ///
+    /// Afterwards `self` contains elements `[at, len)`, and the returned
+    /// `BytesList` contains elements `[0, at)`. Segments are split without
+    /// copying.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_to(&mut self, mut at: usize) -> BytesList {
+        assert!(
+            at <= self.len,
+            "split_to out of bounds: {} <= {}",
+            at,
+            self.len
+        );
+
+        let mut list = BytesList::new();
+        while at > 0 {
+            let segment = self.segments.front_mut().unwrap();
+            if segment.len() <= at {
+                at -= segment.len();
+                let segment = self.pop_front().unwrap();
+                list.push(segment);
+            } else {
+                self.len -= at;
+                list.push(segment.split_to(at));
+                break;
+            }
+        }
+        list
+    }
+
+    /// Converts list into a single `Bytes`.
+    ///
+    /// Single segment is returned without copying.
+    pub fn freeze(mut self) -> Bytes {
+        match self.segments.len() {
+            0 => Bytes::new(),
+            1 => self.segments.pop_front().unwrap(),
+            _ => {
+                let mut buf = BytesMut::with_capacity(self.len);
+                for segment in &self.segments {
+                    buf.put_slice(segment);
+                }
+                buf.freeze()
+            }
+        }
+    }
+}
+
+impl Buf for BytesList {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self.segments
+            .front()
+            .map(|b| b.as_ref())
+            .unwrap_or_default()
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        assert!(cnt <= self.len, "cannot advance past `remaining`");
+
+        while cnt > 0 {
+            let segment = self.segments.front_mut().unwrap();
+            if segment.len() <= cnt {
+                cnt -= segment.len();
+                self.pop_front();
+            } else {
+                segment.advance(cnt);
+                self.len -= cnt;
+                break;
+            }
+        }
+    }
+
+    fn chunks_vectored<'a>(&'a self, dst: &mut [IoSlice<'a>]) -> usize {
+        let mut n = 0;
+        for (slice, segment) in dst.iter_mut().zip(self.segments.iter()) {
+            *slice = IoSlice::new(segment);
+            n += 1;
+        }
+        n
+    }
+
+    fn to_bytes(&mut self) -> Bytes {
+        let list = std::mem::take(self);
+        list.freeze()
+    }
+}
+
+impl From<Bytes> for BytesList {
+    fn from(segment: Bytes) -> Self {
+        let mut list = BytesList::new();
+        list.push(segment);
+        list
+    }
+}
+
+impl From<BytesList> for Bytes {
+    fn from(list: BytesList) -> Self {
+        list.freeze()
+    }
+}
+
+impl FromIterator<Bytes> for BytesList {
+    fn from_iter<T: IntoIterator<Item = Bytes>>(iter: T) -> Self {
+        let mut list = BytesList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl Extend<Bytes> for BytesList {
+    fn extend<T: IntoIterator<Item = Bytes>>(&mut self, iter: T) {
+        for segment in iter {
+            self.push(segment);
+        }
+    }
+}
+
+impl IntoIterator for BytesList {
+    type Item = Bytes;
+    type IntoIter = std::collections::vec_deque::IntoIter<Bytes>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.segments.into_iter()
+    }
+}
+
+impl PartialEq for BytesList {
+    fn eq(&self, other: &BytesList) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .flat_map(|b| b.iter())
+                .eq(other.iter().flat_map(|b| b.iter()))
+    }
+}
+
+impl Eq for BytesList {}
+
+impl PartialEq<[u8]> for BytesList {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.len == other.len() && self.iter().flat_map(|b| b.iter()).eq(other.iter())
+    }
+}
+
+impl fmt::Debug for BytesList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.segments.iter()).finish()
+    }
+}
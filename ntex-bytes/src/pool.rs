@@ -25,6 +25,25 @@ pub struct BufParams {
     pub low: u32,
 }
 
+/// Memory pool usage statistics
+#[derive(Copy, Clone, Debug)]
+pub struct PoolStats {
+    /// Pool id
+    pub id: PoolId,
+    /// Total number of allocated bytes
+    pub allocated: usize,
+    /// Number of bytes held by buffers in use
+    pub in_use: usize,
+    /// Number of bytes held by io read/write buffers cache
+    pub cached: usize,
+    /// Highest number of allocated bytes since last reset
+    pub peak: usize,
+    /// Hard allocation limit, `0` if not set
+    pub limit: usize,
+    /// Number of bytes allocated outside of the pool, included in `allocated`
+    pub external: usize,
+}
+
 bitflags::bitflags! {
     #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
     struct Flags: u8 {
@@ -41,7 +60,10 @@ struct MemoryPool {
     flags: Cell<Flags>,
 
     size: AtomicUsize,
+    peak: AtomicUsize,
+    external: AtomicUsize,
     max_size: Cell<usize>,
+    limit: Cell<usize>,
 
     window_h: Cell<usize>,
     window_l: Cell<usize>,
@@ -98,6 +120,13 @@ impl PoolId {
         self
     }
 
+    #[inline]
+    /// Set hard allocation limit
+    pub fn set_limit(self, size: usize) -> Self {
+        self.pool_ref().set_limit(size);
+        self
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn set_read_params(self, h: u32, l: u32) -> Self {
@@ -126,6 +155,11 @@ impl PoolId {
         self
     }
 
+    /// Get usage statistics of all pools
+    pub fn stats_all() -> Vec<PoolStats> {
+        POOLS.with(|pools| pools.iter().map(|p| PoolRef(p).stats()).collect())
+    }
+
     /// Set future spawn fn to all pools
     pub fn set_spawn_fn_all<T>(f: T)
     where
@@ -184,6 +218,105 @@ impl PoolRef {
         self.0.size.load(Relaxed)
     }
 
+    #[inline]
+    /// Get highest number of allocated bytes since last reset.
+    pub fn peak_allocated(self) -> usize {
+        self.0.peak.load(Relaxed)
+    }
+
+    #[inline]
+    /// Reset highest number of allocated bytes to current value.
+    pub fn reset_peak(self) {
+        self.0.peak.store(self.0.size.load(Relaxed), Relaxed);
+    }
+
+    /// Get number of bytes held by io read/write buffers cache.
+    pub fn cached(self) -> usize {
+        let read: usize = self
+            .0
+            .read_cache
+            .borrow()
+            .iter()
+            .map(|b| b.capacity())
+            .sum();
+        let write: usize = self
+            .0
+            .write_cache
+            .borrow()
+            .iter()
+            .map(|b| b.capacity())
+            .sum();
+        read + write
+    }
+
+    /// Get pool usage statistics.
+    pub fn stats(self) -> PoolStats {
+        let allocated = self.allocated();
+        let cached = self.cached();
+        PoolStats {
+            id: self.0.id,
+            allocated,
+            cached,
+            in_use: allocated.saturating_sub(cached),
+            peak: self.peak_allocated(),
+            limit: self.0.limit.get(),
+            external: self.external(),
+        }
+    }
+
+    #[inline]
+    /// Get number of bytes allocated outside of the pool.
+    pub fn external(self) -> usize {
+        self.0.external.load(Relaxed)
+    }
+
+    #[inline]
+    /// Account memory allocated outside of the pool.
+    ///
+    /// Memory that is owned by third-party libraries (for example tls
+    /// session buffers) could be accounted in pool's allocated size.
+    /// Accounted size must be released with `release_external()`.
+    pub fn acquire_external(self, size: usize) {
+        self.0.external.fetch_add(size, Relaxed);
+        self.acquire(size);
+    }
+
+    #[inline]
+    /// Release memory accounted with `acquire_external()`.
+    pub fn release_external(self, size: usize) {
+        self.0.external.fetch_sub(size, Relaxed);
+        self.release(size);
+    }
+
+    #[inline]
+    /// Get hard allocation limit, `0` if limit is not set.
+    pub fn limit(self) -> usize {
+        self.0.limit.get()
+    }
+
+    #[inline]
+    /// Set hard allocation limit.
+    ///
+    /// Limit does not affect regular allocations, `*_checked` methods
+    /// refuse to allocate if limit is exceeded. `0` disables limit.
+    pub fn set_limit(self, size: usize) -> Self {
+        self.0.limit.set(size);
+        self
+    }
+
+    #[inline]
+    /// Check if allocated memory exceeds hard limit.
+    pub fn is_limit_exceeded(self) -> bool {
+        let limit = self.0.limit.get();
+        limit != 0 && self.allocated() > limit
+    }
+
+    #[inline]
+    fn check_limit(self, cap: usize) -> bool {
+        let limit = self.0.limit.get();
+        limit == 0 || self.allocated().saturating_add(cap) <= limit
+    }
+
     #[inline]
     pub fn move_in(self, buf: &mut BytesMut) {
         buf.move_to_pool(self);
@@ -206,6 +339,30 @@ impl PoolRef {
         BytesVec::with_capacity_in(cap, self)
     }
 
+    #[inline]
+    /// Creates a new `BytesMut` with the specified capacity.
+    ///
+    /// Returns `None` if allocation exceeds pool's hard limit.
+    pub fn buf_with_capacity_checked(self, cap: usize) -> Option<BytesMut> {
+        if self.check_limit(cap) {
+            Some(BytesMut::with_capacity_in(cap, self))
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    /// Creates a new `BytesVec` with the specified capacity.
+    ///
+    /// Returns `None` if allocation exceeds pool's hard limit.
+    pub fn vec_with_capacity_checked(self, cap: usize) -> Option<BytesVec> {
+        if self.check_limit(cap) {
+            Some(BytesVec::with_capacity_in(cap, self))
+        } else {
+            None
+        }
+    }
+
     #[doc(hidden)]
     #[inline]
     /// Set max pool size
@@ -357,6 +514,7 @@ impl PoolRef {
     #[inline]
     pub(crate) fn acquire(self, size: usize) {
         let prev = self.0.size.fetch_add(size, Relaxed);
+        self.0.peak.fetch_max(prev + size, Relaxed);
         if self.0.waker_alive.load(Relaxed) {
             self.wake_driver(prev + size)
         }
@@ -428,7 +586,10 @@ impl MemoryPool {
             flags: Cell::new(Flags::empty()),
 
             size: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            external: AtomicUsize::new(0),
             max_size: Cell::new(0),
+            limit: Cell::new(0),
 
             window_h: Cell::new(0),
             window_l: Cell::new(0),
@@ -499,6 +660,7 @@ impl fmt::Debug for Pool {
         f.debug_struct("Pool")
             .field("id", &self.id().0)
             .field("allocated", &self.inner.size.load(Relaxed))
+            .field("peak", &self.inner.peak.load(Relaxed))
             .field("ready", &self.is_ready())
             .finish()
     }
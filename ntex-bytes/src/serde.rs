@@ -1,10 +1,10 @@
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::{cmp, fmt};
 
-use super::{Bytes, BytesMut};
+use super::{Bytes, BytesMut, BytesVec};
 
 macro_rules! serde_impl {
-    ($ty:ident, $visitor_ty:ident, $from_slice:ident) => {
+    ($ty:ident, $visitor_ty:ident, $from_slice:ident, $from_vec:expr) => {
         impl Serialize for $ty {
             #[inline]
             fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -36,7 +36,7 @@ macro_rules! serde_impl {
                     values.push(value);
                 }
 
-                Ok(values.into())
+                Ok($from_vec(values))
             }
 
             #[inline]
@@ -52,7 +52,7 @@ macro_rules! serde_impl {
             where
                 E: de::Error,
             {
-                Ok($ty::from(v))
+                Ok($from_vec(v))
             }
 
             #[inline]
@@ -68,7 +68,7 @@ macro_rules! serde_impl {
             where
                 E: de::Error,
             {
-                Ok($ty::from(v))
+                Ok($from_vec(v.into_bytes()))
             }
         }
 
@@ -84,8 +84,148 @@ macro_rules! serde_impl {
     };
 }
 
-serde_impl!(Bytes, BytesVisitor, copy_from_slice);
-serde_impl!(BytesMut, BytesMutVisitor, from);
+serde_impl!(Bytes, BytesVisitor, copy_from_slice, Bytes::from);
+serde_impl!(BytesMut, BytesMutVisitor, from, BytesMut::from);
+serde_impl!(
+    BytesVec,
+    BytesVecVisitor,
+    copy_from_slice,
+    BytesVec::copy_from_slice
+);
+
+/// Base64 serialization of byte buffers.
+///
+/// Buffers are serialized as base64 strings for human readable formats
+/// and as raw bytes otherwise. Use with `#[serde(with = "ntex_bytes::base64")]`.
+pub mod base64 {
+    use std::fmt;
+
+    use serde::{de, Deserializer, Serializer};
+
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    /// Serialize buffer as base64 string or raw bytes, depending on format
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]>,
+        S: Serializer,
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&encode(value.as_ref()))
+        } else {
+            serializer.serialize_bytes(value.as_ref())
+        }
+    }
+
+    /// Deserialize buffer from base64 string or raw bytes, depending on format
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<Vec<u8>>,
+        D: Deserializer<'de>,
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(Base64Visitor).map(T::from)
+        } else {
+            deserializer.deserialize_byte_buf(RawVisitor).map(T::from)
+        }
+    }
+
+    struct Base64Visitor;
+
+    impl<'de> de::Visitor<'de> for Base64Visitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("base64 string")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            decode(v.as_bytes())
+                .ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+    }
+
+    struct RawVisitor;
+
+    impl<'de> de::Visitor<'de> for RawVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("byte array")
+        }
+
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(v)
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Self::Value, V::Error>
+        where
+            V: de::SeqAccess<'de>,
+        {
+            let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+            while let Some(value) = seq.next_element()? {
+                values.push(value);
+            }
+            Ok(values)
+        }
+    }
+
+    fn encode(src: &[u8]) -> String {
+        let mut out = String::with_capacity(src.len().div_ceil(3) * 4);
+        for chunk in src.chunks(3) {
+            let n = match *chunk {
+                [a, b, c] => ((a as u32) << 16) | ((b as u32) << 8) | c as u32,
+                [a, b] => ((a as u32) << 16) | ((b as u32) << 8),
+                [a] => (a as u32) << 16,
+                _ => unreachable!(),
+            };
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    out.push(ALPHABET[((n >> (18 - i * 6)) & 0x3f) as usize] as char);
+                } else {
+                    out.push('=');
+                }
+            }
+        }
+        out
+    }
+
+    fn decode(src: &[u8]) -> Option<Vec<u8>> {
+        if !src.len().is_multiple_of(4) {
+            return None;
+        }
+        let mut out = Vec::with_capacity(src.len() / 4 * 3);
+        for (idx, chunk) in src.chunks(4).enumerate() {
+            let last = idx == src.len() / 4 - 1;
+            let pad = chunk.iter().rev().take_while(|c| **c == b'=').count();
+            if pad > 2 || (pad > 0 && !last) {
+                return None;
+            }
+            let mut n = 0u32;
+            for c in &chunk[..4 - pad] {
+                let v = ALPHABET.iter().position(|a| a == c)? as u32;
+                n = (n << 6) | v;
+            }
+            n <<= 6 * pad as u32;
+            out.extend_from_slice(&n.to_be_bytes()[1..4 - pad]);
+        }
+        Some(out)
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -105,5 +245,39 @@ mod tests {
         assert_eq!(s, "[110,105,99,101,32,98,121,116,101,115]");
         let s = serde_json::to_string(&BytesMut::copy_from_slice(b"nice bytes")).unwrap();
         assert_eq!(s, "[110,105,99,101,32,98,121,116,101,115]");
+        let s = serde_json::to_string(&BytesVec::copy_from_slice(b"nice bytes")).unwrap();
+        assert_eq!(s, "[110,105,99,101,32,98,121,116,101,115]");
+        let s: BytesVec = serde_json::from_str(&s).unwrap();
+        assert_eq!(s, "nice bytes");
+    }
+
+    #[test]
+    fn test_base64() {
+        fn to_json(src: &str) -> String {
+            let mut buf = Vec::new();
+            let mut ser = serde_json::Serializer::new(&mut buf);
+            base64::serialize(&Bytes::copy_from_slice(src.as_bytes()), &mut ser).unwrap();
+            String::from_utf8(buf).unwrap()
+        }
+
+        fn from_json(src: &str) -> Result<Bytes, serde_json::Error> {
+            base64::deserialize(&mut serde_json::Deserializer::from_str(src))
+        }
+
+        for (src, encoded) in [
+            ("", "\"\""),
+            ("f", "\"Zg==\""),
+            ("fo", "\"Zm8=\""),
+            ("foo", "\"Zm9v\""),
+            ("foobar", "\"Zm9vYmFy\""),
+        ] {
+            assert_eq!(to_json(src), encoded);
+            assert_eq!(from_json(encoded).unwrap(), src);
+        }
+
+        assert!(from_json("\"Zg=\"").is_err());
+        assert!(from_json("\"Z===\"").is_err());
+        assert!(from_json("\"Zg==Zg==\"").is_err());
+        assert!(from_json("\"Z!==\"").is_err());
     }
 }
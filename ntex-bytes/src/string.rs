@@ -170,6 +170,168 @@ impl ByteString {
     }
 }
 
+/// A builder for `ByteString`.
+///
+/// Builder writes directly to `BytesMut` storage, finished `ByteString`
+/// does not require intermediate `String` allocation or utf-8 validation.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::Write;
+/// use ntex_bytes::ByteStringBuilder;
+///
+/// let mut b = ByteStringBuilder::with_capacity(16);
+/// b.push_str("max-age=");
+/// write!(b, "{}", 3600).unwrap();
+/// assert_eq!(b.finish(), "max-age=3600");
+/// ```
+#[derive(Default)]
+pub struct ByteStringBuilder(BytesMut);
+
+impl ByteStringBuilder {
+    /// Creates a new empty builder.
+    #[inline]
+    pub fn new() -> Self {
+        ByteStringBuilder(BytesMut::new())
+    }
+
+    /// Creates a new builder with the specified capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        ByteStringBuilder(BytesMut::with_capacity(capacity))
+    }
+
+    /// Returns the number of bytes written.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if nothing is written.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get a str slice of written data.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // builder accepts only valid utf-8 data
+        unsafe { str::from_utf8_unchecked(&self.0) }
+    }
+
+    /// Appends a string slice.
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        self.0.extend_from_slice(s.as_bytes());
+    }
+
+    /// Appends a char.
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Converts builder into `ByteString`.
+    #[inline]
+    pub fn finish(self) -> ByteString {
+        ByteString(self.0.freeze())
+    }
+}
+
+impl fmt::Write for ByteStringBuilder {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ByteStringBuilder {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(fmt)
+    }
+}
+
+impl<'a> Extend<&'a str> for ByteStringBuilder {
+    fn extend<T: IntoIterator<Item = &'a str>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|s| self.push_str(s));
+    }
+}
+
+impl Extend<char> for ByteStringBuilder {
+    fn extend<T: IntoIterator<Item = char>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        self.0.reserve(iter.size_hint().0);
+        iter.for_each(|ch| self.push(ch));
+    }
+}
+
+impl Extend<String> for ByteStringBuilder {
+    fn extend<T: IntoIterator<Item = String>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|s| self.push_str(&s));
+    }
+}
+
+impl Extend<ByteString> for ByteStringBuilder {
+    fn extend<T: IntoIterator<Item = ByteString>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|s| self.push_str(&s));
+    }
+}
+
+impl From<ByteStringBuilder> for ByteString {
+    #[inline]
+    fn from(builder: ByteStringBuilder) -> Self {
+        builder.finish()
+    }
+}
+
+impl<T> FromIterator<T> for ByteString
+where
+    ByteStringBuilder: Extend<T>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut builder = ByteStringBuilder::new();
+        builder.extend(iter);
+        builder.finish()
+    }
+}
+
+/// Creates a `ByteString` using interpolation of runtime expressions.
+///
+/// This is similar to `format!` macro, but result is written directly
+/// to `ByteString` storage. Formatting without arguments does not allocate.
+///
+/// # Examples
+///
+/// ```
+/// use ntex_bytes::format_bytestring;
+///
+/// let s = format_bytestring!("bytes {}-{}/{}", 0, 99, 1000);
+/// assert_eq!(s, "bytes 0-99/1000");
+/// ```
+#[macro_export]
+macro_rules! format_bytestring {
+    ($($arg:tt)*) => {
+        $crate::ByteString::from_fmt(format_args!($($arg)*))
+    };
+}
+
+impl ByteString {
+    #[doc(hidden)]
+    pub fn from_fmt(args: fmt::Arguments<'_>) -> ByteString {
+        if let Some(s) = args.as_str() {
+            ByteString::from_static(s)
+        } else {
+            let mut builder = ByteStringBuilder::new();
+            fmt::Write::write_fmt(&mut builder, args)
+                .expect("a formatting trait implementation returned an error");
+            builder.finish()
+        }
+    }
+}
+
 impl PartialEq<str> for ByteString {
     fn eq(&self, other: &str) -> bool {
         &self[..] == other
@@ -328,6 +490,7 @@ impl fmt::Display for ByteString {
     }
 }
 
+#[cfg(feature = "serde")]
 mod serde {
     use serde::de::{Deserialize, Deserializer};
     use serde::ser::{Serialize, Serializer};
@@ -377,6 +540,34 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn test_builder() {
+        let mut b = ByteStringBuilder::new();
+        assert!(b.is_empty());
+        b.push_str("hello");
+        b.push(' ');
+        b.push('ü');
+        b.extend(["a", "b"]);
+        b.extend("cd".chars());
+        b.extend(vec!["e".to_owned()]);
+        b.extend(vec![ByteString::from_static("f")]);
+        assert_eq!(b.len(), 14);
+        assert_eq!(b.as_str(), "hello üabcdef");
+        assert_eq!(format!("{:?}", b), "\"hello üabcdef\"");
+        assert_eq!(ByteString::from(b), "hello üabcdef");
+
+        let s: ByteString = ["a", "b", "c"].into_iter().collect();
+        assert_eq!(s, "abc");
+        let s: ByteString = "test".chars().rev().collect();
+        assert_eq!(s, "tset");
+
+        let s = format_bytestring!("{}-{}", 1, "2");
+        assert_eq!(s, "1-2");
+        let s = format_bytestring!("static");
+        assert_eq!(s, "static");
+        assert_eq!(ByteStringBuilder::with_capacity(64).finish(), "");
+    }
+
     #[test]
     fn test_basics() {
         let mut s = ByteString::from_static("test");
@@ -478,12 +669,14 @@ mod test {
         assert!(ByteString::try_from(BytesVec::copy_from_slice(b"\xc3\x28")).is_err());
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_serialize() {
         let s: ByteString = serde_json::from_str(r#""nice bytes""#).unwrap();
         assert_eq!(s, "nice bytes");
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_deserialize() {
         let s = serde_json::to_string(&ByteString::from_static("nice bytes")).unwrap();
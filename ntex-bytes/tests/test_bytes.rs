@@ -1128,6 +1128,60 @@ fn pool() {
     assert_eq!(p3.allocated(), 2080 + shared_vec());
 }
 
+#[test]
+fn pool_stats() {
+    let p = PoolId::P12.set_limit(2048).pool_ref();
+    assert_eq!(p.limit(), 2048);
+    assert_eq!(p.peak_allocated(), 0);
+
+    let buf = p.buf_with_capacity_checked(1024).unwrap();
+    assert!(p.vec_with_capacity_checked(2048).is_none());
+    assert!(!p.is_limit_exceeded());
+    let allocated = p.allocated();
+
+    let buf2 = BytesMut::with_capacity_in(2048, p);
+    assert!(p.is_limit_exceeded());
+    assert!(p.buf_with_capacity_checked(16).is_none());
+    drop(buf2);
+    assert!(p.peak_allocated() > 2048);
+
+    let stats = p.stats();
+    assert_eq!(stats.id, PoolId::P12);
+    assert_eq!(stats.allocated, allocated);
+    assert_eq!(stats.in_use, allocated);
+    assert_eq!(stats.limit, 2048);
+    assert!(PoolId::stats_all().iter().any(|s| s.id == PoolId::P12));
+
+    p.reset_peak();
+    assert_eq!(p.peak_allocated(), allocated);
+    drop(buf);
+
+    p.set_limit(0);
+    assert!(p.buf_with_capacity_checked(4096).is_some());
+
+    // io buffers cache
+    p.release_read_buf(p.vec_with_capacity(2048));
+    let stats = p.stats();
+    assert!(stats.cached > 0);
+    assert!(stats.in_use < stats.allocated);
+}
+
+#[test]
+fn pool_external() {
+    let p = PoolId::P11.pool_ref();
+    let allocated = p.allocated();
+
+    p.acquire_external(1024);
+    assert_eq!(p.external(), 1024);
+    assert_eq!(p.allocated(), allocated + 1024);
+    assert!(p.peak_allocated() >= allocated + 1024);
+    assert_eq!(p.stats().external, 1024);
+
+    p.release_external(1024);
+    assert_eq!(p.external(), 0);
+    assert_eq!(p.allocated(), allocated);
+}
+
 #[ntex::test]
 async fn pool_usage() {
     use ntex::{time, util};
@@ -1183,3 +1237,46 @@ async fn pool_usage() {
     assert!(p1.is_ready());
     assert!(p2.is_ready());
 }
+
+#[test]
+fn from_owner() {
+    use std::sync::{atomic::AtomicBool, atomic::Ordering, Arc};
+
+    struct Owner(Vec<u8>, Arc<AtomicBool>);
+
+    impl AsRef<[u8]> for Owner {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl Drop for Owner {
+        fn drop(&mut self) {
+            self.1.store(true, Ordering::Relaxed);
+        }
+    }
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let allocated = PoolRef::default().allocated();
+    let b = Bytes::from_owner(Owner(LONG.to_vec(), dropped.clone()));
+    assert_eq!(b, LONG);
+    assert_eq!(PoolRef::default().allocated(), allocated);
+
+    let b2 = b.slice(5..);
+    assert_eq!(b2, &LONG[5..]);
+    let b = b.try_mut().unwrap_err();
+    let b3 = b.clone();
+    drop(b);
+    assert!(!dropped.load(Ordering::Relaxed));
+
+    // read-only memory is copied
+    let mut m = BytesMut::from(b3);
+    m[0] = b'x';
+    assert!(!dropped.load(Ordering::Relaxed));
+    drop(b2);
+    assert!(dropped.load(Ordering::Relaxed));
+
+    let b = Bytes::from_owner(Arc::<[u8]>::from(SHORT));
+    assert_eq!(b, SHORT);
+    assert!(Bytes::from_owner(Vec::new()).is_empty());
+}
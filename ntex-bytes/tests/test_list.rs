@@ -0,0 +1,77 @@
+#![deny(warnings, rust_2018_idioms)]
+
+use std::io::{IoSlice, Write};
+
+use ntex_bytes::{Buf, Bytes, BytesList};
+
+#[test]
+fn list_push() {
+    let mut list = BytesList::with_capacity(4);
+    assert!(list.is_empty());
+    assert_eq!(list.chunk(), b"");
+
+    list.push(Bytes::from_static(b"hello"));
+    list.push(Bytes::new());
+    list.push(Bytes::from_static(b" world"));
+    list.push_front(Bytes::from_static(b">"));
+    assert_eq!(list.len(), 12);
+    assert_eq!(list.segments(), 3);
+    assert_eq!(list, b">hello world"[..]);
+    assert!(format!("{:?}", list).contains("hello"));
+
+    assert_eq!(list.pop_front(), Some(Bytes::from_static(b">")));
+    assert_eq!(list.len(), 11);
+    assert_eq!(list.clone().freeze(), "hello world");
+    list.clear();
+    assert!(list.is_empty());
+    assert_eq!(list.freeze(), Bytes::new());
+}
+
+#[test]
+fn list_buf() {
+    let mut list: BytesList = vec![
+        Bytes::from_static(b"abc"),
+        Bytes::from_static(b"def"),
+        Bytes::from_static(b"gh"),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(list.remaining(), 8);
+    assert_eq!(list.chunk(), b"abc");
+    list.advance(4);
+    assert_eq!(list.chunk(), b"ef");
+    assert_eq!(list.segments(), 2);
+    assert_eq!(list.get_u8(), b'e');
+    assert_eq!(list.remaining(), 3);
+
+    let mut dst = [IoSlice::new(&[]); 4];
+    assert_eq!(list.chunks_vectored(&mut dst), 2);
+    assert_eq!(&dst[0][..], b"f");
+    assert_eq!(&dst[1][..], b"gh");
+
+    let mut out = Vec::new();
+    assert_eq!(out.write_vectored(&dst[..2]).unwrap(), 3);
+    assert_eq!(out, b"fgh");
+
+    assert_eq!(list.to_bytes(), "fgh");
+    assert!(list.is_empty());
+}
+
+#[test]
+fn list_split() {
+    let mut list = BytesList::from(Bytes::from_static(b"hello"));
+    list.extend([Bytes::from_static(b" "), Bytes::from_static(b"world")]);
+
+    let first = list.split_to(7);
+    assert_eq!(first, b"hello w"[..]);
+    assert_eq!(first.segments(), 3);
+    assert_eq!(list, b"orld"[..]);
+    assert_eq!(Bytes::from(list), "orld");
+
+    // single segment is not copied
+    let b = Bytes::from(vec![1; 128]);
+    let ptr = b.as_ptr();
+    assert_eq!(BytesList::from(b).freeze().as_ptr(), ptr);
+    assert_eq!(BytesList::new().into_iter().count(), 0);
+}
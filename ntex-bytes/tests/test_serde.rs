@@ -17,4 +17,6 @@ fn test_ser_de() {
     assert_tokens(&b, &[Token::Bytes(b"bytes")]);
     let b = ntex_bytes::BytesMut::from(&b"bytes"[..]);
     assert_tokens(&b, &[Token::Bytes(b"bytes")]);
+    let b = ntex_bytes::BytesVec::copy_from_slice(&b"bytes"[..]);
+    assert_tokens(&b, &[Token::Bytes(b"bytes")]);
 }
@@ -46,6 +46,27 @@ mod net_impl {
         Ok(Io::with_memory_pool(TcpStream::new(sock), pool))
     }
 
+    /// Opens a TCP connection to a remote host, socket is bound to local
+    /// address and network interface before connecting.
+    ///
+    /// Binding is not supported by this runtime, connection is opened only
+    /// if neither local address nor interface is set.
+    pub async fn tcp_connect_bind(
+        addr: SocketAddr,
+        local_addr: Option<std::net::IpAddr>,
+        interface: Option<ntex_bytes::ByteString>,
+        pool: PoolRef,
+    ) -> Result<Io> {
+        if local_addr.is_some() || interface.is_some() {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Binding to local address is not supported",
+            ))
+        } else {
+            tcp_connect_in(addr, pool).await
+        }
+    }
+
     /// Opens a unix stream connection.
     pub async fn unix_connect<P>(addr: P) -> Result<Io>
     where
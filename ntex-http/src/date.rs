@@ -0,0 +1,284 @@
+//! Http date formatting and parsing
+//!
+//! Current date is formatted at most once per second for each thread.
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{cell::Cell, fmt};
+
+/// Length of formatted http date, `Sun, 06 Nov 1994 08:49:37 GMT`
+pub const DATE_VALUE_LENGTH: usize = 29;
+
+const WEEKDAYS: [&[u8; 3]; 7] = [b"Sun", b"Mon", b"Tue", b"Wed", b"Thu", b"Fri", b"Sat"];
+const MONTHS: [&[u8; 3]; 12] = [
+    b"Jan", b"Feb", b"Mar", b"Apr", b"May", b"Jun", b"Jul", b"Aug", b"Sep", b"Oct", b"Nov",
+    b"Dec",
+];
+
+thread_local! {
+    static CURRENT: Cell<(u64, DateFmt)> = const {
+        Cell::new((u64::MAX, DateFmt([b' '; DATE_VALUE_LENGTH])))
+    };
+}
+
+/// Formatted http date in IMF-fixdate format
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct DateFmt([u8; DATE_VALUE_LENGTH]);
+
+impl DateFmt {
+    /// Returns formatted date as str
+    pub fn as_str(&self) -> &str {
+        // formatted date is always ascii
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+
+    /// Returns formatted date as bytes
+    pub fn as_bytes(&self) -> &[u8; DATE_VALUE_LENGTH] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for DateFmt {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for DateFmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for DateFmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DateFmt").field(&self.as_str()).finish()
+    }
+}
+
+/// Returns current date formatted as http date.
+///
+/// Date is cached per thread and re-formatted only if second changes.
+pub fn now_fmt() -> DateFmt {
+    let secs = secs_since_epoch(SystemTime::now());
+    CURRENT.with(|current| {
+        let (cached, date) = current.get();
+        if cached == secs {
+            date
+        } else {
+            let date = fmt_secs(secs);
+            current.set((secs, date));
+            date
+        }
+    })
+}
+
+/// Format system time as http date.
+///
+/// Time before unix epoch is formatted as epoch.
+pub fn fmt_http_date(time: SystemTime) -> DateFmt {
+    fmt_secs(secs_since_epoch(time))
+}
+
+/// Parse http date.
+///
+/// IMF-fixdate format is parsed without allocations, obsolete
+/// RFC 850 and asctime formats are supported as well.
+pub fn parse_http_date(value: &[u8]) -> Option<SystemTime> {
+    if let Some(secs) = parse_imf_fixdate(value) {
+        Some(UNIX_EPOCH + Duration::from_secs(secs))
+    } else {
+        std::str::from_utf8(value)
+            .ok()
+            .and_then(|s| httpdate::parse_http_date(s).ok())
+    }
+}
+
+fn secs_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn fmt_secs(secs: u64) -> DateFmt {
+    let days = secs / 86400;
+    let rem = secs % 86400;
+    let (year, month, day) = civil_from_days(days);
+    // 1970-01-01 is thursday
+    let weekday = ((days + 4) % 7) as usize;
+
+    let mut buf = *b"   , 00     0000 00:00:00 GMT";
+    buf[0..3].copy_from_slice(WEEKDAYS[weekday]);
+    put2(&mut buf[5..7], day);
+    buf[8..11].copy_from_slice(MONTHS[month as usize - 1]);
+    put2(&mut buf[12..14], (year / 100) % 100);
+    put2(&mut buf[14..16], year % 100);
+    put2(&mut buf[17..19], rem / 3600);
+    put2(&mut buf[20..22], (rem % 3600) / 60);
+    put2(&mut buf[23..25], rem % 60);
+    DateFmt(buf)
+}
+
+#[inline]
+fn put2(buf: &mut [u8], val: u64) {
+    buf[0] = b'0' + (val / 10) as u8;
+    buf[1] = b'0' + (val % 10) as u8;
+}
+
+/// Parse `Sun, 06 Nov 1994 08:49:37 GMT`
+fn parse_imf_fixdate(s: &[u8]) -> Option<u64> {
+    if s.len() != DATE_VALUE_LENGTH
+        || &s[3..5] != b", "
+        || s[7] != b' '
+        || s[11] != b' '
+        || s[16] != b' '
+        || s[19] != b':'
+        || s[22] != b':'
+        || &s[25..] != b" GMT"
+        || !WEEKDAYS.iter().any(|d| &s[..3] == *d)
+    {
+        return None;
+    }
+
+    let day = digits(&s[5..7])?;
+    let month = MONTHS.iter().position(|m| &s[8..11] == *m)? as u64 + 1;
+    let year = digits(&s[12..16])?;
+    let hour = digits(&s[17..19])?;
+    let min = digits(&s[20..22])?;
+    let sec = digits(&s[23..25])?;
+
+    if year < 1970 || hour > 23 || min > 59 || sec > 60 {
+        return None;
+    }
+    if day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    Some(days * 86400 + hour * 3600 + min * 60 + sec)
+}
+
+fn digits(s: &[u8]) -> Option<u64> {
+    s.iter().try_fold(0u64, |acc, b| {
+        if b.is_ascii_digit() {
+            Some(acc * 10 + (b - b'0') as u64)
+        } else {
+            None
+        }
+    })
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if year.is_multiple_of(4)
+            && (!year.is_multiple_of(100) || year.is_multiple_of(400)) =>
+        {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = year - u64::from(month <= 2);
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fmt() {
+        assert_eq!(
+            fmt_http_date(UNIX_EPOCH).as_str(),
+            "Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+        assert_eq!(
+            fmt_http_date(UNIX_EPOCH + Duration::from_secs(784111777)).to_string(),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+        assert_eq!(
+            fmt_http_date(UNIX_EPOCH + Duration::from_secs(951782400)).as_str(),
+            "Tue, 29 Feb 2000 00:00:00 GMT"
+        );
+        assert_eq!(
+            fmt_http_date(UNIX_EPOCH - Duration::from_secs(10)).as_str(),
+            "Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+
+        // compare with reference implementation
+        let mut secs = 0;
+        while secs < 4_102_444_800 {
+            let time = UNIX_EPOCH + Duration::from_secs(secs);
+            let date = fmt_http_date(time);
+            assert_eq!(date.as_str(), httpdate::fmt_http_date(time));
+            assert_eq!(parse_http_date(date.as_bytes()), Some(time));
+            secs += 86_399 * 7 + 13;
+        }
+    }
+
+    #[test]
+    fn test_now() {
+        let date = now_fmt();
+        assert_eq!(date.as_bytes().len(), DATE_VALUE_LENGTH);
+        assert!(date.as_str().ends_with(" GMT"));
+        assert!(format!("{:?}", date).contains("DateFmt"));
+        assert_eq!(date.as_ref(), date.as_bytes());
+
+        let time = parse_http_date(date.as_bytes()).unwrap();
+        let diff = SystemTime::now().duration_since(time).unwrap();
+        assert!(diff < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_parse() {
+        let time = UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(
+            parse_http_date(b"Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(time)
+        );
+        // obsolete formats
+        assert_eq!(
+            parse_http_date(b"Sunday, 06-Nov-94 08:49:37 GMT"),
+            Some(time)
+        );
+        assert_eq!(parse_http_date(b"Sun Nov  6 08:49:37 1994"), Some(time));
+
+        for invalid in [
+            &b"Sun, 06 Nov 1994 08:49:37 UTC"[..],
+            b"Sun, 31 Nov 1994 08:49:37 GMT",
+            b"Sun, 29 Feb 1900 08:49:37 GMT",
+            b"Sun, 06 Nox 1994 08:49:37 GMT",
+            b"Sun, 06 Nov 1994 24:49:37 GMT",
+            b"Sun, 06 Nov 1969 08:49:37 GMT",
+            b"Sun, 0a Nov 1994 08:49:37 GMT",
+            b"Xyz, 06 Nov 1994 08:49:37 GMT",
+            b"",
+            b"\xff",
+        ] {
+            assert_eq!(parse_imf_fixdate(invalid), None);
+        }
+        assert_eq!(parse_http_date(b"\xff"), None);
+        assert_eq!(parse_http_date(b"Sun, 06 Nov 1994 08:49:37 UTC"), None);
+    }
+}
@@ -1,13 +1,18 @@
 //! Http protocol support.
 #![deny(rust_2018_idioms, unreachable_pub, missing_debug_implementations)]
 
+pub mod date;
 pub mod error;
 mod map;
 mod serde;
+pub mod target;
+pub mod typed;
 mod value;
 
 pub use self::error::Error;
 pub use self::map::HeaderMap;
+pub use self::target::RequestTarget;
+pub use self::typed::TypedHeader;
 pub use self::value::HeaderValue;
 
 #[doc(hidden)]
@@ -34,6 +39,7 @@ pub mod header {
 
     #[doc(hidden)]
     pub use crate::map::{AsName, Either, GetAll, Iter, Value};
+    pub use crate::map::{Drain, Entry, OccupiedEntry, VacantEntry};
     pub use crate::value::{HeaderValue, InvalidHeaderValue, ToStrError};
 
     pub use http::header::{HeaderName, InvalidHeaderName};
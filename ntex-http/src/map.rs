@@ -1,5 +1,6 @@
-use std::collections::{self, hash_map, hash_map::Entry, VecDeque};
+use std::collections::{self, hash_map, VecDeque};
 
+use crate::typed::{InvalidTypedHeader, TypedHeader};
 use crate::{HeaderName, HeaderValue};
 
 type HashMap<K, V> = collections::HashMap<K, V, fxhash::FxBuildHasher>;
@@ -45,6 +46,27 @@ impl Value {
         }
     }
 
+    /// Returns number of values
+    pub fn len(&self) -> usize {
+        match self {
+            Value::One(_) => 1,
+            Value::Multi(ref vec) => vec.len(),
+        }
+    }
+
+    /// Returns `true` if there are no values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over all values in insertion order
+    pub fn iter(&self) -> GetAll<'_> {
+        GetAll {
+            idx: 0,
+            item: Some(self),
+        }
+    }
+
     pub(crate) fn append(&mut self, val: HeaderValue) {
         match self {
             Value::One(prev_val) => {
@@ -201,6 +223,11 @@ impl HeaderMap {
         self.inner.reserve(additional)
     }
 
+    /// Shrinks the capacity of the map as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit()
+    }
+
     /// Returns a reference to the value associated with the key.
     ///
     /// If there are multiple values associated with the key, then the first one
@@ -321,13 +348,52 @@ impl HeaderMap {
     /// identical.
     pub fn append(&mut self, key: HeaderName, value: HeaderValue) {
         match self.inner.entry(key) {
-            Entry::Occupied(mut entry) => entry.get_mut().append(value),
-            Entry::Vacant(entry) => {
+            hash_map::Entry::Occupied(mut entry) => entry.get_mut().append(value),
+            hash_map::Entry::Vacant(entry) => {
                 entry.insert(Value::One(value));
             }
         }
     }
 
+    /// Returns typed header, `None` if header is missing or could not be parsed.
+    pub fn typed_get<H: TypedHeader>(&self) -> Option<H> {
+        self.typed_try_get().ok().flatten()
+    }
+
+    /// Returns typed header, `None` if header is missing.
+    pub fn typed_try_get<H: TypedHeader>(&self) -> Result<Option<H>, InvalidTypedHeader> {
+        match self.inner.get(&H::name()) {
+            Some(val) => H::decode(GetAll {
+                idx: 0,
+                item: Some(val),
+            })
+            .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Inserts typed header into the map, all previous values are removed.
+    pub fn typed_insert<H: TypedHeader>(&mut self, header: H) {
+        self.insert(H::name(), header.encode())
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place
+    /// manipulation.
+    pub fn entry(&mut self, key: HeaderName) -> Entry<'_> {
+        match self.inner.entry(key) {
+            hash_map::Entry::Occupied(inner) => Entry::Occupied(OccupiedEntry { inner }),
+            hash_map::Entry::Vacant(inner) => Entry::Vacant(VacantEntry { inner }),
+        }
+    }
+
+    /// Clears the map, returning all headers as an iterator.
+    ///
+    /// Each key is yielded once with all associated values. Keeps
+    /// the allocated memory for reuse.
+    pub fn drain(&mut self) -> Drain<'_> {
+        Drain(self.inner.drain())
+    }
+
     /// Removes all headers for a particular header name from the map.
     pub fn remove<N: AsName>(&mut self, key: N) {
         match key.as_name() {
@@ -409,8 +475,8 @@ where
             })
             .fold(HashMap::default(), |mut map: HashMap<_, Value>, (n, v)| {
                 match map.entry(n) {
-                    Entry::Occupied(mut oc) => oc.get_mut().extend(v),
-                    Entry::Vacant(va) => {
+                    hash_map::Entry::Occupied(mut oc) => oc.get_mut().extend(v),
+                    hash_map::Entry::Vacant(va) => {
                         let _ = va.insert(v);
                     }
                 }
@@ -451,6 +517,18 @@ pub struct GetAll<'a> {
     item: Option<&'a Value>,
 }
 
+impl<'a> GetAll<'a> {
+    fn remaining(&self) -> usize {
+        match self.item {
+            Some(Value::One(_)) => 1,
+            Some(Value::Multi(vec)) => vec.len().saturating_sub(self.idx),
+            None => 0,
+        }
+    }
+}
+
+impl<'a> ExactSizeIterator for GetAll<'a> {}
+
 impl<'a> Iterator for GetAll<'a> {
     type Item = &'a HeaderValue;
 
@@ -477,6 +555,179 @@ impl<'a> Iterator for GetAll<'a> {
             None
         }
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining();
+        (len, Some(len))
+    }
+}
+
+/// A view into a single location in a `HeaderMap`, which may be vacant or occupied.
+#[derive(Debug)]
+pub enum Entry<'a> {
+    /// An occupied entry
+    Occupied(OccupiedEntry<'a>),
+    /// A vacant entry
+    Vacant(VacantEntry<'a>),
+}
+
+impl<'a> Entry<'a> {
+    /// Returns a reference to this entry's key
+    pub fn key(&self) -> &HeaderName {
+        match self {
+            Entry::Occupied(e) => e.key(),
+            Entry::Vacant(e) => e.key(),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the default if empty.
+    ///
+    /// Returns a mutable reference to the first value in the entry.
+    pub fn or_insert(self, default: HeaderValue) -> &'a mut HeaderValue {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the default
+    /// function if empty.
+    ///
+    /// Returns a mutable reference to the first value in the entry.
+    pub fn or_insert_with<F: FnOnce() -> HeaderValue>(
+        self,
+        default: F,
+    ) -> &'a mut HeaderValue {
+        match self {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => e.insert(default()),
+        }
+    }
+
+    /// Appends value to the entry, inserts value if entry is empty.
+    pub fn append(self, value: HeaderValue) {
+        match self {
+            Entry::Occupied(mut e) => e.append(value),
+            Entry::Vacant(e) => {
+                e.insert(value);
+            }
+        }
+    }
+}
+
+/// A view into an occupied entry in a `HeaderMap`.
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    inner: hash_map::OccupiedEntry<'a, HeaderName, Value>,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Returns a reference to the entry's key
+    pub fn key(&self) -> &HeaderName {
+        self.inner.key()
+    }
+
+    /// Returns a reference to the first value in the entry
+    pub fn get(&self) -> &HeaderValue {
+        self.inner.get().get()
+    }
+
+    /// Returns a mutable reference to the first value in the entry
+    pub fn get_mut(&mut self) -> &mut HeaderValue {
+        self.inner.get_mut().get_mut()
+    }
+
+    /// Converts the entry into a mutable reference to the first value
+    pub fn into_mut(self) -> &'a mut HeaderValue {
+        self.inner.into_mut().get_mut()
+    }
+
+    /// Iterate over all values of the entry in insertion order
+    pub fn iter(&self) -> GetAll<'_> {
+        self.inner.get().iter()
+    }
+
+    /// Sets the value of the entry, all previous values are removed
+    /// and returned.
+    pub fn insert(&mut self, value: HeaderValue) -> Value {
+        self.inner.insert(Value::One(value))
+    }
+
+    /// Appends value to the end of the entry's values
+    pub fn append(&mut self, value: HeaderValue) {
+        self.inner.get_mut().append(value)
+    }
+
+    /// Retains only the values specified by the predicate, removes
+    /// entry if no values left.
+    pub fn retain<F>(mut self, mut f: F) -> Option<Self>
+    where
+        F: FnMut(&HeaderValue) -> bool,
+    {
+        let value = std::mem::replace(self.inner.get_mut(), Value::Multi(VecDeque::new()));
+        let mut values = value.into_iter().filter(|v| f(v));
+        if let Some(first) = values.next() {
+            let mut value = Value::One(first);
+            value.extend(values);
+            self.inner.insert(value);
+            Some(self)
+        } else {
+            self.inner.remove();
+            None
+        }
+    }
+
+    /// Removes the entry from the map, returns all values
+    pub fn remove(self) -> Value {
+        self.inner.remove()
+    }
+
+    /// Removes the entry from the map, returns key and all values
+    pub fn remove_entry(self) -> (HeaderName, Value) {
+        self.inner.remove_entry()
+    }
+}
+
+/// A view into a vacant entry in a `HeaderMap`.
+#[derive(Debug)]
+pub struct VacantEntry<'a> {
+    inner: hash_map::VacantEntry<'a, HeaderName, Value>,
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Returns a reference to the entry's key
+    pub fn key(&self) -> &HeaderName {
+        self.inner.key()
+    }
+
+    /// Take ownership of the key
+    pub fn into_key(self) -> HeaderName {
+        self.inner.into_key()
+    }
+
+    /// Sets the value of the entry, returns a mutable reference to it
+    pub fn insert(self, value: HeaderValue) -> &'a mut HeaderValue {
+        self.inner.insert(Value::One(value)).get_mut()
+    }
+}
+
+/// A draining iterator over `HeaderMap`, yields each key with all associated values.
+#[derive(Debug)]
+pub struct Drain<'a>(hash_map::Drain<'a, HeaderName, Value>);
+
+impl<'a> Iterator for Drain<'a> {
+    type Item = (HeaderName, Value);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
 }
 
 #[derive(Debug)]
@@ -549,7 +800,7 @@ impl<'a> Iterator for Iter<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::header::{ACCEPT_ENCODING, CONTENT_TYPE};
+    use crate::header::{ACCEPT_ENCODING, CONTENT_TYPE, SET_COOKIE, VARY};
 
     #[test]
     fn test_from_iter() {
@@ -653,4 +904,98 @@ mod tests {
             Some(&HeaderValue::from_static("gzip"))
         );
     }
+
+    #[test]
+    fn test_entry() {
+        let mut map = HeaderMap::new();
+
+        let val = map
+            .entry(SET_COOKIE)
+            .or_insert(HeaderValue::from_static("a=1"));
+        assert_eq!(*val, "a=1");
+        map.entry(SET_COOKIE)
+            .append(HeaderValue::from_static("b=2"));
+        map.entry(SET_COOKIE)
+            .append(HeaderValue::from_static("c=3"));
+        map.entry(VARY)
+            .or_insert_with(|| HeaderValue::from_static("accept"));
+        assert_eq!(map.get(VARY).unwrap(), "accept");
+
+        match map.entry(SET_COOKIE) {
+            Entry::Occupied(mut e) => {
+                assert_eq!(e.key(), SET_COOKIE);
+                assert_eq!(e.get(), "a=1");
+                assert_eq!(e.iter().len(), 3);
+                assert_eq!(e.iter().collect::<Vec<_>>(), vec!["a=1", "b=2", "c=3"]);
+                *e.get_mut() = HeaderValue::from_static("a=2");
+
+                let e = e.retain(|v| v != "b=2").unwrap();
+                assert_eq!(e.iter().collect::<Vec<_>>(), vec!["a=2", "c=3"]);
+                assert!(e.retain(|_| false).is_none());
+            }
+            Entry::Vacant(_) => panic!(),
+        }
+        assert!(!map.contains_key(SET_COOKIE));
+
+        match map.entry(SET_COOKIE) {
+            Entry::Vacant(e) => {
+                assert_eq!(e.key(), SET_COOKIE);
+                assert_eq!(*e.insert(HeaderValue::from_static("d=4")), "d=4");
+            }
+            Entry::Occupied(_) => panic!(),
+        }
+        match map.entry(SET_COOKIE) {
+            Entry::Occupied(mut e) => {
+                let prev = e.insert(HeaderValue::from_static("e=5"));
+                assert_eq!(prev.len(), 1);
+                assert!(!prev.is_empty());
+                assert_eq!(*e.into_mut(), "e=5");
+            }
+            Entry::Vacant(_) => panic!(),
+        }
+        match map.entry(VARY) {
+            Entry::Occupied(e) => {
+                let (key, val) = e.remove_entry();
+                assert_eq!(key, VARY);
+                assert_eq!(val.iter().collect::<Vec<_>>(), vec!["accept"]);
+            }
+            Entry::Vacant(_) => panic!(),
+        }
+        match map.entry(VARY) {
+            Entry::Vacant(e) => assert_eq!(e.into_key(), VARY),
+            Entry::Occupied(_) => panic!(),
+        }
+        match map.entry(SET_COOKIE) {
+            Entry::Occupied(e) => assert_eq!(e.remove().len(), 1),
+            Entry::Vacant(_) => panic!(),
+        }
+        assert!(map.is_empty());
+        assert_eq!(map.entry(VARY).key(), VARY);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut map = HeaderMap::with_capacity(16);
+        map.append(SET_COOKIE, HeaderValue::from_static("a=1"));
+        map.append(SET_COOKIE, HeaderValue::from_static("b=2"));
+        map.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        assert_eq!(map.get_all(SET_COOKIE).size_hint(), (2, Some(2)));
+        assert_eq!(map.get_all(VARY).len(), 0);
+
+        let mut items = map.drain().collect::<Vec<_>>();
+        items.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].0, CONTENT_TYPE);
+        assert_eq!(items[1].0, SET_COOKIE);
+        assert_eq!(
+            items.pop().unwrap().1.into_iter().collect::<Vec<_>>(),
+            vec!["a=1", "b=2"]
+        );
+        assert!(map.is_empty());
+        assert!(map.capacity() >= 16);
+
+        map.shrink_to_fit();
+        map.reserve(4);
+        assert!(map.capacity() >= 4);
+    }
 }
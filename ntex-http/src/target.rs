@@ -0,0 +1,305 @@
+//! Parsed view of the request target
+use std::{borrow::Cow, fmt, slice};
+
+use crate::Uri;
+
+type Range = (usize, usize);
+
+/// Parsed, indexed view of the request target.
+///
+/// Path segments and query pairs are indexed once, accessors return
+/// slices of the original uri. Percent-decoding allocates only if value
+/// contains escaped characters.
+#[derive(Clone)]
+pub struct RequestTarget {
+    uri: Uri,
+    segments: Vec<Range>,
+    query: Vec<(Range, Range)>,
+}
+
+impl RequestTarget {
+    /// Parse request target
+    pub fn new(uri: Uri) -> Self {
+        let segments = match uri.path().strip_prefix('/') {
+            Some("") | None => Vec::new(),
+            Some(path) => split(path, b'/', 1),
+        };
+        let query = uri
+            .query()
+            .map(|query| {
+                split(query, b'&', 0)
+                    .into_iter()
+                    .filter(|(start, end)| start != end)
+                    .map(|(start, end)| {
+                        match query[start..end].bytes().position(|b| b == b'=') {
+                            Some(pos) => ((start, start + pos), (start + pos + 1, end)),
+                            None => ((start, end), (end, end)),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        RequestTarget {
+            uri,
+            segments,
+            query,
+        }
+    }
+
+    /// Returns request's uri
+    pub fn uri(&self) -> &Uri {
+        &self.uri
+    }
+
+    /// Returns raw path
+    pub fn path(&self) -> &str {
+        self.uri.path()
+    }
+
+    /// Returns raw query string
+    pub fn query(&self) -> Option<&str> {
+        self.uri.query()
+    }
+
+    /// Returns number of path segments
+    pub fn segments_len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns raw path segment
+    pub fn segment(&self, idx: usize) -> Option<&str> {
+        self.segments
+            .get(idx)
+            .map(|(start, end)| &self.path()[*start..*end])
+    }
+
+    /// Returns percent-decoded path segment
+    pub fn decoded_segment(&self, idx: usize) -> Option<Cow<'_, str>> {
+        self.segment(idx).map(|s| decode(s, false))
+    }
+
+    /// Iterate over raw path segments
+    pub fn segments(&self) -> Segments<'_> {
+        Segments {
+            path: self.path(),
+            iter: self.segments.iter(),
+        }
+    }
+
+    /// Iterate over decoded query pairs
+    pub fn query_pairs(&self) -> QueryPairs<'_> {
+        QueryPairs {
+            query: self.query().unwrap_or(""),
+            iter: self.query.iter(),
+        }
+    }
+
+    /// Returns first decoded value of query parameter
+    pub fn query_get(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, val)| val)
+    }
+
+    /// Iterate over all decoded values of query parameter
+    pub fn query_get_all<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = Cow<'a, str>> + 'a {
+        self.query_pairs()
+            .filter(move |(key, _)| key == name)
+            .map(|(_, val)| val)
+    }
+}
+
+impl fmt::Debug for RequestTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestTarget")
+            .field("path", &self.path())
+            .field("segments", &self.segments().collect::<Vec<_>>())
+            .field("query", &self.query())
+            .finish()
+    }
+}
+
+/// Iterator over raw path segments
+#[derive(Debug)]
+pub struct Segments<'a> {
+    path: &'a str,
+    iter: slice::Iter<'a, Range>,
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = &'a str;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a str> {
+        self.iter
+            .next()
+            .map(|(start, end)| &self.path[*start..*end])
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for Segments<'a> {}
+
+/// Iterator over decoded query pairs
+#[derive(Debug)]
+pub struct QueryPairs<'a> {
+    query: &'a str,
+    iter: slice::Iter<'a, (Range, Range)>,
+}
+
+impl<'a> Iterator for QueryPairs<'a> {
+    type Item = (Cow<'a, str>, Cow<'a, str>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(key, val)| {
+            (
+                decode(&self.query[key.0..key.1], true),
+                decode(&self.query[val.0..val.1], true),
+            )
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for QueryPairs<'a> {}
+
+/// Split string into ranges, `offset` is added to all positions
+fn split(s: &str, sep: u8, offset: usize) -> Vec<Range> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (idx, b) in s.bytes().enumerate() {
+        if b == sep {
+            ranges.push((start + offset, idx + offset));
+            start = idx + 1;
+        }
+    }
+    ranges.push((start + offset, s.len() + offset));
+    ranges
+}
+
+/// Percent-decode string, optionally `+` is decoded as space
+fn decode(s: &str, plus: bool) -> Cow<'_, str> {
+    if !s.bytes().any(|b| b == b'%' || (plus && b == b'+')) {
+        return Cow::Borrowed(s);
+    }
+
+    let src = s.as_bytes();
+    let mut buf = Vec::with_capacity(src.len());
+    let mut idx = 0;
+    while idx < src.len() {
+        match src[idx] {
+            b'+' if plus => buf.push(b' '),
+            b'%' if idx + 2 < src.len() => {
+                match (from_hex(src[idx + 1]), from_hex(src[idx + 2])) {
+                    (Some(h), Some(l)) => {
+                        buf.push((h << 4) | l);
+                        idx += 2;
+                    }
+                    _ => buf.push(b'%'),
+                }
+            }
+            ch => buf.push(ch),
+        }
+        idx += 1;
+    }
+    match String::from_utf8(buf) {
+        Ok(s) => Cow::Owned(s),
+        Err(e) => Cow::Owned(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+    }
+}
+
+#[inline]
+fn from_hex(v: u8) -> Option<u8> {
+    match v {
+        b'0'..=b'9' => Some(v - b'0'),
+        b'a'..=b'f' => Some(v - b'a' + 10),
+        b'A'..=b'F' => Some(v - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments() {
+        let target = RequestTarget::new(Uri::from_static("/user/some%20name/files/"));
+        assert_eq!(target.path(), "/user/some%20name/files/");
+        assert_eq!(target.segments_len(), 4);
+        assert_eq!(
+            target.segments().collect::<Vec<_>>(),
+            vec!["user", "some%20name", "files", ""]
+        );
+        assert_eq!(target.segments().len(), 4);
+        assert_eq!(target.segment(1), Some("some%20name"));
+        assert_eq!(target.segment(4), None);
+        assert_eq!(target.decoded_segment(1).unwrap(), "some name");
+        assert!(matches!(
+            target.decoded_segment(0),
+            Some(Cow::Borrowed("user"))
+        ));
+        assert!(format!("{:?}", target).contains("RequestTarget"));
+
+        let target = RequestTarget::new(Uri::from_static("/"));
+        assert_eq!(target.segments_len(), 0);
+        assert_eq!(target.query(), None);
+        assert_eq!(target.query_pairs().len(), 0);
+
+        let target = RequestTarget::new(Uri::from_static("http://localhost/a/b"));
+        assert_eq!(target.segments().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(target.uri().host(), Some("localhost"));
+    }
+
+    #[test]
+    fn test_query() {
+        let target = RequestTarget::new(Uri::from_static(
+            "/?id=10&name=a+b%21&flag&&id=11&bad=%zz%2",
+        ));
+        assert_eq!(
+            target.query(),
+            Some("id=10&name=a+b%21&flag&&id=11&bad=%zz%2")
+        );
+        assert_eq!(
+            target.query_pairs().collect::<Vec<_>>(),
+            vec![
+                (Cow::Borrowed("id"), Cow::Borrowed("10")),
+                (Cow::Borrowed("name"), Cow::Owned("a b!".to_string())),
+                (Cow::Borrowed("flag"), Cow::Borrowed("")),
+                (Cow::Borrowed("id"), Cow::Borrowed("11")),
+                (Cow::Borrowed("bad"), Cow::Owned("%zz%2".to_string())),
+            ]
+        );
+        assert!(matches!(target.query_get("id"), Some(Cow::Borrowed("10"))));
+        assert_eq!(
+            target.query_get_all("id").collect::<Vec<_>>(),
+            vec!["10", "11"]
+        );
+        assert_eq!(target.query_get("flag").unwrap(), "");
+        assert_eq!(target.query_get("missing"), None);
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("a%2Fb", false), "a/b");
+        assert_eq!(decode("a+b", false), "a+b");
+        assert_eq!(decode("a+b", true), "a b");
+        assert_eq!(decode("%", true), "%");
+        assert_eq!(decode("%4", true), "%4");
+        assert_eq!(decode("%41", true), "A");
+        assert_eq!(decode("%ff", true), "\u{fffd}");
+    }
+}
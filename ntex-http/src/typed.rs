@@ -0,0 +1,976 @@
+//! Typed http headers
+//!
+//! Typed headers parse raw header values into structured types and encode
+//! them back, use [`HeaderMap::typed_get`] and [`HeaderMap::typed_insert`]
+//! for access.
+//!
+//! [`HeaderMap::typed_get`]: crate::HeaderMap::typed_get
+//! [`HeaderMap::typed_insert`]: crate::HeaderMap::typed_insert
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{error::Error, fmt, fmt::Write};
+
+use crate::{date, header, HeaderName, HeaderValue};
+
+/// A trait for any object that can be parsed from and encoded to header values.
+pub trait TypedHeader: Sized {
+    /// Returns name of the header
+    fn name() -> HeaderName;
+
+    /// Parse header from all values associated with header name
+    fn decode<'a, I>(values: I) -> Result<Self, InvalidTypedHeader>
+    where
+        I: Iterator<Item = &'a HeaderValue>;
+
+    /// Encode header into header value
+    fn encode(&self) -> HeaderValue;
+}
+
+/// An error for parsing typed header
+#[derive(Clone, PartialEq, Eq)]
+pub struct InvalidTypedHeader {
+    name: HeaderName,
+}
+
+impl InvalidTypedHeader {
+    fn new<H: TypedHeader>() -> Self {
+        InvalidTypedHeader { name: H::name() }
+    }
+
+    /// Name of the header that failed to parse
+    pub fn name(&self) -> &HeaderName {
+        &self.name
+    }
+}
+
+impl fmt::Debug for InvalidTypedHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InvalidTypedHeader")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl fmt::Display for InvalidTypedHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid `{}` header value", self.name)
+    }
+}
+
+impl Error for InvalidTypedHeader {}
+
+fn to_value(s: String) -> HeaderValue {
+    // all typed headers are encoded from valid visible ascii
+    HeaderValue::try_from(s).expect("Typed header is encoded to invalid value")
+}
+
+/// Returns first value as trimmed str
+fn first_str<'a, H, I>(mut values: I) -> Result<&'a str, InvalidTypedHeader>
+where
+    H: TypedHeader,
+    I: Iterator<Item = &'a HeaderValue>,
+{
+    values
+        .next()
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(InvalidTypedHeader::new::<H>)
+}
+
+/// `Content-Type` header
+///
+/// Mime type is stored as is, type and subtype could be accessed
+/// in lowercase via `essence()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentType(String);
+
+impl ContentType {
+    /// Create `Content-Type` header from mime string
+    pub fn new(mime: &str) -> Result<Self, InvalidTypedHeader> {
+        let mime = mime.trim();
+        let essence = mime.split(';').next().unwrap_or("").trim();
+        match essence.split_once('/') {
+            Some((tp, subtp))
+                if !tp.is_empty()
+                    && !subtp.is_empty()
+                    && HeaderValue::from_str(mime).is_ok() =>
+            {
+                Ok(ContentType(mime.to_string()))
+            }
+            _ => Err(InvalidTypedHeader::new::<Self>()),
+        }
+    }
+
+    /// `application/json` content type
+    pub fn json() -> Self {
+        ContentType("application/json".to_string())
+    }
+
+    /// `text/plain; charset=utf-8` content type
+    pub fn text() -> Self {
+        ContentType("text/plain; charset=utf-8".to_string())
+    }
+
+    /// `text/html; charset=utf-8` content type
+    pub fn html() -> Self {
+        ContentType("text/html; charset=utf-8".to_string())
+    }
+
+    /// `application/octet-stream` content type
+    pub fn octet_stream() -> Self {
+        ContentType("application/octet-stream".to_string())
+    }
+
+    /// `application/x-www-form-urlencoded` content type
+    pub fn form_url_encoded() -> Self {
+        ContentType("application/x-www-form-urlencoded".to_string())
+    }
+
+    /// Returns `type/subtype` part of the mime type in lowercase
+    pub fn essence(&self) -> String {
+        self.0
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase()
+    }
+
+    /// Returns value of the mime type parameter, name is case-insensitive
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.0.split(';').skip(1).find_map(|p| {
+            let (key, val) = p.split_once('=')?;
+            if key.trim().eq_ignore_ascii_case(name) {
+                Some(val.trim().trim_matches('"'))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns value of `charset` parameter
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    /// Returns full mime string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TypedHeader for ContentType {
+    fn name() -> HeaderName {
+        header::CONTENT_TYPE
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, InvalidTypedHeader>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        ContentType::new(first_str::<Self, _>(values)?)
+    }
+
+    fn encode(&self) -> HeaderValue {
+        to_value(self.0.clone())
+    }
+}
+
+/// `Cache-Control` header
+///
+/// Directives could be split across several header values.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    no_cache: bool,
+    no_store: bool,
+    no_transform: bool,
+    only_if_cached: bool,
+    must_revalidate: bool,
+    public: bool,
+    private: bool,
+    immutable: bool,
+    max_age: Option<u64>,
+    s_max_age: Option<u64>,
+    max_stale: Option<u64>,
+    min_fresh: Option<u64>,
+}
+
+impl CacheControl {
+    /// Create empty `Cache-Control` header
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `no-cache` directive
+    pub fn with_no_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+
+    /// Set `no-store` directive
+    pub fn with_no_store(mut self) -> Self {
+        self.no_store = true;
+        self
+    }
+
+    /// Set `no-transform` directive
+    pub fn with_no_transform(mut self) -> Self {
+        self.no_transform = true;
+        self
+    }
+
+    /// Set `only-if-cached` directive
+    pub fn with_only_if_cached(mut self) -> Self {
+        self.only_if_cached = true;
+        self
+    }
+
+    /// Set `must-revalidate` directive
+    pub fn with_must_revalidate(mut self) -> Self {
+        self.must_revalidate = true;
+        self
+    }
+
+    /// Set `public` directive
+    pub fn with_public(mut self) -> Self {
+        self.public = true;
+        self
+    }
+
+    /// Set `private` directive
+    pub fn with_private(mut self) -> Self {
+        self.private = true;
+        self
+    }
+
+    /// Set `immutable` directive
+    pub fn with_immutable(mut self) -> Self {
+        self.immutable = true;
+        self
+    }
+
+    /// Set `max-age` directive, in seconds
+    pub fn with_max_age(mut self, secs: u64) -> Self {
+        self.max_age = Some(secs);
+        self
+    }
+
+    /// Set `s-maxage` directive, in seconds
+    pub fn with_s_max_age(mut self, secs: u64) -> Self {
+        self.s_max_age = Some(secs);
+        self
+    }
+
+    /// Set `max-stale` directive, in seconds
+    pub fn with_max_stale(mut self, secs: u64) -> Self {
+        self.max_stale = Some(secs);
+        self
+    }
+
+    /// Set `min-fresh` directive, in seconds
+    pub fn with_min_fresh(mut self, secs: u64) -> Self {
+        self.min_fresh = Some(secs);
+        self
+    }
+
+    /// Check `no-cache` directive
+    pub fn no_cache(&self) -> bool {
+        self.no_cache
+    }
+
+    /// Check `no-store` directive
+    pub fn no_store(&self) -> bool {
+        self.no_store
+    }
+
+    /// Check `no-transform` directive
+    pub fn no_transform(&self) -> bool {
+        self.no_transform
+    }
+
+    /// Check `only-if-cached` directive
+    pub fn only_if_cached(&self) -> bool {
+        self.only_if_cached
+    }
+
+    /// Check `must-revalidate` directive
+    pub fn must_revalidate(&self) -> bool {
+        self.must_revalidate
+    }
+
+    /// Check `public` directive
+    pub fn public(&self) -> bool {
+        self.public
+    }
+
+    /// Check `private` directive
+    pub fn private(&self) -> bool {
+        self.private
+    }
+
+    /// Check `immutable` directive
+    pub fn immutable(&self) -> bool {
+        self.immutable
+    }
+
+    /// Get `max-age` directive
+    pub fn max_age(&self) -> Option<u64> {
+        self.max_age
+    }
+
+    /// Get `s-maxage` directive
+    pub fn s_max_age(&self) -> Option<u64> {
+        self.s_max_age
+    }
+
+    /// Get `max-stale` directive
+    pub fn max_stale(&self) -> Option<u64> {
+        self.max_stale
+    }
+
+    /// Get `min-fresh` directive
+    pub fn min_fresh(&self) -> Option<u64> {
+        self.min_fresh
+    }
+}
+
+impl TypedHeader for CacheControl {
+    fn name() -> HeaderName {
+        header::CACHE_CONTROL
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, InvalidTypedHeader>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let mut cc = CacheControl::default();
+        let mut empty = true;
+
+        for val in values {
+            let val = val
+                .to_str()
+                .map_err(|_| InvalidTypedHeader::new::<Self>())?;
+            for directive in val.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                empty = false;
+                let (key, arg) = match directive.split_once('=') {
+                    Some((key, arg)) => (key.trim(), Some(arg.trim().trim_matches('"'))),
+                    None => (directive, None),
+                };
+                let secs = || {
+                    arg.and_then(|s| s.parse::<u64>().ok())
+                        .ok_or_else(InvalidTypedHeader::new::<Self>)
+                };
+
+                match key.to_ascii_lowercase().as_str() {
+                    "no-cache" => cc.no_cache = true,
+                    "no-store" => cc.no_store = true,
+                    "no-transform" => cc.no_transform = true,
+                    "only-if-cached" => cc.only_if_cached = true,
+                    "must-revalidate" => cc.must_revalidate = true,
+                    "public" => cc.public = true,
+                    "private" => cc.private = true,
+                    "immutable" => cc.immutable = true,
+                    "max-age" => cc.max_age = Some(secs()?),
+                    "s-maxage" => cc.s_max_age = Some(secs()?),
+                    "max-stale" => cc.max_stale = Some(secs()?),
+                    "min-fresh" => cc.min_fresh = Some(secs()?),
+                    // unknown directives must be ignored
+                    _ => (),
+                }
+            }
+        }
+
+        if empty {
+            Err(InvalidTypedHeader::new::<Self>())
+        } else {
+            Ok(cc)
+        }
+    }
+
+    fn encode(&self) -> HeaderValue {
+        let mut parts: Vec<String> = Vec::new();
+        let flags = [
+            (self.no_cache, "no-cache"),
+            (self.no_store, "no-store"),
+            (self.no_transform, "no-transform"),
+            (self.only_if_cached, "only-if-cached"),
+            (self.must_revalidate, "must-revalidate"),
+            (self.public, "public"),
+            (self.private, "private"),
+            (self.immutable, "immutable"),
+        ];
+        for (set, name) in flags {
+            if set {
+                parts.push(name.to_string());
+            }
+        }
+        let secs = [
+            (self.max_age, "max-age"),
+            (self.s_max_age, "s-maxage"),
+            (self.max_stale, "max-stale"),
+            (self.min_fresh, "min-fresh"),
+        ];
+        for (val, name) in secs {
+            if let Some(val) = val {
+                parts.push(format!("{}={}", name, val));
+            }
+        }
+        to_value(parts.join(", "))
+    }
+}
+
+/// `Authorization` header
+#[derive(Clone, PartialEq, Eq)]
+pub enum Authorization {
+    /// `Basic` authentication scheme
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    /// `Bearer` token authentication scheme
+    Bearer(String),
+    /// Any other authentication scheme, credentials must be valid header value
+    Other { scheme: String, credentials: String },
+}
+
+impl Authorization {
+    /// Create `Basic` authorization
+    pub fn basic(username: &str, password: Option<&str>) -> Self {
+        Authorization::Basic {
+            username: username.to_string(),
+            password: password.map(|s| s.to_string()),
+        }
+    }
+
+    /// Create `Bearer` authorization
+    pub fn bearer(token: &str) -> Self {
+        Authorization::Bearer(token.to_string())
+    }
+
+    /// Returns authentication scheme
+    pub fn scheme(&self) -> &str {
+        match self {
+            Authorization::Basic { .. } => "Basic",
+            Authorization::Bearer(_) => "Bearer",
+            Authorization::Other { scheme, .. } => scheme,
+        }
+    }
+}
+
+impl fmt::Debug for Authorization {
+    // credentials are not printed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Authorization::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+            Authorization::Bearer(_) => f.debug_struct("Bearer").finish_non_exhaustive(),
+            Authorization::Other { scheme, .. } => f
+                .debug_struct("Other")
+                .field("scheme", scheme)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+impl TypedHeader for Authorization {
+    fn name() -> HeaderName {
+        header::AUTHORIZATION
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, InvalidTypedHeader>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let val = first_str::<Self, _>(values)?;
+        let (scheme, credentials) = val
+            .split_once(' ')
+            .map(|(s, c)| (s, c.trim()))
+            .filter(|(_, c)| !c.is_empty())
+            .ok_or_else(InvalidTypedHeader::new::<Self>)?;
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = base64_decode(credentials.as_bytes())
+                .and_then(|v| String::from_utf8(v).ok())
+                .ok_or_else(InvalidTypedHeader::new::<Self>)?;
+            Ok(match decoded.split_once(':') {
+                Some((user, pwd)) => Authorization::Basic {
+                    username: user.to_string(),
+                    password: if pwd.is_empty() {
+                        None
+                    } else {
+                        Some(pwd.to_string())
+                    },
+                },
+                None => Authorization::Basic {
+                    username: decoded,
+                    password: None,
+                },
+            })
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            Ok(Authorization::Bearer(credentials.to_string()))
+        } else {
+            Ok(Authorization::Other {
+                scheme: scheme.to_string(),
+                credentials: credentials.to_string(),
+            })
+        }
+    }
+
+    fn encode(&self) -> HeaderValue {
+        let mut val = match self {
+            Authorization::Basic { username, password } => {
+                let creds = format!("{}:{}", username, password.as_deref().unwrap_or(""));
+                to_value(format!("Basic {}", base64_encode(creds.as_bytes())))
+            }
+            Authorization::Bearer(token) => to_value(format!("Bearer {}", token)),
+            Authorization::Other {
+                scheme,
+                credentials,
+            } => to_value(format!("{} {}", scheme, credentials)),
+        };
+        val.set_sensitive(true);
+        val
+    }
+}
+
+const ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(src: &[u8]) -> String {
+    let mut out = String::with_capacity(src.len().div_ceil(3) * 4);
+    for chunk in src.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | ((*b as u32) << (16 - i * 8)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - i * 6)) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(src: &[u8]) -> Option<Vec<u8>> {
+    let src = match src {
+        [rest @ .., b'=', b'='] | [rest @ .., b'='] => rest,
+        _ => src,
+    };
+    if src.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(src.len() * 3 / 4);
+    for chunk in src.chunks(4) {
+        let mut n = 0u32;
+        for (i, b) in chunk.iter().enumerate() {
+            let v = ALPHABET.iter().position(|a| a == b)? as u32;
+            n |= v << (18 - i * 6);
+        }
+        for i in 0..chunk.len() - 1 {
+            out.push((n >> (16 - i * 8)) as u8);
+        }
+    }
+    Some(out)
+}
+
+macro_rules! date_header {
+    ($(#[$doc:meta])* $name:ident, $header:expr) => {
+        $(#[$doc])*
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub struct $name(pub SystemTime);
+
+        impl TypedHeader for $name {
+            fn name() -> HeaderName {
+                $header
+            }
+
+            fn decode<'a, I>(mut values: I) -> Result<Self, InvalidTypedHeader>
+            where
+                I: Iterator<Item = &'a HeaderValue>,
+            {
+                values
+                    .next()
+                    .and_then(|v| date::parse_http_date(v.as_bytes()))
+                    .map($name)
+                    .ok_or_else(InvalidTypedHeader::new::<Self>)
+            }
+
+            fn encode(&self) -> HeaderValue {
+                to_value(date::fmt_http_date(self.0).to_string())
+            }
+        }
+    };
+}
+
+date_header!(
+    /// `If-Modified-Since` header
+    IfModifiedSince,
+    header::IF_MODIFIED_SINCE
+);
+
+date_header!(
+    /// `Last-Modified` header
+    LastModified,
+    header::LAST_MODIFIED
+);
+
+impl IfModifiedSince {
+    /// Check if resource is modified since the date.
+    ///
+    /// Http dates have one second precision, sub-second part of
+    /// the modification time is ignored.
+    pub fn is_modified(&self, modified: SystemTime) -> bool {
+        let secs = |t: SystemTime| {
+            t.duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        };
+        secs(modified) > secs(self.0)
+    }
+}
+
+/// Byte range specification of `Range` header
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ByteRangeSpec {
+    /// `first-last` range, both positions are inclusive
+    FromTo(u64, u64),
+    /// `first-` range, from position to the end of representation
+    From(u64),
+    /// `-suffix` range, last number of bytes
+    Last(u64),
+}
+
+impl ByteRangeSpec {
+    /// Returns inclusive range for representation of `len` bytes,
+    /// `None` if range is not satisfiable
+    pub fn to_satisfiable_range(&self, len: u64) -> Option<(u64, u64)> {
+        match *self {
+            ByteRangeSpec::FromTo(from, to) if from < len && from <= to => {
+                Some((from, to.min(len - 1)))
+            }
+            ByteRangeSpec::From(from) if from < len => Some((from, len - 1)),
+            ByteRangeSpec::Last(last) if last > 0 && len > 0 => {
+                Some((len.saturating_sub(last), len - 1))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ByteRangeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ByteRangeSpec::FromTo(from, to) => write!(f, "{}-{}", from, to),
+            ByteRangeSpec::From(from) => write!(f, "{}-", from),
+            ByteRangeSpec::Last(last) => write!(f, "-{}", last),
+        }
+    }
+}
+
+/// `Range` header, only `bytes` unit is supported
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Range(Vec<ByteRangeSpec>);
+
+impl Range {
+    /// Create `Range` header from byte ranges
+    pub fn bytes<I: IntoIterator<Item = ByteRangeSpec>>(ranges: I) -> Self {
+        Range(ranges.into_iter().collect())
+    }
+
+    /// Returns requested byte ranges
+    pub fn ranges(&self) -> &[ByteRangeSpec] {
+        &self.0
+    }
+
+    /// Returns satisfiable inclusive ranges for representation of `len` bytes
+    pub fn satisfiable_ranges(&self, len: u64) -> Vec<(u64, u64)> {
+        self.0
+            .iter()
+            .filter_map(|r| r.to_satisfiable_range(len))
+            .collect()
+    }
+}
+
+impl TypedHeader for Range {
+    fn name() -> HeaderName {
+        header::RANGE
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, InvalidTypedHeader>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let err = InvalidTypedHeader::new::<Self>;
+        let val = first_str::<Self, _>(values)?;
+        let set = val
+            .split_once('=')
+            .filter(|(unit, _)| unit.trim().eq_ignore_ascii_case("bytes"))
+            .map(|(_, set)| set)
+            .ok_or_else(err)?;
+
+        let mut ranges = Vec::new();
+        for spec in set.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let (from, to) = spec.split_once('-').ok_or_else(err)?;
+            let (from, to) = (from.trim(), to.trim());
+            let pos = |s: &str| s.parse::<u64>().map_err(|_| err());
+
+            ranges.push(match (from.is_empty(), to.is_empty()) {
+                (false, false) => {
+                    let (from, to) = (pos(from)?, pos(to)?);
+                    if from > to {
+                        return Err(err());
+                    }
+                    ByteRangeSpec::FromTo(from, to)
+                }
+                (false, true) => ByteRangeSpec::From(pos(from)?),
+                (true, false) => ByteRangeSpec::Last(pos(to)?),
+                (true, true) => return Err(err()),
+            });
+        }
+
+        if ranges.is_empty() {
+            Err(err())
+        } else {
+            Ok(Range(ranges))
+        }
+    }
+
+    fn encode(&self) -> HeaderValue {
+        let mut s = String::from("bytes=");
+        for (idx, range) in self.0.iter().enumerate() {
+            if idx > 0 {
+                s.push_str(", ");
+            }
+            let _ = write!(s, "{}", range);
+        }
+        to_value(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HeaderMap;
+
+    #[test]
+    fn test_content_type() {
+        let mut map = HeaderMap::new();
+        assert_eq!(map.typed_get::<ContentType>(), None);
+
+        map.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("Text/HTML; Charset=\"UTF-8\""),
+        );
+        let ct = map.typed_get::<ContentType>().unwrap();
+        assert_eq!(ct.essence(), "text/html");
+        assert_eq!(ct.charset(), Some("UTF-8"));
+        assert_eq!(ct.param("boundary"), None);
+
+        map.typed_insert(ContentType::json());
+        assert_eq!(map.get(header::CONTENT_TYPE).unwrap(), "application/json");
+        assert_eq!(map.typed_get::<ContentType>(), Some(ContentType::json()));
+        assert_eq!(ContentType::text().charset(), Some("utf-8"));
+        assert_eq!(ContentType::html().essence(), "text/html");
+        assert_eq!(
+            ContentType::octet_stream().as_str(),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            ContentType::form_url_encoded().as_str(),
+            "application/x-www-form-urlencoded"
+        );
+
+        map.insert(header::CONTENT_TYPE, HeaderValue::from_static("json"));
+        assert_eq!(map.typed_get::<ContentType>(), None);
+        let err = map.typed_try_get::<ContentType>().unwrap_err();
+        assert_eq!(err.name(), &header::CONTENT_TYPE);
+        assert_eq!(err.to_string(), "Invalid `content-type` header value");
+        assert!(format!("{:?}", err).contains("InvalidTypedHeader"));
+    }
+
+    #[test]
+    fn test_cache_control() {
+        let mut map = HeaderMap::new();
+        map.append(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("no-cache, Max-Age=60"),
+        );
+        map.append(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("private, ext=\"value\""),
+        );
+        let cc = map.typed_get::<CacheControl>().unwrap();
+        assert!(cc.no_cache());
+        assert!(cc.private());
+        assert!(!cc.public());
+        assert_eq!(cc.max_age(), Some(60));
+        assert_eq!(cc.s_max_age(), None);
+
+        let cc = CacheControl::new()
+            .with_public()
+            .with_immutable()
+            .with_max_age(3600)
+            .with_s_max_age(60);
+        map.typed_insert(cc.clone());
+        assert_eq!(
+            map.get(header::CACHE_CONTROL).unwrap(),
+            "public, immutable, max-age=3600, s-maxage=60"
+        );
+        assert_eq!(map.typed_get::<CacheControl>(), Some(cc));
+
+        let cc = CacheControl::new()
+            .with_no_store()
+            .with_no_transform()
+            .with_only_if_cached()
+            .with_must_revalidate()
+            .with_private()
+            .with_no_cache()
+            .with_max_stale(1)
+            .with_min_fresh(2);
+        let decoded = CacheControl::decode([cc.encode()].iter()).unwrap();
+        assert_eq!(decoded, cc);
+        assert!(decoded.no_store() && decoded.no_transform());
+        assert!(decoded.only_if_cached() && decoded.must_revalidate());
+        assert!(!decoded.immutable());
+        assert_eq!(decoded.max_stale(), Some(1));
+        assert_eq!(decoded.min_fresh(), Some(2));
+
+        map.insert(header::CACHE_CONTROL, HeaderValue::from_static("max-age=x"));
+        assert!(map.typed_try_get::<CacheControl>().is_err());
+        map.insert(header::CACHE_CONTROL, HeaderValue::from_static(" , "));
+        assert!(map.typed_try_get::<CacheControl>().is_err());
+    }
+
+    #[test]
+    fn test_authorization() {
+        let mut map = HeaderMap::new();
+        map.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="),
+        );
+        let auth = map.typed_get::<Authorization>().unwrap();
+        assert_eq!(auth, Authorization::basic("Aladdin", Some("open sesame")));
+        assert_eq!(auth.scheme(), "Basic");
+        assert!(!format!("{:?}", auth).contains("sesame"));
+
+        map.typed_insert(Authorization::basic("user", None));
+        assert_eq!(map.get(header::AUTHORIZATION).unwrap(), "Basic dXNlcjo=");
+        assert!(map.get(header::AUTHORIZATION).unwrap().is_sensitive());
+        assert_eq!(
+            map.typed_get::<Authorization>(),
+            Some(Authorization::basic("user", None))
+        );
+
+        map.typed_insert(Authorization::bearer("token"));
+        assert_eq!(map.get(header::AUTHORIZATION).unwrap(), "Bearer token");
+        let auth = map.typed_get::<Authorization>().unwrap();
+        assert_eq!(auth.scheme(), "Bearer");
+        assert!(!format!("{:?}", auth).contains("token"));
+
+        map.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Digest username=\"user\""),
+        );
+        let auth = map.typed_get::<Authorization>().unwrap();
+        assert_eq!(auth.scheme(), "Digest");
+        assert_eq!(
+            auth,
+            Authorization::Other {
+                scheme: "Digest".to_string(),
+                credentials: "username=\"user\"".to_string()
+            }
+        );
+        assert_eq!(
+            map.typed_get::<Authorization>().unwrap().encode(),
+            "Digest username=\"user\""
+        );
+
+        map.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic"));
+        assert!(map.typed_try_get::<Authorization>().is_err());
+        map.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic !!"));
+        assert!(map.typed_try_get::<Authorization>().is_err());
+    }
+
+    #[test]
+    fn test_base64() {
+        for src in ["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+            let encoded = base64_encode(src.as_bytes());
+            assert_eq!(base64_decode(encoded.as_bytes()).unwrap(), src.as_bytes());
+        }
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_decode(b"Zm9vY"), None);
+    }
+
+    #[test]
+    fn test_date_headers() {
+        use std::time::Duration;
+
+        let time = UNIX_EPOCH + Duration::from_secs(784111777);
+        let mut map = HeaderMap::new();
+        map.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"),
+        );
+        let since = map.typed_get::<IfModifiedSince>().unwrap();
+        assert_eq!(since, IfModifiedSince(time));
+        assert!(!since.is_modified(time));
+        assert!(!since.is_modified(time + Duration::from_millis(500)));
+        assert!(!since.is_modified(time - Duration::from_secs(1)));
+        assert!(since.is_modified(time + Duration::from_secs(1)));
+
+        map.typed_insert(LastModified(time));
+        assert_eq!(
+            map.get(header::LAST_MODIFIED).unwrap(),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+        assert_eq!(map.typed_get::<LastModified>(), Some(LastModified(time)));
+
+        map.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("yesterday"),
+        );
+        assert!(map.typed_try_get::<IfModifiedSince>().is_err());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut map = HeaderMap::new();
+        map.insert(
+            header::RANGE,
+            HeaderValue::from_static("bytes=0-499, 1000-, -500"),
+        );
+        let range = map.typed_get::<Range>().unwrap();
+        assert_eq!(
+            range.ranges(),
+            &[
+                ByteRangeSpec::FromTo(0, 499),
+                ByteRangeSpec::From(1000),
+                ByteRangeSpec::Last(500)
+            ]
+        );
+        assert_eq!(
+            range.satisfiable_ranges(1200),
+            vec![(0, 499), (1000, 1199), (700, 1199)]
+        );
+        assert_eq!(range.satisfiable_ranges(800), vec![(0, 499), (300, 799)]);
+        assert_eq!(ByteRangeSpec::Last(10).to_satisfiable_range(0), None);
+
+        map.typed_insert(Range::bytes([
+            ByteRangeSpec::FromTo(1, 2),
+            ByteRangeSpec::Last(3),
+        ]));
+        assert_eq!(map.get(header::RANGE).unwrap(), "bytes=1-2, -3");
+
+        for invalid in ["items=0-1", "bytes=", "bytes=5-1", "bytes=-", "bytes=a-b"] {
+            map.insert(header::RANGE, HeaderValue::from_static(invalid));
+            assert!(map.typed_try_get::<Range>().is_err(), "{}", invalid);
+        }
+    }
+}
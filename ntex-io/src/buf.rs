@@ -307,6 +307,12 @@ impl<'a> ReadBuf<'a> {
         self.io.want_shutdown()
     }
 
+    #[inline]
+    /// Get io memory pool
+    pub fn memory_pool(&self) -> PoolRef {
+        self.io.memory_pool()
+    }
+
     #[inline]
     /// Make sure buffer has enough free space
     pub fn resize_buf(&self, buf: &mut BytesVec) {
@@ -439,6 +445,12 @@ impl<'a> WriteBuf<'a> {
         self.io.want_shutdown()
     }
 
+    #[inline]
+    /// Get io memory pool
+    pub fn memory_pool(&self) -> PoolRef {
+        self.io.memory_pool()
+    }
+
     #[inline]
     /// Make sure buffer has enough free space
     pub fn resize_buf(&self, buf: &mut BytesVec) {
@@ -29,7 +29,7 @@ pub use self::framed::Framed;
 pub use self::io::{Io, IoRef, OnDisconnect};
 pub use self::seal::{IoBoxed, Sealed};
 pub use self::tasks::{ReadContext, WriteContext};
-pub use self::timer::TimerHandle;
+pub use self::timer::{set_timer_granularity, timer_granularity, TimerHandle};
 pub use self::utils::{seal, Decoded};
 
 /// Status for read task
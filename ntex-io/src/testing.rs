@@ -3,12 +3,17 @@
 use std::future::{poll_fn, Future};
 use std::sync::{Arc, Mutex};
 use std::task::{ready, Context, Poll, Waker};
-use std::{any, cell::RefCell, cmp, fmt, io, mem, net, pin::Pin, rc::Rc};
+use std::time::{Duration, Instant};
+use std::{
+    any, cell::RefCell, cmp, collections::VecDeque, fmt, io, mem, net, pin::Pin, rc::Rc,
+};
 
 use ntex_bytes::{Buf, BufMut, Bytes, BytesVec};
 use ntex_util::time::{sleep, Millis, Sleep};
 
-use crate::{types, Handle, IoStream, ReadContext, ReadStatus, WriteContext, WriteStatus};
+use crate::{
+    types, Handle, Io, IoStream, ReadContext, ReadStatus, WriteContext, WriteStatus,
+};
 
 #[derive(Default)]
 struct AtomicWaker(Arc<Mutex<RefCell<Option<Waker>>>>);
@@ -67,6 +72,12 @@ struct Channel {
     waker: AtomicWaker,
     read: IoTestState,
     write: IoTestState,
+    read_chunk: usize,
+    read_chunk_sent: bool,
+    read_block: usize,
+    read_delay: Millis,
+    delayed: VecDeque<(Instant, Bytes)>,
+    delayed_timer: Option<Sleep>,
 }
 
 unsafe impl Sync for Channel {}
@@ -76,6 +87,25 @@ impl Channel {
     fn is_closed(&self) -> bool {
         self.flags.contains(IoTestFlags::CLOSED)
     }
+
+    /// Move delayed data to read buffer
+    fn poll_delayed(&mut self, cx: &mut Context<'_>) {
+        let now = Instant::now();
+        while let Some((at, _)) = self.delayed.front() {
+            if *at > now {
+                break;
+            }
+            if let Some((_, data)) = self.delayed.pop_front() {
+                self.buf.extend_from_slice(&data);
+            }
+        }
+
+        self.delayed_timer = self.delayed.front().map(|(at, _)| {
+            let timer = sleep(Millis::from(*at - now) + Millis(1));
+            let _ = timer.poll_elapsed(cx);
+            timer
+        });
+    }
 }
 
 impl Default for IoTestFlags {
@@ -118,6 +148,14 @@ impl IoTest {
         )
     }
 
+    /// Create in-memory `Io` object and test stream connected to it
+    ///
+    /// Must be called within runtime.
+    pub fn pair() -> (IoTest, Io) {
+        let (client, server) = IoTest::create();
+        (client, Io::new(server))
+    }
+
     pub fn is_client_dropped(&self) -> bool {
         self.state.lock().unwrap().borrow().client_dropped
     }
@@ -149,6 +187,29 @@ impl IoTest {
         channel.borrow().waker.wake();
     }
 
+    /// Limit size of data returned by single read on remote side.
+    ///
+    /// Each read returns at most `size` bytes, next read yields `Pending`
+    /// and wakes reader immediately, so remote side observes data in separate
+    /// chunks. Zero disables chunking.
+    pub fn read_chunk(&self, size: usize) {
+        let channel = self.remote.lock().unwrap();
+        channel.borrow_mut().read_chunk = size;
+        channel.borrow().waker.wake();
+    }
+
+    /// Force next `count` reads on remote side to return `Pending`.
+    ///
+    /// Simulates spurious `WouldBlock`, reader is woken immediately.
+    pub fn read_would_block(&self, count: usize) {
+        self.remote.lock().unwrap().borrow_mut().read_block = count;
+    }
+
+    /// Delay delivery of data written with `write()` to remote side.
+    pub fn read_delay(&self, delay: Millis) {
+        self.remote.lock().unwrap().borrow_mut().read_delay = delay;
+    }
+
     /// Set write error on remote side
     pub fn write_error(&self, err: io::Error) {
         self.local.lock().unwrap().borrow_mut().write = IoTestState::Err(err);
@@ -191,7 +252,14 @@ impl IoTest {
     pub fn write<T: AsRef<[u8]>>(&self, data: T) {
         let guard = self.remote.lock().unwrap();
         let mut write = guard.borrow_mut();
-        write.buf.extend_from_slice(data.as_ref());
+        if write.read_delay.is_zero() {
+            write.buf.extend_from_slice(data.as_ref());
+        } else {
+            let at = Instant::now() + Duration::from(write.read_delay);
+            write
+                .delayed
+                .push_back((at, Bytes::copy_from_slice(data.as_ref())));
+        }
         write.waker.wake();
     }
 
@@ -248,12 +316,34 @@ impl IoTest {
         let mut ch = guard.borrow_mut();
         *ch.waker.0.lock().unwrap().borrow_mut() = Some(cx.waker().clone());
 
+        if !ch.delayed.is_empty() {
+            ch.poll_delayed(cx);
+        }
+
+        if ch.read_block > 0 {
+            ch.read_block -= 1;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
         if !ch.buf.is_empty() {
-            let size = std::cmp::min(ch.buf.len(), buf.remaining_mut());
+            let mut size = std::cmp::min(ch.buf.len(), buf.remaining_mut());
+            if ch.read_chunk != 0 {
+                if ch.read_chunk_sent {
+                    ch.read_chunk_sent = false;
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                ch.read_chunk_sent = true;
+                size = std::cmp::min(size, ch.read_chunk);
+            }
             let b = ch.buf.split_to(size);
             buf.put_slice(&b);
             return Poll::Ready(Ok(size));
         }
+        if !ch.delayed.is_empty() {
+            return Poll::Pending;
+        }
 
         match mem::take(&mut ch.read) {
             IoTestState::Ok => Poll::Pending,
@@ -653,6 +743,7 @@ pub(super) fn flush_io(
 #[allow(clippy::redundant_clone)]
 mod tests {
     use super::*;
+    use ntex_codec::BytesCodec;
     use ntex_util::future::lazy;
 
     #[ntex::test]
@@ -686,4 +777,67 @@ mod tests {
         let res = lazy(|cx| server2.poll_write_buf(cx, b"123")).await;
         assert!(res.is_pending());
     }
+
+    #[ntex::test]
+    async fn read_control() {
+        let (client, server) = IoTest::create();
+        let mut buf = BytesVec::new();
+
+        // chunked reads
+        client.read_chunk(2);
+        client.write(b"12345");
+        for expected in [2, 0, 2, 0, 1] {
+            let res = lazy(|cx| server.poll_read_buf(cx, &mut buf)).await;
+            match res {
+                Poll::Ready(Ok(n)) => assert_eq!(n, expected),
+                Poll::Pending => assert_eq!(expected, 0),
+                Poll::Ready(Err(_)) => panic!(),
+            }
+        }
+        assert_eq!(&buf[..], b"12345");
+
+        // spurious would block
+        client.read_chunk(0);
+        client.read_would_block(2);
+        client.write(b"678");
+        assert!(lazy(|cx| server.poll_read_buf(cx, &mut buf))
+            .await
+            .is_pending());
+        assert!(lazy(|cx| server.poll_read_buf(cx, &mut buf))
+            .await
+            .is_pending());
+        let res = lazy(|cx| server.poll_read_buf(cx, &mut buf)).await;
+        assert!(matches!(res, Poll::Ready(Ok(3))));
+
+        // delayed delivery
+        client.read_delay(Millis(50));
+        client.write(b"9");
+        assert!(lazy(|cx| server.poll_read_buf(cx, &mut buf))
+            .await
+            .is_pending());
+        sleep(Millis(100)).await;
+        let res = lazy(|cx| server.poll_read_buf(cx, &mut buf)).await;
+        assert!(matches!(res, Poll::Ready(Ok(1))));
+        assert_eq!(&buf[..], b"123456789");
+    }
+
+    #[ntex::test]
+    async fn pair() {
+        let (client, io) = IoTest::pair();
+        client.remote_buffer_cap(1024);
+        client.read_chunk(3);
+        client.write(b"hello");
+
+        let mut data = Vec::new();
+        while data.len() < 5 {
+            let msg = io.recv(&BytesCodec).await.unwrap().unwrap();
+            data.extend_from_slice(&msg);
+        }
+        assert_eq!(data, b"hello");
+
+        io.send(Bytes::from_static(b"world"), &BytesCodec)
+            .await
+            .unwrap();
+        assert_eq!(client.read().await.unwrap(), Bytes::from_static(b"world"));
+    }
 }
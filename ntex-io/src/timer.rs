@@ -1,5 +1,4 @@
 #![allow(clippy::mutable_key_type)]
-use std::collections::{BTreeMap, VecDeque};
 use std::{cell::Cell, cell::RefCell, ops, rc::Rc, time::Duration, time::Instant};
 
 use ntex_util::time::{now, sleep, Seconds};
@@ -7,21 +6,54 @@ use ntex_util::{spawn, HashSet};
 
 use crate::{io::IoState, IoRef};
 
-const CAP: usize = 64;
-const SEC: Duration = Duration::from_secs(1);
+/// Number of wheel slots
+const SLOTS: u32 = 512;
 
 thread_local! {
     static TIMER: Inner = Inner {
         running: Cell::new(false),
-        base: Cell::new(Instant::now()),
+        base: Cell::new(now()),
         current: Cell::new(0),
+        granularity: Cell::new(Seconds(1)),
+        pending: Cell::new(None),
         storage: RefCell::new(InnerMut {
-            cache: VecDeque::with_capacity(CAP),
-            notifications: BTreeMap::default(),
+            slots: (0..SLOTS).map(|_| HashSet::default()).collect(),
+            len: 0,
         })
     }
 }
 
+/// Set granularity of io timers for current thread
+///
+/// Io timers (keep-alive, read and disconnect timeouts) of all connections
+/// that belong to current thread are tracked by a shared hashed timer wheel.
+/// Granularity defines wheel's tick, timeouts are rounded up to the tick.
+/// Coarse granularity reduces number of timer wakeups for large number
+/// of idle connections, at the cost of timeouts precision.
+///
+/// New granularity is applied when the wheel does not have registered
+/// timers. By default granularity is set to 1 second. Servers should
+/// configure granularity from the worker start hook.
+pub fn set_timer_granularity(granularity: Seconds) {
+    let granularity = if granularity.is_zero() {
+        Seconds(1)
+    } else {
+        granularity
+    };
+    TIMER.with(|timer| {
+        if timer.running.get() {
+            timer.pending.set(Some(granularity));
+        } else {
+            timer.granularity.set(granularity);
+        }
+    });
+}
+
+/// Get granularity of io timers for current thread
+pub fn timer_granularity() -> Seconds {
+    TIMER.with(|timer| timer.pending.get().unwrap_or(timer.granularity.get()))
+}
+
 #[derive(Copy, Clone, Default, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct TimerHandle(u32);
 
@@ -38,13 +70,17 @@ impl TimerHandle {
             if self.0 <= cur {
                 Seconds::ZERO
             } else {
-                Seconds((self.0 - cur) as u16)
+                let secs = (self.0 - cur) * timer.granularity.get().0 as u32;
+                Seconds(secs.min(u16::MAX as u32) as u16)
             }
         })
     }
 
     pub fn instant(&self) -> Instant {
-        TIMER.with(|timer| timer.base.get() + Duration::from_secs(self.0 as u64))
+        TIMER.with(|timer| {
+            timer.base.get()
+                + Duration::from_secs(self.0 as u64 * timer.granularity.get().0 as u64)
+        })
     }
 }
 
@@ -53,7 +89,7 @@ impl ops::Add<Seconds> for TimerHandle {
 
     #[inline]
     fn add(self, other: Seconds) -> TimerHandle {
-        TimerHandle(self.0 + other.0 as u32)
+        TIMER.with(|timer| TimerHandle(self.0 + timer.ticks(other)))
     }
 }
 
@@ -61,20 +97,53 @@ struct Inner {
     running: Cell<bool>,
     base: Cell<Instant>,
     current: Cell<u32>,
+    granularity: Cell<Seconds>,
+    pending: Cell<Option<Seconds>>,
     storage: RefCell<InnerMut>,
 }
 
+impl Inner {
+    /// Number of ticks for the timeout, rounded up
+    fn ticks(&self, timeout: Seconds) -> u32 {
+        let gran = self.granularity.get().0 as u32;
+        (timeout.0 as u32).div_ceil(gran)
+    }
+}
+
 struct InnerMut {
-    cache: VecDeque<HashSet<Rc<IoState>>>,
-    notifications: BTreeMap<u32, HashSet<Rc<IoState>>>,
+    slots: Vec<HashSet<Rc<IoState>>>,
+    len: usize,
 }
 
 impl InnerMut {
+    fn slot(&mut self, hnd: u32) -> &mut HashSet<Rc<IoState>> {
+        &mut self.slots[(hnd % SLOTS) as usize]
+    }
+
     fn unregister(&mut self, hnd: TimerHandle, io: &IoRef) {
-        if let Some(states) = self.notifications.get_mut(&hnd.0) {
-            states.remove(&io.0);
+        if self.slot(hnd.0).remove(&io.0) {
+            self.len -= 1;
         }
     }
+
+    /// Notify expired timers of the current slot
+    fn expire(&mut self, current: u32) {
+        let mut expired = 0;
+        self.slot(current).retain(|st| {
+            let hnd = st.timeout.get();
+            if !hnd.is_set() || hnd.0 <= current {
+                if hnd.is_set() {
+                    st.notify_timeout();
+                }
+                expired += 1;
+                false
+            } else {
+                // timer expires on one of the next wheel rounds
+                true
+            }
+        });
+        self.len -= expired;
+    }
 }
 
 pub(crate) fn unregister(hnd: TimerHandle, io: &IoRef) {
@@ -85,7 +154,7 @@ pub(crate) fn unregister(hnd: TimerHandle, io: &IoRef) {
 
 pub(crate) fn update(hnd: TimerHandle, timeout: Seconds, io: &IoRef) -> TimerHandle {
     TIMER.with(|timer| {
-        let new_hnd = timer.current.get() + timeout.0 as u32;
+        let new_hnd = timer.current.get() + timer.ticks(timeout);
         if hnd.0 == new_hnd || hnd.0 == new_hnd + 1 {
             hnd
         } else {
@@ -99,7 +168,11 @@ pub(crate) fn register(timeout: Seconds, io: &IoRef) -> TimerHandle {
     TIMER.with(|timer| {
         // setup current delta
         if !timer.running.get() {
-            let current = (now() - timer.base.get()).as_secs() as u32;
+            if let Some(granularity) = timer.pending.take() {
+                timer.granularity.set(granularity);
+            }
+            let gran = timer.granularity.get().0 as u64;
+            let current = ((now() - timer.base.get()).as_secs() / gran) as u32;
             timer.current.set(current);
             log::debug!(
                 "{}: Timer driver does not run, current: {}",
@@ -108,21 +181,13 @@ pub(crate) fn register(timeout: Seconds, io: &IoRef) -> TimerHandle {
             );
         }
 
-        let hnd = {
-            let hnd = timer.current.get() + timeout.0 as u32;
+        let hnd = timer.current.get() + timer.ticks(timeout).max(1);
+        {
             let mut inner = timer.storage.borrow_mut();
-
-            // insert key
-            if let Some(item) = inner.notifications.range_mut(hnd..hnd + 1).next() {
-                item.1.insert(io.0.clone());
-                *item.0
-            } else {
-                let mut items = inner.cache.pop_front().unwrap_or_default();
-                items.insert(io.0.clone());
-                inner.notifications.insert(hnd, items);
-                hnd
+            if inner.slot(hnd).insert(io.0.clone()) {
+                inner.len += 1;
             }
-        };
+        }
 
         if !timer.running.get() {
             timer.running.set(true);
@@ -131,28 +196,18 @@ pub(crate) fn register(timeout: Seconds, io: &IoRef) -> TimerHandle {
             let _ = spawn(async move {
                 let guard = TimerGuard;
                 loop {
-                    sleep(SEC).await;
+                    let tick = TIMER.with(|timer| timer.granularity.get());
+                    sleep(tick).await;
                     let stop = TIMER.with(|timer| {
                         let current = timer.current.get();
                         timer.current.set(current + 1);
 
                         // notify io dispatcher
                         let mut inner = timer.storage.borrow_mut();
-                        while let Some(key) = inner.notifications.keys().next() {
-                            let key = *key;
-                            if key <= current {
-                                let mut items = inner.notifications.remove(&key).unwrap();
-                                items.drain().for_each(|st| st.notify_timeout());
-                                if inner.cache.len() <= CAP {
-                                    inner.cache.push_back(items);
-                                }
-                            } else {
-                                break;
-                            }
-                        }
+                        inner.expire(current);
 
                         // new tick
-                        if inner.notifications.is_empty() {
+                        if inner.len == 0 {
                             timer.running.set(false);
                             true
                         } else {
@@ -178,7 +233,42 @@ impl Drop for TimerGuard {
     fn drop(&mut self) {
         TIMER.with(|timer| {
             timer.running.set(false);
-            timer.storage.borrow_mut().notifications.clear();
+            let mut inner = timer.storage.borrow_mut();
+            inner.slots.iter_mut().for_each(|slot| slot.clear());
+            inner.len = 0;
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::IoTest, Io};
+
+    #[ntex::test]
+    async fn timer_wheel() {
+        let (_client, server) = IoTest::create();
+        let io = Io::new(server);
+
+        let hnd = io.start_timer(Seconds(2));
+        assert!(hnd.is_set());
+        assert!(hnd.remains() <= Seconds(2));
+        assert_eq!(io.start_timer(Seconds(2)), hnd);
+        assert_eq!(TIMER.with(|t| t.storage.borrow().len), 1);
+
+        let hnd2 = io.start_timer(Seconds(600));
+        assert_ne!(hnd2, hnd);
+        assert_eq!(TIMER.with(|t| t.storage.borrow().len), 1);
+
+        io.stop_timer();
+        assert!(!io.timer_handle().is_set());
+        assert_eq!(TIMER.with(|t| t.storage.borrow().len), 0);
+
+        TIMER.with(|t| t.granularity.set(Seconds(5)));
+        assert_eq!(TIMER.with(|t| t.ticks(Seconds(1))), 1);
+        assert_eq!(TIMER.with(|t| t.ticks(Seconds(5))), 1);
+        assert_eq!(TIMER.with(|t| t.ticks(Seconds(6))), 2);
+        set_timer_granularity(Seconds::ZERO);
+        assert_eq!(timer_granularity(), Seconds(1));
+    }
+}
@@ -22,6 +22,34 @@ impl fmt::Debug for PeerAddr {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Peer credentials of unix domain socket connection
+///
+/// Credentials are captured when connection is established,
+/// `pid` is not available on some platforms.
+pub struct PeerCred {
+    /// Process id of the peer
+    pub pid: Option<i32>,
+    /// User id of the peer
+    pub uid: u32,
+    /// Group id of the peer
+    pub gid: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// Security context of the unix domain socket peer
+///
+/// SELinux label of the peer process, for example
+/// `system_u:system_r:httpd_t:s0`. Available on linux only,
+/// if security module is enabled.
+pub struct PeerSecContext(pub String);
+
+impl PeerSecContext {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 /// Http protocol definition
 pub enum HttpProtocol {
@@ -21,6 +21,12 @@
 //! - `"path"` - Raw literal string with path for which to register handle. Mandatory.
 //! - `guard = "function_name"` - Registers function as guard using `ntex::web::guard::fn_guard`
 //! - `error = "ErrorRenderer"` - Register handler for specified error renderer
+//! - `summary = "text"` - Operation summary for route introspection
+//! - `description = "text"` - Operation description for route introspection
+//! - `tag = "name"` - Operation tag for route introspection, could be used multiple times
+//! - `deprecated = true` - Mark operation as deprecated
+//!
+//! Function name is used as operation id.
 //!
 //! ## Notes
 //!
@@ -56,6 +62,8 @@ use syn::parse_macro_input;
 /// - `"path"` - Raw literal string with path for which to register handler. Mandatory.
 /// - `guard = "function_name"` - Registers function as guard using `ntex::web::guard::fn_guard`
 /// - `error = "ErrorRenderer"` - Register handler for different error renderer
+/// - `summary = "text"`, `description = "text"`, `tag = "name"`, `deprecated = true` -
+///   Operation metadata for route introspection
 #[proc_macro_attribute]
 pub fn web_get(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(args as syn::AttributeArgs);
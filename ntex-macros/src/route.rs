@@ -44,6 +44,10 @@ struct Args {
     path: syn::LitStr,
     guards: Vec<Ident>,
     error: Path,
+    summary: Vec<syn::LitStr>,
+    description: Vec<syn::LitStr>,
+    tags: Vec<syn::LitStr>,
+    deprecated: bool,
 }
 
 impl Args {
@@ -51,6 +55,10 @@ impl Args {
         let mut path = None;
         let mut guards = Vec::new();
         let mut error: Option<Path> = None;
+        let mut summary = Vec::new();
+        let mut description = Vec::new();
+        let mut tags = Vec::new();
+        let mut deprecated = false;
         for arg in args {
             match arg {
                 NestedMeta::Lit(syn::Lit::Str(lit)) => match path {
@@ -83,10 +91,38 @@ impl Args {
                                 "Attribute error expects type path!",
                             ));
                         }
+                    } else if nv.path.is_ident("summary")
+                        || nv.path.is_ident("description")
+                        || nv.path.is_ident("tag")
+                    {
+                        if let syn::Lit::Str(lit) = nv.lit {
+                            if nv.path.is_ident("summary") {
+                                summary = vec![lit];
+                            } else if nv.path.is_ident("description") {
+                                description = vec![lit];
+                            } else {
+                                tags.push(lit);
+                            }
+                        } else {
+                            return Err(syn::Error::new_spanned(
+                                nv.lit,
+                                "Attribute expects literal string!",
+                            ));
+                        }
+                    } else if nv.path.is_ident("deprecated") {
+                        if let syn::Lit::Bool(lit) = nv.lit {
+                            deprecated = lit.value;
+                        } else {
+                            return Err(syn::Error::new_spanned(
+                                nv.lit,
+                                "Attribute deprecated expects literal bool!",
+                            ));
+                        }
                     } else {
                         return Err(syn::Error::new_spanned(
                             nv.path,
-                            "Unknown attribute key is specified. Allowed: guard or error",
+                            "Unknown attribute key is specified. Allowed: guard, error, \
+                             summary, description, tag or deprecated",
                         ));
                     }
                 }
@@ -100,6 +136,10 @@ impl Args {
             guards,
             error: error
                 .unwrap_or_else(|| syn::parse_str("ntex::web::DefaultError").unwrap()),
+            summary,
+            description,
+            tags,
+            deprecated,
         })
     }
 }
@@ -146,6 +186,14 @@ impl Route {
         let extra_guards = &self.args.guards;
         let error = &self.args.error;
         let method = &self.method;
+        let summary = &self.args.summary;
+        let description = &self.args.description;
+        let tags = &self.args.tags;
+        let deprecated = if self.args.deprecated {
+            quote! { .deprecated() }
+        } else {
+            quote! {}
+        };
 
         let stream = quote! {
             #[allow(non_camel_case_types)]
@@ -160,7 +208,17 @@ impl Route {
                         .name(#resource_name)
                         .guard(ntex::web::guard::#method())
                         #(.guard(ntex::web::guard::fn_guard(#extra_guards)))*
-                        .to(#name);
+                        .route(
+                            ntex::web::route()
+                                .operation(
+                                    ntex::web::introspect::Operation::new(#resource_name)
+                                        #(.summary(#summary))*
+                                        #(.description(#description))*
+                                        #(.tag(#tags))*
+                                        #deprecated
+                                )
+                                .to(#name)
+                        );
 
                     ntex::web::dev::WebServiceFactory::register(__resource, __config)
                 }
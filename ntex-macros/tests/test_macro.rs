@@ -1,6 +1,8 @@
 use futures::{future, Future};
 use ntex::http::{Method, StatusCode};
-use ntex::web::{test, types::Path, App, Error, HttpResponse, HttpResponseBuilder};
+use ntex::web::{
+    test, types::Path, App, Error, HttpRequest, HttpResponse, HttpResponseBuilder,
+};
 use ntex_macros::{
     web_connect, web_delete, web_get, web_head, web_options, web_patch, web_post, web_put,
     web_trace,
@@ -72,6 +74,34 @@ async fn get_param_test(_: Path<String>) -> HttpResponse {
     HttpResponse::Ok().finish()
 }
 
+#[web_get(
+    "/users/{id}",
+    summary = "Get user",
+    description = "Load user by id",
+    tag = "users",
+    tag = "public",
+    deprecated = true
+)]
+async fn get_user(req: HttpRequest) -> HttpResponse {
+    let resources = req.resource_map().resources();
+    let op = resources[0].routes()[0].operation().unwrap();
+    assert_eq!(op.get_operation_id(), Some("get_user"));
+    assert_eq!(op.get_summary(), Some("Get user"));
+    assert_eq!(op.get_description(), Some("Load user by id"));
+    assert_eq!(op.get_tags(), &["users".to_string(), "public".to_string()]);
+    assert!(op.is_deprecated());
+    HttpResponse::Ok().finish()
+}
+
+#[ntex::test]
+async fn test_operation() {
+    let srv = test::server(|| App::new().service(get_user));
+
+    let request = srv.request(Method::GET, srv.url("/users/1"));
+    let response = request.send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
 #[ntex::test]
 async fn test_params() {
     let srv = test::server(|| {
@@ -1,7 +1,7 @@
 //! Utility for async runtime abstraction
 
 #[cfg(feature = "tokio")]
-pub use ntex_tokio::{from_tcp_stream, tcp_connect, tcp_connect_in};
+pub use ntex_tokio::{from_tcp_stream, tcp_connect, tcp_connect_bind, tcp_connect_in};
 
 #[cfg(all(unix, feature = "tokio"))]
 pub use ntex_tokio::{from_unix_stream, unix_connect, unix_connect_in};
@@ -11,7 +11,7 @@ pub use ntex_tokio::{from_unix_stream, unix_connect, unix_connect_in};
     not(feature = "tokio"),
     not(feature = "glommio")
 ))]
-pub use ntex_async_std::{from_tcp_stream, tcp_connect, tcp_connect_in};
+pub use ntex_async_std::{from_tcp_stream, tcp_connect, tcp_connect_bind, tcp_connect_in};
 
 #[cfg(all(
     unix,
@@ -26,7 +26,7 @@ pub use ntex_async_std::{from_unix_stream, unix_connect, unix_connect_in};
     not(feature = "tokio"),
     not(feature = "async-std")
 ))]
-pub use ntex_glommio::{from_tcp_stream, tcp_connect, tcp_connect_in};
+pub use ntex_glommio::{from_tcp_stream, tcp_connect, tcp_connect_bind, tcp_connect_in};
 
 #[cfg(all(
     unix,
@@ -63,6 +63,20 @@ mod no_rt {
         ))
     }
 
+    /// Opens a TCP connection to a remote host, socket is bound to local
+    /// address and network interface before connecting.
+    pub async fn tcp_connect_bind(
+        _: std::net::SocketAddr,
+        _: Option<std::net::IpAddr>,
+        _: Option<ntex_bytes::ByteString>,
+        _: ntex_bytes::PoolRef,
+    ) -> std::io::Result<Io> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "runtime is not configure",
+        ))
+    }
+
     #[cfg(unix)]
     /// Opens a unix stream connection.
     pub async fn unix_connect<'a, P>(_: P) -> std::io::Result<Io>
@@ -1,5 +1,5 @@
 use std::collections::{vec_deque, VecDeque};
-use std::{fmt, iter::FusedIterator, net::SocketAddr};
+use std::{fmt, iter::FusedIterator, net::IpAddr, net::SocketAddr};
 
 use ntex_bytes::ByteString;
 use ntex_util::future::Either;
@@ -68,6 +68,8 @@ pub struct Connect<T> {
     pub(super) req: T,
     pub(super) port: u16,
     pub(super) addr: Option<Either<SocketAddr, VecDeque<SocketAddr>>>,
+    pub(super) local_addr: Option<IpAddr>,
+    pub(super) interface: Option<ByteString>,
 }
 
 impl<T: Address> Connect<T> {
@@ -78,6 +80,8 @@ impl<T: Address> Connect<T> {
             req,
             port: port.unwrap_or(0),
             addr: None,
+            local_addr: None,
+            interface: None,
         }
     }
 
@@ -87,6 +91,8 @@ impl<T: Address> Connect<T> {
             req,
             port: 0,
             addr: Some(Either::Left(addr)),
+            local_addr: None,
+            interface: None,
         }
     }
 
@@ -120,6 +126,30 @@ impl<T: Address> Connect<T> {
         self
     }
 
+    /// Bind socket to local address before connecting.
+    pub fn set_local_addr(mut self, addr: Option<IpAddr>) -> Self {
+        self.local_addr = addr;
+        self
+    }
+
+    /// Bind socket to network interface before connecting.
+    ///
+    /// Binding to network interface is supported only on linux.
+    pub fn set_interface(mut self, interface: Option<ByteString>) -> Self {
+        self.interface = interface;
+        self
+    }
+
+    /// Local address socket is bound to
+    pub fn local_addr(&self) -> Option<IpAddr> {
+        self.local_addr
+    }
+
+    /// Network interface socket is bound to
+    pub fn interface(&self) -> Option<&ByteString> {
+        self.interface.as_ref()
+    }
+
     /// Host name
     pub fn host(&self) -> &str {
         self.req.host()
@@ -180,6 +210,8 @@ impl<T: Address> Connect<T> {
             req,
             port: self.port,
             addr: self.addr,
+            local_addr: self.local_addr,
+            interface: self.interface,
         }
     }
 }
@@ -190,6 +222,8 @@ impl<T: Clone> Clone for Connect<T> {
             req: self.req.clone(),
             port: self.port,
             addr: self.addr.clone(),
+            local_addr: self.local_addr,
+            interface: self.interface.clone(),
         }
     }
 }
@@ -342,6 +376,17 @@ mod tests {
         connect = connect.set_addrs(vec![addr]);
         assert_eq!(format!("{}", connect), "www.rust-lang.org:80");
 
+        assert!(connect.local_addr().is_none());
+        assert!(connect.interface().is_none());
+        let ip: IpAddr = "127.0.0.1".parse().unwrap();
+        let connect = connect
+            .set_local_addr(Some(ip))
+            .set_interface(Some(ByteString::from_static("lo")));
+        assert_eq!(connect.local_addr(), Some(ip));
+        assert_eq!(connect.interface().unwrap(), "lo");
+        let c = connect.clone().map_addr(|_| "www.rust-lang.org:80");
+        assert_eq!(c.local_addr(), Some(ip));
+
         let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
         let mut connect = Connect::new(addr);
         assert_eq!(connect.host(), "");
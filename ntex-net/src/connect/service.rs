@@ -1,13 +1,14 @@
+use std::pin::Pin;
 use std::task::{Context, Poll};
-use std::{collections::VecDeque, fmt, future::Future, io, net::SocketAddr, pin::Pin};
+use std::{collections::VecDeque, fmt, future::Future, io, net::IpAddr, net::SocketAddr};
 
-use ntex_bytes::{PoolId, PoolRef};
+use ntex_bytes::{ByteString, PoolId, PoolRef};
 use ntex_io::{types, Io};
 use ntex_service::{Service, ServiceCtx, ServiceFactory};
 use ntex_util::future::{BoxFuture, Either};
 
 use super::{Address, Connect, ConnectError, Resolver};
-use crate::tcp_connect_in;
+use crate::{tcp_connect_bind, tcp_connect_in};
 
 #[derive(Copy)]
 pub struct Connector<T> {
@@ -57,15 +58,26 @@ impl<T: Address> Connector<T> {
             .await?;
 
         let port = address.port();
-        let Connect { req, addr, .. } = address;
+        let Connect {
+            req,
+            addr,
+            local_addr,
+            interface,
+            ..
+        } = address;
+        let bind = Bind {
+            local_addr,
+            interface,
+        };
 
         if let Some(addr) = addr {
-            TcpConnectorResponse::new(req, port, addr, self.tag, self.pool).await
+            TcpConnectorResponse::new(req, port, addr, bind, self.tag, self.pool).await
         } else if let Some(addr) = req.addr() {
             TcpConnectorResponse::new(
                 req,
                 addr.port(),
                 Either::Left(addr),
+                bind,
                 self.tag,
                 self.pool,
             )
@@ -127,11 +139,37 @@ impl<T: Address> Service<Connect<T>> for Connector<T> {
     }
 }
 
+/// Local address and interface for outgoing connection
+struct Bind {
+    local_addr: Option<IpAddr>,
+    interface: Option<ByteString>,
+}
+
+impl Bind {
+    fn connect(
+        &self,
+        addr: SocketAddr,
+        pool: PoolRef,
+    ) -> BoxFuture<'static, io::Result<Io>> {
+        if self.local_addr.is_none() && self.interface.is_none() {
+            Box::pin(tcp_connect_in(addr, pool))
+        } else {
+            Box::pin(tcp_connect_bind(
+                addr,
+                self.local_addr,
+                self.interface.clone(),
+                pool,
+            ))
+        }
+    }
+}
+
 /// Tcp stream connector response future
 struct TcpConnectorResponse<T> {
     req: Option<T>,
     port: u16,
     addrs: Option<VecDeque<SocketAddr>>,
+    bind: Bind,
     #[allow(clippy::type_complexity)]
     stream: Option<BoxFuture<'static, Result<Io, io::Error>>>,
     tag: &'static str,
@@ -143,6 +181,7 @@ impl<T: Address> TcpConnectorResponse<T> {
         req: T,
         port: u16,
         addr: Either<SocketAddr, VecDeque<SocketAddr>>,
+        bind: Bind,
         tag: &'static str,
         pool: PoolRef,
     ) -> TcpConnectorResponse<T> {
@@ -158,7 +197,8 @@ impl<T: Address> TcpConnectorResponse<T> {
             Either::Left(addr) => TcpConnectorResponse {
                 req: Some(req),
                 addrs: None,
-                stream: Some(Box::pin(tcp_connect_in(addr, pool))),
+                stream: Some(bind.connect(addr, pool)),
+                bind,
                 tag,
                 pool,
                 port,
@@ -170,6 +210,7 @@ impl<T: Address> TcpConnectorResponse<T> {
                 req: Some(req),
                 addrs: Some(addrs),
                 stream: None,
+                bind,
             },
         }
     }
@@ -218,7 +259,7 @@ impl<T: Address> Future for TcpConnectorResponse<T> {
 
             // try to connect
             let addr = this.addrs.as_mut().unwrap().pop_front().unwrap();
-            this.stream = Some(Box::pin(tcp_connect_in(addr, this.pool)));
+            this.stream = Some(this.bind.connect(addr, this.pool));
         }
     }
 }
@@ -256,5 +297,10 @@ mod tests {
         let msg = Connect::new(server.addr());
         let result = crate::connect::connect(msg).await;
         assert!(result.is_ok());
+
+        let msg =
+            Connect::new(server.addr()).set_local_addr(Some("127.0.0.1".parse().unwrap()));
+        let result = crate::connect::connect(msg).await;
+        assert!(result.is_ok());
     }
 }
@@ -0,0 +1,65 @@
+#![feature(test)]
+#![deny(warnings, rust_2018_idioms)]
+
+extern crate test;
+
+use ntex_router::{Path, Router};
+use test::Bencher;
+
+const RESOURCES: usize = 1000;
+
+fn router(radix: bool) -> Router<usize> {
+    let mut router = Router::build();
+    if radix {
+        router.radix();
+    }
+    for idx in 0..RESOURCES {
+        match idx % 4 {
+            0 => router.path(format!("/api/v1/resource{}", idx), idx),
+            1 => router.path(format!("/api/v1/resource{}/{{id}}", idx), idx),
+            2 => router.path(format!("/api/v2/resource{}/{{id}}/items", idx), idx),
+            _ => router.prefix(&format!("/static{}", idx), idx),
+        };
+    }
+    router.finish()
+}
+
+fn recognize(b: &mut Bencher, radix: bool, path: &'static str) {
+    let router = router(radix);
+    assert!(router.recognize(&mut Path::new(path)).is_some());
+
+    b.iter(|| {
+        let mut path = Path::new(path);
+        test::black_box(router.recognize(&mut path));
+    })
+}
+
+#[bench]
+fn tree_first(b: &mut Bencher) {
+    recognize(b, false, "/api/v1/resource0")
+}
+
+#[bench]
+fn tree_last(b: &mut Bencher) {
+    recognize(b, false, "/api/v2/resource998/12345/items")
+}
+
+#[bench]
+fn tree_prefix(b: &mut Bencher) {
+    recognize(b, false, "/static999/css/main.css")
+}
+
+#[bench]
+fn radix_first(b: &mut Bencher) {
+    recognize(b, true, "/api/v1/resource0")
+}
+
+#[bench]
+fn radix_last(b: &mut Bencher) {
+    recognize(b, true, "/api/v2/resource998/12345/items")
+}
+
+#[bench]
+fn radix_prefix(b: &mut Bencher) {
+    recognize(b, true, "/static999/css/main.css")
+}
@@ -9,6 +9,7 @@
 //! Resource path matching library.
 mod de;
 mod path;
+mod radix;
 mod resource;
 mod router;
 mod tree;
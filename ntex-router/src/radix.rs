@@ -0,0 +1,181 @@
+use std::{borrow::Cow, collections::HashMap, mem};
+
+use super::resource::{ResourceDef, Segment};
+use super::tree::Tree;
+use super::{Resource, ResourcePath};
+
+/// Compressed radix tree, indexes resources by static path prefix.
+///
+/// Each resource is matched with its own tree, so pattern syntax and
+/// matching rules are the same as for default router. Radix tree only
+/// selects candidates, candidates are checked in registration order.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Radix {
+    root: Node,
+    trees: Vec<Tree>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    key: Vec<String>,
+    values: Vec<usize>,
+    children: HashMap<String, Node>,
+}
+
+impl Radix {
+    pub(super) fn insert(&mut self, resource: &ResourceDef, insensitive: bool) {
+        let value = self.trees.len();
+        self.trees.push(Tree::new(resource, value));
+
+        for seg in &resource.tp {
+            let key: Vec<_> = seg
+                .tp
+                .iter()
+                .map_while(|seg| match seg {
+                    Segment::Static(s) if insensitive => Some(s.to_ascii_lowercase()),
+                    Segment::Static(s) => Some(s.clone()),
+                    Segment::Dynamic { .. } => None,
+                })
+                .collect();
+            self.root.insert(&key, value);
+        }
+    }
+
+    pub(super) fn find_checked<T, R, F>(
+        &self,
+        resource: &mut R,
+        insensitive: bool,
+        check: &F,
+    ) -> Option<usize>
+    where
+        T: ResourcePath,
+        R: Resource<T>,
+        F: Fn(usize, &R) -> bool,
+    {
+        let mut candidates = self.root.values.clone();
+
+        let path = resource.path();
+        let path = path.strip_prefix('/').unwrap_or(path);
+        let mut segments = path.split('/').map(|seg| {
+            let seg = T::unquote(seg);
+            if insensitive {
+                Cow::Owned(seg.to_ascii_lowercase())
+            } else {
+                seg
+            }
+        });
+
+        let mut node = &self.root;
+        'outer: while let Some(seg) = segments.next() {
+            let child = match node.find(seg.as_ref()) {
+                Some(child) => child,
+                None => break,
+            };
+            for key in &child.key[1..] {
+                match segments.next() {
+                    Some(seg) if seg.as_ref() == key => (),
+                    _ => break 'outer,
+                }
+            }
+            candidates.extend_from_slice(&child.values);
+            node = child;
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        candidates.into_iter().find(|idx| {
+            self.trees[*idx]
+                .find_checked_inner(resource, insensitive, check)
+                .is_some()
+        })
+    }
+}
+
+impl Node {
+    fn insert(&mut self, key: &[String], value: usize) {
+        if key.is_empty() {
+            self.values.push(value);
+            return;
+        }
+
+        match self.children.get_mut(&key[0]) {
+            Some(child) => {
+                let p = child
+                    .key
+                    .iter()
+                    .zip(key.iter())
+                    .take_while(|&(a, b)| a == b)
+                    .count();
+
+                // split child's key, and move all its values to sub node
+                if p < child.key.len() {
+                    let node = Node {
+                        key: child.key.split_off(p),
+                        values: mem::take(&mut child.values),
+                        children: mem::take(&mut child.children),
+                    };
+                    child.children.insert(node.key[0].clone(), node);
+                }
+                child.insert(&key[p..], value);
+            }
+            None => {
+                self.children.insert(
+                    key[0].clone(),
+                    Node {
+                        key: key.to_vec(),
+                        values: vec![value],
+                        children: HashMap::new(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn find(&self, segment: &str) -> Option<&Node> {
+        self.children.get(segment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::Path;
+
+    #[test]
+    fn test_radix() {
+        let mut radix = Radix::default();
+        radix.insert(&ResourceDef::new("/user/{id}"), false);
+        radix.insert(&ResourceDef::new("/user/profile/edit"), false);
+        radix.insert(&ResourceDef::new("/user/profile"), false);
+        radix.insert(&ResourceDef::new("/{tail}*"), false);
+        radix.insert(&ResourceDef::prefix("/static"), false);
+
+        assert_eq!(radix.root.children.len(), 2);
+        assert_eq!(radix.root.values, vec![3]);
+
+        let check = |_, _: &Path<&str>| true;
+        let mut path = Path::new("/user/profile");
+        assert_eq!(radix.find_checked(&mut path, false, &check), Some(0));
+        assert_eq!(path.get("id").unwrap(), "profile");
+        let mut path = Path::new("/user/profile/edit");
+        assert_eq!(radix.find_checked(&mut path, false, &check), Some(1));
+        let mut path = Path::new("/static/css/main.css");
+        assert_eq!(radix.find_checked(&mut path, false, &check), Some(3));
+        let mut path = Path::new("/static/css/main.css");
+        let check = |idx, _: &Path<&str>| idx != 3;
+        assert_eq!(radix.find_checked(&mut path, false, &check), Some(4));
+        let mut path = Path::new("/user/profile");
+        assert_eq!(radix.find_checked(&mut path, false, &check), Some(0));
+
+        let check = |idx, _: &Path<&str>| idx == 2;
+        let mut path = Path::new("/user/profile");
+        assert_eq!(radix.find_checked(&mut path, false, &check), Some(2));
+        let mut path = Path::new("/user/Profile");
+        assert_eq!(radix.find_checked(&mut path, false, &check), None);
+
+        let mut radix = Radix::default();
+        radix.insert(&ResourceDef::new("/User/Profile"), true);
+        let mut path = Path::new("/user/PROFILE");
+        assert_eq!(radix.find_checked(&mut path, true, &|_, _| true), Some(0));
+    }
+}
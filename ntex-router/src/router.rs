@@ -1,3 +1,4 @@
+use super::radix::Radix;
 use super::tree::Tree;
 use super::{IntoPattern, Resource, ResourceDef, ResourcePath};
 
@@ -7,16 +8,23 @@ pub struct ResourceId(u16);
 /// Resource router.
 #[derive(Debug, Clone)]
 pub struct Router<T, U = ()> {
-    tree: Tree,
+    index: Index,
     resources: Vec<(ResourceDef, T, Option<U>)>,
     insensitive: bool,
 }
 
+#[derive(Debug, Clone)]
+enum Index {
+    Tree(Tree),
+    Radix(Radix),
+}
+
 impl<T, U> Router<T, U> {
     pub fn build() -> RouterBuilder<T, U> {
         RouterBuilder {
             resources: Vec::new(),
             insensitive: false,
+            radix: false,
         }
     }
 
@@ -25,11 +33,7 @@ impl<T, U> Router<T, U> {
         R: Resource<P>,
         P: ResourcePath,
     {
-        if let Some(idx) = if self.insensitive {
-            self.tree.find_insensitive(resource)
-        } else {
-            self.tree.find(resource)
-        } {
+        if let Some(idx) = self.find(resource, &|_, _| true) {
             let item = &self.resources[idx];
             Some((&item.1, ResourceId(item.0.id())))
         } else {
@@ -42,11 +46,7 @@ impl<T, U> Router<T, U> {
         R: Resource<P>,
         P: ResourcePath,
     {
-        if let Some(idx) = if self.insensitive {
-            self.tree.find_insensitive(resource)
-        } else {
-            self.tree.find(resource)
-        } {
+        if let Some(idx) = self.find(resource, &|_, _| true) {
             let item = &mut self.resources[idx];
             Some((&mut item.1, ResourceId(item.0.id())))
         } else {
@@ -64,17 +64,10 @@ impl<T, U> Router<T, U> {
         R: Resource<P>,
         P: ResourcePath,
     {
-        if let Some(idx) = if self.insensitive {
-            self.tree.find_checked_insensitive(resource, &|idx, res| {
-                let item = &self.resources[idx];
-                check(res, item.2.as_ref())
-            })
-        } else {
-            self.tree.find_checked(resource, &|idx, res| {
-                let item = &self.resources[idx];
-                check(res, item.2.as_ref())
-            })
-        } {
+        if let Some(idx) = self.find(resource, &|idx, res| {
+            let item = &self.resources[idx];
+            check(res, item.2.as_ref())
+        }) {
             let item = &self.resources[idx];
             Some((&item.1, ResourceId(item.0.id())))
         } else {
@@ -92,28 +85,42 @@ impl<T, U> Router<T, U> {
         R: Resource<P>,
         P: ResourcePath,
     {
-        if let Some(idx) = if self.insensitive {
-            self.tree.find_checked_insensitive(resource, &|idx, res| {
-                let item = &self.resources[idx];
-                check(res, item.2.as_ref())
-            })
-        } else {
-            self.tree.find_checked(resource, &|idx, res| {
-                let item = &self.resources[idx];
-                check(res, item.2.as_ref())
-            })
-        } {
+        if let Some(idx) = self.find(resource, &|idx, res| {
+            let item = &self.resources[idx];
+            check(res, item.2.as_ref())
+        }) {
             let item = &mut self.resources[idx];
             Some((&mut item.1, ResourceId(item.0.id())))
         } else {
             None
         }
     }
+
+    fn find<R, P, F>(&self, resource: &mut R, check: &F) -> Option<usize>
+    where
+        F: Fn(usize, &R) -> bool,
+        R: Resource<P>,
+        P: ResourcePath,
+    {
+        match self.index {
+            Index::Tree(ref tree) => {
+                if self.insensitive {
+                    tree.find_checked_insensitive(resource, check)
+                } else {
+                    tree.find_checked(resource, check)
+                }
+            }
+            Index::Radix(ref radix) => {
+                radix.find_checked(resource, self.insensitive, check)
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct RouterBuilder<T, U = ()> {
     insensitive: bool,
+    radix: bool,
     resources: Vec<(ResourceDef, T, Option<U>)>,
 }
 
@@ -126,6 +133,19 @@ impl<T, U> RouterBuilder<T, U> {
         self.insensitive = true;
     }
 
+    /// Use compressed radix tree for resource lookup.
+    ///
+    /// Resources are indexed by static segments at the start of the pattern,
+    /// only resources with matching static prefix are checked, in registration
+    /// order. Lookup cost does not depend on the number of static routes,
+    /// which makes difference for large route tables.
+    ///
+    /// Pattern syntax is the same as for default router. By default
+    /// segments tree is used.
+    pub fn radix(&mut self) {
+        self.radix = true;
+    }
+
     /// Register resource for specified path.
     pub fn path<P: IntoPattern>(
         &mut self,
@@ -160,18 +180,24 @@ impl<T, U> RouterBuilder<T, U> {
 
     /// Finish configuration and create router instance.
     pub fn finish(self) -> Router<T, U> {
-        let tree = if self.resources.is_empty() {
-            Tree::default()
+        let index = if self.radix {
+            let mut radix = Radix::default();
+            for r in &self.resources {
+                radix.insert(&r.0, self.insensitive);
+            }
+            Index::Radix(radix)
+        } else if self.resources.is_empty() {
+            Index::Tree(Tree::default())
         } else {
             let mut tree = Tree::new(&self.resources[0].0, 0);
             for (idx, r) in self.resources[1..].iter().enumerate() {
                 tree.insert(&r.0, idx + 1)
             }
-            tree
+            Index::Tree(tree)
         };
 
         Router {
-            tree,
+            index,
             resources: self.resources,
             insensitive: self.insensitive,
         }
@@ -303,6 +329,72 @@ mod tests {
         assert!(router.recognize_mut(&mut path).is_none());
     }
 
+    #[test]
+    fn test_recognizer_radix() {
+        let patterns = [
+            "/name",
+            "/name/{val}",
+            "/name/{val}/index.html",
+            "/file/{file}.{ext}",
+            "/v{val}/{val2}/index.html",
+            "/v/{tail}*",
+            "/test2/{test}.html",
+            "/{test}/index.html",
+            "/v2/{custom:.*}/test.html",
+        ];
+        let mut tree = Router::<usize>::build();
+        let mut radix = Router::<usize>::build();
+        radix.radix();
+        for (idx, pattern) in patterns.iter().enumerate() {
+            tree.path(*pattern, idx);
+            radix.path(*pattern, idx);
+        }
+        let (tree, radix) = (tree.finish(), radix.finish());
+
+        for p in [
+            "/unknown",
+            "/name",
+            "/name/",
+            "/name/value",
+            "/name/value2/index.html",
+            "/file/file.gz",
+            "/vtest/ttt/index.html",
+            "/v/blah-blah/index.html",
+            "/test2/index.html",
+            "/bbb/index.html",
+            "/v2/blah-blah/test.html",
+        ] {
+            let mut p1 = Path::new(p);
+            let mut p2 = Path::new(p);
+            assert_eq!(
+                tree.recognize(&mut p1).map(|(h, _)| *h),
+                radix.recognize(&mut p2).map(|(h, _)| *h)
+            );
+            assert_eq!(p1.iter().collect::<Vec<_>>(), p2.iter().collect::<Vec<_>>());
+        }
+
+        let mut p = Path::new("/test/name/value");
+        p.skip(5);
+        assert_eq!(*radix.recognize(&mut p).unwrap().0, 1);
+        assert_eq!(&p["val"], "value");
+
+        let mut router = Router::<usize, usize>::build();
+        router.radix();
+        router.case_insensitive();
+        router.path("/name", 10).2 = Some(0);
+        router.path("/name", 11).2 = Some(1);
+        let router = router.finish();
+
+        let mut p = Path::new("/Name");
+        assert_eq!(
+            *router
+                .recognize_checked(&mut p, |_, v| v == Some(&1))
+                .unwrap()
+                .0,
+            11
+        );
+    }
+
     #[test]
     fn test_recognizer_with_path_skip() {
         let mut router = Router::<usize>::build();
@@ -145,6 +145,7 @@ impl Tree {
         }
     }
 
+    #[cfg(test)]
     pub(crate) fn find<T, R>(&self, resource: &mut R) -> Option<usize>
     where
         T: ResourcePath,
@@ -153,14 +154,6 @@ impl Tree {
         self.find_checked_inner(resource, false, &|_, _| true)
     }
 
-    pub(crate) fn find_insensitive<T, R>(&self, resource: &mut R) -> Option<usize>
-    where
-        T: ResourcePath,
-        R: Resource<T>,
-    {
-        self.find_checked_inner(resource, true, &|_, _| true)
-    }
-
     pub(crate) fn find_checked<T, R, F>(&self, resource: &mut R, check: &F) -> Option<usize>
     where
         T: ResourcePath,
@@ -314,6 +307,7 @@ impl Tree {
                 return Some(val);
             }
         }
+        resource.resource_path().segments = segments;
         None
     }
 
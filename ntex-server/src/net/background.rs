@@ -0,0 +1,175 @@
+use std::task::{Context, Poll};
+use std::{cell::Cell, fmt, future::poll_fn, future::Future, marker::PhantomData, rc::Rc};
+
+use ntex_util::sync::CancellationToken;
+use ntex_util::{channel::condition::Condition, future::BoxFuture, task::LocalWaker};
+
+/// Shutdown signal for background tasks
+///
+/// Signal is triggered when server starts graceful shutdown.
+#[derive(Clone)]
+pub struct Shutdown {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    cond: Condition,
+    token: CancellationToken,
+    stopped: Cell<bool>,
+    running: Cell<usize>,
+    done: LocalWaker,
+}
+
+impl Shutdown {
+    fn new() -> Self {
+        Shutdown {
+            inner: Rc::new(Inner {
+                cond: Condition::new(),
+                token: CancellationToken::new(),
+                stopped: Cell::new(false),
+                running: Cell::new(0),
+                done: LocalWaker::new(),
+            }),
+        }
+    }
+
+    /// Check if shutdown signal is triggered
+    pub fn is_stopped(&self) -> bool {
+        self.inner.stopped.get()
+    }
+
+    /// Wait for shutdown signal
+    pub async fn stopped(&self) {
+        if !self.is_stopped() {
+            self.inner.cond.wait().await
+        }
+    }
+
+    /// Get cancellation token for shutdown signal
+    ///
+    /// Token is cancelled when server starts graceful shutdown.
+    pub fn token(&self) -> CancellationToken {
+        self.inner.token.child_token()
+    }
+
+    fn stop(&self) {
+        if !self.inner.stopped.replace(true) {
+            self.inner.cond.notify_and_lock_readiness();
+            self.inner.token.cancel();
+        }
+    }
+}
+
+impl fmt::Debug for Shutdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shutdown")
+            .field("stopped", &self.inner.stopped.get())
+            .finish()
+    }
+}
+
+/// Set of running background tasks
+pub(crate) struct Background(Shutdown);
+
+impl Background {
+    /// Spawn background tasks in current thread
+    pub(crate) fn start(tasks: &[Box<dyn BackgroundTask + Send>]) -> Self {
+        let shutdown = Shutdown::new();
+        for task in tasks {
+            let fut = task.run(shutdown.clone());
+            let guard = RunningGuard::new(&shutdown.inner);
+            let _ = ntex_rt::spawn(async move {
+                fut.await;
+                drop(guard);
+            });
+        }
+        Background(shutdown)
+    }
+
+    /// Trigger shutdown signal
+    pub(crate) fn stop(&self) {
+        self.0.stop()
+    }
+
+    /// Trigger shutdown signal and wait for completion of all tasks
+    pub(crate) async fn shutdown(&self) {
+        self.stop();
+        poll_fn(|cx| self.poll_done(cx)).await
+    }
+
+    fn poll_done(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.0.inner.done.register(cx.waker());
+        if self.0.inner.running.get() == 0 {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        self.stop()
+    }
+}
+
+struct RunningGuard(Rc<Inner>);
+
+impl RunningGuard {
+    fn new(inner: &Rc<Inner>) -> Self {
+        inner.running.set(inner.running.get() + 1);
+        RunningGuard(inner.clone())
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        let running = self.0.running.get() - 1;
+        self.0.running.set(running);
+        if running == 0 {
+            self.0.done.wake();
+        }
+    }
+}
+
+pub(crate) trait BackgroundTask {
+    fn clone_task(&self) -> Box<dyn BackgroundTask + Send>;
+
+    fn run(&self, shutdown: Shutdown) -> BoxFuture<'static, ()>;
+}
+
+pub(super) struct BackgroundTaskWrapper<F, R> {
+    f: F,
+    _t: PhantomData<R>,
+}
+
+// SAFETY: Send cannot be provided authomatically because of R param
+// but R always get executed in one thread and never leave it
+unsafe impl<F, R> Send for BackgroundTaskWrapper<F, R> where F: Send {}
+
+impl<F, R> BackgroundTaskWrapper<F, R>
+where
+    F: Fn(Shutdown) -> R + Send + Clone + 'static,
+    R: Future<Output = ()> + 'static,
+{
+    pub(super) fn create(f: F) -> Box<dyn BackgroundTask + Send> {
+        Box::new(Self { f, _t: PhantomData })
+    }
+}
+
+impl<F, R> BackgroundTask for BackgroundTaskWrapper<F, R>
+where
+    F: Fn(Shutdown) -> R + Send + Clone + 'static,
+    R: Future<Output = ()> + 'static,
+{
+    fn clone_task(&self) -> Box<dyn BackgroundTask + Send> {
+        Box::new(Self {
+            f: self.f.clone(),
+            _t: PhantomData,
+        })
+    }
+
+    fn run(&self, shutdown: Shutdown) -> BoxFuture<'static, ()> {
+        Box::pin((self.f)(shutdown))
+    }
+}
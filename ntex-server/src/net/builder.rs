@@ -9,8 +9,10 @@ use ntex_util::time::Millis;
 use crate::{Server, WorkerPool};
 
 use super::accept::AcceptLoop;
+use super::background::{Background, BackgroundTask, BackgroundTaskWrapper, Shutdown};
 use super::config::{Config, ServiceConfig};
 use super::factory::{self, FactoryServiceType, OnWorkerStart, OnWorkerStartWrapper};
+use super::pressure::{Pressure, PressureStats};
 use super::{socket::Listener, Connection, ServerStatus, StreamServer, Token};
 
 /// Server builder
@@ -20,6 +22,8 @@ pub struct ServerBuilder {
     services: Vec<FactoryServiceType>,
     sockets: Vec<(Token, String, Listener)>,
     on_worker_start: Vec<Box<dyn OnWorkerStart + Send>>,
+    background: Vec<Box<dyn BackgroundTask + Send>>,
+    background_global: Vec<Box<dyn BackgroundTask + Send>>,
     accept: AcceptLoop,
     pool: WorkerPool,
 }
@@ -38,6 +42,8 @@ impl ServerBuilder {
             services: Vec::new(),
             sockets: Vec::new(),
             on_worker_start: Vec::new(),
+            background: Vec::new(),
+            background_global: Vec::new(),
             accept: AcceptLoop::default(),
             backlog: 2048,
             pool: WorkerPool::new(),
@@ -175,6 +181,67 @@ impl ServerBuilder {
         self
     }
 
+    /// Register background task.
+    ///
+    /// Task is started in each worker after worker services are constructed.
+    /// Task receives `Shutdown` signal, signal is triggered when worker starts
+    /// graceful shutdown. Worker waits for completion of background tasks
+    /// during graceful shutdown, up to shutdown timeout.
+    pub fn background<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(Shutdown) -> R + Send + Clone + 'static,
+        R: Future<Output = ()> + 'static,
+    {
+        self.background.push(BackgroundTaskWrapper::create(f));
+        self
+    }
+
+    /// Register worker pressure detector.
+    ///
+    /// Detector get called in each worker every `interval` with
+    /// worker's load statistics and returns worker's pressure level.
+    /// Under pressure worker stops accepting new connections, could shed
+    /// new requests and close idle keep-alive connections, see [`Pressure`].
+    ///
+    /// ```rust
+    /// use ntex::server::{build, Pressure};
+    /// use ntex::time::Millis;
+    ///
+    /// let builder = build().pressure(Millis(250), |stats| {
+    ///     let allocated: usize = stats.pools.iter().map(|p| p.allocated).sum();
+    ///     if allocated > 512 * 1024 * 1024 {
+    ///         Pressure::CloseIdle
+    ///     } else if stats.in_flight > 10_000 {
+    ///         Pressure::Shed
+    ///     } else {
+    ///         Pressure::Normal
+    ///     }
+    /// });
+    /// ```
+    pub fn pressure<F>(self, interval: Millis, detector: F) -> Self
+    where
+        F: Fn(&PressureStats) -> Pressure + Send + Clone + 'static,
+    {
+        self.background(move |shutdown| {
+            super::pressure::run(interval, detector.clone(), shutdown)
+        })
+    }
+
+    /// Register global background task.
+    ///
+    /// Task is started once, in the thread that runs the server.
+    /// Server waits for completion of global background tasks before
+    /// it stops workers.
+    pub fn background_global<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(Shutdown) -> R + Send + Clone + 'static,
+        R: Future<Output = ()> + 'static,
+    {
+        self.background_global
+            .push(BackgroundTaskWrapper::create(f));
+        self
+    }
+
     /// Add new service to the server.
     pub fn bind<F, U, N, R>(mut self, name: N, addr: U, factory: F) -> io::Result<Self>
     where
@@ -301,10 +368,28 @@ impl ServerBuilder {
         if self.sockets.is_empty() {
             panic!("Server should have at least one bound socket");
         } else {
+            let global = if self.background_global.is_empty() {
+                None
+            } else {
+                let (tx, rx) = oneshot::channel::<oneshot::Sender<()>>();
+                let tasks = self.background_global;
+                let _ = ntex_rt::spawn(async move {
+                    let background = Background::start(&tasks);
+                    let completed = rx.await.ok();
+                    background.shutdown().await;
+                    if let Some(tx) = completed {
+                        let _ = tx.send(());
+                    }
+                });
+                Some(tx)
+            };
+
             let srv = StreamServer::new(
                 self.accept.notify(),
                 self.services,
                 self.on_worker_start,
+                self.background,
+                global,
             );
             let svc = self.pool.run(srv);
 
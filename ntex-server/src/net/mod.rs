@@ -2,17 +2,21 @@
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 mod accept;
+mod background;
 mod builder;
 mod config;
 mod counter;
 mod factory;
+pub mod pressure;
 mod service;
 mod socket;
 mod test;
 
 pub use self::accept::{AcceptLoop, AcceptNotify, AcceptorCommand};
+pub use self::background::Shutdown;
 pub use self::builder::{bind_addr, create_tcp_listener, ServerBuilder};
 pub use self::config::{Config, ServiceConfig, ServiceRuntime};
+pub use self::pressure::{Pressure, PressureStats};
 pub use self::service::{ServerMessage, StreamServer};
 pub use self::socket::{Connection, Stream};
 pub use self::test::{build_test_server, test_server, TestServer};
@@ -0,0 +1,205 @@
+//! Worker pressure detection
+use std::{cell::Cell, cell::RefCell, task::Context};
+
+use ntex_bytes::{PoolId, PoolStats};
+use ntex_util::future::{select, Either};
+use ntex_util::time::{sleep, Millis};
+use ntex_util::{task::LocalWaker, HashMap};
+
+use super::background::Shutdown;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Worker pressure level
+///
+/// Each level includes actions of the previous levels.
+pub enum Pressure {
+    #[default]
+    /// Worker operates normally
+    Normal,
+    /// Worker stops accepting new connections
+    Pause,
+    /// New requests get *503 Service Unavailable* response
+    Shed,
+    /// Idle keep-alive connections get closed
+    CloseIdle,
+}
+
+#[derive(Clone, Debug)]
+/// Worker load statistics
+pub struct PressureStats {
+    /// Number of open connections
+    pub connections: usize,
+    /// Number of requests in processing
+    pub in_flight: usize,
+    /// Number of idle keep-alive connections
+    pub idle: usize,
+    /// Memory pools statistics
+    pub pools: Vec<PoolStats>,
+}
+
+thread_local! {
+    static STATE: State = State {
+        level: Cell::new(Pressure::Normal),
+        in_flight: Cell::new(0),
+        idle_id: Cell::new(0),
+        idle: RefCell::new(HashMap::default()),
+        waker: LocalWaker::new(),
+    };
+}
+
+struct State {
+    level: Cell<Pressure>,
+    in_flight: Cell<usize>,
+    idle_id: Cell<usize>,
+    idle: RefCell<HashMap<usize, Box<dyn FnOnce()>>>,
+    waker: LocalWaker,
+}
+
+/// Current worker's pressure level
+pub fn pressure() -> Pressure {
+    STATE.with(|st| st.level.get())
+}
+
+/// Set worker's pressure level
+///
+/// Usually pressure level is set by pressure detector,
+/// see `ServerBuilder::pressure()`.
+pub fn set_pressure(level: Pressure) {
+    STATE.with(|st| {
+        let prev = st.level.replace(level);
+        if prev != level {
+            log::info!("Worker pressure level is changed to {:?}", level);
+        }
+        if level < Pressure::Pause {
+            st.waker.wake();
+        }
+        if level >= Pressure::CloseIdle {
+            let idle: Vec<_> = st.idle.borrow_mut().drain().collect();
+            for (_, close) in idle {
+                close();
+            }
+        }
+    })
+}
+
+/// Mark request as in processing
+///
+/// Request is in processing until guard is dropped.
+pub fn in_flight() -> InFlight {
+    STATE.with(|st| st.in_flight.set(st.in_flight.get() + 1));
+    InFlight(())
+}
+
+/// Register idle keep-alive connection
+///
+/// `close` callback is called if worker pressure reaches
+/// `Pressure::CloseIdle` level while guard is alive.
+pub fn idle<F>(close: F) -> Idle
+where
+    F: FnOnce() + 'static,
+{
+    STATE.with(|st| {
+        let id = st.idle_id.get().wrapping_add(1);
+        st.idle_id.set(id);
+        if st.level.get() >= Pressure::CloseIdle {
+            close();
+        } else {
+            st.idle.borrow_mut().insert(id, Box::new(close));
+        }
+        Idle(id)
+    })
+}
+
+#[derive(Debug)]
+/// In-flight request guard
+pub struct InFlight(());
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        STATE.with(|st| st.in_flight.set(st.in_flight.get() - 1));
+    }
+}
+
+#[derive(Debug)]
+/// Idle connection guard
+pub struct Idle(usize);
+
+impl Drop for Idle {
+    fn drop(&mut self) {
+        STATE.with(|st| {
+            st.idle.borrow_mut().remove(&self.0);
+        });
+    }
+}
+
+impl PressureStats {
+    /// Collect current worker's stats
+    pub fn current() -> Self {
+        STATE.with(|st| PressureStats {
+            connections: super::num_connections(),
+            in_flight: st.in_flight.get(),
+            idle: st.idle.borrow().len(),
+            pools: PoolId::stats_all(),
+        })
+    }
+}
+
+/// Check if worker could accept new connections
+pub(super) fn poll_accept(cx: &mut Context<'_>) -> bool {
+    STATE.with(|st| {
+        if st.level.get() >= Pressure::Pause {
+            st.waker.register(cx.waker());
+            false
+        } else {
+            true
+        }
+    })
+}
+
+/// Run pressure detector until shutdown
+pub(super) async fn run<F>(interval: Millis, detector: F, shutdown: Shutdown)
+where
+    F: Fn(&PressureStats) -> Pressure,
+{
+    loop {
+        if let Either::Right(_) = select(sleep(interval), shutdown.stopped()).await {
+            break;
+        }
+        set_pressure(detector(&PressureStats::current()));
+    }
+    set_pressure(Pressure::Normal);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    #[test]
+    fn pressure_levels() {
+        let closed = Rc::new(Cell::new(false));
+        let closed2 = closed.clone();
+
+        let req = in_flight();
+        let guard = idle(move || closed2.set(true));
+        let stats = PressureStats::current();
+        assert_eq!(stats.in_flight, 1);
+        assert_eq!(stats.idle, 1);
+        drop(req);
+        assert_eq!(PressureStats::current().in_flight, 0);
+
+        set_pressure(Pressure::Shed);
+        assert_eq!(pressure(), Pressure::Shed);
+        assert!(!closed.get());
+        assert!(Pressure::Shed > Pressure::Pause);
+
+        set_pressure(Pressure::CloseIdle);
+        assert!(closed.get());
+        assert_eq!(PressureStats::current().idle, 0);
+        drop(guard);
+
+        set_pressure(Pressure::Normal);
+        assert_eq!(pressure(), Pressure::Normal);
+    }
+}
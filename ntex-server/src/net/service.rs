@@ -1,4 +1,4 @@
-use std::{task::Context, task::Poll};
+use std::{sync::Arc, sync::Mutex, task::Context, task::Poll};
 
 use ntex_bytes::{Pool, PoolRef};
 use ntex_net::Io;
@@ -8,6 +8,7 @@ use ntex_util::HashMap;
 use crate::{ServerConfiguration, WorkerMessage};
 
 use super::accept::{AcceptNotify, AcceptorCommand};
+use super::background::{Background, BackgroundTask};
 use super::counter::Counter;
 use super::factory::{FactoryServiceType, NetService, OnWorkerStart};
 use super::{socket::Connection, Token, MAX_CONNS_COUNTER};
@@ -16,10 +17,14 @@ pub type ServerMessage = WorkerMessage<Connection>;
 
 pub(super) type BoxService = boxed::BoxService<Io, (), ()>;
 
+type GlobalStop = Arc<Mutex<Option<oneshot::Sender<oneshot::Sender<()>>>>>;
+
 pub struct StreamServer {
     notify: AcceptNotify,
     services: Vec<FactoryServiceType>,
     on_worker_start: Vec<Box<dyn OnWorkerStart + Send>>,
+    background: Vec<Box<dyn BackgroundTask + Send>>,
+    global: GlobalStop,
 }
 
 impl StreamServer {
@@ -27,11 +32,15 @@ impl StreamServer {
         notify: AcceptNotify,
         services: Vec<FactoryServiceType>,
         on_worker_start: Vec<Box<dyn OnWorkerStart + Send>>,
+        background: Vec<Box<dyn BackgroundTask + Send>>,
+        global: Option<oneshot::Sender<oneshot::Sender<()>>>,
     ) -> Self {
         Self {
             notify,
             services,
             on_worker_start,
+            background,
+            global: Arc::new(Mutex::new(global)),
         }
     }
 }
@@ -54,7 +63,10 @@ impl ServerConfiguration for StreamServer {
             services.extend(svc.create().await?);
         }
 
-        Ok(StreamService { services })
+        Ok(StreamService {
+            services,
+            background: self.background.iter().map(|t| t.clone_task()).collect(),
+        })
     }
 
     /// Server is paused
@@ -70,6 +82,8 @@ impl ServerConfiguration for StreamServer {
     /// Server is stopped
     fn terminate(&self) {
         self.notify.send(AcceptorCommand::Terminate);
+        // dropped sender stops global background tasks
+        self.global.lock().unwrap().take();
     }
 
     /// Server is stopped
@@ -77,6 +91,15 @@ impl ServerConfiguration for StreamServer {
         let (tx, rx) = oneshot::channel();
         self.notify.send(AcceptorCommand::Stop(tx));
         let _ = rx.await;
+
+        // stop global background tasks
+        let global = self.global.lock().unwrap().take();
+        if let Some(global) = global {
+            let (tx, rx) = oneshot::channel();
+            if global.send(tx).is_ok() {
+                let _ = rx.await;
+            }
+        }
     }
 }
 
@@ -86,12 +109,15 @@ impl Clone for StreamServer {
             notify: self.notify.clone(),
             services: self.services.iter().map(|s| s.clone_factory()).collect(),
             on_worker_start: self.on_worker_start.iter().map(|f| f.clone_fn()).collect(),
+            background: self.background.iter().map(|t| t.clone_task()).collect(),
+            global: self.global.clone(),
         }
     }
 }
 
 pub struct StreamService {
     services: Vec<NetService>,
+    background: Vec<Box<dyn BackgroundTask + Send>>,
 }
 
 impl ServiceFactory<ServerMessage> for StreamService {
@@ -129,6 +155,7 @@ impl ServiceFactory<ServerMessage> for StreamService {
             tokens,
             services,
             conns,
+            background: Background::start(&self.background),
         })
     }
 }
@@ -137,6 +164,7 @@ pub struct StreamServiceImpl {
     tokens: HashMap<Token, (usize, &'static str, Pool, PoolRef)>,
     services: Vec<BoxService>,
     conns: Counter,
+    background: Background,
 }
 
 impl Service<ServerMessage> for StreamServiceImpl {
@@ -145,6 +173,7 @@ impl Service<ServerMessage> for StreamServiceImpl {
 
     fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         let mut ready = self.conns.available(cx);
+        ready = super::pressure::poll_accept(cx) && ready;
         for (idx, svc) in self.services.iter().enumerate() {
             match svc.poll_ready(cx) {
                 Poll::Pending => ready = false,
@@ -174,6 +203,8 @@ impl Service<ServerMessage> for StreamServiceImpl {
     }
 
     fn poll_shutdown(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.background.stop();
+
         let mut ready = true;
         for svc in &self.services {
             match svc.poll_shutdown(cx) {
@@ -211,7 +242,15 @@ impl Service<ServerMessage> for StreamServiceImpl {
                     Err(())
                 }
             }
-            _ => Ok(()),
+            ServerMessage::Shutdown(_) => {
+                // graceful shutdown is limited by worker's shutdown timeout
+                self.background.shutdown().await;
+                Ok(())
+            }
+            _ => {
+                self.background.stop();
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,336 @@
+use std::hash::{Hash, Hasher};
+use std::{cell::Cell, collections::hash_map::DefaultHasher, fmt, marker, rc::Rc};
+use std::{task::Context, task::Poll};
+
+use crate::{Service, ServiceCtx};
+
+type KeyFn<R> = Rc<dyn Fn(&R) -> u64>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Strategy {
+    RoundRobin,
+    LeastLoaded,
+    ConsistentHash,
+}
+
+struct Member<S> {
+    service: S,
+    ready: Cell<bool>,
+    pending: Cell<usize>,
+}
+
+/// Load balancing service.
+///
+/// Balancer distributes calls over multiple inner services. By default
+/// round-robin strategy is used. Members that are not ready are ejected
+/// from balancing until next readiness check. Balancer is ready if any
+/// of members is ready, it fails only if all members fail.
+pub struct Balancer<S, R> {
+    members: Vec<Member<S>>,
+    strategy: Strategy,
+    next: Cell<usize>,
+    key: Option<KeyFn<R>>,
+    _t: marker::PhantomData<fn(R)>,
+}
+
+impl<S, R> Balancer<S, R> {
+    /// Create round-robin balancer over services.
+    ///
+    /// Panics if services list is empty.
+    pub fn new<I>(services: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+    {
+        let members: Vec<_> = services
+            .into_iter()
+            .map(|service| Member {
+                service,
+                ready: Cell::new(true),
+                pending: Cell::new(0),
+            })
+            .collect();
+        assert!(
+            !members.is_empty(),
+            "Balancer requires at least one service"
+        );
+
+        Self {
+            members,
+            strategy: Strategy::RoundRobin,
+            next: Cell::new(0),
+            key: None,
+            _t: marker::PhantomData,
+        }
+    }
+
+    /// Use least-loaded strategy.
+    ///
+    /// Call is passed to ready service with minimal number of pending calls.
+    pub fn least_loaded(mut self) -> Self {
+        self.strategy = Strategy::LeastLoaded;
+        self
+    }
+
+    /// Use consistent hashing strategy.
+    ///
+    /// Calls with the same key are passed to the same service while it is ready,
+    /// ejection of a service affects only keys mapped to that service.
+    pub fn consistent_hash<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&R) -> u64 + 'static,
+    {
+        self.strategy = Strategy::ConsistentHash;
+        self.key = Some(Rc::new(f));
+        self
+    }
+
+    /// Returns number of services.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns `true` if balancer has no services.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Returns number of services that were ready on last readiness check.
+    pub fn ready_len(&self) -> usize {
+        self.members.iter().filter(|m| m.ready.get()).count()
+    }
+
+    /// Returns number of pending calls for each service.
+    pub fn pending(&self) -> impl Iterator<Item = usize> + '_ {
+        self.members.iter().map(|m| m.pending.get())
+    }
+
+    fn select(&self, req: &R) -> usize {
+        let len = self.members.len();
+        let is_ready = |idx: usize| self.members[idx].ready.get();
+        let any_ready = (0..len).any(is_ready);
+        let candidate = |idx: usize| !any_ready || is_ready(idx);
+
+        match self.strategy {
+            Strategy::RoundRobin => {
+                let start = self.next.get();
+                let idx = (0..len)
+                    .map(|n| (start + n) % len)
+                    .find(|idx| candidate(*idx))
+                    .unwrap_or(start % len);
+                self.next.set((idx + 1) % len);
+                idx
+            }
+            Strategy::LeastLoaded => {
+                let start = self.next.get();
+                let idx = (0..len)
+                    .map(|n| (start + n) % len)
+                    .filter(|idx| candidate(*idx))
+                    .min_by_key(|idx| self.members[*idx].pending.get())
+                    .unwrap_or(start % len);
+                self.next.set((idx + 1) % len);
+                idx
+            }
+            Strategy::ConsistentHash => {
+                // rendezvous hashing
+                let key = (self.key.as_ref().unwrap())(req);
+                (0..len)
+                    .filter(|idx| candidate(*idx))
+                    .max_by_key(|idx| {
+                        let mut hasher = DefaultHasher::new();
+                        (key, *idx).hash(&mut hasher);
+                        hasher.finish()
+                    })
+                    .unwrap_or(0)
+            }
+        }
+    }
+}
+
+impl<S, R> fmt::Debug for Balancer<S, R>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Balancer")
+            .field(
+                "services",
+                &self.members.iter().map(|m| &m.service).collect::<Vec<_>>(),
+            )
+            .field("strategy", &self.strategy)
+            .finish()
+    }
+}
+
+struct PendingGuard<'a>(&'a Cell<usize>);
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+impl<S, R> Service<R> for Balancer<S, R>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let mut ready = false;
+        let mut failed = 0;
+        let mut error = None;
+
+        for member in &self.members {
+            match member.service.poll_ready(cx) {
+                Poll::Ready(Ok(())) => {
+                    member.ready.set(true);
+                    ready = true;
+                }
+                Poll::Ready(Err(err)) => {
+                    member.ready.set(false);
+                    failed += 1;
+                    error = Some(err);
+                }
+                Poll::Pending => member.ready.set(false),
+            }
+        }
+
+        if ready {
+            Poll::Ready(Ok(()))
+        } else if failed == self.members.len() {
+            Poll::Ready(Err(error.unwrap()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn poll_shutdown(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut ready = true;
+        for member in &self.members {
+            ready &= member.service.poll_shutdown(cx).is_ready();
+        }
+        if ready {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    async fn call(
+        &self,
+        req: R,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let member = &self.members[self.select(&req)];
+        member.pending.set(member.pending.get() + 1);
+        let _guard = PendingGuard(&member.pending);
+
+        ctx.call(&member.service, req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, task::Poll};
+
+    use ntex_util::future::lazy;
+
+    use super::*;
+    use crate::Pipeline;
+
+    #[derive(Clone, Debug)]
+    struct Srv(usize, Rc<Cell<bool>>);
+
+    impl Service<usize> for Srv {
+        type Response = usize;
+        type Error = usize;
+
+        fn poll_ready(&self, _: &mut Context<'_>) -> Poll<Result<(), usize>> {
+            if self.1.get() {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Ready(Err(self.0))
+            }
+        }
+
+        async fn call(&self, _: usize, _: ServiceCtx<'_, Self>) -> Result<usize, usize> {
+            Ok(self.0)
+        }
+    }
+
+    fn services() -> (Vec<Srv>, Vec<Rc<Cell<bool>>>) {
+        let ready: Vec<_> = (0..3).map(|_| Rc::new(Cell::new(true))).collect();
+        let srvs = ready
+            .iter()
+            .enumerate()
+            .map(|(idx, r)| Srv(idx, r.clone()))
+            .collect();
+        (srvs, ready)
+    }
+
+    #[ntex::test]
+    async fn test_round_robin() {
+        let (srvs, ready) = services();
+        let srv = Pipeline::new(Balancer::<_, usize>::new(srvs));
+        assert_eq!(srv.get_ref().len(), 3);
+        assert!(!srv.get_ref().is_empty());
+        assert!(format!("{:?}", srv).contains("Balancer"));
+
+        assert_eq!(srv.call(0).await, Ok(0));
+        assert_eq!(srv.call(0).await, Ok(1));
+        assert_eq!(srv.call(0).await, Ok(2));
+        assert_eq!(srv.call(0).await, Ok(0));
+
+        // failed service is ejected
+        ready[1].set(false);
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+        assert_eq!(srv.get_ref().ready_len(), 2);
+        assert_eq!(srv.call(0).await, Ok(2));
+        assert_eq!(srv.call(0).await, Ok(0));
+        assert_eq!(srv.call(0).await, Ok(2));
+
+        // all services failed
+        ready[0].set(false);
+        ready[2].set(false);
+        assert!(lazy(|cx| srv.poll_ready(cx)).await.is_ready());
+        assert!(srv.call(0).await.is_err());
+        assert!(lazy(|cx| srv.poll_shutdown(cx)).await.is_ready());
+    }
+
+    #[ntex::test]
+    async fn test_least_loaded() {
+        let (srvs, _) = services();
+        let srv = Pipeline::new(Balancer::<_, usize>::new(srvs).least_loaded());
+
+        assert_eq!(srv.call(0).await, Ok(0));
+        assert_eq!(srv.call(0).await, Ok(1));
+        assert!(srv.get_ref().pending().all(|p| p == 0));
+    }
+
+    #[ntex::test]
+    async fn test_consistent_hash() {
+        let (srvs, ready) = services();
+        let srv =
+            Pipeline::new(Balancer::new(srvs).consistent_hash(|req: &usize| *req as u64));
+
+        let mut mapping = Vec::new();
+        for key in 0..20 {
+            let idx = srv.call(key).await.unwrap();
+            assert_eq!(srv.call(key).await, Ok(idx));
+            mapping.push(idx);
+        }
+
+        // only keys of ejected service are remapped
+        ready[1].set(false);
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+        for (key, idx) in mapping.into_iter().enumerate() {
+            let new_idx = srv.call(key).await.unwrap();
+            if idx == 1 {
+                assert_ne!(new_idx, 1);
+            } else {
+                assert_eq!(new_idx, idx);
+            }
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::and_then::{AndThen, AndThenFactory};
 use crate::apply::{Apply, ApplyFactory};
 use crate::ctx::ServiceCtx;
 use crate::map::{Map, MapFactory};
+use crate::map_async::{MapRequest, MapRequestFactory, MapResponse, MapResponseFactory};
 use crate::map_err::{MapErr, MapErrFactory};
 use crate::map_init_err::MapInitErr;
 use crate::middleware::{ApplyMiddleware, Middleware};
@@ -100,6 +101,40 @@ impl<Svc: Service<Req>, Req> ServiceChain<Svc, Req> {
         }
     }
 
+    /// Asynchronously map request before passing it to this service.
+    ///
+    /// Mapping function could fail, in that case error is returned and
+    /// service is not called.
+    pub fn map_request<F, Fut, R>(self, f: F) -> ServiceChain<MapRequest<Svc, F, R, Req>, R>
+    where
+        Self: Sized,
+        F: Fn(R) -> Fut,
+        Fut: Future<Output = Result<Req, Svc::Error>>,
+    {
+        ServiceChain {
+            service: MapRequest::new(self.service, f),
+            _t: PhantomData,
+        }
+    }
+
+    /// Asynchronously map this service's response, returning a new service.
+    ///
+    /// Async version of `map`, mapping function could fail.
+    pub fn map_response<F, Fut, Res>(
+        self,
+        f: F,
+    ) -> ServiceChain<MapResponse<Svc, F, Req, Res>, Req>
+    where
+        Self: Sized,
+        F: Fn(Svc::Response) -> Fut,
+        Fut: Future<Output = Result<Res, Svc::Error>>,
+    {
+        ServiceChain {
+            service: MapResponse::new(self.service, f),
+            _t: PhantomData,
+        }
+    }
+
     /// Map this service's error to a different error, returning a new service.
     ///
     /// This function is similar to the `Result::map_err` where it will change
@@ -279,6 +314,38 @@ impl<T: ServiceFactory<Req, C>, Req, C> ServiceChainFactory<T, Req, C> {
         }
     }
 
+    /// Asynchronously map request before passing it to created services.
+    pub fn map_request<F, Fut, R>(
+        self,
+        f: F,
+    ) -> ServiceChainFactory<MapRequestFactory<T, F, R, Req, C>, R, C>
+    where
+        Self: Sized,
+        F: Fn(R) -> Fut + Clone,
+        Fut: Future<Output = Result<Req, T::Error>>,
+    {
+        ServiceChainFactory {
+            factory: MapRequestFactory::new(self.factory, f),
+            _t: PhantomData,
+        }
+    }
+
+    /// Asynchronously map created services' responses.
+    pub fn map_response<F, Fut, Res>(
+        self,
+        f: F,
+    ) -> ServiceChainFactory<MapResponseFactory<T, F, Req, Res, C>, Req, C>
+    where
+        Self: Sized,
+        F: Fn(T::Response) -> Fut + Clone,
+        Fut: Future<Output = Result<Res, T::Error>>,
+    {
+        ServiceChainFactory {
+            factory: MapResponseFactory::new(self.factory, f),
+            _t: PhantomData,
+        }
+    }
+
     /// Map this service's error to a different error.
     pub fn map_err<F, E>(
         self,
@@ -0,0 +1,555 @@
+use std::{cell::Cell, cell::RefCell, collections::VecDeque, fmt, rc::Rc};
+use std::{time::Duration, time::Instant};
+
+use crate::{Middleware, Service, ServiceCtx};
+
+/// Circuit breaker state
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are passed to the inner service
+    Closed,
+    /// Requests are rejected without calling the inner service
+    Open,
+    /// Limited number of probe requests are passed to the inner service
+    HalfOpen,
+}
+
+/// Circuit breaker error
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CircuitBreakerError<E> {
+    /// Circuit is open, request is rejected
+    Open,
+    /// Inner service error
+    Service(E),
+}
+
+impl<E> CircuitBreakerError<E> {
+    /// Returns inner service error, if any
+    pub fn into_service_error(self) -> Option<E> {
+        match self {
+            CircuitBreakerError::Open => None,
+            CircuitBreakerError::Service(err) => Some(err),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for CircuitBreakerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitBreakerError::Open => write!(f, "Circuit is open"),
+            CircuitBreakerError::Service(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<E: fmt::Display + fmt::Debug> std::error::Error for CircuitBreakerError<E> {}
+
+#[derive(Clone)]
+struct Config {
+    failure_rate: f64,
+    window: usize,
+    min_calls: usize,
+    open_timeout: Duration,
+    probes: usize,
+    on_change: Option<Rc<dyn Fn(CircuitState, CircuitState)>>,
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("failure_rate", &self.failure_rate)
+            .field("window", &self.window)
+            .field("min_calls", &self.min_calls)
+            .field("open_timeout", &self.open_timeout)
+            .field("probes", &self.probes)
+            .finish()
+    }
+}
+
+/// Circuit breaker middleware.
+///
+/// Breaker tracks results of the last `window` calls, once failure rate reaches
+/// threshold circuit opens and requests fail fast with `CircuitBreakerError::Open`.
+/// After open timeout circuit switches to half-open state and passes limited number
+/// of probe requests, circuit closes if all probes succeed and opens again otherwise.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    cfg: Config,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreaker {
+    /// Create circuit breaker middleware with default settings.
+    ///
+    /// Default failure rate is 50%, window is 20 calls, minimum number
+    /// of calls is 10, open timeout is 5 seconds, number of probes is 1.
+    pub fn new() -> Self {
+        Self {
+            cfg: Config {
+                failure_rate: 0.5,
+                window: 20,
+                min_calls: 10,
+                open_timeout: Duration::from_secs(5),
+                probes: 1,
+                on_change: None,
+            },
+        }
+    }
+
+    /// Set failure rate threshold, value between `0.0` and `1.0`.
+    ///
+    /// Panics if value is out of range.
+    pub fn failure_rate(mut self, rate: f64) -> Self {
+        assert!(
+            rate > 0.0 && rate <= 1.0,
+            "Failure rate must be in (0.0, 1.0] range"
+        );
+        self.cfg.failure_rate = rate;
+        self
+    }
+
+    /// Set number of recent calls used for failure rate calculation.
+    ///
+    /// Panics if window is 0.
+    pub fn window(mut self, window: usize) -> Self {
+        assert!(window > 0, "Window must be greater than 0");
+        self.cfg.window = window;
+        self.cfg.min_calls = self.cfg.min_calls.min(window);
+        self
+    }
+
+    /// Set minimum number of calls before failure rate is checked.
+    pub fn min_calls(mut self, min_calls: usize) -> Self {
+        self.cfg.min_calls = min_calls.clamp(1, self.cfg.window);
+        self
+    }
+
+    /// Set time circuit stays open before probing inner service.
+    pub fn open_timeout(mut self, timeout: Duration) -> Self {
+        self.cfg.open_timeout = timeout;
+        self
+    }
+
+    /// Set number of successful probes required to close the circuit.
+    ///
+    /// Panics if probes is 0.
+    pub fn probes(mut self, probes: usize) -> Self {
+        assert!(probes > 0, "Number of probes must be greater than 0");
+        self.cfg.probes = probes;
+        self
+    }
+
+    /// Set callback that is called on every state change.
+    ///
+    /// Callback receives previous and new states.
+    pub fn on_state_change<F>(mut self, f: F) -> Self
+    where
+        F: Fn(CircuitState, CircuitState) + 'static,
+    {
+        self.cfg.on_change = Some(Rc::new(f));
+        self
+    }
+}
+
+impl<S> Middleware<S> for CircuitBreaker {
+    type Service = CircuitBreakerService<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        CircuitBreakerService::new(service, self.clone())
+    }
+}
+
+/// Service for the `CircuitBreaker` middleware
+pub struct CircuitBreakerService<S> {
+    service: S,
+    inner: Rc<Breaker>,
+}
+
+struct Breaker {
+    cfg: Config,
+    state: Cell<CircuitState>,
+    // call results, `true` is failure
+    results: RefCell<VecDeque<bool>>,
+    failures: Cell<usize>,
+    opened: Cell<Instant>,
+    // in-flight and succeeded probes in half-open state
+    probes: Cell<usize>,
+    succeeded: Cell<usize>,
+    // incremented on every state change, results of calls
+    // started in previous generation are ignored
+    generation: Cell<usize>,
+}
+
+/// Permit to call inner service
+///
+/// Probe slot is released if call is dropped before result is recorded.
+struct Permit<'a> {
+    breaker: &'a Breaker,
+    generation: usize,
+    recorded: bool,
+}
+
+impl Permit<'_> {
+    fn record(mut self, failure: bool) {
+        self.recorded = true;
+        self.breaker.record(self.generation, failure);
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if !self.recorded {
+            self.breaker.release(self.generation);
+        }
+    }
+}
+
+impl<S> CircuitBreakerService<S> {
+    /// Wrap service with circuit breaker
+    pub fn new(service: S, breaker: CircuitBreaker) -> Self {
+        Self {
+            service,
+            inner: Rc::new(Breaker {
+                results: RefCell::new(VecDeque::with_capacity(breaker.cfg.window)),
+                cfg: breaker.cfg,
+                state: Cell::new(CircuitState::Closed),
+                failures: Cell::new(0),
+                opened: Cell::new(Instant::now()),
+                probes: Cell::new(0),
+                succeeded: Cell::new(0),
+                generation: Cell::new(0),
+            }),
+        }
+    }
+
+    /// Returns current circuit state
+    pub fn state(&self) -> CircuitState {
+        self.inner.state.get()
+    }
+}
+
+impl Breaker {
+    fn set_state(&self, state: CircuitState) {
+        let prev = self.state.replace(state);
+        self.generation.set(self.generation.get().wrapping_add(1));
+        match state {
+            CircuitState::Closed => {
+                self.results.borrow_mut().clear();
+                self.failures.set(0);
+            }
+            CircuitState::Open => self.opened.set(Instant::now()),
+            CircuitState::HalfOpen => {
+                self.probes.set(0);
+                self.succeeded.set(0);
+            }
+        }
+        if prev != state {
+            if let Some(ref f) = self.cfg.on_change {
+                (*f)(prev, state);
+            }
+        }
+    }
+
+    /// Check if request could be passed to inner service
+    fn acquire(&self) -> Option<Permit<'_>> {
+        let allowed = match self.state.get() {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                if self.opened.get().elapsed() >= self.cfg.open_timeout {
+                    self.set_state(CircuitState::HalfOpen);
+                    return self.acquire();
+                } else {
+                    false
+                }
+            }
+            CircuitState::HalfOpen => {
+                let probes = self.probes.get();
+                if probes < self.cfg.probes {
+                    self.probes.set(probes + 1);
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+
+        if allowed {
+            Some(Permit {
+                breaker: self,
+                generation: self.generation.get(),
+                recorded: false,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Release probe slot of cancelled call
+    fn release(&self, generation: usize) {
+        if generation == self.generation.get() && self.state.get() == CircuitState::HalfOpen
+        {
+            self.probes.set(self.probes.get() - 1);
+        }
+    }
+
+    fn record(&self, generation: usize, failure: bool) {
+        // result of request started before last state change
+        if generation != self.generation.get() {
+            return;
+        }
+
+        match self.state.get() {
+            CircuitState::Closed => {
+                let mut results = self.results.borrow_mut();
+                if results.len() == self.cfg.window && results.pop_front() == Some(true) {
+                    self.failures.set(self.failures.get() - 1);
+                }
+                results.push_back(failure);
+                if failure {
+                    self.failures.set(self.failures.get() + 1);
+                }
+
+                let calls = results.len();
+                drop(results);
+                if failure
+                    && calls >= self.cfg.min_calls
+                    && self.failures.get() as f64 / calls as f64 >= self.cfg.failure_rate
+                {
+                    self.set_state(CircuitState::Open);
+                }
+            }
+            CircuitState::HalfOpen => {
+                if failure {
+                    self.set_state(CircuitState::Open);
+                } else {
+                    let succeeded = self.succeeded.get() + 1;
+                    self.succeeded.set(succeeded);
+                    if succeeded >= self.cfg.probes {
+                        self.set_state(CircuitState::Closed);
+                    }
+                }
+            }
+            // requests are not passed to inner service in open state
+            CircuitState::Open => (),
+        }
+    }
+}
+
+impl<S> Clone for CircuitBreakerService<S>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S> fmt::Debug for CircuitBreakerService<S>
+where
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreakerService")
+            .field("service", &self.service)
+            .field("state", &self.inner.state.get())
+            .field("config", &self.inner.cfg)
+            .finish()
+    }
+}
+
+impl<S, R> Service<R> for CircuitBreakerService<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = CircuitBreakerError<S::Error>;
+
+    crate::forward_poll_ready!(service, CircuitBreakerError::Service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        req: R,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let permit = self.inner.acquire().ok_or(CircuitBreakerError::Open)?;
+
+        let result = ctx.call(&self.service, req).await;
+        permit.record(result.is_err());
+        result.map_err(CircuitBreakerError::Service)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, task::Poll};
+
+    use ntex_util::future::lazy;
+
+    use super::*;
+    use crate::{apply, fn_factory, Pipeline, ServiceFactory};
+
+    #[derive(Clone, Debug)]
+    struct Srv(Rc<Cell<bool>>);
+
+    impl Service<usize> for Srv {
+        type Response = usize;
+        type Error = ();
+
+        async fn call(&self, req: usize, _: ServiceCtx<'_, Self>) -> Result<usize, ()> {
+            // slow call
+            if req == 0 {
+                ntex::time::sleep(ntex::time::Millis(100)).await;
+            }
+            if self.0.get() {
+                Err(())
+            } else {
+                Ok(req)
+            }
+        }
+    }
+
+    #[ntex::test]
+    async fn test_circuit_breaker() {
+        let fail = Rc::new(Cell::new(false));
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let changes2 = changes.clone();
+
+        let breaker = CircuitBreaker::new()
+            .window(4)
+            .min_calls(2)
+            .failure_rate(0.5)
+            .open_timeout(Duration::from_millis(50))
+            .probes(2)
+            .on_state_change(move |from, to| changes2.borrow_mut().push((from, to)));
+        let srv = Pipeline::new(CircuitBreakerService::new(Srv(fail.clone()), breaker));
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+        assert!(format!("{:?}", srv).contains("CircuitBreakerService"));
+
+        assert_eq!(srv.call(1).await, Ok(1));
+        assert_eq!(srv.call(1).await, Ok(1));
+        assert_eq!(srv.call(1).await, Ok(1));
+        fail.set(true);
+        assert_eq!(srv.call(1).await, Err(CircuitBreakerError::Service(())));
+        assert_eq!(srv.get_ref().state(), CircuitState::Closed);
+
+        // failure rate reached
+        assert_eq!(srv.call(1).await, Err(CircuitBreakerError::Service(())));
+        assert_eq!(srv.get_ref().state(), CircuitState::Open);
+        fail.set(false);
+        assert_eq!(srv.call(1).await, Err(CircuitBreakerError::Open));
+
+        // failed probe opens circuit again
+        ntex::time::sleep(ntex::time::Millis(60)).await;
+        fail.set(true);
+        assert_eq!(srv.call(1).await, Err(CircuitBreakerError::Service(())));
+        assert_eq!(srv.get_ref().state(), CircuitState::Open);
+        assert_eq!(srv.call(1).await, Err(CircuitBreakerError::Open));
+
+        // successful probes close circuit
+        ntex::time::sleep(ntex::time::Millis(60)).await;
+        fail.set(false);
+        assert_eq!(srv.call(1).await, Ok(1));
+        assert_eq!(srv.get_ref().state(), CircuitState::HalfOpen);
+        assert_eq!(srv.call(1).await, Ok(1));
+        assert_eq!(srv.get_ref().state(), CircuitState::Closed);
+
+        assert_eq!(
+            &*changes.borrow(),
+            &[
+                (CircuitState::Closed, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Closed),
+            ]
+        );
+    }
+
+    #[ntex::test]
+    async fn test_cancelled_probe() {
+        let fail = Rc::new(Cell::new(true));
+        let breaker = CircuitBreaker::new()
+            .window(1)
+            .open_timeout(Duration::from_millis(50))
+            .probes(1);
+        let srv = Pipeline::new(CircuitBreakerService::new(Srv(fail.clone()), breaker));
+
+        assert_eq!(srv.call(1).await, Err(CircuitBreakerError::Service(())));
+        assert_eq!(srv.get_ref().state(), CircuitState::Open);
+
+        // dropped probe releases its slot
+        ntex::time::sleep(ntex::time::Millis(60)).await;
+        fail.set(false);
+        let res = ntex::time::timeout(ntex::time::Millis(10), srv.call(0)).await;
+        assert!(res.is_err());
+        assert_eq!(srv.get_ref().state(), CircuitState::HalfOpen);
+
+        assert_eq!(srv.call(1).await, Ok(1));
+        assert_eq!(srv.get_ref().state(), CircuitState::Closed);
+    }
+
+    #[ntex::test]
+    async fn test_stale_result() {
+        let fail = Rc::new(Cell::new(false));
+        let breaker = CircuitBreaker::new()
+            .window(1)
+            .open_timeout(Duration::from_millis(50))
+            .probes(2);
+        let srv = Pipeline::new(CircuitBreakerService::new(Srv(fail.clone()), breaker));
+
+        // slow call is started in closed state
+        let srv2 = srv.clone();
+        let slow = ntex::rt::spawn(async move { srv2.call(0).await });
+        ntex::time::sleep(ntex::time::Millis(5)).await;
+
+        fail.set(true);
+        assert_eq!(srv.call(1).await, Err(CircuitBreakerError::Service(())));
+        assert_eq!(srv.get_ref().state(), CircuitState::Open);
+
+        ntex::time::sleep(ntex::time::Millis(60)).await;
+        fail.set(false);
+        assert_eq!(srv.call(1).await, Ok(1));
+        assert_eq!(srv.get_ref().state(), CircuitState::HalfOpen);
+
+        // late success of slow call is not counted as probe
+        assert_eq!(slow.await.unwrap(), Ok(0));
+        assert_eq!(srv.get_ref().state(), CircuitState::HalfOpen);
+
+        assert_eq!(srv.call(1).await, Ok(1));
+        assert_eq!(srv.get_ref().state(), CircuitState::Closed);
+    }
+
+    #[ntex::test]
+    async fn test_middleware() {
+        let fail = Rc::new(Cell::new(true));
+        let fail2 = fail.clone();
+        let mw = CircuitBreaker::new().window(1);
+        assert!(format!("{:?}", mw.clone()).contains("CircuitBreaker"));
+
+        let factory = apply(
+            mw,
+            fn_factory(move || {
+                let fail = fail2.clone();
+                async move { Ok::<_, ()>(Srv(fail)) }
+            }),
+        );
+        let srv = factory.pipeline(&()).await.unwrap();
+        assert_eq!(srv.call(1).await, Err(CircuitBreakerError::Service(())));
+        let err = srv.call(1).await.err().unwrap();
+        assert_eq!(err, CircuitBreakerError::Open);
+        assert_eq!(
+            CircuitBreakerError::<std::io::Error>::Open.to_string(),
+            "Circuit is open"
+        );
+        assert_eq!(err.into_service_error(), None);
+    }
+}
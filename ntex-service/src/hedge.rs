@@ -0,0 +1,403 @@
+use std::{cell::Cell, cell::RefCell, collections::VecDeque, fmt, future::poll_fn};
+use std::{future::Future, pin::pin, rc::Rc, task::Poll, time::Duration, time::Instant};
+
+use crate::{Middleware, Service, ServiceCtx};
+
+/// Hedging policy, provides delay futures and request copies.
+pub trait HedgePolicy<Req> {
+    /// Future that resolves when hedged request should be issued.
+    type Future: Future<Output = ()>;
+
+    /// Create future that resolves after specified delay.
+    fn delay(&self, delay: Duration) -> Self::Future;
+
+    /// Clone request for hedged call.
+    ///
+    /// `None` disables hedging for the request.
+    fn clone_request(&self, req: &Req) -> Option<Req>;
+}
+
+impl<T, Req> HedgePolicy<Req> for Rc<T>
+where
+    T: HedgePolicy<Req>,
+{
+    type Future = T::Future;
+
+    fn delay(&self, delay: Duration) -> Self::Future {
+        self.as_ref().delay(delay)
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        self.as_ref().clone_request(req)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Config {
+    percentile: f64,
+    window: usize,
+    min_samples: usize,
+    budget: f64,
+}
+
+/// Hedging middleware.
+///
+/// Middleware tracks latencies of recent successful calls, if call is not
+/// completed within configured latency percentile, second copy of the request
+/// is issued to the inner service and first successful response is returned.
+/// Budget limits ratio of hedged requests to all requests.
+pub struct Hedge<P> {
+    cfg: Config,
+    policy: Rc<P>,
+}
+
+impl<P> Hedge<P> {
+    /// Create hedging middleware with specified policy.
+    ///
+    /// By default hedged request is issued after 95th percentile latency,
+    /// latency window is 100 calls, minimum number of samples is 20,
+    /// and budget is 10% of requests.
+    pub fn new(policy: P) -> Self {
+        Self {
+            cfg: Config {
+                percentile: 0.95,
+                window: 100,
+                min_samples: 20,
+                budget: 0.1,
+            },
+            policy: Rc::new(policy),
+        }
+    }
+
+    /// Set latency percentile, value between `0.0` and `1.0`.
+    ///
+    /// Panics if value is out of range.
+    pub fn percentile(mut self, percentile: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&percentile),
+            "Percentile must be in [0.0, 1.0] range"
+        );
+        self.cfg.percentile = percentile;
+        self
+    }
+
+    /// Set number of recent latencies used for percentile calculation.
+    ///
+    /// Panics if window is 0.
+    pub fn window(mut self, window: usize) -> Self {
+        assert!(window > 0, "Window must be greater than 0");
+        self.cfg.window = window;
+        self.cfg.min_samples = self.cfg.min_samples.min(window);
+        self
+    }
+
+    /// Set minimum number of latency samples before requests are hedged.
+    pub fn min_samples(mut self, min_samples: usize) -> Self {
+        self.cfg.min_samples = min_samples.clamp(1, self.cfg.window);
+        self
+    }
+
+    /// Set max ratio of hedged requests, value between `0.0` and `1.0`.
+    ///
+    /// Panics if value is out of range.
+    pub fn budget(mut self, budget: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&budget),
+            "Budget must be in [0.0, 1.0] range"
+        );
+        self.cfg.budget = budget;
+        self
+    }
+}
+
+impl<P> Clone for Hedge<P> {
+    fn clone(&self) -> Self {
+        Self {
+            cfg: self.cfg,
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<P: fmt::Debug> fmt::Debug for Hedge<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hedge")
+            .field("config", &self.cfg)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<S, P> Middleware<S> for Hedge<P> {
+    type Service = HedgeService<S, P>;
+
+    fn create(&self, service: S) -> Self::Service {
+        HedgeService {
+            service,
+            policy: self.policy.clone(),
+            stats: Rc::new(Stats::new(self.cfg)),
+        }
+    }
+}
+
+/// Service for the `Hedge` middleware
+pub struct HedgeService<S, P> {
+    service: S,
+    policy: Rc<P>,
+    stats: Rc<Stats>,
+}
+
+struct Stats {
+    cfg: Config,
+    latencies: RefCell<VecDeque<Duration>>,
+    tokens: Cell<f64>,
+}
+
+impl Stats {
+    fn new(cfg: Config) -> Self {
+        Stats {
+            cfg,
+            latencies: RefCell::new(VecDeque::with_capacity(cfg.window)),
+            tokens: Cell::new(0.0),
+        }
+    }
+
+    /// Deposit budget for request, returns hedging threshold
+    fn threshold(&self) -> Option<Duration> {
+        let max = self.cfg.budget * self.cfg.window as f64 + 1.0;
+        self.tokens
+            .set((self.tokens.get() + self.cfg.budget).min(max));
+
+        let latencies = self.latencies.borrow();
+        if latencies.len() < self.cfg.min_samples {
+            return None;
+        }
+        let mut sorted: Vec<_> = latencies.iter().copied().collect();
+        sorted.sort_unstable();
+        let idx = ((sorted.len() - 1) as f64 * self.cfg.percentile).round() as usize;
+        Some(sorted[idx])
+    }
+
+    /// Withdraw budget for hedged request
+    fn withdraw(&self) -> bool {
+        let tokens = self.tokens.get();
+        if tokens >= 1.0 {
+            self.tokens.set(tokens - 1.0);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn record(&self, latency: Duration) {
+        let mut latencies = self.latencies.borrow_mut();
+        if latencies.len() == self.cfg.window {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+    }
+}
+
+impl<S, P> HedgeService<S, P> {
+    /// Wrap service with hedging middleware
+    pub fn new(service: S, hedge: Hedge<P>) -> Self {
+        Self {
+            service,
+            policy: hedge.policy,
+            stats: Rc::new(Stats::new(hedge.cfg)),
+        }
+    }
+}
+
+impl<S, P> Clone for HedgeService<S, P>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            policy: self.policy.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<S, P> fmt::Debug for HedgeService<S, P>
+where
+    S: fmt::Debug,
+    P: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HedgeService")
+            .field("service", &self.service)
+            .field("policy", &self.policy)
+            .field("config", &self.stats.cfg)
+            .finish()
+    }
+}
+
+impl<S, P, R> Service<R> for HedgeService<S, P>
+where
+    S: Service<R>,
+    P: HedgePolicy<R>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        req: R,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let start = Instant::now();
+        let hedge = self
+            .stats
+            .threshold()
+            .and_then(|delay| Some((delay, self.policy.clone_request(&req)?)));
+
+        let mut primary = pin!(ctx.call(&self.service, req));
+        let (delay, hedged_req) = if let Some(hedge) = hedge {
+            hedge
+        } else {
+            let result = primary.await;
+            if result.is_ok() {
+                self.stats.record(start.elapsed());
+            }
+            return result;
+        };
+
+        // wait for primary call or hedging delay
+        let mut delay = pin!(self.policy.delay(delay));
+        let result = poll_fn(|cx| {
+            if let Poll::Ready(result) = primary.as_mut().poll(cx) {
+                Poll::Ready(Some(result))
+            } else if delay.as_mut().poll(cx).is_ready() {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+
+        let result = if let Some(result) = result {
+            result
+        } else if !self.stats.withdraw() {
+            primary.await
+        } else {
+            // first successful response wins
+            let mut secondary = pin!(ctx.call(&self.service, hedged_req));
+            let (mut primary_done, mut secondary_done) = (false, false);
+            poll_fn(|cx| {
+                if !primary_done {
+                    if let Poll::Ready(result) = primary.as_mut().poll(cx) {
+                        if result.is_ok() || secondary_done {
+                            return Poll::Ready(result);
+                        }
+                        primary_done = true;
+                    }
+                }
+                if !secondary_done {
+                    if let Poll::Ready(result) = secondary.as_mut().poll(cx) {
+                        if result.is_ok() || primary_done {
+                            return Poll::Ready(result);
+                        }
+                        secondary_done = true;
+                    }
+                }
+                Poll::Pending
+            })
+            .await
+        };
+
+        if result.is_ok() {
+            self.stats.record(start.elapsed());
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, future::Future, pin::Pin, rc::Rc};
+
+    use ntex::time::{sleep, Millis};
+
+    use super::*;
+    use crate::{apply, fn_factory, Pipeline, ServiceFactory};
+
+    #[derive(Clone, Debug)]
+    struct Srv(Rc<Cell<usize>>, usize);
+
+    impl Service<()> for Srv {
+        type Response = usize;
+        type Error = ();
+
+        async fn call(&self, _: (), _: ServiceCtx<'_, Self>) -> Result<usize, ()> {
+            let calls = self.0.get() + 1;
+            self.0.set(calls);
+            if calls == self.1 {
+                sleep(Millis(500)).await;
+            }
+            Ok(calls)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Policy;
+
+    impl HedgePolicy<()> for Policy {
+        type Future = Pin<Box<dyn Future<Output = ()>>>;
+
+        fn delay(&self, delay: Duration) -> Self::Future {
+            Box::pin(sleep(Millis::from(delay) + Millis(10)))
+        }
+
+        fn clone_request(&self, _: &()) -> Option<()> {
+            Some(())
+        }
+    }
+
+    #[ntex::test]
+    async fn test_hedge() {
+        let calls = Rc::new(Cell::new(0));
+        let hedge = Hedge::new(Policy).min_samples(5).budget(1.0);
+        let srv = Pipeline::new(HedgeService::new(Srv(calls.clone(), 6), hedge));
+        assert!(format!("{:?}", srv).contains("HedgeService"));
+
+        for idx in 1..6 {
+            assert_eq!(srv.call(()).await, Ok(idx));
+        }
+
+        // slow primary call, hedged call wins
+        let start = Instant::now();
+        assert_eq!(srv.call(()).await, Ok(7));
+        assert!(start.elapsed() < Duration::from_millis(300));
+    }
+
+    #[ntex::test]
+    async fn test_budget() {
+        let calls = Rc::new(Cell::new(0));
+        let calls2 = calls.clone();
+        let mw = Hedge::new(Policy).window(5).budget(0.1);
+        assert!(format!("{:?}", mw.clone()).contains("Hedge"));
+
+        let factory = apply(
+            mw,
+            fn_factory(move || {
+                let calls = calls2.clone();
+                async move { Ok::<_, ()>(Srv(calls, 6)) }
+            }),
+        );
+        let srv = factory.pipeline(&()).await.unwrap();
+        for idx in 1..6 {
+            assert_eq!(srv.call(()).await, Ok(idx));
+        }
+
+        // budget is exhausted, request is not hedged
+        assert_eq!(srv.call(()).await, Ok(6));
+        assert_eq!(calls.get(), 6);
+    }
+}
@@ -10,28 +10,42 @@ use std::{future::Future, rc::Rc, task, task::Context, task::Poll};
 
 mod and_then;
 mod apply;
+mod balance;
 pub mod boxed;
 mod chain;
+mod circuit;
 mod ctx;
 mod fn_service;
 mod fn_shutdown;
+mod hedge;
 mod macros;
 mod map;
+mod map_async;
 mod map_config;
 mod map_err;
 mod map_init_err;
 mod middleware;
 mod pipeline;
+mod reload;
+mod retry;
 mod then;
 
+#[cfg(feature = "tower")]
+pub mod tower;
+
 pub use self::apply::{apply_fn, apply_fn_factory};
+pub use self::balance::Balancer;
 pub use self::chain::{chain, chain_factory};
+pub use self::circuit::{CircuitBreaker, CircuitBreakerError, CircuitState};
 pub use self::ctx::ServiceCtx;
 pub use self::fn_service::{fn_factory, fn_factory_with_config, fn_service};
 pub use self::fn_shutdown::fn_shutdown;
+pub use self::hedge::{Hedge, HedgePolicy};
 pub use self::map_config::{map_config, unit_config};
 pub use self::middleware::{apply, Identity, Middleware, Stack};
 pub use self::pipeline::{Pipeline, PipelineCall};
+pub use self::reload::{ReloadHandle, Reloadable};
+pub use self::retry::{MaxRetries, Retry, RetryPolicy};
 
 #[allow(unused_variables)]
 /// An asynchronous function of `Request` to a `Response`.
@@ -364,14 +378,20 @@ pub mod dev {
     pub use crate::and_then::{AndThen, AndThenFactory};
     pub use crate::apply::{Apply, ApplyFactory};
     pub use crate::chain::{ServiceChain, ServiceChainFactory};
+    pub use crate::circuit::CircuitBreakerService;
     pub use crate::fn_service::{
         FnService, FnServiceConfig, FnServiceFactory, FnServiceNoConfig,
     };
     pub use crate::fn_shutdown::FnShutdown;
+    pub use crate::hedge::HedgeService;
     pub use crate::map::{Map, MapFactory};
+    pub use crate::map_async::{
+        MapRequest, MapRequestFactory, MapResponse, MapResponseFactory,
+    };
     pub use crate::map_config::{MapConfig, UnitConfig};
     pub use crate::map_err::{MapErr, MapErrFactory};
     pub use crate::map_init_err::MapInitErr;
     pub use crate::middleware::ApplyMiddleware;
+    pub use crate::retry::RetryService;
     pub use crate::then::{Then, ThenFactory};
 }
@@ -0,0 +1,361 @@
+use std::{fmt, future::Future, marker::PhantomData};
+
+use super::{Service, ServiceCtx, ServiceFactory};
+
+/// Service for the `map_request` combinator, asynchronously transforms
+/// request before passing it to the service.
+pub struct MapRequest<A, F, Req, In> {
+    service: A,
+    f: F,
+    _t: PhantomData<fn(Req) -> In>,
+}
+
+impl<A, F, Req, In> MapRequest<A, F, Req, In> {
+    /// Create new `MapRequest` combinator
+    pub(crate) fn new<Fut>(service: A, f: F) -> Self
+    where
+        A: Service<In>,
+        F: Fn(Req) -> Fut,
+        Fut: Future<Output = Result<In, A::Error>>,
+    {
+        Self {
+            service,
+            f,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<A, F, Req, In> Clone for MapRequest<A, F, Req, In>
+where
+    A: Clone,
+    F: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            f: self.f.clone(),
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<A, F, Req, In> fmt::Debug for MapRequest<A, F, Req, In>
+where
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapRequest")
+            .field("service", &self.service)
+            .field("map", &std::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<A, F, Fut, Req, In> Service<Req> for MapRequest<A, F, Req, In>
+where
+    A: Service<In>,
+    F: Fn(Req) -> Fut,
+    Fut: Future<Output = Result<In, A::Error>>,
+{
+    type Response = A::Response;
+    type Error = A::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    #[inline]
+    async fn call(
+        &self,
+        req: Req,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let req = (self.f)(req).await?;
+        ctx.call(&self.service, req).await
+    }
+}
+
+/// `MapRequest` service factory combinator
+pub struct MapRequestFactory<A, F, Req, In, Cfg> {
+    a: A,
+    f: F,
+    r: PhantomData<fn(Req, In, Cfg)>,
+}
+
+impl<A, F, Req, In, Cfg> MapRequestFactory<A, F, Req, In, Cfg> {
+    /// Create new `MapRequest` new service instance
+    pub(crate) fn new<Fut>(a: A, f: F) -> Self
+    where
+        A: ServiceFactory<In, Cfg>,
+        F: Fn(Req) -> Fut + Clone,
+        Fut: Future<Output = Result<In, A::Error>>,
+    {
+        Self {
+            a,
+            f,
+            r: PhantomData,
+        }
+    }
+}
+
+impl<A, F, Req, In, Cfg> Clone for MapRequestFactory<A, F, Req, In, Cfg>
+where
+    A: Clone,
+    F: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            a: self.a.clone(),
+            f: self.f.clone(),
+            r: PhantomData,
+        }
+    }
+}
+
+impl<A, F, Req, In, Cfg> fmt::Debug for MapRequestFactory<A, F, Req, In, Cfg>
+where
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapRequestFactory")
+            .field("factory", &self.a)
+            .field("map", &std::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<A, F, Fut, Req, In, Cfg> ServiceFactory<Req, Cfg>
+    for MapRequestFactory<A, F, Req, In, Cfg>
+where
+    A: ServiceFactory<In, Cfg>,
+    F: Fn(Req) -> Fut + Clone,
+    Fut: Future<Output = Result<In, A::Error>>,
+{
+    type Response = A::Response;
+    type Error = A::Error;
+
+    type Service = MapRequest<A::Service, F, Req, In>;
+    type InitError = A::InitError;
+
+    #[inline]
+    async fn create(&self, cfg: Cfg) -> Result<Self::Service, Self::InitError> {
+        Ok(MapRequest {
+            service: self.a.create(cfg).await?,
+            f: self.f.clone(),
+            _t: PhantomData,
+        })
+    }
+}
+
+/// Service for the `map_response` combinator, asynchronously transforms
+/// service's response.
+pub struct MapResponse<A, F, Req, Res> {
+    service: A,
+    f: F,
+    _t: PhantomData<fn(Req) -> Res>,
+}
+
+impl<A, F, Req, Res> MapResponse<A, F, Req, Res> {
+    /// Create new `MapResponse` combinator
+    pub(crate) fn new<Fut>(service: A, f: F) -> Self
+    where
+        A: Service<Req>,
+        F: Fn(A::Response) -> Fut,
+        Fut: Future<Output = Result<Res, A::Error>>,
+    {
+        Self {
+            service,
+            f,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<A, F, Req, Res> Clone for MapResponse<A, F, Req, Res>
+where
+    A: Clone,
+    F: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            f: self.f.clone(),
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<A, F, Req, Res> fmt::Debug for MapResponse<A, F, Req, Res>
+where
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapResponse")
+            .field("service", &self.service)
+            .field("map", &std::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<A, F, Fut, Req, Res> Service<Req> for MapResponse<A, F, Req, Res>
+where
+    A: Service<Req>,
+    F: Fn(A::Response) -> Fut,
+    Fut: Future<Output = Result<Res, A::Error>>,
+{
+    type Response = Res;
+    type Error = A::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    #[inline]
+    async fn call(
+        &self,
+        req: Req,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let res = ctx.call(&self.service, req).await?;
+        (self.f)(res).await
+    }
+}
+
+/// `MapResponse` service factory combinator
+pub struct MapResponseFactory<A, F, Req, Res, Cfg> {
+    a: A,
+    f: F,
+    r: PhantomData<fn(Req, Cfg) -> Res>,
+}
+
+impl<A, F, Req, Res, Cfg> MapResponseFactory<A, F, Req, Res, Cfg> {
+    /// Create new `MapResponse` new service instance
+    pub(crate) fn new<Fut>(a: A, f: F) -> Self
+    where
+        A: ServiceFactory<Req, Cfg>,
+        F: Fn(A::Response) -> Fut + Clone,
+        Fut: Future<Output = Result<Res, A::Error>>,
+    {
+        Self {
+            a,
+            f,
+            r: PhantomData,
+        }
+    }
+}
+
+impl<A, F, Req, Res, Cfg> Clone for MapResponseFactory<A, F, Req, Res, Cfg>
+where
+    A: Clone,
+    F: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            a: self.a.clone(),
+            f: self.f.clone(),
+            r: PhantomData,
+        }
+    }
+}
+
+impl<A, F, Req, Res, Cfg> fmt::Debug for MapResponseFactory<A, F, Req, Res, Cfg>
+where
+    A: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapResponseFactory")
+            .field("factory", &self.a)
+            .field("map", &std::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<A, F, Fut, Req, Res, Cfg> ServiceFactory<Req, Cfg>
+    for MapResponseFactory<A, F, Req, Res, Cfg>
+where
+    A: ServiceFactory<Req, Cfg>,
+    F: Fn(A::Response) -> Fut + Clone,
+    Fut: Future<Output = Result<Res, A::Error>>,
+{
+    type Response = Res;
+    type Error = A::Error;
+
+    type Service = MapResponse<A::Service, F, Req, Res>;
+    type InitError = A::InitError;
+
+    #[inline]
+    async fn create(&self, cfg: Cfg) -> Result<Self::Service, Self::InitError> {
+        Ok(MapResponse {
+            service: self.a.create(cfg).await?,
+            f: self.f.clone(),
+            _t: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex_util::future::lazy;
+    use std::task::Poll;
+
+    use crate::{chain, chain_factory, fn_factory, Pipeline, Service, ServiceCtx};
+
+    #[derive(Debug, Clone)]
+    struct Srv;
+
+    impl Service<usize> for Srv {
+        type Response = usize;
+        type Error = &'static str;
+
+        async fn call(
+            &self,
+            req: usize,
+            _: ServiceCtx<'_, Self>,
+        ) -> Result<usize, Self::Error> {
+            Ok(req * 2)
+        }
+    }
+
+    #[ntex::test]
+    async fn test_service() {
+        let srv = Pipeline::new(
+            chain(Srv)
+                .map_request(|req: &'static str| async move {
+                    req.parse::<usize>().map_err(|_| "parse error")
+                })
+                .map_response(|res| async move { Ok(res.to_string()) })
+                .clone(),
+        );
+        assert_eq!(srv.call("2").await, Ok("4".to_string()));
+        assert_eq!(srv.call("x").await, Err("parse error"));
+
+        let res = lazy(|cx| srv.poll_ready(cx)).await;
+        assert_eq!(res, Poll::Ready(Ok(())));
+        let res = lazy(|cx| srv.poll_shutdown(cx)).await;
+        assert_eq!(res, Poll::Ready(()));
+        assert!(format!("{:?}", srv).contains("MapResponse"));
+        assert!(format!("{:?}", srv).contains("MapRequest"));
+    }
+
+    #[ntex::test]
+    async fn test_factory() {
+        let factory = chain_factory(fn_factory(|| async { Ok::<_, ()>(Srv) }))
+            .map_request(|req: usize| async move { Ok(req + 1) })
+            .map_response(|res| async move {
+                if res > 10 {
+                    Err("too big")
+                } else {
+                    Ok(res)
+                }
+            })
+            .clone();
+        assert!(format!("{:?}", factory).contains("MapResponseFactory"));
+
+        let srv = factory.pipeline(&()).await.unwrap();
+        assert_eq!(srv.call(1).await, Ok(4));
+        assert_eq!(srv.call(10).await, Err("too big"));
+    }
+}
@@ -0,0 +1,185 @@
+use std::task::{Context, Poll, Waker};
+use std::{cell::Cell, cell::RefCell, fmt, future::poll_fn, rc::Rc};
+
+use crate::{Service, ServiceCtx};
+
+struct Inner<S> {
+    service: RefCell<Rc<S>>,
+    generation: Cell<usize>,
+    waker: Cell<Option<Waker>>,
+}
+
+/// Service wrapper with replaceable inner service.
+///
+/// Inner service could be replaced with `ReloadHandle`. In-flight calls
+/// keep using previous service, previous service is dropped after
+/// all in-flight calls are completed.
+pub struct Reloadable<S>(Rc<Inner<S>>);
+
+/// Handle for replacing inner service of `Reloadable` service.
+pub struct ReloadHandle<S>(Rc<Inner<S>>);
+
+impl<S> Reloadable<S> {
+    /// Create reloadable service
+    pub fn new(service: S) -> Self {
+        Reloadable(Rc::new(Inner {
+            service: RefCell::new(Rc::new(service)),
+            generation: Cell::new(0),
+            waker: Cell::new(None),
+        }))
+    }
+
+    /// Get reload handle
+    pub fn handle(&self) -> ReloadHandle<S> {
+        ReloadHandle(self.0.clone())
+    }
+
+    /// Returns number of service replacements
+    pub fn generation(&self) -> usize {
+        self.0.generation.get()
+    }
+
+    /// Returns current inner service
+    pub fn get_ref(&self) -> Rc<S> {
+        self.0.service.borrow().clone()
+    }
+}
+
+impl<S> ReloadHandle<S> {
+    /// Replace inner service.
+    ///
+    /// New service is polled for readiness first, service is not replaced
+    /// if readiness check fails.
+    pub async fn reload<R>(&self, service: S) -> Result<(), S::Error>
+    where
+        S: Service<R>,
+    {
+        poll_fn(|cx| service.poll_ready(cx)).await?;
+        self.replace(service);
+        Ok(())
+    }
+
+    /// Replace inner service without readiness check.
+    pub fn replace(&self, service: S) {
+        *self.0.service.borrow_mut() = Rc::new(service);
+        self.0.generation.set(self.0.generation.get() + 1);
+
+        // task waits for readiness of previous service
+        if let Some(waker) = self.0.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Returns number of service replacements
+    pub fn generation(&self) -> usize {
+        self.0.generation.get()
+    }
+}
+
+impl<S> Clone for ReloadHandle<S> {
+    fn clone(&self) -> Self {
+        ReloadHandle(self.0.clone())
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Reloadable<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reloadable")
+            .field("service", &self.0.service.borrow())
+            .field("generation", &self.0.generation.get())
+            .finish()
+    }
+}
+
+impl<S> fmt::Debug for ReloadHandle<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReloadHandle")
+            .field("generation", &self.0.generation.get())
+            .finish()
+    }
+}
+
+impl<S, R> Service<R> for Reloadable<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let service = self.get_ref();
+        let result = service.poll_ready(cx);
+        if result.is_pending() {
+            self.0.waker.set(Some(cx.waker().clone()));
+        }
+        result
+    }
+
+    fn poll_shutdown(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.get_ref().poll_shutdown(cx)
+    }
+
+    async fn call(
+        &self,
+        req: R,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        // keep service alive until call completes
+        let service = self.get_ref();
+        let (idx, waiters) = ctx.inner();
+        service.call(req, ServiceCtx::from_ref(idx, waiters)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, task::Poll};
+
+    use ntex_util::future::lazy;
+
+    use super::*;
+    use crate::Pipeline;
+
+    #[derive(Debug)]
+    struct Srv(usize, Rc<Cell<bool>>);
+
+    impl Service<()> for Srv {
+        type Response = usize;
+        type Error = ();
+
+        fn poll_ready(&self, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            if self.1.get() {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        }
+
+        async fn call(&self, _: (), _: ServiceCtx<'_, Self>) -> Result<usize, ()> {
+            Ok(self.0)
+        }
+    }
+
+    #[ntex::test]
+    async fn test_reload() {
+        let ready = Rc::new(Cell::new(true));
+        let srv = Pipeline::new(Reloadable::new(Srv(1, ready.clone())));
+        let handle = srv.get_ref().handle();
+        assert!(format!("{:?}", srv).contains("Reloadable"));
+        assert!(format!("{:?}", handle.clone()).contains("ReloadHandle"));
+
+        assert_eq!(srv.call(()).await, Ok(1));
+        handle.reload(Srv(2, ready.clone())).await.unwrap();
+        assert_eq!(srv.call(()).await, Ok(2));
+        assert_eq!(handle.generation(), 1);
+        assert_eq!(srv.get_ref().generation(), 1);
+
+        // pending service is replaced by ready one
+        ready.set(false);
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Pending);
+        handle.replace(Srv(3, Rc::new(Cell::new(true))));
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+        assert_eq!(srv.call(()).await, Ok(3));
+        assert!(lazy(|cx| srv.poll_shutdown(cx)).await.is_ready());
+    }
+}
@@ -0,0 +1,262 @@
+use std::{fmt, future::Future, rc::Rc};
+
+use crate::{Middleware, Service, ServiceCtx};
+
+/// Retry policy, decides whether and when failed request should be retried.
+pub trait RetryPolicy<Req, Err> {
+    /// Future that resolves when request could be retried.
+    type Future: Future<Output = ()>;
+
+    /// Check whether request should be retried.
+    ///
+    /// `attempt` is number of failed calls, starting from `1`. Returns
+    /// `None` if request should not be retried, otherwise returned future
+    /// delays next call.
+    fn retry(&self, req: &Req, err: &Err, attempt: usize) -> Option<Self::Future>;
+
+    /// Clone request for next attempt.
+    ///
+    /// Request is cloned before each call, `None` disables retry for the request.
+    fn clone_request(&self, req: &Req) -> Option<Req>;
+}
+
+impl<T, Req, Err> RetryPolicy<Req, Err> for Rc<T>
+where
+    T: RetryPolicy<Req, Err>,
+{
+    type Future = T::Future;
+
+    fn retry(&self, req: &Req, err: &Err, attempt: usize) -> Option<Self::Future> {
+        self.as_ref().retry(req, err, attempt)
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        self.as_ref().clone_request(req)
+    }
+}
+
+/// Retry policy that retries cloneable requests immediately, up to specified
+/// number of times.
+#[derive(Copy, Clone, Debug)]
+pub struct MaxRetries(pub usize);
+
+impl<Req: Clone, Err> RetryPolicy<Req, Err> for MaxRetries {
+    type Future = std::future::Ready<()>;
+
+    fn retry(&self, _: &Req, _: &Err, attempt: usize) -> Option<Self::Future> {
+        if attempt <= self.0 {
+            Some(std::future::ready(()))
+        } else {
+            None
+        }
+    }
+
+    fn clone_request(&self, req: &Req) -> Option<Req> {
+        Some(req.clone())
+    }
+}
+
+/// Retry middleware, calls inner service again if policy permits.
+pub struct Retry<P> {
+    policy: Rc<P>,
+}
+
+impl<P> Retry<P> {
+    /// Create retry middleware with specified policy
+    pub fn new(policy: P) -> Self {
+        Self {
+            policy: Rc::new(policy),
+        }
+    }
+}
+
+impl<P> Clone for Retry<P> {
+    fn clone(&self) -> Self {
+        Self {
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<P: fmt::Debug> fmt::Debug for Retry<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Retry")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<S, P> Middleware<S> for Retry<P> {
+    type Service = RetryService<S, P>;
+
+    fn create(&self, service: S) -> Self::Service {
+        RetryService {
+            service,
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+/// Service for the `Retry` middleware
+pub struct RetryService<S, P> {
+    service: S,
+    policy: Rc<P>,
+}
+
+impl<S, P> RetryService<S, P> {
+    /// Wrap service with retry policy
+    pub fn new(service: S, policy: P) -> Self {
+        Self {
+            service,
+            policy: Rc::new(policy),
+        }
+    }
+}
+
+impl<S, P> Clone for RetryService<S, P>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl<S, P> fmt::Debug for RetryService<S, P>
+where
+    S: fmt::Debug,
+    P: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryService")
+            .field("service", &self.service)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl<S, P, R> Service<R> for RetryService<S, P>
+where
+    S: Service<R>,
+    P: RetryPolicy<R, S::Error>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        mut req: R,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let mut attempt = 0;
+        loop {
+            let next = self.policy.clone_request(&req);
+            let err = match ctx.call(&self.service, req).await {
+                Ok(res) => return Ok(res),
+                Err(err) => err,
+            };
+            attempt += 1;
+
+            match next {
+                Some(next) => {
+                    if let Some(delay) = self.policy.retry(&next, &err, attempt) {
+                        delay.await;
+                        req = next;
+                    } else {
+                        return Err(err);
+                    }
+                }
+                None => return Err(err),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, task::Poll};
+
+    use ntex_util::future::lazy;
+
+    use super::*;
+    use crate::{apply, fn_factory, Pipeline, ServiceFactory};
+
+    #[derive(Clone, Debug)]
+    struct Srv(Rc<Cell<usize>>, usize);
+
+    impl Service<usize> for Srv {
+        type Response = usize;
+        type Error = usize;
+
+        async fn call(&self, req: usize, _: ServiceCtx<'_, Self>) -> Result<usize, usize> {
+            let calls = self.0.get() + 1;
+            self.0.set(calls);
+            if calls > self.1 {
+                Ok(req)
+            } else {
+                Err(calls)
+            }
+        }
+    }
+
+    struct NoClone;
+
+    impl RetryPolicy<usize, usize> for NoClone {
+        type Future = std::future::Ready<()>;
+
+        fn retry(&self, _: &usize, _: &usize, _: usize) -> Option<Self::Future> {
+            Some(std::future::ready(()))
+        }
+
+        fn clone_request(&self, _: &usize) -> Option<usize> {
+            None
+        }
+    }
+
+    #[ntex::test]
+    async fn test_retry() {
+        let calls = Rc::new(Cell::new(0));
+        let srv = Pipeline::new(RetryService::new(Srv(calls.clone(), 2), MaxRetries(2)));
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+        assert_eq!(srv.call(10).await, Ok(10));
+        assert_eq!(calls.get(), 3);
+
+        // retries exhausted
+        let calls = Rc::new(Cell::new(0));
+        let srv = Pipeline::new(RetryService::new(Srv(calls.clone(), 5), MaxRetries(2)));
+        assert_eq!(srv.call(10).await, Err(3));
+        assert_eq!(calls.get(), 3);
+        assert!(format!("{:?}", srv).contains("RetryService"));
+
+        // request is not cloneable
+        let calls = Rc::new(Cell::new(0));
+        let srv = Pipeline::new(RetryService::new(Srv(calls.clone(), 1), NoClone));
+        assert_eq!(srv.call(10).await, Err(1));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[ntex::test]
+    async fn test_middleware() {
+        let calls = Rc::new(Cell::new(0));
+        let calls2 = calls.clone();
+        let mw = Retry::new(MaxRetries(1));
+        assert!(format!("{:?}", mw.clone()).contains("Retry"));
+
+        let factory = apply(
+            mw,
+            fn_factory(move || {
+                let calls = calls2.clone();
+                async move { Ok::<_, ()>(Srv(calls, 1)) }
+            }),
+        );
+        let srv = factory.pipeline(&()).await.unwrap();
+        assert_eq!(srv.call(1).await, Ok(1));
+        assert_eq!(calls.get(), 2);
+    }
+}
@@ -0,0 +1,226 @@
+//! Interoperability with [`tower`](https://docs.rs/tower) services.
+//!
+//! Tower services require `poll_ready()` call before each `call()`,
+//! while ntex services share readiness between calls. Adapters
+//! enforce tower readiness contract on every call.
+use std::{cell::RefCell, fmt, future::poll_fn, task::Context, task::Poll};
+
+use crate::{Middleware, Pipeline, PipelineCall, Service, ServiceCtx};
+
+/// Adapter that converts ntex service to tower service.
+pub struct TowerService<S>(Pipeline<S>);
+
+impl<S> TowerService<S> {
+    /// Create tower service from ntex service
+    pub fn new<T: Into<Pipeline<S>>>(svc: T) -> Self {
+        TowerService(svc.into())
+    }
+
+    /// Returns reference to inner pipeline
+    pub fn get_ref(&self) -> &Pipeline<S> {
+        &self.0
+    }
+}
+
+impl<S> Clone for TowerService<S> {
+    fn clone(&self) -> Self {
+        TowerService(self.0.clone())
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for TowerService<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TowerService")
+            .field(self.0.get_ref())
+            .finish()
+    }
+}
+
+impl<S, R> tower::Service<R> for TowerService<S>
+where
+    S: Service<R> + 'static,
+    R: 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = PipelineCall<S, R>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        // tower callers check readiness before call
+        self.0.call_nowait(req)
+    }
+}
+
+/// Adapter that converts tower service to ntex service.
+pub struct FromTower<T>(RefCell<T>);
+
+impl<T> FromTower<T> {
+    /// Create ntex service from tower service
+    pub fn new(svc: T) -> Self {
+        FromTower(RefCell::new(svc))
+    }
+
+    /// Returns inner tower service
+    pub fn into_inner(self) -> T {
+        self.0.into_inner()
+    }
+}
+
+impl<T: Clone> Clone for FromTower<T> {
+    fn clone(&self) -> Self {
+        FromTower(RefCell::new(self.0.borrow().clone()))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for FromTower<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FromTower").field(&*self.0.borrow()).finish()
+    }
+}
+
+impl<T, R> Service<R> for FromTower<T>
+where
+    T: tower::Service<R>,
+{
+    type Response = T::Response;
+    type Error = T::Error;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.borrow_mut().poll_ready(cx)
+    }
+
+    async fn call(
+        &self,
+        req: R,
+        _: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        // ntex readiness is shared between calls, tower service
+        // must be polled for readiness right before each call
+        poll_fn(|cx| self.0.borrow_mut().poll_ready(cx)).await?;
+        let fut = self.0.borrow_mut().call(req);
+        fut.await
+    }
+}
+
+/// Middleware that applies tower layer to ntex service.
+#[derive(Clone)]
+pub struct TowerLayer<L>(L);
+
+impl<L> TowerLayer<L> {
+    /// Create middleware from tower layer
+    pub fn new(layer: L) -> Self {
+        TowerLayer(layer)
+    }
+}
+
+impl<L> fmt::Debug for TowerLayer<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TowerLayer").finish()
+    }
+}
+
+impl<S, L> Middleware<S> for TowerLayer<L>
+where
+    L: tower::Layer<TowerService<S>>,
+{
+    type Service = FromTower<L::Service>;
+
+    fn create(&self, service: S) -> Self::Service {
+        FromTower::new(self.0.layer(TowerService::new(service)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, future::Ready, rc::Rc};
+
+    use super::*;
+    use crate::{apply, fn_service, ServiceFactory};
+
+    #[derive(Clone, Debug)]
+    struct Srv(Rc<Cell<usize>>);
+
+    impl tower::Service<usize> for Srv {
+        type Response = usize;
+        type Error = ();
+        type Future = Ready<Result<usize, ()>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            self.0.set(self.0.get() + 1);
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: usize) -> Self::Future {
+            std::future::ready(Ok(req * 2))
+        }
+    }
+
+    struct Double;
+
+    impl<S> tower::Layer<S> for Double {
+        type Service = Doubled<S>;
+
+        fn layer(&self, inner: S) -> Self::Service {
+            Doubled(inner)
+        }
+    }
+
+    struct Doubled<S>(S);
+
+    impl<S: tower::Service<usize, Response = usize>> tower::Service<usize> for Doubled<S> {
+        type Response = usize;
+        type Error = S::Error;
+        type Future = S::Future;
+
+        fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+            self.0.poll_ready(cx)
+        }
+
+        fn call(&mut self, req: usize) -> Self::Future {
+            self.0.call(req * 2)
+        }
+    }
+
+    #[ntex::test]
+    async fn test_from_tower() {
+        let polls = Rc::new(Cell::new(0));
+        let srv = Pipeline::new(FromTower::new(Srv(polls.clone())));
+        assert!(format!("{:?}", srv).contains("FromTower"));
+
+        assert_eq!(srv.call(1).await, Ok(2));
+        assert_eq!(srv.call(2).await, Ok(4));
+        // tower service is polled before each call
+        assert_eq!(polls.get(), 4);
+        assert_eq!(srv.into_service().unwrap().into_inner().0.get(), 4);
+    }
+
+    #[ntex::test]
+    async fn test_to_tower() {
+        let mut srv =
+            TowerService::new(fn_service(|req: usize| async move { Ok::<_, ()>(req + 1) }));
+        assert!(format!("{:?}", srv.clone()).contains("TowerService"));
+
+        assert_eq!(
+            poll_fn(|cx| tower::Service::<usize>::poll_ready(&mut srv, cx)).await,
+            Ok(())
+        );
+        assert_eq!(tower::Service::call(&mut srv, 1).await, Ok(2));
+    }
+
+    #[ntex::test]
+    async fn test_layer() {
+        let mw = TowerLayer::new(Double);
+        assert!(format!("{:?}", mw).contains("TowerLayer"));
+
+        let factory = apply(
+            mw,
+            fn_service(|req: usize| async move { Ok::<_, ()>(req + 1) }),
+        );
+        let srv = factory.pipeline(&()).await.unwrap();
+        assert_eq!(srv.call(1).await, Ok(3));
+    }
+}
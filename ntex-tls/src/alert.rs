@@ -0,0 +1,200 @@
+//! Tls alerts
+use std::{error::Error, fmt, io};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Tls alert direction
+pub enum AlertDirection {
+    /// Alert is sent to the peer
+    Sent,
+    /// Alert is received from the peer
+    Received,
+}
+
+/// Tls alert error
+///
+/// Handshake and record processing errors that are caused by, or result in,
+/// tls alert get reported as `io::Error` with `TlsAlert` as inner error.
+/// Use `TlsAlert::from_io_error()` to get alert from the acceptor's or
+/// connector's error.
+pub struct TlsAlert {
+    code: u8,
+    direction: AlertDirection,
+    error: Box<dyn Error + Send + Sync>,
+}
+
+impl TlsAlert {
+    #[cfg(any(test, feature = "openssl", feature = "rustls"))]
+    pub(crate) fn new<E>(code: u8, direction: AlertDirection, error: E) -> Self
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        TlsAlert {
+            code,
+            direction,
+            error: error.into(),
+        }
+    }
+
+    /// Get tls alert from io error
+    pub fn from_io_error(err: &io::Error) -> Option<&TlsAlert> {
+        err.get_ref().and_then(|e| e.downcast_ref::<TlsAlert>())
+    }
+
+    #[inline]
+    /// Alert code, as defined by RFC 8446
+    pub fn code(&self) -> u8 {
+        self.code
+    }
+
+    #[inline]
+    /// Alert direction
+    pub fn direction(&self) -> AlertDirection {
+        self.direction
+    }
+
+    /// Alert description, for example `unknown_ca`
+    pub fn description(&self) -> &'static str {
+        ALERTS
+            .iter()
+            .find(|(code, _)| *code == self.code)
+            .map(|(_, name)| *name)
+            .unwrap_or("unknown")
+    }
+
+    /// Underlying tls library error
+    pub fn error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        self.error.as_ref()
+    }
+
+    #[cfg(any(test, feature = "openssl"))]
+    /// Find alert code by description
+    pub(crate) fn code_by_name(name: &str) -> Option<u8> {
+        ALERTS
+            .iter()
+            .find(|(_, desc)| *desc == name)
+            .map(|(code, _)| *code)
+    }
+
+    #[cfg(any(test, feature = "openssl", feature = "rustls"))]
+    pub(crate) fn into_io_error(self) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, self)
+    }
+}
+
+impl fmt::Debug for TlsAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsAlert")
+            .field("code", &self.code)
+            .field("description", &self.description())
+            .field("direction", &self.direction)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl fmt::Display for TlsAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.direction {
+            AlertDirection::Sent => "sent",
+            AlertDirection::Received => "received",
+        };
+        write!(
+            f,
+            "Tls alert {} ({}) {}: {}",
+            self.description(),
+            self.code,
+            direction,
+            self.error
+        )
+    }
+}
+
+impl Error for TlsAlert {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+pub(crate) const CLOSE_NOTIFY: u8 = 0;
+pub(crate) const UNEXPECTED_MESSAGE: u8 = 10;
+pub(crate) const BAD_RECORD_MAC: u8 = 20;
+pub(crate) const RECORD_OVERFLOW: u8 = 22;
+pub(crate) const HANDSHAKE_FAILURE: u8 = 40;
+pub(crate) const BAD_CERTIFICATE: u8 = 42;
+pub(crate) const CERTIFICATE_REVOKED: u8 = 44;
+pub(crate) const CERTIFICATE_EXPIRED: u8 = 45;
+pub(crate) const ILLEGAL_PARAMETER: u8 = 47;
+pub(crate) const UNKNOWN_CA: u8 = 48;
+pub(crate) const DECODE_ERROR: u8 = 50;
+pub(crate) const DECRYPT_ERROR: u8 = 51;
+pub(crate) const PROTOCOL_VERSION: u8 = 70;
+pub(crate) const CERTIFICATE_REQUIRED: u8 = 116;
+pub(crate) const NO_APPLICATION_PROTOCOL: u8 = 120;
+
+const ALERTS: &[(u8, &str)] = &[
+    (CLOSE_NOTIFY, "close_notify"),
+    (UNEXPECTED_MESSAGE, "unexpected_message"),
+    (BAD_RECORD_MAC, "bad_record_mac"),
+    (21, "decryption_failed"),
+    (RECORD_OVERFLOW, "record_overflow"),
+    (30, "decompression_failure"),
+    (HANDSHAKE_FAILURE, "handshake_failure"),
+    (41, "no_certificate"),
+    (BAD_CERTIFICATE, "bad_certificate"),
+    (43, "unsupported_certificate"),
+    (CERTIFICATE_REVOKED, "certificate_revoked"),
+    (CERTIFICATE_EXPIRED, "certificate_expired"),
+    (46, "certificate_unknown"),
+    (ILLEGAL_PARAMETER, "illegal_parameter"),
+    (UNKNOWN_CA, "unknown_ca"),
+    (49, "access_denied"),
+    (DECODE_ERROR, "decode_error"),
+    (DECRYPT_ERROR, "decrypt_error"),
+    (60, "export_restriction"),
+    (PROTOCOL_VERSION, "protocol_version"),
+    (71, "insufficient_security"),
+    (80, "internal_error"),
+    (86, "inappropriate_fallback"),
+    (90, "user_canceled"),
+    (100, "no_renegotiation"),
+    (109, "missing_extension"),
+    (110, "unsupported_extension"),
+    (111, "certificate_unobtainable"),
+    (112, "unrecognized_name"),
+    (113, "bad_certificate_status_response"),
+    (114, "bad_certificate_hash_value"),
+    (115, "unknown_psk_identity"),
+    (CERTIFICATE_REQUIRED, "certificate_required"),
+    (NO_APPLICATION_PROTOCOL, "no_application_protocol"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert() {
+        let alert = TlsAlert::new(UNKNOWN_CA, AlertDirection::Received, "test");
+        assert_eq!(alert.code(), 48);
+        assert_eq!(alert.description(), "unknown_ca");
+        assert_eq!(alert.direction(), AlertDirection::Received);
+        assert_eq!(
+            format!("{}", alert),
+            "Tls alert unknown_ca (48) received: test"
+        );
+        assert!(format!("{:?}", alert).contains("TlsAlert"));
+
+        let err = alert.into_io_error();
+        let alert = TlsAlert::from_io_error(&err).unwrap();
+        assert_eq!(alert.code(), UNKNOWN_CA);
+        let err = io::Error::new(io::ErrorKind::Other, "test");
+        assert!(TlsAlert::from_io_error(&err).is_none());
+
+        assert_eq!(TlsAlert::code_by_name("certificate_expired"), Some(45));
+        assert_eq!(TlsAlert::code_by_name("unknown"), None);
+        assert_eq!(
+            TlsAlert::new(255, AlertDirection::Sent, "test").description(),
+            "unknown"
+        );
+    }
+}
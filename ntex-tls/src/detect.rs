@@ -0,0 +1,204 @@
+//! Tls and plaintext connections on the same listener
+use std::{fmt, task::Context, task::Poll};
+
+use ntex_io::{Filter, Io};
+use ntex_service::{Service, ServiceCtx, ServiceFactory};
+use ntex_util::time::{self, Millis};
+
+/// Content type of tls handshake record
+const HANDSHAKE: u8 = 0x16;
+
+/// Route tls and plaintext connections to different services
+///
+/// Service waits for the first bytes of the connection. Connections that
+/// start with tls handshake record are passed to the tls service, usually
+/// tls acceptor chained with the application service. All other connections,
+/// including connections that do not send any data within timeout, are passed
+/// to the plaintext service. Read bytes stay in the io's read buffer.
+///
+/// ```rust,ignore
+/// let factory = TlsDetect::new(
+///     TlsAcceptor::new(config).and_then(http_service),
+///     redirect_to_https_service,
+/// );
+/// ```
+pub struct TlsDetect<T, P> {
+    tls: T,
+    plain: P,
+    timeout: Millis,
+}
+
+impl<T, P> TlsDetect<T, P> {
+    /// Create detection service factory
+    pub fn new(tls: T, plain: P) -> Self {
+        Self {
+            tls,
+            plain,
+            timeout: Millis(5_000),
+        }
+    }
+
+    /// Set detection timeout.
+    ///
+    /// Connections that do not send any data within timeout are passed
+    /// to the plaintext service. Default is set to 5 seconds.
+    pub fn timeout<U: Into<Millis>>(mut self, timeout: U) -> Self {
+        self.timeout = timeout.into();
+        self
+    }
+}
+
+impl<T: Clone, P: Clone> Clone for TlsDetect<T, P> {
+    fn clone(&self) -> Self {
+        Self {
+            tls: self.tls.clone(),
+            plain: self.plain.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<T, P> fmt::Debug for TlsDetect<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsDetect")
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl<F, T, P, C> ServiceFactory<Io<F>, C> for TlsDetect<T, P>
+where
+    F: Filter,
+    T: ServiceFactory<Io<F>, C>,
+    P: ServiceFactory<
+        Io<F>,
+        C,
+        Response = T::Response,
+        Error = T::Error,
+        InitError = T::InitError,
+    >,
+    C: Clone,
+{
+    type Response = T::Response;
+    type Error = T::Error;
+    type Service = TlsDetectService<T::Service, P::Service>;
+    type InitError = T::InitError;
+
+    async fn create(&self, cfg: C) -> Result<Self::Service, Self::InitError> {
+        Ok(TlsDetectService {
+            tls: self.tls.create(cfg.clone()).await?,
+            plain: self.plain.create(cfg).await?,
+            timeout: self.timeout,
+        })
+    }
+}
+
+/// Tls and plaintext connections routing service
+pub struct TlsDetectService<T, P> {
+    tls: T,
+    plain: P,
+    timeout: Millis,
+}
+
+impl<T, P> fmt::Debug for TlsDetectService<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsDetectService")
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl<F, T, P> Service<Io<F>> for TlsDetectService<T, P>
+where
+    F: Filter,
+    T: Service<Io<F>>,
+    P: Service<Io<F>, Response = T::Response, Error = T::Error>,
+{
+    type Response = T::Response;
+    type Error = T::Error;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let not_ready = !self.tls.poll_ready(cx)?.is_ready();
+        if !self.plain.poll_ready(cx)?.is_ready() || not_ready {
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn poll_shutdown(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.tls.poll_shutdown(cx).is_ready() && self.plain.poll_shutdown(cx).is_ready()
+        {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    async fn call(
+        &self,
+        io: Io<F>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let tls = time::timeout(self.timeout, is_tls(&io))
+            .await
+            .unwrap_or(false);
+
+        if tls {
+            log::trace!("{}: Tls connection is detected", io.tag());
+            ctx.call(&self.tls, io).await
+        } else {
+            log::trace!("{}: Plaintext connection is detected", io.tag());
+            ctx.call(&self.plain, io).await
+        }
+    }
+}
+
+/// Wait for the first byte and check if it starts tls handshake record
+async fn is_tls<F: Filter>(io: &Io<F>) -> bool {
+    loop {
+        if let Some(b) = io.with_read_buf(|buf| buf.first().copied()) {
+            return b == HANDSHAKE;
+        }
+        match io.force_read_ready().await {
+            Ok(Some(_)) => continue,
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex_bytes::Bytes;
+    use ntex_io::testing::IoTest;
+    use ntex_service::{fn_service, Pipeline};
+
+    use super::*;
+
+    #[ntex::test]
+    async fn detect() {
+        let factory = TlsDetect::new(
+            fn_service(|io: Io| async move {
+                Ok::<_, ()>(("tls", io.with_read_buf(|buf| buf.len())))
+            }),
+            fn_service(|io: Io| async move {
+                Ok::<_, ()>(("plain", io.with_read_buf(|buf| buf.len())))
+            }),
+        )
+        .timeout(Millis(50));
+        let srv = Pipeline::new(factory.create(()).await.unwrap());
+
+        let (client, server) = IoTest::create();
+        client.remote_buffer_cap(1024);
+        client.write(Bytes::from_static(b"\x16\x03\x01\x00"));
+        assert_eq!(srv.call(Io::new(server)).await, Ok(("tls", 4)));
+
+        let (client, server) = IoTest::create();
+        client.remote_buffer_cap(1024);
+        client.write(Bytes::from_static(b"GET / HTTP/1.1\r\n"));
+        assert_eq!(srv.call(Io::new(server)).await, Ok(("plain", 16)));
+
+        let (_client, server) = IoTest::create();
+        assert_eq!(srv.call(Io::new(server)).await, Ok(("plain", 0)));
+    }
+}
@@ -9,7 +9,14 @@ pub mod openssl;
 #[cfg(feature = "rustls")]
 pub mod rustls;
 
+mod alert;
 mod counter;
+mod detect;
+mod pin;
+
+pub use self::alert::{AlertDirection, TlsAlert};
+pub use self::detect::{TlsDetect, TlsDetectService};
+pub use self::pin::{CertPins, Pin, PinError};
 
 /// Sets the maximum per-worker concurrent ssl connection establish process.
 ///
@@ -1,4 +1,4 @@
-use std::{fmt, io};
+use std::{fmt, io, rc::Rc};
 
 use ntex_bytes::PoolId;
 use ntex_io::{Io, Layer};
@@ -6,11 +6,13 @@ use ntex_net::connect::{Address, Connect, ConnectError, Connector as BaseConnect
 use ntex_service::{Pipeline, Service, ServiceCtx, ServiceFactory};
 use tls_openssl::ssl::SslConnector as BaseSslConnector;
 
-use super::{connect as connect_io, SslFilter};
+use super::{connect as connect_io, PeerCert, SslFilter};
+use crate::CertPins;
 
 pub struct SslConnector<T> {
     connector: Pipeline<BaseConnector<T>>,
     openssl: BaseSslConnector,
+    pins: Option<Rc<CertPins>>,
 }
 
 impl<T: Address> SslConnector<T> {
@@ -19,6 +21,7 @@ impl<T: Address> SslConnector<T> {
         SslConnector {
             connector: BaseConnector::default().into(),
             openssl: connector,
+            pins: None,
         }
     }
 
@@ -37,8 +40,18 @@ impl<T: Address> SslConnector<T> {
         Self {
             connector,
             openssl: self.openssl,
+            pins: self.pins,
         }
     }
+
+    /// Set certificate pins.
+    ///
+    /// Peer certificate of the connection to a pinned host
+    /// must match one of host's pins, otherwise connection fails.
+    pub fn pins(mut self, pins: CertPins) -> Self {
+        self.pins = Some(Rc::new(pins));
+        self
+    }
 }
 
 impl<T: Address> SslConnector<T> {
@@ -65,11 +78,21 @@ impl<T: Address> SslConnector<T> {
                 match connect_io(io, ssl).await {
                     Ok(io) => {
                         log::trace!("{}: SSL Handshake success: {:?}", tag, host);
+                        if let Some(ref pins) = self.pins {
+                            let cert = io
+                                .query::<PeerCert>()
+                                .as_ref()
+                                .and_then(|cert| cert.0.to_der().ok());
+                            if let Err(e) = pins.verify(&host, cert.as_deref()) {
+                                log::trace!("{}: Certificate pin error: {:?}", tag, e);
+                                return Err(io::Error::from(e).into());
+                            }
+                        }
                         Ok(io)
                     }
                     Err(e) => {
                         log::trace!("{}: SSL Handshake error: {:?}", tag, e);
-                        Err(io::Error::new(io::ErrorKind::Other, format!("{}", e)).into())
+                        Err(e.into())
                     }
                 }
             }
@@ -82,6 +105,7 @@ impl<T> Clone for SslConnector<T> {
         Self {
             connector: self.connector.clone(),
             openssl: self.openssl.clone(),
+            pins: self.pins.clone(),
         }
     }
 }
@@ -91,6 +115,7 @@ impl<T> fmt::Debug for SslConnector<T> {
         f.debug_struct("SslConnector(openssl)")
             .field("connector", &self.connector)
             .field("openssl", &self.openssl)
+            .field("pins", &self.pins)
             .finish()
     }
 }
@@ -6,6 +6,7 @@ use ntex_io::{types, Filter, FilterLayer, Io, Layer, ReadBuf, WriteBuf};
 use tls_openssl::ssl::{self, NameType, SslStream};
 use tls_openssl::x509::X509;
 
+use crate::alert::{self, AlertDirection, TlsAlert};
 use crate::{PskIdentity, Servername};
 
 mod connect;
@@ -73,6 +74,67 @@ impl SslFilter {
         buf.with_read_buf(|b| b.set_src(self.inner.borrow_mut().get_mut().source.take()));
         result
     }
+
+    /// Convert ssl error to io error, errors caused by tls alerts
+    /// get reported as `TlsAlert`
+    fn map_error(&self, err: ssl::Error) -> io::Error {
+        if let Some((code, direction)) = self.alert(&err) {
+            TlsAlert::new(code, direction, err).into_io_error()
+        } else {
+            map_to_ioerr(err)
+        }
+    }
+
+    fn alert(&self, err: &ssl::Error) -> Option<(u8, AlertDirection)> {
+        const PREFIXES: [&str; 4] =
+            ["sslv3 alert ", "tlsv1 alert ", "tlsv13 alert ", "tlsv1 "];
+
+        for e in err.ssl_error()?.errors() {
+            let reason = if let Some(reason) = e.reason() {
+                reason
+            } else {
+                continue;
+            };
+
+            // alerts received from the peer
+            for prefix in PREFIXES {
+                if let Some(name) = reason.strip_prefix(prefix) {
+                    if let Some(code) = TlsAlert::code_by_name(&name.replace(' ', "_")) {
+                        return Some((code, AlertDirection::Received));
+                    }
+                }
+            }
+
+            // alerts sent to the peer
+            let code = match reason {
+                "certificate verify failed" => self.verify_alert(),
+                "no shared cipher" | "no suitable signature algorithm" => {
+                    alert::HANDSHAKE_FAILURE
+                }
+                "unsupported protocol" | "wrong version number" => alert::PROTOCOL_VERSION,
+                "peer did not return a certificate" => alert::CERTIFICATE_REQUIRED,
+                "no application protocol" => alert::NO_APPLICATION_PROTOCOL,
+                "decryption failed or bad record mac" => alert::BAD_RECORD_MAC,
+                _ => continue,
+            };
+            return Some((code, AlertDirection::Sent));
+        }
+        None
+    }
+
+    /// Alert for certificate verification error
+    fn verify_alert(&self) -> u8 {
+        match self.inner.borrow().ssl().verify_result().as_raw() {
+            // X509_V_ERR_CERT_NOT_YET_VALID, X509_V_ERR_CERT_HAS_EXPIRED
+            9 | 10 => alert::CERTIFICATE_EXPIRED,
+            // X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT, X509_V_ERR_DEPTH_ZERO_SELF_SIGNED_CERT,
+            // X509_V_ERR_SELF_SIGNED_CERT_IN_CHAIN, X509_V_ERR_UNABLE_TO_GET_ISSUER_CERT_LOCALLY
+            2 | 18 | 19 | 20 => alert::UNKNOWN_CA,
+            // X509_V_ERR_CERT_REVOKED
+            23 => alert::CERTIFICATE_REVOKED,
+            _ => alert::BAD_CERTIFICATE,
+        }
+    }
 }
 
 impl FilterLayer for SslFilter {
@@ -172,7 +234,7 @@ impl FilterLayer for SslFilter {
                             }
                             Err(e) => {
                                 log::trace!("SSL Error: {:?}", e);
-                                Err(map_to_ioerr(e))
+                                Err(self.map_error(e))
                             }
                         };
                         return result;
@@ -200,7 +262,7 @@ impl FilterLayer for SslFilter {
                                 ssl::ErrorCode::WANT_READ | ssl::ErrorCode::WANT_WRITE => {
                                     Ok(())
                                 }
-                                _ => Err(map_to_ioerr(e)),
+                                _ => Err(self.map_error(e)),
                             };
                         }
                     }
@@ -239,8 +301,8 @@ pub async fn connect<F: Filter>(
     Ok(io)
 }
 
-async fn handle_result<T, F>(
-    io: &Io<F>,
+async fn handle_result<T, F: Filter>(
+    io: &Io<Layer<SslFilter, F>>,
     result: Result<T, ssl::Error>,
 ) -> io::Result<Option<T>> {
     match result {
@@ -254,7 +316,7 @@ async fn handle_result<T, F>(
                 }
             }
             ssl::ErrorCode::WANT_WRITE => Ok(None),
-            _ => Err(io::Error::new(io::ErrorKind::Other, e)),
+            _ => Err(io.filter().map_error(e)),
         },
     }
 }
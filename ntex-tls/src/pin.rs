@@ -0,0 +1,241 @@
+//! Certificate pinning for tls clients
+use std::{collections::HashMap, error, fmt, io};
+
+use sha2::{Digest, Sha256};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Certificate pin
+pub enum Pin {
+    /// Sha-256 hash of DER encoded certificate
+    CertSha256([u8; 32]),
+    /// Sha-256 hash of DER encoded certificate's SubjectPublicKeyInfo
+    SpkiSha256([u8; 32]),
+}
+
+#[derive(Clone, Debug, Default)]
+/// Per-host certificate pins
+///
+/// Peer certificate of the connection to a pinned host must match
+/// at least one of host's pins, otherwise connection fails. Multiple
+/// pins per host could be used for backup keys and key rotation.
+/// Connections to hosts without pins are not checked.
+pub struct CertPins {
+    hosts: HashMap<String, Vec<Pin>>,
+}
+
+impl CertPins {
+    /// Create empty pins set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add pin for the host
+    pub fn pin<H: Into<String>>(mut self, host: H, pin: Pin) -> Self {
+        let pins = self.hosts.entry(host.into().to_lowercase()).or_default();
+        if !pins.contains(&pin) {
+            pins.push(pin);
+        }
+        self
+    }
+
+    /// Add SubjectPublicKeyInfo sha-256 hash pin for the host
+    pub fn spki_sha256<H: Into<String>>(self, host: H, hash: [u8; 32]) -> Self {
+        self.pin(host, Pin::SpkiSha256(hash))
+    }
+
+    /// Add certificate sha-256 hash pin for the host
+    pub fn cert_sha256<H: Into<String>>(self, host: H, hash: [u8; 32]) -> Self {
+        self.pin(host, Pin::CertSha256(hash))
+    }
+
+    /// Check if host has any pins
+    pub fn is_pinned(&self, host: &str) -> bool {
+        self.hosts.contains_key(&host.to_lowercase())
+    }
+
+    /// Verify DER encoded peer certificate against host's pins
+    pub fn verify(&self, host: &str, cert: Option<&[u8]>) -> Result<(), PinError> {
+        let pins = if let Some(pins) = self.hosts.get(&host.to_lowercase()) {
+            pins
+        } else {
+            return Ok(());
+        };
+        let cert = cert.ok_or(PinError::NoCertificate)?;
+
+        let cert_hash: [u8; 32] = Sha256::digest(cert).into();
+        let mut spki_hash = None;
+        for pin in pins {
+            match pin {
+                Pin::CertSha256(hash) => {
+                    if *hash == cert_hash {
+                        return Ok(());
+                    }
+                }
+                Pin::SpkiSha256(hash) => {
+                    if spki_hash.is_none() {
+                        let spki = spki(cert).ok_or(PinError::InvalidCertificate)?;
+                        spki_hash = Some(<[u8; 32]>::from(Sha256::digest(spki)));
+                    }
+                    if spki_hash.as_ref() == Some(hash) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        Err(PinError::Mismatch)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Certificate pin verification error
+pub enum PinError {
+    /// Peer did not present certificate
+    NoCertificate,
+    /// Peer certificate cannot be parsed
+    InvalidCertificate,
+    /// Peer certificate does not match any of host's pins
+    Mismatch,
+}
+
+impl fmt::Display for PinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PinError::NoCertificate => write!(f, "Peer certificate is not available"),
+            PinError::InvalidCertificate => write!(f, "Cannot parse peer certificate"),
+            PinError::Mismatch => write!(f, "Peer certificate does not match pins"),
+        }
+    }
+}
+
+impl error::Error for PinError {}
+
+impl From<PinError> for io::Error {
+    fn from(err: PinError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// DER element tag, content, whole element and remaining data
+type DerElement<'a> = (u8, &'a [u8], &'a [u8], &'a [u8]);
+
+/// Read DER element
+fn der_read(buf: &[u8]) -> Option<DerElement<'_>> {
+    let tag = *buf.first()?;
+    let first = *buf.get(1)? as usize;
+    let (len, hdr) = if first < 0x80 {
+        (first, 2)
+    } else {
+        let num = first & 0x7f;
+        if num == 0 || num > 4 {
+            return None;
+        }
+        let len = buf
+            .get(2..2 + num)?
+            .iter()
+            .fold(0usize, |len, b| (len << 8) | *b as usize);
+        (len, 2 + num)
+    };
+    let end = hdr.checked_add(len)?;
+    if end > buf.len() {
+        None
+    } else {
+        Some((tag, &buf[hdr..end], &buf[..end], &buf[end..]))
+    }
+}
+
+/// Extract DER encoded SubjectPublicKeyInfo from DER encoded x509 certificate
+fn spki(cert: &[u8]) -> Option<&[u8]> {
+    const SEQUENCE: u8 = 0x30;
+    const VERSION: u8 = 0xa0;
+
+    let (tag, cert, _, _) = der_read(cert)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+    let (tag, mut tbs, _, _) = der_read(cert)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+    if tbs.first() == Some(&VERSION) {
+        tbs = der_read(tbs)?.3;
+    }
+    // serial number, signature algorithm, issuer, validity, subject
+    for _ in 0..5 {
+        tbs = der_read(tbs)?.3;
+    }
+    let (tag, _, spki, _) = der_read(tbs)?;
+    if tag == SEQUENCE {
+        Some(spki)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn der_parse() {
+        assert_eq!(der_read(&[0x02, 0x01, 0x05, 0xff]).unwrap().1, &[0x05]);
+        assert_eq!(der_read(&[0x02, 0x01, 0x05, 0xff]).unwrap().3, &[0xff]);
+        assert!(der_read(&[0x02, 0x05, 0x05]).is_none());
+        assert!(der_read(&[0x02, 0x80]).is_none());
+
+        let mut long = vec![0x04, 0x81, 0x80];
+        long.extend_from_slice(&[0; 128]);
+        assert_eq!(der_read(&long).unwrap().1.len(), 128);
+    }
+
+    #[test]
+    fn pins() {
+        let cert = b"not a certificate";
+        let hash: [u8; 32] = Sha256::digest(cert).into();
+
+        let pins = CertPins::new()
+            .cert_sha256("Example.com", [0; 32])
+            .cert_sha256("example.com", hash);
+        assert!(pins.is_pinned("EXAMPLE.com"));
+        assert!(!pins.is_pinned("www.example.com"));
+        assert_eq!(pins.verify("example.com", Some(cert)), Ok(()));
+        assert_eq!(pins.verify("other.com", None), Ok(()));
+        assert_eq!(
+            pins.verify("example.com", None),
+            Err(PinError::NoCertificate)
+        );
+        assert_eq!(
+            pins.verify("example.com", Some(b"other")),
+            Err(PinError::Mismatch)
+        );
+
+        let pins = CertPins::new().spki_sha256("example.com", hash);
+        assert_eq!(
+            pins.verify("example.com", Some(cert)),
+            Err(PinError::InvalidCertificate)
+        );
+    }
+
+    #[test]
+    fn pins_spki() {
+        const SPKI: [u8; 32] = [
+            0x62, 0x19, 0x26, 0xde, 0xb3, 0x35, 0x0f, 0x5a, 0x88, 0x40, 0x3d, 0x30, 0x10,
+            0x46, 0xd6, 0x0d, 0xb3, 0xed, 0x26, 0x64, 0x76, 0xf2, 0xc6, 0x13, 0xb1, 0xb0,
+            0x12, 0xc1, 0x94, 0xe5, 0xa1, 0x5e,
+        ];
+
+        let cert_file =
+            &mut std::io::BufReader::new(std::fs::File::open("examples/cert.pem").unwrap());
+        let cert = rustls_pemfile::certs(cert_file).next().unwrap().unwrap();
+
+        let pins = CertPins::new()
+            .spki_sha256("localhost", [0; 32])
+            .spki_sha256("localhost", SPKI);
+        assert_eq!(pins.verify("localhost", Some(cert.as_ref())), Ok(()));
+
+        let pins = CertPins::new().spki_sha256("localhost", [0; 32]);
+        assert_eq!(
+            pins.verify("localhost", Some(cert.as_ref())),
+            Err(PinError::Mismatch)
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use std::task::{Context, Poll};
-use std::{io, sync::Arc};
+use std::{fmt, io, sync::Arc};
 
 use tls_rust::ServerConfig;
 
@@ -7,16 +7,17 @@ use ntex_io::{Filter, Io, Layer};
 use ntex_service::{Service, ServiceCtx, ServiceFactory};
 use ntex_util::time::Millis;
 
+use super::hello::{ClientHelloAction, ClientHelloHandler, ClientHelloInfo};
 use super::TlsServerFilter;
 use crate::{counter::Counter, MAX_SSL_ACCEPT_COUNTER};
 
-#[derive(Debug)]
 /// Support `SSL` connections via rustls package
 ///
 /// `rust-tls` feature enables `RustlsAcceptor` type
 pub struct TlsAcceptor {
     config: Arc<ServerConfig>,
     timeout: Millis,
+    client_hello: Option<ClientHelloHandler>,
 }
 
 impl TlsAcceptor {
@@ -25,6 +26,7 @@ impl TlsAcceptor {
         Self {
             config,
             timeout: Millis(5_000),
+            client_hello: None,
         }
     }
 
@@ -35,6 +37,29 @@ impl TlsAcceptor {
         self.timeout = timeout.into();
         self
     }
+
+    /// Set client hello handler.
+    ///
+    /// Handler is called with properties of the client hello message
+    /// before handshake starts. Handler could reject connection, select
+    /// different server config or attach tag to the io.
+    pub fn client_hello<T>(mut self, f: T) -> Self
+    where
+        T: Fn(&ClientHelloInfo) -> ClientHelloAction + Send + Sync + 'static,
+    {
+        self.client_hello = Some(Arc::new(f));
+        self
+    }
+}
+
+impl fmt::Debug for TlsAcceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsAcceptor")
+            .field("config", &self.config)
+            .field("timeout", &self.timeout)
+            .field("client_hello", &self.client_hello.is_some())
+            .finish()
+    }
 }
 
 impl From<ServerConfig> for TlsAcceptor {
@@ -48,6 +73,7 @@ impl Clone for TlsAcceptor {
         Self {
             config: self.config.clone(),
             timeout: self.timeout,
+            client_hello: self.client_hello.clone(),
         }
     }
 }
@@ -63,20 +89,32 @@ impl<F: Filter, C> ServiceFactory<Io<F>, C> for TlsAcceptor {
             Ok(TlsAcceptorService {
                 config: self.config.clone(),
                 timeout: self.timeout,
+                client_hello: self.client_hello.clone(),
                 conns: conns.clone(),
             })
         })
     }
 }
 
-#[derive(Debug)]
 /// RusTLS based `Acceptor` service
 pub struct TlsAcceptorService {
     config: Arc<ServerConfig>,
     timeout: Millis,
+    client_hello: Option<ClientHelloHandler>,
     conns: Counter,
 }
 
+impl fmt::Debug for TlsAcceptorService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsAcceptorService")
+            .field("config", &self.config)
+            .field("timeout", &self.timeout)
+            .field("client_hello", &self.client_hello.is_some())
+            .field("conns", &self.conns)
+            .finish()
+    }
+}
+
 impl<F: Filter> Service<Io<F>> for TlsAcceptorService {
     type Response = Io<Layer<TlsServerFilter, F>>;
     type Error = io::Error;
@@ -95,6 +133,16 @@ impl<F: Filter> Service<Io<F>> for TlsAcceptorService {
         _: ServiceCtx<'_, Self>,
     ) -> Result<Self::Response, Self::Error> {
         let _guard = self.conns.get();
-        super::TlsServerFilter::create(io, self.config.clone(), self.timeout).await
+        if let Some(ref handler) = self.client_hello {
+            TlsServerFilter::create_with_hello(
+                io,
+                self.config.clone(),
+                handler,
+                self.timeout,
+            )
+            .await
+        } else {
+            TlsServerFilter::create(io, self.config.clone(), self.timeout).await
+        }
     }
 }
@@ -7,12 +7,13 @@ use ntex_io::{types, Filter, FilterLayer, Io, Layer, ReadBuf, WriteBuf};
 use ntex_util::ready;
 use tls_rust::{pki_types::ServerName, ClientConfig, ClientConnection};
 
-use super::{PeerCert, PeerCertChain, Wrapper};
+use super::{map_error, map_io_error, PeerCert, PeerCertChain, Staging, Wrapper};
 
 #[derive(Debug)]
 /// An implementation of SSL streams
 pub struct TlsClientFilter {
     session: RefCell<ClientConnection>,
+    staging: Staging,
 }
 
 impl FilterLayer for TlsClientFilter {
@@ -72,11 +73,11 @@ impl FilterLayer for TlsClientFilter {
                             Err(err) => return Err(err),
                         };
                         src.split_to(n);
-                        let state = session
-                            .process_new_packets()
-                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                        let state = session.process_new_packets().map_err(map_error)?;
 
                         let new_b = state.plaintext_bytes_to_read();
+                        self.staging
+                            .set(buf.memory_pool(), new_b + state.tls_bytes_to_write());
                         if new_b > 0 {
                             dst.reserve(new_b);
                             let chunk: &mut [u8] =
@@ -84,6 +85,10 @@ impl FilterLayer for TlsClientFilter {
                             let v = session.reader().read(chunk)?;
                             unsafe { dst.advance_mut(v) };
                             new_bytes += v;
+                            self.staging.set(
+                                buf.memory_pool(),
+                                new_b - v + state.tls_bytes_to_write(),
+                            );
                         } else {
                             break;
                         }
@@ -135,6 +140,7 @@ impl TlsClientFilter {
             .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
         let filter = TlsClientFilter {
             session: RefCell::new(session),
+            staging: Staging::default(),
         };
         let io = io.add_filter(filter);
 
@@ -183,7 +189,7 @@ impl TlsClientFilter {
                     })
                     .await?;
                 }
-                Err(e) => return Err(e),
+                Err(e) => return Err(map_io_error(e)),
             }
         }
     }
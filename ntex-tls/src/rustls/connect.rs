@@ -1,4 +1,4 @@
-use std::{fmt, io, sync::Arc};
+use std::{fmt, io, rc::Rc, sync::Arc};
 
 use ntex_bytes::PoolId;
 use ntex_io::{Io, Layer};
@@ -6,12 +6,14 @@ use ntex_net::connect::{Address, Connect, ConnectError, Connector as BaseConnect
 use ntex_service::{Pipeline, Service, ServiceCtx, ServiceFactory};
 use tls_rust::{pki_types::ServerName, ClientConfig};
 
-use super::TlsClientFilter;
+use super::{PeerCert, TlsClientFilter};
+use crate::CertPins;
 
 /// Rustls connector factory
 pub struct TlsConnector<T> {
     connector: Pipeline<BaseConnector<T>>,
     config: Arc<ClientConfig>,
+    pins: Option<Rc<CertPins>>,
 }
 
 impl<T: Address> From<Arc<ClientConfig>> for TlsConnector<T> {
@@ -19,6 +21,7 @@ impl<T: Address> From<Arc<ClientConfig>> for TlsConnector<T> {
         TlsConnector {
             config,
             connector: BaseConnector::default().into(),
+            pins: None,
         }
     }
 }
@@ -28,6 +31,7 @@ impl<T: Address> TlsConnector<T> {
         TlsConnector {
             config: Arc::new(config),
             connector: BaseConnector::default().into(),
+            pins: None,
         }
     }
 
@@ -45,8 +49,18 @@ impl<T: Address> TlsConnector<T> {
         Self {
             connector,
             config: self.config,
+            pins: self.pins,
         }
     }
+
+    /// Set certificate pins.
+    ///
+    /// Peer certificate of the connection to a pinned host
+    /// must match one of host's pins, otherwise connection fails.
+    pub fn pins(mut self, pins: CertPins) -> Self {
+        self.pins = Some(Rc::new(pins));
+        self
+    }
 }
 
 impl<T: Address> TlsConnector<T> {
@@ -59,19 +73,27 @@ impl<T: Address> TlsConnector<T> {
         Connect<T>: From<U>,
     {
         let req = Connect::from(message);
-        let host = req.host().split(':').next().unwrap().to_owned();
+        let hostname = req.host().split(':').next().unwrap().to_owned();
         let io = self.connector.call(req).await?;
 
-        log::trace!("{}: SSL Handshake start for: {:?}", io.tag(), host);
+        log::trace!("{}: SSL Handshake start for: {:?}", io.tag(), hostname);
 
         let tag = io.tag();
         let config = self.config.clone();
-        let host = ServerName::try_from(host)
+        let host = ServerName::try_from(hostname.clone())
             .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{}", e)))?;
 
         match TlsClientFilter::create(io, config, host.clone()).await {
             Ok(io) => {
                 log::trace!("{}: TLS Handshake success: {:?}", tag, &host);
+                if let Some(ref pins) = self.pins {
+                    let cert = io.query::<PeerCert<'_>>();
+                    let cert = cert.as_ref().map(|cert| cert.0.as_ref());
+                    if let Err(e) = pins.verify(&hostname, cert) {
+                        log::trace!("{}: Certificate pin error: {:?}", tag, e);
+                        return Err(io::Error::from(e).into());
+                    }
+                }
                 Ok(io)
             }
             Err(e) => {
@@ -87,6 +109,7 @@ impl<T> Clone for TlsConnector<T> {
         Self {
             config: self.config.clone(),
             connector: self.connector.clone(),
+            pins: self.pins.clone(),
         }
     }
 }
@@ -95,6 +118,7 @@ impl<T> fmt::Debug for TlsConnector<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("TlsConnector(rustls)")
             .field("connector", &self.connector)
+            .field("pins", &self.pins)
             .finish()
     }
 }
@@ -0,0 +1,165 @@
+use std::{any::Any, fmt, io, rc::Rc, sync::Arc};
+
+use ntex_io::{Filter, Io};
+use tls_rust::server::{Acceptor, ServerConnection};
+use tls_rust::{CipherSuite, ServerConfig, SignatureScheme};
+
+use super::map_error;
+
+/// Client hello handler
+pub(super) type ClientHelloHandler =
+    Arc<dyn Fn(&ClientHelloInfo) -> ClientHelloAction + Send + Sync>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Properties of the client hello message
+///
+/// Client hello info of the accepted connection could be queried
+/// from io with `io.query::<ClientHelloInfo>()`.
+pub struct ClientHelloInfo {
+    /// Server name indication
+    pub server_name: Option<String>,
+    /// Offered application protocols
+    pub alpn: Vec<Vec<u8>>,
+    /// Offered cipher suites, in the client's order
+    pub cipher_suites: Vec<CipherSuite>,
+    /// Offered signature schemes
+    pub signature_schemes: Vec<SignatureScheme>,
+}
+
+/// Client hello handler decision
+pub enum ClientHelloAction {
+    /// Continue handshake
+    Accept {
+        /// Use specified config instead of acceptor's config
+        config: Option<Arc<ServerConfig>>,
+        /// Attach tag to the io
+        tag: Option<ClientHelloTag>,
+    },
+    /// Reject connection
+    Reject,
+}
+
+impl ClientHelloAction {
+    /// Continue handshake with acceptor's config
+    pub fn accept() -> Self {
+        ClientHelloAction::Accept {
+            config: None,
+            tag: None,
+        }
+    }
+
+    /// Reject connection
+    pub fn reject() -> Self {
+        ClientHelloAction::Reject
+    }
+
+    /// Continue handshake with specified config
+    pub fn config(self, cfg: Arc<ServerConfig>) -> Self {
+        match self {
+            ClientHelloAction::Accept { tag, .. } => ClientHelloAction::Accept {
+                tag,
+                config: Some(cfg),
+            },
+            ClientHelloAction::Reject => ClientHelloAction::Reject,
+        }
+    }
+
+    /// Attach tag to the io
+    ///
+    /// Tag could be queried from io with `io.query::<ClientHelloTag>()`.
+    pub fn tag<T: Any>(self, tag: T) -> Self {
+        match self {
+            ClientHelloAction::Accept { config, .. } => ClientHelloAction::Accept {
+                config,
+                tag: Some(ClientHelloTag(Rc::new(tag))),
+            },
+            ClientHelloAction::Reject => ClientHelloAction::Reject,
+        }
+    }
+}
+
+impl fmt::Debug for ClientHelloAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientHelloAction::Accept { config, tag } => f
+                .debug_struct("ClientHelloAction::Accept")
+                .field("config", &config.is_some())
+                .field("tag", tag)
+                .finish(),
+            ClientHelloAction::Reject => write!(f, "ClientHelloAction::Reject"),
+        }
+    }
+}
+
+#[derive(Clone)]
+/// Connection tag attached by client hello handler
+pub struct ClientHelloTag(Rc<dyn Any>);
+
+impl ClientHelloTag {
+    /// Get reference to tag's value
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref::<T>()
+    }
+}
+
+impl fmt::Debug for ClientHelloTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientHelloTag").finish()
+    }
+}
+
+/// Read client hello and create server session
+pub(super) async fn accept<F: Filter>(
+    io: &Io<F>,
+    cfg: Arc<ServerConfig>,
+    handler: &ClientHelloHandler,
+) -> io::Result<(ServerConnection, ClientHelloInfo, Option<ClientHelloTag>)> {
+    let mut acceptor = Acceptor::default();
+
+    let accepted = loop {
+        io.with_read_buf(|buf| {
+            if !buf.is_empty() {
+                let n = acceptor.read_tls(&mut io::Cursor::new(&buf[..]))?;
+                buf.split_to(n);
+            }
+            Ok::<_, io::Error>(())
+        })?;
+
+        match acceptor.accept() {
+            Ok(Some(accepted)) => break accepted,
+            Ok(None) => {
+                if io.force_read_ready().await?.is_none() {
+                    return Err(io::Error::new(io::ErrorKind::Other, "disconnected"));
+                }
+            }
+            Err((err, _)) => return Err(map_error(err)),
+        }
+    };
+
+    let hello = accepted.client_hello();
+    let info = ClientHelloInfo {
+        server_name: hello.server_name().map(|name| name.to_string()),
+        alpn: hello
+            .alpn()
+            .map(|protos| protos.map(|p| p.to_vec()).collect())
+            .unwrap_or_default(),
+        cipher_suites: hello.cipher_suites().to_vec(),
+        signature_schemes: hello.signature_schemes().to_vec(),
+    };
+
+    match (handler)(&info) {
+        ClientHelloAction::Accept { config, tag } => {
+            let session = accepted
+                .into_connection(config.unwrap_or(cfg))
+                .map_err(|(err, _)| map_error(err))?;
+            Ok((session, info, tag))
+        }
+        ClientHelloAction::Reject => {
+            log::trace!("{}: Tls connection is rejected: {:?}", io.tag(), info);
+            Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                "Tls connection is rejected",
+            ))
+        }
+    }
+}
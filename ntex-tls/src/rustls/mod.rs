@@ -1,17 +1,22 @@
 //! An implementation of SSL streams for ntex backed by OpenSSL
-use std::{cmp, io};
+use std::{cell::Cell, cmp, io};
 
+use ntex_bytes::PoolRef;
 use ntex_io::WriteBuf;
-use tls_rust::pki_types::CertificateDer;
+use tls_rust::{pki_types::CertificateDer, CertificateError, Error};
+
+use crate::alert::{self, AlertDirection, TlsAlert};
 
 mod accept;
 mod client;
 mod connect;
+mod hello;
 mod server;
 
 pub use self::accept::{TlsAcceptor, TlsAcceptorService};
 pub use self::client::TlsClientFilter;
 pub use self::connect::TlsConnector;
+pub use self::hello::{ClientHelloAction, ClientHelloInfo, ClientHelloTag};
 pub use self::server::TlsServerFilter;
 
 /// Connection's peer cert
@@ -22,6 +27,102 @@ pub struct PeerCert<'a>(pub CertificateDer<'a>);
 #[derive(Debug)]
 pub struct PeerCertChain<'a>(pub Vec<CertificateDer<'a>>);
 
+/// Convert rustls error to io error, errors caused by tls alerts
+/// get reported as `TlsAlert`
+pub(crate) fn map_error(err: Error) -> io::Error {
+    let alert = match err {
+        Error::AlertReceived(desc) => Some((u8::from(desc), AlertDirection::Received)),
+        Error::InvalidCertificate(ref e) => Some((
+            match e {
+                CertificateError::Expired | CertificateError::NotValidYet => {
+                    alert::CERTIFICATE_EXPIRED
+                }
+                CertificateError::UnknownIssuer => alert::UNKNOWN_CA,
+                CertificateError::Revoked => alert::CERTIFICATE_REVOKED,
+                CertificateError::BadSignature => alert::DECRYPT_ERROR,
+                _ => alert::BAD_CERTIFICATE,
+            },
+            AlertDirection::Sent,
+        )),
+        Error::InappropriateMessage { .. }
+        | Error::InappropriateHandshakeMessage { .. } => {
+            Some((alert::UNEXPECTED_MESSAGE, AlertDirection::Sent))
+        }
+        Error::InvalidMessage(_) => Some((alert::DECODE_ERROR, AlertDirection::Sent)),
+        Error::DecryptError => Some((alert::BAD_RECORD_MAC, AlertDirection::Sent)),
+        Error::PeerSentOversizedRecord => {
+            Some((alert::RECORD_OVERFLOW, AlertDirection::Sent))
+        }
+        Error::NoCertificatesPresented => {
+            Some((alert::CERTIFICATE_REQUIRED, AlertDirection::Sent))
+        }
+        Error::NoApplicationProtocol => {
+            Some((alert::NO_APPLICATION_PROTOCOL, AlertDirection::Sent))
+        }
+        Error::PeerIncompatible(_) => {
+            Some((alert::HANDSHAKE_FAILURE, AlertDirection::Sent))
+        }
+        Error::PeerMisbehaved(_) => Some((alert::ILLEGAL_PARAMETER, AlertDirection::Sent)),
+        _ => None,
+    };
+
+    if let Some((code, direction)) = alert {
+        TlsAlert::new(code, direction, err).into_io_error()
+    } else {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Convert io error produced by rustls session
+pub(crate) fn map_io_error(err: io::Error) -> io::Error {
+    if err.get_ref().map(|e| e.is::<Error>()).unwrap_or(false) {
+        let err = err.into_inner().unwrap().downcast::<Error>().unwrap();
+        map_error(*err)
+    } else {
+        err
+    }
+}
+
+/// Rustls session buffers accounting
+///
+/// Plaintext and ciphertext buffered by rustls session get accounted
+/// in io memory pool as external allocations.
+#[derive(Debug, Default)]
+pub(crate) struct Staging {
+    pool: Cell<Option<PoolRef>>,
+    size: Cell<usize>,
+}
+
+impl Staging {
+    /// Update size of buffered data
+    pub(crate) fn set(&self, pool: PoolRef, size: usize) {
+        let prev = self.size.replace(size);
+        match self.pool.replace(Some(pool)) {
+            Some(old) if old.id() == pool.id() => {
+                if size > prev {
+                    pool.acquire_external(size - prev);
+                } else {
+                    pool.release_external(prev - size);
+                }
+            }
+            old => {
+                if let Some(old) = old {
+                    old.release_external(prev);
+                }
+                pool.acquire_external(size);
+            }
+        }
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.get() {
+            pool.release_external(self.size.get());
+        }
+    }
+}
+
 pub(crate) struct Wrapper<'a, 'b>(&'a WriteBuf<'b>);
 
 impl<'a, 'b> io::Read for Wrapper<'a, 'b> {
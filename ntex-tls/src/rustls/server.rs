@@ -9,12 +9,16 @@ use tls_rust::{ServerConfig, ServerConnection};
 
 use crate::Servername;
 
-use super::{PeerCert, PeerCertChain, Wrapper};
+use super::hello::{self, ClientHelloHandler, ClientHelloInfo, ClientHelloTag};
+use super::{map_error, map_io_error, PeerCert, PeerCertChain, Staging, Wrapper};
 
 #[derive(Debug)]
 /// An implementation of SSL streams
 pub struct TlsServerFilter {
     session: RefCell<ServerConnection>,
+    staging: Staging,
+    hello: Option<ClientHelloInfo>,
+    tag: Option<ClientHelloTag>,
 }
 
 impl FilterLayer for TlsServerFilter {
@@ -57,6 +61,14 @@ impl FilterLayer for TlsServerFilter {
             } else {
                 None
             }
+        } else if id == any::TypeId::of::<ClientHelloInfo>() {
+            self.hello
+                .as_ref()
+                .map(|hello| Box::new(hello.clone()) as Box<dyn any::Any>)
+        } else if id == any::TypeId::of::<ClientHelloTag>() {
+            self.tag
+                .as_ref()
+                .map(|tag| Box::new(tag.clone()) as Box<dyn any::Any>)
         } else {
             None
         }
@@ -80,11 +92,11 @@ impl FilterLayer for TlsServerFilter {
                             Err(err) => return Err(err),
                         };
                         src.split_to(n);
-                        let state = session
-                            .process_new_packets()
-                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                        let state = session.process_new_packets().map_err(map_error)?;
 
                         let new_b = state.plaintext_bytes_to_read();
+                        self.staging
+                            .set(buf.memory_pool(), new_b + state.tls_bytes_to_write());
                         if new_b > 0 {
                             dst.reserve(new_b);
                             let chunk: &mut [u8] =
@@ -92,6 +104,10 @@ impl FilterLayer for TlsServerFilter {
                             let v = session.reader().read(chunk)?;
                             unsafe { dst.advance_mut(v) };
                             new_bytes += v;
+                            self.staging.set(
+                                buf.memory_pool(),
+                                new_b - v + state.tls_bytes_to_write(),
+                            );
                         } else {
                             break;
                         }
@@ -144,60 +160,89 @@ impl TlsServerFilter {
                 .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
             let filter = TlsServerFilter {
                 session: RefCell::new(session),
+                staging: Staging::default(),
+                hello: None,
+                tag: None,
             };
-            let io = io.add_filter(filter);
-
-            let filter = io.filter();
-            loop {
-                let (result, wants_read, handshaking) = io.with_buf(|buf| {
-                    let mut session = filter.session.borrow_mut();
-                    let mut wrp = Wrapper(buf);
-                    let mut result = (
-                        session.complete_io(&mut wrp),
-                        session.wants_read(),
-                        session.is_handshaking(),
-                    );
-
-                    if result.0.is_ok() && session.wants_write() {
-                        result.0 = session.complete_io(&mut wrp);
-                    }
-                    result
-                })?;
+            Self::handshake(io.add_filter(filter)).await
+        })
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "rustls handshake timeout"))
+        .and_then(|item| item)
+    }
 
-                match result {
-                    Ok(_) => {
+    /// Read client hello and pass it to the handler before handshake
+    pub(super) async fn create_with_hello<F: Filter>(
+        io: Io<F>,
+        cfg: Arc<ServerConfig>,
+        handler: &ClientHelloHandler,
+        timeout: Millis,
+    ) -> Result<Io<Layer<TlsServerFilter, F>>, io::Error> {
+        time::timeout(timeout, async {
+            let (session, info, tag) = hello::accept(&io, cfg, handler).await?;
+            let filter = TlsServerFilter {
+                session: RefCell::new(session),
+                staging: Staging::default(),
+                hello: Some(info),
+                tag,
+            };
+            Self::handshake(io.add_filter(filter)).await
+        })
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "rustls handshake timeout"))
+        .and_then(|item| item)
+    }
+
+    async fn handshake<F: Filter>(
+        io: Io<Layer<TlsServerFilter, F>>,
+    ) -> Result<Io<Layer<TlsServerFilter, F>>, io::Error> {
+        let filter = io.filter();
+        loop {
+            let (result, wants_read, handshaking) = io.with_buf(|buf| {
+                let mut session = filter.session.borrow_mut();
+                let mut wrp = Wrapper(buf);
+                let mut result = (
+                    session.complete_io(&mut wrp),
+                    session.wants_read(),
+                    session.is_handshaking(),
+                );
+
+                if result.0.is_ok() && session.wants_write() {
+                    result.0 = session.complete_io(&mut wrp);
+                }
+                result
+            })?;
+
+            match result {
+                Ok(_) => {
+                    return Ok(io);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if !handshaking {
                         return Ok(io);
                     }
-                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                        if !handshaking {
-                            return Ok(io);
+                    poll_fn(|cx| {
+                        let read_ready = if wants_read {
+                            match ready!(io.poll_force_read_ready(cx))? {
+                                Some(_) => Ok(true),
+                                None => Err(io::Error::new(
+                                    io::ErrorKind::Other,
+                                    "disconnected",
+                                )),
+                            }?
+                        } else {
+                            true
+                        };
+                        if read_ready {
+                            Poll::Ready(Ok::<_, io::Error>(()))
+                        } else {
+                            Poll::Pending
                         }
-                        poll_fn(|cx| {
-                            let read_ready = if wants_read {
-                                match ready!(io.poll_force_read_ready(cx))? {
-                                    Some(_) => Ok(true),
-                                    None => Err(io::Error::new(
-                                        io::ErrorKind::Other,
-                                        "disconnected",
-                                    )),
-                                }?
-                            } else {
-                                true
-                            };
-                            if read_ready {
-                                Poll::Ready(Ok::<_, io::Error>(()))
-                            } else {
-                                Poll::Pending
-                            }
-                        })
-                        .await?;
-                    }
-                    Err(e) => return Err(e),
+                    })
+                    .await?;
                 }
+                Err(e) => return Err(map_io_error(e)),
             }
-        })
-        .await
-        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "rustls handshake timeout"))
-        .and_then(|item| item)
+        }
     }
 }
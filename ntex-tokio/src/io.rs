@@ -463,14 +463,81 @@ mod unixstream {
 
     impl IoStream for crate::UnixStream {
         fn start(self, read: ReadContext, write: WriteContext) -> Option<Box<dyn Handle>> {
+            let handle = UnixHandle {
+                cred: self.0.peer_cred().ok().map(|cred| types::PeerCred {
+                    pid: cred.pid(),
+                    uid: cred.uid(),
+                    gid: cred.gid(),
+                }),
+                context: peer_sec_context(&self.0),
+            };
             let io = Rc::new(RefCell::new(self.0));
 
             tokio::task::spawn_local(ReadTask::new(io.clone(), read));
             tokio::task::spawn_local(WriteTask::new(io, write));
+            Some(Box::new(handle))
+        }
+    }
+
+    struct UnixHandle {
+        cred: Option<types::PeerCred>,
+        context: Option<String>,
+    }
+
+    impl Handle for UnixHandle {
+        fn query(&self, id: any::TypeId) -> Option<Box<dyn any::Any>> {
+            if id == any::TypeId::of::<types::PeerCred>() {
+                if let Some(cred) = self.cred {
+                    return Some(Box::new(cred));
+                }
+            } else if id == any::TypeId::of::<types::PeerSecContext>() {
+                if let Some(ref context) = self.context {
+                    return Some(Box::new(types::PeerSecContext(context.clone())));
+                }
+            }
             None
         }
     }
 
+    #[cfg(target_os = "linux")]
+    /// Read peer's security context with `SO_PEERSEC` socket option
+    fn peer_sec_context(io: &UnixStream) -> Option<String> {
+        use std::os::unix::io::AsRawFd;
+
+        let mut buf = vec![0u8; 256];
+        loop {
+            let mut len = buf.len() as libc::socklen_t;
+            let res = unsafe {
+                libc::getsockopt(
+                    io.as_raw_fd(),
+                    libc::SOL_SOCKET,
+                    libc::SO_PEERSEC,
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    &mut len,
+                )
+            };
+            if res == 0 {
+                buf.truncate(len as usize);
+                while buf.last() == Some(&0) {
+                    buf.pop();
+                }
+                return String::from_utf8(buf).ok().filter(|s| !s.is_empty());
+            }
+            // buffer is too small for the context
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::ERANGE) && (len as usize) > buf.len() {
+                buf.resize(len as usize, 0);
+            } else {
+                return None;
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn peer_sec_context(_: &UnixStream) -> Option<String> {
+        None
+    }
+
     /// Read io task
     struct ReadTask {
         io: Rc<RefCell<UnixStream>>,
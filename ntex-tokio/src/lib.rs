@@ -1,6 +1,6 @@
-use std::{io::Result, net, net::SocketAddr, path::Path};
+use std::{io::Result, net, net::IpAddr, net::SocketAddr, path::Path};
 
-use ntex_bytes::PoolRef;
+use ntex_bytes::{ByteString, PoolRef};
 use ntex_io::Io;
 
 mod io;
@@ -28,6 +28,47 @@ pub async fn tcp_connect_in(addr: SocketAddr, pool: PoolRef) -> Result<Io> {
     Ok(Io::with_memory_pool(TcpStream(sock), pool))
 }
 
+/// Opens a TCP connection to a remote host, socket is bound to local
+/// address and network interface before connecting.
+///
+/// Binding to network interface is supported only on linux, android
+/// and fuchsia.
+pub async fn tcp_connect_bind(
+    addr: SocketAddr,
+    local_addr: Option<IpAddr>,
+    interface: Option<ByteString>,
+    pool: PoolRef,
+) -> Result<Io> {
+    let sock = if addr.is_ipv4() {
+        tokio::net::TcpSocket::new_v4()?
+    } else {
+        tokio::net::TcpSocket::new_v6()?
+    };
+    if let Some(ip) = local_addr {
+        sock.bind(SocketAddr::new(ip, 0))?;
+    }
+    if let Some(interface) = interface {
+        #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+        sock.bind_device(Some(interface.as_bytes()))?;
+
+        #[cfg(not(any(
+            target_os = "android",
+            target_os = "fuchsia",
+            target_os = "linux"
+        )))]
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!(
+                "Cannot bind to {:?}, not supported on this platform",
+                interface
+            ),
+        ));
+    }
+    let sock = sock.connect(addr).await?;
+    sock.set_nodelay(true)?;
+    Ok(Io::with_memory_pool(TcpStream(sock), pool))
+}
+
 #[cfg(unix)]
 /// Opens a unix stream connection.
 pub async fn unix_connect<'a, P>(addr: P) -> Result<Io>
@@ -0,0 +1,408 @@
+//! A bounded multi-producer, single-consumer, futures-aware, FIFO queue.
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::{fmt, panic::UnwindSafe, pin::Pin, task::Context, task::Poll, task::Waker};
+
+use futures_core::{FusedStream, Stream};
+
+use super::cell::{Cell, WeakCell};
+use super::mpsc::SendError;
+use crate::task::LocalWaker;
+
+/// Creates a bounded in-memory channel with buffered storage.
+///
+/// Channel buffers up to `capacity` messages, senders wait
+/// until receiver frees space in the buffer.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "Channel capacity must be greater than zero");
+
+    let shared = Cell::new(Shared {
+        capacity,
+        has_receiver: true,
+        buffer: VecDeque::with_capacity(capacity),
+        blocked_recv: LocalWaker::new(),
+        blocked_send: Vec::new(),
+    });
+    let sender = Sender {
+        shared: shared.clone(),
+    };
+    let receiver = Receiver { shared };
+    (sender, receiver)
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    buffer: VecDeque<T>,
+    capacity: usize,
+    blocked_recv: LocalWaker,
+    blocked_send: Vec<Waker>,
+    has_receiver: bool,
+}
+
+impl<T> Shared<T> {
+    fn wake_senders(&mut self) {
+        for waker in self.blocked_send.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The transmission end of a bounded channel.
+///
+/// This is created by the `channel` function.
+#[derive(Debug)]
+pub struct Sender<T> {
+    shared: Cell<Shared<T>>,
+}
+
+impl<T> Unpin for Sender<T> {}
+
+impl<T> Sender<T> {
+    /// Sends the provided message along this channel.
+    ///
+    /// Waits until there is free space in the channel's buffer.
+    pub async fn send(&self, item: T) -> Result<(), SendError<T>> {
+        let mut item = Some(item);
+        poll_fn(|cx| self.poll_send(&mut item, cx)).await
+    }
+
+    fn poll_send(
+        &self,
+        item: &mut Option<T>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), SendError<T>>> {
+        match self.try_send(item.take().unwrap()) {
+            Ok(()) => Poll::Ready(Ok(())),
+            Err(TrySendError::Closed(item)) => Poll::Ready(Err(SendError(item))),
+            Err(TrySendError::Full(val)) => {
+                *item = Some(val);
+                let blocked = &mut self.shared.get_mut().blocked_send;
+                if !blocked.iter().any(|w| w.will_wake(cx.waker())) {
+                    blocked.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Attempts to send a message without waiting.
+    ///
+    /// Returns error if the channel's buffer is full or receiver is gone.
+    pub fn try_send(&self, item: T) -> Result<(), TrySendError<T>> {
+        let shared = self.shared.get_mut();
+        if !shared.has_receiver {
+            return Err(TrySendError::Closed(item)); // receiver was dropped
+        }
+        if shared.buffer.len() >= shared.capacity {
+            return Err(TrySendError::Full(item));
+        }
+        shared.buffer.push_back(item);
+        shared.blocked_recv.wake();
+        Ok(())
+    }
+
+    /// Returns the channel's capacity
+    pub fn capacity(&self) -> usize {
+        self.shared.get_ref().capacity
+    }
+
+    /// Returns the number of messages in the channel's buffer
+    pub fn len(&self) -> usize {
+        self.shared.get_ref().buffer.len()
+    }
+
+    /// Returns `true` if the channel's buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.shared.get_ref().buffer.is_empty()
+    }
+
+    /// Closes the sender half
+    ///
+    /// This prevents any further messages from being sent on the channel while
+    /// still enabling the receiver to drain messages that are buffered.
+    pub fn close(&self) {
+        let shared = self.shared.get_mut();
+        shared.has_receiver = false;
+        shared.blocked_recv.wake();
+        shared.wake_senders();
+    }
+
+    /// Returns whether this channel is closed without needing a context.
+    pub fn is_closed(&self) -> bool {
+        self.shared.strong_count() == 1 || !self.shared.get_ref().has_receiver
+    }
+
+    /// Returns downgraded sender
+    pub fn downgrade(self) -> WeakSender<T> {
+        WeakSender {
+            shared: self.shared.downgrade(),
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Sender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let count = self.shared.strong_count();
+        let shared = self.shared.get_mut();
+
+        // check is last sender is about to drop
+        if shared.has_receiver && count == 2 {
+            // Wake up receiver as its stream has ended
+            shared.blocked_recv.wake();
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Weak sender type
+pub struct WeakSender<T> {
+    shared: WeakCell<Shared<T>>,
+}
+
+impl<T> WeakSender<T> {
+    /// Upgrade to `Sender<T>`
+    pub fn upgrade(&self) -> Option<Sender<T>> {
+        self.shared.upgrade().map(|shared| Sender { shared })
+    }
+}
+
+/// The receiving end of a bounded channel which implements the `Stream` trait.
+///
+/// This is created by the `channel` function.
+#[derive(Debug)]
+pub struct Receiver<T> {
+    shared: Cell<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    /// Create a Sender
+    pub fn sender(&self) -> Sender<T> {
+        Sender {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Closes the receiving half of a channel, without dropping it.
+    ///
+    /// This prevents any further messages from being sent on the channel
+    /// while still enabling the receiver to drain messages that are buffered.
+    pub fn close(&self) {
+        let shared = self.shared.get_mut();
+        shared.has_receiver = false;
+        shared.wake_senders();
+    }
+
+    /// Returns whether this channel is closed without needing a context.
+    pub fn is_closed(&self) -> bool {
+        self.shared.strong_count() == 1 || !self.shared.get_ref().has_receiver
+    }
+
+    /// Attempt to pull out the next value of this receiver, registering
+    /// the current task for wakeup if the value is not yet available,
+    /// and returning None if the stream is exhausted.
+    pub async fn recv(&self) -> Option<T> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempt to pull out the next value of this receiver, registering
+    /// the current task for wakeup if the value is not yet available,
+    /// and returning None if the stream is exhausted.
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let shared = self.shared.get_mut();
+
+        if let Some(msg) = shared.buffer.pop_front() {
+            shared.wake_senders();
+            Poll::Ready(Some(msg))
+        } else if shared.has_receiver {
+            shared.blocked_recv.register(cx.waker());
+            if self.shared.strong_count() == 1 {
+                // All senders have been dropped, so drain the buffer and end the
+                // stream.
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            }
+        } else {
+            Poll::Ready(None)
+        }
+    }
+}
+
+impl<T> Unpin for Receiver<T> {}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_recv(cx)
+    }
+}
+
+impl<T> FusedStream for Receiver<T> {
+    fn is_terminated(&self) -> bool {
+        self.is_closed()
+    }
+}
+
+impl<T> UnwindSafe for Receiver<T> {}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let shared = self.shared.get_mut();
+        shared.buffer.clear();
+        shared.has_receiver = false;
+        shared.wake_senders();
+    }
+}
+
+/// Error type for `try_send`
+pub enum TrySendError<T> {
+    /// Channel's buffer is full
+    Full(T),
+    /// Receiving end of a channel is dropped or channel is closed
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Check if channel's buffer is full
+    pub fn is_full(&self) -> bool {
+        matches!(self, TrySendError::Full(_))
+    }
+
+    /// Check if channel is closed
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TrySendError::Closed(_))
+    }
+
+    /// Returns the message that was attempted to be sent but failed.
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(item) | TrySendError::Closed(item) => item,
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => fmt.debug_tuple("Full").field(&"...").finish(),
+            TrySendError::Closed(_) => fmt.debug_tuple("Closed").field(&"...").finish(),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(fmt, "send failed because channel is full"),
+            TrySendError::Closed(_) => {
+                write!(fmt, "send failed because receiver is gone")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use super::*;
+    use crate::{future::lazy, future::stream_recv};
+
+    #[ntex_macros::rt_test2]
+    async fn test_bounded() {
+        let (tx, mut rx) = channel(2);
+        assert!(format!("{:?}", tx).contains("Sender"));
+        assert!(format!("{:?}", rx).contains("Receiver"));
+        assert_eq!(tx.capacity(), 2);
+        assert!(tx.is_empty());
+
+        tx.send("test").await.unwrap();
+        tx.try_send("test2").unwrap();
+        assert_eq!(tx.len(), 2);
+
+        let err = tx.try_send("test3").err().unwrap();
+        assert!(err.is_full());
+        assert!(format!("{:?}", err).contains("Full"));
+        assert!(format!("{}", err).contains("channel is full"));
+        assert_eq!(err.into_inner(), "test3");
+
+        // send waits for free space
+        let tx2 = tx.clone();
+        let mut fut = Box::pin(tx2.send("test3"));
+        assert!(lazy(|cx| fut.as_mut().poll(cx)).await.is_pending());
+
+        assert_eq!(stream_recv(&mut rx).await.unwrap(), "test");
+        assert!(lazy(|cx| fut.as_mut().poll(cx)).await.is_ready());
+        assert_eq!(stream_recv(&mut rx).await.unwrap(), "test2");
+        assert_eq!(stream_recv(&mut rx).await.unwrap(), "test3");
+
+        assert_eq!(
+            lazy(|cx| Pin::new(&mut rx).poll_next(cx)).await,
+            Poll::Pending
+        );
+        drop(fut);
+        drop(tx2);
+        drop(tx);
+        assert_eq!(stream_recv(&mut rx).await, None);
+
+        let (tx, _rx) = channel::<String>(1);
+        let weak_tx = tx.downgrade();
+        assert!(weak_tx.upgrade().is_some());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_closed() {
+        // blocked sender is woken up on receiver drop
+        let (tx, rx) = channel(1);
+        tx.send("test").await.unwrap();
+        let mut fut = Box::pin(tx.send("test2"));
+        assert!(lazy(|cx| fut.as_mut().poll(cx)).await.is_pending());
+        drop(rx);
+        assert_eq!(fut.await.err().unwrap().into_inner(), "test2");
+
+        let err = tx.try_send("test").err().unwrap();
+        assert!(err.is_closed());
+        assert!(format!("{}", err).contains("receiver is gone"));
+
+        let (tx, rx) = channel::<()>(1);
+        assert!(!tx.is_closed());
+        assert!(!rx.is_terminated());
+        tx.close();
+        assert!(tx.is_closed());
+        assert!(rx.is_closed());
+        assert!(rx.is_terminated());
+        assert!(tx.send(()).await.is_err());
+
+        let (tx, rx) = channel::<()>(1);
+        rx.close();
+        assert!(tx.is_closed());
+        assert!(tx.try_send(()).err().unwrap().is_closed());
+
+        let (tx, rx) = channel::<()>(1);
+        drop(tx);
+        assert!(rx.is_closed());
+        let _tx = rx.sender();
+        assert!(!rx.is_closed());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_capacity() {
+        let _ = channel::<()>(0);
+    }
+}
@@ -0,0 +1,332 @@
+//! A single-producer, multi-consumer, futures-aware broadcast queue.
+//!
+//! Every message sent is delivered to all receivers. Messages are stored in
+//! a ring buffer of fixed capacity, if a receiver falls behind and the buffer
+//! overwrites messages it has not yet seen, next receive operation returns
+//! [`RecvError::Lagged`] with number of skipped messages and the receiver
+//! continues from the oldest retained message.
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::{fmt, task::Context, task::Poll, task::Waker};
+
+use super::cell::Cell;
+use super::mpsc::SendError;
+
+/// Creates a broadcast channel with ring buffer of `capacity` messages.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "Channel capacity must be greater than zero");
+
+    let shared = Cell::new(Shared {
+        capacity,
+        buffer: VecDeque::with_capacity(capacity),
+        head: 0,
+        receivers: 1,
+        closed: false,
+        wakers: Vec::new(),
+    });
+    let sender = Sender {
+        shared: shared.clone(),
+    };
+    let receiver = Receiver { shared, pos: 0 };
+    (sender, receiver)
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    buffer: VecDeque<T>,
+    capacity: usize,
+    /// Sequence number of the first message in the buffer
+    head: u64,
+    receivers: usize,
+    closed: bool,
+    wakers: Vec<Waker>,
+}
+
+impl<T> Shared<T> {
+    /// Sequence number of the next message
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+
+    fn wake_receivers(&mut self) {
+        for waker in self.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The transmission end of a broadcast channel.
+///
+/// This is created by the `channel` function.
+#[derive(Debug)]
+pub struct Sender<T> {
+    shared: Cell<Shared<T>>,
+}
+
+impl<T: Clone> Sender<T> {
+    /// Sends the provided message to all active receivers.
+    ///
+    /// Returns number of receivers the message is sent to. If the buffer is
+    /// full, the oldest message is dropped.
+    pub fn send(&self, item: T) -> Result<usize, SendError<T>> {
+        let shared = self.shared.get_mut();
+        if shared.receivers == 0 {
+            return Err(SendError(item)); // all receivers were dropped
+        }
+        if shared.buffer.len() == shared.capacity {
+            shared.buffer.pop_front();
+            shared.head += 1;
+        }
+        shared.buffer.push_back(item);
+        shared.wake_receivers();
+        Ok(shared.receivers)
+    }
+
+    /// Creates a new receiver.
+    ///
+    /// Receiver gets all messages sent after this call.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let shared = self.shared.get_mut();
+        shared.receivers += 1;
+        Receiver {
+            pos: shared.tail(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    /// Returns number of active receivers
+    pub fn receiver_count(&self) -> usize {
+        self.shared.get_ref().receivers
+    }
+
+    /// Returns whether all receivers are dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.get_ref().receivers == 0
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let shared = self.shared.get_mut();
+        shared.closed = true;
+        shared.wake_receivers();
+    }
+}
+
+/// The receiving end of a broadcast channel.
+///
+/// This is created by the `channel` function or by `Sender::subscribe()`.
+#[derive(Debug)]
+pub struct Receiver<T> {
+    shared: Cell<Shared<T>>,
+    pos: u64,
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receive next message, waiting until it is available.
+    ///
+    /// Returns `RecvError::Closed` if sender is dropped and all buffered
+    /// messages are received.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempt to receive next message, registering the current task for
+    /// wakeup if the message is not yet available.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        match self.try_recv() {
+            Ok(item) => Poll::Ready(Ok(item)),
+            Err(TryRecvError::Lagged(n)) => Poll::Ready(Err(RecvError::Lagged(n))),
+            Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError::Closed)),
+            Err(TryRecvError::Empty) => {
+                let wakers = &mut self.shared.get_mut().wakers;
+                if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                    wakers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Attempt to receive next message without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let shared = self.shared.get_ref();
+
+        if self.pos < shared.head {
+            let skipped = shared.head - self.pos;
+            self.pos = shared.head;
+            return Err(TryRecvError::Lagged(skipped));
+        }
+        if let Some(item) = shared.buffer.get((self.pos - shared.head) as usize) {
+            self.pos += 1;
+            Ok(item.clone())
+        } else if shared.closed {
+            Err(TryRecvError::Closed)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Returns number of messages the receiver has not yet seen
+    pub fn len(&self) -> usize {
+        let shared = self.shared.get_ref();
+        (shared.tail() - std::cmp::max(self.pos, shared.head)) as usize
+    }
+
+    /// Returns `true` if there are no pending messages
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether sender is dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.get_ref().closed
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    /// Creates a new receiver at the same position as this receiver.
+    fn clone(&self) -> Self {
+        self.shared.get_mut().receivers += 1;
+        Receiver {
+            shared: self.shared.clone(),
+            pos: self.pos,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let shared = self.shared.get_mut();
+        shared.receivers -= 1;
+        if shared.receivers == 0 {
+            shared.buffer.clear();
+        }
+    }
+}
+
+/// Error returned from `Receiver::recv()`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecvError {
+    /// Receiver fell behind, contains number of skipped messages
+    Lagged(u64),
+    /// Sender is dropped and there are no more messages
+    Closed,
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvError::Lagged(n) => write!(f, "receiver lagged behind by {} messages", n),
+            RecvError::Closed => write!(f, "channel closed"),
+        }
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+/// Error returned from `Receiver::try_recv()`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TryRecvError {
+    /// There are no new messages
+    Empty,
+    /// Receiver fell behind, contains number of skipped messages
+    Lagged(u64),
+    /// Sender is dropped and there are no more messages
+    Closed,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "channel empty"),
+            TryRecvError::Lagged(n) => {
+                write!(f, "receiver lagged behind by {} messages", n)
+            }
+            TryRecvError::Closed => write!(f, "channel closed"),
+        }
+    }
+}
+
+impl std::error::Error for TryRecvError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::future::lazy;
+
+    #[ntex_macros::rt_test2]
+    async fn test_broadcast() {
+        let (tx, mut rx1) = channel(4);
+        let mut rx2 = tx.subscribe();
+        assert!(format!("{:?}", tx).contains("Sender"));
+        assert!(format!("{:?}", rx1).contains("Receiver"));
+        assert_eq!(tx.receiver_count(), 2);
+
+        assert_eq!(tx.send("a").unwrap(), 2);
+        assert_eq!(rx1.len(), 1);
+        assert_eq!(rx1.recv().await.unwrap(), "a");
+        assert_eq!(rx2.recv().await.unwrap(), "a");
+        assert!(rx1.is_empty());
+        assert_eq!(rx1.try_recv(), Err(TryRecvError::Empty));
+        assert!(lazy(|cx| rx1.poll_recv(cx)).await.is_pending());
+
+        // new receiver does not see old messages
+        tx.send("b").unwrap();
+        let mut rx3 = tx.subscribe();
+        tx.send("c").unwrap();
+        assert_eq!(rx3.recv().await.unwrap(), "c");
+
+        // cloned receiver continues from the same position
+        let mut rx4 = rx1.clone();
+        assert_eq!(rx1.recv().await.unwrap(), "b");
+        assert_eq!(rx4.recv().await.unwrap(), "b");
+        assert_eq!(tx.receiver_count(), 4);
+        drop(rx4);
+        assert_eq!(tx.receiver_count(), 3);
+
+        // sender drop closes channel after buffered messages
+        drop(tx);
+        assert!(rx1.is_closed());
+        assert_eq!(rx1.recv().await.unwrap(), "c");
+        assert_eq!(rx1.recv().await, Err(RecvError::Closed));
+        assert_eq!(rx3.try_recv(), Err(TryRecvError::Closed));
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_lagged() {
+        let (tx, mut rx) = channel(2);
+        for i in 0..5 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(rx.len(), 2);
+        assert_eq!(rx.recv().await, Err(RecvError::Lagged(3)));
+        assert!(format!("{}", RecvError::Lagged(3)).contains("lagged"));
+        assert_eq!(rx.recv().await.unwrap(), 3);
+        assert_eq!(rx.recv().await.unwrap(), 4);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        // pending receive completes after send
+        let fut = rx.recv();
+        tx.send(5).unwrap();
+        assert_eq!(fut.await.unwrap(), 5);
+
+        drop(rx);
+        assert!(tx.is_closed());
+        assert_eq!(tx.send(6).err().unwrap().into_inner(), 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_capacity() {
+        let _ = channel::<()>(0);
+    }
+}
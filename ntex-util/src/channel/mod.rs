@@ -1,10 +1,14 @@
 //! Communication primitives
 
+pub mod bounded;
+pub mod broadcast;
 mod cell;
 pub mod condition;
 pub mod mpsc;
 pub mod oneshot;
 pub mod pool;
+pub mod priority;
+pub mod watch;
 
 /// Error returned from a `Receiver` when the corresponding
 /// `Sender` is dropped.
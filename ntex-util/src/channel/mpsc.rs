@@ -226,7 +226,7 @@ impl<T> Drop for Receiver<T> {
 
 /// Error type for sending, used when the receiving end of a channel is
 /// dropped
-pub struct SendError<T>(T);
+pub struct SendError<T>(pub(super) T);
 
 impl<T> std::error::Error for SendError<T> {}
 
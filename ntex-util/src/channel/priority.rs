@@ -0,0 +1,290 @@
+//! Priority-aware condition.
+//!
+//! Waiters are grouped into priority classes, `notify_one` wakes the
+//! earliest registered waiter of the highest priority class.
+use std::collections::{BTreeMap, VecDeque};
+use std::{
+    future::poll_fn, future::Future, pin::Pin, task::Context, task::Poll, task::Waker,
+};
+
+use slab::Slab;
+
+use super::cell::Cell;
+
+/// Condition with prioritized waiters
+#[derive(Clone, Debug)]
+pub struct PriorityCondition(Cell<Inner>);
+
+#[derive(Debug)]
+struct Inner {
+    waiters: Slab<Entry>,
+    queues: BTreeMap<u8, VecDeque<usize>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    priority: u8,
+    waker: Option<Waker>,
+    queued: bool,
+    notified: bool,
+}
+
+impl Inner {
+    fn notify_one(&mut self) -> bool {
+        while let Some(mut queue) = self.queues.last_entry() {
+            if let Some(token) = queue.get_mut().pop_front() {
+                let entry = &mut self.waiters[token];
+                entry.queued = false;
+                entry.notified = true;
+                if let Some(waker) = entry.waker.take() {
+                    waker.wake();
+                }
+                return true;
+            }
+            queue.remove();
+        }
+        false
+    }
+
+    fn notify_all(&mut self) -> usize {
+        let mut count = 0;
+        for (_, queue) in std::mem::take(&mut self.queues) {
+            for token in queue {
+                let entry = &mut self.waiters[token];
+                entry.queued = false;
+                entry.notified = true;
+                if let Some(waker) = entry.waker.take() {
+                    waker.wake();
+                }
+                count += 1;
+            }
+        }
+        count
+    }
+
+    fn dequeue(&mut self, token: usize) {
+        let entry = &mut self.waiters[token];
+        if entry.queued {
+            entry.queued = false;
+            let priority = entry.priority;
+            if let Some(queue) = self.queues.get_mut(&priority) {
+                queue.retain(|t| *t != token);
+                if queue.is_empty() {
+                    self.queues.remove(&priority);
+                }
+            }
+        }
+    }
+}
+
+impl Default for PriorityCondition {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PriorityCondition {
+    /// Construct new condition instance
+    pub fn new() -> PriorityCondition {
+        PriorityCondition(Cell::new(Inner {
+            waiters: Slab::new(),
+            queues: BTreeMap::new(),
+        }))
+    }
+
+    /// Get condition waiter with specified priority.
+    ///
+    /// Waiters with higher priority are notified first.
+    pub fn wait(&self, priority: u8) -> PriorityWaiter {
+        let token = self.0.get_mut().waiters.insert(Entry {
+            priority,
+            waker: None,
+            queued: false,
+            notified: false,
+        });
+        PriorityWaiter {
+            token,
+            inner: self.0.clone(),
+        }
+    }
+
+    /// Notify single waiter.
+    ///
+    /// Waiter with the highest priority that waits the longest is notified.
+    /// Returns `false` if there are no pending waiters, notification is not stored.
+    pub fn notify_one(&self) -> bool {
+        self.0.get_mut().notify_one()
+    }
+
+    /// Notify all pending waiters.
+    ///
+    /// Returns number of notified waiters.
+    pub fn notify_all(&self) -> usize {
+        self.0.get_mut().notify_all()
+    }
+
+    /// Returns number of pending waiters
+    pub fn waiters(&self) -> usize {
+        self.0.get_ref().queues.values().map(|q| q.len()).sum()
+    }
+}
+
+#[derive(Debug)]
+#[must_use = "Waiter do nothing unless polled"]
+/// Waiter of priority condition
+pub struct PriorityWaiter {
+    token: usize,
+    inner: Cell<Inner>,
+}
+
+impl PriorityWaiter {
+    /// Returns waiter's priority
+    pub fn priority(&self) -> u8 {
+        self.inner.get_ref().waiters[self.token].priority
+    }
+
+    /// Wait for notification.
+    pub async fn ready(&self) {
+        poll_fn(|cx| self.poll_ready(cx)).await
+    }
+
+    /// Poll for notification.
+    ///
+    /// Waiter is queued on first poll, after notification is received
+    /// waiter could be polled again to wait for next notification.
+    pub fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let inner = self.inner.get_mut();
+        let entry = &mut inner.waiters[self.token];
+
+        if entry.notified {
+            entry.notified = false;
+            return Poll::Ready(());
+        }
+
+        match entry.waker {
+            Some(ref w) if w.will_wake(cx.waker()) => (),
+            _ => entry.waker = Some(cx.waker().clone()),
+        }
+        if !entry.queued {
+            entry.queued = true;
+            let priority = entry.priority;
+            inner
+                .queues
+                .entry(priority)
+                .or_default()
+                .push_back(self.token);
+        }
+        Poll::Pending
+    }
+}
+
+impl Clone for PriorityWaiter {
+    fn clone(&self) -> Self {
+        let priority = self.priority();
+        PriorityWaiter {
+            token: self.inner.get_mut().waiters.insert(Entry {
+                priority,
+                waker: None,
+                queued: false,
+                notified: false,
+            }),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Future for PriorityWaiter {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().poll_ready(cx)
+    }
+}
+
+impl Drop for PriorityWaiter {
+    fn drop(&mut self) {
+        let inner = self.inner.get_mut();
+        inner.dequeue(self.token);
+        let entry = inner.waiters.remove(self.token);
+
+        // pass unconsumed notification to the next waiter
+        if entry.notified {
+            inner.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::future::lazy;
+
+    #[ntex_macros::rt_test2]
+    async fn test_priority() {
+        let cond = PriorityCondition::new();
+        assert!(format!("{:?}", cond).contains("PriorityCondition"));
+        assert!(!cond.notify_one());
+
+        let low = cond.wait(0);
+        let high1 = cond.wait(10);
+        let high2 = cond.wait(10);
+        assert!(format!("{:?}", low).contains("PriorityWaiter"));
+        assert_eq!(high1.priority(), 10);
+
+        assert!(lazy(|cx| low.poll_ready(cx)).await.is_pending());
+        assert!(lazy(|cx| high2.poll_ready(cx)).await.is_pending());
+        assert!(lazy(|cx| high1.poll_ready(cx)).await.is_pending());
+        // repeated poll does not queue waiter twice
+        assert!(lazy(|cx| high1.poll_ready(cx)).await.is_pending());
+        assert_eq!(cond.waiters(), 3);
+
+        // highest priority, fifo order
+        assert!(cond.notify_one());
+        assert!(lazy(|cx| low.poll_ready(cx)).await.is_pending());
+        assert!(lazy(|cx| high1.poll_ready(cx)).await.is_pending());
+        high2.ready().await;
+
+        assert!(cond.notify_one());
+        high1.ready().await;
+        assert!(cond.notify_one());
+        low.ready().await;
+        assert!(!cond.notify_one());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_notify_all() {
+        let cond = PriorityCondition::default();
+        let w1 = cond.wait(1);
+        let w2 = w1.clone();
+        assert_eq!(w2.priority(), 1);
+        assert!(lazy(|cx| w1.poll_ready(cx)).await.is_pending());
+        assert!(lazy(|cx| w2.poll_ready(cx)).await.is_pending());
+
+        assert_eq!(cond.notify_all(), 2);
+        assert_eq!(cond.waiters(), 0);
+        w1.ready().await;
+        w2.ready().await;
+        assert!(lazy(|cx| w1.poll_ready(cx)).await.is_pending());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_drop_notified() {
+        let cond = PriorityCondition::new();
+        let w1 = cond.wait(5);
+        let w2 = cond.wait(1);
+        assert!(lazy(|cx| w1.poll_ready(cx)).await.is_pending());
+        assert!(lazy(|cx| w2.poll_ready(cx)).await.is_pending());
+
+        // dropped waiter passes notification
+        assert!(cond.notify_one());
+        drop(w1);
+        w2.ready().await;
+
+        // dropped pending waiter is removed from queue
+        let w3 = cond.wait(1);
+        assert!(lazy(|cx| w3.poll_ready(cx)).await.is_pending());
+        drop(w3);
+        assert_eq!(cond.waiters(), 0);
+        assert!(!cond.notify_one());
+    }
+}
@@ -0,0 +1,268 @@
+//! A single-producer, multi-consumer channel that retains only the latest value.
+//!
+//! Receivers are notified when value changes and always observe the most
+//! recent value, intermediate values could be skipped.
+use std::cell::{Ref, RefCell};
+use std::future::poll_fn;
+use std::{fmt, task::Context, task::Poll, task::Waker};
+
+use super::cell::Cell;
+use super::mpsc::SendError;
+
+/// Creates a watch channel with initial value.
+pub fn channel<T>(value: T) -> (Sender<T>, Receiver<T>) {
+    let shared = Cell::new(Shared {
+        value: RefCell::new(value),
+        version: 0,
+        receivers: 1,
+        closed: false,
+        wakers: Vec::new(),
+    });
+    let sender = Sender {
+        shared: shared.clone(),
+    };
+    let receiver = Receiver { shared, version: 0 };
+    (sender, receiver)
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    value: RefCell<T>,
+    version: u64,
+    receivers: usize,
+    closed: bool,
+    wakers: Vec<Waker>,
+}
+
+impl<T> Shared<T> {
+    fn notify(&mut self) {
+        self.version += 1;
+        for waker in self.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The transmission end of a watch channel.
+///
+/// This is created by the `channel` function.
+#[derive(Debug)]
+pub struct Sender<T> {
+    shared: Cell<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Sends new value and notifies all receivers.
+    ///
+    /// Returns error if all receivers are dropped.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.shared.get_ref().receivers == 0 {
+            return Err(SendError(value));
+        }
+        self.send_replace(value);
+        Ok(())
+    }
+
+    /// Sends new value and returns previous one.
+    ///
+    /// Value is updated even if there are no receivers.
+    pub fn send_replace(&self, value: T) -> T {
+        let shared = self.shared.get_mut();
+        let prev = shared.value.replace(value);
+        shared.notify();
+        prev
+    }
+
+    /// Modifies value in-place and notifies all receivers.
+    pub fn send_modify<F: FnOnce(&mut T)>(&self, f: F) {
+        let shared = self.shared.get_mut();
+        f(&mut shared.value.borrow_mut());
+        shared.notify();
+    }
+
+    /// Returns a reference to the current value.
+    ///
+    /// Value could not be modified while reference is held.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.shared.get_ref().value.borrow()
+    }
+
+    /// Creates a new receiver, current value is marked as seen.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let shared = self.shared.get_mut();
+        shared.receivers += 1;
+        Receiver {
+            version: shared.version,
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns number of active receivers
+    pub fn receiver_count(&self) -> usize {
+        self.shared.get_ref().receivers
+    }
+
+    /// Returns whether all receivers are dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.get_ref().receivers == 0
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let shared = self.shared.get_mut();
+        shared.closed = true;
+        for waker in shared.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The receiving end of a watch channel.
+///
+/// This is created by the `channel` function or by `Sender::subscribe()`.
+#[derive(Debug)]
+pub struct Receiver<T> {
+    shared: Cell<Shared<T>>,
+    version: u64,
+}
+
+impl<T> Receiver<T> {
+    /// Returns a reference to the current value, value is not marked as seen.
+    ///
+    /// Value could not be modified while reference is held.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.shared.get_ref().value.borrow()
+    }
+
+    /// Returns a reference to the current value and marks it as seen.
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        let shared = self.shared.get_ref();
+        self.version = shared.version;
+        shared.value.borrow()
+    }
+
+    /// Checks if value has changed since it was last seen.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let shared = self.shared.get_ref();
+        if shared.closed {
+            Err(RecvError)
+        } else {
+            Ok(shared.version != self.version)
+        }
+    }
+
+    /// Waits for value change and marks new value as seen.
+    ///
+    /// Returns error if sender is dropped.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        poll_fn(|cx| self.poll_changed(cx)).await
+    }
+
+    /// Polls for value change and marks new value as seen.
+    pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), RecvError>> {
+        let shared = self.shared.get_mut();
+        if shared.version != self.version {
+            self.version = shared.version;
+            Poll::Ready(Ok(()))
+        } else if shared.closed {
+            Poll::Ready(Err(RecvError))
+        } else {
+            if !shared.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                shared.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        }
+    }
+
+    /// Returns whether sender is dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.get_ref().closed
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    /// Creates a new receiver with the same seen version.
+    fn clone(&self) -> Self {
+        self.shared.get_mut().receivers += 1;
+        Receiver {
+            shared: self.shared.clone(),
+            version: self.version,
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.get_mut().receivers -= 1;
+    }
+}
+
+/// Error returned when sender is dropped
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "watch sender is gone")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::future::lazy;
+
+    #[ntex_macros::rt_test2]
+    async fn test_watch() {
+        let (tx, mut rx) = channel(1);
+        assert!(format!("{:?}", tx).contains("Sender"));
+        assert!(format!("{:?}", rx).contains("Receiver"));
+        assert_eq!(*rx.borrow(), 1);
+        assert_eq!(rx.has_changed(), Ok(false));
+        assert!(lazy(|cx| rx.poll_changed(cx)).await.is_pending());
+
+        tx.send(2).unwrap();
+        tx.send(3).unwrap();
+        assert_eq!(rx.has_changed(), Ok(true));
+        rx.changed().await.unwrap();
+        assert_eq!(*rx.borrow(), 3);
+        assert_eq!(rx.has_changed(), Ok(false));
+
+        // subscribed receiver sees current value as seen
+        let mut rx2 = tx.subscribe();
+        assert_eq!(tx.receiver_count(), 2);
+        assert_eq!(rx2.has_changed(), Ok(false));
+
+        assert_eq!(tx.send_replace(4), 3);
+        tx.send_modify(|v| *v += 1);
+        assert_eq!(*tx.borrow(), 5);
+        assert_eq!(*rx2.borrow_and_update(), 5);
+        assert_eq!(rx2.has_changed(), Ok(false));
+
+        let mut rx3 = rx.clone();
+        rx3.changed().await.unwrap();
+        assert_eq!(*rx3.borrow(), 5);
+
+        drop(tx);
+        assert!(rx.is_closed());
+        assert_eq!(rx.has_changed(), Err(RecvError));
+        // unseen value is reported before close
+        assert_eq!(rx.changed().await, Ok(()));
+        assert_eq!(rx.changed().await, Err(RecvError));
+        assert!(format!("{}", RecvError).contains("sender is gone"));
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_closed() {
+        let (tx, rx) = channel("a");
+        assert!(!tx.is_closed());
+        drop(rx);
+        assert!(tx.is_closed());
+        assert_eq!(tx.send("b").err().unwrap().into_inner(), "b");
+        assert_eq!(tx.send_replace("c"), "a");
+        assert_eq!(*tx.borrow(), "c");
+    }
+}
@@ -9,12 +9,17 @@ mod join;
 mod lazy;
 mod ready;
 mod select;
+mod stream;
 
 pub use self::either::Either;
 pub use self::join::{join, join_all};
 pub use self::lazy::{lazy, Lazy};
 pub use self::ready::Ready;
 pub use self::select::select;
+pub use self::stream::{
+    buffer_unordered, buffered, chunked, debounce, sample, throttle, timeout_between_items,
+    BufferUnordered, Buffered, Chunked, Debounce, Sample, Throttle, TimeoutBetweenItems,
+};
 
 /// An owned dynamically typed Future for use in cases where
 /// you can't statically type your result or need to add some indirection.
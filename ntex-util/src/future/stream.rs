@@ -0,0 +1,699 @@
+use std::collections::VecDeque;
+use std::{fmt, future::Future, mem, pin::Pin, task::Context, task::Poll};
+
+use futures_core::Stream;
+
+use crate::time::{Deadline, Millis, Sleep};
+
+/// Run futures produced by the stream concurrently, yielding results
+/// in the order of the stream.
+///
+/// At most `limit` futures are polled at the same time.
+///
+/// # Panics
+///
+/// Panics if `limit` is zero.
+pub fn buffered<S>(stream: S, limit: usize) -> Buffered<S>
+where
+    S: Stream,
+    S::Item: Future,
+{
+    assert!(limit > 0, "Buffer limit must be greater than zero");
+    Buffered {
+        stream,
+        limit,
+        queue: VecDeque::with_capacity(limit),
+        done: false,
+    }
+}
+
+/// Run futures produced by the stream concurrently, yielding results
+/// as soon as futures complete.
+///
+/// At most `limit` futures are polled at the same time.
+///
+/// # Panics
+///
+/// Panics if `limit` is zero.
+pub fn buffer_unordered<S>(stream: S, limit: usize) -> BufferUnordered<S>
+where
+    S: Stream,
+    S::Item: Future,
+{
+    assert!(limit > 0, "Buffer limit must be greater than zero");
+    BufferUnordered {
+        stream,
+        limit,
+        inflight: Vec::with_capacity(limit),
+        done: false,
+    }
+}
+
+/// Yields `Err(())` if next item is not received within `timeout`.
+///
+/// Stream continues after timeout, timer is restarted on each item.
+pub fn timeout_between_items<S, T>(stream: S, timeout: T) -> TimeoutBetweenItems<S>
+where
+    S: Stream,
+    T: Into<Millis>,
+{
+    let timeout = timeout.into();
+    TimeoutBetweenItems {
+        stream,
+        timeout,
+        sleep: Sleep::new(timeout),
+    }
+}
+
+/// Group stream items into chunks.
+///
+/// Chunk is yielded when it contains `max_items` items or when `max_wait`
+/// time passes since the first item of the chunk is received.
+/// Zero `max_wait` disables time based chunking.
+///
+/// # Panics
+///
+/// Panics if `max_items` is zero.
+pub fn chunked<S, T>(stream: S, max_items: usize, max_wait: T) -> Chunked<S>
+where
+    S: Stream,
+    T: Into<Millis>,
+{
+    assert!(max_items > 0, "Chunk size must be greater than zero");
+    Chunked {
+        stream,
+        max_items,
+        max_wait: max_wait.into(),
+        items: Vec::with_capacity(max_items),
+        deadline: Deadline::new(Millis::ZERO),
+        done: false,
+    }
+}
+
+/// Yield the latest item after the stream is quiet for `quiet` duration.
+///
+/// Each received item restarts the timer and replaces the pending item,
+/// pending item is yielded immediately when the stream ends.
+pub fn debounce<S, T>(stream: S, quiet: T) -> Debounce<S>
+where
+    S: Stream,
+    T: Into<Millis>,
+{
+    Debounce {
+        stream,
+        quiet: quiet.into(),
+        item: None,
+        deadline: Deadline::new(Millis::ZERO),
+        done: false,
+    }
+}
+
+/// Yield an item, then drop all items received during `period`.
+///
+/// First item is yielded immediately.
+pub fn throttle<S, T>(stream: S, period: T) -> Throttle<S>
+where
+    S: Stream,
+    T: Into<Millis>,
+{
+    Throttle {
+        stream,
+        period: period.into(),
+        deadline: Deadline::new(Millis::ZERO),
+    }
+}
+
+/// Yield the latest item received during `period`.
+///
+/// Period starts when an item is received, the latest item is yielded
+/// at the end of the period. Nothing is yielded for periods without items,
+/// pending item is yielded immediately when the stream ends.
+pub fn sample<S, T>(stream: S, period: T) -> Sample<S>
+where
+    S: Stream,
+    T: Into<Millis>,
+{
+    Sample {
+        stream,
+        period: period.into(),
+        item: None,
+        deadline: Deadline::new(Millis::ZERO),
+        done: false,
+    }
+}
+
+enum Slot<F: Future> {
+    Pending(Pin<Box<F>>),
+    Done(F::Output),
+}
+
+pin_project_lite::pin_project! {
+    /// Stream returned by [`buffered`]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Buffered<S>
+    where
+        S: Stream,
+        S::Item: Future,
+    {
+        #[pin]
+        stream: S,
+        queue: VecDeque<Slot<S::Item>>,
+        limit: usize,
+        done: bool,
+    }
+}
+
+impl<S> Stream for Buffered<S>
+where
+    S: Stream,
+    S::Item: Future,
+{
+    type Item = <S::Item as Future>::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        while !*this.done && this.queue.len() < *this.limit {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(fut)) => {
+                    this.queue.push_back(Slot::Pending(Box::pin(fut)))
+                }
+                Poll::Ready(None) => *this.done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        for slot in this.queue.iter_mut() {
+            if let Slot::Pending(fut) = slot {
+                if let Poll::Ready(res) = fut.as_mut().poll(cx) {
+                    *slot = Slot::Done(res);
+                }
+            }
+        }
+
+        if let Some(Slot::Done(_)) = this.queue.front() {
+            if let Some(Slot::Done(res)) = this.queue.pop_front() {
+                return Poll::Ready(Some(res));
+            }
+        }
+
+        if *this.done && this.queue.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.stream.size_hint();
+        let len = self.queue.len();
+        (
+            lower.saturating_add(len),
+            upper.and_then(|u| u.checked_add(len)),
+        )
+    }
+}
+
+impl<S> fmt::Debug for Buffered<S>
+where
+    S: Stream + fmt::Debug,
+    S::Item: Future,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Buffered")
+            .field("stream", &self.stream)
+            .field("queued", &self.queue.len())
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Stream returned by [`buffer_unordered`]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct BufferUnordered<S>
+    where
+        S: Stream,
+        S::Item: Future,
+    {
+        #[pin]
+        stream: S,
+        inflight: Vec<Pin<Box<S::Item>>>,
+        limit: usize,
+        done: bool,
+    }
+}
+
+impl<S> Stream for BufferUnordered<S>
+where
+    S: Stream,
+    S::Item: Future,
+{
+    type Item = <S::Item as Future>::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        while !*this.done && this.inflight.len() < *this.limit {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(fut)) => this.inflight.push(Box::pin(fut)),
+                Poll::Ready(None) => *this.done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        for idx in 0..this.inflight.len() {
+            if let Poll::Ready(res) = this.inflight[idx].as_mut().poll(cx) {
+                drop(this.inflight.swap_remove(idx));
+                return Poll::Ready(Some(res));
+            }
+        }
+
+        if *this.done && this.inflight.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.stream.size_hint();
+        let len = self.inflight.len();
+        (
+            lower.saturating_add(len),
+            upper.and_then(|u| u.checked_add(len)),
+        )
+    }
+}
+
+impl<S> fmt::Debug for BufferUnordered<S>
+where
+    S: Stream + fmt::Debug,
+    S::Item: Future,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferUnordered")
+            .field("stream", &self.stream)
+            .field("inflight", &self.inflight.len())
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Stream returned by [`timeout_between_items`]
+    #[must_use = "streams do nothing unless polled"]
+    #[derive(Debug)]
+    pub struct TimeoutBetweenItems<S> {
+        #[pin]
+        stream: S,
+        timeout: Millis,
+        sleep: Sleep,
+    }
+}
+
+impl<S: Stream> Stream for TimeoutBetweenItems<S> {
+    type Item = Result<S::Item, ()>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(item)) => {
+                this.sleep.reset(*this.timeout);
+                Poll::Ready(Some(Ok(item)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => {
+                if this.sleep.poll_elapsed(cx).is_ready() {
+                    this.sleep.reset(*this.timeout);
+                    Poll::Ready(Some(Err(())))
+                } else {
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Stream returned by [`chunked`]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Chunked<S>
+    where
+        S: Stream,
+    {
+        #[pin]
+        stream: S,
+        items: Vec<S::Item>,
+        max_items: usize,
+        max_wait: Millis,
+        deadline: Deadline,
+        done: bool,
+    }
+}
+
+impl<S: Stream> Stream for Chunked<S> {
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.done {
+                return if this.items.is_empty() {
+                    Poll::Ready(None)
+                } else {
+                    Poll::Ready(Some(mem::take(this.items)))
+                };
+            }
+
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if this.items.is_empty() {
+                        this.deadline.reset(*this.max_wait);
+                    }
+                    this.items.push(item);
+                    if this.items.len() >= *this.max_items {
+                        this.deadline.reset(Millis::ZERO);
+                        let items = Vec::with_capacity(*this.max_items);
+                        return Poll::Ready(Some(mem::replace(this.items, items)));
+                    }
+                }
+                Poll::Ready(None) => {
+                    *this.done = true;
+                    this.deadline.reset(Millis::ZERO);
+                }
+                Poll::Pending => {
+                    if !this.items.is_empty() && this.deadline.poll_elapsed(cx).is_ready() {
+                        this.deadline.reset(Millis::ZERO);
+                        let items = Vec::with_capacity(*this.max_items);
+                        return Poll::Ready(Some(mem::replace(this.items, items)));
+                    }
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+impl<S> fmt::Debug for Chunked<S>
+where
+    S: Stream + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Chunked")
+            .field("stream", &self.stream)
+            .field("items", &self.items.len())
+            .field("max_items", &self.max_items)
+            .field("max_wait", &self.max_wait)
+            .finish()
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Stream returned by [`debounce`]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Debounce<S>
+    where
+        S: Stream,
+    {
+        #[pin]
+        stream: S,
+        quiet: Millis,
+        item: Option<S::Item>,
+        deadline: Deadline,
+        done: bool,
+    }
+}
+
+impl<S: Stream> Stream for Debounce<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        while !*this.done {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    *this.item = Some(item);
+                    this.deadline.reset(*this.quiet);
+                }
+                Poll::Ready(None) => *this.done = true,
+                Poll::Pending => {
+                    if this.item.is_some() && this.deadline.poll_elapsed(cx).is_ready() {
+                        break;
+                    }
+                    return Poll::Pending;
+                }
+            }
+        }
+        this.deadline.reset(Millis::ZERO);
+        Poll::Ready(this.item.take())
+    }
+}
+
+impl<S> fmt::Debug for Debounce<S>
+where
+    S: Stream + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Debounce")
+            .field("stream", &self.stream)
+            .field("quiet", &self.quiet)
+            .field("pending", &self.item.is_some())
+            .finish()
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Stream returned by [`throttle`]
+    #[must_use = "streams do nothing unless polled"]
+    #[derive(Debug)]
+    pub struct Throttle<S> {
+        #[pin]
+        stream: S,
+        period: Millis,
+        deadline: Deadline,
+    }
+}
+
+impl<S: Stream> Stream for Throttle<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if this.deadline.is_elapsed() {
+                        this.deadline.reset(*this.period);
+                        return Poll::Ready(Some(item));
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Stream returned by [`sample`]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Sample<S>
+    where
+        S: Stream,
+    {
+        #[pin]
+        stream: S,
+        period: Millis,
+        item: Option<S::Item>,
+        deadline: Deadline,
+        done: bool,
+    }
+}
+
+impl<S: Stream> Stream for Sample<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        while !*this.done {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if this.item.replace(item).is_none() {
+                        this.deadline.reset(*this.period);
+                    }
+                }
+                Poll::Ready(None) => *this.done = true,
+                Poll::Pending => {
+                    if this.item.is_some() && this.deadline.poll_elapsed(cx).is_ready() {
+                        break;
+                    }
+                    return Poll::Pending;
+                }
+            }
+        }
+        this.deadline.reset(Millis::ZERO);
+        Poll::Ready(this.item.take())
+    }
+}
+
+impl<S> fmt::Debug for Sample<S>
+where
+    S: Stream + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sample")
+            .field("stream", &self.stream)
+            .field("period", &self.period)
+            .field("pending", &self.item.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{channel::mpsc, future::lazy, future::stream_recv, time::sleep};
+
+    #[ntex_macros::rt_test2]
+    async fn test_buffered() {
+        let (tx, rx) = mpsc::channel();
+        let mut s = Box::pin(buffered(rx, 2));
+
+        tx.send(Box::pin(async {
+            sleep(Millis(50)).await;
+            1usize
+        }) as Pin<Box<dyn Future<Output = usize>>>)
+            .unwrap();
+        tx.send(Box::pin(async { 2 })).unwrap();
+        tx.send(Box::pin(async { 3 })).unwrap();
+        drop(tx);
+
+        // results are ordered
+        assert_eq!(stream_recv(&mut s).await, Some(1));
+        assert_eq!(stream_recv(&mut s).await, Some(2));
+        assert_eq!(stream_recv(&mut s).await, Some(3));
+        assert_eq!(stream_recv(&mut s).await, None);
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_buffer_unordered() {
+        let (tx, rx) = mpsc::channel();
+        let mut s = Box::pin(buffer_unordered(rx, 2));
+
+        tx.send(Box::pin(async {
+            sleep(Millis(50)).await;
+            1usize
+        }) as Pin<Box<dyn Future<Output = usize>>>)
+            .unwrap();
+        tx.send(Box::pin(async { 2 })).unwrap();
+        tx.send(Box::pin(async { 3 })).unwrap();
+
+        assert_eq!(stream_recv(&mut s).await, Some(2));
+        assert_eq!(stream_recv(&mut s).await, Some(3));
+        tx.send(Box::pin(async { 4 })).unwrap();
+        assert_eq!(stream_recv(&mut s).await, Some(4));
+        assert_eq!(s.size_hint().0, 1);
+
+        drop(tx);
+        assert_eq!(stream_recv(&mut s).await, Some(1));
+        assert_eq!(stream_recv(&mut s).await, None);
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_timeout_between_items() {
+        let (tx, rx) = mpsc::channel();
+        let mut s = Box::pin(timeout_between_items(rx, Millis(50)));
+
+        tx.send(1).unwrap();
+        assert_eq!(stream_recv(&mut s).await, Some(Ok(1)));
+        assert_eq!(stream_recv(&mut s).await, Some(Err(())));
+
+        tx.send(2).unwrap();
+        assert_eq!(stream_recv(&mut s).await, Some(Ok(2)));
+        drop(tx);
+        assert_eq!(stream_recv(&mut s).await, None);
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_chunked() {
+        let (tx, rx) = mpsc::channel();
+        let mut s = Box::pin(chunked(rx, 2, Millis(50)));
+        assert!(format!("{:?}", s).contains("Chunked"));
+
+        // count based
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        tx.send(3).unwrap();
+        assert_eq!(stream_recv(&mut s).await, Some(vec![1, 2]));
+
+        // time based
+        assert_eq!(stream_recv(&mut s).await, Some(vec![3]));
+
+        // remaining items on stream end
+        tx.send(4).unwrap();
+        drop(tx);
+        assert_eq!(stream_recv(&mut s).await, Some(vec![4]));
+        assert_eq!(stream_recv(&mut s).await, None);
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_debounce() {
+        let (tx, rx) = mpsc::channel();
+        let mut s = Box::pin(debounce(rx, Millis(50)));
+        assert!(format!("{:?}", s).contains("Debounce"));
+
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        sleep(Millis(20)).await;
+        tx.send(3).unwrap();
+        assert_eq!(stream_recv(&mut s).await, Some(3));
+
+        // pending item on stream end
+        tx.send(4).unwrap();
+        drop(tx);
+        assert_eq!(stream_recv(&mut s).await, Some(4));
+        assert_eq!(stream_recv(&mut s).await, None);
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_throttle() {
+        let (tx, rx) = mpsc::channel();
+        let mut s = Box::pin(throttle(rx, Millis(50)));
+        assert!(format!("{:?}", s).contains("Throttle"));
+
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(stream_recv(&mut s).await, Some(1));
+        tx.send(3).unwrap();
+        assert!(lazy(|cx| s.as_mut().poll_next(cx)).await.is_pending());
+
+        sleep(Millis(100)).await;
+        tx.send(4).unwrap();
+        assert_eq!(stream_recv(&mut s).await, Some(4));
+        drop(tx);
+        assert_eq!(stream_recv(&mut s).await, None);
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_sample() {
+        let (tx, rx) = mpsc::channel();
+        let mut s = Box::pin(sample(rx, Millis(50)));
+        assert!(format!("{:?}", s).contains("Sample"));
+
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert!(lazy(|cx| s.as_mut().poll_next(cx)).await.is_pending());
+        assert_eq!(stream_recv(&mut s).await, Some(2));
+
+        tx.send(3).unwrap();
+        drop(tx);
+        assert_eq!(stream_recv(&mut s).await, Some(3));
+        assert_eq!(stream_recv(&mut s).await, None);
+    }
+}
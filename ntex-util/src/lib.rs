@@ -7,6 +7,7 @@ pub use std::task::ready;
 pub mod channel;
 pub mod future;
 pub mod services;
+pub mod sync;
 pub mod task;
 pub mod time;
 
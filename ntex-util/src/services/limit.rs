@@ -0,0 +1,227 @@
+//! Service that limits number of concurrent requests.
+use std::{task::Context, task::Poll};
+
+use ntex_service::{IntoService, Middleware, Service, ServiceCtx};
+
+use super::counter::Counter;
+use crate::sync::Semaphore;
+
+/// Fairness of waiting requests
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Fairness {
+    /// Limit is applied to service readiness, pipeline is not ready
+    /// until request completes, waiting callers are not ordered.
+    Readiness,
+    /// Service is always ready, requests wait for the permit in call
+    /// and are served in FIFO order.
+    Fifo,
+}
+
+/// ConcurrencyLimit - service factory for service that limits number
+/// of concurrent requests.
+///
+/// Default fairness is `Fairness::Fifo`
+#[derive(Copy, Clone, Debug)]
+pub struct ConcurrencyLimit {
+    max: usize,
+    fairness: Fairness,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            fairness: Fairness::Fifo,
+        }
+    }
+
+    /// Set fairness of waiting requests
+    pub fn fairness(mut self, fairness: Fairness) -> Self {
+        self.fairness = fairness;
+        self
+    }
+}
+
+impl<S> Middleware<S> for ConcurrencyLimit {
+    type Service = ConcurrencyLimitService<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        ConcurrencyLimitService {
+            service,
+            limit: Limit::new(self.max, self.fairness),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Limit {
+    Readiness(Counter),
+    Fifo(Semaphore),
+}
+
+impl Limit {
+    fn new(max: usize, fairness: Fairness) -> Self {
+        match fairness {
+            Fairness::Readiness => Limit::Readiness(Counter::new(max)),
+            Fairness::Fifo => Limit::Fifo(Semaphore::new(max)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConcurrencyLimitService<S> {
+    service: S,
+    limit: Limit,
+}
+
+impl<S> ConcurrencyLimitService<S> {
+    pub fn new<U, R>(max: usize, fairness: Fairness, service: U) -> Self
+    where
+        S: Service<R>,
+        U: IntoService<S, R>,
+    {
+        Self {
+            service: service.into_service(),
+            limit: Limit::new(max, fairness),
+        }
+    }
+
+    /// Returns fairness of waiting requests
+    pub fn fairness(&self) -> Fairness {
+        match self.limit {
+            Limit::Readiness(_) => Fairness::Readiness,
+            Limit::Fifo(_) => Fairness::Fifo,
+        }
+    }
+}
+
+impl<S, R> Service<R> for ConcurrencyLimitService<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.service.poll_ready(cx)?.is_pending() {
+            return Poll::Pending;
+        }
+        match self.limit {
+            Limit::Readiness(ref counter) if !counter.available(cx) => {
+                log::trace!("Concurrency limit exceeded");
+                Poll::Pending
+            }
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+
+    #[inline]
+    async fn call(
+        &self,
+        req: R,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        match self.limit {
+            Limit::Readiness(ref counter) => {
+                let _guard = counter.get();
+                ctx.call(&self.service, req).await
+            }
+            Limit::Fifo(ref sem) => {
+                // semaphore is never closed
+                let _permit = sem.acquire().await.ok();
+                ctx.call(&self.service, req).await
+            }
+        }
+    }
+
+    ntex_service::forward_poll_shutdown!(service);
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex_service::{apply, fn_factory, Pipeline, ServiceFactory};
+    use std::{cell::Cell, cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{future::lazy, time::sleep, time::Millis};
+
+    #[derive(Clone, Default)]
+    struct Srv(Rc<State>);
+
+    #[derive(Default)]
+    struct State {
+        active: Cell<usize>,
+        max_active: Cell<usize>,
+        order: RefCell<Vec<usize>>,
+    }
+
+    impl Service<usize> for Srv {
+        type Response = ();
+        type Error = ();
+
+        async fn call(&self, req: usize, _: ServiceCtx<'_, Self>) -> Result<(), ()> {
+            let state = &self.0;
+            state.active.set(state.active.get() + 1);
+            state
+                .max_active
+                .set(state.max_active.get().max(state.active.get()));
+            state.order.borrow_mut().push(req);
+            sleep(Millis(100)).await;
+            state.active.set(state.active.get() - 1);
+            Ok(())
+        }
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_fifo() {
+        let srv = Srv::default();
+        let state = srv.0.clone();
+        let srv = Pipeline::new(ConcurrencyLimitService::new(1, Fairness::Fifo, srv));
+        assert_eq!(srv.get_ref().fairness(), Fairness::Fifo);
+
+        let mut handles = Vec::new();
+        for idx in 0..4 {
+            let srv = srv.clone();
+            // service is always ready
+            assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+            handles.push(crate::spawn(async move { srv.call(idx).await }));
+        }
+        for h in handles {
+            assert_eq!(h.await.unwrap(), Ok(()));
+        }
+        assert_eq!(state.max_active.get(), 1);
+        assert_eq!(&*state.order.borrow(), &[0, 1, 2, 3]);
+        assert!(lazy(|cx| srv.poll_shutdown(cx)).await.is_ready());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_readiness() {
+        let srv = Srv::default();
+        let state = srv.0.clone();
+
+        let factory = apply(
+            ConcurrencyLimit::new(2).fairness(Fairness::Readiness),
+            fn_factory(move || {
+                let srv = srv.clone();
+                async move { Ok::<_, ()>(srv) }
+            }),
+        );
+        let srv = factory.pipeline(&()).await.unwrap();
+        assert_eq!(srv.get_ref().fairness(), Fairness::Readiness);
+
+        let mut handles = Vec::new();
+        for idx in 0..2 {
+            let srv = srv.clone();
+            handles.push(crate::spawn(async move { srv.call(idx).await }));
+        }
+        sleep(Millis(25)).await;
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Pending);
+
+        for h in handles {
+            assert_eq!(h.await.unwrap(), Ok(()));
+        }
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+        assert_eq!(state.max_active.get(), 2);
+    }
+}
@@ -0,0 +1,186 @@
+//! Service that rejects requests if inner service is not ready.
+use std::{cell::Cell, fmt, task::Context, task::Poll};
+
+use ntex_service::{IntoService, Middleware, Service, ServiceCtx};
+
+/// LoadShed - service factory for service that fails requests immediately
+/// if inner service is not ready.
+///
+/// Not ready inner service does not block caller's readiness, instead
+/// request fails with `LoadShedError::Overloaded` error.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct LoadShed;
+
+impl LoadShed {
+    pub fn new() -> Self {
+        LoadShed
+    }
+}
+
+impl<S> Middleware<S> for LoadShed {
+    type Service = LoadShedService<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        LoadShedService {
+            service,
+            overloaded: Cell::new(false),
+        }
+    }
+}
+
+/// Load shed service error
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LoadShedError<E> {
+    /// Service error
+    Service(E),
+    /// Service is overloaded
+    Overloaded,
+}
+
+impl<E> From<E> for LoadShedError<E> {
+    fn from(err: E) -> Self {
+        LoadShedError::Service(err)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for LoadShedError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadShedError::Service(e) => e.fmt(f),
+            LoadShedError::Overloaded => write!(f, "Service is overloaded"),
+        }
+    }
+}
+
+impl<E: fmt::Display + fmt::Debug> std::error::Error for LoadShedError<E> {}
+
+#[derive(Debug)]
+pub struct LoadShedService<S> {
+    service: S,
+    overloaded: Cell<bool>,
+}
+
+impl<S> LoadShedService<S> {
+    pub fn new<U, R>(service: U) -> Self
+    where
+        S: Service<R>,
+        U: IntoService<S, R>,
+    {
+        Self {
+            service: service.into_service(),
+            overloaded: Cell::new(false),
+        }
+    }
+}
+
+impl<S: Clone> Clone for LoadShedService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            overloaded: Cell::new(false),
+        }
+    }
+}
+
+impl<S, R> Service<R> for LoadShedService<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = LoadShedError<S::Error>;
+
+    #[inline]
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.service.poll_ready(cx) {
+            Poll::Ready(Ok(())) => self.overloaded.set(false),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(LoadShedError::Service(e))),
+            Poll::Pending => {
+                log::trace!("Service is overloaded, shedding load");
+                self.overloaded.set(true);
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    async fn call(
+        &self,
+        req: R,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        if self.overloaded.get() {
+            Err(LoadShedError::Overloaded)
+        } else {
+            ctx.call(&self.service, req)
+                .await
+                .map_err(LoadShedError::Service)
+        }
+    }
+
+    ntex_service::forward_poll_shutdown!(service);
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex_service::{apply, fn_factory, Pipeline, ServiceFactory};
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::future::lazy;
+
+    #[derive(Clone, Debug)]
+    struct Srv(Rc<Cell<bool>>);
+
+    impl Service<()> for Srv {
+        type Response = ();
+        type Error = ();
+
+        fn poll_ready(&self, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            if self.0.get() {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        }
+
+        async fn call(&self, _: (), _: ServiceCtx<'_, Self>) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_service() {
+        let ready = Rc::new(Cell::new(true));
+        let srv = Pipeline::new(LoadShedService::new(Srv(ready.clone())).clone());
+        assert_eq!(srv.call(()).await, Ok(()));
+
+        ready.set(false);
+        assert_eq!(lazy(|cx| srv.poll_ready(cx)).await, Poll::Ready(Ok(())));
+        assert_eq!(srv.call(()).await, Err(LoadShedError::Overloaded));
+
+        ready.set(true);
+        assert_eq!(srv.call(()).await, Ok(()));
+        assert!(lazy(|cx| srv.poll_shutdown(cx)).await.is_ready());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_middleware() {
+        let ready = Rc::new(Cell::new(false));
+        let ready2 = ready.clone();
+
+        let factory = apply(
+            LoadShed::new(),
+            fn_factory(move || {
+                let ready = ready2.clone();
+                async move { Ok::<_, ()>(Srv(ready)) }
+            }),
+        );
+        let srv = factory.pipeline(&()).await.unwrap();
+        assert_eq!(srv.call(()).await, Err(LoadShedError::Overloaded));
+
+        let err = LoadShedError::<&str>::Overloaded;
+        assert_eq!(format!("{}", err), "Service is overloaded");
+        let err: LoadShedError<&str> = "error".into();
+        assert_eq!(format!("{}", err), "error");
+    }
+}
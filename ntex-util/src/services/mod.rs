@@ -3,7 +3,10 @@ pub mod counter;
 mod extensions;
 pub mod inflight;
 pub mod keepalive;
+pub mod limit;
+pub mod loadshed;
 pub mod onerequest;
+pub mod queue;
 pub mod timeout;
 pub mod variant;
 
@@ -0,0 +1,341 @@
+//! Service that queues requests to a single service instance.
+use std::task::Poll;
+use std::{
+    cell::RefCell, collections::VecDeque, fmt, future::poll_fn, marker::PhantomData, rc::Rc,
+};
+
+use ntex_service::{IntoService, Middleware, Pipeline, Service, ServiceCtx};
+
+use crate::{channel::oneshot, task::LocalWaker};
+
+/// Queue - middleware that shares single service instance via bounded queue.
+///
+/// Requests are passed to the inner service by background worker task,
+/// if queue is full request fails with `QueueError::Full`.
+/// Default queue size is 16
+pub struct Queue<R> {
+    size: usize,
+    _t: PhantomData<R>,
+}
+
+impl<R> Queue<R> {
+    /// Set max number of queued requests
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+impl<R> Default for Queue<R> {
+    fn default() -> Self {
+        Self {
+            size: 16,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<R> fmt::Debug for Queue<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Queue").field("size", &self.size).finish()
+    }
+}
+
+impl<R> Clone for Queue<R> {
+    fn clone(&self) -> Self {
+        Self {
+            size: self.size,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<R, S> Middleware<S> for Queue<R>
+where
+    S: Service<R> + 'static,
+    R: 'static,
+{
+    type Service = QueueService<R, S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        QueueService::new(self.size, service)
+    }
+}
+
+/// Queue service error
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueError<E> {
+    /// Inner service error
+    Service(E),
+    /// Queue is full
+    Full,
+    /// Queue worker is stopped
+    Closed,
+}
+
+impl<E> From<E> for QueueError<E> {
+    fn from(err: E) -> Self {
+        QueueError::Service(err)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for QueueError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueueError::Service(e) => fmt::Display::fmt(e, f),
+            QueueError::Full => f.write_str("queue service is full"),
+            QueueError::Closed => f.write_str("queue service is closed"),
+        }
+    }
+}
+
+impl<E: fmt::Display + fmt::Debug> std::error::Error for QueueError<E> {}
+
+type Item<R, S> = (
+    R,
+    oneshot::Sender<Result<<S as Service<R>>::Response, <S as Service<R>>::Error>>,
+);
+
+struct Inner<R, S: Service<R>> {
+    size: usize,
+    queue: RefCell<VecDeque<Item<R, S>>>,
+    waker: LocalWaker,
+}
+
+/// Queue service - service that shares single service instance via bounded queue.
+///
+/// Cloned services share the same queue and inner service. Service must be
+/// created within runtime, worker task stops when all clones are dropped.
+pub struct QueueService<R, S: Service<R>> {
+    inner: Rc<Inner<R, S>>,
+}
+
+impl<R, S> QueueService<R, S>
+where
+    S: Service<R> + 'static,
+    R: 'static,
+{
+    /// Create queue service and spawn worker task
+    pub fn new<U>(size: usize, service: U) -> Self
+    where
+        U: IntoService<S, R>,
+    {
+        let inner = Rc::new(Inner {
+            size,
+            queue: RefCell::new(VecDeque::with_capacity(size)),
+            waker: LocalWaker::new(),
+        });
+        let _ = crate::spawn(worker(inner.clone(), Pipeline::new(service.into_service())));
+
+        Self { inner }
+    }
+}
+
+impl<R, S> QueueService<R, S>
+where
+    S: Service<R>,
+{
+    /// Returns number of queued requests
+    pub fn len(&self) -> usize {
+        self.inner.queue.borrow().len()
+    }
+
+    /// Returns `true` if queue is empty
+    pub fn is_empty(&self) -> bool {
+        self.inner.queue.borrow().is_empty()
+    }
+
+    /// Returns `true` if queue is full
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.inner.size
+    }
+}
+
+async fn worker<R, S>(inner: Rc<Inner<R, S>>, svc: Pipeline<S>)
+where
+    S: Service<R> + 'static,
+    R: 'static,
+{
+    loop {
+        let item = poll_fn(|cx| {
+            if let Some(item) = inner.queue.borrow_mut().pop_front() {
+                Poll::Ready(Some(item))
+            } else if Rc::strong_count(&inner) == 1 {
+                // all queue services are dropped
+                Poll::Ready(None)
+            } else {
+                inner.waker.register(cx.waker());
+                Poll::Pending
+            }
+        })
+        .await;
+
+        let Some((req, tx)) = item else {
+            break;
+        };
+        if tx.is_canceled() {
+            continue;
+        }
+        if let Err(err) = svc.ready::<R>().await {
+            let _ = tx.send(Err(err));
+            continue;
+        }
+
+        let fut = svc.call_nowait(req);
+        let _ = crate::spawn(async move {
+            let _ = tx.send(fut.await);
+        });
+    }
+    poll_fn(|cx| svc.poll_shutdown::<R>(cx)).await
+}
+
+impl<R, S: Service<R>> Clone for QueueService<R, S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<R, S: Service<R>> Drop for QueueService<R, S> {
+    fn drop(&mut self) {
+        self.inner.waker.wake();
+    }
+}
+
+impl<R, S: Service<R>> fmt::Debug for QueueService<R, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueueService")
+            .field("size", &self.inner.size)
+            .field("queued", &self.inner.queue.borrow().len())
+            .finish()
+    }
+}
+
+impl<R, S> Service<R> for QueueService<R, S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = QueueError<S::Error>;
+
+    async fn call(
+        &self,
+        req: R,
+        _: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        if self.is_full() {
+            log::trace!("Queue limit exceeded");
+            return Err(QueueError::Full);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        self.inner.queue.borrow_mut().push_back((req, tx));
+        self.inner.waker.wake();
+
+        match rx.await {
+            Ok(res) => res.map_err(QueueError::Service),
+            Err(_) => Err(QueueError::Closed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex_service::{apply, fn_factory, Pipeline, ServiceFactory};
+    use std::{cell::Cell, rc::Rc, task::Context};
+
+    use super::*;
+    use crate::time::{sleep, Millis};
+
+    // not cloneable service
+    struct TestService(Rc<State>);
+
+    struct State {
+        ready: Cell<bool>,
+        waker: LocalWaker,
+        count: Cell<usize>,
+    }
+
+    impl Service<()> for TestService {
+        type Response = usize;
+        type Error = ();
+
+        fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            self.0.waker.register(cx.waker());
+            if self.0.ready.get() {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        }
+
+        async fn call(&self, _: (), _: ServiceCtx<'_, Self>) -> Result<usize, ()> {
+            self.0.count.set(self.0.count.get() + 1);
+            Ok(self.0.count.get())
+        }
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_service() {
+        let state = Rc::new(State {
+            ready: Cell::new(false),
+            waker: LocalWaker::default(),
+            count: Cell::new(0),
+        });
+
+        let srv = Pipeline::new(QueueService::new(1, TestService(state.clone())));
+        assert!(format!("{:?}", srv).contains("QueueService"));
+        assert!(srv.get_ref().is_empty());
+
+        // first request is taken by worker
+        let srv2 = srv.clone();
+        let fut1 = crate::spawn(async move { srv2.call(()).await });
+        sleep(Millis(25)).await;
+        assert!(srv.get_ref().is_empty());
+
+        // second request is queued
+        let srv2 = srv.clone();
+        let fut2 = crate::spawn(async move { srv2.call(()).await });
+        sleep(Millis(25)).await;
+        assert_eq!(srv.get_ref().len(), 1);
+        assert!(srv.get_ref().is_full());
+
+        assert_eq!(srv.call(()).await, Err(QueueError::Full));
+        assert_eq!(
+            format!("{}", QueueError::<&str>::Full),
+            "queue service is full"
+        );
+
+        state.ready.set(true);
+        state.waker.wake();
+        assert_eq!(fut1.await.unwrap(), Ok(1));
+        assert_eq!(fut2.await.unwrap(), Ok(2));
+        assert_eq!(srv.call(()).await, Ok(3));
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_middleware() {
+        let state = Rc::new(State {
+            ready: Cell::new(true),
+            waker: LocalWaker::default(),
+            count: Cell::new(0),
+        });
+        let state2 = state.clone();
+
+        let mw = Queue::default().size(2);
+        let factory = apply(
+            mw.clone(),
+            fn_factory(move || {
+                let state = state2.clone();
+                async move { Ok::<_, ()>(TestService(state)) }
+            }),
+        );
+        assert!(format!("{:?}", Queue::<()>::default()).contains("Queue"));
+
+        let srv = factory.pipeline(&()).await.unwrap();
+        assert_eq!(srv.call(()).await, Ok(1));
+        assert_eq!(srv.call(()).await, Ok(2));
+        assert_eq!(state.count.get(), 2);
+    }
+}
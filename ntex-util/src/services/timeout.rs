@@ -2,12 +2,13 @@
 //!
 //! If the response does not complete within the specified timeout, the response
 //! will be aborted.
-use std::{fmt, marker};
+use std::{fmt, marker, time::Instant};
 
 use ntex_service::{IntoService, Middleware, Service, ServiceCtx};
 
 use crate::future::{select, Either};
-use crate::time::{sleep, Millis};
+use crate::services::Extensions;
+use crate::time::{now, sleep, Millis};
 
 /// Applies a timeout to requests.
 ///
@@ -145,6 +146,149 @@ where
     ntex_service::forward_poll_shutdown!(service);
 }
 
+/// Call deadline, could be stored in request extensions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CallDeadline(pub Instant);
+
+/// Request that carries call deadline.
+///
+/// Deadline overrides default timeout of `CallTimeout` service if it is earlier.
+pub trait RequestDeadline {
+    /// Returns call deadline, `None` means default timeout
+    fn deadline(&self) -> Option<Instant>;
+}
+
+impl RequestDeadline for Extensions {
+    fn deadline(&self) -> Option<Instant> {
+        self.get::<CallDeadline>().map(|d| d.0)
+    }
+}
+
+impl<T: RequestDeadline> RequestDeadline for &T {
+    fn deadline(&self) -> Option<Instant> {
+        (**self).deadline()
+    }
+}
+
+/// Call timeout error
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CallTimeoutError<E> {
+    /// Service error
+    Service(E),
+    /// Call deadline elapsed
+    Elapsed,
+}
+
+impl<E> From<E> for CallTimeoutError<E> {
+    fn from(err: E) -> Self {
+        CallTimeoutError::Service(err)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for CallTimeoutError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallTimeoutError::Service(e) => e.fmt(f),
+            CallTimeoutError::Elapsed => write!(f, "Service call deadline elapsed"),
+        }
+    }
+}
+
+impl<E: fmt::Display + fmt::Debug> std::error::Error for CallTimeoutError<E> {}
+
+/// Applies a timeout to requests, with per-call deadline.
+///
+/// Deadline is taken from request via `RequestDeadline` trait, default timeout
+/// is used if request has no deadline. Default timeout is disabled if it is set to 0.
+#[derive(Debug, Clone)]
+pub struct CallTimeout {
+    timeout: Millis,
+}
+
+impl CallTimeout {
+    pub fn new<T: Into<Millis>>(timeout: T) -> Self {
+        CallTimeout {
+            timeout: timeout.into(),
+        }
+    }
+}
+
+impl<S> Middleware<S> for CallTimeout {
+    type Service = CallTimeoutService<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        CallTimeoutService {
+            service,
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Applies a timeout to requests, with per-call deadline.
+#[derive(Debug, Clone)]
+pub struct CallTimeoutService<S> {
+    service: S,
+    timeout: Millis,
+}
+
+impl<S> CallTimeoutService<S> {
+    pub fn new<T, U, R>(timeout: T, service: U) -> Self
+    where
+        T: Into<Millis>,
+        S: Service<R>,
+        U: IntoService<S, R>,
+    {
+        CallTimeoutService {
+            timeout: timeout.into(),
+            service: service.into_service(),
+        }
+    }
+}
+
+impl<S, R> Service<R> for CallTimeoutService<S>
+where
+    S: Service<R>,
+    R: RequestDeadline,
+{
+    type Response = S::Response;
+    type Error = CallTimeoutError<S::Error>;
+
+    async fn call(
+        &self,
+        request: R,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let timeout = match request.deadline() {
+            Some(deadline) => {
+                let remaining = Millis::from(deadline.saturating_duration_since(now()));
+                if remaining.is_zero() {
+                    return Err(CallTimeoutError::Elapsed);
+                }
+                if self.timeout.is_zero() {
+                    remaining
+                } else {
+                    std::cmp::min(remaining, self.timeout)
+                }
+            }
+            None => self.timeout,
+        };
+
+        if timeout.is_zero() {
+            ctx.call(&self.service, request)
+                .await
+                .map_err(CallTimeoutError::Service)
+        } else {
+            match select(sleep(timeout), ctx.call(&self.service, request)).await {
+                Either::Left(_) => Err(CallTimeoutError::Elapsed),
+                Either::Right(res) => res.map_err(CallTimeoutError::Service),
+            }
+        }
+    }
+
+    ntex_service::forward_poll_ready!(service, CallTimeoutError::Service);
+    ntex_service::forward_poll_shutdown!(service);
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -225,6 +369,92 @@ mod tests {
         assert_eq!(res, TimeoutError::Timeout);
     }
 
+    #[derive(Debug)]
+    struct Req(Option<Instant>);
+
+    impl RequestDeadline for Req {
+        fn deadline(&self) -> Option<Instant> {
+            self.0
+        }
+    }
+
+    struct DeadlineService(Duration);
+
+    impl Service<Req> for DeadlineService {
+        type Response = ();
+        type Error = SrvError;
+
+        async fn call(&self, _: Req, _: ServiceCtx<'_, Self>) -> Result<(), SrvError> {
+            crate::time::sleep(self.0).await;
+            Ok::<_, SrvError>(())
+        }
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_call_timeout() {
+        let wait_time = Duration::from_millis(200);
+        let srv = Pipeline::new(CallTimeoutService::new(
+            Millis(500),
+            DeadlineService(wait_time),
+        ));
+        assert_eq!(srv.call(Req(None)).await, Ok(()));
+        assert!(lazy(|cx| srv.poll_ready(cx)).await.is_ready());
+        assert!(lazy(|cx| srv.poll_shutdown(cx)).await.is_ready());
+
+        // request deadline overrides default timeout
+        let deadline = now() + Duration::from_millis(50);
+        assert_eq!(
+            srv.call(Req(Some(deadline))).await,
+            Err(CallTimeoutError::Elapsed)
+        );
+
+        // deadline already elapsed
+        let deadline = now() - Duration::from_millis(50);
+        assert_eq!(
+            srv.call(Req(Some(deadline))).await,
+            Err(CallTimeoutError::Elapsed)
+        );
+
+        // default timeout is disabled
+        let srv = Pipeline::new(CallTimeoutService::new(
+            Millis(0),
+            DeadlineService(wait_time),
+        ));
+        assert_eq!(srv.call(Req(None)).await, Ok(()));
+        let deadline = now() + Duration::from_millis(50);
+        assert_eq!(
+            srv.call(Req(Some(deadline))).await,
+            Err(CallTimeoutError::Elapsed)
+        );
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_call_timeout_extensions() {
+        let mut ext = Extensions::new();
+        assert_eq!(ext.deadline(), None);
+        let deadline = now() + Duration::from_millis(50);
+        ext.insert(CallDeadline(deadline));
+        assert_eq!(ext.deadline(), Some(deadline));
+        fn by_ref<T: RequestDeadline>(req: T) -> Option<Instant> {
+            req.deadline()
+        }
+        assert_eq!(by_ref(&ext), Some(deadline));
+
+        let factory = apply(
+            CallTimeout::new(Millis(100)),
+            fn_factory(|| async {
+                Ok::<_, ()>(DeadlineService(Duration::from_millis(500)))
+            }),
+        );
+        let srv = factory.pipeline(&()).await.unwrap();
+        assert_eq!(srv.call(Req(None)).await, Err(CallTimeoutError::Elapsed));
+
+        let err = CallTimeoutError::<SrvError>::Elapsed;
+        assert!(format!("{}", err).contains("deadline elapsed"));
+        let err: CallTimeoutError<_> = SrvError.into();
+        assert!(format!("{}", err).contains("SrvError"));
+    }
+
     #[test]
     fn test_error() {
         let err1 = TimeoutError::<SrvError>::Timeout;
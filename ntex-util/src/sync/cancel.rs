@@ -0,0 +1,213 @@
+use std::task::{Context, Poll, Waker};
+use std::{
+    cell::Cell, cell::RefCell, fmt, future::poll_fn, future::Future, rc::Rc, rc::Weak,
+};
+
+use crate::future::{select, Either};
+
+/// Cancellation token.
+///
+/// Token could be cancelled explicitly or via drop guard, cancellation
+/// propagates to all child tokens, but not to the parent token.
+/// Cloned tokens share the same cancellation state.
+#[derive(Clone)]
+pub struct CancellationToken(Rc<Node>);
+
+#[derive(Default)]
+struct Node {
+    cancelled: Cell<bool>,
+    wakers: RefCell<Vec<Waker>>,
+    children: RefCell<Vec<Weak<Node>>>,
+    // keeps parent's children list alive
+    _parent: Option<Rc<Node>>,
+}
+
+impl Node {
+    fn cancel(&self) {
+        if self.cancelled.replace(true) {
+            return;
+        }
+        for waker in self.wakers.take() {
+            waker.wake();
+        }
+        for child in self.children.take() {
+            if let Some(child) = child.upgrade() {
+                child.cancel();
+            }
+        }
+    }
+}
+
+impl CancellationToken {
+    /// Create new token.
+    pub fn new() -> Self {
+        CancellationToken(Rc::new(Node::default()))
+    }
+
+    /// Create child token.
+    ///
+    /// Child token is cancelled when parent token is cancelled,
+    /// cancelling child token does not affect the parent.
+    pub fn child_token(&self) -> Self {
+        let child = Rc::new(Node {
+            cancelled: Cell::new(self.0.cancelled.get()),
+            _parent: Some(self.0.clone()),
+            ..Default::default()
+        });
+        if !self.0.cancelled.get() {
+            let mut children = self.0.children.borrow_mut();
+            children.retain(|c| c.strong_count() > 0);
+            children.push(Rc::downgrade(&child));
+        }
+        CancellationToken(child)
+    }
+
+    /// Cancel the token and all its children.
+    pub fn cancel(&self) {
+        self.0.cancel()
+    }
+
+    /// Check if the token is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.get()
+    }
+
+    /// Wait until the token is cancelled.
+    pub async fn cancelled(&self) {
+        poll_fn(|cx| self.poll_cancelled(cx)).await
+    }
+
+    /// Poll token's cancellation state, registering the current task
+    /// for wakeup if the token is not yet cancelled.
+    pub fn poll_cancelled(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0.cancelled.get() {
+            Poll::Ready(())
+        } else {
+            let mut wakers = self.0.wakers.borrow_mut();
+            if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        }
+    }
+
+    /// Run future until it completes or the token is cancelled.
+    ///
+    /// Returns `None` if the token is cancelled before future completes.
+    pub async fn run_until_cancelled<F: Future>(&self, fut: F) -> Option<F::Output> {
+        match select(fut, self.cancelled()).await {
+            Either::Left(res) => Some(res),
+            Either::Right(_) => None,
+        }
+    }
+
+    /// Create drop guard, the token is cancelled when guard is dropped.
+    pub fn drop_guard(self) -> DropGuard {
+        DropGuard(Some(self))
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        CancellationToken::new()
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Cancels the token on drop.
+#[derive(Debug)]
+pub struct DropGuard(Option<CancellationToken>);
+
+impl DropGuard {
+    /// Returns the token without cancelling it.
+    pub fn disarm(mut self) -> CancellationToken {
+        self.0.take().unwrap()
+    }
+}
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        if let Some(token) = self.0.take() {
+            token.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::future::lazy;
+
+    #[ntex_macros::rt_test2]
+    async fn test_cancel() {
+        let token = CancellationToken::new();
+        assert!(format!("{:?}", token).contains("CancellationToken"));
+        assert!(!token.is_cancelled());
+        assert!(lazy(|cx| token.poll_cancelled(cx)).await.is_pending());
+
+        let token2 = token.clone();
+        token2.cancel();
+        assert!(token.is_cancelled());
+        token.cancelled().await;
+
+        // cancel is idempotent
+        token.cancel();
+        assert!(token.is_cancelled());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_children() {
+        let parent = CancellationToken::new();
+        let child = parent.child_token();
+        let grandchild = child.child_token();
+
+        // child cancellation does not affect parent
+        let other = parent.child_token();
+        other.cancel();
+        assert!(!parent.is_cancelled());
+        assert!(!child.is_cancelled());
+
+        parent.cancel();
+        assert!(child.is_cancelled());
+        assert!(grandchild.is_cancelled());
+        grandchild.cancelled().await;
+
+        // child of cancelled token is cancelled
+        assert!(parent.child_token().is_cancelled());
+
+        // cancellation propagates through dropped intermediate tokens
+        let parent = CancellationToken::new();
+        let grandchild = parent.child_token().child_token();
+        parent.cancel();
+        assert!(grandchild.is_cancelled());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_drop_guard() {
+        let token = CancellationToken::new();
+        let guard = token.clone().drop_guard();
+        drop(guard);
+        assert!(token.is_cancelled());
+
+        let token = CancellationToken::new();
+        let guard = token.clone().drop_guard();
+        let _ = guard.disarm();
+        assert!(!token.is_cancelled());
+
+        assert_eq!(token.run_until_cancelled(async { 1 }).await, Some(1));
+        token.cancel();
+        assert_eq!(
+            token
+                .run_until_cancelled(std::future::pending::<()>())
+                .await,
+            None
+        );
+    }
+}
@@ -0,0 +1,13 @@
+//! Synchronization primitives for single-threaded runtime
+//!
+//! Primitives are not thread-safe and do not use atomics, they are intended
+//! for tasks running on the same thread.
+mod cancel;
+mod ratelimit;
+mod rwlock;
+mod semaphore;
+
+pub use self::cancel::{CancellationToken, DropGuard};
+pub use self::ratelimit::{KeyedRateLimiter, RateLimiter};
+pub use self::rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+pub use self::semaphore::{AcquireError, Semaphore, SemaphorePermit};
@@ -0,0 +1,275 @@
+use std::{cell::Cell, cell::RefCell, cmp, fmt, hash::Hash, rc::Rc, time::Duration};
+use std::{collections::hash_map::Entry, time::Instant};
+
+use crate::time::{now, sleep, Millis};
+use crate::HashMap;
+
+/// Rate limiter quota, generic cell rate algorithm (GCRA)
+#[derive(Copy, Clone, Debug)]
+struct Quota {
+    /// Emission interval of single permit
+    interval: Duration,
+    /// Max deviation from emission schedule, defines burst size
+    tolerance: Duration,
+}
+
+impl Quota {
+    fn new(rate: u32, per: Millis) -> Self {
+        assert!(rate > 0, "Rate must be greater than 0");
+        let interval = Duration::from(per) / rate;
+        Quota {
+            interval,
+            tolerance: interval * rate,
+        }
+    }
+
+    fn burst(self, burst: u32) -> Self {
+        assert!(burst > 0, "Burst must be greater than 0");
+        Quota {
+            interval: self.interval,
+            tolerance: self.interval * burst,
+        }
+    }
+
+    /// Returns new theoretical arrival time, or time to wait
+    fn check(&self, tat: Option<Instant>, now: Instant) -> Result<Instant, Duration> {
+        let new_tat = cmp::max(tat.unwrap_or(now), now) + self.interval;
+        let diff = new_tat - now;
+        if diff > self.tolerance {
+            Err(diff - self.tolerance)
+        } else {
+            Ok(new_tat)
+        }
+    }
+}
+
+async fn wait(delay: Duration) {
+    // round up, timer resolution is 1 millisecond
+    sleep(Millis(Millis::from(delay).0.saturating_add(1))).await
+}
+
+/// Async rate limiter.
+///
+/// Limiter permits `rate` requests per period, with bursts up to `burst`
+/// requests, it implements generic cell rate algorithm, equivalent of token bucket.
+/// Limiter uses timer driver's time, cloned limiters share the same state.
+#[derive(Clone)]
+pub struct RateLimiter(Rc<Limiter>);
+
+struct Limiter {
+    quota: Cell<Quota>,
+    tat: Cell<Option<Instant>>,
+}
+
+impl RateLimiter {
+    /// Create rate limiter that permits `rate` requests per `per` period.
+    ///
+    /// By default burst size is equal to `rate`.
+    ///
+    /// Panics if rate is 0.
+    pub fn new<T: Into<Millis>>(rate: u32, per: T) -> Self {
+        RateLimiter(Rc::new(Limiter {
+            quota: Cell::new(Quota::new(rate, per.into())),
+            tat: Cell::new(None),
+        }))
+    }
+
+    /// Set max burst size.
+    ///
+    /// Panics if burst is 0.
+    pub fn burst(self, burst: u32) -> Self {
+        self.0.quota.set(self.0.quota.get().burst(burst));
+        self
+    }
+
+    /// Try to acquire permit without waiting.
+    ///
+    /// Returns time to wait until next permit is available if limit is reached.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let tat = self.0.quota.get().check(self.0.tat.get(), now())?;
+        self.0.tat.set(Some(tat));
+        Ok(())
+    }
+
+    /// Acquire permit, waits until permit is available.
+    ///
+    /// Limiter does not queue waiters, order of concurrent waiters is not preserved.
+    pub async fn acquire(&self) {
+        while let Err(delay) = self.try_acquire() {
+            wait(delay).await
+        }
+    }
+
+    /// Reset limiter state, full burst is available after reset.
+    pub fn reset(&self) {
+        self.0.tat.set(None);
+    }
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quota = self.0.quota.get();
+        f.debug_struct("RateLimiter")
+            .field("interval", &quota.interval)
+            .field("tolerance", &quota.tolerance)
+            .finish()
+    }
+}
+
+/// Async rate limiter with separate state per key.
+///
+/// Each key is limited independently with the same quota,
+/// cloned limiters share the same state.
+pub struct KeyedRateLimiter<K>(Rc<KeyedLimiter<K>>);
+
+struct KeyedLimiter<K> {
+    quota: Cell<Quota>,
+    keys: RefCell<HashMap<K, Instant>>,
+}
+
+impl<K: Eq + Hash + Clone> KeyedRateLimiter<K> {
+    /// Create rate limiter that permits `rate` requests per `per` period for each key.
+    ///
+    /// By default burst size is equal to `rate`.
+    ///
+    /// Panics if rate is 0.
+    pub fn new<T: Into<Millis>>(rate: u32, per: T) -> Self {
+        KeyedRateLimiter(Rc::new(KeyedLimiter {
+            quota: Cell::new(Quota::new(rate, per.into())),
+            keys: RefCell::new(HashMap::default()),
+        }))
+    }
+
+    /// Set max burst size.
+    ///
+    /// Panics if burst is 0.
+    pub fn burst(self, burst: u32) -> Self {
+        self.0.quota.set(self.0.quota.get().burst(burst));
+        self
+    }
+
+    /// Try to acquire permit for the key without waiting.
+    ///
+    /// Returns time to wait until next permit is available if limit is reached.
+    pub fn try_acquire(&self, key: &K) -> Result<(), Duration> {
+        let quota = self.0.quota.get();
+        let now = now();
+        let mut keys = self.0.keys.borrow_mut();
+
+        match keys.entry(key.clone()) {
+            Entry::Occupied(mut entry) => {
+                let tat = quota.check(Some(*entry.get()), now)?;
+                entry.insert(tat);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(quota.check(None, now)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Acquire permit for the key, waits until permit is available.
+    pub async fn acquire(&self, key: &K) {
+        while let Err(delay) = self.try_acquire(key) {
+            wait(delay).await
+        }
+    }
+
+    /// Returns the number of tracked keys
+    pub fn len(&self) -> usize {
+        self.0.keys.borrow().len()
+    }
+
+    /// Returns `true` if there are no tracked keys
+    pub fn is_empty(&self) -> bool {
+        self.0.keys.borrow().is_empty()
+    }
+
+    /// Remove state of keys with full burst available.
+    ///
+    /// Removed keys behave exactly as before, this method should be
+    /// called periodically to limit memory usage.
+    pub fn cleanup(&self) {
+        let now = now();
+        self.0.keys.borrow_mut().retain(|_, tat| *tat > now);
+    }
+}
+
+impl<K> Clone for KeyedRateLimiter<K> {
+    fn clone(&self) -> Self {
+        KeyedRateLimiter(self.0.clone())
+    }
+}
+
+impl<K> fmt::Debug for KeyedRateLimiter<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quota = self.0.quota.get();
+        f.debug_struct("KeyedRateLimiter")
+            .field("interval", &quota.interval)
+            .field("tolerance", &quota.tolerance)
+            .field("keys", &self.0.keys.borrow().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota() {
+        let quota = Quota::new(2, Millis(100)).burst(3);
+        let now = Instant::now();
+
+        let tat = quota.check(None, now).unwrap();
+        let tat = quota.check(Some(tat), now).unwrap();
+        let tat = quota.check(Some(tat), now).unwrap();
+        assert_eq!(
+            quota.check(Some(tat), now).err(),
+            Some(Duration::from_millis(50))
+        );
+
+        // permits are replenished over time
+        let now = now + Duration::from_millis(50);
+        let tat = quota.check(Some(tat), now).unwrap();
+        assert!(quota.check(Some(tat), now).is_err());
+        assert!(quota.check(Some(tat), now + Duration::from_secs(1)).is_ok());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_limiter() {
+        let limiter = RateLimiter::new(2, Millis(100));
+        assert!(format!("{:?}", limiter).contains("RateLimiter"));
+
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.clone().try_acquire().is_ok());
+        let delay = limiter.try_acquire().err().unwrap();
+        assert!(delay <= Duration::from_millis(50));
+
+        let start = Instant::now();
+        limiter.acquire().await;
+        assert!(Instant::now() - start >= Duration::from_millis(30));
+        assert!(limiter.try_acquire().is_err());
+
+        limiter.reset();
+        assert!(limiter.try_acquire().is_ok());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_keyed() {
+        let limiter = KeyedRateLimiter::new(1, Millis(50)).burst(1);
+        assert!(limiter.is_empty());
+
+        assert!(limiter.try_acquire(&1).is_ok());
+        assert!(limiter.try_acquire(&1).is_err());
+        assert!(limiter.clone().try_acquire(&2).is_ok());
+        assert_eq!(limiter.len(), 2);
+        assert!(format!("{:?}", limiter).contains("KeyedRateLimiter"));
+
+        limiter.acquire(&1).await;
+        sleep(Millis(100)).await;
+        limiter.cleanup();
+        assert!(limiter.is_empty());
+        assert!(limiter.try_acquire(&1).is_ok());
+    }
+}
@@ -0,0 +1,187 @@
+use std::ops::{Deref, DerefMut};
+use std::{cell::UnsafeCell, fmt};
+
+use super::semaphore::{Semaphore, SemaphorePermit};
+
+/// Max number of concurrent readers
+const MAX_READS: usize = u32::MAX as usize;
+
+/// Async reader-writer lock.
+///
+/// Lock allows many readers or a single writer at any point in time.
+/// Lock is fair, readers and writers acquire the lock in FIFO order,
+/// so a queued writer is not starved by readers.
+pub struct RwLock<T: ?Sized> {
+    sem: Semaphore,
+    value: UnsafeCell<T>,
+}
+
+impl<T> RwLock<T> {
+    /// Create new lock.
+    pub fn new(value: T) -> Self {
+        RwLock {
+            sem: Semaphore::new(MAX_READS),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consumes the lock, returning the underlying data.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Locks this lock with shared read access.
+    ///
+    /// Waits until there are no writers holding or waiting for the lock.
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        let permit = self.sem.acquire().await.unwrap();
+        RwLockReadGuard {
+            lock: self,
+            _permit: permit,
+        }
+    }
+
+    /// Locks this lock with exclusive write access.
+    ///
+    /// Waits until all readers and writers release the lock.
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        let permit = self.sem.acquire_many(MAX_READS).await.unwrap();
+        RwLockWriteGuard {
+            lock: self,
+            _permit: permit,
+        }
+    }
+
+    /// Attempts to acquire this lock with shared read access without waiting.
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        self.sem.try_acquire().map(|permit| RwLockReadGuard {
+            lock: self,
+            _permit: permit,
+        })
+    }
+
+    /// Attempts to acquire this lock with exclusive write access without waiting.
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        self.sem
+            .try_acquire_many(MAX_READS)
+            .map(|permit| RwLockWriteGuard {
+                lock: self,
+                _permit: permit,
+            })
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// No locking is needed, mutable borrow guarantees exclusive access.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        RwLock::new(T::default())
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Some(guard) => d.field("data", &&*guard),
+            None => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+/// Shared read access guard, lock is released on drop.
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+    _permit: SemaphorePermit,
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // read permit guarantees there is no writer
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Exclusive write access guard, lock is released on drop.
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+    _permit: SemaphorePermit,
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // write permit guarantees exclusive access
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // write permit guarantees exclusive access
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use super::*;
+    use crate::future::lazy;
+
+    #[ntex_macros::rt_test2]
+    async fn test_rwlock() {
+        let lock = RwLock::new(1);
+        assert!(format!("{:?}", lock).contains('1'));
+
+        let r1 = lock.read().await;
+        let r2 = lock.try_read().unwrap();
+        assert_eq!(*r1 + *r2, 2);
+        assert!(lock.try_write().is_none());
+        assert!(format!("{:?}", lock).contains("data"));
+
+        // queued writer blocks new readers
+        let mut w = Box::pin(lock.write());
+        assert!(lazy(|cx| w.as_mut().poll(cx)).await.is_pending());
+        assert!(lock.try_read().is_none());
+
+        drop(r1);
+        assert!(lazy(|cx| w.as_mut().poll(cx)).await.is_pending());
+        drop(r2);
+        let mut guard = w.await;
+        *guard = 2;
+        assert!(lock.try_read().is_none());
+        assert!(format!("{:?}", lock).contains("locked"));
+        drop(guard);
+
+        assert_eq!(*lock.read().await, 2);
+        *lock.write().await += 1;
+        let mut lock = lock;
+        *lock.get_mut() += 1;
+        assert_eq!(lock.into_inner(), 4);
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::VecDeque;
+use std::task::{Context, Poll, Waker};
+use std::{cell::RefCell, fmt, future::poll_fn, rc::Rc};
+
+use slab::Slab;
+
+/// Async counting semaphore.
+///
+/// Waiters are served in FIFO order, a waiter requesting many permits
+/// blocks all waiters queued after it, until enough permits are released.
+#[derive(Clone)]
+pub struct Semaphore(Rc<RefCell<Inner>>);
+
+struct Inner {
+    permits: usize,
+    closed: bool,
+    waiters: Slab<Waiter>,
+    queue: VecDeque<usize>,
+}
+
+struct Waiter {
+    needed: usize,
+    granted: bool,
+    waker: Option<Waker>,
+}
+
+impl Inner {
+    /// Grant permits to queued waiters
+    fn notify(&mut self) {
+        while let Some(key) = self.queue.front() {
+            let waiter = &mut self.waiters[*key];
+            if waiter.needed > self.permits {
+                break;
+            }
+            self.permits -= waiter.needed;
+            waiter.granted = true;
+            if let Some(waker) = waiter.waker.take() {
+                waker.wake();
+            }
+            self.queue.pop_front();
+        }
+    }
+}
+
+impl Semaphore {
+    /// Create semaphore with specified number of permits.
+    pub fn new(permits: usize) -> Self {
+        Semaphore(Rc::new(RefCell::new(Inner {
+            permits,
+            closed: false,
+            waiters: Slab::new(),
+            queue: VecDeque::new(),
+        })))
+    }
+
+    /// Returns the number of available permits
+    pub fn available_permits(&self) -> usize {
+        self.0.borrow().permits
+    }
+
+    /// Returns the number of tasks waiting for permits
+    pub fn waiters(&self) -> usize {
+        self.0.borrow().queue.len()
+    }
+
+    /// Adds permits to the semaphore.
+    pub fn add_permits(&self, n: usize) {
+        let mut inner = self.0.borrow_mut();
+        inner.permits += n;
+        inner.notify();
+    }
+
+    /// Closes the semaphore.
+    ///
+    /// All pending and future acquire operations fail,
+    /// acquired permits are still valid.
+    pub fn close(&self) {
+        let mut inner = self.0.borrow_mut();
+        inner.closed = true;
+        inner.queue.clear();
+        for (_, waiter) in inner.waiters.iter_mut() {
+            if let Some(waker) = waiter.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Returns whether the semaphore is closed
+    pub fn is_closed(&self) -> bool {
+        self.0.borrow().closed
+    }
+
+    /// Acquire single permit.
+    pub async fn acquire(&self) -> Result<SemaphorePermit, AcquireError> {
+        self.acquire_many(1).await
+    }
+
+    /// Acquire `n` permits.
+    ///
+    /// Waits until all permits are available, permits are acquired at once.
+    pub async fn acquire_many(&self, n: usize) -> Result<SemaphorePermit, AcquireError> {
+        let mut acquire = Acquire {
+            sem: self,
+            needed: n,
+            key: None,
+        };
+        poll_fn(|cx| acquire.poll(cx)).await
+    }
+
+    /// Try to acquire single permit without waiting.
+    pub fn try_acquire(&self) -> Option<SemaphorePermit> {
+        self.try_acquire_many(1)
+    }
+
+    /// Try to acquire `n` permits without waiting.
+    ///
+    /// Fails if there are queued waiters, even if enough permits are available.
+    pub fn try_acquire_many(&self, n: usize) -> Option<SemaphorePermit> {
+        let mut inner = self.0.borrow_mut();
+        if !inner.closed && inner.queue.is_empty() && inner.permits >= n {
+            inner.permits -= n;
+            Some(SemaphorePermit {
+                sem: self.clone(),
+                permits: n,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn release(&self, n: usize) {
+        let mut inner = self.0.borrow_mut();
+        inner.permits += n;
+        inner.notify();
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.0.borrow();
+        f.debug_struct("Semaphore")
+            .field("permits", &inner.permits)
+            .field("waiters", &inner.queue.len())
+            .field("closed", &inner.closed)
+            .finish()
+    }
+}
+
+struct Acquire<'a> {
+    sem: &'a Semaphore,
+    needed: usize,
+    key: Option<usize>,
+}
+
+impl Acquire<'_> {
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<SemaphorePermit, AcquireError>> {
+        let mut inner = self.sem.0.borrow_mut();
+
+        if let Some(key) = self.key {
+            if inner.waiters[key].granted {
+                inner.waiters.remove(key);
+                self.key = None;
+            } else if inner.closed {
+                inner.waiters.remove(key);
+                self.key = None;
+                return Poll::Ready(Err(AcquireError));
+            } else {
+                inner.waiters[key].waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+        } else if inner.closed {
+            return Poll::Ready(Err(AcquireError));
+        } else if inner.queue.is_empty() && inner.permits >= self.needed {
+            inner.permits -= self.needed;
+        } else {
+            let key = inner.waiters.insert(Waiter {
+                needed: self.needed,
+                granted: false,
+                waker: Some(cx.waker().clone()),
+            });
+            inner.queue.push_back(key);
+            self.key = Some(key);
+            return Poll::Pending;
+        }
+
+        Poll::Ready(Ok(SemaphorePermit {
+            sem: self.sem.clone(),
+            permits: self.needed,
+        }))
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let mut inner = self.sem.0.borrow_mut();
+            let waiter = inner.waiters.remove(key);
+            if waiter.granted {
+                // permits were granted but not taken
+                inner.permits += waiter.needed;
+            } else {
+                inner.queue.retain(|k| *k != key);
+            }
+            // removed waiter could block others
+            inner.notify();
+        }
+    }
+}
+
+/// Permits acquired from a semaphore.
+///
+/// Permits are returned to the semaphore on drop.
+pub struct SemaphorePermit {
+    sem: Semaphore,
+    permits: usize,
+}
+
+impl SemaphorePermit {
+    /// Returns the number of permits held
+    pub fn num_permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Forget permits, permits are not returned to the semaphore.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for SemaphorePermit {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.sem.release(self.permits);
+        }
+    }
+}
+
+impl fmt::Debug for SemaphorePermit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemaphorePermit")
+            .field("permits", &self.permits)
+            .finish()
+    }
+}
+
+/// Error returned when semaphore is closed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AcquireError;
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "semaphore closed")
+    }
+}
+
+impl std::error::Error for AcquireError {}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+
+    use super::*;
+    use crate::future::lazy;
+
+    #[ntex_macros::rt_test2]
+    async fn test_semaphore() {
+        let sem = Semaphore::new(2);
+        assert!(format!("{:?}", sem).contains("Semaphore"));
+
+        let p1 = sem.acquire().await.unwrap();
+        let p2 = sem.try_acquire().unwrap();
+        assert!(format!("{:?}", p1).contains("SemaphorePermit"));
+        assert_eq!(sem.available_permits(), 0);
+        assert!(sem.try_acquire().is_none());
+
+        let mut fut = Box::pin(sem.acquire());
+        assert!(lazy(|cx| fut.as_mut().poll(cx)).await.is_pending());
+        assert_eq!(sem.waiters(), 1);
+
+        drop(p1);
+        let p3 = fut.await.unwrap();
+        assert_eq!(sem.available_permits(), 0);
+        drop(p2);
+        drop(p3);
+        assert_eq!(sem.available_permits(), 2);
+
+        let p = sem.acquire_many(2).await.unwrap();
+        assert_eq!(p.num_permits(), 2);
+        p.forget();
+        assert_eq!(sem.available_permits(), 0);
+        sem.add_permits(3);
+        assert_eq!(sem.available_permits(), 3);
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_fairness() {
+        let sem = Semaphore::new(2);
+        let p1 = sem.acquire().await.unwrap();
+
+        // waiter for many permits blocks following waiters
+        let mut fut1 = Box::pin(sem.acquire_many(2));
+        let mut fut2 = Box::pin(sem.acquire());
+        assert!(lazy(|cx| fut1.as_mut().poll(cx)).await.is_pending());
+        assert!(lazy(|cx| fut2.as_mut().poll(cx)).await.is_pending());
+        assert!(sem.try_acquire().is_none());
+        assert_eq!(sem.available_permits(), 1);
+
+        drop(p1);
+        assert!(lazy(|cx| fut2.as_mut().poll(cx)).await.is_pending());
+        let p2 = fut1.await.unwrap();
+        drop(p2);
+        let _p3 = fut2.await.unwrap();
+        assert_eq!(sem.available_permits(), 1);
+
+        // dropped waiter unblocks queue
+        let p1 = sem.acquire().await.unwrap();
+        let mut fut1 = Box::pin(sem.acquire_many(2));
+        let mut fut2 = Box::pin(sem.acquire());
+        assert!(lazy(|cx| fut1.as_mut().poll(cx)).await.is_pending());
+        assert!(lazy(|cx| fut2.as_mut().poll(cx)).await.is_pending());
+        drop(p1);
+        assert!(lazy(|cx| fut2.as_mut().poll(cx)).await.is_pending());
+        drop(fut1);
+        assert!(fut2.await.is_ok());
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_close() {
+        let sem = Semaphore::new(1);
+        let p = sem.acquire().await.unwrap();
+        let mut fut = Box::pin(sem.acquire());
+        assert!(lazy(|cx| fut.as_mut().poll(cx)).await.is_pending());
+
+        sem.close();
+        assert!(sem.is_closed());
+        assert_eq!(fut.await.err(), Some(AcquireError));
+        assert!(sem.acquire().await.is_err());
+        assert!(sem.try_acquire().is_none());
+        assert!(format!("{}", AcquireError).contains("closed"));
+        drop(p);
+        assert_eq!(sem.available_permits(), 1);
+    }
+}
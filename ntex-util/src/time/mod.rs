@@ -4,6 +4,8 @@ use std::{cmp, future::poll_fn, future::Future, pin::Pin, task, task::Poll};
 mod types;
 mod wheel;
 
+pub mod testing;
+
 pub use self::types::{Millis, Seconds};
 pub use self::wheel::{now, query_system_time, system_time, TimerHandle};
 
@@ -18,6 +20,16 @@ pub fn sleep<T: Into<Millis>>(dur: T) -> Sleep {
     Sleep::new(dur.into())
 }
 
+/// Waits until `duration` has elapsed, using high resolution timer.
+///
+/// Unlike `sleep`, future does not use time wheel and completes with
+/// millisecond precision. High resolution timers are more expensive,
+/// use them only for short and precise deadlines.
+#[inline]
+pub fn sleep_precise<T: Into<Millis>>(dur: T) -> Sleep {
+    Sleep::new_precise(dur.into())
+}
+
 /// Waits until `duration` has elapsed.
 ///
 /// This is similar to `sleep` future, but in case of `0` duration deadline future
@@ -27,6 +39,15 @@ pub fn deadline<T: Into<Millis>>(dur: T) -> Deadline {
     Deadline::new(dur.into())
 }
 
+/// Waits until `duration` has elapsed, using high resolution timer.
+///
+/// This is similar to `deadline` future, but completes with
+/// millisecond precision.
+#[inline]
+pub fn deadline_precise<T: Into<Millis>>(dur: T) -> Deadline {
+    Deadline::new_precise(dur.into())
+}
+
 /// Creates new [`Interval`] that yields with interval of `period`.
 ///
 /// An interval will tick indefinitely. At any time, the [`Interval`] value can
@@ -50,6 +71,17 @@ where
     Timeout::new_with_delay(future, Sleep::new(dur.into()))
 }
 
+/// Require a `Future` to complete before the specified duration has elapsed,
+/// using high resolution timer.
+#[inline]
+pub fn timeout_precise<T, U>(dur: U, future: T) -> Timeout<T>
+where
+    T: Future,
+    U: Into<Millis>,
+{
+    Timeout::new_with_delay(future, Sleep::new_precise(dur.into()))
+}
+
 /// Require a `Future` to complete before the specified duration has elapsed.
 ///
 /// If the future completes before the duration has elapsed, then the completed
@@ -95,6 +127,14 @@ impl Sleep {
         }
     }
 
+    /// Create new sleep future with high resolution timer
+    #[inline]
+    pub fn new_precise(duration: Millis) -> Sleep {
+        Sleep {
+            hnd: TimerHandle::new_precise(duration.0 as u64),
+        }
+    }
+
     /// Returns `true` if `Sleep` has elapsed.
     #[inline]
     pub fn is_elapsed(&self) -> bool {
@@ -145,6 +185,7 @@ impl Future for Sleep {
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 pub struct Deadline {
     hnd: Option<TimerHandle>,
+    precise: bool,
 }
 
 impl Deadline {
@@ -154,9 +195,29 @@ impl Deadline {
         if duration.0 != 0 {
             Deadline {
                 hnd: Some(TimerHandle::new(duration.0 as u64)),
+                precise: false,
+            }
+        } else {
+            Deadline {
+                hnd: None,
+                precise: false,
+            }
+        }
+    }
+
+    /// Create new deadline future with high resolution timer
+    #[inline]
+    pub fn new_precise(duration: Millis) -> Deadline {
+        if duration.0 != 0 {
+            Deadline {
+                hnd: Some(TimerHandle::new_precise(duration.0 as u64)),
+                precise: true,
             }
         } else {
-            Deadline { hnd: None }
+            Deadline {
+                hnd: None,
+                precise: true,
+            }
         }
     }
 
@@ -172,6 +233,8 @@ impl Deadline {
         if millis.0 != 0 {
             if let Some(ref mut hnd) = self.hnd {
                 hnd.reset(millis.0 as u64);
+            } else if self.precise {
+                self.hnd = Some(TimerHandle::new_precise(millis.0 as u64));
             } else {
                 self.hnd = Some(TimerHandle::new(millis.0 as u64));
             }
@@ -462,6 +525,32 @@ mod tests {
         assert!(format!("{:?}", dl).contains("Deadline"));
     }
 
+    #[ntex_macros::rt_test2]
+    async fn test_precise() {
+        let time = time::Instant::now();
+        sleep_precise(Millis(3)).await;
+        let elapsed = time::Instant::now() - time;
+        assert!(elapsed >= time::Duration::from_millis(3));
+        assert!(elapsed < time::Duration::from_millis(15), "{:?}", elapsed);
+
+        let fut = sleep_precise(Millis(10000));
+        assert!(!fut.is_elapsed());
+        fut.reset(Millis(2));
+        fut.await;
+
+        let mut dl = deadline_precise(Millis(0));
+        assert!(dl.is_elapsed());
+        assert!(lazy(|cx| dl.poll_elapsed(cx)).await.is_pending());
+        dl.reset(Millis(2));
+        assert!(!dl.is_elapsed());
+        dl.await;
+
+        assert!(timeout_precise(Millis(2), sleep(Millis(100)))
+            .await
+            .is_err());
+        assert_eq!(timeout_precise(Millis(100), async { 1 }).await, Ok(1));
+    }
+
     #[ntex_macros::rt_test2]
     async fn test_interval() {
         let mut int = interval(Millis(250));
@@ -0,0 +1,152 @@
+//! Virtual time for tests.
+//!
+//! Time could be paused on current thread, paused clock does not move
+//! until it is advanced manually. Timers registered with `sleep()`,
+//! `interval()`, `timeout()` and other timer based utilities fire
+//! when clock is advanced past their deadlines, so timeouts could be
+//! tested without waiting for wall clock time.
+//!
+//! High resolution timers (`sleep_precise()` etc) always use wall clock.
+//!
+//! ```rust
+//! use ntex_util::time::{sleep, testing, Millis};
+//!
+//! #[ntex::test]
+//! async fn test_timeout() {
+//!     testing::pause();
+//!
+//!     let fut = sleep(Millis(60_000));
+//!     testing::advance(Millis(60_000)).await;
+//!     assert!(fut.is_elapsed());
+//!
+//!     testing::resume();
+//! }
+//! ```
+use std::{future::poll_fn, task::Poll, time::Duration};
+
+use super::{wheel, Millis};
+
+/// Number of scheduler turns after each timers bucket is fired
+const TURNS: usize = 16;
+
+/// Pause time on current thread.
+///
+/// Clock is frozen at current time, `now()` and `system_time()` return
+/// paused time until time is resumed.
+pub fn pause() {
+    wheel::pause()
+}
+
+/// Resume time on current thread.
+pub fn resume() {
+    wheel::resume()
+}
+
+/// Check if time is paused on current thread.
+pub fn is_paused() -> bool {
+    wheel::is_paused()
+}
+
+/// Advance paused clock by `dur`.
+///
+/// Timers are fired in order of their deadlines, tasks woken by a timer
+/// get chance to run and register new timers before next timer fires.
+///
+/// # Panics
+///
+/// Panics if time is not paused.
+pub async fn advance<T: Into<Millis>>(dur: T) {
+    assert!(is_paused(), "time is not paused");
+
+    // let spawned tasks register their timers
+    yield_now().await;
+
+    let deadline = super::now() + Duration::from(dur.into());
+    while wheel::advance_step(deadline) {
+        yield_now().await;
+    }
+    yield_now().await;
+}
+
+/// Let other tasks run
+async fn yield_now() {
+    for _ in 0..TURNS {
+        let mut yielded = false;
+        poll_fn(|cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, time::Instant};
+
+    use super::*;
+    use crate::time::{now, sleep, system_time, timeout, Seconds};
+
+    #[ntex_macros::rt_test2]
+    async fn test_advance() {
+        pause();
+        assert!(is_paused());
+
+        let start = Instant::now();
+        let paused = now();
+        let stime = system_time();
+
+        let fut = sleep(Seconds(30));
+        assert!(!fut.is_elapsed());
+        advance(Seconds(29)).await;
+        assert!(!fut.is_elapsed());
+        advance(Seconds(3)).await;
+        assert!(fut.is_elapsed());
+
+        assert_eq!(now() - paused, Duration::from_secs(32));
+        assert_eq!(
+            system_time().duration_since(stime).unwrap(),
+            Duration::from_secs(32)
+        );
+
+        // timeout of pending future
+        let res = crate::spawn(timeout(Seconds(60), std::future::pending::<()>()));
+        advance(Seconds(70)).await;
+        assert!(res.await.unwrap().is_err());
+
+        // chained timers
+        let count = Rc::new(Cell::new(0));
+        let count2 = count.clone();
+        crate::spawn(async move {
+            for _ in 0..10 {
+                sleep(Seconds(1)).await;
+                count2.set(count2.get() + 1);
+            }
+        });
+        advance(Millis(10_500)).await;
+        assert_eq!(count.get(), 10);
+
+        // wall clock time is not used
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        resume();
+        assert!(!is_paused());
+        let fut = sleep(Millis(50));
+        fut.await;
+    }
+
+    #[ntex_macros::rt_test2]
+    async fn test_not_paused() {
+        assert!(!is_paused());
+        let start = now();
+        resume();
+        assert!(!is_paused());
+        sleep(Millis(50)).await;
+        assert!(now() > start);
+    }
+}
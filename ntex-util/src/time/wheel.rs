@@ -93,8 +93,68 @@ pub fn query_system_time() -> SystemTime {
     TIMER.with(Timer::system_time)
 }
 
+/// Pause time on current thread
+pub(super) fn pause() {
+    TIMER.with(|t| {
+        if t.0.mock_time.get().is_none() {
+            let now = Instant::now();
+            t.0.mock_time.set(Some(now));
+            t.0.mock_base.set(Some((now, SystemTime::now())));
+        }
+    })
+}
+
+/// Resume time on current thread
+pub(super) fn resume() {
+    TIMER.with(|t| {
+        if t.0.mock_time.take().is_some() {
+            t.0.mock_base.set(None);
+            if t.0.elapsed_time.get().is_some() {
+                t.0.elapsed_time.set(Some(Instant::now()));
+            }
+
+            let mut flags = t.0.flags.get();
+            if flags.contains(Flags::DRIVER_STARTED) {
+                flags.insert(Flags::DRIVER_RECALC);
+                t.0.flags.set(flags);
+                t.0.driver.wake();
+            }
+        }
+    })
+}
+
+/// Check if time is paused on current thread
+pub(super) fn is_paused() -> bool {
+    TIMER.with(|t| t.0.mock_time.get().is_some())
+}
+
+/// Fire next timers bucket if it expires before `deadline`
+///
+/// Returns `false` if there is no such bucket, paused clock is
+/// set to `deadline` in that case.
+pub(super) fn advance_step(deadline: Instant) -> bool {
+    TIMER.with(|t| match t.0.next_deadline() {
+        Some(next) if next <= deadline => {
+            let now = max(next, t.0.mock_time.get().unwrap_or(next));
+            t.0.mock_time.set(Some(now));
+            t.0.expire(now);
+            true
+        }
+        _ => {
+            t.0.mock_time.set(Some(deadline));
+            false
+        }
+    })
+}
+
 #[derive(Debug)]
-pub struct TimerHandle(usize);
+pub struct TimerHandle(Handle);
+
+#[derive(Debug)]
+enum Handle {
+    Wheel(usize),
+    Precise(PreciseTimer),
+}
 
 impl TimerHandle {
     /// Createt new timer and return handle
@@ -102,31 +162,92 @@ impl TimerHandle {
         TIMER.with(|t| t.add_timer(millis))
     }
 
+    /// Create new high resolution timer and return handle
+    ///
+    /// High resolution timer does not use time wheel and fires
+    /// with millisecond precision.
+    pub fn new_precise(millis: u64) -> Self {
+        TimerHandle(Handle::Precise(PreciseTimer::new(millis)))
+    }
+
+    /// Check if timer is high resolution timer
+    pub fn is_precise(&self) -> bool {
+        matches!(self.0, Handle::Precise(_))
+    }
+
     /// Resets the `TimerHandle` instance to a new deadline.
     pub fn reset(&self, millis: u64) {
-        TIMER.with(|t| t.update_timer(self.0, millis))
+        match self.0 {
+            Handle::Wheel(no) => TIMER.with(|t| t.update_timer(no, millis)),
+            Handle::Precise(ref timer) => timer.reset(millis),
+        }
     }
 
     pub fn is_elapsed(&self) -> bool {
-        TIMER.with(|t| t.0.inner.borrow().timers[self.0].bucket.is_none())
+        match self.0 {
+            Handle::Wheel(no) => {
+                TIMER.with(|t| t.0.inner.borrow().timers[no].bucket.is_none())
+            }
+            Handle::Precise(ref timer) => timer.is_elapsed(),
+        }
     }
 
     pub fn poll_elapsed(&self, cx: &mut task::Context<'_>) -> Poll<()> {
-        TIMER.with(|t| {
-            let entry = &t.0.inner.borrow().timers[self.0];
-            if entry.bucket.is_none() {
-                Poll::Ready(())
-            } else {
-                entry.task.register(cx.waker());
-                Poll::Pending
-            }
-        })
+        match self.0 {
+            Handle::Wheel(no) => TIMER.with(|t| {
+                let entry = &t.0.inner.borrow().timers[no];
+                if entry.bucket.is_none() {
+                    Poll::Ready(())
+                } else {
+                    entry.task.register(cx.waker());
+                    Poll::Pending
+                }
+            }),
+            Handle::Precise(ref timer) => timer.poll_elapsed(cx),
+        }
     }
 }
 
 impl Drop for TimerHandle {
     fn drop(&mut self) {
-        TIMER.with(|t| t.remove_timer(self.0));
+        if let Handle::Wheel(no) = self.0 {
+            TIMER.with(|t| t.remove_timer(no));
+        }
+    }
+}
+
+/// High resolution timer, driven by dedicated timer thread
+#[derive(Debug)]
+struct PreciseTimer {
+    delay: RefCell<Delay>,
+    deadline: Cell<Instant>,
+}
+
+impl PreciseTimer {
+    fn new(millis: u64) -> Self {
+        let dur = Duration::from_millis(millis);
+        PreciseTimer {
+            delay: RefCell::new(Delay::new(dur)),
+            deadline: Cell::new(Instant::now() + dur),
+        }
+    }
+
+    fn reset(&self, millis: u64) {
+        let dur = Duration::from_millis(millis);
+        self.deadline.set(Instant::now() + dur);
+        self.delay.borrow_mut().reset(dur);
+    }
+
+    fn is_elapsed(&self) -> bool {
+        Instant::now() >= self.deadline.get()
+    }
+
+    fn poll_elapsed(&self, cx: &mut task::Context<'_>) -> Poll<()> {
+        if self.is_elapsed() {
+            Poll::Ready(())
+        } else {
+            Pin::new(&mut *self.delay.borrow_mut()).poll(cx)
+        }
     }
 }
 
@@ -156,6 +277,8 @@ struct TimerInner {
     lowres_time: Cell<Option<Instant>>,
     lowres_stime: Cell<Option<SystemTime>>,
     lowres_driver: LocalWaker,
+    mock_time: Cell<Option<Instant>>,
+    mock_base: Cell<Option<(Instant, SystemTime)>>,
     inner: RefCell<TimerMod>,
 }
 
@@ -179,6 +302,8 @@ impl Timer {
             lowres_time: Cell::new(None),
             lowres_stime: Cell::new(None),
             lowres_driver: LocalWaker::new(),
+            mock_time: Cell::new(None),
+            mock_base: Cell::new(None),
             inner: RefCell::new(TimerMod {
                 buckets: Self::create_buckets(),
                 timers: Slab::default(),
@@ -200,7 +325,9 @@ impl Timer {
     }
 
     fn now(&self) -> Instant {
-        if let Some(cur) = self.0.lowres_time.get() {
+        if let Some(cur) = self.0.mock_time.get() {
+            cur
+        } else if let Some(cur) = self.0.lowres_time.get() {
             cur
         } else {
             let now = Instant::now();
@@ -220,7 +347,9 @@ impl Timer {
     }
 
     fn system_time(&self) -> SystemTime {
-        if let Some(cur) = self.0.lowres_stime.get() {
+        if let Some(cur) = self.0.mock_system_time() {
+            cur
+        } else if let Some(cur) = self.0.lowres_stime.get() {
             cur
         } else {
             let now = SystemTime::now();
@@ -252,7 +381,7 @@ impl Timer {
                 bucket: None,
                 task: LocalWaker::new(),
             });
-            return TimerHandle(no);
+            return TimerHandle(Handle::Wheel(no));
         }
 
         let mut flags = self.0.flags.get();
@@ -289,7 +418,7 @@ impl Timer {
             }
         }
 
-        TimerHandle(no)
+        TimerHandle(Handle::Wheel(no))
     }
 
     /// Update existing timer
@@ -475,7 +604,7 @@ impl TimerInner {
         if let Some(elapsed_time) = self.elapsed_time.get() {
             elapsed_time
         } else {
-            let elapsed_time = Instant::now();
+            let elapsed_time = self.mock_time.get().unwrap_or_else(Instant::now);
             self.elapsed_time.set(Some(elapsed_time));
             elapsed_time
         }
@@ -487,6 +616,41 @@ impl TimerInner {
             .execute_expired_timers(self.next_expiry.get());
     }
 
+    /// Fire expired timers and find next expiration bucket
+    fn expire(&self, now: Instant) -> bool {
+        self.elapsed.set(self.next_expiry.get());
+        self.elapsed_time.set(Some(now));
+        self.execute_expired_timers();
+
+        if let Some(next_expiry) = self.next_pending_bucket() {
+            self.next_expiry.set(next_expiry);
+            true
+        } else {
+            self.next_expiry.set(u64::MAX);
+            self.elapsed_time.set(None);
+            false
+        }
+    }
+
+    fn mock_system_time(&self) -> Option<SystemTime> {
+        if let (Some(cur), Some((base, base_stime))) =
+            (self.mock_time.get(), self.mock_base.get())
+        {
+            Some(base_stime + (cur - base))
+        } else {
+            None
+        }
+    }
+
+    /// Deadline of next expiration bucket
+    fn next_deadline(&self) -> Option<Instant> {
+        if self.next_expiry.get() == u64::MAX {
+            None
+        } else {
+            Some(self.elapsed_time() + Duration::from_millis(self.next_expiry_ms()))
+        }
+    }
+
     /// Find next expiration bucket
     fn next_pending_bucket(&self) -> Option<u64> {
         let inner = self.inner.borrow_mut();
@@ -627,6 +791,11 @@ impl Future for TimerDriver {
     fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
         self.0.driver.register(cx.waker());
 
+        // paused time, timers are fired by `advance()`
+        if self.0.mock_time.get().is_some() {
+            return Poll::Pending;
+        }
+
         let mut flags = self.0.flags.get();
         if flags.contains(Flags::DRIVER_RECALC) {
             flags.remove(Flags::DRIVER_RECALC);
@@ -647,19 +816,10 @@ impl Future for TimerDriver {
                 .poll(cx)
                 .is_ready()
             {
-                let now = Instant::now();
-                self.0.elapsed.set(self.0.next_expiry.get());
-                self.0.elapsed_time.set(Some(now));
-                self.0.execute_expired_timers();
-
-                if let Some(next_expiry) = self.0.next_pending_bucket() {
-                    self.0.next_expiry.set(next_expiry);
+                if self.0.expire(Instant::now()) {
                     let dur = Duration::from_millis(self.0.next_expiry_ms());
                     self.0.inner.borrow_mut().driver_sleep.reset(dur);
                     continue;
-                } else {
-                    self.0.next_expiry.set(u64::MAX);
-                    self.0.elapsed_time.set(None);
                 }
             }
             return Poll::Pending;
@@ -0,0 +1,74 @@
+#![feature(test)]
+#![deny(warnings, rust_2018_idioms)]
+
+extern crate test;
+
+use ntex::codec::Decoder;
+use ntex::http::h1::Codec;
+use ntex::util::BytesMut;
+use test::Bencher;
+
+const SMALL: &[u8] = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+
+const LARGE: &[u8] = b"GET /api/v1/users/12345/profile?fields=name,email&expand=true HTTP/1.1\r\n\
+Host: api.example.com\r\n\
+User-Agent: Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/115.0\r\n\
+Accept: text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8\r\n\
+Accept-Language: en-US,en;q=0.5\r\n\
+Accept-Encoding: gzip, deflate, br\r\n\
+Connection: keep-alive\r\n\
+Cookie: session=0123456789abcdef0123456789abcdef; theme=dark; lang=en\r\n\
+Upgrade-Insecure-Requests: 1\r\n\
+Sec-Fetch-Dest: document\r\n\
+Sec-Fetch-Mode: navigate\r\n\
+Sec-Fetch-Site: none\r\n\
+Sec-Fetch-User: ?1\r\n\
+Cache-Control: max-age=0\r\n\
+\r\n";
+
+fn decode(b: &mut Bencher, data: &'static [u8]) {
+    let codec = Codec::default();
+    b.bytes = data.len() as u64;
+    b.iter(|| {
+        let mut buf = BytesMut::from(data);
+        test::black_box(codec.decode(&mut buf).unwrap().unwrap());
+    })
+}
+
+#[bench]
+fn decode_small(b: &mut Bencher) {
+    decode(b, SMALL)
+}
+
+#[bench]
+fn decode_large(b: &mut Bencher) {
+    decode(b, LARGE)
+}
+
+#[bench]
+fn decode_partial(b: &mut Bencher) {
+    // request line without line ending
+    let data = &LARGE[..60];
+    let codec = Codec::default();
+    b.bytes = data.len() as u64;
+    b.iter(|| {
+        let mut buf = BytesMut::from(data);
+        test::black_box(codec.decode(&mut buf).unwrap());
+    })
+}
+
+#[bench]
+fn decode_pipelined(b: &mut Bencher) {
+    let mut data = Vec::new();
+    for _ in 0..16 {
+        data.extend_from_slice(LARGE);
+    }
+    let codec = Codec::default();
+    b.bytes = data.len() as u64;
+    b.iter(|| {
+        let mut buf = BytesMut::from(&data[..]);
+        while let Some(item) = codec.decode(&mut buf).unwrap() {
+            test::black_box(item);
+        }
+    })
+}
@@ -0,0 +1,271 @@
+//! Fuzzing entry points for protocol codecs.
+//!
+//! Each function accepts arbitrary bytes, feeds them through a codec and
+//! checks round-trip invariants on everything that decodes successfully.
+//! Functions never panic on malformed input, a panic indicates a bug.
+//!
+//! Fuzz targets for `cargo fuzz` live in the `fuzz/` directory.
+use crate::codec::{Decoder, Encoder};
+use crate::http::body::BodySize;
+use crate::http::error::DecodeError;
+use crate::http::h1::{self, Message, MessageType, PayloadItem, PayloadType};
+use crate::http::header::{HeaderValue, DATE};
+use crate::http::{RequestHead, RequestHeadType};
+use crate::util::{Bytes, BytesMut};
+
+const DATE_VALUE: HeaderValue = HeaderValue::from_static("Thu, 01 Jan 1970 00:00:00 GMT");
+
+// decoder accepts up to 96 headers, re-encoded head gets extra date header
+const MAX_HEADERS: usize = 95;
+
+/// Decode stream of http/1 requests.
+///
+/// Every decoded request head is re-encoded and decoded again,
+/// method and version must survive round-trip.
+pub fn h1_request(data: &[u8]) {
+    let codec = h1::Codec::default();
+    let mut buf = BytesMut::from(data);
+
+    loop {
+        let (req, payload) = match codec.decode(&mut buf) {
+            Ok(Some(item)) => item,
+            Ok(None) | Err(_) => return,
+        };
+
+        let head = req.head();
+        if head.headers.iter().count() < MAX_HEADERS {
+            let mut new_head = RequestHead::default();
+            new_head.method = head.method.clone();
+            new_head.uri = head.uri.clone();
+            new_head.version = head.version;
+            new_head.headers = head.headers.clone();
+            new_head.headers.insert(DATE, DATE_VALUE);
+
+            let mut dst = BytesMut::new();
+            let item = (RequestHeadType::Owned(new_head), BodySize::None);
+            if h1::ClientCodec::default()
+                .encode(Message::Item(item), &mut dst)
+                .is_ok()
+            {
+                match h1::Codec::default().decode(&mut dst) {
+                    Ok(Some((req2, _))) => {
+                        assert_eq!(req2.head().method, head.method);
+                        assert_eq!(req2.head().version, head.version);
+                    }
+                    Ok(None) => panic!("Re-encoded request head is incomplete"),
+                    Err(DecodeError::TooLarge(_)) => (),
+                    Err(err) => panic!("Cannot decode re-encoded request: {:?}", err),
+                }
+            }
+        }
+
+        match payload {
+            PayloadType::None => (),
+            PayloadType::Payload(pl) | PayloadType::Stream(pl) => loop {
+                match pl.decode(&mut buf) {
+                    Ok(Some(PayloadItem::Chunk(_))) => (),
+                    Ok(Some(PayloadItem::Eof)) => break,
+                    Ok(None) | Err(_) => return,
+                }
+            },
+        }
+    }
+}
+
+/// Decode stream of http/1 responses with payloads.
+pub fn h1_response(data: &[u8]) {
+    let mut codec = h1::ClientCodec::default();
+    let mut buf = BytesMut::from(data);
+
+    loop {
+        match codec.decode(&mut buf) {
+            Ok(Some(_)) => (),
+            Ok(None) | Err(_) => return,
+        }
+
+        if codec.message_type() != MessageType::None {
+            let pl = codec.into_payload_codec();
+            loop {
+                match pl.decode(&mut buf) {
+                    Ok(Some(Some(_))) => (),
+                    Ok(Some(None)) => break,
+                    Ok(None) | Err(_) => return,
+                }
+            }
+            codec = pl.into_message_codec();
+        }
+    }
+}
+
+/// Decode chunked request body.
+///
+/// Decoded chunks are re-encoded and decoded again,
+/// body must survive round-trip.
+pub fn h1_chunked(data: &[u8]) {
+    let mut buf =
+        BytesMut::from(&b"POST / HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n"[..]);
+    buf.extend_from_slice(data);
+
+    let chunks = if let Some(chunks) = decode_chunked(&mut buf) {
+        chunks
+    } else {
+        return;
+    };
+
+    let mut head = RequestHead::default();
+    head.method = crate::http::Method::POST;
+    head.headers.insert(DATE, DATE_VALUE);
+
+    let codec = h1::ClientCodec::default();
+    let mut dst = BytesMut::new();
+    codec
+        .encode(
+            Message::Item((RequestHeadType::Owned(head), BodySize::Stream)),
+            &mut dst,
+        )
+        .unwrap();
+    // empty chunk terminates chunked body
+    for chunk in chunks.iter().filter(|c| !c.is_empty()) {
+        codec
+            .encode(Message::Chunk(Some(chunk.clone())), &mut dst)
+            .unwrap();
+    }
+    codec.encode(Message::Chunk(None), &mut dst).unwrap();
+
+    let chunks2 = decode_chunked(&mut dst).expect("Cannot decode re-encoded body");
+    assert_eq!(concat(&chunks), concat(&chunks2));
+}
+
+fn concat(chunks: &[Bytes]) -> Vec<u8> {
+    chunks.iter().flat_map(|c| c.iter().copied()).collect()
+}
+
+fn decode_chunked(buf: &mut BytesMut) -> Option<Vec<Bytes>> {
+    let pl = match h1::Codec::default().decode(buf) {
+        Ok(Some((_, PayloadType::Payload(pl)))) => pl,
+        _ => return None,
+    };
+
+    let mut chunks = Vec::new();
+    loop {
+        match pl.decode(buf) {
+            Ok(Some(PayloadItem::Chunk(chunk))) => chunks.push(chunk),
+            Ok(Some(PayloadItem::Eof)) => return Some(chunks),
+            Ok(None) | Err(_) => return None,
+        }
+    }
+}
+
+#[cfg(feature = "ws")]
+/// Decode stream of client websocket frames.
+///
+/// Decoded frames are re-encoded in client mode and decoded again,
+/// frames must survive round-trip.
+pub fn ws_frames(data: &[u8]) {
+    use crate::util::ByteString;
+    use crate::ws;
+
+    let codec = ws::Codec::new();
+    let mut buf = BytesMut::from(data);
+
+    let mut frames = Vec::new();
+    while let Ok(Some(frame)) = codec.decode(&mut buf) {
+        frames.push(frame);
+    }
+
+    let client = ws::Codec::new().client_mode();
+    let mut dst = BytesMut::new();
+    let mut expected = Vec::new();
+    for frame in frames {
+        let msg = match frame.clone() {
+            ws::Frame::Text(text) => match ByteString::try_from(text) {
+                Ok(text) => ws::Message::Text(text),
+                Err(_) => continue,
+            },
+            ws::Frame::Binary(bin) => ws::Message::Binary(bin),
+            ws::Frame::Continuation(item) => ws::Message::Continuation(item),
+            ws::Frame::Ping(data) => ws::Message::Ping(data),
+            ws::Frame::Pong(data) => ws::Message::Pong(data),
+            ws::Frame::Close(_) => continue,
+        };
+        if client.encode(msg, &mut dst).is_ok() {
+            expected.push(frame);
+        }
+    }
+
+    let codec = ws::Codec::new();
+    for frame in expected {
+        match codec.decode(&mut dst) {
+            Ok(Some(frame2)) => assert_eq!(frame, frame2),
+            res => panic!("Cannot decode re-encoded frame: {:?}", res),
+        }
+    }
+    assert!(dst.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_h1_request() {
+        h1_request(b"");
+        h1_request(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n");
+        h1_request(
+            b"POST /test HTTP/1.1\r\ncontent-length: 4\r\n\r\ndata\
+              GET /test2?q=1 HTTP/1.0\r\nconnection: keep-alive\r\n\r\n",
+        );
+        h1_request(
+            b"POST / HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n4\r\ndata\r\n0\r\n\r\n",
+        );
+        h1_request(b"GET / HTTP/1.1\r\nconnection: upgrade\r\nupgrade: websocket\r\n\r\n");
+        h1_request(b"\x00\xff GET / HTTP/1.1\r\n\r\n");
+        h1_request(b"GET / HTTP/1.1\r\ncontent-length: 1\r\ncontent-length: 2\r\n\r\n");
+    }
+
+    #[test]
+    fn test_h1_response() {
+        h1_response(b"");
+        h1_response(
+            b"HTTP/1.1 200 OK\r\ncontent-length: 4\r\n\r\ndata\
+              HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n4\r\ndata\r\n0\r\n\r\n\
+              HTTP/1.1 204 No Content\r\n\r\n",
+        );
+        h1_response(b"HTTP/1.1 200 OK\r\n\r\nbody until eof");
+        h1_response(b"HTTP/1.1 20x OK\r\n\r\n");
+    }
+
+    #[test]
+    fn test_h1_chunked() {
+        h1_chunked(b"");
+        h1_chunked(b"4\r\ndata\r\n4;ext=1\r\nline\r\n0\r\n\r\n");
+        h1_chunked(b"0\r\n\r\n");
+        h1_chunked(b"zz\r\n");
+        h1_chunked(b"ffffffffffffffffff\r\n");
+    }
+
+    #[cfg(feature = "ws")]
+    #[test]
+    fn test_ws_frames() {
+        use crate::ws;
+
+        ws_frames(b"");
+        ws_frames(b"\x81\x05hello");
+        ws_frames(b"\xff\xff\xff\xff");
+
+        let client = ws::Codec::new().client_mode();
+        let mut buf = BytesMut::new();
+        for msg in [
+            ws::Message::Text("text".into()),
+            ws::Message::Binary(Bytes::from_static(b"binary")),
+            ws::Message::Ping(Bytes::from_static(b"ping")),
+            ws::Message::Continuation(ws::Item::FirstBinary(Bytes::from_static(b"1"))),
+            ws::Message::Pong(Bytes::new()),
+            ws::Message::Continuation(ws::Item::Last(Bytes::from_static(b"2"))),
+            ws::Message::Close(None),
+        ] {
+            client.encode(msg, &mut buf).unwrap();
+        }
+        ws_frames(&buf);
+    }
+}
@@ -0,0 +1,140 @@
+use std::{error, fmt};
+
+use crate::util::{BufMut, Bytes, BytesMut};
+
+use super::Status;
+
+/// Grpc message serialization
+///
+/// Code generators implement this trait for generated message types.
+pub trait Message: Sized {
+    /// Serialize message to the buffer
+    fn encode(&self, dst: &mut BytesMut);
+
+    /// Deserialize message
+    fn decode(src: Bytes) -> Result<Self, MessageDecodeError>;
+}
+
+impl Message for Bytes {
+    fn encode(&self, dst: &mut BytesMut) {
+        dst.extend_from_slice(self);
+    }
+
+    fn decode(src: Bytes) -> Result<Self, MessageDecodeError> {
+        Ok(src)
+    }
+}
+
+impl Message for () {
+    fn encode(&self, _: &mut BytesMut) {}
+
+    fn decode(_: Bytes) -> Result<Self, MessageDecodeError> {
+        Ok(())
+    }
+}
+
+/// Message deserialization error
+#[derive(Debug, Clone)]
+pub struct MessageDecodeError(pub String);
+
+impl MessageDecodeError {
+    /// Create new error
+    pub fn new<T: Into<String>>(msg: T) -> Self {
+        MessageDecodeError(msg.into())
+    }
+}
+
+impl fmt::Display for MessageDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cannot decode message: {}", self.0)
+    }
+}
+
+impl error::Error for MessageDecodeError {}
+
+impl From<MessageDecodeError> for Status {
+    fn from(err: MessageDecodeError) -> Status {
+        Status::internal(err.to_string())
+    }
+}
+
+/// Encode message into length-prefixed grpc frame
+pub fn encode_frame<T: Message>(msg: &T, dst: &mut BytesMut) {
+    let start = dst.len();
+    dst.reserve(5);
+    dst.put_u8(0);
+    dst.put_u32(0);
+    msg.encode(dst);
+
+    let len = (dst.len() - start - 5) as u32;
+    dst[start + 1..start + 5].copy_from_slice(&len.to_be_bytes());
+}
+
+/// Decode length-prefixed grpc frame
+///
+/// Returns `None` if buffer does not contain complete frame. Compressed
+/// messages are not supported.
+pub fn decode_frame(src: &mut BytesMut, max_size: usize) -> Result<Option<Bytes>, Status> {
+    if src.len() < 5 {
+        return Ok(None);
+    }
+    if src[0] != 0 {
+        return Err(Status::unimplemented(
+            "Message compression is not supported",
+        ));
+    }
+    let len = u32::from_be_bytes([src[1], src[2], src[3], src[4]]) as usize;
+    if len > max_size {
+        return Err(Status::resource_exhausted(format!(
+            "Message size {} exceeds limit {}",
+            len, max_size
+        )));
+    }
+    if src.len() < len + 5 {
+        Ok(None)
+    } else {
+        let _ = src.split_to(5);
+        Ok(Some(src.split_to(len).freeze()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::Code;
+
+    #[test]
+    fn test_frame() {
+        let mut buf = BytesMut::new();
+        encode_frame(&Bytes::from_static(b"message"), &mut buf);
+        assert_eq!(&buf[..], b"\x00\x00\x00\x00\x07message");
+
+        encode_frame(&(), &mut buf);
+        assert_eq!(buf.len(), 17);
+
+        let mut part = BytesMut::from(&buf[..8]);
+        assert!(decode_frame(&mut part, 1024).unwrap().is_none());
+        assert_eq!(part.len(), 8);
+
+        let msg = decode_frame(&mut buf, 1024).unwrap().unwrap();
+        assert_eq!(msg, Bytes::from_static(b"message"));
+        let msg = decode_frame(&mut buf, 1024).unwrap().unwrap();
+        assert!(msg.is_empty());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_frame_errors() {
+        let mut buf = BytesMut::from(&b"\x01\x00\x00\x00\x01a"[..]);
+        let err = decode_frame(&mut buf, 1024).err().unwrap();
+        assert_eq!(err.code(), Code::Unimplemented);
+
+        let mut buf = BytesMut::new();
+        encode_frame(&Bytes::from_static(b"message"), &mut buf);
+        let err = decode_frame(&mut buf, 4).err().unwrap();
+        assert_eq!(err.code(), Code::ResourceExhausted);
+
+        let err: Status = MessageDecodeError::new("test").into();
+        assert_eq!(err.code(), Code::Internal);
+    }
+}
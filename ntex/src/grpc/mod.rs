@@ -0,0 +1,25 @@
+//! gRPC protocol support.
+//!
+//! Provides grpc message framing, status codes and `grpc-timeout` deadline
+//! propagation. Message serialization is pluggable via [`Message`] trait and
+//! grpc methods are described by [`MethodDef`] trait, so code generators could
+//! implement both traits for generated types.
+//!
+//! Grpc methods are served with `ntex::web` application, see
+//! [`web::grpc`](crate::web::grpc), so grpc services could share ports and
+//! middlewares with regular http routes.
+mod codec;
+mod request;
+mod status;
+mod timeout;
+
+pub use self::codec::{decode_frame, encode_frame, Message, MessageDecodeError};
+pub use self::request::{MethodDef, Request};
+pub use self::status::{Code, Status};
+pub use self::timeout::parse_timeout;
+
+/// Grpc content type
+pub const CONTENT_TYPE: &str = "application/grpc";
+
+/// Default max size of decoded message, 4Mb
+pub const MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
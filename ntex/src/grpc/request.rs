@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+use crate::http::HeaderMap;
+
+use super::Message;
+
+/// Grpc method definition
+///
+/// Code generators implement this trait for each method of grpc service.
+pub trait MethodDef {
+    /// Full method path, `/{package}.{Service}/{Method}`
+    const PATH: &'static str;
+
+    /// Method input message
+    type Input: Message;
+
+    /// Method output message
+    type Output: Message;
+}
+
+/// Grpc request
+#[derive(Debug)]
+pub struct Request<T> {
+    message: T,
+    metadata: HeaderMap,
+    timeout: Option<Duration>,
+    created: Instant,
+}
+
+impl<T> Request<T> {
+    /// Create new grpc request
+    pub fn new(message: T, metadata: HeaderMap, timeout: Option<Duration>) -> Self {
+        Request {
+            message,
+            metadata,
+            timeout,
+            created: Instant::now(),
+        }
+    }
+
+    /// Get reference to request message
+    pub fn get_ref(&self) -> &T {
+        &self.message
+    }
+
+    /// Get mutable reference to request message
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.message
+    }
+
+    /// Get request message
+    pub fn into_inner(self) -> T {
+        self.message
+    }
+
+    /// Request metadata (request headers)
+    pub fn metadata(&self) -> &HeaderMap {
+        &self.metadata
+    }
+
+    /// Request timeout, as specified by `grpc-timeout` header
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Request deadline
+    pub fn deadline(&self) -> Option<Instant> {
+        self.timeout.map(|timeout| self.created + timeout)
+    }
+
+    /// Remaining time until deadline
+    ///
+    /// Could be used for deadline propagation to downstream calls.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request() {
+        let mut req = Request::new(1u8, HeaderMap::new(), Some(Duration::from_secs(10)));
+        assert_eq!(*req.get_ref(), 1);
+        *req.get_mut() = 2;
+        assert!(req.metadata().is_empty());
+        assert_eq!(req.timeout(), Some(Duration::from_secs(10)));
+        assert!(req.deadline().is_some());
+        assert!(req.remaining().unwrap() <= Duration::from_secs(10));
+        assert_eq!(req.into_inner(), 2);
+
+        let req = Request::new((), HeaderMap::new(), None);
+        assert!(req.deadline().is_none());
+        assert!(req.remaining().is_none());
+    }
+}
@@ -0,0 +1,258 @@
+use std::{error, fmt};
+
+use crate::http::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// Grpc status codes
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Code {
+    /// The operation completed successfully.
+    Ok = 0,
+    /// The operation was cancelled.
+    Cancelled = 1,
+    /// Unknown error.
+    Unknown = 2,
+    /// Client specified an invalid argument.
+    InvalidArgument = 3,
+    /// Deadline expired before operation could complete.
+    DeadlineExceeded = 4,
+    /// Some requested entity was not found.
+    NotFound = 5,
+    /// Some entity that we attempted to create already exists.
+    AlreadyExists = 6,
+    /// The caller does not have permission to execute the specified operation.
+    PermissionDenied = 7,
+    /// Some resource has been exhausted.
+    ResourceExhausted = 8,
+    /// The system is not in a state required for the operation's execution.
+    FailedPrecondition = 9,
+    /// The operation was aborted.
+    Aborted = 10,
+    /// Operation was attempted past the valid range.
+    OutOfRange = 11,
+    /// Operation is not implemented or not supported.
+    Unimplemented = 12,
+    /// Internal error.
+    Internal = 13,
+    /// The service is currently unavailable.
+    Unavailable = 14,
+    /// Unrecoverable data loss or corruption.
+    DataLoss = 15,
+    /// The request does not have valid authentication credentials.
+    Unauthenticated = 16,
+}
+
+impl Code {
+    /// Get code from numeric value, unknown values map to `Code::Unknown`
+    pub fn from_u8(code: u8) -> Code {
+        match code {
+            0 => Code::Ok,
+            1 => Code::Cancelled,
+            3 => Code::InvalidArgument,
+            4 => Code::DeadlineExceeded,
+            5 => Code::NotFound,
+            6 => Code::AlreadyExists,
+            7 => Code::PermissionDenied,
+            8 => Code::ResourceExhausted,
+            9 => Code::FailedPrecondition,
+            10 => Code::Aborted,
+            11 => Code::OutOfRange,
+            12 => Code::Unimplemented,
+            13 => Code::Internal,
+            14 => Code::Unavailable,
+            15 => Code::DataLoss,
+            16 => Code::Unauthenticated,
+            _ => Code::Unknown,
+        }
+    }
+
+    /// Get header value for `grpc-status` header
+    pub fn to_header_value(self) -> HeaderValue {
+        HeaderValue::from(self as u8 as u16)
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Grpc call status
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Status {
+    code: Code,
+    message: String,
+}
+
+impl Status {
+    #[allow(clippy::declare_interior_mutable_const)]
+    /// Grpc status header name
+    pub const STATUS: HeaderName = HeaderName::from_static("grpc-status");
+    #[allow(clippy::declare_interior_mutable_const)]
+    /// Grpc message header name
+    pub const MESSAGE: HeaderName = HeaderName::from_static("grpc-message");
+
+    /// Create new status
+    pub fn new<T: Into<String>>(code: Code, message: T) -> Self {
+        Status {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Successful status
+    pub fn ok() -> Self {
+        Status::new(Code::Ok, "")
+    }
+
+    /// Create status with `Code::InvalidArgument` code
+    pub fn invalid_argument<T: Into<String>>(message: T) -> Self {
+        Status::new(Code::InvalidArgument, message)
+    }
+
+    /// Create status with `Code::DeadlineExceeded` code
+    pub fn deadline_exceeded<T: Into<String>>(message: T) -> Self {
+        Status::new(Code::DeadlineExceeded, message)
+    }
+
+    /// Create status with `Code::NotFound` code
+    pub fn not_found<T: Into<String>>(message: T) -> Self {
+        Status::new(Code::NotFound, message)
+    }
+
+    /// Create status with `Code::ResourceExhausted` code
+    pub fn resource_exhausted<T: Into<String>>(message: T) -> Self {
+        Status::new(Code::ResourceExhausted, message)
+    }
+
+    /// Create status with `Code::Unimplemented` code
+    pub fn unimplemented<T: Into<String>>(message: T) -> Self {
+        Status::new(Code::Unimplemented, message)
+    }
+
+    /// Create status with `Code::Internal` code
+    pub fn internal<T: Into<String>>(message: T) -> Self {
+        Status::new(Code::Internal, message)
+    }
+
+    /// Create status with `Code::Unavailable` code
+    pub fn unavailable<T: Into<String>>(message: T) -> Self {
+        Status::new(Code::Unavailable, message)
+    }
+
+    /// Status code
+    pub fn code(&self) -> Code {
+        self.code
+    }
+
+    /// Status message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Read status from response headers or trailers
+    pub fn from_headers(headers: &HeaderMap) -> Option<Status> {
+        let code = headers
+            .get(&Status::STATUS)?
+            .to_str()
+            .ok()?
+            .parse::<u8>()
+            .ok()?;
+        let message = headers
+            .get(&Status::MESSAGE)
+            .and_then(|val| val.to_str().ok())
+            .map(percent_decode)
+            .unwrap_or_default();
+        Some(Status::new(Code::from_u8(code), message))
+    }
+
+    /// Add `grpc-status` and `grpc-message` headers
+    pub fn to_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(Status::STATUS, self.code.to_header_value());
+        if !self.message.is_empty() {
+            if let Ok(val) = HeaderValue::from_str(&percent_encode(&self.message)) {
+                headers.insert(Status::MESSAGE, val);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "grpc status: {}, message: {:?}", self.code, self.message)
+    }
+}
+
+impl error::Error for Status {}
+
+/// Percent encode grpc message, as described by grpc http/2 protocol
+fn percent_encode(msg: &str) -> String {
+    let mut result = String::with_capacity(msg.len());
+    for b in msg.bytes() {
+        if (b' '..=b'~').contains(&b) && b != b'%' {
+            result.push(b as char);
+        } else {
+            result.push_str(&format!("%{:02X}", b));
+        }
+    }
+    result
+}
+
+fn percent_decode(msg: &str) -> String {
+    let src = msg.as_bytes();
+    let mut result = Vec::with_capacity(src.len());
+    let mut idx = 0;
+    while idx < src.len() {
+        if src[idx] == b'%' && idx + 2 < src.len() {
+            let hex = std::str::from_utf8(&src[idx + 1..idx + 3]).ok();
+            if let Some(b) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                result.push(b);
+                idx += 3;
+                continue;
+            }
+        }
+        result.push(src[idx]);
+        idx += 1;
+    }
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code() {
+        for code in 0..=16 {
+            assert_eq!(Code::from_u8(code) as u8, code);
+        }
+        assert_eq!(Code::from_u8(100), Code::Unknown);
+        assert_eq!(Code::NotFound.to_header_value(), "5");
+        assert_eq!(Code::NotFound.to_string(), "NotFound");
+    }
+
+    #[test]
+    fn test_status_headers() {
+        let status = Status::not_found("item 100% missing\n");
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "item 100% missing\n");
+        assert!(status.to_string().contains("NotFound"));
+
+        let mut headers = HeaderMap::new();
+        status.to_headers(&mut headers);
+        assert_eq!(headers.get(&Status::STATUS).unwrap(), "5");
+        assert_eq!(
+            headers.get(&Status::MESSAGE).unwrap(),
+            "item 100%25 missing%0A"
+        );
+        assert_eq!(Status::from_headers(&headers).unwrap(), status);
+
+        let mut headers = HeaderMap::new();
+        Status::ok().to_headers(&mut headers);
+        assert_eq!(headers.get(&Status::STATUS).unwrap(), "0");
+        assert!(!headers.contains_key(&Status::MESSAGE));
+        assert_eq!(Status::from_headers(&headers).unwrap(), Status::ok());
+        assert!(Status::from_headers(&HeaderMap::new()).is_none());
+    }
+}
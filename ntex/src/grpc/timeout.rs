@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use crate::http::header::HeaderValue;
+
+/// Parse `grpc-timeout` header value
+///
+/// Value is a positive integer of at most 8 digits followed by time unit:
+/// `H` - hours, `M` - minutes, `S` - seconds, `m` - milliseconds,
+/// `u` - microseconds, `n` - nanoseconds.
+pub fn parse_timeout(value: &HeaderValue) -> Option<Duration> {
+    let value = value.as_bytes();
+    if value.len() < 2 || value.len() > 9 {
+        return None;
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let num: u64 = std::str::from_utf8(digits).ok()?.parse().ok()?;
+
+    match unit[0] {
+        b'H' => Some(Duration::from_secs(num * 3600)),
+        b'M' => Some(Duration::from_secs(num * 60)),
+        b'S' => Some(Duration::from_secs(num)),
+        b'm' => Some(Duration::from_millis(num)),
+        b'u' => Some(Duration::from_micros(num)),
+        b'n' => Some(Duration::from_nanos(num)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timeout() {
+        let parse = |val| parse_timeout(&HeaderValue::from_static(val));
+
+        assert_eq!(parse("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse("2M"), Some(Duration::from_secs(120)));
+        assert_eq!(parse("10S"), Some(Duration::from_secs(10)));
+        assert_eq!(parse("100m"), Some(Duration::from_millis(100)));
+        assert_eq!(parse("100u"), Some(Duration::from_micros(100)));
+        assert_eq!(parse("100n"), Some(Duration::from_nanos(100)));
+        assert_eq!(parse("99999999S"), Some(Duration::from_secs(99_999_999)));
+
+        assert_eq!(parse("S"), None);
+        assert_eq!(parse("100"), None);
+        assert_eq!(parse("100x"), None);
+        assert_eq!(parse("-1S"), None);
+        assert_eq!(parse("123456789S"), None);
+    }
+}
@@ -1,7 +1,11 @@
 use std::{
-    error::Error, fmt, marker::PhantomData, mem, pin::Pin, task::Context, task::Poll,
+    cell::RefCell, collections::VecDeque, error::Error, fmt, marker::PhantomData, mem,
+    pin::Pin, rc::Rc, task::Context, task::Poll,
 };
 
+use crate::channel::condition::Condition;
+use crate::http::header::HeaderMap;
+use crate::task::LocalWaker;
 use crate::util::{Bytes, BytesMut, Stream};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -19,7 +23,105 @@ impl BodySize {
     }
 }
 
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+/// Body size bounds
+///
+/// By default size is unknown, lower bound is 0 and upper bound is not set.
+pub struct SizeHint {
+    lower: u64,
+    upper: Option<u64>,
+}
+
+impl SizeHint {
+    /// Create size hint with unknown size
+    pub fn new() -> Self {
+        SizeHint::default()
+    }
+
+    /// Create size hint with exact size
+    pub fn with_exact(size: u64) -> Self {
+        SizeHint {
+            lower: size,
+            upper: Some(size),
+        }
+    }
+
+    /// Create size hint with known lower bound
+    pub fn at_least(size: u64) -> Self {
+        SizeHint {
+            lower: size,
+            upper: None,
+        }
+    }
+
+    /// Lower bound of body size
+    pub fn lower(&self) -> u64 {
+        self.lower
+    }
+
+    /// Upper bound of body size, if known
+    pub fn upper(&self) -> Option<u64> {
+        self.upper
+    }
+
+    /// Exact body size, if lower and upper bounds are equal
+    pub fn exact(&self) -> Option<u64> {
+        self.upper.filter(|upper| *upper == self.lower)
+    }
+}
+
+impl From<BodySize> for SizeHint {
+    fn from(size: BodySize) -> Self {
+        match size {
+            BodySize::None | BodySize::Empty => SizeHint::with_exact(0),
+            BodySize::Sized(size) => SizeHint::with_exact(size),
+            BodySize::Stream => SizeHint::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Message body frame
+pub enum BodyFrame {
+    /// Data chunk
+    Data(Bytes),
+    /// Trailers, always the last frame of the body
+    Trailers(HeaderMap),
+}
+
+impl BodyFrame {
+    /// Check if frame is data frame
+    pub fn is_data(&self) -> bool {
+        matches!(self, BodyFrame::Data(_))
+    }
+
+    /// Check if frame is trailers frame
+    pub fn is_trailers(&self) -> bool {
+        matches!(self, BodyFrame::Trailers(_))
+    }
+
+    /// Convert to data chunk
+    pub fn into_data(self) -> Option<Bytes> {
+        match self {
+            BodyFrame::Data(data) => Some(data),
+            BodyFrame::Trailers(_) => None,
+        }
+    }
+
+    /// Convert to trailers
+    pub fn into_trailers(self) -> Option<HeaderMap> {
+        match self {
+            BodyFrame::Data(_) => None,
+            BodyFrame::Trailers(trailers) => Some(trailers),
+        }
+    }
+}
+
 /// Type that provides this trait can be streamed to a peer.
+///
+/// Body is a sequence of data frames optionally followed by trailers frame.
+/// Chunk based bodies implement `poll_next_chunk()` only, frame based bodies
+/// implement both methods, `poll_next_chunk()` skips trailers.
 pub trait MessageBody: 'static {
     fn size(&self) -> BodySize;
 
@@ -27,6 +129,20 @@ pub trait MessageBody: 'static {
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>>;
+
+    /// Body size bounds
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::from(self.size())
+    }
+
+    /// Attempt to pull out the next frame of the body
+    fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<BodyFrame, Box<dyn Error>>>> {
+        self.poll_next_chunk(cx)
+            .map(|item| item.map(|res| res.map(BodyFrame::Data)))
+    }
 }
 
 impl MessageBody for () {
@@ -53,6 +169,17 @@ impl<T: MessageBody> MessageBody for Box<T> {
     ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
         self.as_mut().poll_next_chunk(cx)
     }
+
+    fn size_hint(&self) -> SizeHint {
+        self.as_ref().size_hint()
+    }
+
+    fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<BodyFrame, Box<dyn Error>>>> {
+        self.as_mut().poll_frame(cx)
+    }
 }
 
 #[derive(Debug)]
@@ -122,6 +249,23 @@ impl<B: MessageBody> MessageBody for ResponseBody<B> {
             ResponseBody::Other(ref mut body) => body.poll_next_chunk(cx),
         }
     }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            ResponseBody::Body(ref body) => body.size_hint(),
+            ResponseBody::Other(ref body) => body.size_hint(),
+        }
+    }
+
+    fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<BodyFrame, Box<dyn Error>>>> {
+        match self {
+            ResponseBody::Body(ref mut body) => body.poll_frame(cx),
+            ResponseBody::Other(ref mut body) => body.poll_frame(cx),
+        }
+    }
 }
 
 impl<B: MessageBody + Unpin> Stream for ResponseBody<B> {
@@ -187,6 +331,25 @@ impl MessageBody for Body {
             Body::Message(ref mut body) => body.poll_next_chunk(cx),
         }
     }
+
+    fn size_hint(&self) -> SizeHint {
+        match self {
+            Body::Message(ref body) => body.size_hint(),
+            _ => SizeHint::from(self.size()),
+        }
+    }
+
+    fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<BodyFrame, Box<dyn Error>>>> {
+        match self {
+            Body::Message(ref mut body) => body.poll_frame(cx),
+            _ => self
+                .poll_next_chunk(cx)
+                .map(|item| item.map(|res| res.map(BodyFrame::Data))),
+        }
+    }
 }
 
 impl PartialEq for Body {
@@ -549,6 +712,524 @@ where
     }
 }
 
+/// Type represent frame based streaming body.
+///
+/// Stream yields data frames optionally followed by trailers frame.
+pub struct FrameStream<S, E> {
+    stream: S,
+    size: SizeHint,
+    _t: PhantomData<E>,
+}
+
+impl<S, E> FrameStream<S, E>
+where
+    S: Stream<Item = Result<BodyFrame, E>> + Unpin,
+    E: Error,
+{
+    pub fn new(stream: S) -> Self {
+        FrameStream {
+            stream,
+            size: SizeHint::new(),
+            _t: PhantomData,
+        }
+    }
+
+    /// Set body size hint
+    ///
+    /// Body with exact size hint is sent with `content-length` header.
+    pub fn with_size(mut self, size: SizeHint) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+impl<S, E> fmt::Debug for FrameStream<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameStream")
+            .field("stream", &std::any::type_name::<S>())
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl<S, E> MessageBody for FrameStream<S, E>
+where
+    S: Stream<Item = Result<BodyFrame, E>> + Unpin + 'static,
+    E: Error + 'static,
+{
+    fn size(&self) -> BodySize {
+        match self.size.exact() {
+            Some(0) => BodySize::Empty,
+            Some(size) => BodySize::Sized(size),
+            None => BodySize::Stream,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.size
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        loop {
+            return match self.poll_frame(cx) {
+                Poll::Ready(Some(Ok(BodyFrame::Data(chunk)))) => {
+                    Poll::Ready(Some(Ok(chunk)))
+                }
+                Poll::Ready(Some(Ok(BodyFrame::Trailers(_)))) => continue,
+                Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            };
+        }
+    }
+
+    fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<BodyFrame, Box<dyn Error>>>> {
+        loop {
+            return Poll::Ready(match Pin::new(&mut self.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(BodyFrame::Data(ref bytes)))) if bytes.is_empty() => {
+                    continue
+                }
+                Poll::Ready(opt) => opt.map(|res| res.map_err(Into::into)),
+                Poll::Pending => return Poll::Pending,
+            });
+        }
+    }
+}
+
+/// Message body with trailers.
+///
+/// Trailers frame is emitted after all data frames of the inner body.
+pub struct WithTrailers<B> {
+    body: B,
+    trailers: Option<HeaderMap>,
+}
+
+impl<B: MessageBody> WithTrailers<B> {
+    pub fn new(body: B, trailers: HeaderMap) -> Self {
+        WithTrailers {
+            body,
+            trailers: Some(trailers),
+        }
+    }
+}
+
+impl<B> fmt::Debug for WithTrailers<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithTrailers")
+            .field("body", &std::any::type_name::<B>())
+            .field("trailers", &self.trailers)
+            .finish()
+    }
+}
+
+impl<B: MessageBody> MessageBody for WithTrailers<B> {
+    fn size(&self) -> BodySize {
+        // empty body must not complete message before trailers
+        match self.body.size() {
+            size if size.is_eof() => BodySize::Stream,
+            size => size,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        self.body.poll_next_chunk(cx)
+    }
+
+    fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<BodyFrame, Box<dyn Error>>>> {
+        match self.body.poll_frame(cx) {
+            Poll::Ready(None) => {
+                Poll::Ready(self.trailers.take().map(|t| Ok(BodyFrame::Trailers(t))))
+            }
+            res => res,
+        }
+    }
+}
+
+/// Message body adapter that transforms data chunks.
+///
+/// Mapping function is called for every data chunk of the inner body, it
+/// could modify chunk or abort body stream by returning an error. Trailers
+/// are passed as is. Mapped body is sent with transfer encoding, because
+/// mapping function could change chunk size.
+pub struct BodyMap<B, F> {
+    body: B,
+    f: F,
+}
+
+impl<B, F> BodyMap<B, F>
+where
+    B: MessageBody,
+    F: FnMut(Bytes) -> Result<Bytes, Box<dyn Error>> + 'static,
+{
+    pub fn new(body: B, f: F) -> Self {
+        BodyMap { body, f }
+    }
+}
+
+impl<B, F> fmt::Debug for BodyMap<B, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyMap")
+            .field("body", &std::any::type_name::<B>())
+            .field("f", &std::any::type_name::<F>())
+            .finish()
+    }
+}
+
+impl<B, F> MessageBody for BodyMap<B, F>
+where
+    B: MessageBody,
+    F: FnMut(Bytes) -> Result<Bytes, Box<dyn Error>> + 'static,
+{
+    fn size(&self) -> BodySize {
+        match self.body.size() {
+            size if size.is_eof() => size,
+            _ => BodySize::Stream,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::from(self.size())
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        match self.body.poll_next_chunk(cx) {
+            Poll::Ready(Some(Ok(chunk))) => Poll::Ready(Some((self.f)(chunk))),
+            res => res,
+        }
+    }
+
+    fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<BodyFrame, Box<dyn Error>>>> {
+        match self.body.poll_frame(cx) {
+            Poll::Ready(Some(Ok(BodyFrame::Data(chunk)))) => {
+                Poll::Ready(Some((self.f)(chunk).map(BodyFrame::Data)))
+            }
+            res => res,
+        }
+    }
+}
+
+/// Type represent streaming body from `AsyncRead` reader.
+pub struct ReaderStream<R> {
+    reader: R,
+    size: BodySize,
+    capacity: usize,
+    eof: bool,
+}
+
+impl<R> ReaderStream<R>
+where
+    R: futures_io::AsyncRead + Unpin,
+{
+    /// Create body from reader, body is sent with transfer encoding
+    pub fn new(reader: R) -> Self {
+        ReaderStream {
+            reader,
+            size: BodySize::Stream,
+            capacity: 8 * 1024,
+            eof: false,
+        }
+    }
+
+    /// Create body from reader with known size
+    ///
+    /// Reader must produce exactly `size` bytes.
+    pub fn sized(size: u64, reader: R) -> Self {
+        ReaderStream {
+            size: BodySize::Sized(size),
+            ..ReaderStream::new(reader)
+        }
+    }
+
+    /// Set read buffer size, default is 8kb
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = std::cmp::max(capacity, 1);
+        self
+    }
+}
+
+impl<R> fmt::Debug for ReaderStream<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReaderStream")
+            .field("reader", &std::any::type_name::<R>())
+            .field("size", &self.size)
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+impl<R> MessageBody for ReaderStream<R>
+where
+    R: futures_io::AsyncRead + Unpin + 'static,
+{
+    fn size(&self) -> BodySize {
+        self.size
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        if self.eof {
+            return Poll::Ready(None);
+        }
+
+        let mut buf = BytesMut::with_capacity(self.capacity);
+        buf.resize(self.capacity, 0);
+        loop {
+            return match Pin::new(&mut self.reader).poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(0)) => {
+                    self.eof = true;
+                    Poll::Ready(None)
+                }
+                Poll::Ready(Ok(n)) => {
+                    buf.truncate(n);
+                    Poll::Ready(Some(Ok(buf.freeze())))
+                }
+                Poll::Ready(Err(err)) if err.kind() == std::io::ErrorKind::Interrupted => {
+                    continue
+                }
+                Poll::Ready(Err(err)) => {
+                    self.eof = true;
+                    Poll::Ready(Some(Err(Box::new(err))))
+                }
+                Poll::Pending => Poll::Pending,
+            };
+        }
+    }
+}
+
+/// Create bounded response body channel.
+///
+/// Handler could return response with `BodyReceiver` body immediately and
+/// push chunks later with `BodySender`. `BodySender::send()` resolves when
+/// the chunk is consumed by the dispatcher, so producer never runs ahead of
+/// the peer. Up to `capacity` chunks could be buffered with `BodySender::feed()`.
+///
+/// Body is completed when all senders are dropped or closed.
+pub fn channel(capacity: usize) -> (BodySender, BodyReceiver) {
+    let inner = Rc::new(RefCell::new(ChannelInner {
+        capacity: std::cmp::max(capacity, 1),
+        queue: VecDeque::new(),
+        sent: 0,
+        consumed: 0,
+        senders: 1,
+        eof: false,
+        err: None,
+        rx_dropped: false,
+        rx_task: LocalWaker::new(),
+    }));
+    let cond = Condition::new();
+    (
+        BodySender {
+            inner: inner.clone(),
+            cond: cond.clone(),
+        },
+        BodyReceiver { inner, cond },
+    )
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Response body receiver is gone")]
+/// Body receiver is dropped, peer is gone or response is discarded
+pub struct BodyReceiverGone;
+
+struct ChannelInner {
+    capacity: usize,
+    queue: VecDeque<Bytes>,
+    sent: u64,
+    consumed: u64,
+    senders: usize,
+    eof: bool,
+    err: Option<Box<dyn Error>>,
+    rx_dropped: bool,
+    rx_task: LocalWaker,
+}
+
+/// Sender part of the response body channel
+pub struct BodySender {
+    inner: Rc<RefCell<ChannelInner>>,
+    cond: Condition,
+}
+
+impl BodySender {
+    /// Send chunk and wait until dispatcher consumes it.
+    pub async fn send(&self, chunk: Bytes) -> Result<(), BodyReceiverGone> {
+        self.ready().await?;
+        let seq = self.push(chunk)?;
+        self.wait(|inner| inner.consumed >= seq).await
+    }
+
+    /// Buffer chunk, waits only if channel is full.
+    pub async fn feed(&self, chunk: Bytes) -> Result<(), BodyReceiverGone> {
+        self.ready().await?;
+        self.push(chunk).map(|_| ())
+    }
+
+    /// Wait until channel has capacity for a new chunk.
+    pub async fn ready(&self) -> Result<(), BodyReceiverGone> {
+        self.wait(|inner| inner.queue.len() < inner.capacity).await
+    }
+
+    /// Wait until all buffered chunks are consumed.
+    pub async fn flush(&self) -> Result<(), BodyReceiverGone> {
+        self.wait(|inner| inner.queue.is_empty()).await
+    }
+
+    /// Complete response body with error.
+    ///
+    /// Dispatcher drops connection, response could not be completed.
+    pub fn set_error<E: Error + 'static>(&self, err: E) {
+        let mut inner = self.inner.borrow_mut();
+        inner.err = Some(Box::new(err));
+        inner.rx_task.wake();
+    }
+
+    /// Complete response body.
+    pub fn close(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.eof = true;
+        inner.rx_task.wake();
+    }
+
+    /// Check if receiver is dropped
+    pub fn is_closed(&self) -> bool {
+        self.inner.borrow().rx_dropped
+    }
+
+    fn push(&self, chunk: Bytes) -> Result<u64, BodyReceiverGone> {
+        let mut inner = self.inner.borrow_mut();
+        if inner.rx_dropped || inner.eof {
+            Err(BodyReceiverGone)
+        } else {
+            inner.sent += 1;
+            inner.queue.push_back(chunk);
+            inner.rx_task.wake();
+            Ok(inner.sent)
+        }
+    }
+
+    async fn wait<F>(&self, f: F) -> Result<(), BodyReceiverGone>
+    where
+        F: Fn(&ChannelInner) -> bool,
+    {
+        loop {
+            {
+                let inner = self.inner.borrow();
+                if inner.rx_dropped {
+                    return Err(BodyReceiverGone);
+                } else if f(&inner) {
+                    return Ok(());
+                }
+            }
+            self.cond.wait().ready().await;
+        }
+    }
+}
+
+impl Clone for BodySender {
+    fn clone(&self) -> Self {
+        self.inner.borrow_mut().senders += 1;
+        BodySender {
+            inner: self.inner.clone(),
+            cond: self.cond.clone(),
+        }
+    }
+}
+
+impl Drop for BodySender {
+    fn drop(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.senders -= 1;
+        if inner.senders == 0 {
+            inner.eof = true;
+            inner.rx_task.wake();
+        }
+    }
+}
+
+impl fmt::Debug for BodySender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.borrow();
+        f.debug_struct("BodySender")
+            .field("capacity", &inner.capacity)
+            .field("buffered", &inner.queue.len())
+            .finish()
+    }
+}
+
+/// Receiver part of the response body channel
+pub struct BodyReceiver {
+    inner: Rc<RefCell<ChannelInner>>,
+    cond: Condition,
+}
+
+impl Drop for BodyReceiver {
+    fn drop(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.rx_dropped = true;
+        inner.queue.clear();
+        drop(inner);
+        self.cond.notify();
+    }
+}
+
+impl fmt::Debug for BodyReceiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyReceiver")
+            .field("buffered", &self.inner.borrow().queue.len())
+            .finish()
+    }
+}
+
+impl MessageBody for BodyReceiver {
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        let mut inner = self.inner.borrow_mut();
+        let result = if let Some(err) = inner.err.take() {
+            Poll::Ready(Some(Err(err)))
+        } else if let Some(chunk) = inner.queue.pop_front() {
+            inner.consumed += 1;
+            Poll::Ready(Some(Ok(chunk)))
+        } else if inner.eof {
+            Poll::Ready(None)
+        } else {
+            inner.rx_task.register(cx.waker());
+            return Poll::Pending;
+        };
+        drop(inner);
+        self.cond.notify();
+        result
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use futures_util::stream;
@@ -796,4 +1477,239 @@ mod tests {
             Some(Bytes::from("2")),
         );
     }
+
+    #[crate::rt_test]
+    async fn body_channel() {
+        let (tx, mut rx) = channel(2);
+        assert_eq!(rx.size(), BodySize::Stream);
+        assert!(format!("{:?}", tx).contains("BodySender"));
+        assert!(format!("{:?}", rx).contains("BodyReceiver"));
+
+        tx.feed(Bytes::from_static(b"1")).await.unwrap();
+        tx.feed(Bytes::from_static(b"2")).await.unwrap();
+
+        let tx2 = tx.clone();
+        let handle = crate::rt::spawn(async move {
+            tx2.send(Bytes::from_static(b"3")).await.unwrap();
+            tx2.close();
+        });
+
+        assert_eq!(
+            poll_fn(|cx| rx.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b"1")),
+        );
+        assert_eq!(
+            poll_fn(|cx| rx.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b"2")),
+        );
+        assert_eq!(
+            poll_fn(|cx| rx.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b"3")),
+        );
+        let _ = handle.await;
+        drop(tx);
+        assert!(poll_fn(|cx| rx.poll_next_chunk(cx)).await.is_none());
+    }
+
+    #[crate::rt_test]
+    async fn body_channel_error() {
+        let (tx, mut rx) = channel(1);
+        tx.set_error(io::Error::new(io::ErrorKind::Other, "err"));
+        assert!(poll_fn(|cx| rx.poll_next_chunk(cx)).await.unwrap().is_err());
+
+        drop(rx);
+        assert!(tx.is_closed());
+        assert_eq!(
+            tx.send(Bytes::from_static(b"1")).await,
+            Err(BodyReceiverGone)
+        );
+    }
+
+    #[test]
+    fn size_hint() {
+        let hint = SizeHint::new();
+        assert_eq!(hint.lower(), 0);
+        assert_eq!(hint.upper(), None);
+        assert_eq!(hint.exact(), None);
+
+        let hint = SizeHint::with_exact(10);
+        assert_eq!(hint.exact(), Some(10));
+        assert_eq!(SizeHint::at_least(10).lower(), 10);
+        assert_eq!(SizeHint::at_least(10).exact(), None);
+
+        assert_eq!(SizeHint::from(BodySize::Empty).exact(), Some(0));
+        assert_eq!(SizeHint::from(BodySize::Sized(5)).exact(), Some(5));
+        assert_eq!(SizeHint::from(BodySize::Stream), SizeHint::new());
+        assert_eq!(Body::from("test").size_hint().exact(), Some(4));
+    }
+
+    #[crate::rt_test]
+    async fn frame_stream() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            crate::http::header::CONTENT_TYPE,
+            crate::http::header::HeaderValue::from_static("text"),
+        );
+        let frames = || {
+            stream::iter(vec![
+                Ok::<_, io::Error>(BodyFrame::Data(Bytes::from_static(b"1"))),
+                Ok(BodyFrame::Data(Bytes::new())),
+                Ok(BodyFrame::Data(Bytes::from_static(b"2"))),
+                Ok(BodyFrame::Trailers(trailers.clone())),
+            ])
+        };
+
+        let mut body = FrameStream::new(frames());
+        assert_eq!(body.size(), BodySize::Stream);
+        assert!(format!("{:?}", body).contains("FrameStream"));
+        assert_eq!(
+            poll_fn(|cx| body.poll_frame(cx)).await.unwrap().unwrap(),
+            BodyFrame::Data(Bytes::from_static(b"1"))
+        );
+        assert_eq!(
+            poll_fn(|cx| body.poll_frame(cx)).await.unwrap().unwrap(),
+            BodyFrame::Data(Bytes::from_static(b"2"))
+        );
+        let frame = poll_fn(|cx| body.poll_frame(cx)).await.unwrap().unwrap();
+        assert!(frame.is_trailers());
+        assert_eq!(frame.into_trailers().unwrap(), trailers);
+        assert!(poll_fn(|cx| body.poll_frame(cx)).await.is_none());
+
+        // chunks api skips trailers
+        let mut body = FrameStream::new(frames()).with_size(SizeHint::with_exact(2));
+        assert_eq!(body.size(), BodySize::Sized(2));
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b"1"))
+        );
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b"2"))
+        );
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+    }
+
+    #[crate::rt_test]
+    async fn with_trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            crate::http::header::CONTENT_TYPE,
+            crate::http::header::HeaderValue::from_static("text"),
+        );
+
+        let mut body = WithTrailers::new(Bytes::from_static(b"test"), trailers.clone());
+        assert_eq!(body.size(), BodySize::Sized(4));
+        let frame = poll_fn(|cx| body.poll_frame(cx)).await.unwrap().unwrap();
+        assert!(frame.is_data());
+        assert_eq!(frame.into_data().unwrap(), Bytes::from_static(b"test"));
+        assert_eq!(
+            poll_fn(|cx| body.poll_frame(cx)).await.unwrap().unwrap(),
+            BodyFrame::Trailers(trailers.clone())
+        );
+        assert!(poll_fn(|cx| body.poll_frame(cx)).await.is_none());
+
+        // default frames implementation
+        let mut body = Body::from_message(WithTrailers::new((), trailers.clone()));
+        assert_eq!(body.size(), BodySize::Stream);
+        assert_eq!(
+            poll_fn(|cx| body.poll_frame(cx)).await.unwrap().unwrap(),
+            BodyFrame::Trailers(trailers)
+        );
+        let mut body = Body::from("test");
+        assert_eq!(
+            poll_fn(|cx| body.poll_frame(cx)).await.unwrap().unwrap(),
+            BodyFrame::Data(Bytes::from_static(b"test"))
+        );
+    }
+
+    #[crate::rt_test]
+    async fn body_map() {
+        let mut body = BodyMap::new(Body::from("test"), |chunk| {
+            Ok(Bytes::from(chunk.to_ascii_uppercase()))
+        });
+        assert_eq!(body.size(), BodySize::Stream);
+        assert!(format!("{:?}", body).contains("BodyMap"));
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b"TEST"))
+        );
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+
+        let body = BodyMap::new(Body::Empty, Ok);
+        assert_eq!(body.size(), BodySize::Empty);
+
+        // abort on limit, trailers are passed as is
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            crate::http::header::CONTENT_TYPE,
+            crate::http::header::HeaderValue::from_static("text"),
+        );
+        let mut total = 0;
+        let mut body = BodyMap::new(
+            WithTrailers::new(Bytes::from_static(b"test"), trailers.clone()),
+            move |chunk: Bytes| {
+                total += chunk.len();
+                if total > 4 {
+                    Err(io::Error::new(io::ErrorKind::Other, "limit").into())
+                } else {
+                    Ok(chunk)
+                }
+            },
+        );
+        assert_eq!(
+            poll_fn(|cx| body.poll_frame(cx)).await.unwrap().unwrap(),
+            BodyFrame::Data(Bytes::from_static(b"test"))
+        );
+        assert_eq!(
+            poll_fn(|cx| body.poll_frame(cx)).await.unwrap().unwrap(),
+            BodyFrame::Trailers(trailers)
+        );
+
+        let mut body = BodyMap::new(Bytes::from_static(b"test"), |_| {
+            Err(io::Error::new(io::ErrorKind::Other, "limit").into())
+        });
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx))
+            .await
+            .unwrap()
+            .is_err());
+    }
+
+    struct Reader(&'static [u8]);
+
+    impl futures_io::AsyncRead for Reader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let n = std::cmp::min(buf.len(), self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[crate::rt_test]
+    async fn reader_stream() {
+        let mut body = ReaderStream::new(Reader(b"test data")).capacity(4);
+        assert_eq!(body.size(), BodySize::Stream);
+        assert!(format!("{:?}", body).contains("ReaderStream"));
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b"test"))
+        );
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b" dat"))
+        );
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b"a"))
+        );
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+
+        let body = ReaderStream::sized(4, Reader(b"test"));
+        assert_eq!(body.size(), BodySize::Sized(4));
+    }
 }
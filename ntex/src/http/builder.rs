@@ -5,6 +5,8 @@ use crate::http::config::{KeepAlive, ServiceConfig};
 use crate::http::error::{H2Error, ResponseError};
 use crate::http::h1::{self, H1Service};
 use crate::http::h2::{self, H2Service};
+use crate::http::header::HeaderValue;
+use crate::http::hooks::DispatcherHooks;
 use crate::http::{request::Request, response::Response, service::HttpService};
 use crate::service::{IntoServiceFactory, ServiceFactory};
 use crate::{io::Filter, time::Seconds};
@@ -64,6 +66,22 @@ where
         self
     }
 
+    /// Set max number of requests served per connection.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn keepalive_max_requests(mut self, num: usize) -> Self {
+        self.config.keepalive_max_requests(num);
+        self
+    }
+
+    /// Set max lifetime of a connection.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn keepalive_max_lifetime(mut self, lifetime: Seconds) -> Self {
+        self.config.keepalive_max_lifetime(lifetime);
+        self
+    }
+
     /// Set request headers read timeout.
     ///
     /// Defines a timeout for reading client request header. If a client does not transmit
@@ -136,6 +154,125 @@ where
         self
     }
 
+    /// Set max time for receiving complete request head.
+    ///
+    /// To disable timeout set value to 0. By default timeout is disabled.
+    pub fn headers_timeout(mut self, timeout: Seconds) -> Self {
+        self.config.headers_timeout(timeout);
+        self
+    }
+
+    /// Set minimum transfer rate for request's payload, in bytes per second.
+    ///
+    /// Rate is checked every `window` period. By default check is disabled.
+    pub fn payload_min_rate(mut self, rate: u32, window: Seconds) -> Self {
+        self.config.payload_min_rate(rate, window);
+        self
+    }
+
+    /// Set max number of request headers.
+    ///
+    /// By default max number of headers is set to 96.
+    pub fn max_headers(mut self, num: usize) -> Self {
+        self.config.max_headers(num);
+        self
+    }
+
+    /// Set max size of individual request header (name and value).
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn max_header_size(mut self, size: usize) -> Self {
+        self.config.max_header_size(size);
+        self
+    }
+
+    /// Set max total size of request head, including request line.
+    ///
+    /// By default max size is set to 32Kb.
+    pub fn max_headers_size(mut self, size: usize) -> Self {
+        self.config.max_headers_size(size);
+        self
+    }
+
+    /// Set max length of request uri.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn max_uri_length(mut self, size: usize) -> Self {
+        self.config.max_uri_length(size);
+        self
+    }
+
+    /// Advertise alternative services with `Alt-Svc` header.
+    ///
+    /// By default alternative services are not advertised.
+    pub fn alt_svc(mut self, value: HeaderValue) -> Self {
+        self.config.alt_svc(value);
+        self
+    }
+
+    /// Enable cleartext http/2 (h2c) with prior knowledge.
+    ///
+    /// Requests with `Upgrade: h2c` header are served as regular http/1.1
+    /// requests, upgrade mechanism is deprecated by RFC 9113. Service
+    /// created with `h1()` method fails to start if h2c is enabled.
+    ///
+    /// By default h2c is disabled.
+    pub fn h2c(mut self, enabled: bool) -> Self {
+        self.config.h2c(enabled);
+        self
+    }
+
+    /// Set max size of request payload.
+    ///
+    /// Requests with larger `Content-Length` get rejected with
+    /// 413 (Payload Too Large) error before payload is read.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn max_payload_size(mut self, size: u64) -> Self {
+        self.config.max_payload_size(size);
+        self
+    }
+
+    /// Defer `100 Continue` response until service reads request payload.
+    ///
+    /// By default lazy continue is disabled.
+    pub fn h1_lazy_continue(mut self, enabled: bool) -> Self {
+        self.config.h1_lazy_continue(enabled);
+        self
+    }
+
+    /// Set response write flush strategy for http/1 connections.
+    ///
+    /// By default responses are coalesced.
+    pub fn h1_flush_strategy(mut self, strategy: h1::FlushStrategy) -> Self {
+        self.config.h1_flush_strategy(strategy);
+        self
+    }
+
+    /// Set strict parsing checks for http/1 requests.
+    ///
+    /// By default strict checks are disabled.
+    pub fn h1_strict(mut self, checks: h1::Strict) -> Self {
+        self.config.h1_strict(checks);
+        self
+    }
+
+    /// Set max size of chunk extensions for http/1 requests.
+    ///
+    /// By default max size is set to 1Kb.
+    pub fn h1_max_chunk_extension(mut self, size: usize) -> Self {
+        self.config.h1_max_chunk_extension(size);
+        self
+    }
+
+    /// Set dispatcher instrumentation hooks.
+    ///
+    /// By default hooks are not set.
+    pub fn dispatcher_hooks<T: DispatcherHooks>(mut self, hooks: T) -> Self {
+        self.config.dispatcher_hooks(hooks);
+        self
+    }
+
     /// Provide control service for http/1.
     pub fn h1_control<CF, CT>(self, control: CF) -> HttpServiceBuilder<F, S, CT, C2>
     where
@@ -0,0 +1,604 @@
+use std::collections::{HashMap, VecDeque};
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use md5::Md5;
+use nanorand::{Rng, WyRand};
+use sha2::{Digest, Sha256};
+
+use crate::http::header::{self, HeaderMap, HeaderValue};
+use crate::http::{Method, StatusCode, Uri};
+use crate::service::{Middleware, Service, ServiceCtx};
+
+use super::error::SendRequestError;
+use super::{ClientMessage, ClientResponse, ClientService};
+
+/// Digest access authentication middleware (RFC 7616).
+///
+/// If server responds with `401 Unauthorized` and `Digest` challenge,
+/// request is re-sent with `Authorization` header. Challenge is remembered
+/// per origin, subsequent requests to the same origin are authorized
+/// without extra round trip. `MD5`, `SHA-256` and their `-sess` variants
+/// are supported, `auth-int` quality of protection is not supported.
+/// Challenges are kept for up to 256 origins by default, least recently
+/// used origins are evicted first.
+///
+/// Only `Digest` scheme is supported, `NTLM` and `Negotiate` challenges
+/// are ignored and `401` response is returned as is.
+///
+/// Requests with streaming body and requests with explicitly set
+/// `Authorization` header are not handled.
+///
+/// ```rust
+/// use ntex::http::client::{Client, DigestAuth};
+///
+/// let client = Client::build()
+///     .wrap(DigestAuth::new("user", "password"))
+///     .finish();
+/// ```
+#[derive(Clone)]
+pub struct DigestAuth(Rc<Inner>);
+
+struct Inner {
+    username: String,
+    password: String,
+    challenges: RefCell<Challenges>,
+}
+
+impl DigestAuth {
+    /// Create digest authentication middleware with credentials
+    pub fn new<U, P>(username: U, password: P) -> Self
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        DigestAuth(Rc::new(Inner {
+            username: username.into(),
+            password: password.into(),
+            challenges: RefCell::new(Challenges {
+                capacity: 256,
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+        }))
+    }
+
+    /// Set max number of origins to keep challenges for.
+    ///
+    /// Least recently used origins are evicted first. By default
+    /// challenges are kept for 256 origins.
+    pub fn max_origins(self, capacity: usize) -> Self {
+        self.0.challenges.borrow_mut().capacity = capacity;
+        self
+    }
+}
+
+impl fmt::Debug for DigestAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DigestAuth")
+            .field("username", &self.0.username)
+            .finish()
+    }
+}
+
+impl Middleware<ClientService> for DigestAuth {
+    type Service = DigestAuthMiddleware;
+
+    fn create(&self, service: ClientService) -> Self::Service {
+        DigestAuthMiddleware {
+            service,
+            inner: self.0.clone(),
+        }
+    }
+}
+
+/// Digest access authentication service
+pub struct DigestAuthMiddleware {
+    service: ClientService,
+    inner: Rc<Inner>,
+}
+
+impl fmt::Debug for DigestAuthMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DigestAuthMiddleware")
+            .field("username", &self.inner.username)
+            .finish()
+    }
+}
+
+impl Service<ClientMessage> for DigestAuthMiddleware {
+    type Response = ClientResponse;
+    type Error = SendRequestError;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        mut msg: ClientMessage,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<ClientResponse, SendRequestError> {
+        let has_auth = msg.head().headers.contains_key(header::AUTHORIZATION)
+            || msg
+                .head_type()
+                .extra_headers()
+                .map(|h| h.contains_key(header::AUTHORIZATION))
+                .unwrap_or(false);
+        if has_auth {
+            return ctx.call(&self.service, msg).await;
+        }
+
+        let method = msg.head().method.clone();
+        let uri = msg.head().uri.clone();
+        let origin = origin(&uri);
+
+        // authorize with known challenge
+        let auth = self
+            .inner
+            .challenges
+            .borrow_mut()
+            .get(&origin)
+            .and_then(|ch| self.authorization(ch, &method, &uri));
+        if let Some(auth) = auth {
+            msg.set_header(header::AUTHORIZATION, auth);
+        }
+
+        let retry = msg.try_clone();
+        let res = ctx.call(&self.service, msg).await?;
+        if res.status() != StatusCode::UNAUTHORIZED {
+            return Ok(res);
+        }
+
+        let (mut retry, mut challenge) = match (retry, Challenge::select(res.headers())) {
+            (Some(retry), Some(challenge)) => (retry, challenge),
+            _ => return Ok(res),
+        };
+        drop(res);
+
+        log::trace!("Authorizing {:?} with digest challenge", uri);
+        let auth = self.authorization(&mut challenge, &method, &uri);
+        self.inner.challenges.borrow_mut().insert(origin, challenge);
+        if let Some(auth) = auth {
+            retry.set_header(header::AUTHORIZATION, auth);
+        }
+        ctx.call(&self.service, retry).await
+    }
+}
+
+impl DigestAuthMiddleware {
+    fn authorization(
+        &self,
+        challenge: &mut Challenge,
+        method: &Method,
+        uri: &Uri,
+    ) -> Option<HeaderValue> {
+        let mut rng = WyRand::new();
+        let cnonce = format!(
+            "{:016x}{:016x}",
+            rng.generate::<u64>(),
+            rng.generate::<u64>()
+        );
+        let value = challenge.authorize(
+            &self.inner.username,
+            &self.inner.password,
+            method,
+            uri,
+            &cnonce,
+        );
+        match HeaderValue::try_from(value) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                Some(value)
+            }
+            Err(e) => {
+                log::error!("Digest authorization header error: {:?}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Per origin challenges, least recently used origins are evicted first
+struct Challenges {
+    capacity: usize,
+    entries: HashMap<String, Challenge>,
+    order: VecDeque<String>,
+}
+
+impl Challenges {
+    fn get(&mut self, origin: &str) -> Option<&mut Challenge> {
+        if self.entries.contains_key(origin) {
+            self.touch(origin);
+        }
+        self.entries.get_mut(origin)
+    }
+
+    fn insert(&mut self, origin: String, challenge: Challenge) {
+        if self.entries.insert(origin.clone(), challenge).is_some() {
+            self.touch(&origin);
+        } else {
+            self.order.push_back(origin);
+        }
+        while self.entries.len() > self.capacity {
+            if let Some(origin) = self.order.pop_front() {
+                self.entries.remove(&origin);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn touch(&mut self, origin: &str) {
+        if let Some(idx) = self.order.iter().position(|o| o == origin) {
+            if let Some(origin) = self.order.remove(idx) {
+                self.order.push_back(origin);
+            }
+        }
+    }
+}
+
+fn origin(uri: &Uri) -> String {
+    format!(
+        "{}://{}",
+        uri.scheme_str().unwrap_or("http"),
+        uri.authority().map(|a| a.as_str()).unwrap_or("")
+    )
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Algorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl Algorithm {
+    fn parse(value: &str) -> Option<Self> {
+        if value.eq_ignore_ascii_case("MD5") {
+            Some(Algorithm::Md5)
+        } else if value.eq_ignore_ascii_case("MD5-sess") {
+            Some(Algorithm::Md5Sess)
+        } else if value.eq_ignore_ascii_case("SHA-256") {
+            Some(Algorithm::Sha256)
+        } else if value.eq_ignore_ascii_case("SHA-256-sess") {
+            Some(Algorithm::Sha256Sess)
+        } else {
+            None
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Md5Sess => "MD5-sess",
+            Algorithm::Sha256 => "SHA-256",
+            Algorithm::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn is_sess(&self) -> bool {
+        matches!(self, Algorithm::Md5Sess | Algorithm::Sha256Sess)
+    }
+
+    fn hash(&self, data: &str) -> String {
+        let digest = match self {
+            Algorithm::Md5 | Algorithm::Md5Sess => Md5::digest(data.as_bytes()).to_vec(),
+            Algorithm::Sha256 | Algorithm::Sha256Sess => {
+                Sha256::digest(data.as_bytes()).to_vec()
+            }
+        };
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Digest challenge
+#[derive(Clone, Debug)]
+struct Challenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: Algorithm,
+    qop: bool,
+    userhash: bool,
+    nc: u32,
+}
+
+impl Challenge {
+    /// Select strongest supported digest challenge
+    fn select(headers: &HeaderMap) -> Option<Challenge> {
+        headers
+            .get_all(header::WWW_AUTHENTICATE)
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(parse_challenges)
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("digest"))
+            .filter_map(|(_, params)| Challenge::new(&params))
+            .max_by_key(|ch| ch.algorithm)
+    }
+
+    fn new(params: &[(String, String)]) -> Option<Challenge> {
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+
+        let qop = match param("qop") {
+            Some(qop) => {
+                if qop
+                    .split(',')
+                    .any(|q| q.trim().eq_ignore_ascii_case("auth"))
+                {
+                    true
+                } else {
+                    // auth-int is not supported
+                    return None;
+                }
+            }
+            None => false,
+        };
+
+        Some(Challenge {
+            qop,
+            realm: param("realm")?.to_string(),
+            nonce: param("nonce")?.to_string(),
+            opaque: param("opaque").map(|v| v.to_string()),
+            algorithm: match param("algorithm") {
+                Some(alg) => Algorithm::parse(alg)?,
+                None => Algorithm::Md5,
+            },
+            userhash: param("userhash")
+                .map(|v| v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            nc: 0,
+        })
+    }
+
+    /// Build `Authorization` header value
+    fn authorize(
+        &mut self,
+        username: &str,
+        password: &str,
+        method: &Method,
+        uri: &Uri,
+        cnonce: &str,
+    ) -> String {
+        self.nc += 1;
+        let alg = self.algorithm;
+        let digest_uri = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+        let nc = format!("{:08x}", self.nc);
+
+        let mut ha1 = alg.hash(&format!("{}:{}:{}", username, self.realm, password));
+        if alg.is_sess() {
+            ha1 = alg.hash(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = alg.hash(&format!("{}:{}", method.as_str(), digest_uri));
+        let response = if self.qop {
+            alg.hash(&format!(
+                "{}:{}:{}:{}:auth:{}",
+                ha1, self.nonce, nc, cnonce, ha2
+            ))
+        } else {
+            alg.hash(&format!("{}:{}:{}", ha1, self.nonce, ha2))
+        };
+
+        let username = if self.userhash {
+            alg.hash(&format!("{}:{}", username, self.realm))
+        } else {
+            username.to_string()
+        };
+        let mut value = format!(
+            "Digest username={}, realm={}, nonce={}, uri={}, algorithm={}, response=\"{}\"",
+            quote(&username),
+            quote(&self.realm),
+            quote(&self.nonce),
+            quote(digest_uri),
+            alg.as_str(),
+            response
+        );
+        if self.qop {
+            value.push_str(&format!(", qop=auth, nc={}, cnonce=\"{}\"", nc, cnonce));
+        }
+        if let Some(ref opaque) = self.opaque {
+            value.push_str(&format!(", opaque={}", quote(opaque)));
+        }
+        if self.userhash {
+            value.push_str(", userhash=true");
+        }
+        value
+    }
+}
+
+fn quote(value: &str) -> String {
+    let mut s = String::with_capacity(value.len() + 2);
+    s.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            s.push('\\');
+        }
+        s.push(c);
+    }
+    s.push('"');
+    s
+}
+
+/// Parse `WWW-Authenticate` header value, it could contain several challenges
+fn parse_challenges(value: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let bytes = value.as_bytes();
+    let is_ws = |b: u8| b == b' ' || b == b'\t';
+    let mut challenges: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        if is_ws(bytes[pos]) || bytes[pos] == b',' {
+            pos += 1;
+            continue;
+        }
+        let start = pos;
+        while pos < bytes.len()
+            && !is_ws(bytes[pos])
+            && bytes[pos] != b','
+            && bytes[pos] != b'='
+        {
+            pos += 1;
+        }
+        let token = &value[start..pos];
+        let mut next = pos;
+        while next < bytes.len() && is_ws(bytes[next]) {
+            next += 1;
+        }
+
+        if next < bytes.len() && bytes[next] == b'=' {
+            // auth-param
+            pos = next + 1;
+            while pos < bytes.len() && is_ws(bytes[pos]) {
+                pos += 1;
+            }
+            let mut val = Vec::new();
+            if pos < bytes.len() && bytes[pos] == b'"' {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos] != b'"' {
+                    if bytes[pos] == b'\\' && pos + 1 < bytes.len() {
+                        pos += 1;
+                    }
+                    val.push(bytes[pos]);
+                    pos += 1;
+                }
+                pos += 1;
+            } else {
+                while pos < bytes.len() && !is_ws(bytes[pos]) && bytes[pos] != b',' {
+                    val.push(bytes[pos]);
+                    pos += 1;
+                }
+            }
+            if let Some((_, params)) = challenges.last_mut() {
+                params.push((
+                    token.to_string(),
+                    String::from_utf8_lossy(&val).into_owned(),
+                ));
+            }
+        } else {
+            challenges.push((token.to_string(), Vec::new()));
+        }
+    }
+    challenges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge(value: &str) -> Option<Challenge> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::WWW_AUTHENTICATE,
+            HeaderValue::try_from(value).unwrap(),
+        );
+        Challenge::select(&headers)
+    }
+
+    #[test]
+    fn test_parse_challenges() {
+        let ch = parse_challenges(
+            "Basic realm=\"simple\", Digest realm=\"a \\\"b\\\"\", qop=\"auth, auth-int\",
+             nonce=abc, Newauth",
+        );
+        assert_eq!(ch.len(), 3);
+        assert_eq!(ch[0].0, "Basic");
+        assert_eq!(ch[0].1, vec![("realm".to_string(), "simple".to_string())]);
+        assert_eq!(ch[1].0, "Digest");
+        assert_eq!(ch[1].1[0].1, "a \"b\"");
+        assert_eq!(ch[1].1[1].1, "auth, auth-int");
+        assert_eq!(ch[1].1[2], ("nonce".to_string(), "abc".to_string()));
+        assert_eq!(ch[2].0, "Newauth");
+        assert!(ch[2].1.is_empty());
+    }
+
+    #[test]
+    fn test_select() {
+        let ch = challenge(
+            "Digest realm=\"r\", nonce=\"n\", algorithm=MD5, \
+             Digest realm=\"r\", nonce=\"n\", algorithm=SHA-256, opaque=\"o\"",
+        )
+        .unwrap();
+        assert_eq!(ch.algorithm, Algorithm::Sha256);
+        assert_eq!(ch.opaque.as_deref(), Some("o"));
+        assert!(!ch.qop);
+
+        assert!(challenge("Digest realm=\"r\", nonce=\"n\", qop=\"auth-int\"").is_none());
+        assert!(challenge("Digest realm=\"r\", nonce=\"n\", algorithm=SHA-512").is_none());
+        assert!(challenge("Digest realm=\"r\"").is_none());
+        assert!(challenge("Basic realm=\"r\"").is_none());
+    }
+
+    #[test]
+    fn test_rfc2617() {
+        let mut ch = challenge(
+            "Digest realm=\"testrealm@host.com\", qop=\"auth,auth-int\", \
+             nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", \
+             opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"",
+        )
+        .unwrap();
+        let value = ch.authorize(
+            "Mufasa",
+            "Circle Of Life",
+            &Method::GET,
+            &Uri::from_static("http://host.com/dir/index.html"),
+            "0a4f113b",
+        );
+        assert!(value.contains("response=\"6629fae49393a05397450978507c4ef1\""));
+        assert!(value.contains("nc=00000001"));
+        assert!(value.contains("uri=\"/dir/index.html\""));
+        assert!(value.contains("opaque=\"5ccc069c403ebaf9f0171e9517f40e41\""));
+
+        let value = ch.authorize(
+            "Mufasa",
+            "Circle Of Life",
+            &Method::GET,
+            &Uri::from_static("http://host.com/dir/index.html"),
+            "0a4f113b",
+        );
+        assert!(value.contains("nc=00000002"));
+    }
+
+    #[test]
+    fn test_rfc7616() {
+        let uri = Uri::from_static("http://www.example.org/dir/index.html");
+        let params = "realm=\"http-auth@example.org\", qop=\"auth, auth-int\", \
+                      nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", \
+                      opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"";
+        let cnonce = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+
+        let mut ch = challenge(&format!("Digest {}, algorithm=MD5", params)).unwrap();
+        let value = ch.authorize("Mufasa", "Circle of Life", &Method::GET, &uri, cnonce);
+        assert!(value.contains("response=\"8ca523f5e9506fed4657c9700eebdbec\""));
+        assert!(value.contains("algorithm=MD5,"));
+
+        let mut ch = challenge(&format!("Digest {}, algorithm=SHA-256", params)).unwrap();
+        let value = ch.authorize("Mufasa", "Circle of Life", &Method::GET, &uri, cnonce);
+        assert!(value.contains(
+            "response=\"753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1\""
+        ));
+    }
+
+    #[test]
+    fn test_max_origins() {
+        let ch = challenge("Digest realm=\"r\", nonce=\"n\"").unwrap();
+        let auth = DigestAuth::new("user", "pwd").max_origins(2);
+        let mut challenges = auth.0.challenges.borrow_mut();
+        challenges.insert("http://a".to_string(), ch.clone());
+        challenges.insert("http://b".to_string(), ch.clone());
+        assert!(challenges.get("http://a").is_some());
+
+        challenges.insert("http://c".to_string(), ch);
+        assert_eq!(challenges.entries.len(), 2);
+        assert!(challenges.get("http://a").is_some());
+        assert!(challenges.get("http://b").is_none());
+        assert!(challenges.get("http://c").is_some());
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("a\"b\\c"), "\"a\\\"b\\\\c\"");
+    }
+}
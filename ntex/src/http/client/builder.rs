@@ -1,25 +1,28 @@
-use std::{fmt, rc::Rc};
+use std::{fmt, net::IpAddr, rc::Rc};
 
 use base64::{engine::general_purpose::STANDARD as base64, Engine};
 
 use crate::http::error::HttpError;
 use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
-use crate::{service::Service, time::Millis};
+use crate::service::{boxed, Middleware, Service};
+use crate::{time::Millis, util::ByteString};
 
-use super::connect::ConnectorWrapper;
-use super::error::ConnectError;
-use super::{Client, ClientConfig, Connect, Connection, Connector};
+use super::connect::{ConnectorWrapper, LocalBind};
+use super::error::{ConnectError, SendRequestError};
+use super::middleware::{Layer, MiddlewareWrapper};
+use super::{Client, ClientConfig, ClientMessage, ClientResponse, ClientService};
+use super::{Connect, Connection, Connector, Proxy, RetryPolicy};
 
 /// An HTTP Client builder
 ///
 /// This type can be used to construct an instance of `Client` through a
 /// builder-like pattern.
-#[derive(Debug)]
 pub struct ClientBuilder {
     config: ClientConfig,
     default_headers: bool,
     allow_redirects: bool,
     max_redirects: usize,
+    middlewares: Vec<Layer>,
 }
 
 impl Default for ClientBuilder {
@@ -34,11 +37,24 @@ impl ClientBuilder {
             default_headers: true,
             allow_redirects: true,
             max_redirects: 10,
+            middlewares: Vec::new(),
             config: ClientConfig {
                 headers: HeaderMap::new(),
                 timeout: Millis(5_000),
                 response_pl_limit: 262_144,
                 response_pl_timeout: Millis(10_000),
+                response_decompress: true,
+                decompress_limit: 0,
+                decompress_ratio: 0,
+                expect_timeout: Millis(1_000),
+                write_timeout: Millis::ZERO,
+                deadline: Millis::ZERO,
+                max_redirects: 10,
+                retry: None,
+                proxies: Vec::new(),
+                bind: LocalBind::default(),
+                #[cfg(feature = "cookie")]
+                cookies: None,
                 connector: Box::new(ConnectorWrapper(Connector::default().finish().into())),
             },
         }
@@ -57,7 +73,8 @@ impl ClientBuilder {
 
     /// Set request timeout.
     ///
-    /// Request timeout is the total time before a response must be received.
+    /// Request timeout is the time before a response head must be received
+    /// after request is sent (time to first byte).
     /// Default value is 5 seconds.
     pub fn timeout<T: Into<Millis>>(mut self, timeout: T) -> Self {
         self.config.timeout = timeout.into();
@@ -70,9 +87,34 @@ impl ClientBuilder {
         self
     }
 
+    /// Set request write timeout.
+    ///
+    /// Write timeout is max time for sending request head and for sending
+    /// request body. Request fails with `SendRequestError::WriteTimeout` error.
+    /// By default write timeout is disabled.
+    pub fn write_timeout<T: Into<Millis>>(mut self, timeout: T) -> Self {
+        self.config.write_timeout = timeout.into();
+        self
+    }
+
+    /// Set request deadline.
+    ///
+    /// Deadline is the total time for connecting, sending request and receiving
+    /// response head, including redirects and retries. Request fails with
+    /// `SendRequestError::DeadlineExceeded` error. By default deadline is disabled.
+    pub fn deadline<T: Into<Millis>>(mut self, timeout: T) -> Self {
+        self.config.deadline = timeout.into();
+        self
+    }
+
     /// Do not follow redirects.
     ///
-    /// Redirects are allowed by default.
+    /// Redirects are allowed by default. For `301` and `302` responses
+    /// `POST` requests are re-sent as `GET`, for `303` responses all requests
+    /// are re-sent as `GET` requests without body, for `307` and `308` responses
+    /// method and body are preserved. Requests with streaming body are re-sent
+    /// only if body is not required. `Authorization`, `Cookie` and `Host`
+    /// headers are not sent to a different origin.
     pub fn disable_redirects(mut self) -> Self {
         self.allow_redirects = false;
         self
@@ -80,12 +122,59 @@ impl ClientBuilder {
 
     /// Set max number of redirects.
     ///
+    /// If number of redirects exceeds max value, request fails with
+    /// `SendRequestError::TooManyRedirects` error.
     /// Max redirects is set to 10 by default.
     pub fn max_redirects(mut self, num: usize) -> Self {
         self.max_redirects = num;
         self
     }
 
+    /// Set retry policy for all requests.
+    ///
+    /// By default requests are not retried.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.config.retry = Some(policy);
+        self
+    }
+
+    /// Send requests through proxy.
+    ///
+    /// Multiple proxies could be registered, for example separate proxies
+    /// for `http` and `https` requests. First proxy that intercepts request
+    /// is used. By default proxy is not used.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.config.proxies.push(proxy);
+        self
+    }
+
+    /// Bind outgoing connections to local address.
+    ///
+    /// Useful for hosts with multiple network addresses.
+    pub fn local_address(mut self, addr: IpAddr) -> Self {
+        self.config.bind.addr = Some(addr);
+        self
+    }
+
+    /// Bind outgoing connections to network interface (`SO_BINDTODEVICE`).
+    ///
+    /// Binding to network interface is supported only on linux and
+    /// with tokio runtime.
+    pub fn interface(mut self, name: &str) -> Self {
+        self.config.bind.interface = Some(ByteString::from(name));
+        self
+    }
+
+    #[cfg(feature = "cookie")]
+    /// Use cookie store for all requests.
+    ///
+    /// Cookies from responses are recorded to the store and matching
+    /// cookies are sent with subsequent requests.
+    pub fn cookie_store(mut self, store: super::CookieStore) -> Self {
+        self.config.cookies = Some(store);
+        self
+    }
+
     /// Do not add default request headers.
     /// By default `Date` and `User-Agent` headers are set.
     pub fn no_default_headers(mut self) -> Self {
@@ -100,6 +189,33 @@ impl ClientBuilder {
         self
     }
 
+    /// Disable automatic decompress of response's body.
+    ///
+    /// Raw response payload is returned, decompression could be enabled
+    /// for specific request.
+    pub fn no_decompress(mut self) -> Self {
+        self.config.response_decompress = false;
+        self
+    }
+
+    /// Max size of decompressed response payload.
+    ///
+    /// If decompressed payload exceeds limit, payload returns
+    /// `PayloadError::Overflow` error. By default size is not limited.
+    pub fn response_decompress_limit(mut self, limit: usize) -> Self {
+        self.config.decompress_limit = limit;
+        self
+    }
+
+    /// Max ratio of decompressed payload size to compressed payload size.
+    ///
+    /// Protects from decompression bombs. If ratio is exceeded, payload
+    /// returns `PayloadError::Overflow` error. By default ratio is not limited.
+    pub fn response_decompress_ratio(mut self, ratio: usize) -> Self {
+        self.config.decompress_ratio = ratio;
+        self
+    }
+
     /// Set response timeout.
     ///
     /// Response payload timeout is the total time before a payload must be received.
@@ -109,6 +225,19 @@ impl ClientBuilder {
         self
     }
 
+    /// Set expect-continue timeout.
+    ///
+    /// For http/1 requests with `Expect: 100-continue` header, client waits
+    /// for `100 Continue` interim response before sending request body. If
+    /// server responds with final response, request body is not sent. If server
+    /// does not respond within timeout, request body is sent anyway.
+    ///
+    /// To disable waiting set value to 0. Default value is 1 second.
+    pub fn expect_timeout<T: Into<Millis>>(mut self, timeout: T) -> Self {
+        self.config.expect_timeout = timeout.into();
+        self
+    }
+
     /// Add default header. Headers added by this method
     /// get added to every request.
     pub fn header<K, V>(mut self, key: K, value: V) -> Self
@@ -153,9 +282,76 @@ impl ClientBuilder {
         self.header(header::AUTHORIZATION, format!("Bearer {}", token))
     }
 
+    /// Register client middleware.
+    ///
+    /// Middleware wraps the whole request processing, including redirects
+    /// and retries. Middleware registered last is called first. Middleware
+    /// could be used for injecting headers, logging, metrics, etc.
+    ///
+    /// ```rust
+    /// use ntex::http::client::{Client, ClientMessage, ClientService};
+    /// use ntex::service::{fn_service, Middleware};
+    ///
+    /// struct Logger;
+    ///
+    /// impl Middleware<ClientService> for Logger {
+    ///     type Service = ClientService;
+    ///
+    ///     fn create(&self, service: ClientService) -> ClientService {
+    ///         let service = ntex::service::Pipeline::new(service);
+    ///         ntex::service::boxed::service(fn_service(move |msg: ClientMessage| {
+    ///             let service = service.clone();
+    ///             async move {
+    ///                 println!("{} {}", msg.head().method, msg.head().uri);
+    ///                 service.call(msg).await
+    ///             }
+    ///         }))
+    ///     }
+    /// }
+    ///
+    /// let client = Client::build().wrap(Logger).finish();
+    /// ```
+    pub fn wrap<M>(mut self, mw: M) -> Self
+    where
+        M: Middleware<ClientService> + 'static,
+        M::Service: Service<ClientMessage, Response = ClientResponse, Error = SendRequestError>
+            + 'static,
+    {
+        self.middlewares
+            .push(Box::new(move |svc| boxed::service(mw.create(svc))));
+        self
+    }
+
     /// Finish build process and create `Client` instance.
-    pub fn finish(self) -> Client {
-        Client(Rc::new(self.config))
+    pub fn finish(mut self) -> Client {
+        self.config.max_redirects = if self.allow_redirects {
+            self.max_redirects
+        } else {
+            0
+        };
+        if self.middlewares.is_empty() {
+            Client(Rc::new(self.config))
+        } else {
+            Client(Rc::new(ClientConfig {
+                connector: Box::new(MiddlewareWrapper::new(
+                    self.config.connector,
+                    self.middlewares,
+                )),
+                ..self.config
+            }))
+        }
+    }
+}
+
+impl fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("config", &self.config)
+            .field("default_headers", &self.default_headers)
+            .field("allow_redirects", &self.allow_redirects)
+            .field("max_redirects", &self.max_redirects)
+            .field("middlewares", &self.middlewares.len())
+            .finish()
     }
 }
 
@@ -0,0 +1,771 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{
+    cell::RefCell, fmt, fs, io, path::PathBuf, pin::Pin, rc::Rc, task::Context, task::Poll,
+};
+
+use crate::http::error::PayloadError;
+use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use crate::http::{h1, Method, Payload, ResponseHead, StatusCode, Uri, Version};
+use crate::service::{Middleware, Service, ServiceCtx};
+use crate::util::{stream_recv, Bytes, BytesMut, Stream};
+
+use super::error::SendRequestError;
+use super::{ClientMessage, ClientResponse, ClientService};
+
+/// Response stored in the cache
+#[derive(Clone, Debug)]
+pub struct CachedResponse {
+    /// Response status
+    pub status: StatusCode,
+    /// Response version
+    pub version: Version,
+    /// Response headers
+    pub headers: HeaderMap,
+    /// Request headers selected by response's `Vary` header
+    pub vary: HeaderMap,
+    /// Response body
+    pub body: Bytes,
+    /// Time when response was received or revalidated
+    pub stored: SystemTime,
+}
+
+/// Storage for cached responses.
+///
+/// Key is the request's uri.
+pub trait CacheStore {
+    /// Get cached response
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+
+    /// Store response
+    fn put(&self, key: &str, response: CachedResponse);
+
+    /// Remove cached response
+    fn remove(&self, key: &str);
+}
+
+/// Client side http cache middleware.
+///
+/// Cache stores responses to `GET` requests and honors `Cache-Control`,
+/// `Expires` and `Vary` headers. Fresh responses are served from the cache,
+/// stale responses with `ETag` or `Last-Modified` validators are revalidated
+/// with conditional request. Successful requests with unsafe methods
+/// invalidate cached response for the same uri.
+///
+/// Requests with `Cache-Control: no-store` and conditional requests set
+/// by user are not cached.
+///
+/// ```rust
+/// use ntex::http::client::{Client, ClientCache, MemoryStore};
+///
+/// let client = Client::build()
+///     .wrap(ClientCache::new(MemoryStore::new(1024)).max_size(1024 * 1024))
+///     .finish();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ClientCache<S> {
+    store: S,
+    max_size: usize,
+}
+
+impl<S> ClientCache<S> {
+    /// Create cache middleware with specified store
+    pub fn new(store: S) -> Self {
+        ClientCache {
+            store,
+            max_size: 1024 * 1024,
+        }
+    }
+
+    /// Max size of response body that could be cached.
+    ///
+    /// By default max size is 1Mb.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = size;
+        self
+    }
+}
+
+impl<S> Middleware<ClientService> for ClientCache<S>
+where
+    S: CacheStore + Clone,
+{
+    type Service = ClientCacheMiddleware<S>;
+
+    fn create(&self, service: ClientService) -> Self::Service {
+        ClientCacheMiddleware {
+            service,
+            store: self.store.clone(),
+            max_size: self.max_size,
+        }
+    }
+}
+
+/// Client side http cache service
+pub struct ClientCacheMiddleware<S> {
+    service: ClientService,
+    store: S,
+    max_size: usize,
+}
+
+impl<S> Service<ClientMessage> for ClientCacheMiddleware<S>
+where
+    S: CacheStore,
+{
+    type Response = ClientResponse;
+    type Error = SendRequestError;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        mut msg: ClientMessage,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<ClientResponse, SendRequestError> {
+        let method = msg.head().method.clone();
+        let uri = msg.head().uri.clone();
+        let key = uri.to_string();
+
+        if method != Method::GET {
+            let res = ctx.call(&self.service, msg).await?;
+            if !method.is_safe()
+                && (res.status().is_success() || res.status().is_redirection())
+            {
+                self.store.remove(&key);
+            }
+            return Ok(res);
+        }
+
+        // user controls caching
+        let req_cc = CacheControl::new(request_headers(&msg, &header::CACHE_CONTROL));
+        if req_cc.no_store
+            || request_header(&msg, &header::IF_NONE_MATCH).is_some()
+            || request_header(&msg, &header::IF_MODIFIED_SINCE).is_some()
+            || request_header(&msg, &header::RANGE).is_some()
+        {
+            return ctx.call(&self.service, msg).await;
+        }
+
+        let cached = self
+            .store
+            .get(&key)
+            .filter(|cached| vary_matches(cached, &msg));
+        if let Some(ref cached) = cached {
+            let age = current_age(cached);
+            let cc = CacheControl::new(cached.headers.get_all(header::CACHE_CONTROL));
+            let fresh = !req_cc.no_cache
+                && !cc.no_cache
+                && age < freshness_lifetime(cached, &cc)
+                && req_cc
+                    .max_age
+                    .map(|max| age.as_secs() <= max)
+                    .unwrap_or(true);
+            if fresh {
+                log::trace!("Serving {:?} from cache", uri);
+                return Ok(self.response(cached.clone(), age, &msg, uri));
+            }
+
+            // revalidate stale response
+            if let Some(etag) = cached.headers.get(header::ETAG) {
+                msg.set_header(header::IF_NONE_MATCH, etag.clone());
+            }
+            if let Some(modified) = cached.headers.get(header::LAST_MODIFIED) {
+                msg.set_header(header::IF_MODIFIED_SINCE, modified.clone());
+            }
+        }
+
+        let mut req_headers = HeaderMap::new();
+        for (name, value) in msg.head().headers.iter() {
+            req_headers.append(name.clone(), value.clone());
+        }
+        if let Some(extra) = msg.head_type().extra_headers() {
+            for (name, value) in extra.iter() {
+                req_headers.insert(name.clone(), value.clone());
+            }
+        }
+        let cfg = msg.cfg.clone();
+
+        let mut res = ctx.call(&self.service, msg).await?;
+
+        if res.status() == StatusCode::NOT_MODIFIED {
+            if let Some(mut cached) = cached {
+                log::trace!("Cached response for {:?} is revalidated", uri);
+                for (name, value) in res.headers().iter() {
+                    if *name != header::CONTENT_LENGTH {
+                        cached.headers.insert(name.clone(), value.clone());
+                    }
+                }
+                cached.stored = SystemTime::now();
+                self.store.put(&key, cached.clone());
+
+                let mut res = ClientResponse::new(
+                    cached_head(&cached, Duration::ZERO),
+                    payload(cached.body),
+                    cfg,
+                );
+                res.url = uri;
+                return Ok(res);
+            }
+        }
+
+        if is_cacheable(&res) {
+            let mut pl = res.take_payload();
+            let mut buf = BytesMut::new();
+            loop {
+                match stream_recv(&mut pl).await {
+                    Some(Ok(chunk)) => {
+                        buf.extend_from_slice(&chunk);
+                        if buf.len() > self.max_size {
+                            // body is too large, restore payload
+                            res.set_payload(Payload::from_stream(Prefixed {
+                                prefix: Some(buf.freeze()),
+                                payload: pl,
+                            }));
+                            break;
+                        }
+                    }
+                    Some(Err(err)) => {
+                        let (mut tx, pl) = h1::Payload::create(false);
+                        tx.feed_data(buf.freeze());
+                        tx.set_error(err);
+                        res.set_payload(pl.into());
+                        break;
+                    }
+                    None => {
+                        let body = buf.freeze();
+                        let mut vary = HeaderMap::new();
+                        for name in vary_names(res.headers()) {
+                            for value in req_headers.get_all(&name) {
+                                vary.append(name.clone(), value.clone());
+                            }
+                        }
+                        self.store.put(
+                            &key,
+                            CachedResponse {
+                                vary,
+                                status: res.status(),
+                                version: res.version(),
+                                headers: res.headers().clone(),
+                                body: body.clone(),
+                                stored: SystemTime::now(),
+                            },
+                        );
+                        res.set_payload(payload(body));
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl<S> ClientCacheMiddleware<S> {
+    fn response(
+        &self,
+        cached: CachedResponse,
+        age: Duration,
+        msg: &ClientMessage,
+        uri: Uri,
+    ) -> ClientResponse {
+        let mut res = ClientResponse::new(
+            cached_head(&cached, age),
+            payload(cached.body),
+            msg.cfg.clone(),
+        );
+        res.url = uri;
+        res
+    }
+}
+
+impl<S> fmt::Debug for ClientCacheMiddleware<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientCacheMiddleware")
+            .field("max_size", &self.max_size)
+            .finish()
+    }
+}
+
+fn cached_head(cached: &CachedResponse, age: Duration) -> ResponseHead {
+    let mut head = ResponseHead::new(cached.status);
+    head.version = cached.version;
+    head.headers = cached.headers.clone();
+    if !age.is_zero() {
+        head.headers
+            .insert(header::AGE, HeaderValue::from(age.as_secs()));
+    }
+    head
+}
+
+fn payload(body: Bytes) -> Payload {
+    let mut pl = h1::Payload::empty();
+    if !body.is_empty() {
+        pl.unread_data(body);
+    }
+    pl.into()
+}
+
+fn request_header<'a>(
+    msg: &'a ClientMessage,
+    name: &HeaderName,
+) -> Option<&'a HeaderValue> {
+    msg.head_type()
+        .extra_headers()
+        .and_then(|h| h.get(name))
+        .or_else(|| msg.head().headers.get(name))
+}
+
+fn request_headers<'a>(
+    msg: &'a ClientMessage,
+    name: &HeaderName,
+) -> impl Iterator<Item = &'a HeaderValue> {
+    match msg.head_type().extra_headers() {
+        Some(extra) if extra.contains_key(name) => extra.get_all(name),
+        _ => msg.head().headers.get_all(name),
+    }
+}
+
+fn vary_names(headers: &HeaderMap) -> Vec<HeaderName> {
+    headers
+        .get_all(header::VARY)
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|name| HeaderName::try_from(name.trim()).ok())
+        .collect()
+}
+
+fn vary_matches(cached: &CachedResponse, msg: &ClientMessage) -> bool {
+    vary_names(&cached.headers)
+        .iter()
+        .all(|name| request_header(msg, name) == cached.vary.get(name))
+}
+
+fn is_cacheable(res: &ClientResponse) -> bool {
+    const STATUSES: &[u16] = &[200, 203, 204, 300, 301, 308, 404, 405, 410, 414, 501];
+
+    if !STATUSES.contains(&res.status().as_u16()) {
+        return false;
+    }
+    let cc = CacheControl::new(res.headers().get_all(header::CACHE_CONTROL));
+    if cc.no_store
+        || res
+            .headers()
+            .get_all(header::VARY)
+            .any(|v| v.to_str().map(|v| v.trim() == "*").unwrap_or(false))
+    {
+        return false;
+    }
+    cc.max_age.is_some()
+        || res.headers().contains_key(header::EXPIRES)
+        || res.headers().contains_key(header::ETAG)
+        || res.headers().contains_key(header::LAST_MODIFIED)
+}
+
+fn header_date(headers: &HeaderMap, name: HeaderName) -> Option<SystemTime> {
+    headers
+        .get(name)
+        .and_then(|v| ntex_http::date::parse_http_date(v.as_bytes()))
+}
+
+/// Age of cached response
+fn current_age(cached: &CachedResponse) -> Duration {
+    let age = cached
+        .headers
+        .get(header::AGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_default();
+    age + SystemTime::now()
+        .duration_since(cached.stored)
+        .unwrap_or_default()
+}
+
+/// Freshness lifetime, `max-age`, `Expires` header or heuristic
+/// freshness based on `Last-Modified` header
+fn freshness_lifetime(cached: &CachedResponse, cc: &CacheControl) -> Duration {
+    if let Some(max_age) = cc.max_age {
+        return Duration::from_secs(max_age);
+    }
+
+    let date = header_date(&cached.headers, header::DATE).unwrap_or(cached.stored);
+    if cached.headers.contains_key(header::EXPIRES) {
+        // invalid date means expired
+        header_date(&cached.headers, header::EXPIRES)
+            .and_then(|expires| expires.duration_since(date).ok())
+            .unwrap_or_default()
+    } else if let Some(modified) = header_date(&cached.headers, header::LAST_MODIFIED) {
+        date.duration_since(modified).unwrap_or_default() / 10
+    } else {
+        Duration::ZERO
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    max_age: Option<u64>,
+}
+
+impl CacheControl {
+    fn new<'a>(values: impl Iterator<Item = &'a HeaderValue>) -> Self {
+        let mut cc = CacheControl::default();
+        for directive in values
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+        {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+            if name.eq_ignore_ascii_case("no-store") {
+                cc.no_store = true;
+            } else if name.eq_ignore_ascii_case("no-cache") {
+                cc.no_cache = true;
+            } else if name.eq_ignore_ascii_case("max-age") {
+                cc.max_age = value.and_then(|v| v.parse().ok()).or(Some(0));
+            }
+        }
+        cc
+    }
+}
+
+struct Prefixed {
+    prefix: Option<Bytes>,
+    payload: Payload,
+}
+
+impl Stream for Prefixed {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        if let Some(prefix) = self.prefix.take() {
+            Poll::Ready(Some(Ok(prefix)))
+        } else {
+            Pin::new(&mut self.payload).poll_next(cx)
+        }
+    }
+}
+
+/// In-memory cache store.
+///
+/// Store keeps up to `capacity` responses, oldest responses are evicted first.
+#[derive(Clone, Debug)]
+pub struct MemoryStore(Rc<RefCell<MemoryInner>>);
+
+#[derive(Debug)]
+struct MemoryInner {
+    capacity: usize,
+    entries: HashMap<String, CachedResponse>,
+    order: VecDeque<String>,
+}
+
+impl MemoryStore {
+    /// Create memory store
+    pub fn new(capacity: usize) -> Self {
+        MemoryStore(Rc::new(RefCell::new(MemoryInner {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        })))
+    }
+
+    /// Number of stored responses
+    pub fn len(&self) -> usize {
+        self.0.borrow().entries.len()
+    }
+
+    /// Check if store is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().entries.is_empty()
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.0.borrow().entries.get(key).cloned()
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) {
+        let mut inner = self.0.borrow_mut();
+        if inner.entries.insert(key.to_string(), response).is_none() {
+            inner.order.push_back(key.to_string());
+        }
+        while inner.entries.len() > inner.capacity {
+            if let Some(key) = inner.order.pop_front() {
+                inner.entries.remove(&key);
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let mut inner = self.0.borrow_mut();
+        if inner.entries.remove(key).is_some() {
+            inner.order.retain(|k| k != key);
+        }
+    }
+}
+
+/// Disk cache store.
+///
+/// Every response is stored in separate file in the cache directory.
+/// Store uses blocking file system operations.
+#[derive(Clone, Debug)]
+pub struct DiskStore {
+    dir: Rc<PathBuf>,
+}
+
+impl DiskStore {
+    /// Create disk store, cache directory is created if it does not exist
+    pub fn new<P: Into<PathBuf>>(dir: P) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(DiskStore { dir: Rc::new(dir) })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // fnv-1a, file names must be stable between restarts
+        let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+        self.dir.join(format!("{:016x}.cache", hash))
+    }
+}
+
+impl CacheStore for DiskStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let data = fs::read(self.path(key)).ok()?;
+        let (stored_key, response) = decode(&data)?;
+        if stored_key == key {
+            Some(response)
+        } else {
+            None
+        }
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) {
+        let path = self.path(key);
+        let tmp = path.with_extension("tmp");
+        if let Err(e) =
+            fs::write(&tmp, encode(key, &response)).and_then(|_| fs::rename(&tmp, &path))
+        {
+            log::error!("Cannot store cached response {:?}: {}", path, e);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = fs::remove_file(self.path(key));
+    }
+}
+
+const MAGIC: &[u8] = b"NTXCACHE1";
+
+fn encode(key: &str, res: &CachedResponse) -> Vec<u8> {
+    fn put_bytes(buf: &mut Vec<u8>, data: &[u8]) {
+        buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        buf.extend_from_slice(data);
+    }
+    fn put_headers(buf: &mut Vec<u8>, headers: &HeaderMap) {
+        buf.extend_from_slice(&(headers.iter().count() as u32).to_be_bytes());
+        for (name, value) in headers.iter() {
+            put_bytes(buf, name.as_str().as_bytes());
+            put_bytes(buf, value.as_bytes());
+        }
+    }
+
+    let stored = res.stored.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let version = match res.version {
+        Version::HTTP_09 => 0,
+        Version::HTTP_10 => 1,
+        Version::HTTP_2 => 3,
+        Version::HTTP_3 => 4,
+        _ => 2,
+    };
+
+    let mut buf = Vec::with_capacity(res.body.len() + 512);
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&stored.as_secs().to_be_bytes());
+    buf.extend_from_slice(&stored.subsec_nanos().to_be_bytes());
+    buf.extend_from_slice(&res.status.as_u16().to_be_bytes());
+    buf.push(version);
+    put_bytes(&mut buf, key.as_bytes());
+    put_headers(&mut buf, &res.headers);
+    put_headers(&mut buf, &res.vary);
+    buf.extend_from_slice(&res.body);
+    buf
+}
+
+fn decode(data: &[u8]) -> Option<(String, CachedResponse)> {
+    struct Reader<'a>(&'a [u8]);
+
+    impl<'a> Reader<'a> {
+        fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+            if self.0.len() >= n {
+                let (head, tail) = self.0.split_at(n);
+                self.0 = tail;
+                Some(head)
+            } else {
+                None
+            }
+        }
+
+        fn u32(&mut self) -> Option<u32> {
+            Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+        }
+
+        fn bytes(&mut self) -> Option<&'a [u8]> {
+            let len = self.u32()? as usize;
+            self.take(len)
+        }
+
+        fn headers(&mut self) -> Option<HeaderMap> {
+            let mut headers = HeaderMap::new();
+            for _ in 0..self.u32()? {
+                let name = HeaderName::from_bytes(self.bytes()?).ok()?;
+                let value = HeaderValue::from_bytes(self.bytes()?).ok()?;
+                headers.append(name, value);
+            }
+            Some(headers)
+        }
+    }
+
+    let mut rd = Reader(data);
+    if rd.take(MAGIC.len())? != MAGIC {
+        return None;
+    }
+    let secs = u64::from_be_bytes(rd.take(8)?.try_into().ok()?);
+    let nanos = rd.u32()?;
+    let status = u16::from_be_bytes(rd.take(2)?.try_into().ok()?);
+    let version = match rd.take(1)?[0] {
+        0 => Version::HTTP_09,
+        1 => Version::HTTP_10,
+        3 => Version::HTTP_2,
+        4 => Version::HTTP_3,
+        _ => Version::HTTP_11,
+    };
+    let key = String::from_utf8(rd.bytes()?.to_vec()).ok()?;
+    let headers = rd.headers()?;
+    let vary = rd.headers()?;
+
+    Some((
+        key,
+        CachedResponse {
+            headers,
+            vary,
+            version,
+            status: StatusCode::from_u16(status).ok()?,
+            body: Bytes::copy_from_slice(rd.0),
+            stored: UNIX_EPOCH + Duration::new(secs, nanos),
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(headers: &[(HeaderName, &'static str)]) -> CachedResponse {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(name.clone(), HeaderValue::from_static(value));
+        }
+        CachedResponse {
+            status: StatusCode::OK,
+            version: Version::HTTP_11,
+            headers: map,
+            vary: HeaderMap::new(),
+            body: Bytes::from_static(b"body"),
+            stored: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn test_cache_control() {
+        let values = [
+            HeaderValue::from_static("no-cache, Max-Age=\"10\""),
+            HeaderValue::from_static("no-store"),
+        ];
+        let cc = CacheControl::new(values.iter());
+        assert!(cc.no_store);
+        assert!(cc.no_cache);
+        assert_eq!(cc.max_age, Some(10));
+
+        let cc = CacheControl::new([HeaderValue::from_static("max-age=x")].iter());
+        assert_eq!(cc.max_age, Some(0));
+    }
+
+    #[test]
+    fn test_freshness() {
+        let cc = CacheControl::default();
+        let res = cached(&[(header::CACHE_CONTROL, "max-age=60"), (header::AGE, "10")]);
+        let cc2 = CacheControl::new(res.headers.get_all(header::CACHE_CONTROL));
+        assert_eq!(freshness_lifetime(&res, &cc2), Duration::from_secs(60));
+        assert!(current_age(&res) >= Duration::from_secs(10));
+
+        let res = cached(&[
+            (header::DATE, "Sun, 06 Nov 1994 08:49:37 GMT"),
+            (header::EXPIRES, "Sun, 06 Nov 1994 08:50:37 GMT"),
+        ]);
+        assert_eq!(freshness_lifetime(&res, &cc), Duration::from_secs(60));
+
+        let res = cached(&[(header::EXPIRES, "0")]);
+        assert_eq!(freshness_lifetime(&res, &cc), Duration::ZERO);
+
+        let res = cached(&[
+            (header::DATE, "Sun, 06 Nov 1994 08:49:37 GMT"),
+            (header::LAST_MODIFIED, "Sun, 06 Nov 1994 08:39:37 GMT"),
+        ]);
+        assert_eq!(freshness_lifetime(&res, &cc), Duration::from_secs(60));
+
+        let res = cached(&[]);
+        assert_eq!(freshness_lifetime(&res, &cc), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let store = MemoryStore::new(2);
+        assert!(store.is_empty());
+        store.put("a", cached(&[]));
+        store.put("b", cached(&[]));
+        store.put("a", cached(&[]));
+        assert_eq!(store.len(), 2);
+        store.put("c", cached(&[]));
+        assert_eq!(store.len(), 2);
+        assert!(store.get("a").is_none());
+        assert!(store.get("b").is_some());
+        store.remove("b");
+        assert!(store.get("b").is_none());
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_disk_store() {
+        let dir = std::env::temp_dir().join(format!("ntex-cache-{}", std::process::id()));
+        let store = DiskStore::new(&dir).unwrap();
+
+        let mut res = cached(&[(header::ETAG, "\"v1\""), (header::VARY, "accept")]);
+        res.vary
+            .insert(header::ACCEPT, HeaderValue::from_static("text/plain"));
+        store.put("http://localhost/test", res.clone());
+
+        let stored = store.get("http://localhost/test").unwrap();
+        assert_eq!(stored.status, res.status);
+        assert_eq!(stored.version, res.version);
+        assert_eq!(stored.headers.get(header::ETAG).unwrap(), "\"v1\"");
+        assert_eq!(stored.vary.get(header::ACCEPT).unwrap(), "text/plain");
+        assert_eq!(stored.body, res.body);
+        assert_eq!(stored.stored, res.stored);
+        assert!(store.get("http://localhost/other").is_none());
+
+        store.remove("http://localhost/test");
+        assert!(store.get("http://localhost/test").is_none());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -1,14 +1,29 @@
 use std::{fmt, net, rc::Rc};
 
-use crate::http::{body::Body, RequestHeadType};
-use crate::{service::Pipeline, service::Service, time::Millis, util::BoxFuture};
+use crate::http::header::PROXY_AUTHORIZATION;
+use crate::http::{body::Body, Payload, RequestHeadType, ResponseHead};
+use crate::service::{Pipeline, Service};
+use crate::time::{sleep, timeout_checked, Millis};
+use crate::util::{BoxFuture, ByteString};
 
 use super::error::{ConnectError, SendRequestError};
+use super::redirect::Replay;
 use super::response::ClientResponse;
+use super::retry::RetryPolicy;
 use super::{ClientConfig, Connect as ClientConnect, Connection};
 
 pub(super) struct ConnectorWrapper<T>(pub(crate) Pipeline<T>);
 
+/// Per-request deadline
+pub(super) struct RequestDeadline(pub(super) Millis);
+
+/// Local address and network interface for outgoing connections
+#[derive(Clone, Debug, Default)]
+pub(super) struct LocalBind {
+    pub(super) addr: Option<net::IpAddr>,
+    pub(super) interface: Option<ByteString>,
+}
+
 impl<T> fmt::Debug for ConnectorWrapper<T>
 where
     T: fmt::Debug,
@@ -44,19 +59,171 @@ where
         cfg: Rc<ClientConfig>,
     ) -> BoxFuture<'_, Result<ClientResponse, SendRequestError>> {
         Box::pin(async move {
-            // connect to the host
-            let fut = self.0.call(ClientConnect {
-                uri: head.as_ref().uri.clone(),
-                addr,
-            });
-
-            let connection = fut.await?;
+            let deadline = head
+                .as_ref()
+                .extensions()
+                .get::<RequestDeadline>()
+                .map(|d| d.0)
+                .unwrap_or(cfg.deadline);
 
-            // send request
-            connection
-                .send_request(head, body, timeout)
+            timeout_checked(deadline, self.send_loop(head, body, addr, timeout, cfg))
                 .await
-                .map(|(head, payload)| ClientResponse::new(head, payload, cfg))
+                .map_err(|_| SendRequestError::DeadlineExceeded)
+                .and_then(|res| res)
         })
     }
 }
+
+impl<T> ConnectorWrapper<T>
+where
+    T: Service<ClientConnect, Response = Connection, Error = ConnectError>,
+{
+    async fn send_loop(
+        &self,
+        mut head: RequestHeadType,
+        mut body: Body,
+        mut addr: Option<net::SocketAddr>,
+        timeout: Millis,
+        cfg: Rc<ClientConfig>,
+    ) -> Result<ClientResponse, SendRequestError> {
+        let (mut redirects, mut attempt) = (0, 0);
+
+        let retry = {
+            let h = head.as_ref();
+            h.extensions()
+                .get::<RetryPolicy>()
+                .cloned()
+                .or_else(|| cfg.retry.clone())
+                .filter(|policy| policy.is_retryable(&h.method))
+        };
+        let bind = head
+            .as_ref()
+            .extensions()
+            .get::<LocalBind>()
+            .cloned()
+            .unwrap_or_else(|| cfg.bind.clone());
+
+        loop {
+            let uri = head.as_ref().uri.clone();
+            let replay = if cfg.max_redirects > 0 || retry.is_some() {
+                Some(Replay::new(&head, &body))
+            } else {
+                None
+            };
+
+            // attach stored cookies
+            #[cfg(feature = "cookie")]
+            {
+                if let Some(ref store) = cfg.cookies {
+                    store.apply(&mut head);
+                }
+            }
+
+            let result = self.send_once(head, body, addr, &bind, timeout, &cfg).await;
+
+            // record response cookies
+            #[cfg(feature = "cookie")]
+            {
+                if let (Some(store), Ok((res_head, _))) = (&cfg.cookies, &result) {
+                    store.store(&uri, &res_head.headers);
+                }
+            }
+
+            // retry request
+            if let Some(ref policy) = retry {
+                if replay.as_ref().map(|r| r.is_replayable()).unwrap_or(false) {
+                    let delay = policy.next_delay(
+                        attempt,
+                        &result.as_ref().map(|(h, _)| (h.status, &h.headers)),
+                    );
+                    if let Some(delay) = delay {
+                        log::trace!("Retrying request {:?} in {:?}", uri, delay);
+                        drop(result);
+                        sleep(delay).await;
+
+                        attempt += 1;
+                        (head, body) = replay
+                            .and_then(|r| r.retry())
+                            .map(|(h, b)| (h.into(), b))
+                            .unwrap();
+                        continue;
+                    }
+                }
+            }
+            let (res_head, payload) = result?;
+
+            // follow redirect
+            if cfg.max_redirects > 0 {
+                if let Some((next, next_body, same_origin)) =
+                    replay.and_then(|r| r.redirect(res_head.status, &res_head.headers))
+                {
+                    if redirects >= cfg.max_redirects {
+                        return Err(SendRequestError::TooManyRedirects);
+                    }
+                    log::trace!("Following redirect {:?} -> {:?}", uri, next.uri);
+
+                    redirects += 1;
+                    head = next.into();
+                    body = next_body;
+                    if !same_origin {
+                        addr = None;
+                    }
+                    continue;
+                }
+            }
+
+            let mut res = ClientResponse::new(res_head, payload, cfg);
+            res.url = uri;
+            return Ok(res);
+        }
+    }
+
+    async fn send_once(
+        &self,
+        mut head: RequestHeadType,
+        body: Body,
+        addr: Option<net::SocketAddr>,
+        bind: &LocalBind,
+        timeout: Millis,
+        cfg: &ClientConfig,
+    ) -> Result<(ResponseHead, Payload), SendRequestError> {
+        let uri = head.as_ref().uri.clone();
+        let proxy = cfg.proxies.iter().find(|p| p.intercepts(&uri)).cloned();
+
+        // plain http requests are sent to the proxy in absolute form,
+        // secure requests use tunnel
+        let absolute = match proxy {
+            Some(ref proxy) if !matches!(uri.scheme_str(), Some("https") | Some("wss")) => {
+                if let Some(auth) = proxy.authorization() {
+                    head.set_header(PROXY_AUTHORIZATION, auth.clone());
+                }
+                true
+            }
+            _ => false,
+        };
+
+        // connect to the host
+        let connection = self
+            .0
+            .call(ClientConnect {
+                uri,
+                addr,
+                proxy,
+                local_addr: bind.addr,
+                interface: bind.interface.clone(),
+            })
+            .await?;
+
+        // send request
+        connection
+            .send_request(
+                head,
+                body,
+                timeout,
+                cfg.write_timeout,
+                cfg.expect_timeout,
+                absolute,
+            )
+            .await
+    }
+}
@@ -83,6 +83,9 @@ impl Connection {
         head: H,
         body: B,
         timeout: Millis,
+        write_timeout: Millis,
+        expect_timeout: Millis,
+        absolute: bool,
     ) -> Result<(ResponseHead, Payload), SendRequestError> {
         match self.io.take().unwrap() {
             ConnectionType::H1(io) => {
@@ -92,12 +95,23 @@ impl Connection {
                     body,
                     self.created,
                     timeout,
+                    write_timeout,
+                    expect_timeout,
+                    absolute,
                     self.pool,
                 )
                 .await
             }
             ConnectionType::H2(io) => {
-                h2proto::send_request(io, head.into(), body, timeout).await
+                h2proto::send_request(
+                    io,
+                    head.into(),
+                    body,
+                    timeout,
+                    write_timeout,
+                    self.pool,
+                )
+                .await
             }
         }
     }
@@ -1,14 +1,19 @@
-use std::{fmt, task::Context, task::Poll, time::Duration};
+use std::{fmt, future::Future, io, task::Context, task::Poll, time::Instant};
 
 use ntex_h2::{self as h2};
 
-use crate::connect::{Connect as TcpConnect, Connector as TcpConnector};
-use crate::service::{apply_fn, boxed, Service, ServiceCtx};
+use crate::connect::{Connect as TcpConnect, Connector as TcpConnector, Resolver};
+use crate::service::{apply_fn, boxed, fn_service, Pipeline, Service, ServiceCtx};
+#[cfg(any(feature = "openssl", feature = "rustls"))]
+use crate::time::timeout_checked;
 use crate::time::{Millis, Seconds};
 use crate::util::{timeout::TimeoutError, timeout::TimeoutService};
-use crate::{http::Uri, io::IoBoxed};
+use crate::util::{BytesMut, Either};
+use crate::{http::h1, http::Uri, io::Io, io::IoBoxed};
 
-use super::{connection::Connection, error::ConnectError, pool::ConnectionPool, Connect};
+use super::metrics::{ConnectTimings, PoolMetrics};
+use super::pool::{ConnectionPool, PoolConfig};
+use super::{connection::Connection, error::ConnectError, Connect, Proxy};
 
 #[cfg(feature = "openssl")]
 use tls_openssl::ssl::SslConnector as OpensslConnector;
@@ -17,6 +22,20 @@ use tls_openssl::ssl::SslConnector as OpensslConnector;
 use tls_rustls::ClientConfig;
 
 type BoxedConnector = boxed::BoxService<TcpConnect<Uri>, IoBoxed, ConnectError>;
+type TimedConnector = boxed::BoxService<
+    (TcpConnect<Uri>, Option<Proxy>),
+    (IoBoxed, ConnectTimings),
+    ConnectError,
+>;
+
+#[derive(Debug)]
+enum SecureConnector {
+    Custom(BoxedConnector),
+    #[cfg(feature = "openssl")]
+    Openssl(OpensslConnector),
+    #[cfg(feature = "rustls")]
+    Rustls(std::sync::Arc<ClientConfig>),
+}
 
 #[derive(Debug)]
 /// Manages http client network connectivity.
@@ -34,13 +53,10 @@ type BoxedConnector = boxed::BoxService<TcpConnect<Uri>, IoBoxed, ConnectError>;
 /// ```
 pub struct Connector {
     timeout: Millis,
-    conn_lifetime: Duration,
-    conn_keep_alive: Duration,
-    disconnect_timeout: Seconds,
-    limit: usize,
-    h2config: h2::Config,
-    connector: BoxedConnector,
-    ssl_connector: Option<BoxedConnector>,
+    handshake_timeout: Millis,
+    pool: PoolConfig,
+    connector: Option<BoxedConnector>,
+    ssl_connector: Option<SecureConnector>,
 }
 
 impl Default for Connector {
@@ -52,18 +68,11 @@ impl Default for Connector {
 impl Connector {
     pub fn new() -> Connector {
         let conn = Connector {
-            connector: boxed::service(
-                TcpConnector::new()
-                    .map(IoBoxed::from)
-                    .map_err(ConnectError::from),
-            ),
+            connector: None,
             ssl_connector: None,
             timeout: Millis(1_000),
-            conn_lifetime: Duration::from_secs(75),
-            conn_keep_alive: Duration::from_secs(15),
-            disconnect_timeout: Seconds(3),
-            limit: 100,
-            h2config: h2::Config::client(),
+            handshake_timeout: Millis(5_000),
+            pool: PoolConfig::default(),
         };
 
         #[cfg(feature = "openssl")]
@@ -103,34 +112,100 @@ impl Connector {
     /// Connection timeout.
     ///
     /// i.e. max time to connect to remote host including dns name resolution.
+    /// For custom secure connectors timeout includes tls handshake.
     /// Set to 1 second by default.
     pub fn timeout<T: Into<Millis>>(mut self, timeout: T) -> Self {
         self.timeout = timeout.into();
         self
     }
 
+    /// Tls handshake timeout.
+    ///
+    /// Timeout is applied to openssl and rustls connectors.
+    /// Set to 5 seconds by default.
+    pub fn handshake_timeout<T: Into<Millis>>(mut self, timeout: T) -> Self {
+        self.handshake_timeout = timeout.into();
+        self
+    }
+
     #[cfg(feature = "openssl")]
     /// Use openssl connector for secured connections.
-    pub fn openssl(self, connector: OpensslConnector) -> Self {
-        use crate::connect::openssl::SslConnector;
-
-        self.secure_connector(SslConnector::new(connector))
+    pub fn openssl(mut self, connector: OpensslConnector) -> Self {
+        self.ssl_connector = Some(SecureConnector::Openssl(connector));
+        self
     }
 
     #[cfg(feature = "rustls")]
     /// Use rustls connector for secured connections.
-    pub fn rustls(self, connector: ClientConfig) -> Self {
-        use crate::connect::rustls::TlsConnector;
-
-        self.secure_connector(TlsConnector::new(connector))
+    pub fn rustls(mut self, connector: ClientConfig) -> Self {
+        self.ssl_connector = Some(SecureConnector::Rustls(std::sync::Arc::new(connector)));
+        self
     }
 
     /// Set total number of simultaneous connections per type of scheme.
     ///
+    /// Requests to http/2 hosts are multiplexed over shared connections
+    /// and do not use connection slots.
+    ///
     /// If limit is 0, the connector has no limit.
     /// The default limit size is 100.
     pub fn limit(mut self, limit: usize) -> Self {
-        self.limit = limit;
+        self.pool.limit = limit;
+        self
+    }
+
+    /// Set number of simultaneous connections per host.
+    ///
+    /// If limit is 0, the connector has no per host limit.
+    /// By default per host limit is not set.
+    pub fn limit_per_host(mut self, limit: usize) -> Self {
+        self.pool.limit_per_host = limit;
+        self
+    }
+
+    /// Set max number of idle connections per host.
+    ///
+    /// Oldest idle connection is closed if limit is reached.
+    /// If limit is 0, number of idle connections is not limited.
+    /// By default max idle limit is not set.
+    pub fn max_idle(mut self, limit: usize) -> Self {
+        self.pool.max_idle = limit;
+        self
+    }
+
+    /// Set max time to wait for available connection if pool is full.
+    ///
+    /// If timeout expires, `ConnectError::AcquireTimeout` is returned.
+    /// By default timeout is not set.
+    pub fn acquire_timeout<T: Into<Millis>>(mut self, timeout: T) -> Self {
+        self.pool.acquire_timeout = timeout.into();
+        self
+    }
+
+    /// Collect connection pool metrics.
+    ///
+    /// Same metrics object is used for secure and un-secured connections.
+    pub fn metrics(mut self, metrics: PoolMetrics) -> Self {
+        self.pool.metrics = Some(metrics);
+        self
+    }
+
+    /// Set max number of concurrent streams per http/2 connection.
+    ///
+    /// If all http/2 connections to the host are saturated, new connection
+    /// is opened. Http/2 connection with open streams uses single slot of
+    /// pool limits. The default limit is 100.
+    pub fn http2_max_streams(mut self, num: usize) -> Self {
+        self.pool.h2streams = num.max(1);
+        self
+    }
+
+    /// Set ping timeout for http/2 connections.
+    ///
+    /// Idle http/2 connection is checked with ping frames, connection is
+    /// closed if peer does not respond within timeout.
+    pub fn http2_ping_timeout<T: Into<Seconds>>(self, timeout: T) -> Self {
+        self.pool.h2config.ping_timeout(timeout.into());
         self
     }
 
@@ -141,7 +216,7 @@ impl Connector {
     /// exceeds this period, the connection is closed.
     /// Default keep-alive period is 15 seconds.
     pub fn keep_alive(mut self, dur: Seconds) -> Self {
-        self.conn_keep_alive = dur.into();
+        self.pool.conn_keep_alive = dur.into();
         self
     }
 
@@ -151,7 +226,7 @@ impl Connector {
     /// until it is closed regardless of keep-alive period.
     /// Default lifetime period is 75 seconds.
     pub fn lifetime(mut self, dur: Seconds) -> Self {
-        self.conn_lifetime = dur.into();
+        self.pool.conn_lifetime = dur.into();
         self
     }
 
@@ -164,7 +239,7 @@ impl Connector {
     ///
     /// By default disconnect timeout is set to 3 seconds.
     pub fn disconnect_timeout<T: Into<Seconds>>(mut self, timeout: T) -> Self {
-        self.disconnect_timeout = timeout.into();
+        self.pool.disconnect_timeout = timeout.into();
         self
     }
 
@@ -174,7 +249,7 @@ impl Connector {
     where
         O: FnOnce(&h2::Config) -> R,
     {
-        let _ = f(&self.h2config);
+        let _ = f(&self.pool.h2config);
         self
     }
 
@@ -184,20 +259,82 @@ impl Connector {
         T: Service<TcpConnect<Uri>, Error = crate::connect::ConnectError> + 'static,
         IoBoxed: From<T::Response>,
     {
-        self.connector =
-            boxed::service(connector.map(IoBoxed::from).map_err(ConnectError::from));
+        self.connector = Some(boxed::service(
+            connector.map(IoBoxed::from).map_err(ConnectError::from),
+        ));
+        self
+    }
+
+    /// Use custom io factory to open un-secured connections.
+    ///
+    /// Factory receives request's uri and returns connected io object,
+    /// for example in-memory stream or connection to a proxy. Requests are
+    /// sent over returned io as usual, `Host` header is set from request's uri.
+    ///
+    /// ```rust,no_run
+    /// use ntex::http::client::{Client, Connector};
+    ///
+    /// let addr = "127.0.0.1:8080".parse().unwrap();
+    /// let client = Client::build()
+    ///     .connector(
+    ///         Connector::default()
+    ///             .io_factory(move |_| ntex::rt::tcp_connect(addr))
+    ///             .finish(),
+    ///     )
+    ///     .finish();
+    /// ```
+    pub fn io_factory<F, Fut, R>(mut self, f: F) -> Self
+    where
+        F: Fn(Uri) -> Fut + 'static,
+        Fut: Future<Output = io::Result<R>> + 'static,
+        IoBoxed: From<R>,
+    {
+        self.connector = Some(boxed::service(fn_service(move |req: TcpConnect<Uri>| {
+            let fut = f(req.get_ref().clone());
+            async move {
+                fut.await
+                    .map(IoBoxed::from)
+                    .map_err(|e| ConnectError::Disconnected(Some(e)))
+            }
+        })));
         self
     }
 
+    #[cfg(unix)]
+    /// Open un-secured connections to unix domain socket.
+    ///
+    /// All `http` requests are sent to specified socket, uri's authority is
+    /// used only for `Host` header. Usefull for talking to local daemons,
+    /// like docker.
+    ///
+    /// ```rust,no_run
+    /// use ntex::http::client::{Client, Connector};
+    ///
+    /// #[ntex::main]
+    /// async fn main() {
+    ///     let client = Client::build()
+    ///         .connector(Connector::default().unix("/var/run/docker.sock").finish())
+    ///         .finish();
+    ///     let res = client.get("http://localhost/version").send().await;
+    /// }
+    /// ```
+    pub fn unix<P: Into<std::path::PathBuf>>(self, path: P) -> Self {
+        let path = std::rc::Rc::new(path.into());
+        self.io_factory(move |_| {
+            let path = path.clone();
+            async move { crate::rt::unix_connect(path.as_path()).await }
+        })
+    }
+
     /// Use custom connector to open secure connections.
     pub fn secure_connector<T>(mut self, connector: T) -> Self
     where
         T: Service<TcpConnect<Uri>, Error = crate::connect::ConnectError> + 'static,
         IoBoxed: From<T::Response>,
     {
-        self.ssl_connector = Some(boxed::service(
+        self.ssl_connector = Some(SecureConnector::Custom(boxed::service(
             connector.map(IoBoxed::from).map_err(ConnectError::from),
-        ));
+        )));
         self
     }
 
@@ -208,49 +345,64 @@ impl Connector {
         self,
     ) -> impl Service<Connect, Response = Connection, Error = ConnectError> + fmt::Debug
     {
-        let tcp_service = connector(self.connector, self.timeout, self.disconnect_timeout);
+        let disconnect_timeout = self.pool.disconnect_timeout;
+        let tcp_connector = match self.connector {
+            Some(srv) => timed(srv),
+            None => tcp_connector(),
+        };
+        let tcp_service = connector(tcp_connector, self.timeout, disconnect_timeout, false);
 
         let ssl_pool = if let Some(ssl_connector) = self.ssl_connector {
-            let srv = connector(ssl_connector, self.timeout, self.disconnect_timeout);
-            Some(ConnectionPool::new(
-                srv,
-                self.conn_lifetime,
-                self.conn_keep_alive,
-                self.disconnect_timeout,
-                self.limit,
-                self.h2config.clone(),
-            ))
+            // handshake timeout is handled by openssl and rustls connectors
+            let (ssl_connector, timeout) = match ssl_connector {
+                SecureConnector::Custom(srv) => (timed(srv), self.timeout),
+                #[cfg(feature = "openssl")]
+                SecureConnector::Openssl(ssl) => (
+                    openssl_connector(ssl, self.timeout, self.handshake_timeout),
+                    self.timeout + self.handshake_timeout,
+                ),
+                #[cfg(feature = "rustls")]
+                SecureConnector::Rustls(cfg) => (
+                    rustls_connector(cfg, self.timeout, self.handshake_timeout),
+                    self.timeout + self.handshake_timeout,
+                ),
+            };
+            let srv = connector(ssl_connector, timeout, disconnect_timeout, true);
+            Some(ConnectionPool::new(srv, self.pool.clone()))
         } else {
             None
         };
 
         InnerConnector {
-            tcp_pool: ConnectionPool::new(
-                tcp_service,
-                self.conn_lifetime,
-                self.conn_keep_alive,
-                self.disconnect_timeout,
-                self.limit,
-                self.h2config.clone(),
-            ),
+            tcp_pool: ConnectionPool::new(tcp_service, self.pool),
             ssl_pool,
         }
     }
 }
 
 fn connector(
-    connector: BoxedConnector,
+    connector: TimedConnector,
     timeout: Millis,
     disconnect_timeout: Seconds,
-) -> impl Service<Connect, Response = IoBoxed, Error = ConnectError> + fmt::Debug {
+    tunnel: bool,
+) -> impl Service<Connect, Response = (IoBoxed, ConnectTimings), Error = ConnectError> + fmt::Debug
+{
     TimeoutService::new(
         timeout,
-        apply_fn(connector, |msg: Connect, svc| async move {
-            svc.call(TcpConnect::new(msg.uri).set_addr(msg.addr)).await
+        apply_fn(connector, move |msg: Connect, svc| async move {
+            let (req, proxy) = match msg.proxy {
+                // plain requests are sent directly to the proxy
+                Some(proxy) if !tunnel => (TcpConnect::new(proxy.uri().clone()), None),
+                proxy => (TcpConnect::new(msg.uri).set_addr(msg.addr), proxy),
+            };
+            let req = req
+                .set_local_addr(msg.local_addr)
+                .set_interface(msg.interface);
+            svc.call((req, proxy)).await
         })
-        .map(move |io: IoBoxed| {
+        .map(move |(io, timings): (IoBoxed, ConnectTimings)| {
             io.set_disconnect_timeout(disconnect_timeout);
-            io
+            (io, timings)
         })
         .map_err(ConnectError::from),
     )
@@ -260,6 +412,201 @@ fn connector(
     })
 }
 
+/// Measure connect time of custom connector
+fn timed(connector: BoxedConnector) -> TimedConnector {
+    let connector = Pipeline::new(connector);
+    boxed::service(fn_service(
+        move |(req, proxy): (TcpConnect<Uri>, Option<Proxy>)| {
+            let connector = connector.clone();
+            async move {
+                if proxy.is_some() {
+                    return Err(ConnectError::Disconnected(Some(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "Proxy tunnel is not supported by custom connector",
+                    ))));
+                }
+                let start = Instant::now();
+                let io = connector.call(req).await?;
+                let timings = ConnectTimings {
+                    connect: start.elapsed(),
+                    ..Default::default()
+                };
+                Ok::<_, ConnectError>((io, timings))
+            }
+        },
+    ))
+}
+
+/// Resolve host name and open tcp connection
+///
+/// If proxy is set, connection is opened to the proxy and tunnel
+/// to the host is established.
+async fn tcp_connect(
+    resolver: &Pipeline<Resolver<Uri>>,
+    tcp: &Pipeline<TcpConnector<Uri>>,
+    req: TcpConnect<Uri>,
+    proxy: Option<Proxy>,
+    timings: &mut ConnectTimings,
+) -> Result<Io, ConnectError> {
+    let (req, target) = match proxy {
+        Some(proxy) => (
+            TcpConnect::new(proxy.uri().clone())
+                .set_local_addr(req.local_addr())
+                .set_interface(req.interface().cloned()),
+            Some((req.get_ref().clone(), proxy)),
+        ),
+        None => (req, None),
+    };
+
+    let start = Instant::now();
+    let req = resolver.call(req).await?;
+    timings.dns = Some(start.elapsed());
+
+    let start = Instant::now();
+    let io = tcp.call(req).await?;
+    if let Some((uri, proxy)) = target {
+        tunnel(&io, &uri, &proxy).await?;
+    }
+    timings.connect = start.elapsed();
+    Ok(io)
+}
+
+/// Open tunnel to the host with `CONNECT` request
+async fn tunnel(io: &Io, uri: &Uri, proxy: &Proxy) -> Result<(), ConnectError> {
+    let host = uri.host().ok_or(ConnectError::Unresolved)?;
+    let port = uri.port_u16().unwrap_or(443);
+    log::trace!("{}: Open proxy tunnel to {}:{}", io.tag(), host, port);
+
+    let mut buf = BytesMut::with_capacity(256);
+    buf.extend_from_slice(
+        format!("CONNECT {0}:{1} HTTP/1.1\r\nHost: {0}:{1}\r\n", host, port).as_bytes(),
+    );
+    if let Some(auth) = proxy.authorization() {
+        buf.extend_from_slice(b"Proxy-Authorization: ");
+        buf.extend_from_slice(auth.as_bytes());
+        buf.extend_from_slice(b"\r\n");
+    }
+    buf.extend_from_slice(b"\r\n");
+
+    io.write(&buf)
+        .map_err(|e| ConnectError::Disconnected(Some(e)))?;
+    io.flush(true)
+        .await
+        .map_err(|e| ConnectError::Disconnected(Some(e)))?;
+
+    match io.recv(&h1::ClientCodec::default()).await {
+        Ok(Some(head)) if head.status.is_success() => Ok(()),
+        Ok(Some(head)) => Err(ConnectError::ProxyTunnel(head.status)),
+        Ok(None) => Err(ConnectError::Disconnected(None)),
+        Err(Either::Left(e)) => Err(ConnectError::Disconnected(Some(io::Error::new(
+            io::ErrorKind::InvalidData,
+            e,
+        )))),
+        Err(Either::Right(e)) => Err(ConnectError::Disconnected(Some(e))),
+    }
+}
+
+fn tcp_connector() -> TimedConnector {
+    let resolver = Pipeline::new(Resolver::new());
+    let tcp = Pipeline::new(TcpConnector::new());
+    boxed::service(fn_service(
+        move |(req, proxy): (TcpConnect<Uri>, Option<Proxy>)| {
+            let (resolver, tcp) = (resolver.clone(), tcp.clone());
+            async move {
+                let mut timings = ConnectTimings::default();
+                let io = tcp_connect(&resolver, &tcp, req, proxy, &mut timings).await?;
+                Ok::<_, ConnectError>((IoBoxed::from(io), timings))
+            }
+        },
+    ))
+}
+
+#[cfg(feature = "openssl")]
+fn openssl_connector(
+    ssl: OpensslConnector,
+    timeout: Millis,
+    handshake_timeout: Millis,
+) -> TimedConnector {
+    use tls_openssl::ssl::Error as SslError;
+
+    let resolver = Pipeline::new(Resolver::new());
+    let tcp = Pipeline::new(TcpConnector::new());
+    boxed::service(fn_service(
+        move |(req, proxy): (TcpConnect<Uri>, Option<Proxy>)| {
+            let (resolver, tcp, ssl) = (resolver.clone(), tcp.clone(), ssl.clone());
+            async move {
+                let mut timings = ConnectTimings::default();
+                let host = req.host().split(':').next().unwrap().to_string();
+                let io = timeout_checked(
+                    timeout,
+                    tcp_connect(&resolver, &tcp, req, proxy, &mut timings),
+                )
+                .await
+                .map_err(|_| ConnectError::Timeout)??;
+
+                log::trace!("{}: SSL Handshake start for: {:?}", io.tag(), host);
+                let ssl = ssl
+                    .configure()
+                    .and_then(|cfg| cfg.into_ssl(&host))
+                    .map_err(SslError::from)?;
+                let start = Instant::now();
+                let io =
+                    timeout_checked(handshake_timeout, ntex_tls::openssl::connect(io, ssl))
+                        .await
+                        .map_err(|_| ConnectError::HandshakeTimeout)?
+                        .map_err(|e| ConnectError::SslHandshakeError(e.to_string()))?;
+                timings.tls = Some(start.elapsed());
+                Ok::<_, ConnectError>((IoBoxed::from(io), timings))
+            }
+        },
+    ))
+}
+
+#[cfg(feature = "rustls")]
+fn rustls_connector(
+    cfg: std::sync::Arc<ClientConfig>,
+    timeout: Millis,
+    handshake_timeout: Millis,
+) -> TimedConnector {
+    use tls_rustls::pki_types::ServerName;
+
+    let resolver = Pipeline::new(Resolver::new());
+    let tcp = Pipeline::new(TcpConnector::new());
+    boxed::service(fn_service(
+        move |(req, proxy): (TcpConnect<Uri>, Option<Proxy>)| {
+            let (resolver, tcp, cfg) = (resolver.clone(), tcp.clone(), cfg.clone());
+            async move {
+                let mut timings = ConnectTimings::default();
+                let host = req.host().split(':').next().unwrap().to_string();
+                let io = timeout_checked(
+                    timeout,
+                    tcp_connect(&resolver, &tcp, req, proxy, &mut timings),
+                )
+                .await
+                .map_err(|_| ConnectError::Timeout)??;
+
+                log::trace!("{}: TLS Handshake start for: {:?}", io.tag(), host);
+                let host = ServerName::try_from(host).map_err(|e| {
+                    ConnectError::Disconnected(Some(io::Error::new(
+                        io::ErrorKind::Other,
+                        e,
+                    )))
+                })?;
+                let start = Instant::now();
+                let io = timeout_checked(
+                    handshake_timeout,
+                    ntex_tls::rustls::TlsClientFilter::create(io, cfg, host),
+                )
+                .await
+                .map_err(|_| ConnectError::HandshakeTimeout)?
+                .map_err(|e| ConnectError::Disconnected(Some(e)))?;
+                timings.tls = Some(start.elapsed());
+                Ok::<_, ConnectError>((IoBoxed::from(io), timings))
+            }
+        },
+    ))
+}
+
 #[derive(Debug)]
 struct InnerConnector<T> {
     tcp_pool: ConnectionPool<T>,
@@ -268,7 +615,8 @@ struct InnerConnector<T> {
 
 impl<T> Service<Connect> for InnerConnector<T>
 where
-    T: Service<Connect, Response = IoBoxed, Error = ConnectError> + 'static,
+    T: Service<Connect, Response = (IoBoxed, ConnectTimings), Error = ConnectError>
+        + 'static,
 {
     type Response = <ConnectionPool<T> as Service<Connect>>::Response;
     type Error = ConnectError;
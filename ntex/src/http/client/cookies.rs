@@ -0,0 +1,452 @@
+use std::fmt::Write as FmtWrite;
+use std::time::{Duration, SystemTime};
+use std::{cell::RefCell, fmt, net::IpAddr, rc::Rc};
+
+use coo_kie::Cookie;
+use percent_encoding::percent_encode;
+
+use crate::http::header::{HeaderMap, HeaderValue, COOKIE, SET_COOKIE};
+use crate::http::{helpers::USERINFO, uri::Scheme, RequestHeadType, Uri};
+
+/// Cookie store for http client.
+///
+/// Store records cookies from `Set-Cookie` response headers and attaches
+/// matching cookies to subsequent requests. Cookies are matched by domain,
+/// path, secure flag and expiry time. Public suffix list is not checked.
+///
+/// Store is shared between all clones, so it could be inspected and
+/// persisted while client is in use.
+///
+/// ```rust
+/// use ntex::http::client::{Client, CookieStore};
+///
+/// let store = CookieStore::new();
+/// let client = Client::build().cookie_store(store.clone()).finish();
+///
+/// // all stored cookies
+/// let cookies = store.cookies();
+/// ```
+#[derive(Clone, Default)]
+pub struct CookieStore(Rc<RefCell<Vec<Entry>>>);
+
+struct Entry {
+    cookie: Cookie<'static>,
+    domain: String,
+    path: String,
+    host_only: bool,
+    secure: bool,
+    expires: Option<SystemTime>,
+}
+
+impl Entry {
+    fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires.map(|t| t <= now).unwrap_or(false)
+    }
+
+    fn matches(&self, host: &str, path: &str, secure: bool) -> bool {
+        (!self.secure || secure)
+            && if self.host_only {
+                host == self.domain
+            } else {
+                domain_match(host, &self.domain)
+            }
+            && path_match(path, &self.path)
+    }
+}
+
+impl CookieStore {
+    /// Create empty cookie store.
+    pub fn new() -> Self {
+        CookieStore::default()
+    }
+
+    /// Number of stored cookies.
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    /// Check if store is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// Get all stored cookies.
+    ///
+    /// Returned cookies have domain, path, secure flag and expiry time set,
+    /// and could be loaded back to store with `CookieStore::insert()`.
+    pub fn cookies(&self) -> Vec<Cookie<'static>> {
+        let now = SystemTime::now();
+        self.0
+            .borrow()
+            .iter()
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| {
+                let mut cookie = entry.cookie.clone();
+                cookie.set_domain(entry.domain.clone());
+                cookie.set_path(entry.path.clone());
+                cookie.set_secure(entry.secure);
+                if let Some(expires) = entry.expires {
+                    cookie.set_expires(time::OffsetDateTime::from(expires));
+                }
+                cookie
+            })
+            .collect()
+    }
+
+    /// Get cookies that would be sent with request to specified url.
+    pub fn cookies_for(&self, url: &Uri) -> Vec<Cookie<'static>> {
+        let host = if let Some(host) = url.host() {
+            host.to_ascii_lowercase()
+        } else {
+            return Vec::new();
+        };
+        let secure = is_secure(url);
+        let now = SystemTime::now();
+
+        let mut entries = self.0.borrow_mut();
+        entries.retain(|entry| !entry.is_expired(now));
+
+        let mut cookies: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.matches(&host, url.path(), secure))
+            .collect();
+        // cookies with longer paths are listed first
+        cookies.sort_by(|a, b| b.path.len().cmp(&a.path.len()));
+        cookies
+            .into_iter()
+            .map(|entry| entry.cookie.clone())
+            .collect()
+    }
+
+    /// Add cookie to the store.
+    ///
+    /// Cookie must have domain set, cookie is sent to the domain and its
+    /// sub-domains. If path is not set, cookie is sent for all paths.
+    /// Returns `false` if cookie does not have domain.
+    pub fn insert(&self, cookie: Cookie<'static>) -> bool {
+        let domain = match cookie.domain() {
+            Some(domain) if !domain.is_empty() => domain.to_ascii_lowercase(),
+            _ => return false,
+        };
+        let path = match cookie.path() {
+            Some(path) if path.starts_with('/') => path.to_string(),
+            _ => "/".to_string(),
+        };
+        let expires = cookie.expires_datetime().map(SystemTime::from);
+        let secure = cookie.secure().unwrap_or(false);
+
+        self.add(Entry {
+            cookie: Cookie::new(cookie.name().to_string(), cookie.value().to_string()),
+            domain,
+            path,
+            host_only: false,
+            secure,
+            expires,
+        });
+        true
+    }
+
+    /// Remove cookies with specified name.
+    pub fn remove(&self, name: &str) {
+        self.0
+            .borrow_mut()
+            .retain(|entry| entry.cookie.name() != name);
+    }
+
+    /// Remove all cookies.
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear()
+    }
+
+    fn add(&self, entry: Entry) {
+        let mut entries = self.0.borrow_mut();
+        entries.retain(|e| {
+            e.cookie.name() != entry.cookie.name()
+                || e.domain != entry.domain
+                || e.path != entry.path
+        });
+        entries.push(entry);
+    }
+
+    fn delete(&self, name: &str, domain: &str, path: &str) {
+        self.0
+            .borrow_mut()
+            .retain(|e| e.cookie.name() != name || e.domain != domain || e.path != path);
+    }
+
+    /// Record cookies from response headers
+    pub(super) fn store(&self, url: &Uri, headers: &HeaderMap) {
+        let host = if let Some(host) = url.host() {
+            host.to_ascii_lowercase()
+        } else {
+            return;
+        };
+        let secure = is_secure(url);
+        let now = SystemTime::now();
+
+        for hdr in headers.get_all(&SET_COOKIE) {
+            let cookie = match hdr.to_str().map(Cookie::parse_encoded) {
+                Ok(Ok(cookie)) => cookie.into_owned(),
+                _ => {
+                    log::trace!("Cannot parse cookie {:?}", hdr);
+                    continue;
+                }
+            };
+
+            // secure cookies could be set only by secure origin
+            let is_secure_cookie = cookie.secure().unwrap_or(false);
+            if is_secure_cookie && !secure {
+                continue;
+            }
+
+            let (domain, host_only) = match cookie.domain() {
+                Some(domain) if !domain.is_empty() => {
+                    let domain = domain.to_ascii_lowercase();
+                    if !domain_match(&host, &domain) {
+                        log::trace!("Cookie domain {:?} does not match {:?}", domain, host);
+                        continue;
+                    }
+                    (domain, false)
+                }
+                _ => (host.clone(), true),
+            };
+            let path = match cookie.path() {
+                Some(path) if path.starts_with('/') => path.to_string(),
+                _ => default_path(url.path()),
+            };
+
+            let expires = if let Some(max_age) = cookie.max_age() {
+                if max_age.is_negative() || max_age.is_zero() {
+                    self.delete(cookie.name(), &domain, &path);
+                    continue;
+                }
+                Some(now + Duration::from_secs(max_age.whole_seconds() as u64))
+            } else if let Some(expires) = cookie.expires_datetime() {
+                let expires = SystemTime::from(expires);
+                if expires <= now {
+                    self.delete(cookie.name(), &domain, &path);
+                    continue;
+                }
+                Some(expires)
+            } else {
+                None
+            };
+
+            self.add(Entry {
+                cookie: Cookie::new(cookie.name().to_string(), cookie.value().to_string()),
+                domain,
+                path,
+                host_only,
+                secure: is_secure_cookie,
+                expires,
+            });
+        }
+    }
+
+    /// Attach matching cookies to request
+    pub(super) fn apply(&self, head: &mut RequestHeadType) {
+        let cookies = self.cookies_for(&head.as_ref().uri);
+        if cookies.is_empty() {
+            return;
+        }
+
+        let mut value = String::new();
+        for c in cookies {
+            let name = percent_encode(c.name().as_bytes(), USERINFO);
+            let val = percent_encode(c.value().as_bytes(), USERINFO);
+            let _ = write!(value, "; {}={}", name, val);
+        }
+
+        let value = match head.extra_headers().filter(|h| h.contains_key(COOKIE)) {
+            Some(extra) => merge(extra, &value[2..]),
+            None => merge(&head.as_ref().headers, &value[2..]),
+        };
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            match head {
+                RequestHeadType::Owned(h) => {
+                    h.headers.insert(COOKIE, value);
+                }
+                RequestHeadType::Rc(_, extra) => {
+                    extra
+                        .get_or_insert_with(HeaderMap::new)
+                        .insert(COOKIE, value);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CookieStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CookieStore")
+            .field("cookies", &self.len())
+            .finish()
+    }
+}
+
+/// Append cookies to existing `Cookie` header
+fn merge(headers: &HeaderMap, value: &str) -> String {
+    match headers.get(COOKIE).and_then(|h| h.to_str().ok()) {
+        Some(existing) if !existing.is_empty() => format!("{}; {}", existing, value),
+        _ => value.to_string(),
+    }
+}
+
+fn is_secure(url: &Uri) -> bool {
+    url.scheme() == Some(&Scheme::HTTPS) || url.scheme_str() == Some("wss")
+}
+
+fn domain_match(host: &str, domain: &str) -> bool {
+    if host == domain {
+        true
+    } else if host.parse::<IpAddr>().is_ok() {
+        false
+    } else {
+        host.len() > domain.len()
+            && host.ends_with(domain)
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+    }
+}
+
+fn path_match(path: &str, cookie_path: &str) -> bool {
+    let path = if path.is_empty() { "/" } else { path };
+    path == cookie_path
+        || (path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/')
+                || path.as_bytes().get(cookie_path.len()) == Some(&b'/')))
+}
+
+fn default_path(path: &str) -> String {
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(pos) => path[..pos].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::RequestHead;
+
+    fn set_cookies(values: &[&'static str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for val in values {
+            headers.append(SET_COOKIE, HeaderValue::from_static(val));
+        }
+        headers
+    }
+
+    fn names(cookies: Vec<Cookie<'static>>) -> Vec<String> {
+        cookies.iter().map(|c| c.name().to_string()).collect()
+    }
+
+    #[test]
+    fn test_matching() {
+        assert!(domain_match("example.com", "example.com"));
+        assert!(domain_match("www.example.com", "example.com"));
+        assert!(!domain_match("wwwexample.com", "example.com"));
+        assert!(!domain_match("127.0.0.1", "0.0.1"));
+        assert!(path_match("/a/b", "/a"));
+        assert!(path_match("/a/b", "/a/"));
+        assert!(!path_match("/ab", "/a"));
+        assert_eq!(default_path("/a/b"), "/a");
+        assert_eq!(default_path("/a"), "/");
+    }
+
+    #[test]
+    fn test_store() {
+        let store = CookieStore::new();
+        let url = Uri::from_static("https://www.example.com/app/login");
+        store.store(
+            &url,
+            &set_cookies(&[
+                "session=1",
+                "domain=2; Domain=example.com; Path=/",
+                "secure=3; Secure; Path=/app",
+                "other=4; Domain=other.com",
+                "expired=5; Max-Age=0",
+            ]),
+        );
+        assert_eq!(store.len(), 3);
+
+        // host-only cookie with default path
+        assert_eq!(
+            names(store.cookies_for(&Uri::from_static("https://www.example.com/app/x"))),
+            vec!["session", "secure", "domain"]
+        );
+        assert_eq!(
+            names(store.cookies_for(&Uri::from_static("https://api.example.com/app"))),
+            vec!["domain"]
+        );
+        assert_eq!(
+            names(store.cookies_for(&Uri::from_static("http://www.example.com/app"))),
+            vec!["session", "domain"]
+        );
+        assert!(store
+            .cookies_for(&Uri::from_static("https://other.com/"))
+            .is_empty());
+
+        // replace and delete cookies
+        store.store(
+            &url,
+            &set_cookies(&[
+                "domain=6; Domain=example.com; Path=/",
+                "session=; Max-Age=0",
+            ]),
+        );
+        let cookies = store.cookies_for(&Uri::from_static("https://example.com/"));
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].value(), "6");
+        assert_eq!(store.len(), 2);
+
+        // secure cookie cannot be set by insecure origin
+        store.store(
+            &Uri::from_static("http://example.com/"),
+            &set_cookies(&["insecure=7; Secure"]),
+        );
+        assert_eq!(store.len(), 2);
+
+        // persist and load
+        let cookies = store.cookies();
+        let store2 = CookieStore::new();
+        for cookie in cookies {
+            assert!(store2.insert(cookie));
+        }
+        assert_eq!(
+            names(store2.cookies_for(&Uri::from_static("https://www.example.com/app/x"))),
+            vec!["secure", "domain"]
+        );
+        assert!(!store2.insert(Cookie::new("name", "value")));
+
+        store.remove("domain");
+        assert_eq!(store.len(), 1);
+        store.clear();
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_apply() {
+        let store = CookieStore::new();
+        let url = Uri::from_static("http://localhost/");
+        store.store(&url, &set_cookies(&["a=1", "b=2"]));
+
+        let mut head = RequestHeadType::Owned(RequestHead {
+            uri: url.clone(),
+            ..Default::default()
+        });
+        store.apply(&mut head);
+        assert_eq!(head.as_ref().headers.get(COOKIE).unwrap(), "a=1; b=2");
+
+        let mut req = RequestHead {
+            uri: url,
+            ..Default::default()
+        };
+        req.headers.insert(COOKIE, HeaderValue::from_static("c=3"));
+        let mut head = RequestHeadType::Rc(Rc::new(req), None);
+        store.apply(&mut head);
+        assert_eq!(
+            head.extra_headers().unwrap().get(COOKIE).unwrap(),
+            "c=3; a=1; b=2"
+        );
+    }
+}
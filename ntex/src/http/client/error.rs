@@ -8,6 +8,7 @@ use thiserror::Error;
 use tls_openssl::ssl::{Error as SslError, HandshakeError};
 
 use crate::http::error::{DecodeError, EncodeError, HttpError, PayloadError};
+use crate::http::StatusCode;
 use crate::util::Either;
 
 /// A set of errors that can occur during parsing json payloads
@@ -53,6 +54,14 @@ pub enum ConnectError {
     #[error("Timeout while establishing connection")]
     Timeout,
 
+    /// Tls handshake took too long
+    #[error("Timeout during tls handshake")]
+    HandshakeTimeout,
+
+    /// Waiting for available connection in pool took too long
+    #[error("Timeout while waiting for available connection")]
+    AcquireTimeout,
+
     /// Connector has been disconnected
     #[error("Connector has been disconnected")]
     Disconnected(Option<io::Error>),
@@ -60,6 +69,10 @@ pub enum ConnectError {
     /// Unresolved host name
     #[error("Connector received `Connect` method with unresolved host")]
     Unresolved,
+
+    /// Proxy refused to open tunnel
+    #[error("Proxy tunnel error: {0}")]
+    ProxyTunnel(StatusCode),
 }
 
 impl Clone for ConnectError {
@@ -77,6 +90,8 @@ impl Clone for ConnectError {
             }
             ConnectError::NoRecords => ConnectError::NoRecords,
             ConnectError::Timeout => ConnectError::Timeout,
+            ConnectError::HandshakeTimeout => ConnectError::HandshakeTimeout,
+            ConnectError::AcquireTimeout => ConnectError::AcquireTimeout,
             ConnectError::Disconnected(e) => {
                 if let Some(e) = e {
                     ConnectError::Disconnected(Some(io::Error::new(
@@ -88,10 +103,46 @@ impl Clone for ConnectError {
                 }
             }
             ConnectError::Unresolved => ConnectError::Unresolved,
+            ConnectError::ProxyTunnel(status) => ConnectError::ProxyTunnel(*status),
         }
     }
 }
 
+impl ConnectError {
+    /// Check if error is caused by tls
+    pub fn is_tls(&self) -> bool {
+        match self {
+            ConnectError::SslIsNotSupported | ConnectError::HandshakeTimeout => true,
+            #[cfg(feature = "openssl")]
+            ConnectError::SslError(_) | ConnectError::SslHandshakeError(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Check if error is caused by timeout
+    pub fn is_timeout(&self) -> bool {
+        matches!(
+            self,
+            ConnectError::Timeout
+                | ConnectError::HandshakeTimeout
+                | ConnectError::AcquireTimeout
+        )
+    }
+
+    /// Check if connecting could succeed on next attempt
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ConnectError::Resolver(_)
+                | ConnectError::NoRecords
+                | ConnectError::Timeout
+                | ConnectError::HandshakeTimeout
+                | ConnectError::AcquireTimeout
+                | ConnectError::Disconnected(_)
+        )
+    }
+}
+
 #[cfg(feature = "openssl")]
 impl From<SslError> for ConnectError {
     fn from(err: SslError) -> Self {
@@ -157,14 +208,133 @@ pub enum SendRequestError {
     /// Response took too long
     #[error("Timeout while waiting for response")]
     Timeout,
+    /// Sending request took too long
+    #[error("Timeout while sending request")]
+    WriteTimeout,
+    /// Request deadline is exceeded
+    #[error("Request deadline is exceeded")]
+    DeadlineExceeded,
     /// Tunnels are not supported for http2 connection
     #[error("Tunnels are not supported for http2 connection")]
     TunnelNotSupported,
+    /// Max number of redirects is reached
+    #[error("Max number of redirects is reached")]
+    TooManyRedirects,
     /// Error sending request body
     #[error("Error sending request body {0}")]
     Error(#[from] Box<dyn Error>),
 }
 
+impl SendRequestError {
+    /// Get error kind
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            SendRequestError::Url(_)
+            | SendRequestError::Http(_)
+            | SendRequestError::Request(_) => ErrorKind::Request,
+            SendRequestError::Connect(ConnectError::Timeout) => {
+                ErrorKind::Timeout(TimeoutPhase::Connect)
+            }
+            SendRequestError::Connect(ConnectError::HandshakeTimeout) => {
+                ErrorKind::Timeout(TimeoutPhase::Handshake)
+            }
+            SendRequestError::Connect(ConnectError::AcquireTimeout) => {
+                ErrorKind::Timeout(TimeoutPhase::Acquire)
+            }
+            SendRequestError::Connect(ConnectError::ProxyTunnel(_)) => ErrorKind::Status,
+            SendRequestError::Connect(e) if e.is_tls() => ErrorKind::Tls,
+            SendRequestError::Connect(_) => ErrorKind::Connect,
+            SendRequestError::Send(_) => ErrorKind::Io,
+            SendRequestError::Response(_)
+            | SendRequestError::H2(_)
+            | SendRequestError::TunnelNotSupported => ErrorKind::Protocol,
+            SendRequestError::WriteTimeout => ErrorKind::Timeout(TimeoutPhase::Write),
+            SendRequestError::Timeout => ErrorKind::Timeout(TimeoutPhase::FirstByte),
+            SendRequestError::DeadlineExceeded => {
+                ErrorKind::Timeout(TimeoutPhase::Deadline)
+            }
+            SendRequestError::TooManyRedirects => ErrorKind::Status,
+            SendRequestError::Error(_) => ErrorKind::Body,
+        }
+    }
+
+    /// Check if error occurred while connecting to the host
+    pub fn is_connect(&self) -> bool {
+        matches!(self, SendRequestError::Connect(_))
+    }
+
+    /// Check if error is caused by timeout
+    pub fn is_timeout(&self) -> bool {
+        self.timeout_phase().is_some()
+    }
+
+    /// Check if request could be re-sent.
+    ///
+    /// Request is retryable if connection to the host could not be
+    /// established, or if connection was closed by peer while request
+    /// was sent. Request method is not checked, retrying is safe only
+    /// for idempotent methods.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SendRequestError::Connect(e) => e.is_retryable(),
+            SendRequestError::Send(e) => matches!(
+                e.kind(),
+                io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::UnexpectedEof
+            ),
+            _ => false,
+        }
+    }
+
+    /// Get request phase that timed out, if error is caused by timeout
+    pub fn timeout_phase(&self) -> Option<TimeoutPhase> {
+        match self.kind() {
+            ErrorKind::Timeout(phase) => Some(phase),
+            _ => None,
+        }
+    }
+}
+
+/// Kind of request error
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Invalid url or request could not be built or encoded
+    Request,
+    /// Failed to connect to host
+    Connect,
+    /// Tls error
+    Tls,
+    /// Timeout in specific phase of request processing
+    Timeout(TimeoutPhase),
+    /// Io error while request was sent or response was read
+    Io,
+    /// Invalid response or http2 protocol error
+    Protocol,
+    /// Error while streaming request body
+    Body,
+    /// Unexpected status, proxy rejected tunnel or too many redirects
+    Status,
+}
+
+/// Phase of request processing that timed out
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimeoutPhase {
+    /// Waiting for available connection in pool
+    Acquire,
+    /// Connecting to remote host, including dns resolution
+    Connect,
+    /// Tls handshake
+    Handshake,
+    /// Sending request head and body
+    Write,
+    /// Waiting for response head
+    FirstByte,
+    /// Total request deadline, including redirects and retries
+    Deadline,
+}
+
 impl From<Either<EncodeError, io::Error>> for SendRequestError {
     fn from(err: Either<EncodeError, io::Error>) -> Self {
         match err {
@@ -202,3 +372,52 @@ impl From<FreezeRequestError> for SendRequestError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind() {
+        let err = SendRequestError::from(ConnectError::Timeout);
+        assert_eq!(err.kind(), ErrorKind::Timeout(TimeoutPhase::Connect));
+        assert!(err.is_connect());
+        assert!(err.is_timeout());
+        assert!(err.is_retryable());
+
+        let err = SendRequestError::from(ConnectError::SslIsNotSupported);
+        assert_eq!(err.kind(), ErrorKind::Tls);
+        assert!(err.is_connect());
+        assert!(!err.is_retryable());
+
+        let err = SendRequestError::from(ConnectError::Disconnected(None));
+        assert_eq!(err.kind(), ErrorKind::Connect);
+        assert!(err.is_retryable());
+
+        let err = SendRequestError::from(ConnectError::ProxyTunnel(StatusCode::FORBIDDEN));
+        assert_eq!(err.kind(), ErrorKind::Status);
+        assert!(!err.is_retryable());
+
+        let err = SendRequestError::from(InvalidUrl::MissingHost);
+        assert_eq!(err.kind(), ErrorKind::Request);
+        assert!(!err.is_connect());
+
+        let err = SendRequestError::from(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert_eq!(err.kind(), ErrorKind::Io);
+        assert!(err.is_retryable());
+        let err = SendRequestError::from(io::Error::from(io::ErrorKind::InvalidData));
+        assert!(!err.is_retryable());
+
+        let err = SendRequestError::from(DecodeError::Status);
+        assert_eq!(err.kind(), ErrorKind::Protocol);
+
+        let err = SendRequestError::Timeout;
+        assert_eq!(err.timeout_phase(), Some(TimeoutPhase::FirstByte));
+        assert!(!err.is_retryable());
+        assert_eq!(SendRequestError::TooManyRedirects.kind(), ErrorKind::Status);
+        assert_eq!(
+            SendRequestError::from(Box::<dyn Error>::from("err")).kind(),
+            ErrorKind::Body
+        );
+    }
+}
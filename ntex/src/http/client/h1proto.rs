@@ -1,13 +1,15 @@
 use std::{
-    future::poll_fn, io, io::Write, pin::Pin, task::Context, task::Poll, time::Instant,
+    future::poll_fn, io, io::Write, pin::Pin, rc::Rc, task::Context, task::Poll,
+    time::Instant,
 };
 
 use crate::http::body::{BodySize, MessageBody};
 use crate::http::error::PayloadError;
 use crate::http::h1;
-use crate::http::header::{HeaderMap, HeaderValue, HOST};
+use crate::http::header::{HeaderMap, HeaderValue, EXPECT, HOST};
 use crate::http::message::{RequestHeadType, ResponseHead};
 use crate::http::payload::{Payload, PayloadStream};
+use crate::http::StatusCode;
 use crate::io::{IoBoxed, RecvError};
 use crate::time::{timeout_checked, Millis};
 use crate::util::{ready, BufMut, Bytes, BytesMut, Stream};
@@ -16,13 +18,23 @@ use super::connection::{Connection, ConnectionType};
 use super::error::{ConnectError, SendRequestError};
 use super::pool::Acquired;
 
+#[derive(Clone)]
+/// Informational responses callback, stored in request extensions
+pub(super) struct InformationalHook(pub(super) Rc<dyn Fn(&ResponseHead)>);
+
+/// Connection io of switching protocols response, stored in response extensions
+pub(super) struct UpgradedIo(pub(super) IoBoxed);
+
 pub(super) async fn send_request<B>(
     io: IoBoxed,
     mut head: RequestHeadType,
     body: B,
     created: Instant,
     timeout: Millis,
-    pool: Option<Acquired>,
+    write_timeout: Millis,
+    expect_timeout: Millis,
+    absolute: bool,
+    mut pool: Option<Acquired>,
 ) -> Result<(ResponseHead, Payload), SendRequestError>
 where
     B: MessageBody,
@@ -60,40 +72,89 @@ where
         body.size()
     );
 
+    let expect = !expect_timeout.is_zero() && expect_continue(&head);
+    let hook = head
+        .as_ref()
+        .extensions()
+        .get::<InformationalHook>()
+        .cloned();
+
     // send request
     let codec = h1::ClientCodec::default();
-    io.send((head, body.size()).into(), &codec).await?;
+    if absolute {
+        codec.set_absolute_form();
+    }
+    timeout_checked(write_timeout, io.send((head, body.size()).into(), &codec))
+        .await
+        .map_err(|_| SendRequestError::WriteTimeout)??;
 
     log::trace!("http1 request has been sent");
 
     // send request body
+    let mut response = None;
     match body.size() {
         BodySize::None | BodySize::Empty | BodySize::Sized(0) => (),
         _ => {
-            send_body(body, &io, &codec).await?;
+            if expect {
+                // wait for `100 Continue` before sending body, if server does not
+                // respond within expect timeout send body anyway
+                log::trace!("waiting for 100-continue response");
+                let fut = async {
+                    loop {
+                        let head = recv_head(&io, &codec).await?;
+                        if !informational(&head, &hook)
+                            || head.status == StatusCode::CONTINUE
+                        {
+                            break Ok::<_, SendRequestError>(head);
+                        }
+                    }
+                };
+                if let Ok(res) = timeout_checked(expect_timeout, fut).await {
+                    let res = res?;
+                    if res.status != StatusCode::CONTINUE {
+                        log::trace!("request is rejected before body is sent: {:?}", res);
+                        response = Some(res);
+                    }
+                }
+            }
+            if response.is_none() {
+                timeout_checked(write_timeout, send_body(body, &io, &codec))
+                    .await
+                    .map_err(|_| SendRequestError::WriteTimeout)??;
+            } else {
+                // request body is not sent, connection can not be reused
+                pool.take();
+            }
         }
     };
 
     log::trace!("reading http1 response");
 
     // read response and init read body
-    let fut = async {
-        if let Some(result) = io.recv(&codec).await? {
-            log::trace!(
-                "http1 response is received, type: {:?}, response: {:#?}",
-                codec.message_type(),
-                result
-            );
-            Ok(result)
-        } else {
-            Err(SendRequestError::from(ConnectError::Disconnected(None)))
-        }
+    let head = if let Some(head) = response {
+        head
+    } else {
+        let fut = async {
+            loop {
+                let head = recv_head(&io, &codec).await?;
+                // skip interim response
+                if !informational(&head, &hook) {
+                    break Ok::<_, SendRequestError>(head);
+                }
+            }
+        };
+        timeout_checked(timeout, fut)
+            .await
+            .map_err(|_| SendRequestError::Timeout)
+            .and_then(|res| res)?
     };
 
-    let head = timeout_checked(timeout, fut)
-        .await
-        .map_err(|_| SendRequestError::Timeout)
-        .and_then(|res| res)?;
+    if head.status == StatusCode::SWITCHING_PROTOCOLS {
+        // connection is not http anymore, hand io over to the caller
+        log::trace!("http1 connection is upgraded");
+        head.extensions_mut().insert(UpgradedIo(io));
+        return Ok((head, Payload::None));
+    }
 
     match codec.message_type() {
         h1::MessageType::None => {
@@ -107,6 +168,47 @@ where
     }
 }
 
+/// Check if request contains `Expect: 100-continue` header
+fn expect_continue(head: &RequestHeadType) -> bool {
+    let is_continue = |hdrs: &HeaderMap| {
+        hdrs.get(EXPECT)
+            .map(|val| val.as_bytes().eq_ignore_ascii_case(b"100-continue"))
+            .unwrap_or(false)
+    };
+    is_continue(&head.as_ref().headers)
+        || head.extra_headers().map(is_continue).unwrap_or(false)
+}
+
+/// Check if response is informational, informational responses are passed to the hook
+fn informational(head: &ResponseHead, hook: &Option<InformationalHook>) -> bool {
+    if head.status.is_informational() && head.status != StatusCode::SWITCHING_PROTOCOLS {
+        log::trace!("informational response is received: {:?}", head.status);
+        if let Some(ref hook) = hook {
+            (hook.0)(head);
+        }
+        true
+    } else {
+        false
+    }
+}
+
+/// read response head
+async fn recv_head(
+    io: &IoBoxed,
+    codec: &h1::ClientCodec,
+) -> Result<ResponseHead, SendRequestError> {
+    if let Some(result) = io.recv(codec).await? {
+        log::trace!(
+            "http1 response is received, type: {:?}, response: {:#?}",
+            codec.message_type(),
+            result
+        );
+        Ok(result)
+    } else {
+        Err(SendRequestError::from(ConnectError::Disconnected(None)))
+    }
+}
+
 /// send request body to the peer
 pub(super) async fn send_body<B>(
     mut body: B,
@@ -1,4 +1,4 @@
-use std::{future::poll_fn, io};
+use std::{cell::Cell, fmt, future::poll_fn, io, rc::Rc};
 
 use ntex_h2::client::{RecvStream, SimpleClient};
 use ntex_h2::{self as h2, frame};
@@ -11,12 +11,15 @@ use crate::time::{timeout_checked, Millis};
 use crate::util::{ByteString, Bytes};
 
 use super::error::{ConnectError, SendRequestError};
+use super::pool::Acquired;
 
 pub(super) async fn send_request<B>(
     client: H2Client,
     head: RequestHeadType,
     body: B,
     timeout: Millis,
+    write_timeout: Millis,
+    pool: Option<Acquired>,
 ) -> Result<(ResponseHead, Payload), SendRequestError>
 where
     B: MessageBody,
@@ -71,24 +74,35 @@ where
         .path_and_query()
         .map(|p| ByteString::from(format!("{}", p)))
         .unwrap_or_else(|| ByteString::from(uri.path()));
-    let (snd_stream, rcv_stream) = client
-        .client
-        .send(head.as_ref().method.clone(), path, hdrs, eof)
-        .await?;
+    let (snd_stream, rcv_stream) = timeout_checked(
+        write_timeout,
+        client
+            .client
+            .send(head.as_ref().method.clone(), path, hdrs, eof),
+    )
+    .await
+    .map_err(|_| SendRequestError::WriteTimeout)??;
 
     // send body
     if !eof {
         // sending body is async process, we can handle upload and download
         // at the same time
         crate::rt::spawn(async move {
-            if let Err(e) = send_body(body, &snd_stream).await {
-                log::error!("Cannot send body: {:?}", e);
-                snd_stream.reset(frame::Reason::INTERNAL_ERROR);
+            match timeout_checked(write_timeout, send_body(body, &snd_stream)).await {
+                Ok(Ok(())) => (),
+                Ok(Err(e)) => {
+                    log::error!("Cannot send body: {:?}", e);
+                    snd_stream.reset(frame::Reason::INTERNAL_ERROR);
+                }
+                Err(_) => {
+                    log::debug!("Timeout while sending body");
+                    snd_stream.reset(frame::Reason::CANCEL);
+                }
             }
         });
     }
 
-    timeout_checked(timeout, get_response(rcv_stream))
+    timeout_checked(timeout, get_response(rcv_stream, pool))
         .await
         .map_err(|_| SendRequestError::Timeout)
         .and_then(|res| res)
@@ -96,6 +110,7 @@ where
 
 async fn get_response(
     rcv_stream: RecvStream,
+    pool: Option<Acquired>,
 ) -> Result<(ResponseHead, Payload), SendRequestError> {
     let h2::Message { stream, kind } = rcv_stream
         .recv()
@@ -126,6 +141,8 @@ async fn get_response(
                         let (mut pl, payload) =
                             payload::Payload::create(stream.empty_capacity());
                         crate::rt::spawn(async move {
+                            // stream is active until payload is consumed
+                            let _pool = pool;
                             loop {
                                 let h2::Message { stream, kind } =
                                     match rcv_stream.recv().await {
@@ -223,11 +240,28 @@ async fn send_body<B: MessageBody>(
 #[derive(Clone)]
 pub(super) struct H2Client {
     client: SimpleClient,
+    streams: Rc<Cell<usize>>,
 }
 
 impl H2Client {
     pub(super) fn new(client: SimpleClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            streams: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// Number of active streams
+    pub(super) fn streams(&self) -> usize {
+        self.streams.get()
+    }
+
+    pub(super) fn stream_opened(&self) {
+        self.streams.set(self.streams.get() + 1)
+    }
+
+    pub(super) fn stream_closed(&self) {
+        self.streams.set(self.streams.get() - 1)
     }
 
     pub(super) fn close(&self) {
@@ -238,3 +272,12 @@ impl H2Client {
         self.client.is_closed()
     }
 }
+
+impl fmt::Debug for H2Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("H2Client")
+            .field("streams", &self.streams.get())
+            .field("closed", &self.client.is_closed())
+            .finish()
+    }
+}
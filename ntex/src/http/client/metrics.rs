@@ -0,0 +1,187 @@
+use std::{cell::Cell, cell::RefCell, fmt, rc::Rc, time::Duration};
+
+use crate::http::uri::Authority;
+
+/// Connection pool metrics.
+///
+/// Metrics collects counters for all connections acquired from the pool
+/// and optionally reports every acquired connection to a hook.
+///
+/// Metrics object is cheap to clone, all clones share the same counters.
+///
+/// ```rust
+/// use ntex::http::client::{Client, Connector, PoolMetrics};
+///
+/// let metrics = PoolMetrics::new().on_acquire(|info| {
+///     println!("{}: reused: {}, wait: {:?}", info.authority, info.reused, info.wait);
+/// });
+/// let client = Client::build()
+///     .connector(Connector::default().metrics(metrics.clone()).finish())
+///     .finish();
+/// ```
+#[derive(Clone, Default)]
+pub struct PoolMetrics(Rc<Inner>);
+
+#[derive(Default)]
+struct Inner {
+    acquired: Cell<u64>,
+    reused: Cell<u64>,
+    waits: Cell<u64>,
+    wait_time: Cell<Duration>,
+    hook: RefCell<Option<Box<dyn Fn(&AcquireInfo)>>>,
+}
+
+/// Information about acquired connection
+#[derive(Clone, Debug)]
+pub struct AcquireInfo {
+    /// Remote host
+    pub authority: Authority,
+    /// Existing connection is used
+    pub reused: bool,
+    /// Pool was full and request had to wait for available connection
+    pub waited: bool,
+    /// Time spent waiting for available connection, excluding connect time
+    pub wait: Duration,
+    /// Connect timings, available only for new connections
+    pub timings: Option<ConnectTimings>,
+}
+
+/// Timings of new connection
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnectTimings {
+    /// Dns name resolution, not available for custom connectors
+    pub dns: Option<Duration>,
+    /// Tcp connect. For custom connectors, time of whole connect process
+    pub connect: Duration,
+    /// Tls handshake, not available for plain connections and custom
+    /// connectors
+    pub tls: Option<Duration>,
+}
+
+impl ConnectTimings {
+    /// Total connect time
+    pub fn total(&self) -> Duration {
+        self.dns.unwrap_or_default() + self.connect + self.tls.unwrap_or_default()
+    }
+}
+
+impl PoolMetrics {
+    /// Create new metrics object
+    pub fn new() -> Self {
+        PoolMetrics::default()
+    }
+
+    /// Set hook, it is called for every acquired connection
+    pub fn on_acquire<F>(self, f: F) -> Self
+    where
+        F: Fn(&AcquireInfo) + 'static,
+    {
+        *self.0.hook.borrow_mut() = Some(Box::new(f));
+        self
+    }
+
+    /// Number of acquired connections
+    pub fn acquired(&self) -> u64 {
+        self.0.acquired.get()
+    }
+
+    /// Number of acquired connections that reused existing connection
+    pub fn reused(&self) -> u64 {
+        self.0.reused.get()
+    }
+
+    /// Number of new connections
+    pub fn opened(&self) -> u64 {
+        self.0.acquired.get() - self.0.reused.get()
+    }
+
+    /// Ratio of reused connections to all acquired connections
+    pub fn reuse_ratio(&self) -> f64 {
+        let acquired = self.0.acquired.get();
+        if acquired == 0 {
+            0.0
+        } else {
+            self.0.reused.get() as f64 / acquired as f64
+        }
+    }
+
+    /// Number of requests that had to wait for available connection
+    pub fn waits(&self) -> u64 {
+        self.0.waits.get()
+    }
+
+    /// Total time spent waiting for available connections
+    pub fn wait_time(&self) -> Duration {
+        self.0.wait_time.get()
+    }
+
+    pub(super) fn record(&self, info: AcquireInfo) {
+        let inner = &self.0;
+        inner.acquired.set(inner.acquired.get() + 1);
+        if info.reused {
+            inner.reused.set(inner.reused.get() + 1);
+        }
+        if info.waited {
+            inner.waits.set(inner.waits.get() + 1);
+            inner.wait_time.set(inner.wait_time.get() + info.wait);
+        }
+        if let Some(ref hook) = *inner.hook.borrow() {
+            (*hook)(&info);
+        }
+    }
+}
+
+impl fmt::Debug for PoolMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoolMetrics")
+            .field("acquired", &self.acquired())
+            .field("reused", &self.reused())
+            .field("waits", &self.waits())
+            .field("wait_time", &self.wait_time())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let reported = Rc::new(Cell::new(0));
+        let reported2 = reported.clone();
+        let metrics = PoolMetrics::new().on_acquire(move |_| {
+            reported2.set(reported2.get() + 1);
+        });
+        assert_eq!(metrics.reuse_ratio(), 0.0);
+
+        let info = AcquireInfo {
+            authority: Authority::from_static("localhost"),
+            reused: false,
+            waited: true,
+            wait: Duration::from_millis(10),
+            timings: Some(ConnectTimings {
+                dns: Some(Duration::from_millis(1)),
+                connect: Duration::from_millis(2),
+                tls: None,
+            }),
+        };
+        assert_eq!(info.timings.unwrap().total(), Duration::from_millis(3));
+        metrics.record(info.clone());
+        metrics.clone().record(AcquireInfo {
+            reused: true,
+            waited: false,
+            timings: None,
+            ..info
+        });
+
+        assert_eq!(reported.get(), 2);
+        assert_eq!(metrics.acquired(), 2);
+        assert_eq!(metrics.reused(), 1);
+        assert_eq!(metrics.opened(), 1);
+        assert_eq!(metrics.reuse_ratio(), 0.5);
+        assert_eq!(metrics.waits(), 1);
+        assert_eq!(metrics.wait_time(), Duration::from_millis(10));
+        assert!(format!("{:?}", metrics).contains("PoolMetrics"));
+    }
+}
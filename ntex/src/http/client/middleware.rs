@@ -0,0 +1,153 @@
+use std::{fmt, net, rc::Rc};
+
+use crate::http::header::{HeaderName, HeaderValue};
+use crate::http::{body::Body, RequestHead, RequestHeadType};
+use crate::service::{boxed, boxed::BoxService, Pipeline, Service, ServiceCtx};
+use crate::{time::Millis, util::BoxFuture};
+
+use super::connect::Connect;
+use super::error::SendRequestError;
+#[cfg(feature = "digest-auth")]
+use super::redirect::Replay;
+use super::{ClientConfig, ClientResponse};
+
+/// Boxed client service, middlewares wrap this service.
+pub type ClientService = BoxService<ClientMessage, ClientResponse, SendRequestError>;
+
+pub(super) type Layer = Box<dyn Fn(ClientService) -> ClientService>;
+
+/// Client request passed through client middlewares.
+///
+/// Message contains request head and body, middlewares could modify
+/// request before it is sent.
+pub struct ClientMessage {
+    head: RequestHeadType,
+    body: Body,
+    addr: Option<net::SocketAddr>,
+    timeout: Millis,
+    pub(super) cfg: Rc<ClientConfig>,
+}
+
+impl ClientMessage {
+    /// Get request head
+    pub fn head(&self) -> &RequestHead {
+        self.head.as_ref()
+    }
+
+    /// Get request head and extra headers
+    pub fn head_type(&self) -> &RequestHeadType {
+        &self.head
+    }
+
+    /// Get request's body
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+
+    /// Replace request's body
+    pub fn set_body<B: Into<Body>>(&mut self, body: B) {
+        self.body = body.into();
+    }
+
+    /// Get socket address of the server, if set
+    pub fn addr(&self) -> Option<net::SocketAddr> {
+        self.addr
+    }
+
+    /// Get request timeout
+    pub fn timeout(&self) -> Millis {
+        self.timeout
+    }
+
+    /// Set request timeout
+    pub fn set_timeout<T: Into<Millis>>(&mut self, timeout: T) {
+        self.timeout = timeout.into();
+    }
+
+    /// Insert a header, replaces any that were set with an equivalent field name.
+    pub fn set_header(&mut self, key: HeaderName, value: HeaderValue) {
+        self.head.set_header(key, value);
+    }
+
+    /// Copy message for re-sending, streaming body could not be copied
+    #[cfg(feature = "digest-auth")]
+    pub(super) fn try_clone(&self) -> Option<ClientMessage> {
+        Replay::new(&self.head, &self.body)
+            .retry()
+            .map(|(head, body)| ClientMessage {
+                body,
+                head: head.into(),
+                addr: self.addr,
+                timeout: self.timeout,
+                cfg: self.cfg.clone(),
+            })
+    }
+}
+
+impl fmt::Debug for ClientMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientMessage")
+            .field("head", &self.head)
+            .field("body", &self.body)
+            .field("addr", &self.addr)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// Innermost client service, sends request with client connector
+struct SendService(Box<dyn Connect>);
+
+impl Service<ClientMessage> for SendService {
+    type Response = ClientResponse;
+    type Error = SendRequestError;
+
+    async fn call(
+        &self,
+        msg: ClientMessage,
+        _: ServiceCtx<'_, Self>,
+    ) -> Result<ClientResponse, SendRequestError> {
+        self.0
+            .send_request(msg.head, msg.body, msg.addr, msg.timeout, msg.cfg)
+            .await
+    }
+}
+
+/// Connector wrapped with middlewares
+pub(super) struct MiddlewareWrapper(Pipeline<ClientService>);
+
+impl MiddlewareWrapper {
+    pub(super) fn new(connector: Box<dyn Connect>, layers: Vec<Layer>) -> Self {
+        let svc = layers
+            .iter()
+            .fold(boxed::service(SendService(connector)), |svc, layer| {
+                (*layer)(svc)
+            });
+        MiddlewareWrapper(Pipeline::new(svc))
+    }
+}
+
+impl fmt::Debug for MiddlewareWrapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MiddlewareWrapper").finish()
+    }
+}
+
+impl Connect for MiddlewareWrapper {
+    fn send_request(
+        &self,
+        head: RequestHeadType,
+        body: Body,
+        addr: Option<net::SocketAddr>,
+        timeout: Millis,
+        cfg: Rc<ClientConfig>,
+    ) -> BoxFuture<'_, Result<ClientResponse, SendRequestError>> {
+        Box::pin(self.0.call(ClientMessage {
+            head,
+            body,
+            addr,
+            timeout,
+            cfg,
+        }))
+    }
+}
@@ -18,26 +18,51 @@
 //! ```
 use std::rc::Rc;
 
+#[cfg(feature = "digest-auth")]
+mod auth;
 mod builder;
+mod cache;
 mod connect;
 mod connection;
 mod connector;
+#[cfg(feature = "cookie")]
+mod cookies;
 pub mod error;
 mod frozen;
 mod h1proto;
 mod h2proto;
+mod metrics;
+mod middleware;
+mod multipart;
 mod pool;
+mod progress;
+mod proxy;
+mod redirect;
 mod request;
 mod response;
+mod retry;
 mod sender;
 mod test;
 
+#[cfg(feature = "digest-auth")]
+pub use self::auth::{DigestAuth, DigestAuthMiddleware};
 pub use self::builder::ClientBuilder;
+pub use self::cache::{
+    CacheStore, CachedResponse, ClientCache, ClientCacheMiddleware, DiskStore, MemoryStore,
+};
 pub use self::connection::Connection;
 pub use self::connector::Connector;
+#[cfg(feature = "cookie")]
+pub use self::cookies::CookieStore;
 pub use self::frozen::{FrozenClientRequest, FrozenSendBuilder};
+pub use self::metrics::{AcquireInfo, ConnectTimings, PoolMetrics};
+pub use self::middleware::{ClientMessage, ClientService};
+pub use self::multipart::ClientMultipart;
+pub use self::progress::Progress;
+pub use self::proxy::Proxy;
 pub use self::request::ClientRequest;
-pub use self::response::{ClientResponse, JsonBody, MessageBody};
+pub use self::response::{ClientResponse, JsonBody, MessageBody, NdJsonStream};
+pub use self::retry::RetryPolicy;
 pub use self::sender::SendClientRequest;
 pub use self::test::TestResponse;
 
@@ -45,12 +70,15 @@ use crate::http::error::HttpError;
 use crate::http::{HeaderMap, Method, RequestHead, Uri};
 use crate::time::Millis;
 
-use self::connect::{Connect as HttpConnect, ConnectorWrapper};
+use self::connect::{Connect as HttpConnect, ConnectorWrapper, LocalBind};
 
 #[derive(Debug, Clone)]
 pub struct Connect {
     pub uri: Uri,
     pub addr: Option<std::net::SocketAddr>,
+    pub proxy: Option<Proxy>,
+    pub local_addr: Option<std::net::IpAddr>,
+    pub interface: Option<crate::util::ByteString>,
 }
 
 /// An HTTP Client
@@ -80,6 +108,18 @@ pub(crate) struct ClientConfig {
     pub(self) timeout: Millis,
     pub(self) response_pl_limit: usize,
     pub(self) response_pl_timeout: Millis,
+    pub(self) response_decompress: bool,
+    pub(self) decompress_limit: usize,
+    pub(self) decompress_ratio: usize,
+    pub(self) expect_timeout: Millis,
+    pub(self) write_timeout: Millis,
+    pub(self) deadline: Millis,
+    pub(self) max_redirects: usize,
+    pub(self) retry: Option<RetryPolicy>,
+    pub(self) proxies: Vec<Proxy>,
+    pub(self) bind: LocalBind,
+    #[cfg(feature = "cookie")]
+    pub(self) cookies: Option<CookieStore>,
 }
 
 impl Default for ClientConfig {
@@ -89,6 +129,18 @@ impl Default for ClientConfig {
             timeout: Millis(5_000),
             response_pl_limit: 262_144,
             response_pl_timeout: Millis(10_000),
+            response_decompress: true,
+            decompress_limit: 0,
+            decompress_ratio: 0,
+            expect_timeout: Millis(1_000),
+            write_timeout: Millis::ZERO,
+            deadline: Millis::ZERO,
+            max_redirects: 10,
+            retry: None,
+            proxies: Vec::new(),
+            bind: LocalBind::default(),
+            #[cfg(feature = "cookie")]
+            cookies: None,
             connector: Box::new(ConnectorWrapper(Connector::default().finish().into())),
         }
     }
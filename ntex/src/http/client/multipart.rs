@@ -0,0 +1,349 @@
+use std::{collections::VecDeque, error::Error, fmt, pin::Pin, task::Context, task::Poll};
+
+use nanorand::{Rng, WyRand};
+
+use crate::http::body::{BodySize, MessageBody};
+use crate::util::{Bytes, BytesMut, Stream};
+
+type BoxedStream = Pin<Box<dyn Stream<Item = Result<Bytes, Box<dyn Error>>>>>;
+
+/// `multipart/form-data` request body builder.
+///
+/// Parts are sent in the order they were added. Stream parts are polled
+/// only when body is sent, so large files are never buffered in memory.
+///
+/// ```rust
+/// use ntex::http::client::{Client, ClientMultipart};
+/// use ntex::util::Bytes;
+///
+/// #[ntex::main]
+/// async fn main() {
+///     let body = ClientMultipart::new()
+///         .text("name", "report")
+///         .file("file", "report.txt", mime::TEXT_PLAIN, Bytes::from_static(b"data"));
+///
+///     let res = Client::new()
+///         .post("http://www.rust-lang.org")
+///         .send_multipart(body)
+///         .await;
+/// }
+/// ```
+pub struct ClientMultipart {
+    boundary: String,
+    parts: VecDeque<Part>,
+    current: Option<BoxedStream>,
+    eof: bool,
+}
+
+struct Part {
+    headers: Bytes,
+    body: PartBody,
+}
+
+enum PartBody {
+    Bytes(Bytes),
+    Stream(BoxedStream, Option<u64>),
+}
+
+impl Default for ClientMultipart {
+    fn default() -> Self {
+        ClientMultipart::new()
+    }
+}
+
+impl ClientMultipart {
+    /// Create multipart body with random boundary.
+    pub fn new() -> Self {
+        const CHARS: &[u8] =
+            b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+        let mut rng = WyRand::new();
+        let boundary = (0..32)
+            .map(|_| CHARS[rng.generate_range(0..CHARS.len())] as char)
+            .collect();
+        ClientMultipart::with_boundary(boundary)
+    }
+
+    /// Create multipart body with specified boundary.
+    pub fn with_boundary<T: Into<String>>(boundary: T) -> Self {
+        ClientMultipart {
+            boundary: boundary.into(),
+            parts: VecDeque::new(),
+            current: None,
+            eof: false,
+        }
+    }
+
+    /// Get boundary
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Get value for `Content-Type` header
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Add text field.
+    pub fn text<V: Into<String>>(mut self, name: &str, value: V) -> Self {
+        let headers = self.part_headers(name, None, None);
+        self.parts.push_back(Part {
+            headers,
+            body: PartBody::Bytes(Bytes::from(value.into())),
+        });
+        self
+    }
+
+    /// Add file from memory.
+    pub fn file<B: Into<Bytes>>(
+        mut self,
+        name: &str,
+        filename: &str,
+        content_type: mime::Mime,
+        data: B,
+    ) -> Self {
+        let headers = self.part_headers(name, Some(filename), Some(&content_type));
+        self.parts.push_back(Part {
+            headers,
+            body: PartBody::Bytes(data.into()),
+        });
+        self
+    }
+
+    /// Add file from stream of unknown size.
+    ///
+    /// Request body is sent with chunked transfer encoding.
+    pub fn stream<S, E>(
+        mut self,
+        name: &str,
+        filename: &str,
+        content_type: mime::Mime,
+        stream: S,
+    ) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + 'static,
+        E: Error + 'static,
+    {
+        let headers = self.part_headers(name, Some(filename), Some(&content_type));
+        self.parts.push_back(Part {
+            headers,
+            body: PartBody::Stream(Box::pin(MapErr { stream }), None),
+        });
+        self
+    }
+
+    /// Add file from stream of known size.
+    ///
+    /// If all parts have known size, `Content-Length` header is set for
+    /// request. Stream must produce exactly `size` bytes.
+    pub fn sized_stream<S, E>(
+        mut self,
+        name: &str,
+        filename: &str,
+        content_type: mime::Mime,
+        size: u64,
+        stream: S,
+    ) -> Self
+    where
+        S: Stream<Item = Result<Bytes, E>> + 'static,
+        E: Error + 'static,
+    {
+        let headers = self.part_headers(name, Some(filename), Some(&content_type));
+        self.parts.push_back(Part {
+            headers,
+            body: PartBody::Stream(Box::pin(MapErr { stream }), Some(size)),
+        });
+        self
+    }
+
+    fn part_headers(
+        &self,
+        name: &str,
+        filename: Option<&str>,
+        content_type: Option<&mime::Mime>,
+    ) -> Bytes {
+        let mut buf = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+            self.boundary,
+            escape(name)
+        );
+        if let Some(filename) = filename {
+            buf.push_str(&format!("; filename=\"{}\"", escape(filename)));
+        }
+        buf.push_str("\r\n");
+        if let Some(content_type) = content_type {
+            buf.push_str(&format!("Content-Type: {}\r\n", content_type));
+        }
+        buf.push_str("\r\n");
+        Bytes::from(buf)
+    }
+
+    fn closing(&self) -> Bytes {
+        Bytes::from(format!("--{}--\r\n", self.boundary))
+    }
+}
+
+/// Escape quoted parameter value
+fn escape(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+impl MessageBody for ClientMultipart {
+    fn size(&self) -> BodySize {
+        let mut size = self.closing().len() as u64;
+        for part in &self.parts {
+            size += part.headers.len() as u64 + 2;
+            match part.body {
+                PartBody::Bytes(ref b) => size += b.len() as u64,
+                PartBody::Stream(_, Some(len)) => size += len,
+                PartBody::Stream(_, None) => return BodySize::Stream,
+            }
+        }
+        BodySize::Sized(size)
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        loop {
+            if let Some(ref mut stream) = self.current {
+                return match stream.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(Ok(chunk))) => {
+                        if chunk.is_empty() {
+                            continue;
+                        }
+                        Poll::Ready(Some(Ok(chunk)))
+                    }
+                    Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e))),
+                    Poll::Ready(None) => {
+                        self.current = None;
+                        Poll::Ready(Some(Ok(Bytes::from_static(b"\r\n"))))
+                    }
+                    Poll::Pending => Poll::Pending,
+                };
+            }
+
+            return if let Some(part) = self.parts.pop_front() {
+                match part.body {
+                    PartBody::Bytes(data) => {
+                        let mut buf =
+                            BytesMut::with_capacity(part.headers.len() + data.len() + 2);
+                        buf.extend_from_slice(&part.headers);
+                        buf.extend_from_slice(&data);
+                        buf.extend_from_slice(b"\r\n");
+                        Poll::Ready(Some(Ok(buf.freeze())))
+                    }
+                    PartBody::Stream(stream, _) => {
+                        self.current = Some(stream);
+                        Poll::Ready(Some(Ok(part.headers)))
+                    }
+                }
+            } else if !self.eof {
+                self.eof = true;
+                Poll::Ready(Some(Ok(self.closing())))
+            } else {
+                Poll::Ready(None)
+            };
+        }
+    }
+}
+
+impl fmt::Debug for ClientMultipart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientMultipart")
+            .field("boundary", &self.boundary)
+            .field("parts", &self.parts.len())
+            .finish()
+    }
+}
+
+pin_project_lite::pin_project! {
+    struct MapErr<S> {
+        #[pin]
+        stream: S,
+    }
+}
+
+impl<S, E> Stream for MapErr<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: Error + 'static,
+{
+    type Item = Result<Bytes, Box<dyn Error>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project()
+            .stream
+            .poll_next(cx)
+            .map(|item| item.map(|res| res.map_err(|e| Box::new(e) as Box<dyn Error>)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::poll_fn, io};
+
+    use super::*;
+
+    async fn read_body(mut body: ClientMultipart) -> Bytes {
+        let mut buf = BytesMut::new();
+        while let Some(chunk) = poll_fn(|cx| body.poll_next_chunk(cx)).await {
+            buf.extend_from_slice(&chunk.unwrap());
+        }
+        buf.freeze()
+    }
+
+    fn stream(
+        chunks: &[&'static [u8]],
+    ) -> impl Stream<Item = Result<Bytes, io::Error>> + 'static {
+        let (tx, rx) = crate::channel::mpsc::channel();
+        for chunk in chunks {
+            tx.send(Ok(Bytes::from_static(chunk))).unwrap();
+        }
+        rx
+    }
+
+    #[crate::rt_test]
+    async fn test_multipart() {
+        let body = ClientMultipart::with_boundary("abc")
+            .text("na\"me", "value")
+            .file("f", "a.txt", mime::TEXT_PLAIN, "data")
+            .sized_stream(
+                "s",
+                "b.bin",
+                mime::APPLICATION_OCTET_STREAM,
+                6,
+                stream(&[b"123", b"456"]),
+            );
+        assert_eq!(body.content_type(), "multipart/form-data; boundary=abc");
+
+        let expected = "--abc\r\n\
+            Content-Disposition: form-data; name=\"na%22me\"\r\n\r\n\
+            value\r\n\
+            --abc\r\n\
+            Content-Disposition: form-data; name=\"f\"; filename=\"a.txt\"\r\n\
+            Content-Type: text/plain\r\n\r\n\
+            data\r\n\
+            --abc\r\n\
+            Content-Disposition: form-data; name=\"s\"; filename=\"b.bin\"\r\n\
+            Content-Type: application/octet-stream\r\n\r\n\
+            123456\r\n\
+            --abc--\r\n";
+        assert_eq!(body.size(), BodySize::Sized(expected.len() as u64));
+        assert_eq!(read_body(body).await, Bytes::from(expected));
+
+        // unknown size
+        let body =
+            ClientMultipart::new().stream("s", "b", mime::TEXT_PLAIN, stream(&[b"1"]));
+        assert_eq!(body.size(), BodySize::Stream);
+        assert_eq!(body.boundary().len(), 32);
+
+        // empty body
+        let body = ClientMultipart::with_boundary("abc");
+        assert_eq!(read_body(body).await, Bytes::from_static(b"--abc--\r\n"));
+    }
+}
@@ -1,35 +1,56 @@
+use std::rc::Rc;
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
-use std::{cell::RefCell, collections::VecDeque, future::Future, pin::Pin, rc::Rc};
+use std::{cell::RefCell, collections::VecDeque, future::Future, net::IpAddr, pin::Pin};
 
 use ntex_h2::{self as h2};
 
 use crate::http::uri::{Authority, Scheme, Uri};
 use crate::io::{types::HttpProtocol, IoBoxed};
 use crate::service::{Pipeline, PipelineCall, Service, ServiceCtx};
-use crate::time::{now, Seconds};
+use crate::time::{now, timeout_checked, Millis, Seconds};
 use crate::util::{ready, ByteString, HashMap, HashSet};
 use crate::{channel::pool, rt::spawn, task::LocalWaker};
 
 use super::connection::{Connection, ConnectionType};
+use super::metrics::{AcquireInfo, ConnectTimings, PoolMetrics};
 use super::{error::ConnectError, h2proto::H2Client, Connect};
 
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub(super) struct Key {
     authority: Authority,
+    local_addr: Option<IpAddr>,
+    interface: Option<ByteString>,
 }
 
 impl From<Authority> for Key {
     fn from(authority: Authority) -> Key {
-        Key { authority }
+        Key {
+            authority,
+            local_addr: None,
+            interface: None,
+        }
     }
 }
 
-type Waiter = pool::Sender<Result<Connection, ConnectError>>;
-type WaiterReceiver = pool::Receiver<Result<Connection, ConnectError>>;
+impl Key {
+    /// Connections bound to different local addresses are not shared
+    fn new(req: &Connect) -> Option<Key> {
+        req.uri.authority().map(|authority| Key {
+            authority: authority.clone(),
+            local_addr: req.local_addr,
+            interface: req.interface.clone(),
+        })
+    }
+}
+
+type Acquisition = Result<(Connection, Option<ConnectTimings>), ConnectError>;
+type Waiter = pool::Sender<Acquisition>;
+type WaiterReceiver = pool::Receiver<Acquisition>;
 
 enum Acquire {
     Acquired(ConnectionType, Instant),
+    Stream(H2Client, Instant),
     Available,
     NotAvailable,
 }
@@ -41,6 +62,45 @@ struct AvailableConnection {
     created: Instant,
 }
 
+#[derive(Debug)]
+struct H2Connection {
+    client: H2Client,
+    used: Instant,
+    created: Instant,
+}
+
+/// Connections pool configuration
+#[derive(Clone, Debug)]
+pub(super) struct PoolConfig {
+    pub(super) conn_lifetime: Duration,
+    pub(super) conn_keep_alive: Duration,
+    pub(super) disconnect_timeout: Seconds,
+    pub(super) limit: usize,
+    pub(super) limit_per_host: usize,
+    pub(super) max_idle: usize,
+    pub(super) acquire_timeout: Millis,
+    pub(super) h2streams: usize,
+    pub(super) h2config: h2::Config,
+    pub(super) metrics: Option<PoolMetrics>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            conn_lifetime: Duration::from_secs(75),
+            conn_keep_alive: Duration::from_secs(15),
+            disconnect_timeout: Seconds(3),
+            limit: 100,
+            limit_per_host: 0,
+            max_idle: 0,
+            acquire_timeout: Millis::ZERO,
+            h2streams: 100,
+            h2config: h2::Config::client(),
+            metrics: None,
+        }
+    }
+}
+
 /// Connections pool
 #[derive(Debug)]
 pub(super) struct ConnectionPool<T> {
@@ -51,29 +111,32 @@ pub(super) struct ConnectionPool<T> {
 
 impl<T> ConnectionPool<T>
 where
-    T: Service<Connect, Response = IoBoxed, Error = ConnectError> + 'static,
+    T: Service<Connect, Response = (IoBoxed, ConnectTimings), Error = ConnectError>
+        + 'static,
 {
-    pub(super) fn new(
-        connector: T,
-        conn_lifetime: Duration,
-        conn_keep_alive: Duration,
-        disconnect_timeout: Seconds,
-        limit: usize,
-        h2config: h2::Config,
-    ) -> Self {
+    pub(super) fn new(connector: T, config: PoolConfig) -> Self {
         let connector = Pipeline::new(connector);
         let waiters = Rc::new(RefCell::new(Waiters {
             waiters: HashMap::default(),
             pool: pool::new(),
         }));
         let inner = Rc::new(RefCell::new(Inner {
-            conn_lifetime,
-            conn_keep_alive,
-            disconnect_timeout,
-            limit,
-            h2config,
+            conn_lifetime: config.conn_lifetime,
+            conn_keep_alive: config.conn_keep_alive,
+            disconnect_timeout: config.disconnect_timeout,
+            limit: config.limit,
+            limit_per_host: config.limit_per_host,
+            max_idle: config.max_idle,
+            acquire_timeout: config.acquire_timeout,
+            h2streams: config.h2streams,
+            h2config: config.h2config,
+            metrics: config.metrics,
             acquired: 0,
+            hosts: HashMap::default(),
+            opening: 0,
             available: HashMap::default(),
+            http2: HashMap::default(),
+            http1: HashSet::default(),
             connecting: HashSet::default(),
             waker: LocalWaker::new(),
             waiters: waiters.clone(),
@@ -112,7 +175,8 @@ impl<T> Clone for ConnectionPool<T> {
 
 impl<T> Service<Connect> for ConnectionPool<T>
 where
-    T: Service<Connect, Response = IoBoxed, Error = ConnectError> + 'static,
+    T: Service<Connect, Response = (IoBoxed, ConnectTimings), Error = ConnectError>
+        + 'static,
 {
     type Response = Connection;
     type Error = ConnectError;
@@ -129,34 +193,50 @@ where
         let inner = self.inner.clone();
         let waiters = self.waiters.clone();
 
-        let key = if let Some(authority) = req.uri.authority() {
-            authority.clone().into()
+        let key = if let Some(key) = Key::new(&req) {
+            key
         } else {
             return Err(ConnectError::Unresolved);
         };
 
         // acquire connection
+        let start = Instant::now();
+        let mut waited = false;
         let result = inner.borrow_mut().acquire(&key);
-        match result {
+        let (conn, timings) = match result {
             // use existing connection
             Acquire::Acquired(io, created) => {
                 log::trace!("Use existing {:?} connection for {:?}", io, req.uri);
-                Ok(Connection::new(
-                    io,
-                    created,
-                    Some(Acquired::new(key, inner)),
-                ))
+                let acquired = Some(Acquired::new(key.clone(), inner.clone()));
+                (Connection::new(io, created, acquired), None)
+            }
+            // open new stream on existing http/2 connection
+            Acquire::Stream(client, created) => {
+                log::trace!("Use existing http/2 connection for {:?}", req.uri);
+                let acquired =
+                    Some(Acquired::stream(key.clone(), inner.clone(), client.clone()));
+                (
+                    Connection::new(ConnectionType::H2(client), created, acquired),
+                    None,
+                )
             }
             // open new tcp connection
             Acquire::Available => {
                 log::trace!("Connecting to {:?}", req.uri);
                 let uri = req.uri.clone();
                 let (tx, rx) = waiters.borrow_mut().pool.channel();
-                OpenConnection::spawn(key, tx, uri, inner, &self.connector, req);
+                OpenConnection::spawn(
+                    key.clone(),
+                    tx,
+                    uri,
+                    inner.clone(),
+                    &self.connector,
+                    req,
+                );
 
                 match rx.await {
-                    Err(_) => Err(ConnectError::Disconnected(None)),
-                    Ok(res) => res,
+                    Err(_) => return Err(ConnectError::Disconnected(None)),
+                    Ok(res) => res?,
                 }
             }
             // pool is full, wait
@@ -165,13 +245,33 @@ where
                     "Pool is full, waiting for available connections for {:?}",
                     req.uri
                 );
+                waited = true;
+                let timeout = inner.borrow().acquire_timeout;
                 let rx = waiters.borrow_mut().wait_for(req);
-                match rx.await {
-                    Err(_) => Err(ConnectError::Disconnected(None)),
-                    Ok(res) => res,
+                match timeout_checked(timeout, rx).await {
+                    Err(_) => return Err(ConnectError::AcquireTimeout),
+                    Ok(Err(_)) => return Err(ConnectError::Disconnected(None)),
+                    Ok(Ok(res)) => res?,
                 }
             }
+        };
+
+        let metrics = inner.borrow().metrics.clone();
+        if let Some(metrics) = metrics {
+            let elapsed = start.elapsed();
+            metrics.record(AcquireInfo {
+                authority: key.authority,
+                reused: timings.is_none(),
+                waited,
+                wait: if waited {
+                    elapsed.saturating_sub(timings.map(|t| t.total()).unwrap_or_default())
+                } else {
+                    Duration::ZERO
+                },
+                timings,
+            });
         }
+        Ok(conn)
     }
 }
 
@@ -181,9 +281,18 @@ pub(super) struct Inner {
     conn_keep_alive: Duration,
     disconnect_timeout: Seconds,
     limit: usize,
+    limit_per_host: usize,
+    max_idle: usize,
+    acquire_timeout: Millis,
+    h2streams: usize,
     h2config: h2::Config,
+    metrics: Option<PoolMetrics>,
     acquired: usize,
+    hosts: HashMap<Key, usize>,
+    opening: usize,
     available: HashMap<Key, VecDeque<AvailableConnection>>,
+    http2: HashMap<Key, Vec<H2Connection>>,
+    http1: HashSet<Key>,
     connecting: HashSet<Key>,
     waker: LocalWaker,
     waiters: Rc<RefCell<Waiters>>,
@@ -192,14 +301,14 @@ pub(super) struct Inner {
 #[derive(Debug)]
 struct Waiters {
     waiters: HashMap<Key, VecDeque<(Connect, Waiter)>>,
-    pool: pool::Pool<Result<Connection, ConnectError>>,
+    pool: pool::Pool<Acquisition>,
 }
 
 impl Waiters {
     /// connection is not available, wait
     fn wait_for(&mut self, connect: Connect) -> WaiterReceiver {
         let (tx, rx) = self.pool.channel();
-        let key: Key = connect.uri.authority().unwrap().clone().into();
+        let key = Key::new(&connect).unwrap();
         self.waiters
             .entry(key)
             .or_default()
@@ -237,8 +346,19 @@ impl Waiters {
 
 impl Inner {
     fn acquire(&mut self, key: &Key) -> Acquire {
+        // multiplex request over existing http/2 connection,
+        // http/2 connection uses single pool slot for all streams
+        if let Some(stream) = self.acquire_stream(key) {
+            return stream;
+        }
+
         // check limits
-        if self.limit > 0 && self.acquired >= self.limit {
+        if self.limit > 0 && self.acquired + self.opening >= self.limit {
+            return Acquire::NotAvailable;
+        }
+        if self.limit_per_host > 0
+            && self.hosts.get(key).copied().unwrap_or(0) >= self.limit_per_host
+        {
             return Acquire::NotAvailable;
         }
 
@@ -281,29 +401,98 @@ impl Inner {
                         if s.is_closed() {
                             continue;
                         }
-                        let conn = AvailableConnection {
-                            io: ConnectionType::H2(s.clone()),
-                            used: now,
-                            created: conn.created,
-                        };
-                        connections.push_front(conn);
                     }
                 }
                 return Acquire::Acquired(io, conn.created);
             }
         }
 
-        if self.connecting.contains(key) {
+        // protocol of the host is unknown or host supports http/2,
+        // wait for the connection that is being established
+        if self.connecting.contains(key) && !self.http1.contains(key) {
             Acquire::NotAvailable
         } else {
             Acquire::Available
         }
     }
 
+    fn acquire_stream(&mut self, key: &Key) -> Option<Acquire> {
+        let connections = self.http2.get_mut(key)?;
+        let now = now();
+
+        // cleanup closed and stale connections
+        connections.retain(|conn| {
+            if conn.client.is_closed() {
+                false
+            } else if conn.client.streams() == 0
+                && ((now - conn.used) > self.conn_keep_alive
+                    || (now - conn.created) > self.conn_lifetime)
+            {
+                conn.client.close();
+                false
+            } else {
+                true
+            }
+        });
+
+        let result = connections
+            .iter_mut()
+            .find(|conn| {
+                conn.client.streams() < self.h2streams
+                    && (now - conn.created) <= self.conn_lifetime
+            })
+            .map(|conn| {
+                conn.used = now;
+                Acquire::Stream(conn.client.clone(), conn.created)
+            });
+
+        if connections.is_empty() {
+            self.http2.remove(key);
+        }
+        result
+    }
+
+    /// Busy http/2 connection uses pool slot
+    fn h2_stream_opened(&mut self, key: &Key, client: &H2Client) {
+        if client.streams() == 0 {
+            self.acquired += 1;
+            self.host_acquired(key);
+        }
+        client.stream_opened();
+    }
+
+    /// Http/2 connection without open streams releases pool slot
+    fn h2_stream_closed(&mut self, key: &Key, client: &H2Client) {
+        client.stream_closed();
+        if client.streams() == 0 {
+            self.acquired -= 1;
+            self.host_released(key);
+        }
+    }
+
+    /// Connection for host is acquired or being opened
+    fn host_acquired(&mut self, key: &Key) {
+        *self.hosts.entry(key.clone()).or_default() += 1;
+    }
+
+    /// Connection for host is released
+    fn host_released(&mut self, key: &Key) {
+        if let Some(num) = self.hosts.get_mut(key) {
+            *num -= 1;
+            if *num == 0 {
+                self.hosts.remove(key);
+            }
+        }
+    }
+
     fn check_availibility(&mut self) {
         let mut waiters = self.waiters.borrow_mut();
         waiters.cleanup();
-        if !waiters.waiters.is_empty() && self.acquired < self.limit {
+        if !waiters.waiters.is_empty()
+            && (self.limit == 0
+                || self.acquired + self.opening < self.limit
+                || !self.http2.is_empty())
+        {
             self.waker.wake();
         }
     }
@@ -317,7 +506,8 @@ struct ConnectionPoolSupport<T> {
 
 impl<T> Future for ConnectionPoolSupport<T>
 where
-    T: Service<Connect, Response = IoBoxed, Error = ConnectError> + 'static,
+    T: Service<Connect, Response = (IoBoxed, ConnectTimings), Error = ConnectError>
+        + 'static,
 {
     type Output = ();
 
@@ -355,10 +545,33 @@ where
                         );
                         cleanup = true;
                         let (_, tx) = waiters.pop_front().unwrap();
-                        let _ = tx.send(Ok(Connection::new(
-                            io,
-                            created,
-                            Some(Acquired::new(key.clone(), this.inner.clone())),
+                        let _ = tx.send(Ok((
+                            Connection::new(
+                                io,
+                                created,
+                                Some(Acquired::new(key.clone(), this.inner.clone())),
+                            ),
+                            None,
+                        )));
+                    }
+                    Acquire::Stream(client, created) => {
+                        log::trace!(
+                            "Use existing http/2 connection for {:?}, wake up waiter",
+                            req.uri
+                        );
+                        cleanup = true;
+                        let (_, tx) = waiters.pop_front().unwrap();
+                        let _ = tx.send(Ok((
+                            Connection::new(
+                                ConnectionType::H2(client.clone()),
+                                created,
+                                Some(Acquired::stream(
+                                    key.clone(),
+                                    this.inner.clone(),
+                                    client,
+                                )),
+                            ),
+                            None,
                         )));
                     }
                     Acquire::Available => {
@@ -405,7 +618,8 @@ pin_project_lite::pin_project! {
 
 impl<T> OpenConnection<T>
 where
-    T: Service<Connect, Response = IoBoxed, Error = ConnectError> + 'static,
+    T: Service<Connect, Response = (IoBoxed, ConnectTimings), Error = ConnectError>
+        + 'static,
 {
     fn spawn(
         key: Key,
@@ -436,7 +650,7 @@ where
 
 impl<T> Future for OpenConnection<T>
 where
-    T: Service<Connect, Response = IoBoxed, Error = ConnectError>,
+    T: Service<Connect, Response = (IoBoxed, ConnectTimings), Error = ConnectError>,
 {
     type Output = ();
 
@@ -457,7 +671,7 @@ where
                 }
                 Poll::Ready(())
             }
-            Ok(io) => {
+            Ok((io, timings)) => {
                 io.set_disconnect_timeout(*this.disconnect_timeout);
 
                 // handle http2 proto
@@ -480,23 +694,23 @@ where
                         auth,
                     );
                     let client = H2Client::new(client);
-                    let guard = this.guard.take().unwrap().consume();
-                    let conn = Connection::new(
-                        ConnectionType::H2(client.clone()),
-                        now(),
-                        Some(guard.clone()),
-                    );
-                    if this.tx.take().unwrap().send(Ok(conn)).is_err() {
-                        // waiter is gone, return connection to pool
+                    let guard = this.guard.take().unwrap().multiplex(client.clone());
+                    let conn =
+                        Connection::new(ConnectionType::H2(client), now(), Some(guard));
+                    if this
+                        .tx
+                        .take()
+                        .unwrap()
+                        .send(Ok((conn, Some(timings))))
+                        .is_err()
+                    {
+                        // waiter is gone, connection stays in pool
                         log::trace!(
                             "Waiter for {:?} is gone while connecting to host",
                             &this.key.authority
                         );
                     }
-
-                    // put h2 connection to list of available connections
-                    Connection::new(ConnectionType::H2(client), now(), Some(guard))
-                        .release(false);
+                    this.inner.borrow_mut().check_availibility();
 
                     Poll::Ready(())
                 } else {
@@ -504,12 +718,18 @@ where
                         "Connection for {:?} is established, init http1 connection",
                         &this.key.authority
                     );
+                    // host does not support http/2, do not wait for
+                    // pending connections to this host
+                    this.inner.borrow_mut().http1.insert(this.key.clone());
+
                     let conn = Connection::new(
                         ConnectionType::H1(io),
                         now(),
                         Some(this.guard.take().unwrap().consume()),
                     );
-                    if let Err(Ok(conn)) = this.tx.take().unwrap().send(Ok(conn)) {
+                    if let Err(Ok((conn, _))) =
+                        this.tx.take().unwrap().send(Ok((conn, Some(timings))))
+                    {
                         // waiter is gone, return connection to pool
                         conn.release(false)
                     }
@@ -528,7 +748,12 @@ struct OpenGuard {
 
 impl OpenGuard {
     fn new(key: Key, inner: Rc<RefCell<Inner>>) -> Self {
-        inner.borrow_mut().connecting.insert(key.clone());
+        {
+            let mut pool = inner.borrow_mut();
+            pool.opening += 1;
+            pool.connecting.insert(key.clone());
+            pool.host_acquired(&key);
+        }
         OpenGuard {
             key,
             inner: Some(inner),
@@ -537,62 +762,131 @@ impl OpenGuard {
 
     fn consume(mut self) -> Acquired {
         let inner = self.inner.take().unwrap();
-        inner.borrow_mut().connecting.remove(&self.key);
+        {
+            let mut pool = inner.borrow_mut();
+            pool.opening -= 1;
+            pool.connecting.remove(&self.key);
+            pool.host_released(&self.key);
+        }
         Acquired::new(self.key.clone(), inner)
     }
+
+    /// Register http/2 connection and open first stream
+    fn multiplex(mut self, client: H2Client) -> Acquired {
+        let inner = self.inner.take().unwrap();
+        {
+            let mut pool = inner.borrow_mut();
+            pool.opening -= 1;
+            pool.connecting.remove(&self.key);
+            pool.host_released(&self.key);
+            pool.http1.remove(&self.key);
+            pool.http2
+                .entry(self.key.clone())
+                .or_default()
+                .push(H2Connection {
+                    client: client.clone(),
+                    used: now(),
+                    created: now(),
+                });
+        }
+        Acquired::stream(self.key.clone(), inner, client)
+    }
 }
 
 impl Drop for OpenGuard {
     fn drop(&mut self) {
         if let Some(inner) = self.inner.take() {
             let mut pool = inner.borrow_mut();
+            pool.opening -= 1;
             pool.connecting.remove(&self.key);
+            pool.host_released(&self.key);
             pool.check_availibility();
         }
     }
 }
 
-pub(super) struct Acquired(Key, Option<Rc<RefCell<Inner>>>);
+pub(super) struct Acquired {
+    key: Key,
+    inner: Option<Rc<RefCell<Inner>>>,
+    stream: Option<H2Client>,
+}
 
 impl Acquired {
     fn new(key: Key, inner: Rc<RefCell<Inner>>) -> Self {
-        inner.borrow_mut().acquired += 1;
-        Acquired(key, Some(inner))
+        {
+            let mut pool = inner.borrow_mut();
+            pool.acquired += 1;
+            pool.host_acquired(&key);
+        }
+        Acquired {
+            key,
+            inner: Some(inner),
+            stream: None,
+        }
     }
 
-    fn clone(&self) -> Self {
-        Acquired::new(self.0.clone(), self.1.as_ref().unwrap().clone())
+    /// Stream of http/2 connection
+    fn stream(key: Key, inner: Rc<RefCell<Inner>>, client: H2Client) -> Self {
+        inner.borrow_mut().h2_stream_opened(&key, &client);
+        Acquired {
+            key,
+            inner: Some(inner),
+            stream: Some(client),
+        }
     }
 
     pub(super) fn release(&mut self, conn: Connection, close: bool) {
-        if let Some(inner) = self.1.take() {
+        if let Some(inner) = self.inner.take() {
             let (io, created, _) = conn.into_inner();
             let mut inner = inner.borrow_mut();
-            inner.acquired -= 1;
-            if close {
-                log::trace!(
-                    "Releasing and closing connection for {:?}",
-                    self.0.authority
-                );
-                match io {
-                    ConnectionType::H1(io) => {
-                        spawn(async move {
-                            let _ = io.shutdown().await;
-                        });
-                    }
-                    ConnectionType::H2(io) => io.close(),
+            if let Some(client) = self.stream.take() {
+                inner.h2_stream_closed(&self.key, &client);
+                if close {
+                    client.close();
                 }
             } else {
-                log::trace!("Releasing connection for {:?}", self.0.authority);
-                inner
-                    .available
-                    .entry(self.0.clone())
-                    .or_insert_with(VecDeque::new)
-                    .push_back(AvailableConnection {
+                inner.acquired -= 1;
+                inner.host_released(&self.key);
+                if close {
+                    log::trace!(
+                        "Releasing and closing connection for {:?}",
+                        self.key.authority
+                    );
+                    match io {
+                        ConnectionType::H1(io) => {
+                            spawn(async move {
+                                let _ = io.shutdown().await;
+                            });
+                        }
+                        ConnectionType::H2(io) => io.close(),
+                    }
+                } else {
+                    log::trace!("Releasing connection for {:?}", self.key.authority);
+                    let max_idle = inner.max_idle;
+                    let available = inner
+                        .available
+                        .entry(self.key.clone())
+                        .or_insert_with(VecDeque::new);
+
+                    // close oldest idle connection
+                    if max_idle > 0 && available.len() >= max_idle {
+                        if let Some(conn) = available.pop_front() {
+                            match conn.io {
+                                ConnectionType::H1(io) => {
+                                    spawn(async move {
+                                        let _ = io.shutdown().await;
+                                    });
+                                }
+                                ConnectionType::H2(io) => io.close(),
+                            }
+                        }
+                    }
+                    available.push_back(AvailableConnection {
                         io,
                         created,
                         used: now(),
                     });
+                }
             }
             inner.check_availibility();
         }
@@ -601,9 +895,14 @@ impl Acquired {
 
 impl Drop for Acquired {
     fn drop(&mut self) {
-        if let Some(inner) = self.1.take() {
+        if let Some(inner) = self.inner.take() {
             let mut inner = inner.borrow_mut();
-            inner.acquired -= 1;
+            if let Some(client) = self.stream.take() {
+                inner.h2_stream_closed(&self.key, &client);
+            } else {
+                inner.acquired -= 1;
+                inner.host_released(&self.key);
+            }
             inner.check_availibility();
         }
     }
@@ -625,13 +924,20 @@ mod tests {
                 fn_service(move |req| {
                     let (client, server) = Io::create();
                     store2.borrow_mut().push((req, server));
-                    Box::pin(async move { Ok(IoBoxed::from(nio::Io::new(client))) })
+                    Box::pin(async move {
+                        Ok((
+                            IoBoxed::from(nio::Io::new(client)),
+                            ConnectTimings::default(),
+                        ))
+                    })
                 }),
-                Duration::from_secs(10),
-                Duration::from_secs(10),
-                Seconds::ZERO,
-                1,
-                h2::Config::client(),
+                PoolConfig {
+                    conn_lifetime: Duration::from_secs(10),
+                    conn_keep_alive: Duration::from_secs(10),
+                    disconnect_timeout: Seconds::ZERO,
+                    limit: 1,
+                    ..PoolConfig::default()
+                },
             )
             .clone(),
         );
@@ -640,6 +946,9 @@ mod tests {
         let req = Connect {
             uri: Uri::try_from("/test").unwrap(),
             addr: None,
+            proxy: None,
+            local_addr: None,
+            interface: None,
         };
         match pool.call(req).await {
             Err(ConnectError::Unresolved) => (),
@@ -650,6 +959,9 @@ mod tests {
         let req = Connect {
             uri: Uri::try_from("http://localhost/test").unwrap(),
             addr: None,
+            proxy: None,
+            local_addr: None,
+            interface: None,
         };
         let conn = pool.call(req.clone()).await.unwrap();
         assert_eq!(store.borrow().len(), 1);
@@ -703,6 +1015,9 @@ mod tests {
         let req = Connect {
             uri: Uri::try_from("http://localhost2/test").unwrap(),
             addr: None,
+            proxy: None,
+            local_addr: None,
+            interface: None,
         };
         let mut fut = std::pin::pin!(pool.call(req.clone()));
         assert!(lazy(|cx| fut.as_mut().poll(cx)).await.is_pending());
@@ -723,4 +1038,136 @@ mod tests {
         assert!(lazy(|cx| pool.poll_ready(cx)).await.is_ready());
         assert!(lazy(|cx| pool.poll_shutdown(cx)).await.is_ready());
     }
+
+    #[crate::rt_test]
+    async fn test_http2_streams() {
+        let pool = Pipeline::new(ConnectionPool::new(
+            fn_service(|_| async { Err::<(IoBoxed, _), _>(ConnectError::Timeout) }),
+            PoolConfig {
+                disconnect_timeout: Seconds::ZERO,
+                limit: 1,
+                acquire_timeout: Millis(50),
+                h2streams: 2,
+                ..PoolConfig::default()
+            },
+        ));
+
+        let (client, _server) = Io::create();
+        let client = H2Client::new(h2::client::SimpleClient::new(
+            IoBoxed::from(nio::Io::new(client)),
+            h2::Config::client(),
+            Scheme::HTTP,
+            ByteString::from_static("localhost"),
+        ));
+        let key: Key = Authority::from_static("localhost").into();
+        let inner = pool.get_ref().inner.clone();
+
+        // first stream is opened with connection
+        let first = OpenGuard::new(key, inner.clone()).multiplex(client.clone());
+        assert_eq!(client.streams(), 1);
+        assert_eq!(inner.borrow().acquired, 1);
+        assert!(inner.borrow().connecting.is_empty());
+
+        // requests are multiplexed and use single pool slot
+        let req = Connect {
+            uri: Uri::try_from("http://localhost/test").unwrap(),
+            addr: None,
+            proxy: None,
+            local_addr: None,
+            interface: None,
+        };
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert_eq!(conn.protocol(), HttpProtocol::Http2);
+        assert_eq!(client.streams(), 2);
+        assert_eq!(inner.borrow().acquired, 1);
+
+        // connection is saturated and pool limit is reached
+        assert!(matches!(
+            pool.call(req.clone()).await,
+            Err(ConnectError::AcquireTimeout)
+        ));
+
+        drop(conn);
+        assert_eq!(client.streams(), 1);
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert_eq!(client.streams(), 2);
+
+        conn.release(false);
+        drop(first);
+        assert_eq!(client.streams(), 0);
+        assert_eq!(inner.borrow().acquired, 0);
+        assert_eq!(inner.borrow().http2.len(), 1);
+    }
+
+    #[crate::rt_test]
+    async fn test_pool_limits() {
+        let store = Rc::new(RefCell::new(Vec::new()));
+        let store2 = store.clone();
+        let metrics = PoolMetrics::new();
+
+        let pool = Pipeline::new(ConnectionPool::new(
+            fn_service(move |_| {
+                let (client, server) = Io::create();
+                store2.borrow_mut().push(server);
+                Box::pin(async move {
+                    Ok((
+                        IoBoxed::from(nio::Io::new(client)),
+                        ConnectTimings::default(),
+                    ))
+                })
+            }),
+            PoolConfig {
+                disconnect_timeout: Seconds::ZERO,
+                limit_per_host: 2,
+                max_idle: 1,
+                acquire_timeout: Millis(50),
+                metrics: Some(metrics.clone()),
+                ..PoolConfig::default()
+            },
+        ));
+        let req = Connect {
+            uri: Uri::try_from("http://localhost/test").unwrap(),
+            addr: None,
+            proxy: None,
+            local_addr: None,
+            interface: None,
+        };
+        let req2 = Connect {
+            uri: Uri::try_from("http://localhost2/test").unwrap(),
+            addr: None,
+            proxy: None,
+            local_addr: None,
+            interface: None,
+        };
+
+        // per host limit
+        let conn1 = pool.call(req.clone()).await.unwrap();
+        let conn2 = pool.call(req.clone()).await.unwrap();
+        assert_eq!(pool.get_ref().inner.borrow().acquired, 2);
+        assert!(matches!(
+            pool.call(req.clone()).await,
+            Err(ConnectError::AcquireTimeout)
+        ));
+
+        // other hosts are not affected
+        let conn3 = pool.call(req2.clone()).await.unwrap();
+        assert_eq!(store.borrow().len(), 3);
+        assert_eq!(metrics.opened(), 3);
+
+        // only one idle connection is kept
+        conn1.release(false);
+        conn2.release(false);
+        conn3.release(false);
+        assert_eq!(pool.get_ref().inner.borrow().acquired, 0);
+        assert!(pool.get_ref().inner.borrow().hosts.is_empty());
+        let key: Key = Authority::from_static("localhost").into();
+        assert_eq!(pool.get_ref().inner.borrow().available[&key].len(), 1);
+
+        // reuse idle connection
+        let _conn = pool.call(req.clone()).await.unwrap();
+        assert_eq!(store.borrow().len(), 3);
+        assert_eq!(metrics.acquired(), 4);
+        assert_eq!(metrics.reused(), 1);
+        assert_eq!(metrics.reuse_ratio(), 0.25);
+    }
 }
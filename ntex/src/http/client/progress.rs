@@ -0,0 +1,266 @@
+use std::task::{Context, Poll};
+use std::{error::Error, fmt, pin::Pin, rc::Rc, time::Duration, time::Instant};
+
+use crate::http::body::{Body, BodySize, MessageBody};
+use crate::http::error::PayloadError;
+use crate::http::Payload;
+use crate::util::{Bytes, Stream};
+
+/// Max size of chunk for in-memory request body
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Transfer progress of request or response body
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Progress {
+    transferred: u64,
+    total: Option<u64>,
+    elapsed: Duration,
+    complete: bool,
+}
+
+impl Progress {
+    /// Number of transferred bytes
+    pub fn transferred(&self) -> u64 {
+        self.transferred
+    }
+
+    /// Total size of body, if known
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// Time since transfer started
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Check if transfer is completed
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Average throughput in bytes per second
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.transferred as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Transferred fraction of the body, from `0.0` to `1.0`, if total size is known
+    pub fn fraction(&self) -> Option<f64> {
+        self.total.map(|total| {
+            if total == 0 {
+                1.0
+            } else {
+                self.transferred as f64 / total as f64
+            }
+        })
+    }
+}
+
+/// Progress callback
+#[derive(Clone)]
+pub(super) struct ProgressHook(pub(super) Rc<dyn Fn(Progress)>);
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressHook").finish()
+    }
+}
+
+/// Progress callbacks of the request
+#[derive(Clone, Debug, Default)]
+pub(super) struct ProgressHooks {
+    pub(super) upload: Option<ProgressHook>,
+    pub(super) download: Option<ProgressHook>,
+}
+
+struct Tracker {
+    hook: ProgressHook,
+    transferred: u64,
+    total: Option<u64>,
+    start: Instant,
+}
+
+impl Tracker {
+    fn new(hook: ProgressHook, total: Option<u64>) -> Self {
+        Tracker {
+            hook,
+            total,
+            transferred: 0,
+            start: Instant::now(),
+        }
+    }
+
+    fn report(&mut self, size: usize, complete: bool) {
+        self.transferred += size as u64;
+        (*self.hook.0)(Progress {
+            complete,
+            transferred: self.transferred,
+            total: self.total,
+            elapsed: self.start.elapsed(),
+        });
+    }
+}
+
+/// Wrap request body, in-memory body is sent in chunks
+pub(super) fn upload(body: Body, hook: ProgressHook) -> Body {
+    let total = match body.size() {
+        BodySize::None | BodySize::Empty => return body,
+        BodySize::Sized(size) => Some(size),
+        BodySize::Stream => None,
+    };
+    Body::from_message(UploadBody {
+        size: body.size(),
+        body,
+        tracker: Tracker::new(hook, total),
+    })
+}
+
+struct UploadBody {
+    body: Body,
+    size: BodySize,
+    tracker: Tracker,
+}
+
+impl MessageBody for UploadBody {
+    fn size(&self) -> BodySize {
+        self.size
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        let chunk = if let Body::Bytes(ref mut bytes) = self.body {
+            if bytes.is_empty() {
+                None
+            } else {
+                Some(Ok(bytes.split_to(std::cmp::min(bytes.len(), CHUNK_SIZE))))
+            }
+        } else {
+            match self.body.poll_next_chunk(cx) {
+                Poll::Ready(chunk) => chunk,
+                Poll::Pending => return Poll::Pending,
+            }
+        };
+
+        match chunk {
+            Some(Ok(chunk)) => {
+                let complete = matches!(self.body, Body::Bytes(ref b) if b.is_empty());
+                self.tracker.report(chunk.len(), complete);
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => {
+                if !matches!(self.body, Body::Bytes(_)) {
+                    self.tracker.report(0, true);
+                }
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+/// Wrap response payload
+pub(super) fn download(
+    payload: Payload,
+    total: Option<u64>,
+    hook: ProgressHook,
+) -> Payload {
+    Payload::from_stream(DownloadPayload {
+        payload,
+        tracker: Tracker::new(hook, total),
+    })
+}
+
+struct DownloadPayload {
+    payload: Payload,
+    tracker: Tracker,
+}
+
+impl Stream for DownloadPayload {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        match Pin::new(&mut self.payload).poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                self.tracker.report(chunk.len(), false);
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(None) => {
+                self.tracker.report(0, true);
+                Poll::Ready(None)
+            }
+            res => res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, future::poll_fn};
+
+    use super::*;
+    use crate::util::stream_recv;
+
+    fn hook() -> (ProgressHook, Rc<RefCell<Vec<Progress>>>) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let events2 = events.clone();
+        (
+            ProgressHook(Rc::new(move |p| events2.borrow_mut().push(p))),
+            events,
+        )
+    }
+
+    #[crate::rt_test]
+    async fn test_upload() {
+        let (hook, events) = hook();
+        let mut body = upload(Body::from(vec![0u8; CHUNK_SIZE + 10]), hook);
+        assert_eq!(body.size(), BodySize::Sized((CHUNK_SIZE + 10) as u64));
+
+        let chunk = poll_fn(|cx| body.poll_next_chunk(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap().len(), CHUNK_SIZE);
+        let chunk = poll_fn(|cx| body.poll_next_chunk(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap().len(), 10);
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+
+        let events = events.borrow();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].transferred(), CHUNK_SIZE as u64);
+        assert!(!events[0].is_complete());
+        assert_eq!(events[1].transferred(), (CHUNK_SIZE + 10) as u64);
+        assert_eq!(events[1].total(), Some((CHUNK_SIZE + 10) as u64));
+        assert_eq!(events[1].fraction(), Some(1.0));
+        assert!(events[1].is_complete());
+
+        // empty body is not tracked
+        let (hook, _) = hook();
+        assert!(matches!(upload(Body::Empty, hook), Body::Empty));
+    }
+
+    #[crate::rt_test]
+    async fn test_download() {
+        let (hook, events) = hook();
+        let (mut tx, pl) = crate::http::h1::Payload::create(false);
+        let mut pl = download(pl.into(), None, hook);
+        tx.feed_data(Bytes::from_static(b"data"));
+        tx.feed_eof();
+
+        assert_eq!(stream_recv(&mut pl).await.unwrap().unwrap(), "data");
+        assert!(stream_recv(&mut pl).await.is_none());
+
+        let events = events.borrow();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].transferred(), 4);
+        assert_eq!(events[0].fraction(), None);
+        assert!(events[1].is_complete());
+        assert_eq!(events[1].transferred(), 4);
+    }
+}
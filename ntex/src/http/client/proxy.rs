@@ -0,0 +1,324 @@
+use std::{fmt, net::IpAddr, rc::Rc};
+
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+
+use crate::http::header::HeaderValue;
+use crate::http::Uri;
+
+use super::error::InvalidUrl;
+
+/// Http proxy configuration.
+///
+/// Plain http requests are sent to the proxy in absolute form, secure
+/// requests are sent through tunnel opened with `CONNECT` method.
+/// Only http proxies are supported.
+///
+/// ```rust
+/// use ntex::http::client::{Client, Proxy};
+///
+/// let client = Client::build()
+///     .proxy(
+///         Proxy::all("http://proxy.local:3128")
+///             .unwrap()
+///             .basic_auth("user", Some("password"))
+///             .no_proxy("localhost, .internal.local, 10.0.0.0/8"),
+///     )
+///     .finish();
+/// ```
+#[derive(Clone)]
+pub struct Proxy(Rc<ProxyInner>);
+
+#[derive(Clone)]
+struct ProxyInner {
+    uri: Uri,
+    intercept: Intercept,
+    auth: Option<HeaderValue>,
+    no_proxy: NoProxy,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Intercept {
+    All,
+    Http,
+    Https,
+}
+
+impl Proxy {
+    /// Proxy all requests.
+    pub fn all<U>(uri: U) -> Result<Proxy, InvalidUrl>
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: Into<crate::http::error::HttpError>,
+    {
+        Proxy::new(uri, Intercept::All)
+    }
+
+    /// Proxy only `http` and `ws` requests.
+    pub fn http<U>(uri: U) -> Result<Proxy, InvalidUrl>
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: Into<crate::http::error::HttpError>,
+    {
+        Proxy::new(uri, Intercept::Http)
+    }
+
+    /// Proxy only `https` and `wss` requests.
+    pub fn https<U>(uri: U) -> Result<Proxy, InvalidUrl>
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: Into<crate::http::error::HttpError>,
+    {
+        Proxy::new(uri, Intercept::Https)
+    }
+
+    fn new<U>(uri: U, intercept: Intercept) -> Result<Proxy, InvalidUrl>
+    where
+        Uri: TryFrom<U>,
+        <Uri as TryFrom<U>>::Error: Into<crate::http::error::HttpError>,
+    {
+        let uri = Uri::try_from(uri).map_err(|e| InvalidUrl::Http(e.into()))?;
+        match uri.scheme_str() {
+            None | Some("http") => (),
+            Some(_) => return Err(InvalidUrl::UnknownScheme),
+        }
+        let host = uri.host().ok_or(InvalidUrl::MissingHost)?;
+        let port = uri.port_u16().unwrap_or(80);
+
+        // normalize proxy address, connector uses it as connect target
+        let uri = Uri::try_from(format!("http://{}:{}/", host, port))
+            .map_err(|e| InvalidUrl::Http(e.into()))?;
+
+        Ok(Proxy(Rc::new(ProxyInner {
+            uri,
+            intercept,
+            auth: None,
+            no_proxy: NoProxy::default(),
+        })))
+    }
+
+    /// Set proxy basic authorization
+    pub fn basic_auth<U>(self, username: U, password: Option<&str>) -> Self
+    where
+        U: fmt::Display,
+    {
+        let auth = match password {
+            Some(password) => format!("{}:{}", username, password),
+            None => format!("{}:", username),
+        };
+        self.auth(format!("Basic {}", base64.encode(auth)))
+    }
+
+    /// Set proxy bearer authorization
+    pub fn bearer_auth<T>(self, token: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        self.auth(format!("Bearer {}", token))
+    }
+
+    fn auth(mut self, value: String) -> Self {
+        match HeaderValue::try_from(value) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                Rc::make_mut(&mut self.0).auth = Some(value);
+            }
+            Err(e) => log::error!("Proxy authorization header error: {:?}", e),
+        }
+        self
+    }
+
+    /// Add bypass rules.
+    ///
+    /// Rules is a comma separated list in `NO_PROXY` format. Rule could be
+    /// `*` which bypasses proxy for all hosts, domain name that matches the
+    /// domain and all its subdomains (`example.com` or `.example.com`),
+    /// ip address or network in CIDR notation (`192.168.0.0/16`).
+    pub fn no_proxy(mut self, rules: &str) -> Self {
+        Rc::make_mut(&mut self.0).no_proxy.add(rules);
+        self
+    }
+
+    /// Get proxy address
+    pub fn uri(&self) -> &Uri {
+        &self.0.uri
+    }
+
+    /// Check if request to `uri` should be sent through this proxy
+    pub fn intercepts(&self, uri: &Uri) -> bool {
+        let scheme = match uri.scheme_str() {
+            Some("http") | Some("ws") => Intercept::Http,
+            Some("https") | Some("wss") => Intercept::Https,
+            _ => return false,
+        };
+        if self.0.intercept != Intercept::All && self.0.intercept != scheme {
+            return false;
+        }
+        uri.host()
+            .map(|host| !self.0.no_proxy.matches(host))
+            .unwrap_or(false)
+    }
+
+    /// Get value for `Proxy-Authorization` header
+    pub(super) fn authorization(&self) -> Option<&HeaderValue> {
+        self.0.auth.as_ref()
+    }
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proxy")
+            .field("uri", &self.0.uri)
+            .field("intercept", &self.0.intercept)
+            .field("auth", &self.0.auth.is_some())
+            .field("no_proxy", &self.0.no_proxy)
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct NoProxy {
+    all: bool,
+    domains: Vec<String>,
+    networks: Vec<(IpAddr, u8)>,
+}
+
+impl NoProxy {
+    fn add(&mut self, rules: &str) {
+        for rule in rules.split(',').map(|r| r.trim()).filter(|r| !r.is_empty()) {
+            if rule == "*" {
+                self.all = true;
+            } else if let Some((addr, prefix)) = rule.split_once('/') {
+                match (addr.parse::<IpAddr>(), prefix.parse::<u8>()) {
+                    (Ok(addr), Ok(prefix)) if prefix <= max_prefix(&addr) => {
+                        self.networks.push((addr, prefix))
+                    }
+                    _ => log::error!("Invalid no_proxy network: {:?}", rule),
+                }
+            } else if let Ok(addr) = strip_brackets(rule).parse::<IpAddr>() {
+                self.networks.push((addr, max_prefix(&addr)));
+            } else {
+                // strip port and leading dot
+                let domain = match rule.rsplit_once(':') {
+                    Some((domain, port)) if port.parse::<u16>().is_ok() => domain,
+                    _ => rule,
+                };
+                let domain = domain.trim_start_matches("*.").trim_start_matches('.');
+                self.domains.push(domain.to_ascii_lowercase());
+            }
+        }
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        if self.all {
+            return true;
+        }
+        if let Ok(addr) = strip_brackets(host).parse::<IpAddr>() {
+            self.networks
+                .iter()
+                .any(|(net, prefix)| in_network(net, *prefix, &addr))
+        } else {
+            let host = host.trim_end_matches('.').to_ascii_lowercase();
+            self.domains.iter().any(|domain| {
+                host == *domain
+                    || (host.len() > domain.len()
+                        && host.ends_with(domain.as_str())
+                        && host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+            })
+        }
+    }
+}
+
+fn strip_brackets(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+fn max_prefix(addr: &IpAddr) -> u8 {
+    if addr.is_ipv4() {
+        32
+    } else {
+        128
+    }
+}
+
+fn in_network(net: &IpAddr, prefix: u8, addr: &IpAddr) -> bool {
+    match (net, addr) {
+        (IpAddr::V4(net), IpAddr::V4(addr)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(*net) & mask == u32::from(*addr) & mask
+        }
+        (IpAddr::V6(net), IpAddr::V6(addr)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(*net) & mask == u128::from(*addr) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proxy() {
+        let proxy = Proxy::all("proxy.local:3128").unwrap();
+        assert_eq!(proxy.uri(), "http://proxy.local:3128/");
+        assert!(proxy.authorization().is_none());
+        assert!(proxy.intercepts(&Uri::from_static("http://example.com/")));
+        assert!(proxy.intercepts(&Uri::from_static("wss://example.com/")));
+
+        let proxy = Proxy::http("http://proxy.local")
+            .unwrap()
+            .bearer_auth("token");
+        assert_eq!(proxy.uri(), "http://proxy.local:80/");
+        assert_eq!(proxy.authorization().unwrap(), "Bearer token");
+        assert!(proxy.intercepts(&Uri::from_static("http://example.com/")));
+        assert!(!proxy.intercepts(&Uri::from_static("https://example.com/")));
+
+        let proxy = Proxy::https("http://proxy.local")
+            .unwrap()
+            .basic_auth("user", Some("pass"));
+        assert_eq!(proxy.authorization().unwrap(), "Basic dXNlcjpwYXNz");
+        assert!(!proxy.intercepts(&Uri::from_static("http://example.com/")));
+        assert!(proxy.intercepts(&Uri::from_static("https://example.com/")));
+        assert!(format!("{:?}", proxy).contains("Proxy"));
+
+        assert!(matches!(
+            Proxy::all("https://proxy.local"),
+            Err(InvalidUrl::UnknownScheme)
+        ));
+    }
+
+    #[test]
+    fn test_no_proxy() {
+        let proxy = Proxy::all("http://proxy.local")
+            .unwrap()
+            .no_proxy("localhost, .internal.local,Example.com:8080")
+            .no_proxy("10.0.0.0/8, ::1, 192.168.1.1, bad/64");
+
+        let bypass = |uri: &'static str| !proxy.intercepts(&Uri::from_static(uri));
+        assert!(bypass("http://localhost:8080/"));
+        assert!(bypass("http://internal.local/"));
+        assert!(bypass("http://api.internal.local/"));
+        assert!(bypass("http://www.example.com/"));
+        assert!(bypass("http://EXAMPLE.com/"));
+        assert!(bypass("http://10.1.2.3/"));
+        assert!(bypass("http://[::1]:8080/"));
+        assert!(bypass("http://192.168.1.1/"));
+        assert!(!bypass("http://notexample.com/"));
+        assert!(!bypass("http://example.org/"));
+        assert!(!bypass("http://11.1.2.3/"));
+        assert!(!bypass("http://192.168.1.2/"));
+        assert!(!bypass("http://[::2]/"));
+
+        let proxy = proxy.no_proxy("*");
+        assert!(!proxy.intercepts(&Uri::from_static("http://example.org/")));
+
+        let proxy = Proxy::all("http://proxy.local")
+            .unwrap()
+            .no_proxy("0.0.0.0/0");
+        assert!(!proxy.intercepts(&Uri::from_static("http://1.1.1.1/")));
+        assert!(proxy.intercepts(&Uri::from_static("http://[::2]/")));
+    }
+}
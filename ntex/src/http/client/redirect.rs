@@ -0,0 +1,236 @@
+use crate::http::body::Body;
+use crate::http::header::{self, HeaderMap};
+use crate::http::{Method, RequestHead, RequestHeadType, StatusCode, Uri};
+
+/// Headers that are not forwarded to a different origin
+const SENSITIVE_HEADERS: [header::HeaderName; 4] = [
+    header::AUTHORIZATION,
+    header::PROXY_AUTHORIZATION,
+    header::COOKIE,
+    header::HOST,
+];
+
+/// Copy of the request that could be re-sent
+pub(super) struct Replay {
+    head: RequestHead,
+    body: Option<Body>,
+}
+
+impl Replay {
+    /// Copy request head and replayable body.
+    pub(super) fn new(head: &RequestHeadType, body: &Body) -> Self {
+        let h = head.as_ref();
+        let mut headers = h.headers.clone();
+        if let Some(extra) = head.extra_headers() {
+            for (key, value) in extra.iter() {
+                headers.insert(key.clone(), value.clone());
+            }
+        }
+
+        let new_head = RequestHead {
+            uri: h.uri.clone(),
+            method: h.method.clone(),
+            version: h.version,
+            flags: h.flags,
+            headers,
+            ..Default::default()
+        };
+
+        let body = match body {
+            Body::None => Some(Body::None),
+            Body::Empty => Some(Body::Empty),
+            Body::Bytes(b) => Some(Body::Bytes(b.clone())),
+            Body::Message(_) => None,
+        };
+
+        Replay {
+            head: new_head,
+            body,
+        }
+    }
+
+    /// Check if request body could be re-sent
+    pub(super) fn is_replayable(&self) -> bool {
+        self.body.is_some()
+    }
+
+    /// Construct same request for retry
+    pub(super) fn retry(self) -> Option<(RequestHead, Body)> {
+        Some((self.head, self.body?))
+    }
+
+    /// Construct request for redirect response.
+    ///
+    /// Returns `None` if response is not a redirect or if redirect
+    /// cannot be followed.
+    pub(super) fn redirect(
+        self,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Option<(RequestHead, Body, bool)> {
+        let Replay { mut head, body } = self;
+
+        let (method, body) = match status {
+            StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => {
+                if head.method == Method::POST {
+                    (Method::GET, Body::None)
+                } else {
+                    (head.method.clone(), body?)
+                }
+            }
+            StatusCode::SEE_OTHER => {
+                if head.method == Method::HEAD {
+                    (Method::HEAD, Body::None)
+                } else {
+                    (Method::GET, Body::None)
+                }
+            }
+            StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => {
+                (head.method.clone(), body?)
+            }
+            _ => return None,
+        };
+
+        let location = headers.get(&header::LOCATION)?.to_str().ok()?;
+        let uri = resolve(&head.uri, location)?;
+
+        if method != head.method {
+            head.headers.remove(&header::CONTENT_TYPE);
+            head.headers.remove(&header::CONTENT_LENGTH);
+            head.headers.remove(&header::CONTENT_ENCODING);
+            head.headers.remove(&header::TRANSFER_ENCODING);
+        }
+
+        let same_origin = uri.scheme() == head.uri.scheme()
+            && uri.host() == head.uri.host()
+            && uri.port_u16() == head.uri.port_u16();
+        if !same_origin {
+            for name in &SENSITIVE_HEADERS {
+                head.headers.remove(name);
+            }
+        }
+
+        head.method = method;
+        head.uri = uri;
+        Some((head, body, same_origin))
+    }
+}
+
+/// Resolve redirect location relative to the request uri
+pub(super) fn resolve(base: &Uri, location: &str) -> Option<Uri> {
+    // fragments are not sent to the server
+    let location = location.split('#').next().unwrap_or_default();
+
+    let uri = if location.contains("://") {
+        location.to_string()
+    } else {
+        let scheme = base.scheme_str()?;
+        let authority = base.authority()?.as_str();
+
+        if let Some(location) = location.strip_prefix("//") {
+            format!("{}://{}", scheme, location)
+        } else if location.starts_with('/') {
+            format!("{}://{}{}", scheme, authority, location)
+        } else {
+            let path = base.path();
+            let dir = &path[..path.rfind('/').map(|pos| pos + 1).unwrap_or(0)];
+            let dir = if dir.is_empty() { "/" } else { dir };
+            format!("{}://{}{}{}", scheme, authority, dir, location)
+        }
+    };
+
+    let uri = Uri::try_from(uri).ok()?;
+    if uri.host().is_some() {
+        Some(uri)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::HeaderValue;
+    use crate::util::Bytes;
+
+    #[test]
+    fn test_resolve() {
+        let base = Uri::from_static("http://localhost:8080/a/b?x=1");
+        assert_eq!(
+            resolve(&base, "https://example.com/path").unwrap(),
+            "https://example.com/path"
+        );
+        assert_eq!(
+            resolve(&base, "//example.com/path").unwrap(),
+            "http://example.com/path"
+        );
+        assert_eq!(
+            resolve(&base, "/c?y=2#frag").unwrap(),
+            "http://localhost:8080/c?y=2"
+        );
+        assert_eq!(resolve(&base, "c").unwrap(), "http://localhost:8080/a/c");
+        assert!(resolve(&base, "http://").is_none());
+    }
+
+    #[test]
+    fn test_redirect() {
+        let mut head = RequestHead {
+            method: Method::POST,
+            uri: Uri::from_static("http://localhost/a"),
+            ..Default::default()
+        };
+        head.headers
+            .insert(header::AUTHORIZATION, HeaderValue::from_static("secret"));
+        head.headers
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        let head = RequestHeadType::Owned(head);
+        let body = Body::Bytes(Bytes::from_static(b"data"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::LOCATION, HeaderValue::from_static("/b"));
+
+        // 302, POST is changed to GET
+        let (h, b, same) = Replay::new(&head, &body)
+            .redirect(StatusCode::FOUND, &headers)
+            .unwrap();
+        assert_eq!(h.method, Method::GET);
+        assert_eq!(h.uri, "http://localhost/b");
+        assert!(matches!(b, Body::None));
+        assert!(h.headers.get(header::CONTENT_TYPE).is_none());
+        assert!(h.headers.get(header::AUTHORIZATION).is_some());
+        assert!(same);
+
+        // 307, method and body are preserved
+        let (h, b, _) = Replay::new(&head, &body)
+            .redirect(StatusCode::TEMPORARY_REDIRECT, &headers)
+            .unwrap();
+        assert_eq!(h.method, Method::POST);
+        assert!(matches!(b, Body::Bytes(_)));
+        assert!(h.headers.get(header::CONTENT_TYPE).is_some());
+
+        // cross-origin, sensitive headers are removed
+        headers.insert(
+            header::LOCATION,
+            HeaderValue::from_static("http://example.com/"),
+        );
+        let (h, _, same) = Replay::new(&head, &body)
+            .redirect(StatusCode::PERMANENT_REDIRECT, &headers)
+            .unwrap();
+        assert!(h.headers.get(header::AUTHORIZATION).is_none());
+        assert!(!same);
+
+        // streaming body cannot be replayed
+        let body = Body::from_message(Bytes::from_static(b"data"));
+        assert!(Replay::new(&head, &body)
+            .redirect(StatusCode::TEMPORARY_REDIRECT, &headers)
+            .is_none());
+        assert!(Replay::new(&head, &body)
+            .redirect(StatusCode::SEE_OTHER, &headers)
+            .is_some());
+
+        // not a redirect
+        assert!(Replay::new(&head, &Body::None)
+            .redirect(StatusCode::OK, &headers)
+            .is_none());
+    }
+}
@@ -9,19 +9,30 @@ use crate::http::body::Body;
 use crate::http::error::HttpError;
 use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use crate::http::{
-    uri, ConnectionType, Method, RequestHead, RequestHeadType, Uri, Version,
+    uri, ConnectionType, Method, RequestHead, RequestHeadType, ResponseHead, Uri, Version,
 };
-use crate::{time::Millis, util::Bytes, util::Stream};
+use crate::time::Millis;
+use crate::util::{ByteString, Bytes, Stream};
 
+use super::connect::{LocalBind, RequestDeadline};
 use super::error::{FreezeRequestError, InvalidUrl};
+use super::h1proto::InformationalHook;
+use super::progress::{Progress, ProgressHook, ProgressHooks};
 use super::sender::{PrepForSendingError, SendClientRequest};
-use super::{frozen::FrozenClientRequest, ClientConfig};
+use super::{frozen::FrozenClientRequest, ClientConfig, ClientMultipart, RetryPolicy};
 
-#[cfg(feature = "compress")]
+#[cfg(all(feature = "compress", feature = "zstd"))]
+const HTTPS_ENCODING: &str = "br, gzip, deflate, zstd";
+#[cfg(all(feature = "compress", not(feature = "zstd")))]
 const HTTPS_ENCODING: &str = "br, gzip, deflate";
 #[cfg(not(feature = "compress"))]
 const HTTPS_ENCODING: &str = "br";
 
+#[cfg(all(feature = "compress", feature = "zstd"))]
+const HTTP_ENCODING: &str = "gzip, deflate, zstd";
+#[cfg(all(feature = "compress", not(feature = "zstd")))]
+const HTTP_ENCODING: &str = "gzip, deflate";
+
 /// An HTTP Client request builder
 ///
 /// This type can be used to construct an instance of `ClientRequest` through a
@@ -63,14 +74,14 @@ impl ClientRequest {
         <Uri as TryFrom<U>>::Error: Into<HttpError>,
     {
         ClientRequest {
-            config,
             head: RequestHead::default(),
             err: None,
             addr: None,
             #[cfg(feature = "cookie")]
             cookies: None,
             timeout: Millis::ZERO,
-            response_decompress: true,
+            response_decompress: config.response_decompress,
+            config,
         }
         .method(method)
         .uri(uri)
@@ -252,6 +263,17 @@ impl ClientRequest {
         self.header(header::CONTENT_LENGTH, len)
     }
 
+    /// Set `Expect: 100-continue` header
+    ///
+    /// Request body is sent only after server responds with `100 Continue`
+    /// or after client's expect timeout.
+    pub fn expect_continue(mut self) -> Self {
+        self.head
+            .headers
+            .insert(header::EXPECT, HeaderValue::from_static("100-continue"));
+        self
+    }
+
     /// Set HTTP basic authorization header
     pub fn basic_auth<U>(self, username: U, password: Option<&str>) -> Self
     where
@@ -318,9 +340,110 @@ impl ClientRequest {
         self
     }
 
+    /// Enable automatic decompress of response's body
+    ///
+    /// Overrides client wide setting.
+    pub fn decompress(mut self) -> Self {
+        self.response_decompress = true;
+        self
+    }
+
+    /// Set retry policy for this request.
+    ///
+    /// Overrides client wide retry policy.
+    pub fn retry(self, policy: RetryPolicy) -> Self {
+        self.head.extensions_mut().insert(policy);
+        self
+    }
+
+    /// Bind connection for this request to local address.
+    ///
+    /// Overrides client wide setting.
+    pub fn local_address(self, addr: net::IpAddr) -> Self {
+        self.update_bind(|bind| bind.addr = Some(addr));
+        self
+    }
+
+    /// Bind connection for this request to network interface.
+    ///
+    /// Overrides client wide setting.
+    pub fn interface(self, name: &str) -> Self {
+        self.update_bind(|bind| bind.interface = Some(ByteString::from(name)));
+        self
+    }
+
+    fn update_bind<F: FnOnce(&mut LocalBind)>(&self, f: F) {
+        let mut ext = self.head.extensions_mut();
+        if !ext.contains::<LocalBind>() {
+            ext.insert(self.config.bind.clone());
+        }
+        f(ext.get_mut::<LocalBind>().unwrap());
+    }
+
+    /// Set request deadline. Overrides client wide deadline setting.
+    ///
+    /// Deadline is the total time for connecting, sending request and receiving
+    /// response head, including redirects and retries.
+    pub fn deadline<T: Into<Millis>>(self, timeout: T) -> Self {
+        self.head
+            .extensions_mut()
+            .insert(RequestDeadline(timeout.into()));
+        self
+    }
+
+    /// Set callback for request body upload progress.
+    ///
+    /// Callback is called after each chunk of the body is written. In-memory
+    /// bodies are sent in chunks of 64KB. Request bodies that are re-sent
+    /// on retries or redirects are not tracked.
+    pub fn upload_progress<F>(self, f: F) -> Self
+    where
+        F: Fn(Progress) + 'static,
+    {
+        self.update_progress(|hooks| hooks.upload = Some(ProgressHook(Rc::new(f))));
+        self
+    }
+
+    /// Set callback for response body download progress.
+    ///
+    /// Callback is called after each chunk of the response payload is received
+    /// and once more after the payload is complete.
+    pub fn download_progress<F>(self, f: F) -> Self
+    where
+        F: Fn(Progress) + 'static,
+    {
+        self.update_progress(|hooks| hooks.download = Some(ProgressHook(Rc::new(f))));
+        self
+    }
+
+    /// Set callback for informational (1xx) responses.
+    ///
+    /// Callback is called for each interim response that is received
+    /// before the final response, for example `103 Early Hints`.
+    /// `101 Switching Protocols` is a final response, use
+    /// `ClientResponse::take_io()` to get upgraded connection.
+    pub fn informational<F>(self, f: F) -> Self
+    where
+        F: Fn(&ResponseHead) + 'static,
+    {
+        self.head
+            .extensions_mut()
+            .insert(InformationalHook(Rc::new(f)));
+        self
+    }
+
+    fn update_progress<F: FnOnce(&mut ProgressHooks)>(&self, f: F) {
+        let mut ext = self.head.extensions_mut();
+        if !ext.contains::<ProgressHooks>() {
+            ext.insert(ProgressHooks::default());
+        }
+        f(ext.get_mut::<ProgressHooks>().unwrap());
+    }
+
     /// Set request timeout in millis. Overrides client wide timeout setting.
     ///
-    /// Request timeout is the total time before a response must be received.
+    /// Request timeout is the time before a response head must be received
+    /// after request is sent (time to first byte).
     /// Default value is 5 seconds.
     pub fn timeout<T: Into<Millis>>(mut self, timeout: T) -> Self {
         self.timeout = timeout.into();
@@ -446,6 +569,22 @@ impl ClientRequest {
         )
     }
 
+    /// Set a `multipart/form-data` body and generate `ClientRequest`
+    pub fn send_multipart(self, body: ClientMultipart) -> SendClientRequest {
+        let slf = match self.prep_for_sending() {
+            Ok(slf) => slf,
+            Err(e) => return e.into(),
+        };
+
+        RequestHeadType::Owned(slf.head).send_multipart(
+            slf.addr,
+            slf.response_decompress,
+            slf.timeout,
+            slf.config,
+            body,
+        )
+    }
+
     /// Set an streaming body and generate `ClientRequest`.
     pub fn send_stream<S, E>(self, stream: S) -> SendClientRequest
     where
@@ -541,7 +680,7 @@ impl ClientRequest {
             } else {
                 #[cfg(feature = "compress")]
                 {
-                    slf = slf.set_header_if_none(header::ACCEPT_ENCODING, "gzip, deflate")
+                    slf = slf.set_header_if_none(header::ACCEPT_ENCODING, HTTP_ENCODING)
                 }
             };
         }
@@ -9,17 +9,21 @@ use coo_kie::{Cookie, ParseError as CookieParseError};
 
 use crate::http::error::PayloadError;
 use crate::http::header::{AsName, HeaderValue, CONTENT_LENGTH};
-use crate::http::{HeaderMap, HttpMessage, Payload, ResponseHead, StatusCode, Version};
+use crate::http::{
+    HeaderMap, HttpMessage, Payload, ResponseHead, StatusCode, Uri, Version,
+};
+use crate::io::IoBoxed;
 use crate::time::{Deadline, Millis};
 use crate::util::{Bytes, BytesMut, Extensions, Stream};
 
-use super::{error::JsonPayloadError, ClientConfig};
+use super::{error::JsonPayloadError, h1proto::UpgradedIo, ClientConfig};
 
 /// Client Response
 pub struct ClientResponse {
     pub(crate) head: ResponseHead,
     pub(crate) payload: Payload,
-    config: Rc<ClientConfig>,
+    pub(crate) url: Uri,
+    pub(super) config: Rc<ClientConfig>,
 }
 
 impl HttpMessage for ClientResponse {
@@ -68,6 +72,7 @@ impl ClientResponse {
             head,
             payload,
             config,
+            url: Uri::default(),
         }
     }
 
@@ -92,6 +97,14 @@ impl ClientResponse {
         self.head().version
     }
 
+    /// Get the url of the response.
+    ///
+    /// If redirects are followed, this is the url of the last request.
+    #[inline]
+    pub fn url(&self) -> &Uri {
+        &self.url
+    }
+
     /// Get the status from the server.
     #[inline]
     pub fn status(&self) -> StatusCode {
@@ -126,6 +139,19 @@ impl ClientResponse {
         mem::take(&mut self.payload)
     }
 
+    /// Take connection io of `101 Switching Protocols` response
+    ///
+    /// Upgraded connection is not managed by client anymore and could be
+    /// used for custom protocols. Bytes that are received after response
+    /// head stay in io's read buffer. Returns `None` for other responses
+    /// and for http/2 connections.
+    pub fn take_io(&mut self) -> Option<IoBoxed> {
+        self.head
+            .extensions_mut()
+            .remove::<UpgradedIo>()
+            .map(|upgraded| upgraded.0)
+    }
+
     /// Request extensions
     #[inline]
     pub fn extensions(&self) -> Ref<'_, Extensions> {
@@ -155,6 +181,16 @@ impl ClientResponse {
     pub fn json<T: DeserializeOwned>(&mut self) -> JsonBody<T> {
         JsonBody::new(self)
     }
+
+    /// Returns stream of newline-delimited json values.
+    ///
+    /// Returns error:
+    ///
+    /// * content type is not `application/x-ndjson` or `application/jsonl`
+    /// * line length is greater than 64k
+    pub fn ndjson<T: DeserializeOwned>(&mut self) -> NdJsonStream<T> {
+        NdJsonStream::new(self)
+    }
 }
 
 impl Stream for ClientResponse {
@@ -364,6 +400,119 @@ where
     }
 }
 
+#[derive(Debug)]
+/// Response's payload newline-delimited json parser.
+///
+/// Stream yields deserialized `T` value for each non-empty line.
+pub struct NdJsonStream<U> {
+    stream: Payload,
+    buf: BytesMut,
+    limit: usize,
+    err: Option<JsonPayloadError>,
+    eof: bool,
+    _t: PhantomData<U>,
+}
+
+impl<U> NdJsonStream<U>
+where
+    U: DeserializeOwned,
+{
+    /// Create `NdJsonStream` for response.
+    pub fn new(res: &mut ClientResponse) -> Self {
+        // check content-type
+        let ndjson = if let Ok(Some(mime)) = res.mime_type() {
+            matches!(mime.subtype().as_str(), "x-ndjson" | "ndjson" | "jsonl")
+        } else {
+            false
+        };
+
+        NdJsonStream {
+            stream: res.take_payload(),
+            buf: BytesMut::new(),
+            limit: 65_536,
+            err: if ndjson {
+                None
+            } else {
+                Some(JsonPayloadError::ContentType)
+            },
+            eof: false,
+            _t: PhantomData,
+        }
+    }
+
+    /// Change max size of the line. By default max size is 64Kb
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    fn next_line(&mut self) -> Option<Result<U, JsonPayloadError>> {
+        loop {
+            let pos = self.buf.iter().position(|b| *b == b'\n');
+            let len = pos.unwrap_or(self.buf.len());
+            if self.limit > 0 && len > self.limit {
+                self.eof = true;
+                self.buf.clear();
+                return Some(Err(JsonPayloadError::Payload(PayloadError::Overflow)));
+            }
+
+            let line = if let Some(pos) = pos {
+                self.buf.split_to(pos + 1).freeze()
+            } else if self.eof && !self.buf.is_empty() {
+                self.buf.split().freeze()
+            } else {
+                return None;
+            };
+
+            let line = line.trim_ascii();
+            if !line.is_empty() {
+                return Some(
+                    serde_json::from_slice::<U>(line).map_err(JsonPayloadError::from),
+                );
+            }
+        }
+    }
+}
+
+impl<U> Unpin for NdJsonStream<U> {}
+
+impl<U> Stream for NdJsonStream<U>
+where
+    U: DeserializeOwned,
+{
+    type Item = Result<U, JsonPayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Some(err) = this.err.take() {
+            this.eof = true;
+            this.buf.clear();
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        loop {
+            if let Some(item) = this.next_line() {
+                return Poll::Ready(Some(item));
+            }
+            if this.eof {
+                return Poll::Ready(None);
+            }
+
+            match Pin::new(&mut this.stream).poll_next(cx) {
+                Poll::Ready(Some(Ok(chunk))) => this.buf.extend_from_slice(&chunk),
+                Poll::Ready(Some(Err(err))) => {
+                    this.eof = true;
+                    this.buf.clear();
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+                Poll::Ready(None) => this.eof = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 struct ReadBody {
     stream: Payload,
@@ -520,4 +669,51 @@ mod tests {
             }
         );
     }
+
+    #[crate::rt_test]
+    async fn test_ndjson() {
+        use crate::util::stream_recv;
+
+        let mut req = TestResponse::default().finish();
+        let mut stream = req.ndjson::<MyObject>();
+        assert!(json_eq(
+            stream_recv(&mut stream).await.unwrap().err().unwrap(),
+            JsonPayloadError::ContentType
+        ));
+        assert!(stream_recv(&mut stream).await.is_none());
+
+        let mut req = TestResponse::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/x-ndjson"),
+            )
+            .set_payload(Bytes::from_static(
+                b"{\"name\": \"a\"}\n\n{\"name\": \"b\"}\r\n{\"name\": \"c\"}",
+            ))
+            .finish();
+        let mut stream = req.ndjson::<MyObject>();
+        for name in ["a", "b", "c"] {
+            assert_eq!(
+                stream_recv(&mut stream).await.unwrap().unwrap(),
+                MyObject {
+                    name: name.to_owned()
+                }
+            );
+        }
+        assert!(stream_recv(&mut stream).await.is_none());
+
+        let mut req = TestResponse::default()
+            .header(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/jsonl"),
+            )
+            .set_payload(Bytes::from_static(b"{\"name\": \"aaaaaaaaaaaaaaa\"}\n"))
+            .finish();
+        let mut stream = req.ndjson::<MyObject>().limit(10);
+        assert!(json_eq(
+            stream_recv(&mut stream).await.unwrap().err().unwrap(),
+            JsonPayloadError::Payload(PayloadError::Overflow)
+        ));
+        assert!(stream_recv(&mut stream).await.is_none());
+    }
 }
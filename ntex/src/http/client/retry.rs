@@ -0,0 +1,289 @@
+use std::{cell::Cell, rc::Rc, time::Duration, time::SystemTime};
+
+use nanorand::{Rng, WyRand};
+
+use crate::http::header::{HeaderMap, RETRY_AFTER};
+use crate::http::{Method, StatusCode};
+use crate::time::Millis;
+
+use super::error::SendRequestError;
+
+/// Retry policy for http client requests.
+///
+/// By default, requests with idempotent methods (`GET`, `HEAD`, `OPTIONS`,
+/// `PUT`, `DELETE` and `TRACE`) are retried up to 3 times if request failed
+/// with retryable error (see `SendRequestError::is_retryable()`), or if
+/// server responds with *429 Too Many Requests* or with *5xx* status.
+/// Delay between attempts grows
+/// exponentially, with random jitter. `Retry-After` response header is
+/// respected, if requested delay exceeds max backoff request is not retried.
+///
+/// Requests with streaming body are never retried.
+///
+/// Policy could be set for all requests of a client with
+/// `ClientBuilder::retry()` or for specific request with
+/// `ClientRequest::retry()`.
+///
+/// ```rust
+/// use ntex::http::client::{Client, RetryPolicy};
+///
+/// let policy = RetryPolicy::new().max_retries(5).budget(0.2);
+/// let client = Client::build().retry(policy).finish();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_retries: usize,
+    backoff: Millis,
+    max_backoff: Millis,
+    methods: Option<Vec<Method>>,
+    statuses: Option<Vec<StatusCode>>,
+    budget: Option<Rc<Budget>>,
+}
+
+#[derive(Debug)]
+struct Budget {
+    ratio: f32,
+    max: f32,
+    tokens: Cell<f32>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            backoff: Millis(100),
+            max_backoff: Millis(10_000),
+            methods: None,
+            statuses: None,
+            budget: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create retry policy with default settings.
+    pub fn new() -> Self {
+        RetryPolicy::default()
+    }
+
+    /// Set max number of retries.
+    ///
+    /// By default 3 retries.
+    pub fn max_retries(mut self, num: usize) -> Self {
+        self.max_retries = num;
+        self
+    }
+
+    /// Set initial backoff delay and max backoff delay.
+    ///
+    /// Delay is doubled for each next attempt. Actual delay is a random
+    /// value between half and full delay. By default initial delay is 100
+    /// millis and max delay is 10 seconds.
+    pub fn backoff<T: Into<Millis>, M: Into<Millis>>(mut self, initial: T, max: M) -> Self {
+        self.backoff = initial.into();
+        self.max_backoff = max.into();
+        self
+    }
+
+    /// Set methods that could be retried.
+    ///
+    /// By default, only idempotent methods are retried.
+    pub fn methods<I: IntoIterator<Item = Method>>(mut self, methods: I) -> Self {
+        self.methods = Some(methods.into_iter().collect());
+        self
+    }
+
+    /// Set response statuses that should be retried.
+    ///
+    /// By default, *429 Too Many Requests* and *5xx* responses are retried.
+    pub fn statuses<I: IntoIterator<Item = StatusCode>>(mut self, statuses: I) -> Self {
+        self.statuses = Some(statuses.into_iter().collect());
+        self
+    }
+
+    /// Limit number of retries with retry budget.
+    ///
+    /// Each request deposits `ratio` of a retry to the budget, each retry
+    /// withdraws one retry. Budget holds up to 10 retries, initially budget
+    /// is full. Budget is shared between all clones of the policy.
+    ///
+    /// For example, ratio of `0.1` allows 10% of requests to be retried.
+    pub fn budget(mut self, ratio: f32) -> Self {
+        self.budget = Some(Rc::new(Budget {
+            ratio,
+            max: 10.0,
+            tokens: Cell::new(10.0),
+        }));
+        self
+    }
+
+    /// Check if request could be retried.
+    pub(super) fn is_retryable(&self, method: &Method) -> bool {
+        if self.max_retries == 0 {
+            return false;
+        }
+        if let Some(ref budget) = self.budget {
+            budget
+                .tokens
+                .set((budget.tokens.get() + budget.ratio).min(budget.max));
+        }
+        if let Some(ref methods) = self.methods {
+            methods.contains(method)
+        } else {
+            matches!(
+                *method,
+                Method::GET
+                    | Method::HEAD
+                    | Method::OPTIONS
+                    | Method::PUT
+                    | Method::DELETE
+                    | Method::TRACE
+            )
+        }
+    }
+
+    /// Get delay before next attempt, returns `None` if request
+    /// should not be retried.
+    pub(super) fn next_delay(
+        &self,
+        attempt: usize,
+        res: &Result<(StatusCode, &HeaderMap), &SendRequestError>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_retries {
+            return None;
+        }
+
+        let retry_after = match res {
+            Ok((status, headers)) => {
+                let retry = if let Some(ref statuses) = self.statuses {
+                    statuses.contains(status)
+                } else {
+                    *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+                };
+                if !retry {
+                    return None;
+                }
+                retry_after(headers)
+            }
+            Err(err) if err.is_retryable() => None,
+            Err(_) => return None,
+        };
+
+        let max: Duration = self.max_backoff.into();
+        let delay = if let Some(delay) = retry_after {
+            if delay > max {
+                return None;
+            }
+            delay
+        } else {
+            let base: Duration = self.backoff.into();
+            let delay = base
+                .saturating_mul(1u32 << attempt.min(16))
+                .min(max)
+                .as_millis() as u64;
+            let half = delay / 2;
+            Duration::from_millis(half + WyRand::new().generate_range(0..=half))
+        };
+
+        if let Some(ref budget) = self.budget {
+            let tokens = budget.tokens.get();
+            if tokens < 1.0 {
+                log::trace!("Retry budget is exhausted");
+                return None;
+            }
+            budget.tokens.set(tokens - 1.0);
+        }
+        Some(delay)
+    }
+}
+
+/// Parse `Retry-After` header, delay-seconds or http-date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(&RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        Some(Duration::from_secs(secs))
+    } else {
+        let date = ntex_http::date::parse_http_date(value.as_bytes())?;
+        Some(
+            date.duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::client::error::ConnectError;
+    use crate::http::header::HeaderValue;
+
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy::new().backoff(Millis(100), Millis(1_000));
+        assert!(policy.is_retryable(&Method::GET));
+        assert!(!policy.is_retryable(&Method::POST));
+        assert!(RetryPolicy::new()
+            .methods([Method::POST])
+            .is_retryable(&Method::POST));
+        assert!(!RetryPolicy::new().max_retries(0).is_retryable(&Method::GET));
+
+        let mut headers = HeaderMap::new();
+        let delay = policy
+            .next_delay(0, &Ok((StatusCode::SERVICE_UNAVAILABLE, &headers)))
+            .unwrap();
+        assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        let delay = policy
+            .next_delay(2, &Ok((StatusCode::BAD_GATEWAY, &headers)))
+            .unwrap();
+        assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400));
+        assert!(policy
+            .next_delay(3, &Ok((StatusCode::BAD_GATEWAY, &headers)))
+            .is_none());
+        assert!(policy
+            .next_delay(0, &Ok((StatusCode::NOT_FOUND, &headers)))
+            .is_none());
+        assert!(policy
+            .next_delay(0, &Err(&SendRequestError::Connect(ConnectError::Timeout)))
+            .is_some());
+        assert!(policy
+            .next_delay(0, &Err(&SendRequestError::Timeout))
+            .is_none());
+
+        // retry-after
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("1"));
+        assert_eq!(
+            policy.next_delay(0, &Ok((StatusCode::TOO_MANY_REQUESTS, &headers))),
+            Some(Duration::from_secs(1))
+        );
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert!(policy
+            .next_delay(0, &Ok((StatusCode::TOO_MANY_REQUESTS, &headers)))
+            .is_none());
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            policy.next_delay(0, &Ok((StatusCode::TOO_MANY_REQUESTS, &headers))),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_retry_budget() {
+        let policy = RetryPolicy::new().budget(0.5);
+        let headers = HeaderMap::new();
+        let res = Ok((StatusCode::SERVICE_UNAVAILABLE, &headers));
+
+        for _ in 0..10 {
+            assert!(policy.next_delay(0, &res).is_some());
+        }
+        assert!(policy.next_delay(0, &res).is_none());
+
+        // two requests deposit one retry
+        assert!(policy.is_retryable(&Method::GET));
+        assert!(policy.clone().is_retryable(&Method::GET));
+        assert!(policy.next_delay(0, &res).is_some());
+        assert!(policy.next_delay(0, &res).is_none());
+    }
+}
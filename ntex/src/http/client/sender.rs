@@ -16,7 +16,8 @@ use crate::http::encoding::Decoder;
 use crate::http::Payload;
 
 use super::error::{FreezeRequestError, InvalidUrl, SendRequestError};
-use super::{ClientConfig, ClientResponse};
+use super::progress::{self, ProgressHooks};
+use super::{ClientConfig, ClientMultipart, ClientResponse};
 
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum PrepForSendingError {
@@ -80,10 +81,10 @@ impl Future for SendClientRequest {
                 let res = res.map(|mut res| {
                     if *_response_decompress {
                         let payload = res.take_payload();
-                        res.set_payload(Payload::from_stream(Decoder::from_headers(
-                            payload,
-                            &res.head.headers,
-                        )))
+                        let decoder = Decoder::from_headers(payload, &res.head.headers)
+                            .max_size(res.config.decompress_limit)
+                            .max_ratio(res.config.decompress_ratio);
+                        res.set_payload(Payload::from_stream(decoder))
                     }
                     res
                 });
@@ -131,14 +132,30 @@ impl RequestHeadType {
         if timeout.is_zero() {
             timeout = config.timeout;
         }
-        let body = body.into();
+        let mut body = body.into();
+
+        let hooks = self.as_ref().extensions().get::<ProgressHooks>().cloned();
+        if let Some(hook) = hooks.as_ref().and_then(|h| h.upload.clone()) {
+            body = progress::upload(body, hook);
+        }
 
         let fut = Box::pin(async move {
-            config
+            let mut res = config
                 .clone()
                 .connector
                 .send_request(self, body, addr, timeout, config)
-                .await
+                .await?;
+
+            if let Some(hook) = hooks.and_then(|h| h.download) {
+                let total = res
+                    .headers()
+                    .get(&header::CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok());
+                let payload = progress::download(res.take_payload(), total, hook);
+                res.set_payload(payload);
+            }
+            Ok(res)
         });
 
         SendClientRequest::new(fut, response_decompress)
@@ -199,6 +216,28 @@ impl RequestHeadType {
         )
     }
 
+    pub(super) fn send_multipart(
+        mut self,
+        addr: Option<net::SocketAddr>,
+        response_decompress: bool,
+        timeout: Millis,
+        config: Rc<ClientConfig>,
+        body: ClientMultipart,
+    ) -> SendClientRequest {
+        // set content-type
+        if let Err(e) = self.set_header_if_none(header::CONTENT_TYPE, body.content_type()) {
+            return e.into();
+        }
+
+        self.send_body(
+            addr,
+            response_decompress,
+            timeout,
+            config,
+            Body::from_message(body),
+        )
+    }
+
     pub(super) fn send_stream<S, E>(
         self,
         addr: Option<net::SocketAddr>,
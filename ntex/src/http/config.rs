@@ -2,9 +2,14 @@ use std::{cell::Cell, ptr::copy_nonoverlapping, rc::Rc, time};
 
 use ntex_h2::{self as h2};
 
+use super::h1::{DecoderConfig, FlushStrategy, Strict};
 use crate::time::{sleep, Millis, Seconds};
 use crate::{service::Pipeline, util::BytesMut};
 
+use super::header::{self, HeaderValue};
+use super::hooks::{DispatcherHooks, Hooks};
+use super::{error::DecodeError, HeaderMap, StatusCode};
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 /// Server keep-alive setting
 pub enum KeepAlive {
@@ -48,12 +53,23 @@ pub struct ServiceConfig {
     pub(super) h2config: h2::Config,
     pub(super) headers_read_rate: Option<ReadRate>,
     pub(super) payload_read_rate: Option<ReadRate>,
+    pub(super) payload_min_rate: Option<ReadRate>,
+    pub(super) headers_timeout: Seconds,
+    pub(super) h1_decoder: DecoderConfig,
+    pub(super) max_requests: usize,
+    pub(super) max_lifetime: Seconds,
+    pub(super) alt_svc: Option<HeaderValue>,
+    pub(super) h2c: bool,
+    pub(super) max_payload_size: u64,
+    pub(super) lazy_continue: bool,
+    pub(super) h1_flush: FlushStrategy,
+    pub(super) hooks: Hooks,
     pub(super) timer: DateService,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(super) struct ReadRate {
-    pub(super) rate: u16,
+    pub(super) rate: u32,
     pub(super) timeout: Seconds,
     pub(super) max_timeout: Seconds,
 }
@@ -68,6 +84,73 @@ impl Default for ReadRate {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+/// Request headers limits
+pub(crate) struct Limits {
+    pub(crate) max_headers: usize,
+    pub(crate) max_header_size: usize,
+    pub(crate) max_headers_size: usize,
+    pub(crate) max_uri_length: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_headers: 96,
+            max_header_size: 0,
+            max_headers_size: 32_768,
+            max_uri_length: 0,
+        }
+    }
+}
+
+impl Limits {
+    /// Check size of parsed http/1 message head
+    pub(crate) fn check_headers(
+        &self,
+        len: usize,
+        headers: &[httparse::Header<'_>],
+    ) -> Result<(), DecodeError> {
+        if len > self.max_headers_size {
+            log::debug!("Max headers size is reached: {}", len);
+            return Err(DecodeError::TooLarge(len));
+        }
+        if self.max_header_size != 0 {
+            for hdr in headers {
+                let size = hdr.name.len() + hdr.value.len();
+                if size > self.max_header_size {
+                    log::debug!("Max header size is reached: {:?} {}", hdr.name, size);
+                    return Err(DecodeError::TooLarge(size));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check http/2 request head, returns error response status
+    pub(crate) fn check_h2(&self, path: &str, headers: &HeaderMap) -> Option<StatusCode> {
+        if self.max_uri_length != 0 && path.len() > self.max_uri_length {
+            return Some(StatusCode::URI_TOO_LONG);
+        }
+
+        let mut num = 0;
+        let mut total = path.len();
+        for (name, value) in headers.iter() {
+            let size = name.as_str().len() + value.len();
+            if self.max_header_size != 0 && size > self.max_header_size {
+                return Some(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+            }
+            num += 1;
+            total += size;
+        }
+        if num > self.max_headers || total > self.max_headers_size {
+            Some(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE)
+        } else {
+            None
+        }
+    }
+}
+
 impl Default for ServiceConfig {
     fn default() -> Self {
         Self::new(
@@ -113,6 +196,17 @@ impl ServiceConfig {
                 max_timeout: client_timeout + Seconds(15),
             }),
             payload_read_rate: None,
+            payload_min_rate: None,
+            headers_timeout: Seconds::ZERO,
+            h1_decoder: DecoderConfig::default(),
+            max_requests: 0,
+            max_lifetime: Seconds::ZERO,
+            alt_svc: None,
+            h2c: false,
+            max_payload_size: 0,
+            lazy_continue: false,
+            h1_flush: FlushStrategy::Coalesce,
+            hooks: Hooks::default(),
         }
     }
 
@@ -157,6 +251,30 @@ impl ServiceConfig {
         self
     }
 
+    /// Set max number of requests served per connection.
+    ///
+    /// Once the limit is reached, http/1 connection responds with
+    /// `Connection: close` header and http/2 connection sends `GOAWAY` frame.
+    /// Connection get closed after in-flight requests are completed.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn keepalive_max_requests(&mut self, num: usize) -> &mut Self {
+        self.max_requests = num;
+        self
+    }
+
+    /// Set max lifetime of a connection.
+    ///
+    /// Once connection's lifetime is exceeded, it gets closed same way
+    /// as with `keepalive_max_requests()` limit. Idle http/2 connections
+    /// are closed immediately.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn keepalive_max_lifetime(&mut self, lifetime: Seconds) -> &mut Self {
+        self.max_lifetime = lifetime;
+        self
+    }
+
     /// Set connection disconnect timeout.
     ///
     /// Defines a timeout for disconnect connection. If a disconnect procedure does not complete
@@ -198,7 +316,7 @@ impl ServiceConfig {
     ) -> &mut Self {
         if !timeout.is_zero() {
             self.headers_read_rate = Some(ReadRate {
-                rate,
+                rate: rate as u32,
                 timeout,
                 max_timeout,
             });
@@ -223,7 +341,7 @@ impl ServiceConfig {
     ) -> &mut Self {
         if !timeout.is_zero() {
             self.payload_read_rate = Some(ReadRate {
-                rate,
+                rate: rate as u32,
                 timeout,
                 max_timeout,
             });
@@ -232,6 +350,188 @@ impl ServiceConfig {
         }
         self
     }
+
+    /// Set max time for receiving complete request head.
+    ///
+    /// Timeout starts when first bytes of the request are received and
+    /// is enforced regardless of headers read rate, so slow clients could
+    /// not extend headers reading indefinitely. Connection is closed with
+    /// `408 Request Timeout` response.
+    ///
+    /// To disable timeout set value to 0. By default timeout is disabled.
+    pub fn headers_timeout(&mut self, timeout: Seconds) -> &mut Self {
+        self.headers_timeout = timeout;
+        self
+    }
+
+    /// Set minimum transfer rate for request's payload.
+    ///
+    /// Payload read rate is checked every `window` period, if the client sends
+    /// less than `rate` bytes per second on average within the window,
+    /// request is terminated with `408 Request Timeout` response. Unlike
+    /// `payload_read_rate()`, payload reading is not limited in time
+    /// as long as the client keeps minimum rate. If set, minimum rate check
+    /// takes precedence over `payload_read_rate()` setting.
+    ///
+    /// To disable check set rate to 0. By default check is disabled.
+    pub fn payload_min_rate(&mut self, rate: u32, window: Seconds) -> &mut Self {
+        if rate != 0 && !window.is_zero() {
+            self.payload_min_rate = Some(ReadRate {
+                rate: rate.saturating_mul(window.0 as u32),
+                timeout: window,
+                max_timeout: Seconds::ZERO,
+            });
+        } else {
+            self.payload_min_rate = None;
+        }
+        self
+    }
+
+    /// Set strict parsing checks for http/1 requests.
+    ///
+    /// Requests that violate any of enabled checks get rejected
+    /// with 400 (Bad Request) error.
+    ///
+    /// By default strict checks are disabled.
+    pub fn h1_strict(&mut self, checks: Strict) -> &mut Self {
+        self.h1_decoder.strict = checks;
+        self
+    }
+
+    /// Set max number of request headers.
+    ///
+    /// Requests with more headers get rejected with
+    /// 431 (Request Header Fields Too Large) error.
+    ///
+    /// By default max number of headers is set to 96.
+    pub fn max_headers(&mut self, num: usize) -> &mut Self {
+        self.h1_decoder.limits.max_headers = num;
+        self
+    }
+
+    /// Set max size of individual request header (name and value).
+    ///
+    /// Requests with larger headers get rejected with
+    /// 431 (Request Header Fields Too Large) error.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn max_header_size(&mut self, size: usize) -> &mut Self {
+        self.h1_decoder.limits.max_header_size = size;
+        self
+    }
+
+    /// Set max total size of request head, including request line.
+    ///
+    /// Requests with larger head get rejected with
+    /// 431 (Request Header Fields Too Large) error.
+    ///
+    /// By default max size is set to 32Kb.
+    pub fn max_headers_size(&mut self, size: usize) -> &mut Self {
+        self.h1_decoder.limits.max_headers_size = size;
+        self
+    }
+
+    /// Set max length of request uri.
+    ///
+    /// Requests with longer uri get rejected with 414 (URI Too Long) error.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled,
+    /// request line size is limited by max headers size.
+    pub fn max_uri_length(&mut self, size: usize) -> &mut Self {
+        self.h1_decoder.limits.max_uri_length = size;
+        self
+    }
+
+    /// Advertise alternative services.
+    ///
+    /// Value is sent with `Alt-Svc` header in all responses, unless
+    /// response already contains `Alt-Svc` header. For example
+    /// `h3=":443"; ma=86400` advertises http/3 endpoint.
+    ///
+    /// By default alternative services are not advertised.
+    pub fn alt_svc(&mut self, value: HeaderValue) -> &mut Self {
+        self.alt_svc = Some(value);
+        self
+    }
+
+    /// Enable cleartext http/2 (h2c) with prior knowledge.
+    ///
+    /// If enabled, http service detects http/2 connection preface on
+    /// plain text connections and serves connection with http/2 dispatcher.
+    ///
+    /// `Upgrade: h2c` is not supported, such requests are served as regular
+    /// http/1.1 requests and `101 Switching Protocols` is never sent. Upgrade
+    /// mechanism is deprecated by RFC 9113.
+    ///
+    /// h2c requires both http/1 and http/2 dispatchers, http/1 only
+    /// service fails to start if h2c is enabled.
+    ///
+    /// By default h2c is disabled.
+    pub fn h2c(&mut self, enabled: bool) -> &mut Self {
+        self.h2c = enabled;
+        self
+    }
+
+    /// Set max size of request payload.
+    ///
+    /// Requests with `Content-Length` header value larger than the limit
+    /// get rejected with 413 (Payload Too Large) error before payload is read.
+    /// For requests with `Expect: 100-continue` header, `100 Continue`
+    /// response is not sent, so client does not send payload at all.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn max_payload_size(&mut self, size: u64) -> &mut Self {
+        self.max_payload_size = size;
+        self
+    }
+
+    /// Defer `100 Continue` response until service starts reading request payload.
+    ///
+    /// By default, http/1 dispatcher sends `100 Continue` response right after
+    /// control service acks expect request. With lazy continue, the interim
+    /// response is sent only when service reads request payload, so guards and
+    /// middlewares could reject request (for example with 417 or 413 errors)
+    /// before client sends payload. If response is sent without reading payload,
+    /// connection get closed after response.
+    ///
+    /// By default lazy continue is disabled.
+    pub fn h1_lazy_continue(&mut self, enabled: bool) -> &mut Self {
+        self.lazy_continue = enabled;
+        self
+    }
+
+    /// Set response write flush strategy for http/1 connections.
+    ///
+    /// Controls whether dispatcher waits for response to be written to
+    /// the socket before reading next pipelined request.
+    ///
+    /// By default responses are coalesced.
+    pub fn h1_flush_strategy(&mut self, strategy: FlushStrategy) -> &mut Self {
+        self.h1_flush = strategy;
+        self
+    }
+
+    /// Set max size of chunk extensions for http/1 requests.
+    ///
+    /// Limit is applied only if `Strict::CHUNK_EXTENSION` check is enabled.
+    ///
+    /// By default max size is set to 1Kb.
+    pub fn h1_max_chunk_extension(&mut self, size: usize) -> &mut Self {
+        self.h1_decoder.max_chunk_extension = size;
+        self
+    }
+
+    /// Set dispatcher instrumentation hooks.
+    ///
+    /// Hooks get notified about connection start and end, parsed
+    /// request heads, queued response heads and written response bodies
+    /// for both http/1 and http/2 connections.
+    ///
+    /// By default hooks are not set.
+    pub fn dispatcher_hooks<T: DispatcherHooks>(&mut self, hooks: T) -> &mut Self {
+        self.hooks = Hooks::new(hooks);
+        self
+    }
 }
 
 pub(super) struct DispatcherConfig<S, C> {
@@ -243,6 +543,17 @@ pub(super) struct DispatcherConfig<S, C> {
     pub(super) ka_enabled: bool,
     pub(super) headers_read_rate: Option<ReadRate>,
     pub(super) payload_read_rate: Option<ReadRate>,
+    pub(super) payload_min_rate: Option<ReadRate>,
+    pub(super) headers_timeout: Seconds,
+    pub(super) h1_decoder: DecoderConfig,
+    pub(super) max_requests: usize,
+    pub(super) max_lifetime: Seconds,
+    pub(super) alt_svc: Option<HeaderValue>,
+    pub(super) h2c: bool,
+    pub(super) max_payload_size: u64,
+    pub(super) lazy_continue: bool,
+    pub(super) h1_flush: FlushStrategy,
+    pub(super) hooks: Hooks,
     pub(super) timer: DateService,
 }
 
@@ -256,6 +567,17 @@ impl<S, C> DispatcherConfig<S, C> {
             ka_enabled: cfg.ka_enabled,
             headers_read_rate: cfg.headers_read_rate,
             payload_read_rate: cfg.payload_read_rate,
+            payload_min_rate: cfg.payload_min_rate,
+            headers_timeout: cfg.headers_timeout,
+            h1_decoder: cfg.h1_decoder,
+            max_requests: cfg.max_requests,
+            max_lifetime: cfg.max_lifetime,
+            alt_svc: cfg.alt_svc.clone(),
+            h2c: cfg.h2c,
+            max_payload_size: cfg.max_payload_size,
+            lazy_continue: cfg.lazy_continue,
+            h1_flush: cfg.h1_flush,
+            hooks: cfg.hooks.clone(),
             h2config: cfg.h2config.clone(),
             timer: cfg.timer.clone(),
         }
@@ -269,6 +591,44 @@ impl<S, C> DispatcherConfig<S, C> {
     pub(super) fn headers_read_rate(&self) -> Option<&ReadRate> {
         self.headers_read_rate.as_ref()
     }
+
+    pub(super) fn payload_read_rate(&self) -> Option<&ReadRate> {
+        self.payload_min_rate
+            .as_ref()
+            .or(self.payload_read_rate.as_ref())
+    }
+
+    /// Check if connection reached max requests or max lifetime limits
+    pub(super) fn conn_expired(&self, requests: usize, created: time::Instant) -> bool {
+        (self.max_requests != 0 && requests >= self.max_requests)
+            || (!self.max_lifetime.is_zero()
+                && crate::time::now() - created >= self.max_lifetime.into())
+    }
+
+    /// Add `Alt-Svc` header to response headers
+    pub(super) fn set_alt_svc(&self, headers: &mut HeaderMap) {
+        if let Some(ref value) = self.alt_svc {
+            if !headers.contains_key(header::ALT_SVC) {
+                headers.insert(header::ALT_SVC, value.clone());
+            }
+        }
+    }
+
+    /// Check if request's declared payload size exceeds the limit
+    pub(super) fn payload_too_large(&self, headers: &HeaderMap) -> bool {
+        self.max_payload_size != 0
+            && headers
+                .get(header::CONTENT_LENGTH)
+                .and_then(|val| val.to_str().ok())
+                .and_then(|val| val.trim().parse::<u64>().ok())
+                .map(|len| len > self.max_payload_size)
+                .unwrap_or(false)
+    }
+
+    /// Request headers limits
+    pub(super) fn limits(&self) -> &Limits {
+        &self.h1_decoder.limits
+    }
 }
 
 const DATE_VALUE_LENGTH_HDR: usize = 39;
@@ -308,8 +668,7 @@ impl DateServiceInner {
         self.current_time.set(time::Instant::now());
 
         let mut bytes = DATE_VALUE_DEFAULT;
-        let dt = httpdate::HttpDate::from(time::SystemTime::now()).to_string();
-        bytes[6..35].copy_from_slice(dt.as_ref());
+        bytes[6..35].copy_from_slice(ntex_http::date::now_fmt().as_bytes());
         self.current_date.set(bytes);
     }
 }
@@ -378,4 +737,31 @@ mod tests {
             Option::<usize>::Some(10).into()
         );
     }
+
+    #[test]
+    fn payload_min_rate() {
+        let mut cfg = ServiceConfig::default();
+        cfg.payload_read_rate(Seconds(1), Seconds(5), 128);
+        cfg.payload_min_rate(64, Seconds(2));
+        assert_eq!(
+            cfg.payload_min_rate,
+            Some(ReadRate {
+                rate: 128,
+                timeout: Seconds(2),
+                max_timeout: Seconds::ZERO,
+            })
+        );
+
+        // disabling min rate keeps payload read rate
+        cfg.payload_min_rate(0, Seconds(2));
+        assert_eq!(cfg.payload_min_rate, None);
+        assert_eq!(
+            cfg.payload_read_rate,
+            Some(ReadRate {
+                rate: 128,
+                timeout: Seconds(1),
+                max_timeout: Seconds(5),
+            })
+        );
+    }
 }
@@ -0,0 +1,193 @@
+use std::task::{Context, Poll, Waker};
+use std::{cell::Cell, cell::RefCell, future::Future, pin::Pin, rc::Rc};
+
+use crate::io::OnDisconnect;
+use crate::sync::{CancellationToken, DropGuard};
+use crate::util::{select, Either};
+
+use super::message::RequestHead;
+
+#[derive(Debug)]
+/// Client disconnect notification
+///
+/// Future resolves when the client of the current request goes away.
+/// For http/1 it resolves when underlying connection get closed,
+/// for http/2 it resolves when connection get closed or when request's
+/// stream get reset by the peer.
+///
+/// Long-running handlers could use it to abort expensive work early.
+#[must_use = "Disconnected do nothing unless polled"]
+pub struct Disconnected {
+    io: Option<OnDisconnect>,
+    stream: Option<Rc<StreamReset>>,
+}
+
+impl Disconnected {
+    pub(crate) fn new(head: &RequestHead) -> Self {
+        Disconnected {
+            io: head.io.as_ref().map(|io| io.on_disconnect()),
+            stream: head.extensions().get::<Rc<StreamReset>>().cloned(),
+        }
+    }
+
+    #[inline]
+    /// Check if client is disconnected
+    ///
+    /// If request is not bound to any connection (ie test requests),
+    /// it never resolves.
+    pub fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(ref io) = self.io {
+            if io.poll_ready(cx).is_ready() {
+                return Poll::Ready(());
+            }
+        }
+        if let Some(ref stream) = self.stream {
+            if stream.poll_reset(cx).is_ready() {
+                return Poll::Ready(());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Clone for Disconnected {
+    fn clone(&self) -> Self {
+        Disconnected {
+            io: self.io.clone(),
+            stream: self.stream.clone(),
+        }
+    }
+}
+
+impl Future for Disconnected {
+    type Output = ();
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.poll_ready(cx)
+    }
+}
+
+/// Request cancellation token
+///
+/// Token is cancelled when the client of the request goes away.
+pub(crate) fn cancellation_token(head: &RequestHead) -> CancellationToken {
+    if let Some(cancel) = head.extensions().get::<RequestCancel>() {
+        return cancel.token.child_token();
+    }
+
+    let token = CancellationToken::new();
+    let stop = CancellationToken::new();
+    let disconnected = Disconnected::new(head);
+    let (tok, st) = (token.clone(), stop.clone());
+    let _ = crate::rt::spawn(async move {
+        if let Either::Left(_) = select(disconnected, st.cancelled()).await {
+            tok.cancel();
+        }
+    });
+
+    let child = token.child_token();
+    head.extensions_mut().insert(RequestCancel {
+        token,
+        _stop: stop.drop_guard(),
+    });
+    child
+}
+
+/// Per-request cancellation state, disconnect watcher stops
+/// when request is released
+struct RequestCancel {
+    token: CancellationToken,
+    _stop: DropGuard,
+}
+
+#[derive(Debug, Default)]
+/// Per-stream reset state
+pub(crate) struct StreamReset {
+    reset: Cell<bool>,
+    wakers: RefCell<Vec<Waker>>,
+}
+
+impl StreamReset {
+    /// Mark stream as reset and notify all waiters
+    pub(crate) fn set(&self) {
+        self.reset.set(true);
+        for waker in self.wakers.borrow_mut().drain(..) {
+            waker.wake();
+        }
+    }
+
+    fn poll_reset(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.reset.get() {
+            Poll::Ready(())
+        } else {
+            let mut wakers = self.wakers.borrow_mut();
+            if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{message::CurrentIo, Request};
+    use crate::{io::Io, testing::IoTest, util::lazy};
+
+    #[crate::rt_test]
+    async fn test_io_disconnected() {
+        let (client, server) = IoTest::create();
+        let io = Io::new(server);
+
+        let mut req = Request::new();
+        req.head_mut().io = CurrentIo::Ref(io.get_ref());
+
+        let disconnected = req.disconnected();
+        assert!(lazy(|cx| disconnected.poll_ready(cx)).await.is_pending());
+        let disconnected2 = disconnected.clone();
+
+        client.close().await;
+        disconnected.await;
+        disconnected2.await;
+    }
+
+    #[crate::rt_test]
+    async fn test_stream_reset() {
+        let reset = Rc::new(StreamReset::default());
+        let req = Request::new();
+        req.extensions_mut().insert(reset.clone());
+
+        let disconnected = req.disconnected();
+        assert!(lazy(|cx| disconnected.poll_ready(cx)).await.is_pending());
+        assert!(lazy(|cx| Request::new().disconnected().poll_ready(cx))
+            .await
+            .is_pending());
+
+        reset.set();
+        disconnected.await;
+    }
+
+    #[crate::rt_test]
+    async fn test_cancellation_token() {
+        let reset = Rc::new(StreamReset::default());
+        let req = Request::new();
+        req.extensions_mut().insert(reset.clone());
+
+        let token = req.cancellation_token();
+        let token2 = req.cancellation_token();
+        assert!(!token.is_cancelled());
+        crate::time::sleep(crate::time::Millis(10)).await;
+        assert!(!token.is_cancelled());
+
+        reset.set();
+        token.cancelled().await;
+        assert!(token2.is_cancelled());
+
+        // cancelling child token does not cancel the request
+        let req = Request::new();
+        req.cancellation_token().cancel();
+        assert!(!req.cancellation_token().is_cancelled());
+    }
+}
@@ -3,8 +3,10 @@ use std::{future::Future, io, io::Write, pin::Pin, task::Context, task::Poll};
 #[cfg(feature = "brotli")]
 use brotli2::write::BrotliDecoder;
 use flate2::write::{GzDecoder, ZlibDecoder};
+#[cfg(feature = "zstd")]
+use zstd::stream::write::Decoder as ZstdDecoder;
 
-use super::Writer;
+use super::{LimitExceeded, Writer};
 use crate::http::error::PayloadError;
 use crate::http::header::{ContentEncoding, HeaderMap, CONTENT_ENCODING};
 use crate::rt::{spawn_blocking, JoinHandle};
@@ -12,11 +14,17 @@ use crate::util::{Bytes, Stream};
 
 const INPLACE: usize = 2049;
 
+/// Compression ratio is checked only after decoded size reaches this size
+const RATIO_THRESHOLD: u64 = 65_536;
+
 pub struct Decoder<S> {
     decoder: Option<ContentDecoder>,
     stream: S,
     eof: bool,
     fut: Option<JoinHandle<Result<(Option<Bytes>, ContentDecoder), io::Error>>>,
+    max_size: usize,
+    max_ratio: usize,
+    encoded: u64,
 }
 
 impl<S> Decoder<S>
@@ -37,6 +45,10 @@ where
             ContentEncoding::Gzip => Some(ContentDecoder::Gzip(Box::new(GzDecoder::new(
                 Writer::new(),
             )))),
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => ZstdDecoder::new(Writer::new())
+                .ok()
+                .map(|decoder| ContentDecoder::Zstd(Box::new(decoder))),
             _ => None,
         };
         Decoder {
@@ -44,6 +56,63 @@ where
             stream,
             fut: None,
             eof: false,
+            max_size: 0,
+            max_ratio: 0,
+            encoded: 0,
+        }
+    }
+
+    /// Set max size of decoded payload.
+    ///
+    /// Decoding stops once decoded payload reaches limit and
+    /// `PayloadError::Overflow` is returned. By default size is not limited.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    /// Set max ratio of decoded size to encoded size.
+    ///
+    /// Protects from decompression bombs, ratio is checked once decoded
+    /// payload exceeds 64Kb. Decoding stops once ratio is reached and
+    /// `PayloadError::Overflow` is returned. By default ratio is not limited.
+    pub fn max_ratio(mut self, ratio: usize) -> Self {
+        self.max_ratio = ratio;
+        self
+    }
+
+    /// Max decoded size for currently encoded size
+    fn limit(&self) -> u64 {
+        let size = if self.max_size > 0 {
+            self.max_size as u64
+        } else {
+            u64::MAX
+        };
+        let ratio = if self.max_ratio > 0 {
+            std::cmp::max(
+                RATIO_THRESHOLD,
+                self.encoded.saturating_mul(self.max_ratio as u64),
+            )
+        } else {
+            u64::MAX
+        };
+        std::cmp::min(size, ratio)
+    }
+
+    /// Stop decoding after error
+    fn error(&mut self, err: io::Error) -> PayloadError {
+        self.eof = true;
+        self.decoder = None;
+
+        if err
+            .get_ref()
+            .map(|e| e.is::<LimitExceeded>())
+            .unwrap_or(false)
+        {
+            log::debug!("Decoded payload overflow, encoded {} bytes", self.encoded);
+            PayloadError::Overflow
+        } else {
+            err.into()
         }
     }
 
@@ -79,7 +148,10 @@ where
             if let Some(ref mut fut) = self.fut {
                 let (chunk, decoder) = match Pin::new(fut).poll(cx) {
                     Poll::Ready(Ok(Ok(item))) => item,
-                    Poll::Ready(Ok(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                    Poll::Ready(Ok(Err(e))) => {
+                        self.fut.take();
+                        return Poll::Ready(Some(Err(self.error(e))));
+                    }
                     Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e.into()))),
                     Poll::Pending => return Poll::Pending,
                 };
@@ -98,11 +170,17 @@ where
                 Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
                 Poll::Ready(Some(Ok(chunk))) => {
                     if let Some(mut decoder) = self.decoder.take() {
+                        self.encoded += chunk.len() as u64;
+                        decoder.writer().limit = self.limit();
                         if chunk.len() < INPLACE {
-                            let chunk = decoder.feed_data(chunk)?;
-                            self.decoder = Some(decoder);
-                            if let Some(chunk) = chunk {
-                                return Poll::Ready(Some(Ok(chunk)));
+                            match decoder.feed_data(chunk) {
+                                Ok(chunk) => {
+                                    self.decoder = Some(decoder);
+                                    if let Some(chunk) = chunk {
+                                        return Poll::Ready(Some(Ok(chunk)));
+                                    }
+                                }
+                                Err(e) => return Poll::Ready(Some(Err(self.error(e)))),
                             }
                         } else {
                             self.fut = Some(spawn_blocking(move || {
@@ -121,7 +199,7 @@ where
                         match decoder.feed_eof() {
                             Ok(Some(res)) => Poll::Ready(Some(Ok(res))),
                             Ok(None) => Poll::Ready(None),
-                            Err(err) => Poll::Ready(Some(Err(err.into()))),
+                            Err(err) => Poll::Ready(Some(Err(self.error(err)))),
                         }
                     } else {
                         Poll::Ready(None)
@@ -139,9 +217,22 @@ enum ContentDecoder {
     Gzip(Box<GzDecoder<Writer>>),
     #[cfg(feature = "brotli")]
     Br(Box<BrotliDecoder<Writer>>),
+    #[cfg(feature = "zstd")]
+    Zstd(Box<ZstdDecoder<'static, Writer>>),
 }
 
 impl ContentDecoder {
+    fn writer(&mut self) -> &mut Writer {
+        match self {
+            #[cfg(feature = "brotli")]
+            ContentDecoder::Br(ref mut decoder) => decoder.get_mut(),
+            #[cfg(feature = "zstd")]
+            ContentDecoder::Zstd(ref mut decoder) => decoder.get_mut(),
+            ContentDecoder::Gzip(ref mut decoder) => decoder.get_mut(),
+            ContentDecoder::Deflate(ref mut decoder) => decoder.get_mut(),
+        }
+    }
+
     fn feed_eof(&mut self) -> io::Result<Option<Bytes>> {
         match self {
             #[cfg(feature = "brotli")]
@@ -156,6 +247,18 @@ impl ContentDecoder {
                 }
                 Err(e) => Err(e),
             },
+            #[cfg(feature = "zstd")]
+            ContentDecoder::Zstd(ref mut decoder) => match decoder.flush() {
+                Ok(()) => {
+                    let b = decoder.get_mut().take();
+                    if !b.is_empty() {
+                        Ok(Some(b))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(e) => Err(e),
+            },
             ContentDecoder::Gzip(ref mut decoder) => match decoder.try_finish() {
                 Ok(_) => {
                     let b = decoder.get_mut().take();
@@ -196,6 +299,19 @@ impl ContentDecoder {
                 }
                 Err(e) => Err(e),
             },
+            #[cfg(feature = "zstd")]
+            ContentDecoder::Zstd(ref mut decoder) => match decoder.write_all(&data) {
+                Ok(_) => {
+                    decoder.flush()?;
+                    let b = decoder.get_mut().take();
+                    if !b.is_empty() {
+                        Ok(Some(b))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(e) => Err(e),
+            },
             ContentDecoder::Gzip(ref mut decoder) => match decoder.write_all(&data) {
                 Ok(_) => {
                     decoder.flush()?;
@@ -223,3 +339,51 @@ impl ContentDecoder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{future::poll_fn, io::Write};
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+    use crate::channel::mpsc;
+
+    fn gzip(data: &[u8]) -> Bytes {
+        let mut enc = GzEncoder::new(Vec::new(), Compression::best());
+        enc.write_all(data).unwrap();
+        Bytes::from(enc.finish().unwrap())
+    }
+
+    #[crate::rt_test]
+    async fn test_decoder_limits() {
+        // decoded data is limited while single chunk is decoded
+        let (tx, rx) = mpsc::channel();
+        let _ = tx.send(Ok(gzip(&[0; 1024 * 1024])));
+        let mut decoder = Decoder::new(rx, ContentEncoding::Gzip).max_size(1024);
+        let res = poll_fn(|cx| Pin::new(&mut decoder).poll_next(cx)).await;
+        assert!(matches!(res, Some(Err(PayloadError::Overflow))));
+        let _ = tx.send(Ok(Bytes::from_static(b"data")));
+        assert!(poll_fn(|cx| Pin::new(&mut decoder).poll_next(cx))
+            .await
+            .is_none());
+
+        let (tx, rx) = mpsc::channel();
+        let _ = tx.send(Ok(gzip(&[0; 1024 * 1024])));
+        let mut decoder = Decoder::new(rx, ContentEncoding::Gzip).max_ratio(10);
+        let res = poll_fn(|cx| Pin::new(&mut decoder).poll_next(cx)).await;
+        assert!(matches!(res, Some(Err(PayloadError::Overflow))));
+
+        let (tx, rx) = mpsc::channel();
+        let _ = tx.send(Ok(gzip(b"hello world")));
+        drop(tx);
+        let mut decoder = Decoder::new(rx, ContentEncoding::Gzip)
+            .max_size(1024)
+            .max_ratio(10);
+        let res = poll_fn(|cx| Pin::new(&mut decoder).poll_next(cx)).await;
+        assert_eq!(res.unwrap().unwrap(), Bytes::from_static(b"hello world"));
+        assert!(poll_fn(|cx| Pin::new(&mut decoder).poll_next(cx))
+            .await
+            .is_none());
+    }
+}
@@ -11,12 +11,16 @@ pub use self::encoder::Encoder;
 
 struct Writer {
     buf: BytesMut,
+    written: u64,
+    limit: u64,
 }
 
 impl Writer {
     fn new() -> Writer {
         Writer {
             buf: BytesMut::with_capacity(8192),
+            written: 0,
+            limit: u64::MAX,
         }
     }
 
@@ -27,6 +31,12 @@ impl Writer {
 
 impl io::Write for Writer {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // stop decoder before decoded data exceeds limit
+        let written = self.written + buf.len() as u64;
+        if written > self.limit {
+            return Err(io::Error::new(io::ErrorKind::Other, LimitExceeded));
+        }
+        self.written = written;
         self.buf.extend_from_slice(buf);
         Ok(buf.len())
     }
@@ -35,3 +45,15 @@ impl io::Write for Writer {
         Ok(())
     }
 }
+
+/// Decoded data exceeds decoder limits
+#[derive(Debug)]
+struct LimitExceeded;
+
+impl std::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Decoded payload limit exceeded")
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
@@ -81,6 +81,9 @@ pub enum DecodeError {
     /// An invalid `Uri`, such as `exam ple.domain`.
     #[error("Uri error: {0}")]
     Uri(#[from] InvalidUri),
+    /// A request uri is too long.
+    #[error("Uri is too long")]
+    UriTooLong,
     /// An invalid `HttpVersion`, such as `HTP/1.1`
     #[error("Invalid HTTP version specified")]
     Version,
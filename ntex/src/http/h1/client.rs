@@ -19,6 +19,7 @@ bitflags! {
         const HEAD              = 0b0000_0001;
         const KEEPALIVE_ENABLED = 0b0000_1000;
         const STREAM            = 0b0001_0000;
+        const ABSOLUTE_FORM     = 0b0010_0000;
     }
 }
 
@@ -76,6 +77,15 @@ impl ClientCodec {
         }
     }
 
+    /// Send request uri in absolute form.
+    ///
+    /// Absolute form is required for requests sent to http proxy.
+    pub fn set_absolute_form(&self) {
+        let mut flags = self.inner.flags.get();
+        flags.insert(Flags::ABSOLUTE_FORM);
+        self.inner.flags.set(flags);
+    }
+
     /// Check if request is upgrade
     pub fn upgrade(&self) -> bool {
         self.inner.ctype.get() == ConnectionType::Upgrade
@@ -220,6 +230,7 @@ impl Encoder for ClientCodec {
                     &mut head,
                     false,
                     false,
+                    inner.flags.get().contains(Flags::ABSOLUTE_FORM),
                     inner.version.get(),
                     length,
                     inner.ctype.get(),
@@ -99,6 +99,12 @@ impl Codec {
         self.ctype.get() == ConnectionType::KeepAlive
     }
 
+    /// Set request decoder configuration
+    pub(super) fn decoder_config(mut self, cfg: decoder::DecoderConfig) -> Self {
+        self.decoder = decoder::MessageDecoder::new(cfg);
+        self
+    }
+
     pub(super) fn set_ctype(&self, ctype: ConnectionType) {
         self.ctype.set(ctype)
     }
@@ -176,6 +182,7 @@ impl Encoder for Codec {
                     &mut res,
                     self.flags.get().contains(Flags::HEAD),
                     self.flags.get().contains(Flags::STREAM),
+                    false,
                     self.version.get(),
                     length,
                     self.ctype.get(),
@@ -4,19 +4,74 @@ use ntex_http::header::{HeaderName, HeaderValue};
 use ntex_http::{header, Method, StatusCode, Uri, Version};
 
 use crate::codec::Decoder;
+use crate::http::config::Limits;
 use crate::http::error::DecodeError;
 use crate::http::header::HeaderMap;
 use crate::http::message::{ConnectionType, ResponseHead};
 use crate::http::request::Request;
 use crate::util::{Buf, Bytes, BytesMut};
 
-use super::MAX_BUFFER_SIZE;
+use super::scan;
 
 const MAX_HEADERS: usize = 96;
+const MAX_CHUNK_EXTENSION: usize = 1024;
+
+bitflags::bitflags! {
+    #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+    /// Strict http/1 parsing checks
+    ///
+    /// Each check rejects messages that could be interpreted differently
+    /// by intermediaries, it protects from request smuggling in mixed proxy chains.
+    pub struct Strict: u8 {
+        /// Reject messages with both `Content-Length` and `Transfer-Encoding` headers
+        const CONFLICTING_LENGTH = 0b0000_0001;
+        /// Reject headers with obsolete line folding
+        const OBS_FOLD           = 0b0000_0010;
+        /// Reject `CR` that is not followed by `LF`
+        const BARE_CR            = 0b0000_0100;
+        /// Reject `LF` that is not preceded by `CR`
+        const BARE_LF            = 0b0000_1000;
+        /// Reject chunk extensions larger than configured limit
+        const CHUNK_EXTENSION    = 0b0001_0000;
+        /// Reject messages with multiple `Host` headers
+        const MULTIPLE_HOST      = 0b0010_0000;
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+/// Http/1 message decoder configuration
+pub(crate) struct DecoderConfig {
+    pub(crate) strict: Strict,
+    pub(crate) max_chunk_extension: usize,
+    pub(crate) limits: Limits,
+}
+
+impl Default for DecoderConfig {
+    fn default() -> Self {
+        DecoderConfig {
+            strict: Strict::empty(),
+            max_chunk_extension: MAX_CHUNK_EXTENSION,
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl DecoderConfig {
+    fn max_chunk_extension(&self) -> usize {
+        if self.strict.contains(Strict::CHUNK_EXTENSION) {
+            self.max_chunk_extension
+        } else {
+            0
+        }
+    }
+}
 
 #[derive(Debug)]
 /// Incoming messagd decoder
-pub(super) struct MessageDecoder<T: MessageType>(PhantomData<T>);
+pub(super) struct MessageDecoder<T: MessageType> {
+    cfg: DecoderConfig,
+    _t: PhantomData<T>,
+}
 
 #[derive(Debug, PartialEq, Eq)]
 /// Incoming request type
@@ -28,13 +83,22 @@ pub enum PayloadType {
 
 impl<T: MessageType> Default for MessageDecoder<T> {
     fn default() -> Self {
-        MessageDecoder(PhantomData)
+        MessageDecoder::new(DecoderConfig::default())
     }
 }
 
 impl<T: MessageType> Clone for MessageDecoder<T> {
     fn clone(&self) -> Self {
-        MessageDecoder(PhantomData)
+        MessageDecoder::new(self.cfg)
+    }
+}
+
+impl<T: MessageType> MessageDecoder<T> {
+    pub(super) fn new(cfg: DecoderConfig) -> Self {
+        MessageDecoder {
+            cfg,
+            _t: PhantomData,
+        }
     }
 }
 
@@ -43,7 +107,7 @@ impl<T: MessageType> Decoder for MessageDecoder<T> {
     type Error = DecodeError;
 
     fn decode(&self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        T::decode(src)
+        T::decode(src, &self.cfg)
     }
 }
 
@@ -57,6 +121,8 @@ pub(super) enum PayloadLength {
 #[allow(clippy::declare_interior_mutable_const)]
 const ZERO: PayloadLength = PayloadLength::Payload(PayloadType::Payload(PayloadDecoder {
     kind: Cell::new(Kind::Length(0)),
+    ext: Cell::new(0),
+    max_ext: 0,
 }));
 
 impl PayloadLength {
@@ -78,19 +144,26 @@ pub(super) trait MessageType: Sized {
 
     fn headers_mut(&mut self) -> &mut HeaderMap;
 
-    fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, DecodeError>;
+    fn decode(
+        src: &mut BytesMut,
+        cfg: &DecoderConfig,
+    ) -> Result<Option<(Self, PayloadType)>, DecodeError>;
 
     fn set_headers(
         &mut self,
         slice: &Bytes,
         version: Version,
         raw_headers: &[HeaderIndex],
+        cfg: &DecoderConfig,
     ) -> Result<PayloadLength, DecodeError> {
         let mut ka = None;
         let mut has_upgrade = false;
+        let mut h2c = false;
         let mut expect = false;
         let mut chunked = false;
         let mut seen_te = false;
+        let mut has_te = false;
+        let mut has_host = false;
         let mut content_length = None;
 
         {
@@ -105,6 +178,16 @@ pub(super) trait MessageType: Sized {
                         slice.slice(idx.value.0..idx.value.1),
                     )
                 };
+                if name == header::TRANSFER_ENCODING {
+                    has_te = true;
+                }
+                if name == header::HOST {
+                    if has_host && cfg.strict.contains(Strict::MULTIPLE_HOST) {
+                        log::debug!("multiple Host headers are not allowed");
+                        return Err(DecodeError::Header);
+                    }
+                    has_host = true;
+                }
                 match name {
                     header::CONTENT_LENGTH if content_length.is_some() || chunked => {
                         log::debug!("multiple Content-Length not allowed");
@@ -168,10 +251,17 @@ pub(super) trait MessageType: Sized {
                         };
                     }
                     header::UPGRADE => {
-                        has_upgrade = true;
+                        let val = value.to_str().map(|val| val.trim());
+                        // h2c upgrade is deprecated by rfc9113,
+                        // serve request as regular http/1.1 request
+                        if val.map(|v| v.eq_ignore_ascii_case("h2c")).unwrap_or(false) {
+                            h2c = true;
+                        } else {
+                            has_upgrade = true;
+                        }
                         // check content-length, some clients (dart)
                         // sends "content-length: 0" with websocket upgrade
-                        if let Ok(val) = value.to_str().map(|val| val.trim()) {
+                        if let Ok(val) = val {
                             if val.eq_ignore_ascii_case("websocket") {
                                 content_length = None;
                             }
@@ -189,6 +279,17 @@ pub(super) trait MessageType: Sized {
                 headers.append(name, value);
             }
         }
+        if has_te
+            && content_length.is_some()
+            && cfg.strict.contains(Strict::CONFLICTING_LENGTH)
+        {
+            log::debug!("both Content-Length and Transfer-Encoding are not allowed");
+            return Err(DecodeError::Header);
+        }
+
+        if h2c && ka == Some(ConnectionType::Upgrade) {
+            ka = None;
+        }
         self.set_connection_type(ka);
         if expect {
             self.set_expect()
@@ -198,7 +299,7 @@ pub(super) trait MessageType: Sized {
         if chunked {
             // Chunked encoding
             Ok(PayloadLength::Payload(PayloadType::Payload(
-                PayloadDecoder::chunked(),
+                PayloadDecoder::chunked().max_extension(cfg.max_chunk_extension()),
             )))
         } else if let Some(len) = content_length {
             // Content-Length
@@ -228,20 +329,37 @@ impl MessageType for Request {
         &mut self.head_mut().headers
     }
 
-    fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, DecodeError> {
-        let mut headers: [mem::MaybeUninit<HeaderIndex>; MAX_HEADERS] = uninit_array();
+    fn decode(
+        src: &mut BytesMut,
+        cfg: &DecoderConfig,
+    ) -> Result<Option<(Self, PayloadType)>, DecodeError> {
+        let limits = &cfg.limits;
+        let mut headers_arr: [mem::MaybeUninit<HeaderIndex>; MAX_HEADERS] = uninit_array();
+        let mut headers_vec = Vec::new();
+        let headers = uninit_slice(&mut headers_arr, &mut headers_vec, limits.max_headers);
 
         let (len, method, uri, ver, headers) = {
-            let mut parsed: [mem::MaybeUninit<httparse::Header<'_>>; MAX_HEADERS] =
+            let mut parsed_arr: [mem::MaybeUninit<httparse::Header<'_>>; MAX_HEADERS] =
                 uninit_array();
+            let mut parsed_vec = Vec::new();
+            let parsed = uninit_slice(&mut parsed_arr, &mut parsed_vec, limits.max_headers);
 
             let mut req = httparse::Request::new(&mut []);
 
-            match req.parse_with_uninit_headers(src, &mut parsed)? {
+            match req.parse_with_uninit_headers(src, parsed)? {
                 httparse::Status::Complete(len) => {
+                    check_line_endings(&src[..len], cfg.strict)?;
+                    limits.check_headers(len, req.headers)?;
+
+                    let path = req.path.unwrap();
+                    if limits.max_uri_length != 0 && path.len() > limits.max_uri_length {
+                        log::debug!("Request uri is too long: {}", path.len());
+                        return Err(DecodeError::UriTooLong);
+                    }
+
                     let method = Method::from_bytes(req.method.unwrap().as_bytes())
                         .map_err(|_| DecodeError::Method)?;
-                    let uri = Uri::try_from(req.path.unwrap())?;
+                    let uri = Uri::try_from(path)?;
                     let version = if req.version.unwrap() == 1 {
                         Version::HTTP_11
                     } else {
@@ -253,12 +371,23 @@ impl MessageType for Request {
                         method,
                         uri,
                         version,
-                        HeaderIndex::record(src, req.headers, &mut headers),
+                        HeaderIndex::record(src, req.headers, headers),
                     )
                 }
                 httparse::Status::Partial => {
-                    if src.len() >= MAX_BUFFER_SIZE {
-                        log::trace!("MAX_BUFFER_SIZE unprocessed data reached, closing");
+                    if limits.max_uri_length != 0 && scan::find(src, b'\n').is_none() {
+                        // request line is not complete yet
+                        if let Some(pos) = scan::find(src, b' ') {
+                            if src.len() - pos - 1 > limits.max_uri_length {
+                                log::debug!("Request uri is too long");
+                                return Err(DecodeError::UriTooLong);
+                            }
+                        }
+                    }
+                    if src.len() >= limits.max_headers_size {
+                        log::trace!(
+                            "Max headers size of unprocessed data reached, closing"
+                        );
                         return Err(DecodeError::TooLarge(src.len()));
                     }
                     return Ok(None);
@@ -269,7 +398,7 @@ impl MessageType for Request {
         let mut msg = Request::new();
 
         // convert headers
-        let mut length = msg.set_headers(&src.split_to(len).freeze(), ver, headers)?;
+        let mut length = msg.set_headers(&src.split_to(len).freeze(), ver, headers, cfg)?;
 
         // disallow HTTP/1.0 POST requests that do not contain a Content-Length headers
         // see https://datatracker.ietf.org/doc/html/rfc1945#section-7.2.2
@@ -325,20 +454,29 @@ impl MessageType for ResponseHead {
         &mut self.headers
     }
 
-    fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, DecodeError> {
-        let mut headers: [mem::MaybeUninit<HeaderIndex>; MAX_HEADERS] = uninit_array();
+    fn decode(
+        src: &mut BytesMut,
+        cfg: &DecoderConfig,
+    ) -> Result<Option<(Self, PayloadType)>, DecodeError> {
+        let limits = &cfg.limits;
+        let mut headers_arr: [mem::MaybeUninit<HeaderIndex>; MAX_HEADERS] = uninit_array();
+        let mut headers_vec = Vec::new();
+        let headers = uninit_slice(&mut headers_arr, &mut headers_vec, limits.max_headers);
 
         let (len, ver, status, headers) = {
-            let mut parsed: [mem::MaybeUninit<httparse::Header<'_>>; MAX_HEADERS] =
+            let mut parsed_arr: [mem::MaybeUninit<httparse::Header<'_>>; MAX_HEADERS] =
                 uninit_array();
+            let mut parsed_vec = Vec::new();
+            let parsed = uninit_slice(&mut parsed_arr, &mut parsed_vec, limits.max_headers);
 
             let mut res = httparse::Response::new(&mut []);
-            match httparse::ParserConfig::default().parse_response_with_uninit_headers(
-                &mut res,
-                src,
-                &mut parsed,
-            )? {
+            match httparse::ParserConfig::default()
+                .parse_response_with_uninit_headers(&mut res, src, parsed)?
+            {
                 httparse::Status::Complete(len) => {
+                    check_line_endings(&src[..len], cfg.strict)?;
+                    limits.check_headers(len, res.headers)?;
+
                     let version = if res.version.unwrap() == 1 {
                         Version::HTTP_11
                     } else {
@@ -351,12 +489,14 @@ impl MessageType for ResponseHead {
                         len,
                         version,
                         status,
-                        HeaderIndex::record(src, res.headers, &mut headers),
+                        HeaderIndex::record(src, res.headers, headers),
                     )
                 }
                 httparse::Status::Partial => {
-                    return if src.len() >= MAX_BUFFER_SIZE {
-                        log::error!("MAX_BUFFER_SIZE unprocessed data reached, closing");
+                    return if src.len() >= limits.max_headers_size {
+                        log::error!(
+                            "Max headers size of unprocessed data reached, closing"
+                        );
                         Err(DecodeError::TooLarge(src.len()))
                     } else {
                         Ok(None)
@@ -369,7 +509,7 @@ impl MessageType for ResponseHead {
         msg.version = ver;
 
         // convert headers
-        let mut length = msg.set_headers(&src.split_to(len).freeze(), ver, headers)?;
+        let mut length = msg.set_headers(&src.split_to(len).freeze(), ver, headers, cfg)?;
 
         // Remove CL value if 0 now that all headers and HTTP/1.0 special cases are processed.
         // Protects against some request smuggling attacks.
@@ -398,6 +538,42 @@ impl MessageType for ResponseHead {
     }
 }
 
+/// Check message head for bare `CR`, bare `LF` and obsolete line folding
+fn check_line_endings(head: &[u8], strict: Strict) -> Result<(), DecodeError> {
+    if !strict.intersects(Strict::BARE_CR | Strict::BARE_LF | Strict::OBS_FOLD) {
+        return Ok(());
+    }
+
+    let mut idx = 0;
+    while let Some(pos) = scan::find2(&head[idx..], b'\r', b'\n') {
+        idx += pos;
+        match head[idx] {
+            b'\r' if strict.contains(Strict::BARE_CR) => {
+                if head.get(idx + 1) != Some(&b'\n') {
+                    log::debug!("bare CR is not allowed");
+                    return Err(DecodeError::InvalidInput("Bare CR in message head"));
+                }
+            }
+            b'\n' => {
+                if strict.contains(Strict::BARE_LF) && (idx == 0 || head[idx - 1] != b'\r')
+                {
+                    log::debug!("bare LF is not allowed");
+                    return Err(DecodeError::InvalidInput("Bare LF in message head"));
+                }
+                if strict.contains(Strict::OBS_FOLD)
+                    && matches!(head.get(idx + 1), Some(b' ' | b'\t'))
+                {
+                    log::debug!("obsolete line folding is not allowed");
+                    return Err(DecodeError::Header);
+                }
+            }
+            _ => (),
+        }
+        idx += 1;
+    }
+    Ok(())
+}
+
 #[derive(Clone, Copy)]
 pub(super) struct HeaderIndex {
     pub(super) name: (usize, usize),
@@ -452,26 +628,40 @@ pub enum PayloadItem {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PayloadDecoder {
     kind: Cell<Kind>,
+    ext: Cell<usize>,
+    max_ext: usize,
 }
 
 impl PayloadDecoder {
     pub(super) fn length(x: u64) -> PayloadDecoder {
         PayloadDecoder {
             kind: Cell::new(Kind::Length(x)),
+            ext: Cell::new(0),
+            max_ext: 0,
         }
     }
 
     pub(super) fn chunked() -> PayloadDecoder {
         PayloadDecoder {
             kind: Cell::new(Kind::Chunked(ChunkedState::Size, 0)),
+            ext: Cell::new(0),
+            max_ext: 0,
         }
     }
 
     pub(super) fn eof() -> PayloadDecoder {
         PayloadDecoder {
             kind: Cell::new(Kind::Eof),
+            ext: Cell::new(0),
+            max_ext: 0,
         }
     }
+
+    /// Set max size of chunk extensions, 0 disables check
+    pub(super) fn max_extension(mut self, size: usize) -> Self {
+        self.max_ext = size;
+        self
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -551,6 +741,22 @@ impl Decoder for PayloadDecoder {
                         Poll::Ready(Err(e)) => break Err(e),
                     };
 
+                    // check chunk extension size
+                    if self.max_ext != 0 {
+                        if *state == ChunkedState::Extension {
+                            let size = self.ext.get() + 1;
+                            if size > self.max_ext {
+                                log::debug!("chunk extension is too large");
+                                break Err(DecodeError::InvalidInput(
+                                    "Chunk extension is too large",
+                                ));
+                            }
+                            self.ext.set(size);
+                        } else if *state == ChunkedState::SizeLf {
+                            self.ext.set(0);
+                        }
+                    }
+
                     if *state == ChunkedState::End {
                         log::trace!("End of chunked stream");
                         break Ok(Some(PayloadItem::Eof));
@@ -737,6 +943,20 @@ fn uninit_array<T, const LEN: usize>() -> [mem::MaybeUninit<T>; LEN] {
     unsafe { mem::MaybeUninit::uninit().assume_init() }
 }
 
+/// Get uninitialized slice of `len` items, allocate if array is too small
+fn uninit_slice<'a, T, const LEN: usize>(
+    arr: &'a mut [mem::MaybeUninit<T>; LEN],
+    vec: &'a mut Vec<T>,
+    len: usize,
+) -> &'a mut [mem::MaybeUninit<T>] {
+    if len <= LEN {
+        &mut arr[..len]
+    } else {
+        vec.reserve_exact(len);
+        &mut vec.spare_capacity_mut()[..len]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1106,6 +1326,33 @@ mod tests {
         assert_eq!(req.head().connection_type(), ConnectionType::Upgrade);
     }
 
+    #[test]
+    fn test_conn_upgrade_h2c() {
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\n\
+             upgrade: h2c\r\n\
+             http2-settings: AAMAAABkAAQAAP__\r\n\
+             connection: Upgrade, HTTP2-Settings\r\n\r\n",
+        );
+        let req = parse_ready!(&mut buf);
+
+        assert!(!req.upgrade());
+        assert_eq!(req.head().connection_type(), ConnectionType::KeepAlive);
+
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\n\
+             upgrade: h2c\r\n\
+             connection: upgrade\r\n\r\n",
+        );
+        let (req, pl) = MessageDecoder::<Request>::default()
+            .decode(&mut buf)
+            .unwrap()
+            .unwrap();
+        assert!(!req.upgrade());
+        assert_eq!(req.head().connection_type(), ConnectionType::KeepAlive);
+        assert!(matches!(pl, PayloadType::None));
+    }
+
     #[test]
     fn test_conn_upgrade_connect_method() {
         let mut buf = BytesMut::from(
@@ -1510,4 +1757,218 @@ mod tests {
         let chunk = pl.decode(&mut buf).unwrap().unwrap();
         assert_eq!(chunk, PayloadItem::Chunk(Bytes::from_static(b"0\r\n")));
     }
+
+    fn strict(checks: Strict) -> MessageDecoder<Request> {
+        MessageDecoder::new(DecoderConfig {
+            strict: checks,
+            max_chunk_extension: 16,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_strict_conflicting_length() {
+        let data = "GET /test HTTP/1.1\r\n\
+             Host: example.com\r\n\
+             Content-Length: 3\r\n\
+             Transfer-Encoding: identity\r\n\
+             \r\n\
+             0\r\n";
+        let mut buf = BytesMut::from(data);
+        assert!(strict(Strict::empty()).decode(&mut buf).is_ok());
+
+        let mut buf = BytesMut::from(data);
+        assert!(strict(Strict::CONFLICTING_LENGTH).decode(&mut buf).is_err());
+
+        // transfer encoding is ignored for http/1.0 but still conflicts
+        let mut buf = BytesMut::from(
+            "GET / HTTP/1.0\r\n\
+            Content-Length: 3\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            000",
+        );
+        assert!(strict(Strict::CONFLICTING_LENGTH).decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_strict_line_endings() {
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\nHost: example.com\n\n");
+        assert!(strict(Strict::empty()).decode(&mut buf).is_ok());
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\nHost: example.com\n\n");
+        assert!(strict(Strict::BARE_LF).decode(&mut buf).is_err());
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\nHost: example.com\r\n\r\n");
+        assert!(strict(Strict::all()).decode(&mut buf).is_ok());
+    }
+
+    #[test]
+    fn test_strict_obs_fold() {
+        let data = "GET /test HTTP/1.1\r\nX-Test: a\r\n b\r\n\r\n";
+        let mut buf = BytesMut::from(data);
+        assert!(strict(Strict::OBS_FOLD).decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_strict_multiple_host() {
+        let data = "GET /test HTTP/1.1\r\nHost: a.com\r\nHost: b.com\r\n\r\n";
+        let mut buf = BytesMut::from(data);
+        assert!(strict(Strict::empty()).decode(&mut buf).is_ok());
+
+        let mut buf = BytesMut::from(data);
+        assert!(strict(Strict::MULTIPLE_HOST).decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_strict_chunk_extension() {
+        let data = "GET /test HTTP/1.1\r\n\
+             transfer-encoding: chunked\r\n\r\n\
+             4;ext=0123456789abcdef\r\ndata\r\n0\r\n\r\n";
+
+        let mut buf = BytesMut::from(data);
+        let (_, pl) = strict(Strict::empty()).decode(&mut buf).unwrap().unwrap();
+        let pl = pl.unwrap();
+        assert_eq!(
+            pl.decode(&mut buf).unwrap().unwrap().chunk().as_ref(),
+            b"data"
+        );
+        assert!(pl.decode(&mut buf).unwrap().unwrap().eof());
+
+        let mut buf = BytesMut::from(data);
+        let (_, pl) = strict(Strict::CHUNK_EXTENSION)
+            .decode(&mut buf)
+            .unwrap()
+            .unwrap();
+        assert!(pl.unwrap().decode(&mut buf).is_err());
+
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\n\
+             transfer-encoding: chunked\r\n\r\n\
+             4;ext=01\r\ndata\r\n4;ext=01\r\ndata\r\n0\r\n\r\n",
+        );
+        let (_, pl) = strict(Strict::CHUNK_EXTENSION)
+            .decode(&mut buf)
+            .unwrap()
+            .unwrap();
+        let pl = pl.unwrap();
+        assert_eq!(
+            pl.decode(&mut buf).unwrap().unwrap().chunk().as_ref(),
+            b"data"
+        );
+        assert_eq!(
+            pl.decode(&mut buf).unwrap().unwrap().chunk().as_ref(),
+            b"data"
+        );
+        assert!(pl.decode(&mut buf).unwrap().unwrap().eof());
+    }
+
+    fn limits(limits: Limits) -> MessageDecoder<Request> {
+        MessageDecoder::new(DecoderConfig {
+            limits,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_limits_max_headers() {
+        let data = "GET /test HTTP/1.1\r\na: 1\r\nb: 2\r\nc: 3\r\n\r\n";
+        let lim = Limits {
+            max_headers: 2,
+            ..Default::default()
+        };
+        let mut buf = BytesMut::from(data);
+        assert!(matches!(
+            limits(lim).decode(&mut buf),
+            Err(DecodeError::TooLarge(_))
+        ));
+
+        let lim = Limits {
+            max_headers: 3,
+            ..Default::default()
+        };
+        let mut buf = BytesMut::from(data);
+        let (req, _) = limits(lim).decode(&mut buf).unwrap().unwrap();
+        assert_eq!(req.headers().len(), 3);
+
+        // more headers than default stack storage
+        let mut data = "GET /test HTTP/1.1\r\n".to_string();
+        for idx in 0..120 {
+            data.push_str(&format!("x-hdr-{}: {}\r\n", idx, idx));
+        }
+        data.push_str("\r\n");
+        let mut buf = BytesMut::from(data.as_str());
+        assert!(matches!(
+            limits(Limits::default()).decode(&mut buf),
+            Err(DecodeError::TooLarge(_))
+        ));
+
+        let lim = Limits {
+            max_headers: 128,
+            ..Default::default()
+        };
+        let mut buf = BytesMut::from(data.as_str());
+        let (req, _) = limits(lim).decode(&mut buf).unwrap().unwrap();
+        assert_eq!(req.headers().len(), 120);
+    }
+
+    #[test]
+    fn test_limits_header_size() {
+        let data = "GET /test HTTP/1.1\r\nx-hdr: 0123456789\r\n\r\n";
+        let lim = Limits {
+            max_header_size: 10,
+            ..Default::default()
+        };
+        let mut buf = BytesMut::from(data);
+        assert!(matches!(
+            limits(lim).decode(&mut buf),
+            Err(DecodeError::TooLarge(15))
+        ));
+
+        let lim = Limits {
+            max_header_size: 15,
+            ..Default::default()
+        };
+        let mut buf = BytesMut::from(data);
+        assert!(limits(lim).decode(&mut buf).unwrap().is_some());
+
+        let lim = Limits {
+            max_headers_size: 16,
+            ..Default::default()
+        };
+        let mut buf = BytesMut::from(data);
+        assert!(matches!(
+            limits(lim).decode(&mut buf),
+            Err(DecodeError::TooLarge(_))
+        ));
+
+        // incomplete head
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\nx-hdr: 0123456789");
+        assert!(matches!(
+            limits(lim).decode(&mut buf),
+            Err(DecodeError::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn test_limits_uri_length() {
+        let lim = Limits {
+            max_uri_length: 8,
+            ..Default::default()
+        };
+        let mut buf = BytesMut::from("GET /test/long HTTP/1.1\r\n\r\n");
+        assert!(matches!(
+            limits(lim).decode(&mut buf),
+            Err(DecodeError::UriTooLong)
+        ));
+
+        let mut buf = BytesMut::from("GET /test/long/uri");
+        assert!(matches!(
+            limits(lim).decode(&mut buf),
+            Err(DecodeError::UriTooLong)
+        ));
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\n\r\n");
+        assert!(limits(lim).decode(&mut buf).unwrap().is_some());
+    }
 }
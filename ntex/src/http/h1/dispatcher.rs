@@ -1,15 +1,19 @@
 //! HTTP/1 protocol dispatcher
 use std::{error, future, io, marker, pin::Pin, rc::Rc, task::Context, task::Poll};
+use std::{mem, time::Duration, time::Instant};
 
-use crate::io::{Decoded, Filter, Io, IoBoxed, IoStatusUpdate, RecvError};
+use crate::io::{types, Decoded, Filter, Io, IoBoxed, IoStatusUpdate, RecvError};
+use crate::server::pressure::{self, Idle, InFlight, Pressure};
 use crate::service::{PipelineCall, Service};
-use crate::time::Seconds;
+use crate::time::{now, Seconds};
 use crate::util::{ready, Either};
 
-use crate::http::body::{BodySize, MessageBody, ResponseBody};
+use crate::http::body::{Body, BodySize, MessageBody, ResponseBody};
 use crate::http::error::{PayloadError, ResponseError};
+use crate::http::hooks::ConnHooks;
 use crate::http::message::{ConnectionType, CurrentIo};
-use crate::http::{self, config::DispatcherConfig, request::Request, response::Response};
+use crate::http::{self, StatusCode};
+use crate::http::{config::DispatcherConfig, request::Request, response::Response};
 
 use super::control::{Control, ControlAck, ControlFlags, ControlResult};
 use super::decoder::{PayloadDecoder, PayloadItem, PayloadType};
@@ -25,15 +29,38 @@ bitflags::bitflags! {
         const SENDPAYLOAD_AND_STOP = 0b0000_0010;
         /// Complete operation and disconnect
         const DISCONNECT           = 0b0000_0100;
+        /// Send `100 Continue` on first payload read
+        const CONTINUE             = 0b0000_1000;
         /// Keep-alive is enabled
         const READ_KA_TIMEOUT      = 0b0001_0000;
         /// Read headers timer is enabled
         const READ_HDRS_TIMEOUT    = 0b0010_0000;
         /// Read headers payload is enabled
         const READ_PL_TIMEOUT      = 0b0100_0000;
+        /// Flush write buffer before reading next request
+        const FLUSH                = 0b1000_0000;
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// Response write flush strategy for http/1 connections
+pub enum FlushStrategy {
+    /// Flush write buffer after each response.
+    ///
+    /// Next request is not read until response is written to the socket,
+    /// suitable for latency-sensitive services.
+    Response,
+    /// Coalesce responses of pipelined requests.
+    ///
+    /// Dispatcher reads next request while previous responses are
+    /// still in write buffer, write task flushes buffer in background.
+    #[default]
+    Coalesce,
+    /// Coalesce responses until write buffer reaches specified size,
+    /// then flush buffer before reading next request.
+    HighWatermark(usize),
+}
+
 pin_project_lite::pin_project! {
     /// Dispatcher for HTTP/1.1 protocol
     pub struct Dispatcher<F, S: Service<Request>, B, C: Service<Control<F, S::Error>>>
@@ -84,6 +111,13 @@ struct DispatcherInner<F, C, S, B> {
     read_remains: u32,
     read_consumed: u32,
     read_max_timeout: Seconds,
+    read_started: Instant,
+    requests: usize,
+    created: Instant,
+    hooks: ConnHooks,
+    body_sent: u64,
+    in_flight: Option<InFlight>,
+    idle: Option<Idle>,
     _t: marker::PhantomData<(S, B)>,
 }
 
@@ -98,13 +132,19 @@ where
 {
     /// Construct new `Dispatcher` instance with outgoing messages stream.
     pub(in crate::http) fn new(io: Io<F>, config: Rc<DispatcherConfig<S, C>>) -> Self {
-        let codec = Codec::new(config.timer.clone(), config.keep_alive_enabled());
+        let codec = Codec::new(config.timer.clone(), config.keep_alive_enabled())
+            .decoder_config(config.h1_decoder);
         io.set_disconnect_timeout(config.client_disconnect);
+        let hooks = config.hooks.connect(&io, types::HttpProtocol::Http1);
 
         // slow-request timer
+        let created = now();
         let (flags, max_timeout) = if let Some(cfg) = config.headers_read_rate() {
-            io.start_timer(cfg.timeout);
+            io.start_timer(headers_timer(cfg.timeout, config.headers_timeout, created));
             (Flags::READ_HDRS_TIMEOUT, cfg.max_timeout)
+        } else if !config.headers_timeout.is_zero() {
+            io.start_timer(config.headers_timeout);
+            (Flags::READ_HDRS_TIMEOUT, Seconds::ZERO)
         } else {
             (Flags::empty(), Seconds::ZERO)
         };
@@ -120,6 +160,13 @@ where
                 read_remains: 0,
                 read_consumed: 0,
                 read_max_timeout: max_timeout,
+                read_started: created,
+                requests: 0,
+                created,
+                hooks,
+                body_sent: 0,
+                in_flight: None,
+                idle: None,
                 _t: marker::PhantomData,
             },
         }
@@ -167,10 +214,9 @@ where
                 State::CallControl { fut } => match Pin::new(fut).poll(cx) {
                     Poll::Ready(Ok(ControlAck { result, flags })) => {
                         if flags.contains(ControlFlags::CONTINUE) {
-                            let result = inner.io.with_write_buf(|buf| {
-                                buf.extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n")
-                            });
-                            if let Err(err) = result {
+                            if inner.config.lazy_continue && inner.payload.is_some() {
+                                inner.flags.insert(Flags::CONTINUE);
+                            } else if let Err(err) = inner.send_continue() {
                                 *this.st = inner.ctl_peer_gone(Some(err));
                                 continue;
                             }
@@ -251,7 +297,14 @@ where
     B: MessageBody,
 {
     fn poll_read_request(&mut self, cx: &mut Context<'_>) -> Poll<State<F, C, S, B>> {
+        if self.flags.contains(Flags::FLUSH) {
+            if let Err(err) = ready!(self.io.poll_flush(cx, true)) {
+                return Poll::Ready(self.ctl_peer_gone(Some(err)));
+            }
+            self.flags.remove(Flags::FLUSH);
+        }
         log::trace!("{}: Trying to read http message", self.io.tag());
+        self.in_flight = None;
 
         let result = match self.io.poll_recv_decode(&self.codec, cx) {
             Ok(decoded) => {
@@ -259,8 +312,16 @@ where
                     return Poll::Ready(st);
                 }
                 if let Some(item) = decoded.item {
+                    self.idle = None;
                     Ok(item)
                 } else {
+                    // idle keep-alive connection could be closed under pressure
+                    if self.requests == 0 || decoded.remains != 0 {
+                        self.idle = None;
+                    } else if self.idle.is_none() {
+                        let io = self.io.get_ref();
+                        self.idle = Some(pressure::idle(move || io.close()));
+                    }
                     return Poll::Pending;
                 }
             }
@@ -277,17 +338,56 @@ where
                     pl
                 );
                 req.head_mut().io = CurrentIo::Ref(self.io.get_ref());
+                self.hooks.request(req.head());
+
+                // check keep-alive limits, last request closes connection
+                self.requests += 1;
+                if self.codec.keepalive()
+                    && self.config.conn_expired(self.requests, self.created)
+                {
+                    log::trace!(
+                        "{}: Connection limits are reached, close after response",
+                        self.io.tag()
+                    );
+                    self.codec.set_ctype(ConnectionType::Close);
+                    self.flags.insert(Flags::DISCONNECT);
+                }
+
+                // reject request before reading payload
+                if self.config.payload_too_large(req.headers()) {
+                    log::trace!("{}: Request payload is too large", self.io.tag());
+                    let (res, body) =
+                        Response::new(StatusCode::PAYLOAD_TOO_LARGE).into_parts();
+                    let body: Body = body.into();
+                    self.codec.set_ctype(ConnectionType::Close);
+                    self.flags.insert(Flags::DISCONNECT);
+                    return Poll::Ready(self.send_response(res, body.into()));
+                }
+
+                // worker is under pressure, reject request
+                if pressure::pressure() >= Pressure::Shed {
+                    log::trace!(
+                        "{}: Worker is under pressure, shed request",
+                        self.io.tag()
+                    );
+                    let (res, body) =
+                        Response::new(StatusCode::SERVICE_UNAVAILABLE).into_parts();
+                    let body: Body = body.into();
+                    self.codec.set_ctype(ConnectionType::Close);
+                    self.flags.insert(Flags::DISCONNECT);
+                    return Poll::Ready(self.send_response(res, body.into()));
+                }
+                self.in_flight = Some(pressure::in_flight());
 
                 // configure request payload
+                let lazy = self.config.lazy_continue && req.head().expect();
                 match pl {
                     PayloadType::None => (),
-                    PayloadType::Payload(decoder) => {
-                        let (ps, pl) = Payload::create(false);
-                        req.replace_payload(http::Payload::H1(pl));
-                        self.payload = Some((decoder, ps));
-                    }
-                    PayloadType::Stream(decoder) => {
+                    PayloadType::Payload(decoder) | PayloadType::Stream(decoder) => {
                         let (ps, pl) = Payload::create(false);
+                        if lazy {
+                            ps.pause();
+                        }
                         req.replace_payload(http::Payload::H1(pl));
                         self.payload = Some((decoder, ps));
                     }
@@ -335,7 +435,7 @@ where
 
     fn send_response(
         &mut self,
-        msg: Response<()>,
+        mut msg: Response<()>,
         body: ResponseBody<B>,
     ) -> State<F, C, S, B> {
         log::trace!(
@@ -351,6 +451,18 @@ where
         if self.io.is_closed() {
             self.stop()
         } else {
+            // payload is not requested, client is still waiting for `100 Continue`
+            if self.flags.contains(Flags::CONTINUE) {
+                self.flags.remove(Flags::CONTINUE);
+                self.flags.insert(Flags::DISCONNECT);
+                self.codec.set_ctype(ConnectionType::Close);
+                if let Some(mut payload) = self.payload.take() {
+                    payload.1.set_error(PayloadError::Incomplete(None));
+                }
+            }
+            self.config.set_alt_svc(msg.headers_mut());
+            self.hooks.response(msg.head(), body.size());
+
             let result = self
                 .io
                 .encode(Message::Item((msg, body.size())), &self.codec)
@@ -364,15 +476,19 @@ where
             match result {
                 Ok(()) => match body.size() {
                     BodySize::None | BodySize::Empty => {
+                        self.hooks.response_sent(0);
                         if self
                             .flags
                             .intersects(Flags::DISCONNECT | Flags::SENDPAYLOAD_AND_STOP)
                         {
                             self.stop()
-                        } else if self.payload.is_some() {
-                            State::ReadPayload
                         } else {
-                            State::ReadRequest
+                            self.response_sent();
+                            if self.payload.is_some() {
+                                State::ReadPayload
+                            } else {
+                                State::ReadRequest
+                            }
                         }
                     }
                     _ => State::SendPayload { body },
@@ -383,6 +499,26 @@ where
         }
     }
 
+    /// Response is written to write buffer, check flush strategy
+    fn response_sent(&mut self) {
+        let flush = match self.config.h1_flush {
+            FlushStrategy::Coalesce => false,
+            FlushStrategy::Response => true,
+            FlushStrategy::HighWatermark(size) => self
+                .io
+                .with_write_buf(|buf| buf.len() >= size)
+                .unwrap_or(false),
+        };
+        if flush {
+            self.flags.insert(Flags::FLUSH);
+        }
+    }
+
+    fn send_continue(&self) -> io::Result<()> {
+        self.io
+            .with_write_buf(|buf| buf.extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n"))
+    }
+
     fn poll_send_payload(
         &mut self,
         cx: &mut Context<'_>,
@@ -402,6 +538,7 @@ where
             let st = match item {
                 Some(Ok(item)) => {
                     log::trace!("{}: Got response chunk: {:?}", self.io.tag(), item.len());
+                    self.body_sent += item.len() as u64;
                     match self.io.encode(Message::Chunk(Some(item)), &self.codec) {
                         Ok(_) => continue,
                         Err(err) => self.ctl_proto_err(err.into()),
@@ -409,14 +546,18 @@ where
                 }
                 None => {
                     log::trace!("{}: Response payload eof {:?}", self.io.tag(), self.flags);
+                    self.hooks.response_sent(mem::take(&mut self.body_sent));
                     if let Err(err) = self.io.encode(Message::Chunk(None), &self.codec) {
                         self.ctl_proto_err(err.into())
                     } else if self.flags.contains(Flags::DISCONNECT) {
                         self.stop()
-                    } else if self.payload.is_some() {
-                        State::ReadPayload
                     } else {
-                        State::ReadRequest
+                        self.response_sent();
+                        if self.payload.is_some() {
+                            State::ReadPayload
+                        } else {
+                            State::ReadRequest
+                        }
                     }
                 }
                 Some(Err(err)) => {
@@ -450,6 +591,7 @@ where
 
         self.codec.set_ctype(ConnectionType::Close);
         self.codec.unset_streaming();
+        self.hooks.response(res.head(), body.size());
 
         if io
             .encode(Message::Item((res, body.size())), &self.codec)
@@ -550,6 +692,14 @@ where
 
         match self.payload.as_mut().unwrap().1.poll_data_required(cx) {
             PayloadStatus::Read => {
+                // service requests payload, let client send it
+                if self.flags.contains(Flags::CONTINUE) {
+                    self.flags.remove(Flags::CONTINUE);
+                    if let Err(err) = self.send_continue() {
+                        return Poll::Ready(Err(Either::Right(Some(err))));
+                    }
+                }
+
                 // read request payload
                 let mut updated = false;
                 loop {
@@ -665,28 +815,44 @@ where
     }
 
     fn handle_timeout(&mut self) -> Result<(), ProtocolError> {
+        // check request head timeout
+        let headers_timeout = self.config.headers_timeout;
+        if self.flags.contains(Flags::READ_HDRS_TIMEOUT) && !headers_timeout.is_zero() {
+            if now() - self.read_started >= Duration::from(headers_timeout) {
+                log::trace!(
+                    "{}: Request head is not received within {:?}",
+                    self.io.tag(),
+                    headers_timeout
+                );
+                return Err(ProtocolError::SlowRequestTimeout);
+            } else if self.config.headers_read_rate.is_none() {
+                self.io.start_timer(headers_timer(
+                    Seconds::ZERO,
+                    headers_timeout,
+                    self.read_started,
+                ));
+                return Ok(());
+            }
+        }
+
         // check read rate
         if self
             .flags
             .intersects(Flags::READ_PL_TIMEOUT | Flags::READ_HDRS_TIMEOUT)
         {
             let cfg = if self.flags.contains(Flags::READ_HDRS_TIMEOUT) {
-                &self.config.headers_read_rate
+                self.config.headers_read_rate()
             } else {
-                &self.config.payload_read_rate
+                self.config.payload_read_rate()
             };
 
-            if let Some(ref cfg) = cfg {
+            if let Some(cfg) = cfg {
                 let total = if self.flags.contains(Flags::READ_HDRS_TIMEOUT) {
-                    let total = (self.read_remains - self.read_consumed)
-                        .try_into()
-                        .unwrap_or(u16::MAX);
+                    let total = self.read_remains - self.read_consumed;
                     self.read_remains = 0;
                     total
                 } else {
-                    let total = (self.read_remains + self.read_consumed)
-                        .try_into()
-                        .unwrap_or(u16::MAX);
+                    let total = self.read_remains + self.read_consumed;
                     self.read_consumed = 0;
                     total
                 };
@@ -705,7 +871,16 @@ where
                             self.io.tag(),
                             total
                         );
-                        self.io.start_timer(cfg.timeout);
+                        let timeout = if self.flags.contains(Flags::READ_HDRS_TIMEOUT) {
+                            headers_timer(
+                                cfg.timeout,
+                                self.config.headers_timeout,
+                                self.read_started,
+                            )
+                        } else {
+                            cfg.timeout
+                        };
+                        self.io.start_timer(timeout);
                         return Ok(());
                     }
                 }
@@ -756,12 +931,19 @@ where
                 self.io.close();
                 return Some(self.stop());
             }
-        } else if let Some(ref cfg) = self.config.headers_read_rate {
-            log::debug!(
-                "{}: Start headers read timer {:?}",
-                self.io.tag(),
-                cfg.timeout
-            );
+        } else if self.config.headers_read_rate.is_some()
+            || !self.config.headers_timeout.is_zero()
+        {
+            let (timeout, max_timeout) = self
+                .config
+                .headers_read_rate
+                .map(|cfg| (cfg.timeout, cfg.max_timeout))
+                .unwrap_or_default();
+            self.read_started = now();
+            let timeout =
+                headers_timer(timeout, self.config.headers_timeout, self.read_started);
+
+            log::debug!("{}: Start headers read timer {:?}", self.io.tag(), timeout);
 
             // we got new data but not enough to parse single frame
             // start read timer
@@ -770,8 +952,8 @@ where
 
             self.read_consumed = 0;
             self.read_remains = decoded.remains as u32;
-            self.read_max_timeout = cfg.max_timeout;
-            self.io.start_timer(cfg.timeout);
+            self.read_max_timeout = max_timeout;
+            self.io.start_timer(timeout);
         }
         None
     }
@@ -780,7 +962,7 @@ where
         if self.flags.contains(Flags::READ_PL_TIMEOUT) {
             self.read_remains = decoded.remains as u32;
             self.read_consumed += decoded.consumed as u32;
-        } else if let Some(ref cfg) = self.config.payload_read_rate {
+        } else if let Some(cfg) = self.config.payload_read_rate() {
             // start payload timer
             self.flags.insert(Flags::READ_PL_TIMEOUT);
 
@@ -835,10 +1017,25 @@ where
     }
 }
 
+/// Headers read timer period, limited by remaining request head timeout
+fn headers_timer(period: Seconds, timeout: Seconds, started: Instant) -> Seconds {
+    if timeout.is_zero() {
+        period
+    } else {
+        let elapsed = (now() - started).as_secs();
+        let remains = (timeout.0 as u64).saturating_sub(elapsed).max(1) as u16;
+        if period.is_zero() || remains < period.0 {
+            Seconds(remains)
+        } else {
+            period
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-    use std::{cell::Cell, future::poll_fn, future::Future, sync::Arc};
+    use std::{cell::Cell, cell::RefCell, future::poll_fn, future::Future, sync::Arc};
 
     use ntex_h2::Config;
     use rand::Rng;
@@ -999,6 +1196,417 @@ mod tests {
         assert!(client.is_server_dropped());
     }
 
+    #[crate::rt_test]
+    async fn test_keepalive_max_requests() {
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        let mut decoder = ClientCodec::default();
+
+        let mut config = ServiceConfig::default();
+        config.keepalive_max_requests(2);
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            Rc::new(DispatcherConfig::new(
+                config,
+                fn_service(|_| async { Ok::<_, io::Error>(Response::Ok().finish()) }),
+                DefaultControlService,
+            )),
+        ));
+
+        client.write("GET /test1 HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert!(head.status.is_success());
+        assert_eq!(head.connection_type(), ConnectionType::KeepAlive);
+        assert!(!client.is_server_dropped());
+
+        client.write("GET /test2 HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert!(head.status.is_success());
+        assert_eq!(head.connection_type(), ConnectionType::Close);
+
+        sleep(Millis(50)).await;
+        assert!(client.is_server_dropped());
+    }
+
+    #[crate::rt_test]
+    async fn test_dispatcher_hooks() {
+        #[derive(Clone, Default)]
+        struct Hooks(Rc<RefCell<Vec<String>>>);
+
+        impl http::DispatcherHooks for Hooks {
+            fn on_connect(&self, conn: &http::PeerConnectionInfo) {
+                assert_eq!(conn.protocol(), types::HttpProtocol::Http1);
+                self.0.borrow_mut().push("connect".to_string());
+            }
+            fn on_disconnect(&self, conn: &http::PeerConnectionInfo) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("disconnect {}", conn.requests()));
+            }
+            fn on_request(
+                &self,
+                conn: &http::PeerConnectionInfo,
+                head: &http::RequestHead,
+            ) {
+                self.0.borrow_mut().push(format!(
+                    "request {} {}",
+                    conn.requests(),
+                    head.uri
+                ));
+            }
+            fn on_response(
+                &self,
+                _: &http::PeerConnectionInfo,
+                head: &ResponseHead,
+                _: BodySize,
+            ) {
+                self.0
+                    .borrow_mut()
+                    .push(format!("response {}", head.status));
+            }
+            fn on_response_sent(&self, _: &http::PeerConnectionInfo, bytes: u64) {
+                self.0.borrow_mut().push(format!("sent {}", bytes));
+            }
+        }
+
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+
+        let hooks = Hooks::default();
+        let mut config = ServiceConfig::default();
+        config.dispatcher_hooks(hooks.clone());
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            Rc::new(DispatcherConfig::new(
+                config,
+                fn_service(|_| async {
+                    Ok::<_, io::Error>(Response::Ok().body("test body"))
+                }),
+                DefaultControlService,
+            )),
+        ));
+
+        client.write("GET /test1 HTTP/1.1\r\n\r\nGET /test2 HTTP/1.1\r\n\r\n");
+        let _ = client.read().await.unwrap();
+        sleep(Millis(50)).await;
+
+        client.close().await;
+        sleep(Millis(50)).await;
+        assert_eq!(
+            &hooks.0.borrow()[..],
+            &[
+                "connect",
+                "request 1 /test1",
+                "response 200 OK",
+                "sent 9",
+                "request 2 /test2",
+                "response 200 OK",
+                "sent 9",
+                "disconnect 2"
+            ]
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_keepalive_max_lifetime() {
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        let mut decoder = ClientCodec::default();
+
+        let mut config = ServiceConfig::default();
+        config.keepalive_max_lifetime(Seconds(1));
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            Rc::new(DispatcherConfig::new(
+                config,
+                fn_service(|_| async { Ok::<_, io::Error>(Response::Ok().finish()) }),
+                DefaultControlService,
+            )),
+        ));
+
+        client.write("GET /test1 HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert_eq!(head.connection_type(), ConnectionType::KeepAlive);
+
+        sleep(Millis(1100)).await;
+        client.write("GET /test2 HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert!(head.status.is_success());
+        assert_eq!(head.connection_type(), ConnectionType::Close);
+
+        sleep(Millis(50)).await;
+        assert!(client.is_server_dropped());
+    }
+
+    #[crate::rt_test]
+    async fn test_keepalive_virtual_time() {
+        crate::time::testing::pause();
+
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        let mut decoder = ClientCodec::default();
+
+        let mut config = ServiceConfig::default();
+        config.keepalive(Seconds(30));
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            Rc::new(DispatcherConfig::new(
+                config,
+                fn_service(|_| async { Ok::<_, io::Error>(Response::Ok().finish()) }),
+                DefaultControlService,
+            )),
+        ));
+
+        client.write("GET /test HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert_eq!(head.connection_type(), ConnectionType::KeepAlive);
+
+        crate::time::testing::advance(Seconds(25)).await;
+        assert!(!client.is_server_dropped());
+
+        // keep-alive timeout and disconnect timeout
+        crate::time::testing::advance(Seconds(10)).await;
+        crate::time::testing::advance(Seconds(5)).await;
+        assert!(client.is_server_dropped());
+
+        crate::time::testing::resume();
+    }
+
+    #[crate::rt_test]
+    async fn test_alt_svc() {
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        let mut decoder = ClientCodec::default();
+
+        let mut config = ServiceConfig::default();
+        config.alt_svc(http::header::HeaderValue::from_static("h3=\":443\""));
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            Rc::new(DispatcherConfig::new(
+                config,
+                fn_service(|req: Request| async move {
+                    if req.path() == "/custom" {
+                        Ok::<_, io::Error>(
+                            Response::Ok().header("alt-svc", "clear").finish(),
+                        )
+                    } else {
+                        Ok::<_, io::Error>(Response::Ok().finish())
+                    }
+                }),
+                DefaultControlService,
+            )),
+        ));
+
+        client.write("GET /test HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert_eq!(
+            head.headers.get(http::header::ALT_SVC).unwrap(),
+            "h3=\":443\""
+        );
+
+        client.write("GET /custom HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert_eq!(head.headers.get(http::header::ALT_SVC).unwrap(), "clear");
+    }
+
+    #[crate::rt_test]
+    async fn test_h2_preface() {
+        // http/2 preface is detected by http service, not by h1 dispatcher
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        client.write("PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n");
+
+        let mut h1 = h1(server, |_| {
+            Box::pin(async { Ok::<_, io::Error>(Response::Ok().finish()) })
+        });
+        sleep(Millis(50)).await;
+        let _ = lazy(|cx| Pin::new(&mut h1).poll(cx)).await;
+        sleep(Millis(50)).await;
+        client.local_buffer(|buf| assert_eq!(&buf[..26], b"HTTP/1.1 400 Bad Request\r\n"));
+    }
+
+    #[crate::rt_test]
+    async fn test_max_payload_size() {
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        let mut decoder = ClientCodec::default();
+
+        let mut config = ServiceConfig::default();
+        config.max_payload_size(4);
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            Rc::new(DispatcherConfig::new(
+                config,
+                fn_service(|_| async { Ok::<_, io::Error>(Response::Ok().finish()) }),
+                DefaultControlService,
+            )),
+        ));
+
+        client.write("POST /test HTTP/1.1\r\ncontent-length: 4\r\n\r\ntest");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert!(head.status.is_success());
+
+        client.write(
+            "POST /test HTTP/1.1\r\ncontent-length: 5\r\nexpect: 100-continue\r\n\r\n",
+        );
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert_eq!(head.status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(head.connection_type(), ConnectionType::Close);
+
+        sleep(Millis(50)).await;
+        assert!(client.is_server_dropped());
+    }
+
+    #[crate::rt_test]
+    async fn test_worker_pressure() {
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        let mut decoder = ClientCodec::default();
+
+        let config = Rc::new(DispatcherConfig::new(
+            ServiceConfig::default(),
+            fn_service(|_| async { Ok::<_, io::Error>(Response::Ok().finish()) }),
+            DefaultControlService,
+        ));
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            config.clone(),
+        ));
+
+        client.write("GET /test HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert!(head.status.is_success());
+        sleep(Millis(50)).await;
+
+        // shed new requests
+        pressure::set_pressure(Pressure::Shed);
+        client.write("GET /test HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert_eq!(head.status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(head.connection_type(), ConnectionType::Close);
+        sleep(Millis(50)).await;
+        assert!(client.is_server_dropped());
+
+        // close idle keep-alive connections
+        pressure::set_pressure(Pressure::Normal);
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            config,
+        ));
+        client.write("GET /test HTTP/1.1\r\n\r\n");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert!(head.status.is_success());
+        sleep(Millis(50)).await;
+        assert!(!client.is_server_dropped());
+
+        pressure::set_pressure(Pressure::CloseIdle);
+        sleep(Millis(50)).await;
+        assert!(client.is_server_dropped());
+        pressure::set_pressure(Pressure::Normal);
+    }
+
+    #[crate::rt_test]
+    async fn test_flush_strategy() {
+        for (strategy, expected) in [
+            (FlushStrategy::Coalesce, 2),
+            (FlushStrategy::Response, 1),
+            (FlushStrategy::HighWatermark(1024), 2),
+            (FlushStrategy::HighWatermark(16), 1),
+        ] {
+            let (client, server) = Io::create();
+            client.remote_buffer_cap(0);
+            client.write("GET /test HTTP/1.1\r\n\r\nGET /test HTTP/1.1\r\n\r\n");
+
+            let num = Rc::new(Cell::new(0));
+            let num2 = num.clone();
+            let mut config = ServiceConfig::default();
+            config.h1_flush_strategy(strategy);
+            crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+                nio::Io::new(server),
+                Rc::new(DispatcherConfig::new(
+                    config,
+                    fn_service(move |_| {
+                        num2.set(num2.get() + 1);
+                        async { Ok::<_, io::Error>(Response::Ok().finish()) }
+                    }),
+                    DefaultControlService,
+                )),
+            ));
+            sleep(Millis(50)).await;
+            assert_eq!(num.get(), expected, "{:?}", strategy);
+
+            // write buffer is flushed, next request is processed
+            client.remote_buffer_cap(1024);
+            sleep(Millis(50)).await;
+            assert_eq!(num.get(), 2);
+            let data = client.read_any();
+            assert_eq!(data.windows(8).filter(|w| *w == b"HTTP/1.1").count(), 2);
+        }
+    }
+
+    #[crate::rt_test]
+    async fn test_lazy_continue() {
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        let mut decoder = ClientCodec::default();
+
+        let mut config = ServiceConfig::default();
+        config.h1_lazy_continue(true);
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            Rc::new(DispatcherConfig::new(
+                config,
+                fn_service(|mut req: Request| async move {
+                    if req.path() == "/reject" {
+                        return Ok::<_, io::Error>(Response::ExpectationFailed().finish());
+                    }
+                    let mut p = req.take_payload();
+                    while (stream_recv(&mut p).await).is_some() {}
+                    Ok::<_, io::Error>(Response::Ok().finish())
+                }),
+                DefaultControlService,
+            )),
+        ));
+
+        // service reads payload, interim response is sent
+        client.write(
+            "POST /test HTTP/1.1\r\ncontent-length: 4\r\nexpect: 100-continue\r\n\r\n",
+        );
+        let data = client.read().await.unwrap();
+        assert_eq!(&data[..], b"HTTP/1.1 100 Continue\r\n\r\n");
+        client.write("test");
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert!(head.status.is_success());
+        assert_eq!(head.connection_type(), ConnectionType::KeepAlive);
+
+        // service rejects request without reading payload
+        client.write(
+            "POST /reject HTTP/1.1\r\ncontent-length: 4\r\nexpect: 100-continue\r\n\r\n",
+        );
+        let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
+        let head = load(&mut decoder, &mut buf);
+        assert_eq!(head.status, StatusCode::EXPECTATION_FAILED);
+        assert_eq!(head.connection_type(), ConnectionType::Close);
+
+        sleep(Millis(50)).await;
+        assert!(client.is_server_dropped());
+    }
+
     #[crate::rt_test]
     async fn test_pipeline_with_payload() {
         let (client, server) = Io::create();
@@ -1127,7 +1735,10 @@ mod tests {
         assert!(h1.inner.io.is_closed());
 
         let mut buf = BytesMut::from(&client.read().await.unwrap()[..]);
-        assert_eq!(load(&mut decoder, &mut buf).status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            load(&mut decoder, &mut buf).status,
+            StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE
+        );
     }
 
     #[crate::rt_test]
@@ -1348,4 +1959,35 @@ mod tests {
         }
         assert!(mark.load(Ordering::Relaxed) == 1536);
     }
+
+    #[crate::rt_test]
+    async fn test_headers_timeout() {
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+
+        let mut config = ServiceConfig::default();
+        config.headers_read_rate(Seconds(1), Seconds::ZERO, 1);
+        config.headers_timeout(Seconds(2));
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            Rc::new(DispatcherConfig::new(
+                config,
+                fn_service(|_| async { Ok::<_, io::Error>(Response::Ok().finish()) }),
+                DefaultControlService,
+            )),
+        ));
+
+        // slow client satisfies read rate but not head timeout
+        client.write("GET /test HTTP/1.1\r\n");
+        for _ in 0..6 {
+            sleep(Millis(500)).await;
+            client.write("x-header: value\r\n");
+        }
+        sleep(Millis(100)).await;
+
+        let buf = client.read().await.unwrap();
+        assert!(buf.starts_with(b"HTTP/1.1 408"));
+        sleep(Millis(50)).await;
+        assert!(client.is_server_dropped());
+    }
 }
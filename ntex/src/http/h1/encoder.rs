@@ -47,7 +47,8 @@ pub(super) trait MessageType: Sized {
 
     fn chunked(&self) -> bool;
 
-    fn encode_status(&self, dst: &mut BytesMut) -> Result<(), EncodeError>;
+    /// Encode status line, request uri is encoded in absolute form if `absolute` is set
+    fn encode_status(&self, dst: &mut BytesMut, absolute: bool) -> Result<(), EncodeError>;
 
     fn encode_headers(
         &self,
@@ -214,7 +215,7 @@ impl MessageType for Response<()> {
         None
     }
 
-    fn encode_status(&self, dst: &mut BytesMut) -> Result<(), EncodeError> {
+    fn encode_status(&self, dst: &mut BytesMut, _: bool) -> Result<(), EncodeError> {
         let head = self.head();
         let reason = head.reason().as_bytes();
         dst.reserve(256 + head.headers.len() * AVERAGE_HEADER_SIZE + reason.len());
@@ -243,13 +244,23 @@ impl MessageType for RequestHeadType {
         self.extra_headers()
     }
 
-    fn encode_status(&self, dst: &mut BytesMut) -> Result<(), EncodeError> {
+    fn encode_status(&self, dst: &mut BytesMut, absolute: bool) -> Result<(), EncodeError> {
         let head = self.as_ref();
         dst.reserve(256 + head.headers.len() * AVERAGE_HEADER_SIZE);
+        write!(helpers::Writer(dst), "{} ", head.method).map_err(EncodeError::Fmt)?;
+
+        // absolute form, used for requests to http proxy
+        if absolute {
+            if let (Some(scheme), Some(authority)) =
+                (head.uri.scheme_str(), head.uri.authority())
+            {
+                write!(helpers::Writer(dst), "{}://{}", scheme, authority)
+                    .map_err(EncodeError::Fmt)?;
+            }
+        }
         write!(
             helpers::Writer(dst),
-            "{} {} {}",
-            head.method,
+            "{} {}",
             head.uri.path_and_query().map(|u| u.as_str()).unwrap_or("/"),
             // only HTTP-0.9/1.1
             match head.version {
@@ -290,6 +301,7 @@ impl<T: MessageType> MessageEncoder<T> {
         message: &mut T,
         head: bool,
         stream: bool,
+        absolute: bool,
         version: Version,
         length: BodySize,
         ctype: ConnectionType,
@@ -313,7 +325,7 @@ impl<T: MessageType> MessageEncoder<T> {
             self.te.set(TransferEncoding::empty());
         }
 
-        message.encode_status(dst)?;
+        message.encode_status(dst, absolute)?;
         message.encode_headers(dst, version, length, ctype, timer)
     }
 }
@@ -638,6 +650,24 @@ mod tests {
         assert!(data.contains("date: date\r\n"));
     }
 
+    #[test]
+    fn test_absolute_form() {
+        let mut bytes = BytesMut::with_capacity(2048);
+
+        let mut head = RequestHead::default();
+        head.uri = crate::http::Uri::from_static("http://example.com/path?q=1");
+        let head = RequestHeadType::Owned(head);
+
+        head.encode_status(&mut bytes, false).unwrap();
+        assert_eq!(bytes.split(), b"GET /path?q=1 HTTP/1.1"[..]);
+
+        head.encode_status(&mut bytes, true).unwrap();
+        assert_eq!(
+            bytes.split(),
+            b"GET http://example.com/path?q=1 HTTP/1.1"[..]
+        );
+    }
+
     #[test]
     fn test_write_content_length() {
         let mut bytes = BytesMut::new();
@@ -8,6 +8,7 @@ mod default;
 mod dispatcher;
 mod encoder;
 mod payload;
+mod scan;
 mod service;
 
 pub mod control;
@@ -15,15 +16,15 @@ pub mod control;
 pub use self::client::{ClientCodec, ClientPayloadCodec};
 pub use self::codec::Codec;
 pub use self::control::{Control, ControlAck};
-pub use self::decoder::{PayloadDecoder, PayloadItem, PayloadType};
+pub use self::decoder::{PayloadDecoder, PayloadItem, PayloadType, Strict};
 pub use self::default::DefaultControlService;
+pub use self::dispatcher::FlushStrategy;
 pub use self::payload::Payload;
 pub use self::service::{H1Service, H1ServiceHandler};
 
+pub(super) use self::decoder::DecoderConfig;
 pub(super) use self::dispatcher::Dispatcher;
 
-const MAX_BUFFER_SIZE: usize = 32_768;
-
 #[derive(Debug)]
 /// Codec message
 pub enum Message<T> {
@@ -88,6 +89,12 @@ pub enum ProtocolError {
 impl super::ResponseError for ProtocolError {
     fn error_response(&self) -> super::Response {
         match self {
+            ProtocolError::Decode(super::error::DecodeError::TooLarge(_)) => {
+                super::Response::new(super::StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE)
+            }
+            ProtocolError::Decode(super::error::DecodeError::UriTooLong) => {
+                super::Response::UriTooLong().into()
+            }
             ProtocolError::Decode(_) => super::Response::BadRequest().into(),
 
             ProtocolError::SlowRequestTimeout | ProtocolError::SlowPayloadTimeout => {
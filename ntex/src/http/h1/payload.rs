@@ -115,6 +115,13 @@ impl PayloadSender {
         }
     }
 
+    /// Do not read payload until consumer requests data
+    pub(super) fn pause(&self) {
+        if let Some(shared) = self.inner.upgrade() {
+            shared.borrow_mut().need_read = false;
+        }
+    }
+
     pub(super) fn poll_data_required(&self, cx: &mut Context<'_>) -> PayloadStatus {
         // we check only if Payload (other side) is alive,
         // otherwise always return true (consume payload)
@@ -0,0 +1,90 @@
+//! Byte scanning helpers for http/1 message head.
+//!
+//! With `simd` feature input is scanned by 8 bytes words (SWAR),
+//! otherwise byte by byte.
+
+/// Returns position of the first `needle` byte
+#[inline]
+pub(super) fn find(haystack: &[u8], needle: u8) -> Option<usize> {
+    find2(haystack, needle, needle)
+}
+
+/// Returns position of the first `a` or `b` byte
+#[cfg(not(feature = "simd"))]
+#[inline]
+pub(super) fn find2(haystack: &[u8], a: u8, b: u8) -> Option<usize> {
+    haystack.iter().position(|ch| *ch == a || *ch == b)
+}
+
+/// Returns position of the first `a` or `b` byte
+#[cfg(feature = "simd")]
+#[inline]
+pub(super) fn find2(haystack: &[u8], a: u8, b: u8) -> Option<usize> {
+    swar::find2(haystack, a, b)
+}
+
+#[cfg(any(feature = "simd", test))]
+mod swar {
+    const LO: u64 = 0x0101_0101_0101_0101;
+    const HI: u64 = 0x8080_8080_8080_8080;
+    const WORD: usize = std::mem::size_of::<u64>();
+
+    /// Returns word with high bit set in every zero byte.
+    ///
+    /// Borrow could set false positives only above real zero byte,
+    /// so lowest set bit is always exact.
+    #[inline(always)]
+    fn zero_bytes(word: u64) -> u64 {
+        word.wrapping_sub(LO) & !word & HI
+    }
+
+    pub(super) fn find2(haystack: &[u8], a: u8, b: u8) -> Option<usize> {
+        let va = LO * a as u64;
+        let vb = LO * b as u64;
+
+        let mut chunks = haystack.chunks_exact(WORD);
+        let mut offset = 0;
+        for chunk in &mut chunks {
+            let word = u64::from_le_bytes(chunk.try_into().unwrap());
+            let found = zero_bytes(word ^ va) | zero_bytes(word ^ vb);
+            if found != 0 {
+                return Some(offset + (found.trailing_zeros() / 8) as usize);
+            }
+            offset += WORD;
+        }
+        chunks
+            .remainder()
+            .iter()
+            .position(|ch| *ch == a || *ch == b)
+            .map(|pos| offset + pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(haystack: &[u8], a: u8, b: u8) {
+        let expected = haystack.iter().position(|ch| *ch == a || *ch == b);
+        assert_eq!(find2(haystack, a, b), expected);
+        assert_eq!(swar::find2(haystack, a, b), expected);
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(b"", b'\n'), None);
+        assert_eq!(find(b"GET / HTTP/1.1\r\n", b'\n'), Some(15));
+        assert_eq!(find(b"GET / HTTP/1.1\r\n", b' '), Some(3));
+        assert_eq!(find(b"GET", b' '), None);
+
+        let data = b"GET /index.html HTTP/1.1\r\nHost: example.com\r\n\r\n\x80\x81\xff\x00";
+        for start in 0..data.len() {
+            for (a, b) in [(b'\r', b'\n'), (b' ', b' '), (0x80, 0x00), (0xff, 0x01)] {
+                check(&data[start..], a, b);
+            }
+        }
+        // byte before match must not cause false positive
+        check(b"\x01\x00\x00\x00\x00\x00\x00\x00", 0x00, 0x00);
+        check(b"aaaaaaaaaaaaaaab", b'b', b'b');
+    }
+}
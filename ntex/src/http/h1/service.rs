@@ -169,6 +169,11 @@ where
     type Service = H1ServiceHandler<F, S::Service, B, C::Service>;
 
     async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        if self.cfg.h2c {
+            log::error!("Cleartext http/2 (h2c) is not supported by http/1 service");
+            return Err(());
+        }
+
         let service = self
             .srv
             .create(())
@@ -13,3 +13,6 @@ pub use self::payload::Payload;
 pub use self::service::H2Service;
 
 pub(in crate::http) use self::service::handle;
+
+/// Http/2 connection preface prefix
+pub(in crate::http) const PREFACE: &[u8] = b"PRI * HTTP/2";
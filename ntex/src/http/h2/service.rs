@@ -1,17 +1,22 @@
-use std::{cell::RefCell, io, task::Context, task::Poll};
-use std::{error::Error, fmt, future::poll_fn, marker, mem, rc::Rc};
+use std::{cell::Cell, cell::RefCell, io, task::Context, task::Poll, time::Instant};
+use std::{error::Error, fmt, future::pending, future::poll_fn, marker, mem, rc::Rc};
 
-use ntex_h2::{self as h2, frame::StreamId, server};
+use ntex_h2::{self as h2, frame, frame::StreamId, server};
 
-use crate::http::body::{BodySize, MessageBody};
+use crate::http::body::{BodyFrame, BodySize, MessageBody};
 use crate::http::config::{DispatcherConfig, ServiceConfig};
+use crate::http::disconnect::StreamReset;
 use crate::http::error::{DispatchError, H2Error, ResponseError};
 use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use crate::http::hooks::ConnHooks;
 use crate::http::message::{CurrentIo, ResponseHead};
-use crate::http::{DateService, Method, Request, Response, StatusCode, Uri, Version};
+use crate::http::{
+    DateService, Method, Request, Response, StatusCode, Trailers, Uri, Version,
+};
 use crate::io::{types, Filter, Io, IoBoxed, IoRef};
 use crate::service::{IntoServiceFactory, Service, ServiceCtx, ServiceFactory};
-use crate::util::{Bytes, BytesMut, HashMap};
+use crate::time::{now, sleep};
+use crate::util::{select, Bytes, BytesMut, HashMap};
 
 use super::payload::{Payload, PayloadSender};
 use super::DefaultControlService;
@@ -254,22 +259,115 @@ where
 {
     io.set_disconnect_timeout(config.client_disconnect);
     let ioref = io.get_ref();
+    let hooks = config.hooks.connect(&ioref, types::HttpProtocol::Http2);
+    let streams = Rc::new(InFlightStreams::new(ioref.clone()));
+    let max_lifetime = config.max_lifetime;
 
-    let _ = server::handle_one(
+    let fut = server::handle_one(
         io,
         config.h2config.clone(),
         control,
-        PublishService::new(ioref, config),
-    )
-    .await;
+        PublishService::new(ioref, config, streams.clone(), hooks),
+    );
+
+    if max_lifetime.is_zero() {
+        let _ = fut.await;
+    } else {
+        let lifetime = async {
+            sleep(max_lifetime).await;
+            log::trace!("Connection max lifetime is reached, stop accepting new streams");
+            streams.shutdown();
+            pending::<()>().await
+        };
+        let _ = select(fut, lifetime).await;
+    }
 
     Ok(())
 }
 
+/// In-flight streams of http/2 connection
+struct InFlightStreams {
+    io: IoRef,
+    resets: RefCell<HashMap<StreamId, Rc<StreamReset>>>,
+    goaway: Cell<bool>,
+    last_id: Cell<StreamId>,
+}
+
+impl InFlightStreams {
+    fn new(io: IoRef) -> Self {
+        Self {
+            io,
+            resets: RefCell::new(HashMap::default()),
+            goaway: Cell::new(false),
+            last_id: Cell::new(StreamId::from(0)),
+        }
+    }
+
+    /// Send GOAWAY frame, streams with higher id are not accepted
+    fn goaway(&self, last_id: StreamId) {
+        if !self.goaway.replace(true) {
+            log::trace!("Send GOAWAY, last stream {:?}", last_id);
+            self.last_id.set(last_id);
+
+            // go away frame does not depend on connection's codec state
+            let frame =
+                frame::GoAway::new(frame::Reason::NO_ERROR).set_last_stream_id(last_id);
+            if let Err(err) = self.io.encode(frame.into(), &h2::Codec::default()) {
+                log::debug!("Cannot send GOAWAY frame: {:?}", err);
+            }
+        }
+    }
+
+    /// Stop accepting new streams, close connection after last in-flight stream
+    fn shutdown(&self) {
+        self.goaway(self.last_id.get());
+        if self.resets.borrow().is_empty() {
+            self.io.close();
+        }
+    }
+
+    fn started(&self, id: StreamId) -> InFlightStream<'_> {
+        let reset = Rc::new(StreamReset::default());
+        self.resets.borrow_mut().insert(id, reset.clone());
+        InFlightStream {
+            id,
+            reset,
+            streams: self,
+        }
+    }
+
+    fn completed(&self, id: StreamId) {
+        let mut resets = self.resets.borrow_mut();
+        resets.remove(&id);
+
+        // last in-flight stream is completed
+        if self.goaway.get() && resets.is_empty() {
+            self.io.close();
+        }
+    }
+}
+
+/// Stream is tracked until response body and trailers are sent
+struct InFlightStream<'a> {
+    id: StreamId,
+    reset: Rc<StreamReset>,
+    streams: &'a InFlightStreams,
+}
+
+impl Drop for InFlightStream<'_> {
+    fn drop(&mut self) {
+        self.streams.completed(self.id);
+    }
+}
+
 struct PublishService<S: Service<Request>, B, C> {
     io: IoRef,
     config: Rc<DispatcherConfig<S, C>>,
     streams: RefCell<HashMap<StreamId, PayloadSender>>,
+    inflight: Rc<InFlightStreams>,
+    requests: Cell<usize>,
+    created: Instant,
+    hooks: ConnHooks,
     _t: marker::PhantomData<B>,
 }
 
@@ -280,14 +378,43 @@ where
     S::Response: Into<Response<B>>,
     B: MessageBody,
 {
-    fn new(io: IoRef, config: Rc<DispatcherConfig<S, C>>) -> Self {
+    fn new(
+        io: IoRef,
+        config: Rc<DispatcherConfig<S, C>>,
+        inflight: Rc<InFlightStreams>,
+        hooks: ConnHooks,
+    ) -> Self {
         Self {
             io,
             config,
+            inflight,
             streams: RefCell::new(HashMap::default()),
+            requests: Cell::new(0),
+            created: now(),
+            hooks,
             _t: marker::PhantomData,
         }
     }
+
+    /// Check keep-alive limits, returns `false` if stream must be refused
+    ///
+    /// Stream that reaches limits is served, `GOAWAY` frame is sent and
+    /// connection is closed after last in-flight stream is completed.
+    fn check_limits(&self, id: StreamId) -> bool {
+        if self.inflight.goaway.get() {
+            return false;
+        }
+        self.inflight.last_id.set(id);
+
+        let requests = self.requests.get() + 1;
+        self.requests.set(requests);
+
+        if self.config.conn_expired(requests, self.created) {
+            log::trace!("Connection limits are reached, last stream {:?}", id);
+            self.inflight.goaway(id);
+        }
+        true
+    }
 }
 
 impl<S, B, C> Service<h2::Message> for PublishService<S, B, C>
@@ -334,6 +461,11 @@ where
             }
             h2::MessageKind::Eof(item) => {
                 log::debug!("Got payload eof for {:?}: {:?}", stream.id(), item);
+                if let h2::StreamEof::Error(_) = item {
+                    if let Some(reset) = self.inflight.resets.borrow().get(&stream.id()) {
+                        reset.set();
+                    }
+                }
                 if let Some(mut sender) = self.streams.borrow_mut().remove(&stream.id()) {
                     match item {
                         h2::StreamEof::Data(data) => {
@@ -349,6 +481,9 @@ where
             }
             h2::MessageKind::Disconnect(err) => {
                 log::debug!("Connection is disconnected {:?}", err);
+                if let Some(reset) = self.inflight.resets.borrow().get(&stream.id()) {
+                    reset.set();
+                }
                 if let Some(mut sender) = self.streams.borrow_mut().remove(&stream.id()) {
                     sender.set_error(io::Error::new(io::ErrorKind::Other, err).into());
                 }
@@ -371,9 +506,36 @@ where
             Request::new()
         };
 
+        // connection is closing, client can safely retry refused stream
+        if !self.check_limits(stream.id()) {
+            log::debug!("{:?} connection is closing, refuse stream", stream.id());
+            self.streams.borrow_mut().remove(&stream.id());
+            stream.reset(frame::Reason::REFUSED_STREAM);
+            return Ok(());
+        }
+
+        // stream is tracked until response is sent, even if it is rejected early
+        let inflight = self.inflight.started(stream.id());
+
         let path = pseudo.path.ok_or(H2Error::MissingPseudo("Path"))?;
         let method = pseudo.method.ok_or(H2Error::MissingPseudo("Method"))?;
 
+        // check request headers limits
+        if let Some(status) = cfg.limits().check_h2(&path, &headers) {
+            log::debug!("{:?} request headers limits are reached", stream.id());
+            self.streams.borrow_mut().remove(&stream.id());
+            stream.send_response(status, HeaderMap::new(), true)?;
+            return Ok(());
+        }
+
+        // check declared payload size
+        if cfg.payload_too_large(&headers) {
+            log::debug!("{:?} request payload is too large", stream.id());
+            self.streams.borrow_mut().remove(&stream.id());
+            stream.send_response(StatusCode::PAYLOAD_TOO_LARGE, HeaderMap::new(), true)?;
+            return Ok(());
+        }
+
         let head = req.head_mut();
         head.uri = if let Some(ref authority) = pseudo.authority {
             let scheme = pseudo.scheme.ok_or(H2Error::MissingPseudo("Scheme"))?;
@@ -386,8 +548,14 @@ where
         head.method = method;
         head.headers = headers;
         head.io = CurrentIo::Ref(io);
+        self.hooks.request(head);
+
+        // stream reset notification
+        head.extensions_mut().insert(inflight.reset.clone());
 
-        let (mut res, mut body) = match cfg.service.call(req).await {
+        let result = cfg.service.call(req).await;
+
+        let (mut res, mut body) = match result {
             Ok(res) => res.into().into_parts(),
             Err(err) => {
                 let (res, body) = Response::from(&err).into_parts();
@@ -398,39 +566,68 @@ where
         let head = res.head_mut();
         let mut size = body.size();
         prepare_response(&cfg.timer, head, &mut size);
+        cfg.set_alt_svc(&mut head.headers);
+        self.hooks.response(head, size);
 
         log::debug!("Received service response: {:?} payload: {:?}", head, size);
 
+        let trailers = if is_head_req {
+            None
+        } else {
+            head.extensions_mut().remove::<Trailers>()
+        };
+
         let hdrs = mem::replace(&mut head.headers, HeaderMap::new());
-        if size.is_eof() || is_head_req {
+        let mut sent = 0;
+        if (size.is_eof() || is_head_req) && trailers.is_none() {
             stream.send_response(head.status, hdrs, true)?;
         } else {
             stream.send_response(head.status, hdrs, false)?;
 
+            let mut body_trailers = None;
             loop {
-                match poll_fn(|cx| body.poll_next_chunk(cx)).await {
-                    None => {
-                        log::debug!("{:?} closing payload stream", stream.id());
-                        stream.send_payload(Bytes::new(), true).await?;
-                        break;
-                    }
-                    Some(Ok(chunk)) => {
+                match poll_fn(|cx| body.poll_frame(cx)).await {
+                    None => break,
+                    Some(Ok(BodyFrame::Data(chunk))) => {
                         log::debug!(
                             "{:?} sending data chunk {:?} bytes",
                             stream.id(),
                             chunk.len()
                         );
                         if !chunk.is_empty() {
+                            sent += chunk.len() as u64;
                             stream.send_payload(chunk, false).await?;
                         }
                     }
+                    Some(Ok(BodyFrame::Trailers(map))) => {
+                        body_trailers = Some(map);
+                        break;
+                    }
                     Some(Err(e)) => {
                         log::error!("Response payload stream error: {:?}", e);
                         return Err(e.into());
                     }
                 }
             }
+
+            if trailers.is_some() || body_trailers.is_some() {
+                // body trailers are merged with response trailers
+                let mut map = trailers.map(|t| t.take()).unwrap_or_default();
+                if let Some(body_trailers) = body_trailers {
+                    for (name, value) in body_trailers.iter() {
+                        map.append(name.clone(), value.clone());
+                    }
+                }
+                log::debug!("{:?} sending trailers", stream.id());
+                stream.send_trailers(map);
+            } else {
+                log::debug!("{:?} closing payload stream", stream.id());
+                stream.send_payload(Bytes::new(), true).await?;
+            }
         }
+
+        self.hooks.response_sent(sent);
+        drop(inflight);
         Ok(())
     }
 }
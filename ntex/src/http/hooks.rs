@@ -0,0 +1,157 @@
+//! Http dispatcher instrumentation hooks
+use std::{cell::Cell, fmt, net::SocketAddr, rc::Rc, time::Instant};
+
+use crate::io::{types, IoRef};
+use crate::time::now;
+
+use super::body::BodySize;
+use super::message::{RequestHead, ResponseHead};
+
+thread_local! {
+    static CONN_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Http/1 and http/2 dispatchers instrumentation hooks
+///
+/// Hooks are called by dispatchers for events that are not observable
+/// by middlewares, like connection reuse or the time when response head
+/// is queued for writing. All methods have empty default implementations.
+pub trait DispatcherHooks: 'static {
+    /// New connection is started
+    fn on_connect(&self, _: &PeerConnectionInfo) {}
+
+    /// Connection is closed
+    fn on_disconnect(&self, _: &PeerConnectionInfo) {}
+
+    /// Request head is parsed
+    fn on_request(&self, _: &PeerConnectionInfo, _: &RequestHead) {}
+
+    /// Response head is queued for writing
+    fn on_response(&self, _: &PeerConnectionInfo, _: &ResponseHead, _: BodySize) {}
+
+    /// Response body is written, `bytes` is number of written body bytes
+    fn on_response_sent(&self, _: &PeerConnectionInfo, _bytes: u64) {}
+}
+
+#[derive(Debug)]
+/// Connection information passed to dispatcher hooks
+pub struct PeerConnectionInfo {
+    id: u64,
+    protocol: types::HttpProtocol,
+    peer_addr: Option<SocketAddr>,
+    created: Instant,
+    requests: Cell<usize>,
+}
+
+impl PeerConnectionInfo {
+    fn new(io: &IoRef, protocol: types::HttpProtocol) -> Self {
+        let id = CONN_ID.with(|id| {
+            let val = id.get().wrapping_add(1);
+            id.set(val);
+            val
+        });
+
+        PeerConnectionInfo {
+            id,
+            protocol,
+            peer_addr: io
+                .query::<types::PeerAddr>()
+                .get()
+                .map(types::PeerAddr::into_inner),
+            created: now(),
+            requests: Cell::new(0),
+        }
+    }
+
+    #[inline]
+    /// Connection id, unique within worker thread
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    #[inline]
+    /// Connection protocol
+    pub fn protocol(&self) -> types::HttpProtocol {
+        self.protocol
+    }
+
+    #[inline]
+    /// Peer socket address
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
+    #[inline]
+    /// Connection start time
+    pub fn created(&self) -> Instant {
+        self.created
+    }
+
+    #[inline]
+    /// Number of requests received on this connection, including current one
+    ///
+    /// Value greater than one indicates connection reuse.
+    pub fn requests(&self) -> usize {
+        self.requests.get()
+    }
+}
+
+#[derive(Clone, Default)]
+/// Configured dispatcher hooks
+pub(super) struct Hooks(Option<Rc<dyn DispatcherHooks>>);
+
+impl Hooks {
+    pub(super) fn new<T: DispatcherHooks>(hooks: T) -> Self {
+        Hooks(Some(Rc::new(hooks)))
+    }
+
+    /// Start connection instrumentation
+    pub(super) fn connect(&self, io: &IoRef, protocol: types::HttpProtocol) -> ConnHooks {
+        ConnHooks(self.0.as_ref().map(|hooks| {
+            let info = PeerConnectionInfo::new(io, protocol);
+            hooks.on_connect(&info);
+            (hooks.clone(), info)
+        }))
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("enabled", &self.0.is_some())
+            .finish()
+    }
+}
+
+#[derive(Default)]
+/// Per-connection hooks, calls `on_disconnect` on drop
+pub(super) struct ConnHooks(Option<(Rc<dyn DispatcherHooks>, PeerConnectionInfo)>);
+
+impl ConnHooks {
+    pub(super) fn request(&self, head: &RequestHead) {
+        if let Some((ref hooks, ref info)) = self.0 {
+            info.requests.set(info.requests.get() + 1);
+            hooks.on_request(info, head);
+        }
+    }
+
+    pub(super) fn response(&self, head: &ResponseHead, size: BodySize) {
+        if let Some((ref hooks, ref info)) = self.0 {
+            hooks.on_response(info, head, size);
+        }
+    }
+
+    pub(super) fn response_sent(&self, bytes: u64) {
+        if let Some((ref hooks, ref info)) = self.0 {
+            hooks.on_response_sent(info, bytes);
+        }
+    }
+}
+
+impl Drop for ConnHooks {
+    fn drop(&mut self) {
+        if let Some((ref hooks, ref info)) = self.0 {
+            hooks.on_disconnect(info);
+        }
+    }
+}
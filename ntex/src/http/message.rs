@@ -2,8 +2,8 @@ use std::{cell::Ref, cell::RefCell, cell::RefMut, net, rc::Rc};
 
 use bitflags::bitflags;
 
-use crate::http::header::HeaderMap;
-use crate::http::{h1::Codec, Method, StatusCode, Uri, Version};
+use crate::http::header::{HeaderMap, HeaderName, HeaderValue};
+use crate::http::{h1::Codec, Method, RequestTarget, StatusCode, Uri, Version};
 use crate::io::{types, IoBoxed, IoRef};
 use crate::util::Extensions;
 
@@ -74,12 +74,14 @@ pub struct RequestHead {
     pub extensions: RefCell<Extensions>,
     pub(crate) io: CurrentIo,
     pub(crate) flags: Flags,
+    pub(crate) target: RefCell<Option<Rc<RequestTarget>>>,
 }
 
 impl Default for RequestHead {
     fn default() -> RequestHead {
         RequestHead {
             io: CurrentIo::None,
+            target: RefCell::new(None),
             uri: Uri::default(),
             method: Method::default(),
             version: Version::HTTP_11,
@@ -96,6 +98,7 @@ impl Head for RequestHead {
         self.flags = Flags::empty();
         self.headers.clear();
         self.extensions.get_mut().clear();
+        self.target.get_mut().take();
     }
 
     fn with_pool<F, R>(f: F) -> R
@@ -119,6 +122,21 @@ impl RequestHead {
         self.extensions.borrow_mut()
     }
 
+    /// Parsed view of the request target.
+    ///
+    /// Target is parsed on first access and shared for the lifetime
+    /// of the request, it is re-parsed only if request's uri changes.
+    pub fn target(&self) -> Rc<RequestTarget> {
+        if let Some(target) = self.target.borrow().as_ref() {
+            if target.uri() == &self.uri {
+                return target.clone();
+            }
+        }
+        let target = Rc::new(RequestTarget::new(self.uri.clone()));
+        *self.target.borrow_mut() = Some(target.clone());
+        target
+    }
+
     /// Read the message headers.
     pub fn headers(&self) -> &HeaderMap {
         &self.headers
@@ -226,6 +244,20 @@ impl RequestHeadType {
             RequestHeadType::Rc(_, headers) => headers.as_ref(),
         }
     }
+
+    /// Insert a header, for shared head header is inserted to extra headers
+    pub(crate) fn set_header(&mut self, key: HeaderName, value: HeaderValue) {
+        match self {
+            RequestHeadType::Owned(head) => {
+                head.headers.insert(key, value);
+            }
+            RequestHeadType::Rc(_, extra_headers) => {
+                extra_headers
+                    .get_or_insert(HeaderMap::new())
+                    .insert(key, value);
+            }
+        }
+    }
 }
 
 impl AsRef<RequestHead> for RequestHeadType {
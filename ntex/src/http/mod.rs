@@ -3,9 +3,11 @@ pub mod body;
 mod builder;
 pub mod client;
 mod config;
+pub(crate) mod disconnect;
 #[cfg(feature = "compress")]
 pub mod encoding;
 pub(crate) mod helpers;
+mod hooks;
 mod httpcodes;
 mod httpmessage;
 mod message;
@@ -13,6 +15,7 @@ mod payload;
 mod request;
 mod response;
 mod service;
+mod trailers;
 
 pub mod error;
 pub mod h1;
@@ -25,15 +28,18 @@ pub(crate) use self::message::Message;
 pub use self::builder::HttpServiceBuilder;
 pub use self::client::Client;
 pub use self::config::{DateService, KeepAlive, ServiceConfig};
+pub use self::disconnect::Disconnected;
 pub use self::error::ResponseError;
+pub use self::hooks::{DispatcherHooks, PeerConnectionInfo};
 pub use self::httpmessage::HttpMessage;
 pub use self::message::{ConnectionType, RequestHead, RequestHeadType, ResponseHead};
 pub use self::payload::{Payload, PayloadStream};
 pub use self::request::Request;
 pub use self::response::{Response, ResponseBuilder};
 pub use self::service::HttpService;
+pub use self::trailers::Trailers;
 pub use crate::io::types::HttpProtocol;
 
 // re-exports
 pub use ntex_http::uri::{self, Uri};
-pub use ntex_http::{HeaderMap, Method, StatusCode, Version};
+pub use ntex_http::{HeaderMap, Method, RequestTarget, StatusCode, Version};
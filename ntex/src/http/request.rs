@@ -1,10 +1,12 @@
 use std::{cell::Ref, cell::RefMut, fmt, mem, net};
 
+use crate::http::disconnect::{self, Disconnected};
 use crate::http::header::{self, HeaderMap};
 use crate::http::httpmessage::HttpMessage;
 use crate::http::message::{Message, RequestHead};
 use crate::http::{payload::Payload, Method, Uri, Version};
 use crate::io::{types, IoRef};
+use crate::sync::CancellationToken;
 use crate::util::Extensions;
 
 /// Request
@@ -119,12 +121,25 @@ impl Request {
     pub fn upgrade(&self) -> bool {
         if let Some(conn) = self.head().headers.get(header::CONNECTION) {
             if let Ok(s) = conn.to_str() {
-                return s.to_lowercase().contains("upgrade");
+                return s.to_lowercase().contains("upgrade") && !self.h2c_upgrade();
             }
         }
         self.head().method == Method::CONNECT
     }
 
+    /// Check if request asks for h2c upgrade
+    ///
+    /// h2c upgrade is deprecated by rfc9113, such requests
+    /// are handled as regular http/1.1 requests
+    fn h2c_upgrade(&self) -> bool {
+        self.head()
+            .headers
+            .get(header::UPGRADE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().eq_ignore_ascii_case("h2c"))
+            .unwrap_or(false)
+    }
+
     /// Io reference for current connection
     #[inline]
     pub fn io(&self) -> Option<&IoRef> {
@@ -144,6 +159,22 @@ impl Request {
         })
     }
 
+    /// Notify when client of this request get disconnected
+    ///
+    /// For http/2 requests it also resolves when request's stream get reset.
+    #[inline]
+    pub fn disconnected(&self) -> Disconnected {
+        Disconnected::new(self.head())
+    }
+
+    /// Get cancellation token of this request
+    ///
+    /// Token is cancelled when client of this request get disconnected,
+    /// long-running tasks spawned by the handler could use it to stop.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        disconnect::cancellation_token(self.head())
+    }
+
     /// Get request's payload
     pub fn payload(&mut self) -> &mut Payload {
         &mut self.payload
@@ -10,7 +10,7 @@ use crate::http::body::{Body, BodyStream, MessageBody, ResponseBody};
 use crate::http::error::{HttpError, ResponseError};
 use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use crate::http::message::{ConnectionType, Message, ResponseHead};
-use crate::http::StatusCode;
+use crate::http::{StatusCode, Trailers};
 use crate::util::{Bytes, BytesMut, Extensions, Stream};
 
 /// An HTTP Response
@@ -175,6 +175,20 @@ impl<B> Response<B> {
         self.head.extensions.borrow_mut()
     }
 
+    /// Get response trailers
+    #[inline]
+    pub fn trailers(&self) -> Option<Trailers> {
+        self.extensions().get::<Trailers>().cloned()
+    }
+
+    /// Set response trailers
+    ///
+    /// Trailers are sent after response body, only for http/2 connections.
+    #[inline]
+    pub fn set_trailers(&mut self, trailers: Trailers) {
+        self.extensions_mut().insert(trailers);
+    }
+
     /// Get body of this response
     #[inline]
     pub fn body(&self) -> &ResponseBody<B> {
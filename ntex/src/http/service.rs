@@ -2,6 +2,7 @@ use std::{error, fmt, marker, rc::Rc, task::Context, task::Poll};
 
 use crate::io::{types, Filter, Io};
 use crate::service::{IntoServiceFactory, Service, ServiceCtx, ServiceFactory};
+use crate::time::{timeout_checked, Seconds};
 
 use super::body::MessageBody;
 use super::builder::HttpServiceBuilder;
@@ -11,6 +12,9 @@ use super::request::Request;
 use super::response::Response;
 use super::{h1, h2};
 
+/// Preface detection timeout if client timeout is disabled
+const H2C_PREFACE_TIMEOUT: Seconds = Seconds(5);
+
 /// `ServiceFactory` HTTP1.1/HTTP2 transport implementation
 pub struct HttpService<
     F,
@@ -202,7 +206,10 @@ mod rustls {
         C2::Error: error::Error,
         C2::InitError: fmt::Debug,
     {
-        /// Create openssl based service
+        /// Create rustls based service
+        ///
+        /// Alpn protocols are set to "h2" and "http/1.1" if config
+        /// does not define alpn protocols.
         pub fn rustls(
             self,
             mut config: ServerConfig,
@@ -212,8 +219,10 @@ mod rustls {
             Error = SslError<DispatchError>,
             InitError = (),
         > {
-            let protos = vec!["h2".to_string().into(), "http/1.1".to_string().into()];
-            config.alpn_protocols = protos;
+            if config.alpn_protocols.is_empty() {
+                let protos = vec!["h2".to_string().into(), "http/1.1".to_string().into()];
+                config.alpn_protocols = protos;
+            }
 
             TlsAcceptor::from(config)
                 .timeout(self.cfg.ssl_handshake_timeout)
@@ -338,7 +347,10 @@ where
             io.query::<types::PeerAddr>().get()
         );
 
-        if io.query::<types::HttpProtocol>().get() == Some(types::HttpProtocol::Http2) {
+        let h2 =
+            io.query::<types::HttpProtocol>().get() == Some(types::HttpProtocol::Http2);
+
+        if h2 || (self.config.h2c && h2c_preface(&io, &self.config).await) {
             let control = self.h2_control.create(()).await.map_err(|e| {
                 DispatchError::Control(
                     format!("Cannot construct control service: {:?}", e).into(),
@@ -352,3 +364,34 @@ where
         }
     }
 }
+
+/// Check if cleartext connection starts with http/2 preface
+async fn h2c_preface<F, S, C>(io: &Io<F>, cfg: &DispatcherConfig<S, C>) -> bool {
+    let timeout = cfg
+        .headers_read_rate()
+        .map(|rate| rate.timeout)
+        .unwrap_or(H2C_PREFACE_TIMEOUT);
+
+    let detect = async {
+        loop {
+            let result = io.with_read_buf(|buf| {
+                if buf.len() >= h2::PREFACE.len() {
+                    Some(buf.starts_with(h2::PREFACE))
+                } else if !h2::PREFACE.starts_with(&buf[..]) {
+                    Some(false)
+                } else {
+                    None
+                }
+            });
+            if let Some(result) = result {
+                return result;
+            }
+            // wait for more data
+            if !matches!(io.read_ready().await, Ok(Some(_))) {
+                return false;
+            }
+        }
+    };
+    // on timeout, let http/1 dispatcher handle slow request
+    timeout_checked(timeout, detect).await.unwrap_or(false)
+}
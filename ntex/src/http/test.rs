@@ -10,7 +10,7 @@ use crate::io::Io;
 #[cfg(feature = "ws")]
 use crate::ws::{error::WsClientError, WsClient, WsConnection};
 use crate::{rt::System, service::ServiceFactory};
-use crate::{time::Millis, time::Seconds, util::Bytes};
+use crate::{time::Millis, time::Seconds, util::Bytes, util::Stream};
 
 use super::client::{Client, ClientRequest, ClientResponse, Connector};
 use super::error::{HttpError, PayloadError};
@@ -136,6 +136,15 @@ impl TestRequest {
         self
     }
 
+    /// Set request payload stream
+    pub fn set_payload_stream<S>(&mut self, stream: S) -> &mut Self
+    where
+        S: Stream<Item = Result<Bytes, PayloadError>> + 'static,
+    {
+        parts(&mut self.0).payload = Some(Payload::from_stream(stream));
+        self
+    }
+
     /// Take test request
     pub fn take(&mut self) -> TestRequest {
         TestRequest(self.0.take())
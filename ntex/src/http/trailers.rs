@@ -0,0 +1,68 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::http::header::{HeaderMap, HeaderName, HeaderValue};
+
+#[derive(Clone, Default)]
+/// Response trailers
+///
+/// Trailers are sent after response body. Trailers handle is shared,
+/// so trailers could be set while response body is streaming.
+///
+/// Trailers are supported only by http/2 protocol, http/1 connections
+/// ignore response trailers.
+pub struct Trailers(Rc<RefCell<HeaderMap>>);
+
+impl Trailers {
+    /// Create empty trailers
+    pub fn new() -> Self {
+        Trailers::default()
+    }
+
+    /// Set a trailer, replaces previous value with the same name
+    pub fn insert(&self, name: HeaderName, value: HeaderValue) {
+        self.0.borrow_mut().insert(name, value);
+    }
+
+    /// Get trailer value
+    pub fn get(&self, name: &HeaderName) -> Option<HeaderValue> {
+        self.0.borrow().get(name).cloned()
+    }
+
+    /// Check if trailers are empty
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    /// Take trailers, leaves empty map
+    pub fn take(&self) -> HeaderMap {
+        std::mem::take(&mut *self.0.borrow_mut())
+    }
+}
+
+impl fmt::Debug for Trailers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Trailers").field(&*self.0.borrow()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header;
+
+    #[test]
+    fn test_trailers() {
+        let trailers = Trailers::new();
+        assert!(trailers.is_empty());
+
+        let trailers2 = trailers.clone();
+        trailers2.insert(header::CONTENT_TYPE, HeaderValue::from_static("text"));
+        assert!(!trailers.is_empty());
+        assert_eq!(trailers.get(&header::CONTENT_TYPE).unwrap(), "text");
+        assert!(format!("{:?}", trailers).contains("Trailers"));
+
+        let map = trailers.take();
+        assert_eq!(map.len(), 1);
+        assert!(trailers2.is_empty());
+    }
+}
@@ -6,6 +6,7 @@
 //! * `rustls` - enables ssl support via `rustls` crate
 //! * `compress` - enables compression support in http and web modules
 //! * `cookie` - enables cookie support in http and web modules
+//! * `grpc` - enables grpc support
 #![warn(
     rust_2018_idioms,
     unreachable_pub,
@@ -35,12 +36,18 @@ pub mod web;
 #[cfg(feature = "ws")]
 pub mod ws;
 
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
+#[cfg(any(feature = "fuzzing", test))]
+pub mod fuzzing;
+
 pub use self::service::{
     chain, chain_factory, fn_service, into_service, IntoService, IntoServiceFactory,
     Middleware, Pipeline, Service, ServiceCtx, ServiceFactory,
 };
 
-pub use ntex_util::{channel, task};
+pub use ntex_util::{channel, sync, task};
 
 pub mod codec {
     //! Utilities for encoding and decoding frames.
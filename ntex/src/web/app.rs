@@ -7,6 +7,7 @@ use crate::service::{
     chain_factory, dev::ServiceChainFactory, map_config, IntoServiceFactory,
 };
 use crate::service::{Identity, Middleware, Service, ServiceCtx, ServiceFactory, Stack};
+use crate::time::{sleep, Millis};
 use crate::util::{BoxFuture, Extensions};
 
 use super::app_service::{AppFactory, AppService};
@@ -16,12 +17,32 @@ use super::resource::Resource;
 use super::response::WebResponse;
 use super::route::Route;
 use super::service::{AppServiceFactory, ServiceFactoryWrapper, WebServiceFactory};
+use super::types::Provider;
 use super::{DefaultError, ErrorRenderer};
 
 type HttpNewService<Err: ErrorRenderer> =
     BoxServiceFactory<(), WebRequest<Err>, WebResponse, Err::Container, ()>;
 type FnStateFactory = Box<dyn Fn(Extensions) -> BoxFuture<'static, Result<Extensions, ()>>>;
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// State factory failure policy
+pub enum StatePolicy {
+    /// Fail application initialization, worker does not start
+    Abort,
+    /// Retry state construction after `delay`, but no more than `retries` times
+    Retry { retries: usize, delay: Millis },
+}
+
+impl StatePolicy {
+    /// Retry state construction until it succeeds
+    pub fn retry_forever(delay: Millis) -> Self {
+        StatePolicy::Retry {
+            delay,
+            retries: usize::MAX,
+        }
+    }
+}
+
 /// Application builder - structure that follows the builder pattern
 /// for building application instances.
 pub struct App<M, F, Err: ErrorRenderer = DefaultError> {
@@ -34,6 +55,8 @@ pub struct App<M, F, Err: ErrorRenderer = DefaultError> {
     state_factories: Vec<FnStateFactory>,
     error_renderer: Err,
     case_insensitive: bool,
+    radix_routing: bool,
+    method_not_allowed: bool,
 }
 
 impl App<Identity, Filter<DefaultError>, DefaultError> {
@@ -49,6 +72,8 @@ impl App<Identity, Filter<DefaultError>, DefaultError> {
             extensions: Extensions::new(),
             error_renderer: DefaultError,
             case_insensitive: false,
+            radix_routing: false,
+            method_not_allowed: false,
         }
     }
 }
@@ -66,6 +91,8 @@ impl<Err: ErrorRenderer> App<Identity, Filter<Err>, Err> {
             extensions: Extensions::new(),
             error_renderer: err,
             case_insensitive: false,
+            radix_routing: false,
+            method_not_allowed: false,
         }
     }
 }
@@ -117,27 +144,100 @@ where
         self
     }
 
+    /// Register request-scoped dependency provider.
+    ///
+    /// Provider constructs value lazily, once per request, when value
+    /// is requested with `Inject<T>` extractor.
+    ///
+    /// ```rust
+    /// use ntex::web::{self, types::Inject, types::Provider, App, HttpRequest};
+    ///
+    /// struct RequestId(String);
+    ///
+    /// let app = App::new()
+    ///     .provide(Provider::new(|req: HttpRequest| async move {
+    ///         let id = req.headers().get("x-request-id")
+    ///             .and_then(|v| v.to_str().ok()).unwrap_or("-").to_string();
+    ///         Ok::<_, web::Error>(RequestId(id))
+    ///     }))
+    ///     .route("/index.html", web::get().to(|id: Inject<RequestId>| async move {
+    ///         id.0.clone()
+    ///     }));
+    /// ```
+    pub fn provide<U: 'static>(mut self, provider: Provider<U, Err>) -> Self {
+        self.extensions.insert(provider);
+        self
+    }
+
     /// Set application state factory. This function is
     /// similar to `.state()` but it accepts state factory. State object get
     /// constructed asynchronously during application initialization.
-    pub fn state_factory<F, Out, D, E>(mut self, state: F) -> Self
+    ///
+    /// Application initialization fails if state factory fails.
+    pub fn state_factory<F, Out, D, E>(self, state: F) -> Self
+    where
+        F: Fn() -> Out + 'static,
+        Out: Future<Output = Result<D, E>> + 'static,
+        D: 'static,
+        E: fmt::Debug,
+    {
+        self.state_factory_with(state, StatePolicy::Abort)
+    }
+
+    /// Set application state factory with failure policy.
+    ///
+    /// State factories run in each worker during application initialization,
+    /// worker does not accept connections until all states are constructed.
+    /// Failed state construction either fails worker start or
+    /// get retried, depending on `policy`.
+    ///
+    /// ```rust
+    /// use ntex::{time::Millis, web::{self, App, StatePolicy}};
+    ///
+    /// struct DbPool;
+    ///
+    /// async fn connect() -> Result<DbPool, std::io::Error> {
+    ///     Ok(DbPool)
+    /// }
+    ///
+    /// let app = App::new()
+    ///     .state_factory_with(connect, StatePolicy::Retry { retries: 5, delay: Millis(500) })
+    ///     .route("/", web::get().to(|_: web::types::State<DbPool>| async { "ok" }));
+    /// ```
+    pub fn state_factory_with<F, Out, D, E>(mut self, state: F, policy: StatePolicy) -> Self
     where
         F: Fn() -> Out + 'static,
         Out: Future<Output = Result<D, E>> + 'static,
         D: 'static,
         E: fmt::Debug,
     {
+        let state = Rc::new(state);
         self.state_factories.push(Box::new(move |mut ext| {
-            let fut = state();
+            let state = state.clone();
             Box::pin(async move {
-                match fut.await {
-                    Err(e) => {
-                        log::error!("Cannot construct state instance: {:?}", e);
-                        Err(())
-                    }
-                    Ok(st) => {
-                        ext.insert(st);
-                        Ok(ext)
+                let mut retries = 0;
+                loop {
+                    match state().await {
+                        Ok(st) => {
+                            ext.insert(st);
+                            return Ok(ext);
+                        }
+                        Err(e) => match policy {
+                            StatePolicy::Retry { retries: max, delay } if retries < max => {
+                                retries += 1;
+                                log::warn!(
+                                    "Cannot construct state instance: {:?}, retry #{} in {:?}",
+                                    e,
+                                    retries,
+                                    delay
+                                );
+                                sleep(delay).await;
+                            }
+                            _ => {
+                                log::error!("Cannot construct state instance: {:?}", e);
+                                return Err(());
+                            }
+                        },
                     }
                 }
             })
@@ -367,6 +467,8 @@ where
             extensions: self.extensions,
             error_renderer: self.error_renderer,
             case_insensitive: self.case_insensitive,
+            radix_routing: self.radix_routing,
+            method_not_allowed: self.method_not_allowed,
         }
     }
 
@@ -407,6 +509,8 @@ where
             extensions: self.extensions,
             error_renderer: self.error_renderer,
             case_insensitive: self.case_insensitive,
+            radix_routing: self.radix_routing,
+            method_not_allowed: self.method_not_allowed,
         }
     }
 
@@ -417,6 +521,43 @@ where
         self.case_insensitive = true;
         self
     }
+
+    /// Use radix tree router for top level resources.
+    ///
+    /// Router indexes resources by static path prefix, so resolution cost
+    /// does not grow with the number of registered resources. It is useful
+    /// for applications with large routing tables. Resources are checked
+    /// in registration order, pattern syntax is the same as for default router.
+    pub fn radix_routing(mut self) -> Self {
+        self.radix_routing = true;
+        self
+    }
+
+    /// Respond with *405 Method Not Allowed* if request path matches
+    /// a resource but request method does not.
+    ///
+    /// Response contains `Allow` header with the list of methods supported
+    /// by the matched resources. `OPTIONS` requests are answered
+    /// automatically with *204 No Content* and `Allow` header.
+    /// Setting could be overridden for specific scope with
+    /// `Scope::method_not_allowed()` method.
+    ///
+    /// By default path with unmatched method is routed to the default service.
+    ///
+    /// ```rust
+    /// use ntex::web::{self, App, HttpResponse};
+    ///
+    /// fn main() {
+    ///     let app = App::new()
+    ///         .method_not_allowed(true)
+    ///         .route("/index.html", web::get().to(|| async { HttpResponse::Ok() }))
+    ///         .route("/index.html", web::post().to(|| async { HttpResponse::Ok() }));
+    /// }
+    /// ```
+    pub fn method_not_allowed(mut self, enabled: bool) -> Self {
+        self.method_not_allowed = enabled;
+        self
+    }
 }
 
 impl<M, F, Err> App<M, F, Err>
@@ -499,6 +640,8 @@ where
             default: self.default,
             extensions: RefCell::new(Some(self.extensions)),
             case_insensitive: self.case_insensitive,
+            radix_routing: self.radix_routing,
+            method_not_allowed: self.method_not_allowed,
         };
         map_config(app, move |_| cfg.clone())
     }
@@ -527,6 +670,8 @@ where
             default: self.default,
             extensions: RefCell::new(Some(self.extensions)),
             case_insensitive: self.case_insensitive,
+            radix_routing: self.radix_routing,
+            method_not_allowed: self.method_not_allowed,
         }
     }
 }
@@ -553,6 +698,8 @@ where
             default: self.default,
             extensions: RefCell::new(Some(self.extensions)),
             case_insensitive: self.case_insensitive,
+            radix_routing: self.radix_routing,
+            method_not_allowed: self.method_not_allowed,
         }
     }
 }
@@ -676,6 +823,60 @@ mod tests {
         assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[crate::rt_test]
+    async fn test_state_factory_policy() {
+        let attempts = Rc::new(std::cell::Cell::new(0));
+        let attempts2 = attempts.clone();
+        let srv = init_service(
+            App::new()
+                .state_factory_with(
+                    move || {
+                        let attempts = attempts2.clone();
+                        async move {
+                            attempts.set(attempts.get() + 1);
+                            if attempts.get() < 3 {
+                                Err("not ready")
+                            } else {
+                                Ok(10usize)
+                            }
+                        }
+                    },
+                    StatePolicy::Retry {
+                        retries: 5,
+                        delay: Millis(10),
+                    },
+                )
+                .service(
+                    web::resource("/")
+                        .to(|_: web::types::State<usize>| async { HttpResponse::Ok() }),
+                ),
+        )
+        .await;
+        assert_eq!(attempts.get(), 3);
+        let req = TestRequest::default().to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let attempts2 = attempts.clone();
+        attempts.set(0);
+        let app = App::new().state_factory_with(
+            move || {
+                let attempts = attempts2.clone();
+                async move {
+                    attempts.set(attempts.get() + 1);
+                    Err::<usize, _>("not ready")
+                }
+            },
+            StatePolicy::Retry {
+                retries: 2,
+                delay: Millis(10),
+            },
+        );
+        let res = app.into_factory().create(AppConfig::default()).await;
+        assert!(res.is_err());
+        assert_eq!(attempts.get(), 3);
+    }
+
     #[crate::rt_test]
     async fn test_extension() {
         let srv = init_service(
@@ -772,6 +973,48 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
+    #[crate::rt_test]
+    async fn test_radix_router() {
+        let mut app = App::new().radix_routing();
+        for idx in 0..100 {
+            app = app.route(
+                &format!("/resource{}/{{id}}", idx),
+                web::get().to(move |id: web::types::Path<String>| async move {
+                    format!("{}:{}", idx, id.into_inner())
+                }),
+            );
+        }
+        let srv = init_service(
+            app.service(
+                web::scope("/api")
+                    .route("/test", web::get().to(|| async { HttpResponse::Created() })),
+            )
+            .route("/{name}", web::get().to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/resource42/abc").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"42:abc"));
+
+        let req = TestRequest::with_uri("/api/test").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let req = TestRequest::with_uri("/other").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/resource42").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/resource42/abc/def").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
     #[cfg(feature = "url")]
     #[crate::rt_test]
     async fn test_external_resource() {
@@ -793,4 +1036,72 @@ mod tests {
         let body = read_body(resp).await;
         assert_eq!(body, Bytes::from_static(b"https://youtube.com/watch/12345"));
     }
+
+    #[crate::rt_test]
+    async fn test_method_not_allowed() {
+        let srv = init_service(
+            App::new()
+                .method_not_allowed(true)
+                .route("/test", web::get().to(|| async { HttpResponse::Ok() }))
+                .route("/test", web::put().to(|| async { HttpResponse::Ok() }))
+                .service(
+                    web::resource("/res")
+                        .route(web::get().to(|| async { HttpResponse::Ok() })),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::PUT)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::DELETE)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            resp.headers().get(header::ALLOW).unwrap(),
+            HeaderValue::from_static("GET, PUT, OPTIONS")
+        );
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::OPTIONS)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::ALLOW).unwrap(),
+            HeaderValue::from_static("GET, PUT, OPTIONS")
+        );
+
+        let req = TestRequest::with_uri("/res")
+            .method(Method::OPTIONS)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::ALLOW).unwrap(),
+            HeaderValue::from_static("GET, OPTIONS")
+        );
+
+        let req = TestRequest::with_uri("/unknown")
+            .method(Method::DELETE)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // disabled by default
+        let srv = init_service(
+            App::new().route("/test", web::get().to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+        let req = TestRequest::with_uri("/test")
+            .method(Method::DELETE)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -12,6 +12,7 @@ use super::error::ErrorRenderer;
 use super::guard::Guard;
 use super::httprequest::{HttpRequest, HttpRequestPool};
 use super::request::WebRequest;
+use super::resource;
 use super::response::WebResponse;
 use super::rmap::ResourceMap;
 use super::service::{AppServiceFactory, AppState, WebServiceConfig};
@@ -43,6 +44,8 @@ where
     pub(super) default: Option<Rc<HttpNewService<Err>>>,
     pub(super) external: RefCell<Vec<ResourceDef>>,
     pub(super) case_insensitive: bool,
+    pub(super) radix_routing: bool,
+    pub(super) method_not_allowed: bool,
 }
 
 impl<T, F, Err> ServiceFactory<Request> for AppFactory<T, F, Err>
@@ -107,6 +110,9 @@ where
         if self.case_insensitive {
             router.case_insensitive();
         }
+        if self.radix_routing {
+            router.radix();
+        }
 
         // app state factories
         for fut in state_factories.iter() {
@@ -118,6 +124,7 @@ where
 
         // App config
         let mut config = WebServiceConfig::new(state.clone(), default.clone());
+        config.set_method_not_allowed(self.method_not_allowed);
 
         // register services
         services
@@ -134,8 +141,8 @@ where
         // complete pipeline creation
         let services: Vec<_> = services
             .into_iter()
-            .map(|(mut rdef, srv, guards, nested)| {
-                rmap.add(&mut rdef, nested);
+            .map(|(mut rdef, srv, guards, nested, info)| {
+                rmap.add_resource(&mut rdef, nested, info);
                 (rdef, srv, RefCell::new(guards))
             })
             .collect();
@@ -155,6 +162,7 @@ where
 
         let routing = AppRouting {
             router: router.finish(),
+            method_not_allowed: self.method_not_allowed,
             default: Some(
                 default
                     .create(())
@@ -246,6 +254,7 @@ where
 struct AppRouting<Err: ErrorRenderer> {
     router: Router<HttpService<Err>, Guards>,
     default: Option<HttpService<Err>>,
+    method_not_allowed: bool,
 }
 
 impl<Err: ErrorRenderer> Service<WebRequest<Err>> for AppRouting<Err> {
@@ -269,8 +278,17 @@ impl<Err: ErrorRenderer> Service<WebRequest<Err>> for AppRouting<Err> {
         });
 
         if let Some((srv, _info)) = res {
-            ctx.call(srv, req).await
-        } else if let Some(ref default) = self.default {
+            return ctx.call(srv, req).await;
+        }
+
+        if self.method_not_allowed {
+            let methods = resource::allowed_methods(&self.router, &mut req);
+            if !methods.is_empty() {
+                return Ok(resource::method_not_allowed(req, methods, true));
+            }
+        }
+
+        if let Some(ref default) = self.default {
             ctx.call(default, req).await
         } else {
             let req = req.into_parts().0;
@@ -2,6 +2,7 @@ use std::{net::SocketAddr, rc::Rc};
 
 use crate::{router::ResourceDef, util::Extensions};
 
+use super::info::TrustedProxies;
 use super::resource::Resource;
 use super::route::Route;
 use super::service::{AppServiceFactory, ServiceFactoryWrapper, WebServiceFactory};
@@ -16,12 +17,36 @@ struct AppConfigInner {
     secure: bool,
     host: String,
     addr: SocketAddr,
+    trusted_proxies: Option<TrustedProxies>,
 }
 
 impl AppConfig {
     /// Create an AppConfig instance.
     pub fn new(secure: bool, addr: SocketAddr, host: String) -> Self {
-        AppConfig(Rc::new(AppConfigInner { secure, host, addr }))
+        AppConfig(Rc::new(AppConfigInner {
+            secure,
+            host,
+            addr,
+            trusted_proxies: None,
+        }))
+    }
+
+    /// Set trusted proxies configuration.
+    ///
+    /// Check [TrustedProxies](./struct.TrustedProxies.html)
+    /// documentation for more information.
+    pub fn with_trusted_proxies(self, proxies: Option<TrustedProxies>) -> Self {
+        AppConfig(Rc::new(AppConfigInner {
+            secure: self.0.secure,
+            host: self.0.host.clone(),
+            addr: self.0.addr,
+            trusted_proxies: proxies,
+        }))
+    }
+
+    /// Trusted proxies configuration
+    pub fn trusted_proxies(&self) -> Option<&TrustedProxies> {
+        self.0.trusted_proxies.as_ref()
     }
 
     /// Server host name.
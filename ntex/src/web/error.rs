@@ -25,6 +25,71 @@ pub trait ErrorRenderer: Sized + 'static {
 pub trait ErrorContainer: error::ResponseError + Sized {
     /// Generate response for error container
     fn error_response(&self, req: &HttpRequest) -> HttpResponse;
+
+    /// Error category
+    ///
+    /// `ErrorCategory::Handler` is returned by default.
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::Handler
+    }
+}
+
+/// Category of the web error
+///
+/// Category allows to distinguish errors caused by client, for example
+/// client disconnected during payload upload, from handler failures.
+/// Category of the error is stored in response extensions, it is available
+/// via `WebResponse::error_category()` method.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Request handling failed
+    Handler,
+    /// Request aborted by the client
+    Aborted,
+    /// Request handling timed out
+    Timeout,
+}
+
+impl ErrorCategory {
+    /// Category name, `handler`, `aborted` or `timeout`
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Handler => "handler",
+            ErrorCategory::Aborted => "aborted",
+            ErrorCategory::Timeout => "timeout",
+        }
+    }
+
+    /// Category of the io error
+    pub fn from_io(err: &std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        match err.kind() {
+            ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof => ErrorCategory::Aborted,
+            ErrorKind::TimedOut => ErrorCategory::Timeout,
+            _ => ErrorCategory::Handler,
+        }
+    }
+
+    /// Category of the payload error
+    pub fn from_payload(err: &error::PayloadError) -> Self {
+        match err {
+            error::PayloadError::Incomplete(_) | error::PayloadError::Http2Payload(_) => {
+                ErrorCategory::Aborted
+            }
+            error::PayloadError::Io(err) => ErrorCategory::from_io(err),
+            _ => ErrorCategory::Handler,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// Error that can be rendered to a `Response`
@@ -53,6 +118,13 @@ where
         );
         resp.set_body(Body::from(buf))
     }
+
+    /// Error category
+    ///
+    /// `ErrorCategory::Handler` is returned by default.
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::Handler
+    }
 }
 
 impl<Err: ErrorRenderer> WebResponseError<Err> for std::convert::Infallible {}
@@ -76,6 +148,13 @@ where
             Either::Right(ref b) => b.error_response(req),
         }
     }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            Either::Left(ref a) => a.category(),
+            Either::Right(ref b) => b.category(),
+        }
+    }
 }
 
 /// Errors which can occur when attempting to work with `State` extractor
@@ -171,6 +250,80 @@ pub enum PayloadError {
     Decoding,
 }
 
+/// Authentication error kind
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AuthErrorKind {
+    /// `Authorization` header is missing
+    #[error("Authorization header is missing")]
+    Missing,
+    /// `Authorization` header uses different scheme
+    #[error("Unsupported authorization scheme")]
+    InvalidScheme,
+    /// Credentials cannot be parsed
+    #[error("Invalid authorization credentials")]
+    Invalid,
+    /// Credentials are rejected by validator
+    #[error("Authorization credentials are rejected")]
+    Rejected,
+}
+
+/// Errors which can occur when attempting to extract authorization credentials.
+///
+/// Error generates *401 Unauthorized* response with `WWW-Authenticate` header.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{kind}")]
+pub struct AuthError {
+    scheme: &'static str,
+    realm: Option<String>,
+    kind: AuthErrorKind,
+}
+
+impl AuthError {
+    /// Create authentication error for specified scheme
+    pub fn new(scheme: &'static str, kind: AuthErrorKind) -> Self {
+        AuthError {
+            scheme,
+            kind,
+            realm: None,
+        }
+    }
+
+    /// Set authentication realm
+    pub fn realm<T: Into<String>>(mut self, realm: T) -> Self {
+        self.realm = Some(realm.into());
+        self
+    }
+
+    /// Error kind
+    pub fn kind(&self) -> AuthErrorKind {
+        self.kind
+    }
+
+    /// Authentication scheme
+    pub fn scheme(&self) -> &'static str {
+        self.scheme
+    }
+
+    /// `WWW-Authenticate` challenge
+    pub fn challenge(&self) -> String {
+        let mut challenge = self.scheme.to_string();
+        let mut params = Vec::new();
+        if let Some(ref realm) = self.realm {
+            params.push(format!("realm=\"{}\"", realm.replace(['"', '\\'], "")));
+        }
+        if self.scheme == "Bearer"
+            && matches!(self.kind, AuthErrorKind::Invalid | AuthErrorKind::Rejected)
+        {
+            params.push("error=\"invalid_token\"".to_string());
+        }
+        if !params.is_empty() {
+            challenge.push(' ');
+            challenge.push_str(&params.join(", "));
+        }
+        challenge
+    }
+}
+
 /// Helper type that can wrap any error and generate custom response.
 ///
 /// In following example any `io::Error` will be converted into "BAD REQUEST"
@@ -712,6 +865,13 @@ mod tests {
         );
         assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
 
+        use crate::util::timeout::CallTimeoutError;
+        let resp = WebResponseError::<DefaultError>::error_response(
+            &CallTimeoutError::<UrlencodedError>::Elapsed,
+            &req,
+        );
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+
         let resp = WebResponseError::<DefaultError>::error_response(
             &SendRequestError::Connect(ConnectError::Timeout),
             &req,
@@ -8,11 +8,12 @@ use serde_urlencoded::ser::Error as FormError;
 use crate::http::body::Body;
 use crate::http::helpers::Writer;
 use crate::http::{self, header, StatusCode};
-use crate::util::{timeout::TimeoutError, BytesMut};
+use crate::util::timeout::{CallTimeoutError, TimeoutError};
+use crate::util::BytesMut;
 #[cfg(feature = "ws")]
 use crate::ws::error::HandshakeError;
 
-use super::error::{self, ErrorContainer, ErrorRenderer, WebResponseError};
+use super::error::{self, ErrorCategory, ErrorContainer, ErrorRenderer, WebResponseError};
 use super::{HttpRequest, HttpResponse};
 
 /// Default error type
@@ -55,11 +56,16 @@ impl ErrorContainer for Error {
     fn error_response(&self, req: &HttpRequest) -> HttpResponse {
         self.cause.error_response(req)
     }
+
+    fn category(&self) -> ErrorCategory {
+        self.cause.category()
+    }
 }
 
 impl crate::http::error::ResponseError for Error {
     fn error_response(&self) -> HttpResponse {
         let mut resp = HttpResponse::new(self.cause.status_code());
+        resp.extensions_mut().insert(self.cause.category());
         let mut buf = BytesMut::new();
         let _ = write!(Writer(&mut buf), "{}", self.cause);
         resp.headers_mut().insert(
@@ -90,6 +96,32 @@ impl<E: WebResponseError<DefaultError>> WebResponseError<DefaultError> for Timeo
             TimeoutError::Timeout => StatusCode::GATEWAY_TIMEOUT,
         }
     }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            TimeoutError::Service(e) => e.category(),
+            TimeoutError::Timeout => ErrorCategory::Timeout,
+        }
+    }
+}
+
+/// Return `GATEWAY_TIMEOUT` for `CallTimeoutError`
+impl<E: WebResponseError<DefaultError>> WebResponseError<DefaultError>
+    for CallTimeoutError<E>
+{
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CallTimeoutError::Service(e) => e.status_code(),
+            CallTimeoutError::Elapsed => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            CallTimeoutError::Service(e) => e.category(),
+            CallTimeoutError::Elapsed => ErrorCategory::Timeout,
+        }
+    }
 }
 
 /// `InternalServerError` for `StateExtractorError`
@@ -148,6 +180,10 @@ impl WebResponseError<DefaultError> for io::Error {
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
+
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::from_io(self)
+    }
 }
 
 /// `InternalServerError` for `UrlGeneratorError`
@@ -162,6 +198,13 @@ impl WebResponseError<DefaultError> for error::UrlencodedError {
             _ => StatusCode::BAD_REQUEST,
         }
     }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            error::UrlencodedError::Payload(err) => ErrorCategory::from_payload(err),
+            _ => ErrorCategory::Handler,
+        }
+    }
 }
 
 /// Return `BadRequest` for `JsonPayloadError`
@@ -172,6 +215,13 @@ impl WebResponseError<DefaultError> for error::JsonPayloadError {
             _ => StatusCode::BAD_REQUEST,
         }
     }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            error::JsonPayloadError::Payload(err) => ErrorCategory::from_payload(err),
+            _ => ErrorCategory::Handler,
+        }
+    }
 }
 
 /// Error renderer for `PathError`
@@ -192,6 +242,13 @@ impl WebResponseError<DefaultError> for error::PayloadError {
     fn status_code(&self) -> StatusCode {
         StatusCode::BAD_REQUEST
     }
+
+    fn category(&self) -> ErrorCategory {
+        match self {
+            error::PayloadError::Payload(err) => ErrorCategory::from_payload(err),
+            _ => ErrorCategory::Handler,
+        }
+    }
 }
 
 /// `PayloadError` returns two possible results:
@@ -205,6 +262,29 @@ impl WebResponseError<DefaultError> for http::error::PayloadError {
             _ => StatusCode::BAD_REQUEST,
         }
     }
+
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::from_payload(self)
+    }
+}
+
+/// Return `Unauthorized` with `WWW-Authenticate` challenge for `AuthError`
+impl WebResponseError<DefaultError> for error::AuthError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+
+    fn error_response(&self, _: &HttpRequest) -> HttpResponse {
+        let mut resp = HttpResponse::new(self.status_code());
+        if let Ok(val) = header::HeaderValue::try_from(self.challenge()) {
+            resp.headers_mut().insert(header::WWW_AUTHENTICATE, val);
+        }
+        resp.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("text/plain; charset=utf-8"),
+        );
+        resp.set_body(Body::from(self.to_string()))
+    }
 }
 
 #[cfg(feature = "cookie")]
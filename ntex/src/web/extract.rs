@@ -3,7 +3,7 @@ use std::future::Future;
 
 use super::error::ErrorRenderer;
 use super::httprequest::HttpRequest;
-use crate::http::Payload;
+use crate::http::{Disconnected, Payload};
 
 /// Trait implemented by types that can be extracted from request.
 ///
@@ -157,6 +157,34 @@ impl<E: ErrorRenderer> FromRequest<E> for () {
     }
 }
 
+/// Client disconnect notification could be used as an extractor
+///
+/// ## Example
+///
+/// ```rust
+/// use ntex::{http::Disconnected, time, util::select, web};
+///
+/// async fn index(disconnected: Disconnected) -> String {
+///     // abort long running computation if client goes away
+///     select(disconnected, time::sleep(time::Seconds(30))).await;
+///     "done".to_string()
+/// }
+///
+/// fn main() {
+///     let app = web::App::new().service(
+///         web::resource("/").route(web::get().to(index))
+///     );
+/// }
+/// ```
+impl<E: ErrorRenderer> FromRequest<E> for Disconnected {
+    type Error = E::Container;
+
+    #[inline]
+    async fn from_request(req: &HttpRequest, _: &mut Payload) -> Result<Self, Self::Error> {
+        Ok(req.disconnected())
+    }
+}
+
 macro_rules! tuple_from_req ({$fut_type:ident, $(($n:tt, $T:ident)),+} => {
     /// FromRequest implementation for a tuple
     #[allow(unused_parens)]
@@ -250,6 +278,17 @@ mod tests {
         assert_eq!(r, None);
     }
 
+    #[crate::rt_test]
+    async fn test_disconnected() {
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let disconnected = from_request::<crate::http::Disconnected>(&req, &mut pl)
+            .await
+            .unwrap();
+        assert!(crate::util::lazy(|cx| disconnected.poll_ready(cx))
+            .await
+            .is_pending());
+    }
+
     #[crate::rt_test]
     async fn test_result() {
         let (req, mut pl) = TestRequest::with_header(
@@ -0,0 +1,236 @@
+//! Grpc methods for web application
+//!
+//! ```rust,no_run
+//! use ntex::grpc::{MethodDef, Request, Status};
+//! use ntex::{util::Bytes, web};
+//!
+//! struct Echo;
+//!
+//! impl MethodDef for Echo {
+//!     const PATH: &'static str = "/echo.Echo/Echo";
+//!     type Input = Bytes;
+//!     type Output = Bytes;
+//! }
+//!
+//! async fn echo(req: Request<Bytes>) -> Result<Bytes, Status> {
+//!     Ok(req.into_inner())
+//! }
+//!
+//! #[ntex::main]
+//! async fn main() -> std::io::Result<()> {
+//!     web::server(|| web::App::new().service(web::grpc::unary::<Echo, _, _, _>(echo)))
+//!         .bind_h2c("127.0.0.1:50051")?
+//!         .run()
+//!         .await
+//! }
+//! ```
+use std::{future::Future, rc::Rc};
+
+use crate::grpc::{self, Message, MethodDef, Status};
+use crate::http::header::{self, HeaderMap, HeaderValue};
+use crate::http::{Response, StatusCode, Trailers};
+use crate::util::{Bytes, BytesMut};
+use crate::{time::timeout, web};
+
+use super::{ErrorRenderer, HttpRequest, Resource};
+
+pub use crate::grpc::{Code, Request};
+
+/// Create resource for unary grpc method
+///
+/// Resource is registered for `MethodDef::PATH` and accepts only `POST`
+/// requests with `application/grpc` content type. If request contains
+/// `grpc-timeout` header, handler is cancelled once deadline is reached
+/// and `DEADLINE_EXCEEDED` status is returned to the client.
+pub fn unary<M, F, R, Err>(handler: F) -> Resource<Err>
+where
+    M: MethodDef + 'static,
+    F: Fn(Request<M::Input>) -> R + 'static,
+    R: Future<Output = Result<M::Output, Status>> + 'static,
+    Err: ErrorRenderer,
+{
+    let handler = Rc::new(handler);
+
+    web::resource(M::PATH).route(web::post().to(
+        move |req: HttpRequest, payload: web::types::Payload| {
+            let handler = handler.clone();
+            async move {
+                match call::<M, _, _>(&req, payload, &*handler).await {
+                    Ok(Some(msg)) => response(&msg),
+                    Ok(None) => Response::new(StatusCode::UNSUPPORTED_MEDIA_TYPE),
+                    Err(status) => error_response(&status),
+                }
+            }
+        },
+    ))
+}
+
+async fn call<M, F, R>(
+    req: &HttpRequest,
+    mut payload: web::types::Payload,
+    handler: &F,
+) -> Result<Option<M::Output>, Status>
+where
+    M: MethodDef,
+    F: Fn(Request<M::Input>) -> R,
+    R: Future<Output = Result<M::Output, Status>>,
+{
+    let is_grpc = req
+        .headers()
+        .get(&header::CONTENT_TYPE)
+        .and_then(|hdr| hdr.to_str().ok())
+        .map(|ct| ct.starts_with(grpc::CONTENT_TYPE))
+        .unwrap_or(false);
+    if !is_grpc {
+        return Ok(None);
+    }
+
+    // read request message
+    let mut buf = BytesMut::new();
+    let msg = loop {
+        if let Some(msg) = grpc::decode_frame(&mut buf, grpc::MAX_MESSAGE_SIZE)? {
+            break msg;
+        }
+        match payload.recv().await {
+            Some(Ok(chunk)) => buf.extend_from_slice(&chunk),
+            Some(Err(e)) => return Err(Status::internal(e.to_string())),
+            None => return Err(Status::internal("Incomplete grpc message")),
+        }
+    };
+    let msg = M::Input::decode(msg)?;
+
+    let tm = req
+        .headers()
+        .get(&header::HeaderName::from_static("grpc-timeout"))
+        .and_then(grpc::parse_timeout);
+    let fut = (*handler)(Request::new(msg, req.headers().clone(), tm));
+
+    if let Some(tm) = tm {
+        timeout(tm, fut)
+            .await
+            .map_err(|_| Status::deadline_exceeded("Deadline exceeded"))?
+            .map(Some)
+    } else {
+        fut.await.map(Some)
+    }
+}
+
+fn response<T: Message>(msg: &T) -> Response {
+    let mut body = BytesMut::new();
+    grpc::encode_frame(msg, &mut body);
+
+    let trailers = Trailers::new();
+    let mut hdrs = HeaderMap::new();
+    Status::ok().to_headers(&mut hdrs);
+    for (name, value) in hdrs.iter() {
+        trailers.insert(name.clone(), value.clone());
+    }
+
+    let mut res = Response::Ok()
+        .header(header::CONTENT_TYPE, grpc_content_type())
+        .body(Bytes::from(body));
+    res.set_trailers(trailers);
+    res
+}
+
+fn error_response(status: &Status) -> Response {
+    // trailers-only response
+    let mut res = Response::Ok()
+        .header(header::CONTENT_TYPE, grpc_content_type())
+        .finish();
+    status.to_headers(res.headers_mut());
+    res
+}
+
+fn grpc_content_type() -> HeaderValue {
+    HeaderValue::from_static(grpc::CONTENT_TYPE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Method;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::{time::sleep, time::Millis, web::App};
+
+    struct Echo;
+
+    impl MethodDef for Echo {
+        const PATH: &'static str = "/test.Echo/Echo";
+        type Input = Bytes;
+        type Output = Bytes;
+    }
+
+    fn frame(data: &'static [u8]) -> Bytes {
+        let mut buf = BytesMut::new();
+        grpc::encode_frame(&Bytes::from_static(data), &mut buf);
+        buf.freeze()
+    }
+
+    #[crate::rt_test]
+    async fn test_unary() {
+        let srv = init_service(App::new().service(unary::<Echo, _, _, _>(
+            |req: Request<Bytes>| async move {
+                if req.get_ref().is_empty() {
+                    Err(Status::invalid_argument("empty"))
+                } else {
+                    Ok(req.into_inner())
+                }
+            },
+        )))
+        .await;
+
+        let req = TestRequest::with_uri(Echo::PATH)
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, "application/grpc")
+            .set_payload(frame(b"hello"))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let trailers = resp.response().trailers().unwrap();
+        assert_eq!(
+            trailers.get(&Status::STATUS).unwrap(),
+            HeaderValue::from_static("0")
+        );
+        assert_eq!(read_body(resp).await, frame(b"hello"));
+
+        let req = TestRequest::with_uri(Echo::PATH)
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, "application/grpc+proto")
+            .set_payload(frame(b""))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let status = Status::from_headers(resp.headers()).unwrap();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), "empty");
+
+        let req = TestRequest::with_uri(Echo::PATH)
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, "application/json")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[crate::rt_test]
+    async fn test_unary_deadline() {
+        let srv = init_service(App::new().service(unary::<Echo, _, _, _>(
+            |req: Request<Bytes>| async move {
+                sleep(Millis(500)).await;
+                Ok(req.into_inner())
+            },
+        )))
+        .await;
+
+        let req = TestRequest::with_uri(Echo::PATH)
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, "application/grpc")
+            .header("grpc-timeout", "50m")
+            .set_payload(frame(b"hello"))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        let status = Status::from_headers(resp.headers()).unwrap();
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+    }
+}
@@ -40,6 +40,13 @@ pub trait Guard {
     /// Check if request matches predicate
     fn check(&self, request: &RequestHead) -> bool;
 
+    /// Http method matched by the guard, if guard is a method guard.
+    ///
+    /// Router uses it for `Allow` header generation.
+    fn method(&self) -> Option<&Method> {
+        None
+    }
+
     /// Debug format
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("Guard").finish()
@@ -221,6 +228,31 @@ impl fmt::Debug for AllGuard {
     }
 }
 
+/// Collect methods of method guards.
+///
+/// Methods are added only if all other guards match request,
+/// returns `false` otherwise.
+pub(super) fn allowed_methods(
+    guards: &[Box<dyn Guard>],
+    head: &RequestHead,
+    methods: &mut Vec<Method>,
+) -> bool {
+    let mut allowed = Vec::new();
+    for guard in guards {
+        if let Some(method) = guard.method() {
+            allowed.push(method);
+        } else if !guard.check(head) {
+            return false;
+        }
+    }
+    for method in allowed {
+        if !methods.contains(method) {
+            methods.push(method.clone());
+        }
+    }
+    true
+}
+
 /// Return guard that matches if supplied guard does not match.
 pub fn Not<F: Guard + 'static>(guard: F) -> NotGuard {
     NotGuard(Box::new(guard))
@@ -258,6 +290,10 @@ impl Guard for MethodGuard {
         request.method == self.0
     }
 
+    fn method(&self) -> Option<&Method> {
+        Some(&self.0)
+    }
+
     /// Debug format
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::Debug::fmt(self, f)
@@ -343,6 +379,9 @@ impl Guard for HeaderGuard {
 
 /// Return predicate that matches if request contains specified Host name.
 ///
+/// Host name could contain wildcard, `*.rust-lang.org` matches any
+/// subdomain of `rust-lang.org` and `*` matches any host.
+///
 /// ```rust
 /// use ntex::web::{self, guard::Host, App, HttpResponse};
 ///
@@ -368,6 +407,19 @@ fn get_host_uri(req: &RequestHead) -> Option<Uri> {
         .and_then(|host_success| host_success)
 }
 
+/// Check if host name matches host pattern, pattern could contain wildcard
+pub(super) fn host_matches(pattern: &str, host: &str) -> bool {
+    if pattern == "*" {
+        true
+    } else if let Some(suffix) = pattern.strip_prefix("*.") {
+        host.len() > suffix.len() + 1
+            && host.as_bytes()[host.len() - suffix.len() - 1] == b'.'
+            && host[host.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+    } else {
+        pattern.eq_ignore_ascii_case(host)
+    }
+}
+
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct HostGuard(String, Option<String>);
@@ -389,7 +441,7 @@ impl Guard for HostGuard {
         };
 
         if let Some(uri_host) = req_host_uri.host() {
-            if self.0 != uri_host {
+            if !host_matches(&self.0, uri_host) {
                 return false;
             }
         } else {
@@ -459,6 +511,25 @@ mod tests {
 
         let pred = Host("localhost");
         assert!(!pred.check(req.head()));
+
+        let pred = Host("*.rust-lang.org");
+        assert!(pred.check(req.head()));
+
+        let pred = Host("*");
+        assert!(pred.check(req.head()));
+
+        let pred = Host("*.www.rust-lang.org");
+        assert!(!pred.check(req.head()));
+    }
+
+    #[test]
+    fn test_host_matches() {
+        assert!(host_matches("*.example.com", "a.example.com"));
+        assert!(host_matches("*.example.com", "a.b.EXAMPLE.com"));
+        assert!(!host_matches("*.example.com", "example.com"));
+        assert!(!host_matches("*.example.com", "aexample.com"));
+        assert!(host_matches("Example.com", "example.COM"));
+        assert!(!host_matches("example.com", "a.example.com"));
     }
 
     #[test]
@@ -1,9 +1,10 @@
-use std::{fmt, future::Future, marker::PhantomData};
+use std::{fmt, future::Future, marker::PhantomData, time::Instant};
 
 use crate::util::BoxFuture;
 
 use super::error::ErrorRenderer;
 use super::extract::FromRequest;
+use super::middleware::Timings;
 use super::request::WebRequest;
 use super::responder::Responder;
 use super::response::WebResponse;
@@ -71,19 +72,42 @@ where
         req: WebRequest<Err>,
     ) -> BoxFuture<'_, Result<WebResponse, Err::Container>> {
         Box::pin(async move {
-            let (req, mut payload) = req.into_parts();
-            let param = match T::from_request(&req, &mut payload).await {
-                Ok(param) => param,
-                Err(e) => return Ok(WebResponse::from_err::<Err, _>(e, req)),
-            };
-
-            let result = self.hnd.call(param).await;
-            let response = result.respond_to(&req).await;
-            Ok(WebResponse::new(response, req))
+            let timings = req.extensions().get::<Timings>().cloned();
+            if let Some(timings) = timings {
+                let start = Instant::now();
+                let res = self.call_handler(req).await;
+                timings.record("handler", start.elapsed());
+                res
+            } else {
+                self.call_handler(req).await
+            }
         })
     }
 }
 
+impl<F, T, Err> HandlerWrapper<F, T, Err>
+where
+    F: Handler<T, Err> + 'static,
+    T: FromRequest<Err> + 'static,
+    T::Error: Into<Err::Container>,
+    Err: ErrorRenderer,
+{
+    async fn call_handler(
+        &self,
+        req: WebRequest<Err>,
+    ) -> Result<WebResponse, Err::Container> {
+        let (req, mut payload) = req.into_parts();
+        let param = match T::from_request(&req, &mut payload).await {
+            Ok(param) => param,
+            Err(e) => return Ok(WebResponse::from_err::<Err, _>(e, req)),
+        };
+
+        let result = self.hnd.call(param).await;
+        let response = result.respond_to(&req).await;
+        Ok(WebResponse::new(response, req))
+    }
+}
+
 /// FromRequest trait impl for tuples
 macro_rules! factory_tuple ({ $(($n:tt, $T:ident)),+} => {
     impl<Func, $($T,)+ Res, Err> Handler<($($T,)+), Err> for Func
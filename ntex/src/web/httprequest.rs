@@ -1,10 +1,12 @@
 use std::{cell::Ref, cell::RefCell, cell::RefMut, fmt, net, rc::Rc};
 
 use crate::http::{
-    HeaderMap, HttpMessage, Message, Method, Payload, RequestHead, Uri, Version,
+    Disconnected, HeaderMap, HttpMessage, Message, Method, Payload, RequestHead,
+    RequestTarget, Uri, Version,
 };
 use crate::io::{types, IoRef};
 use crate::router::Path;
+use crate::sync::CancellationToken;
 use crate::util::Extensions;
 
 use super::config::AppConfig;
@@ -104,6 +106,14 @@ impl HttpRequest {
         }
     }
 
+    /// Parsed view of the request target.
+    ///
+    /// Path segments and query pairs are parsed once per request.
+    #[inline]
+    pub fn target(&self) -> Rc<RequestTarget> {
+        self.head().target()
+    }
+
     /// Io reference for current connection
     #[inline]
     pub fn io(&self) -> Option<&IoRef> {
@@ -121,6 +131,22 @@ impl HttpRequest {
             .unwrap_or(None)
     }
 
+    /// Notify when client of this request get disconnected
+    ///
+    /// For http/2 requests it also resolves when request's stream get reset.
+    #[inline]
+    pub fn disconnected(&self) -> Disconnected {
+        Disconnected::new(self.head())
+    }
+
+    /// Get cancellation token of this request
+    ///
+    /// Token is cancelled when client of this request get disconnected,
+    /// long-running tasks spawned by the handler could use it to stop.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        crate::http::disconnect::cancellation_token(self.head())
+    }
+
     /// Get a reference to the Path parameters.
     ///
     /// Params is a container for url parameters.
@@ -193,6 +219,41 @@ impl HttpRequest {
         self.url_for(name, NO_PARAMS)
     }
 
+    /// Generate path for named resource
+    ///
+    /// This method is similar to `HttpRequest::url_for()` but it generates
+    /// path only, without scheme and host. Generated path is suitable for
+    /// `Location` headers and html templates. For external resources full
+    /// url is returned.
+    ///
+    /// ```rust
+    /// # use ntex::web::{self, App, HttpRequest, HttpResponse};
+    /// #
+    /// async fn index(req: HttpRequest) -> HttpResponse {
+    ///     let path = req.path_for("foo", &["1"]).unwrap(); // <- "/test/1"
+    ///     HttpResponse::Found().header("location", path).finish()
+    /// }
+    ///
+    /// fn main() {
+    ///     let app = App::new()
+    ///         .service(web::resource("/test/{one}")
+    ///              .name("foo")  // <- set resource name, then it could be used in `path_for`
+    ///              .route(web::get().to(index))
+    ///         );
+    /// }
+    /// ```
+    pub fn path_for<U, I>(
+        &self,
+        name: &str,
+        elements: U,
+    ) -> Result<String, super::error::UrlGenerationError>
+    where
+        U: IntoIterator<Item = I>,
+        I: AsRef<str>,
+    {
+        self.0.rmap.path_for(name, elements)
+    }
+
     #[inline]
     /// Get a reference to a `ResourceMap` of current application.
     pub fn resource_map(&self) -> &ResourceMap {
@@ -391,6 +452,25 @@ mod tests {
         assert_eq!(req.query_string(), "id=test");
     }
 
+    #[test]
+    fn test_request_target() {
+        let mut req = TestRequest::with_uri("/user/a%20b?id=1&name=x+y").to_http_request();
+        let target = req.target();
+        assert_eq!(target.segments().collect::<Vec<_>>(), vec!["user", "a%20b"]);
+        assert_eq!(target.decoded_segment(1).unwrap(), "a b");
+        assert_eq!(target.query_get("name").unwrap(), "x y");
+        // target is parsed once
+        assert!(Rc::ptr_eq(&target, &req.target()));
+        drop(target);
+
+        req.head_mut().uri = Uri::from_static("/index.html");
+        assert_eq!(
+            req.target().segments().collect::<Vec<_>>(),
+            vec!["index.html"]
+        );
+        assert_eq!(req.target().query_pairs().len(), 0);
+    }
+
     #[cfg(feature = "url")]
     #[test]
     fn test_url_for() {
@@ -462,6 +542,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_path_for() {
+        let mut res = ResourceDef::new("/user/{name}.{ext}");
+        *res.name_mut() = "index".to_string();
+        let mut ext = ResourceDef::new("//cdn.example.com/{file}");
+        *ext.name_mut() = "cdn".to_string();
+
+        let mut rmap = ResourceMap::new(ResourceDef::new(""));
+        rmap.add(&mut res, None);
+        rmap.add(&mut ext, None);
+
+        let req = TestRequest::default().rmap(rmap).to_http_request();
+        assert_eq!(
+            req.path_for("unknown", ["test"]),
+            Err(crate::web::error::UrlGenerationError::ResourceNotFound)
+        );
+        assert_eq!(
+            req.path_for("index", ["test"]),
+            Err(crate::web::error::UrlGenerationError::NotEnoughElements)
+        );
+        assert_eq!(
+            req.path_for("index", ["test", "html"]).unwrap(),
+            "/user/test.html"
+        );
+
+        #[cfg(feature = "url")]
+        {
+            let url = req.url_for("cdn", ["app.js"]).unwrap();
+            assert_eq!(url.as_str(), "http://cdn.example.com/app.js");
+        }
+    }
+
     #[crate::rt_test]
     async fn test_state() {
         let srv = init_service(App::new().state(10usize).service(web::resource("/").to(
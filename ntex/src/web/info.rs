@@ -1,4 +1,4 @@
-use std::cell::Ref;
+use std::{cell::Ref, net::IpAddr, net::SocketAddr};
 
 use crate::http::header::{self, HeaderName};
 use crate::http::RequestHead;
@@ -28,6 +28,10 @@ impl ConnectionInfo {
 
     #[allow(clippy::cognitive_complexity)]
     fn new(req: &RequestHead, cfg: &AppConfig) -> ConnectionInfo {
+        if let Some(proxies) = cfg.trusted_proxies() {
+            return ConnectionInfo::new_trusted(req, cfg, proxies, req.peer_addr());
+        }
+
         let mut host = None;
         let mut scheme = None;
         let mut remote = None;
@@ -131,6 +135,65 @@ impl ConnectionInfo {
         }
     }
 
+    /// Resolve connection info with trusted proxies configuration.
+    fn new_trusted(
+        req: &RequestHead,
+        cfg: &AppConfig,
+        proxies: &TrustedProxies,
+        peer: Option<SocketAddr>,
+    ) -> ConnectionInfo {
+        let mut scheme = req.uri.scheme().map(|a| a.as_str());
+        if scheme.is_none() && cfg.secure() {
+            scheme = Some("https")
+        }
+        let mut host = req
+            .headers
+            .get(&header::HOST)
+            .and_then(|h| h.to_str().ok())
+            .or_else(|| req.uri.authority().map(|a| a.as_str()));
+        let mut remote = None;
+
+        if peer
+            .map(|addr| proxies.is_trusted(addr.ip()))
+            .unwrap_or(false)
+        {
+            let hops = forwarded_hops(req);
+
+            for (idx, hop) in hops.iter().rev().enumerate() {
+                if idx >= proxies.hops {
+                    break;
+                }
+                // nearest proxy defines scheme and host
+                if idx == 0 {
+                    if let Some(proto) = hop.proto {
+                        scheme = Some(proto);
+                    }
+                    if let Some(h) = hop.host {
+                        host = Some(h);
+                    }
+                }
+                if let Some(addr) = hop.addr {
+                    remote = Some(addr);
+                    if !parse_ip(addr)
+                        .map(|ip| proxies.is_trusted(ip))
+                        .unwrap_or(false)
+                    {
+                        break;
+                    }
+                } else {
+                    break;
+                }
+            }
+        }
+
+        ConnectionInfo {
+            peer: peer.map(|addr| format!("{}", addr)),
+            scheme: scheme.unwrap_or("http").to_owned(),
+            host: host.unwrap_or_else(|| cfg.host()).to_owned(),
+            remote: remote.map(|s| s.to_owned()),
+        }
+    }
+
     /// Scheme of the request.
     ///
     /// Scheme is resolved through the following headers, in this order:
@@ -166,7 +229,8 @@ impl ConnectionInfo {
     ///
     /// # Security
     /// Do not use this function for security purposes, unless you can ensure the Forwarded and
-    /// X-Forwarded-For headers cannot be spoofed by the client. If you want the client's socket
+    /// X-Forwarded-For headers cannot be spoofed by the client, or trusted proxies are
+    /// configured with `HttpServer::trusted_proxies()`. If you want the client's socket
     /// address explicitly, use
     /// [`HttpRequest::peer_addr()`](../web/struct.HttpRequest.html#method.peer_addr) instead.
     #[inline]
@@ -181,6 +245,177 @@ impl ConnectionInfo {
     }
 }
 
+/// Trusted proxies configuration
+///
+/// By default `ConnectionInfo` trusts forwarding headers of any request.
+/// If trusted proxies are configured, `Forwarded` and `X-Forwarded-*`
+/// headers are used only if request is received from one of trusted
+/// networks. Client address is resolved by walking forwarded chain
+/// from the nearest proxy, until untrusted address is found or number
+/// of processed hops reaches configured limit.
+///
+/// ```rust
+/// use ntex::web::{self, dev::TrustedProxies, App, HttpServer};
+///
+/// fn main() {
+///     let srv = HttpServer::new(|| App::new())
+///         .trusted_proxies(TrustedProxies::new().trust("10.0.0.0/8").hops(2));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrustedProxies {
+    nets: Vec<(IpAddr, u8)>,
+    hops: usize,
+}
+
+impl Default for TrustedProxies {
+    fn default() -> Self {
+        TrustedProxies::new()
+    }
+}
+
+impl TrustedProxies {
+    /// Create empty configuration, no proxies are trusted.
+    pub fn new() -> Self {
+        TrustedProxies {
+            nets: Vec::new(),
+            hops: 1,
+        }
+    }
+
+    /// Trust network, in CIDR notation, or single ip address.
+    ///
+    /// Panics if network is malformed.
+    pub fn trust<T: AsRef<str>>(mut self, net: T) -> Self {
+        let net = net.as_ref();
+        let (addr, len) = if let Some((addr, len)) = net.split_once('/') {
+            (addr, Some(len))
+        } else {
+            (net, None)
+        };
+        let addr: IpAddr = addr
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("Malformed network: {:?}", net));
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let len = len
+            .map(|len| {
+                len.trim()
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|len| *len <= max)
+                    .unwrap_or_else(|| panic!("Malformed network: {:?}", net))
+            })
+            .unwrap_or(max);
+        self.nets.push((addr, len));
+        self
+    }
+
+    /// Set max number of proxy hops to process.
+    ///
+    /// By default only one hop is processed.
+    pub fn hops(mut self, hops: usize) -> Self {
+        self.hops = hops;
+        self
+    }
+
+    /// Check if address belongs to one of trusted networks
+    pub fn is_trusted(&self, addr: IpAddr) -> bool {
+        let addr = match addr {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(addr),
+            _ => addr,
+        };
+        self.nets.iter().any(|(net, len)| match (net, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - *len as u32).unwrap_or(0);
+                u32::from(*net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - *len as u32).unwrap_or(0);
+                u128::from(*net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        })
+    }
+}
+
+/// Single proxy hop information
+#[derive(Default)]
+struct Hop<'a> {
+    addr: Option<&'a str>,
+    proto: Option<&'a str>,
+    host: Option<&'a str>,
+}
+
+/// Collect forwarded hops, `Forwarded` header takes precedence over `X-Forwarded-*`
+fn forwarded_hops(req: &RequestHead) -> Vec<Hop<'_>> {
+    let mut hops = Vec::new();
+    for hdr in req.headers.get_all(&header::FORWARDED) {
+        if let Ok(val) = hdr.to_str() {
+            for el in val.split(',') {
+                let mut hop = Hop::default();
+                for pair in el.split(';') {
+                    let mut items = pair.trim().splitn(2, '=');
+                    if let (Some(name), Some(val)) = (items.next(), items.next()) {
+                        let val = val.trim().trim_matches('"');
+                        match &name.trim().to_lowercase() as &str {
+                            "for" => hop.addr = Some(val),
+                            "proto" => hop.proto = Some(val),
+                            "host" => hop.host = Some(val),
+                            _ => (),
+                        }
+                    }
+                }
+                hops.push(hop);
+            }
+        }
+    }
+    if !hops.is_empty() {
+        return hops;
+    }
+
+    let last = |name: &[u8]| {
+        req.headers
+            .get(&HeaderName::from_lowercase(name).unwrap())
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.rsplit(',').next())
+            .map(|v| v.trim())
+    };
+    if let Some(h) = req
+        .headers
+        .get(&HeaderName::from_lowercase(X_FORWARDED_FOR).unwrap())
+    {
+        if let Ok(h) = h.to_str() {
+            for addr in h.split(',') {
+                hops.push(Hop {
+                    addr: Some(addr.trim()),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+    if hops.is_empty() {
+        hops.push(Hop::default());
+    }
+    if let Some(hop) = hops.last_mut() {
+        hop.proto = last(X_FORWARDED_PROTO);
+        hop.host = last(X_FORWARDED_HOST);
+    }
+    hops
+}
+
+/// Parse forwarded node address, port is optional
+fn parse_ip(addr: &str) -> Option<IpAddr> {
+    if let Some(rest) = addr.strip_prefix('[') {
+        // ipv6 with optional port
+        rest.split(']').next().and_then(|a| a.parse().ok())
+    } else if let Ok(ip) = addr.parse() {
+        Some(ip)
+    } else {
+        addr.rsplit_once(':').and_then(|(a, _)| a.parse().ok())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,4 +468,87 @@ mod tests {
         let info = req.connection_info();
         assert_eq!(info.scheme(), "https");
     }
+
+    #[test]
+    fn test_trusted_proxies() {
+        let proxies = TrustedProxies::new()
+            .trust("10.0.0.0/8")
+            .trust("::1")
+            .hops(2);
+        assert!(proxies.is_trusted("10.1.2.3".parse().unwrap()));
+        assert!(proxies.is_trusted("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(proxies.is_trusted("::1".parse().unwrap()));
+        assert!(!proxies.is_trusted("11.0.0.1".parse().unwrap()));
+        assert!(!TrustedProxies::new().is_trusted("10.0.0.1".parse().unwrap()));
+        assert!(TrustedProxies::new()
+            .trust("0.0.0.0/0")
+            .is_trusted("1.2.3.4".parse().unwrap()));
+
+        let cfg = AppConfig::default();
+        let info = |req: &crate::web::HttpRequest, peer: &str| {
+            ConnectionInfo::new_trusted(
+                req.head(),
+                &cfg,
+                &proxies,
+                Some(peer.parse().unwrap()),
+            )
+        };
+
+        // untrusted peer, headers are ignored
+        let req = TestRequest::default()
+            .header(header::HOST, "rust-lang.org")
+            .header(X_FORWARDED_FOR, "192.0.2.60")
+            .header(X_FORWARDED_HOST, "spoofed.org")
+            .header(X_FORWARDED_PROTO, "https")
+            .to_http_request();
+        let info1 = info(&req, "192.0.2.1:1234");
+        assert_eq!(info1.scheme(), "http");
+        assert_eq!(info1.host(), "rust-lang.org");
+        assert_eq!(info1.remote(), Some("192.0.2.1:1234"));
+
+        // trusted peer
+        let req = TestRequest::default()
+            .header(X_FORWARDED_FOR, "1.1.1.1, 192.0.2.60, 10.0.0.2")
+            .header(X_FORWARDED_HOST, "rust-lang.org")
+            .header(X_FORWARDED_PROTO, "https")
+            .to_http_request();
+        let info1 = info(&req, "10.0.0.1:1234");
+        assert_eq!(info1.scheme(), "https");
+        assert_eq!(info1.host(), "rust-lang.org");
+        assert_eq!(info1.remote(), Some("192.0.2.60"));
+
+        // hops limit
+        let req = TestRequest::default()
+            .header(X_FORWARDED_FOR, "192.0.2.60, 10.0.0.3, 10.0.0.2")
+            .to_http_request();
+        let info1 = info(&req, "10.0.0.1:1234");
+        assert_eq!(info1.remote(), Some("10.0.0.3"));
+
+        // forwarded header
+        let req = TestRequest::default()
+            .header(
+                header::FORWARDED,
+                "for=192.0.2.43, for=\"[2001:db8:cafe::17]:4711\";proto=https;host=rust-lang.org",
+            )
+            .to_http_request();
+        let info1 = info(&req, "10.0.0.1:1234");
+        assert_eq!(info1.scheme(), "https");
+        assert_eq!(info1.host(), "rust-lang.org");
+        assert_eq!(info1.remote(), Some("[2001:db8:cafe::17]:4711"));
+
+        // proxies are configured via app config
+        let cfg = AppConfig::default().with_trusted_proxies(Some(TrustedProxies::new()));
+        assert!(cfg.trusted_proxies().is_some());
+        let req = TestRequest::default()
+            .header(X_FORWARDED_FOR, "192.0.2.60")
+            .to_http_request();
+        let info1 = ConnectionInfo::new(req.head(), &cfg);
+        assert_eq!(info1.remote(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_trusted_proxies_malformed() {
+        let _ = TrustedProxies::new().trust("10.0.0.0/33");
+    }
 }
@@ -0,0 +1,523 @@
+//! Route introspection
+//!
+//! Application resource map could be inspected at runtime, it contains
+//! information about registered resources, their patterns, methods,
+//! guards and handler metadata. Metadata could be attached to a route
+//! with `Route::operation()` method or with route macros attributes.
+//!
+//! ```rust
+//! use ntex::web::{self, introspect::OpenApi, App};
+//!
+//! /// Get user by id
+//! #[web::get("/users/{id}", summary = "Get user", tag = "users")]
+//! async fn user(id: web::types::Path<String>) -> String {
+//!     format!("user: {}", id)
+//! }
+//!
+//! fn main() {
+//!     let app = App::new()
+//!         .service(user)
+//!         .service(OpenApi::new("Users service", "1.0").resource("/openapi.json"));
+//! }
+//! ```
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use serde_json::{json, Map, Value};
+
+use crate::http::{header, Method, Response};
+
+use super::error::ErrorRenderer;
+use super::guard::Guard;
+use super::httprequest::HttpRequest;
+use super::resource::Resource;
+use super::rmap::ResourceMap;
+
+/// Route operation metadata
+#[derive(Clone, Debug, Default)]
+pub struct Operation {
+    operation_id: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    deprecated: bool,
+}
+
+impl Operation {
+    /// Create operation metadata with operation id
+    pub fn new(operation_id: &str) -> Self {
+        Operation {
+            operation_id: Some(operation_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Set operation summary
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_string());
+        self
+    }
+
+    /// Set operation description
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Add operation tag
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Mark operation as deprecated
+    pub fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    /// Operation id
+    pub fn get_operation_id(&self) -> Option<&str> {
+        self.operation_id.as_deref()
+    }
+
+    /// Operation summary
+    pub fn get_summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    /// Operation description
+    pub fn get_description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Operation tags
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Check if operation is deprecated
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+}
+
+/// Registered route information
+#[derive(Clone, Debug)]
+pub struct RouteInfo {
+    methods: Vec<Method>,
+    guards: Vec<String>,
+    operation: Option<Rc<Operation>>,
+}
+
+impl RouteInfo {
+    pub(super) fn new(
+        mut methods: Vec<Method>,
+        guards: &[Box<dyn Guard>],
+        operation: Option<Rc<Operation>>,
+    ) -> Self {
+        let guards = split_guards(guards, &mut methods);
+        RouteInfo {
+            methods,
+            guards,
+            operation,
+        }
+    }
+
+    /// Route methods, empty list means any method
+    pub fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+
+    /// Debug representation of route guards, except method guards
+    pub fn guards(&self) -> &[String] {
+        &self.guards
+    }
+
+    /// Route operation metadata
+    pub fn operation(&self) -> Option<&Operation> {
+        self.operation.as_deref()
+    }
+}
+
+/// Registered resource information
+#[derive(Clone, Debug)]
+pub struct ResourceInfo {
+    pattern: String,
+    name: Option<String>,
+    methods: Vec<Method>,
+    guards: Vec<String>,
+    routes: Vec<RouteInfo>,
+}
+
+impl ResourceInfo {
+    pub(super) fn new(
+        name: Option<String>,
+        guards: &[Box<dyn Guard>],
+        routes: Vec<RouteInfo>,
+    ) -> Self {
+        let mut methods = Vec::new();
+        let guards = split_guards(guards, &mut methods);
+        ResourceInfo {
+            name,
+            methods,
+            guards,
+            routes,
+            pattern: String::new(),
+        }
+    }
+
+    pub(super) fn set_pattern(&mut self, pattern: String) {
+        self.pattern = pattern;
+    }
+
+    /// Full resource pattern, including scopes prefixes
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Resource name
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Resource level methods, empty list means any method
+    pub fn methods(&self) -> &[Method] {
+        &self.methods
+    }
+
+    /// Debug representation of resource guards, except method guards
+    pub fn guards(&self) -> &[String] {
+        &self.guards
+    }
+
+    /// Resource routes
+    pub fn routes(&self) -> &[RouteInfo] {
+        &self.routes
+    }
+}
+
+/// Split guards to methods and debug representation of other guards
+fn split_guards(guards: &[Box<dyn Guard>], methods: &mut Vec<Method>) -> Vec<String> {
+    struct GuardFmt<'a>(&'a dyn Guard);
+
+    impl<'a> fmt::Debug for GuardFmt<'a> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+
+    let mut result = Vec::new();
+    for guard in guards {
+        if let Some(method) = guard.method() {
+            if !methods.contains(method) {
+                methods.push(method.clone());
+            }
+        } else {
+            result.push(format!("{:?}", GuardFmt(guard.as_ref())));
+        }
+    }
+    result
+}
+
+/// OpenAPI document generator
+///
+/// Document is generated from application resource map. Only routes
+/// with known methods are included.
+#[derive(Clone, Debug)]
+pub struct OpenApi {
+    title: String,
+    version: String,
+    description: Option<String>,
+}
+
+impl OpenApi {
+    /// Create OpenAPI document generator
+    pub fn new(title: &str, version: &str) -> Self {
+        OpenApi {
+            title: title.to_string(),
+            version: version.to_string(),
+            description: None,
+        }
+    }
+
+    /// Set api description
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Generate OpenAPI document for resource map
+    pub fn document(&self, rmap: &ResourceMap) -> Value {
+        let mut info = Map::new();
+        info.insert("title".into(), Value::from(self.title.as_str()));
+        info.insert("version".into(), Value::from(self.version.as_str()));
+        if let Some(ref desc) = self.description {
+            info.insert("description".into(), Value::from(desc.as_str()));
+        }
+
+        let mut paths = Map::new();
+        for res in rmap.resources() {
+            let (path, params) = openapi_path(res.pattern());
+            let mut item = Map::new();
+
+            for route in res.routes() {
+                let methods = if route.methods().is_empty() {
+                    res.methods()
+                } else {
+                    route.methods()
+                };
+                for method in methods {
+                    item.insert(
+                        method.as_str().to_lowercase(),
+                        operation(route.operation(), &params),
+                    );
+                }
+            }
+            if !item.is_empty() {
+                paths.insert(path, Value::Object(item));
+            }
+        }
+
+        json!({
+            "openapi": "3.0.3",
+            "info": info,
+            "paths": paths,
+        })
+    }
+
+    /// Create resource that serves OpenAPI document.
+    ///
+    /// Document is generated once, on first request.
+    pub fn resource<Err: ErrorRenderer>(self, path: &str) -> Resource<Err> {
+        let doc: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+
+        Resource::new(path).route(super::get().to(move |req: HttpRequest| {
+            let doc = doc.clone();
+            let api = self.clone();
+            async move {
+                let body = doc
+                    .borrow_mut()
+                    .get_or_insert_with(|| api.document(req.resource_map()).to_string())
+                    .clone();
+                Response::Ok()
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(body)
+            }
+        }))
+    }
+}
+
+/// Convert resource pattern to OpenAPI path and list of path parameters
+fn openapi_path(pattern: &str) -> (String, Vec<String>) {
+    let mut path = String::new();
+    let mut params = Vec::new();
+    let mut rest = pattern;
+
+    while let Some(start) = rest.find('{') {
+        path.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let name = rest[start + 1..end]
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .trim();
+        path.push('{');
+        path.push_str(name);
+        path.push('}');
+        params.push(name.to_string());
+
+        rest = &rest[end + 1..];
+        if let Some(r) = rest.strip_prefix('*') {
+            rest = r;
+        }
+    }
+    path.push_str(rest);
+    if path.is_empty() {
+        path.push('/');
+    }
+    (path, params)
+}
+
+fn operation(op: Option<&Operation>, params: &[String]) -> Value {
+    let mut item = Map::new();
+    if let Some(op) = op {
+        if let Some(id) = op.get_operation_id() {
+            item.insert("operationId".into(), Value::from(id));
+        }
+        if let Some(summary) = op.get_summary() {
+            item.insert("summary".into(), Value::from(summary));
+        }
+        if let Some(desc) = op.get_description() {
+            item.insert("description".into(), Value::from(desc));
+        }
+        if !op.get_tags().is_empty() {
+            item.insert("tags".into(), Value::from(op.get_tags().to_vec()));
+        }
+        if op.is_deprecated() {
+            item.insert("deprecated".into(), Value::from(true));
+        }
+    }
+    if !params.is_empty() {
+        let params: Vec<_> = params
+            .iter()
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": {"type": "string"},
+                })
+            })
+            .collect();
+        item.insert("parameters".into(), Value::from(params));
+    }
+    item.insert(
+        "responses".into(),
+        json!({"default": {"description": "Default response"}}),
+    );
+    Value::Object(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, guard, App, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_resources() {
+        let srv = init_service(
+            App::new()
+                .service(
+                    web::resource("/users/{id}")
+                        .name("user")
+                        .route(
+                            web::get()
+                                .operation(Operation::new("get_user").summary("Get user"))
+                                .to(|| async { HttpResponse::Ok() }),
+                        )
+                        .route(
+                            web::delete()
+                                .guard(guard::Header("x-admin", "1"))
+                                .to(|| async { HttpResponse::Ok() }),
+                        ),
+                )
+                .service(
+                    web::scope("/api").service(
+                        web::resource("/items/{tail}*")
+                            .guard(guard::Post())
+                            .to(|| async { HttpResponse::Ok() }),
+                    ),
+                )
+                .route(
+                    "/index.html",
+                    web::get()
+                        .operation(Operation::new("index").tag("pages").deprecated())
+                        .to(|req: HttpRequest| async move {
+                            let res = req.resource_map().resources();
+                            HttpResponse::Ok().body(format!("{}", res.len()))
+                        }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/index.html").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, "3");
+
+        let rmap = {
+            let req = TestRequest::with_uri("/users/1").to_request();
+            let resp = call_service(&srv, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            resp.request().resource_map().resources()
+        };
+        assert_eq!(rmap.len(), 3);
+
+        let user = &rmap[0];
+        assert_eq!(user.pattern(), "/users/{id}");
+        assert_eq!(user.name(), Some("user"));
+        assert!(user.methods().is_empty());
+        assert_eq!(user.routes().len(), 2);
+        assert_eq!(user.routes()[0].methods(), &[Method::GET]);
+        assert_eq!(
+            user.routes()[0].operation().unwrap().get_summary(),
+            Some("Get user")
+        );
+        assert_eq!(user.routes()[1].methods(), &[Method::DELETE]);
+        assert_eq!(user.routes()[1].guards().len(), 1);
+
+        let items = &rmap[1];
+        assert_eq!(items.pattern(), "/api/items/{tail}*");
+        assert_eq!(items.methods(), &[Method::POST]);
+        assert!(items.guards().is_empty());
+
+        let index = &rmap[2];
+        assert_eq!(index.pattern(), "/index.html");
+        assert_eq!(index.methods(), &[Method::GET]);
+        assert!(index.routes()[0].operation().unwrap().is_deprecated());
+    }
+
+    #[crate::rt_test]
+    async fn test_openapi() {
+        let srv = init_service(
+            App::new()
+                .service(
+                    web::resource("/users/{id:\\d+}").route(
+                        web::get()
+                            .operation(
+                                Operation::new("get_user")
+                                    .summary("Get user")
+                                    .description("Load user by id")
+                                    .tag("users"),
+                            )
+                            .to(|| async { HttpResponse::Ok() }),
+                    ),
+                )
+                .service(web::resource("/any").to(|| async { HttpResponse::Ok() }))
+                .service(
+                    OpenApi::new("Test", "1.0")
+                        .description("Test api")
+                        .resource("/openapi.json"),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/openapi.json").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let doc: Value = serde_json::from_slice(&read_body(resp).await).unwrap();
+
+        assert_eq!(doc["openapi"], "3.0.3");
+        assert_eq!(doc["info"]["title"], "Test");
+        assert_eq!(doc["info"]["description"], "Test api");
+        let op = &doc["paths"]["/users/{id}"]["get"];
+        assert_eq!(op["operationId"], "get_user");
+        assert_eq!(op["summary"], "Get user");
+        assert_eq!(op["description"], "Load user by id");
+        assert_eq!(op["tags"][0], "users");
+        assert_eq!(op["parameters"][0]["name"], "id");
+        assert_eq!(op["parameters"][0]["in"], "path");
+        assert!(doc["paths"]["/openapi.json"]["get"].is_object());
+        assert!(doc["paths"]["/any"].is_null());
+    }
+
+    #[test]
+    fn test_openapi_path() {
+        assert_eq!(openapi_path(""), ("/".to_string(), vec![]));
+        assert_eq!(
+            openapi_path("/a/{id}/b/{name:[a-z]+}"),
+            (
+                "/a/{id}/b/{name}".to_string(),
+                vec!["id".to_string(), "name".to_string()]
+            )
+        );
+        assert_eq!(
+            openapi_path("/files/{tail}*"),
+            ("/files/{tail}".to_string(), vec!["tail".to_string()])
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Authentication middleware
+use std::{fmt, marker::PhantomData, rc::Rc, task::Context, task::Poll};
+
+use crate::service::{Middleware, Service, ServiceCtx};
+use crate::util::Either;
+use crate::web::error::{AuthError, ErrorRenderer};
+use crate::web::types::{AuthConfig, BasicAuth, BearerAuth, Credentials};
+use crate::web::{WebRequest, WebResponse};
+
+/// `Middleware` for http authentication.
+///
+/// Middleware extracts credentials from the `Authorization` header and calls
+/// validator service with credentials and request. If credentials are
+/// accepted, validator returns request, otherwise validator returns response,
+/// `Credentials::reject()` creates *401 Unauthorized* response with
+/// `WWW-Authenticate` header. If credentials are missing or malformed,
+/// validator is not called and *401 Unauthorized* response is returned.
+///
+/// Authentication realm is taken from `AuthConfig` app state.
+///
+/// ```rust
+/// use ntex::{service::fn_service, util::Either};
+/// use ntex::web::types::{BearerAuth, Credentials};
+/// use ntex::web::{self, middleware::HttpAuthentication, App, WebRequest};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(HttpAuthentication::bearer(fn_service(
+///             |(auth, req): (BearerAuth, WebRequest<web::DefaultError>)| async move {
+///                 if auth.token() == "secret" {
+///                     Ok::<_, web::Error>(Either::Left(req))
+///                 } else {
+///                     Ok(Either::Right(BearerAuth::reject(req)))
+///                 }
+///             },
+///         )))
+///         .service(web::resource("/index.html").to(|| async { "Hello" }));
+/// }
+/// ```
+pub struct HttpAuthentication<C, V> {
+    validator: Rc<V>,
+    _t: PhantomData<C>,
+}
+
+impl<V> HttpAuthentication<BasicAuth, V> {
+    /// Construct middleware for `Basic` authorization scheme.
+    pub fn basic(validator: V) -> Self {
+        HttpAuthentication::new(validator)
+    }
+}
+
+impl<V> HttpAuthentication<BearerAuth, V> {
+    /// Construct middleware for `Bearer` authorization scheme.
+    pub fn bearer(validator: V) -> Self {
+        HttpAuthentication::new(validator)
+    }
+}
+
+impl<C, V> HttpAuthentication<C, V> {
+    /// Construct middleware for custom credentials type.
+    pub fn new(validator: V) -> Self {
+        HttpAuthentication {
+            validator: Rc::new(validator),
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<C, V> fmt::Debug for HttpAuthentication<C, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpAuthentication")
+            .field("credentials", &std::any::type_name::<C>())
+            .finish()
+    }
+}
+
+impl<S, C, V> Middleware<S> for HttpAuthentication<C, V> {
+    type Service = HttpAuthenticationMiddleware<S, C, V>;
+
+    fn create(&self, service: S) -> Self::Service {
+        HttpAuthenticationMiddleware {
+            service,
+            validator: self.validator.clone(),
+            _t: PhantomData,
+        }
+    }
+}
+
+pub struct HttpAuthenticationMiddleware<S, C, V> {
+    service: S,
+    validator: Rc<V>,
+    _t: PhantomData<C>,
+}
+
+impl<S, C, V> fmt::Debug for HttpAuthenticationMiddleware<S, C, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpAuthenticationMiddleware")
+            .field("credentials", &std::any::type_name::<C>())
+            .finish()
+    }
+}
+
+impl<S, C, V, Err> Service<WebRequest<Err>> for HttpAuthenticationMiddleware<S, C, V>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse, Error = Err::Container>,
+    V: Service<
+        (C, WebRequest<Err>),
+        Response = Either<WebRequest<Err>, WebResponse>,
+        Error = Err::Container,
+    >,
+    C: Credentials,
+    Err: ErrorRenderer,
+    Err::Container: From<AuthError>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let ready1 = self.service.poll_ready(cx)?.is_ready();
+        let ready2 = self.validator.poll_ready(cx)?.is_ready();
+        if ready1 && ready2 {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn poll_shutdown(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let ready1 = self.service.poll_shutdown(cx).is_ready();
+        let ready2 = self.validator.poll_shutdown(cx).is_ready();
+        if ready1 && ready2 {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let creds = match C::from_headers(req.headers()) {
+            Ok(creds) => creds,
+            Err(kind) => {
+                let err = C::auth_error(kind, req.app_state::<AuthConfig>());
+                return Ok(req.error_response(err));
+            }
+        };
+
+        match ctx.call(&*self.validator, (creds, req)).await? {
+            Either::Left(req) => ctx.call(&self.service, req).await,
+            Either::Right(res) => Ok(res),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{header, StatusCode};
+    use crate::service::fn_service;
+    use crate::web::test::{call_service, init_service, TestRequest};
+    use crate::web::{self, App, DefaultError, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_http_authentication() {
+        let srv = init_service(
+            App::new()
+                .state(AuthConfig::default().realm("test"))
+                .wrap(HttpAuthentication::basic(fn_service(
+                    |(auth, req): (BasicAuth, WebRequest<DefaultError>)| async move {
+                        if auth.user_id() == "user" && auth.password() == Some("pass") {
+                            Ok::<_, web::Error>(Either::Left(req))
+                        } else {
+                            Ok(Either::Right(BasicAuth::reject(req)))
+                        }
+                    },
+                )))
+                .service(web::resource("/").to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+
+        // user:pass
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Basic dXNlcjpwYXNz")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // user:wrong
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Basic dXNlcjp3cm9uZw==")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            resp.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+            "Basic realm=\"test\""
+        );
+
+        let req = TestRequest::default().to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Bearer token")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        assert!(format!(
+            "{:?}",
+            HttpAuthentication::bearer(fn_service(
+                |(_, req): (BearerAuth, WebRequest<DefaultError>)| async move {
+                    Ok::<_, web::Error>(Either::<_, WebResponse>::Left(req))
+                }
+            ))
+        )
+        .contains("BearerAuth"));
+    }
+}
@@ -0,0 +1,547 @@
+//! In-memory response cache
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::{fmt, fmt::Write, time::Duration, time::Instant};
+
+use crate::http::body::{Body, ResponseBody};
+use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use crate::http::{Method, Response, StatusCode};
+use crate::service::{Middleware, Service, ServiceCtx};
+use crate::time::Millis;
+use crate::util::Bytes;
+use crate::web::{WebRequest, WebResponse};
+
+/// `Middleware` for caching responses in memory.
+///
+/// Successful (*200 OK*) responses to `GET` and `HEAD` requests with
+/// buffered bodies are stored in the cache, subsequent requests with the same
+/// key are served from the cache without invoking inner service. Cache key
+/// is derived from request method, path, query string and values of
+/// configured `vary` headers. Responses with `Set-Cookie` header or with
+/// `no-store` or `private` cache control directives are not cached.
+/// Successful responses to unsafe methods invalidate cached entries
+/// of the request path.
+///
+/// If stale-while-revalidate period is configured, expired entry is
+/// revalidated by the first request, concurrent requests are served with
+/// stale response until revalidation completes.
+///
+/// Cache storage is shared between all clones of `ResponseCache`, so the
+/// same cache could be registered as app state and used for invalidation.
+///
+/// ```rust
+/// use ntex::web::{self, middleware::ResponseCache, App, HttpResponse};
+/// use ntex::{http::header, time::Seconds};
+///
+/// async fn update(cache: web::types::State<ResponseCache>) -> HttpResponse {
+///     cache.invalidate("/index.html");
+///     HttpResponse::Ok().finish()
+/// }
+///
+/// fn main() {
+///     let cache = ResponseCache::new()
+///         .ttl(Seconds(30))
+///         .max_entries(1024)
+///         .vary(header::ACCEPT_LANGUAGE);
+///
+///     let app = App::new()
+///         .state(cache.clone())
+///         .wrap(cache)
+///         .service(web::resource("/index.html").to(|| async { "Hello" }))
+///         .service(web::resource("/update").to(update));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ResponseCache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+struct Inner {
+    ttl: Duration,
+    stale: Duration,
+    max_entries: usize,
+    max_bytes: usize,
+    vary: Vec<HeaderName>,
+    entries: HashMap<String, Entry>,
+    bytes: usize,
+    counter: u64,
+}
+
+struct Entry {
+    path: String,
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    created: Instant,
+    accessed: u64,
+    revalidating: bool,
+}
+
+enum Lookup {
+    Hit(Response),
+    Revalidate,
+    Miss,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        ResponseCache {
+            inner: Arc::new(Mutex::new(Inner {
+                ttl: Duration::from_secs(60),
+                stale: Duration::ZERO,
+                max_entries: 1024,
+                max_bytes: 16 * 1024 * 1024,
+                vary: Vec::new(),
+                entries: HashMap::new(),
+                bytes: 0,
+                counter: 0,
+            })),
+        }
+    }
+}
+
+impl ResponseCache {
+    /// Construct `ResponseCache` middleware.
+    pub fn new() -> ResponseCache {
+        ResponseCache::default()
+    }
+
+    /// Set time to live for cached responses.
+    ///
+    /// By default ttl is 60 seconds.
+    pub fn ttl<T: Into<Millis>>(self, ttl: T) -> Self {
+        self.inner.lock().unwrap().ttl = ttl.into().into();
+        self
+    }
+
+    /// Set stale-while-revalidate period.
+    ///
+    /// Expired responses are served during this period, while response
+    /// is revalidated. By default stale responses are not served.
+    pub fn stale_while_revalidate<T: Into<Millis>>(self, period: T) -> Self {
+        self.inner.lock().unwrap().stale = period.into().into();
+        self
+    }
+
+    /// Set max number of cached responses.
+    ///
+    /// Least recently used responses are evicted. By default 1024 entries.
+    pub fn max_entries(self, max: usize) -> Self {
+        self.inner.lock().unwrap().max_entries = max;
+        self
+    }
+
+    /// Set max total size of cached response bodies.
+    ///
+    /// Least recently used responses are evicted. By default 16Mb.
+    pub fn max_bytes(self, max: usize) -> Self {
+        self.inner.lock().unwrap().max_bytes = max;
+        self
+    }
+
+    /// Add request header to the cache key.
+    pub fn vary(self, name: HeaderName) -> Self {
+        self.inner.lock().unwrap().vary.push(name);
+        self
+    }
+
+    /// Remove all cached responses for specified path.
+    pub fn invalidate(&self, path: &str) {
+        self.inner
+            .lock()
+            .unwrap()
+            .remove(|entry| entry.path == path);
+    }
+
+    /// Remove all cached responses for paths with specified prefix.
+    pub fn invalidate_prefix(&self, prefix: &str) {
+        self.inner
+            .lock()
+            .unwrap()
+            .remove(|entry| entry.path.starts_with(prefix));
+    }
+
+    /// Remove all cached responses.
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.clear();
+        inner.bytes = 0;
+    }
+
+    /// Number of cached responses.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    /// Returns true if cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn key<E>(&self, req: &WebRequest<E>) -> String {
+        let inner = self.inner.lock().unwrap();
+
+        let mut key = format!("{} {}", req.method(), req.uri());
+        for name in &inner.vary {
+            let _ = write!(key, "\n{}:", name);
+            for val in req.headers().get_all(name) {
+                key.push_str(&String::from_utf8_lossy(val.as_bytes()));
+                key.push(',');
+            }
+        }
+        key
+    }
+
+    fn lookup(&self, key: &str) -> Lookup {
+        let mut inner = self.inner.lock().unwrap();
+        let (ttl, stale) = (inner.ttl, inner.stale);
+        inner.counter += 1;
+        let counter = inner.counter;
+
+        let entry = if let Some(entry) = inner.entries.get_mut(key) {
+            entry
+        } else {
+            return Lookup::Miss;
+        };
+
+        let age = entry.created.elapsed();
+        if age < ttl || (age < ttl + stale && entry.revalidating) {
+            entry.accessed = counter;
+            Lookup::Hit(entry.response(age))
+        } else if age < ttl + stale {
+            entry.revalidating = true;
+            Lookup::Revalidate
+        } else {
+            inner.remove(|entry| entry.created.elapsed() >= ttl + stale);
+            Lookup::Miss
+        }
+    }
+
+    fn store(&self, key: String, path: &str, res: &WebResponse) {
+        let body = match res.response().body() {
+            ResponseBody::Body(Body::Bytes(b)) | ResponseBody::Other(Body::Bytes(b)) => {
+                b.clone()
+            }
+            ResponseBody::Body(Body::Empty) | ResponseBody::Other(Body::Empty) => {
+                Bytes::new()
+            }
+            _ => return self.release(&key),
+        };
+        if res.status() != StatusCode::OK
+            || res.headers().contains_key(header::SET_COOKIE)
+            || !cacheable(res.headers())
+        {
+            return self.release(&key);
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if body.len() > inner.max_bytes || inner.max_entries == 0 {
+            drop(inner);
+            return self.release(&key);
+        }
+
+        if let Some(prev) = inner.entries.remove(&key) {
+            inner.bytes -= prev.body.len();
+        }
+        while !inner.entries.is_empty()
+            && (inner.entries.len() >= inner.max_entries
+                || inner.bytes + body.len() > inner.max_bytes)
+        {
+            inner.evict();
+        }
+
+        inner.counter += 1;
+        inner.bytes += body.len();
+        let entry = Entry {
+            path: path.to_string(),
+            status: res.status(),
+            headers: res.headers().clone(),
+            body,
+            created: Instant::now(),
+            accessed: inner.counter,
+            revalidating: false,
+        };
+        inner.entries.insert(key, entry);
+    }
+
+    /// Revalidation failed, drop stale entry
+    fn release(&self, key: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner
+            .entries
+            .get(key)
+            .map(|e| e.revalidating)
+            .unwrap_or(false)
+        {
+            if let Some(entry) = inner.entries.remove(key) {
+                inner.bytes -= entry.body.len();
+            }
+        }
+    }
+}
+
+impl Inner {
+    fn remove<F: Fn(&Entry) -> bool>(&mut self, f: F) {
+        let mut removed = 0;
+        self.entries.retain(|_, entry| {
+            if f(entry) {
+                removed += entry.body.len();
+                false
+            } else {
+                true
+            }
+        });
+        self.bytes -= removed;
+    }
+
+    fn evict(&mut self) {
+        let key = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.accessed)
+            .map(|(key, _)| key.clone());
+        if let Some(entry) = key.and_then(|key| self.entries.remove(&key)) {
+            self.bytes -= entry.body.len();
+        }
+    }
+}
+
+impl Entry {
+    fn response(&self, age: Duration) -> Response {
+        let mut res = Response::with_body(self.status, Body::Bytes(self.body.clone()));
+        *res.headers_mut() = self.headers.clone();
+        res.headers_mut()
+            .insert(header::AGE, HeaderValue::from(age.as_secs()));
+        res
+    }
+}
+
+fn cacheable(headers: &HeaderMap) -> bool {
+    headers.get_all(header::CACHE_CONTROL).all(|val| {
+        val.to_str()
+            .map(|val| {
+                !val.split(',').any(|d| {
+                    let d = d.trim();
+                    d.eq_ignore_ascii_case("no-store") || d.eq_ignore_ascii_case("private")
+                })
+            })
+            .unwrap_or(false)
+    })
+}
+
+impl fmt::Debug for ResponseCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_struct("ResponseCache")
+            .field("ttl", &inner.ttl)
+            .field("stale_while_revalidate", &inner.stale)
+            .field("max_entries", &inner.max_entries)
+            .field("max_bytes", &inner.max_bytes)
+            .field("vary", &inner.vary)
+            .field("entries", &inner.entries.len())
+            .finish()
+    }
+}
+
+impl<S> Middleware<S> for ResponseCache {
+    type Service = ResponseCacheMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        ResponseCacheMiddleware {
+            service,
+            cache: self.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ResponseCacheMiddleware<S> {
+    service: S,
+    cache: ResponseCache,
+}
+
+impl<S, E> Service<WebRequest<E>> for ResponseCacheMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<E>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let path = req.path().to_string();
+
+        if req.method() != Method::GET && req.method() != Method::HEAD {
+            let unsafe_method = !matches!(*req.method(), Method::OPTIONS | Method::TRACE);
+            let res = ctx.call(&self.service, req).await?;
+            if unsafe_method && res.status().is_success() {
+                self.cache.invalidate(&path);
+            }
+            return Ok(res);
+        }
+
+        let key = self.cache.key(&req);
+        match self.cache.lookup(&key) {
+            Lookup::Hit(res) => Ok(req.into_response(res)),
+            Lookup::Revalidate | Lookup::Miss => match ctx.call(&self.service, req).await {
+                Ok(res) => {
+                    self.cache.store(key, &path, &res);
+                    Ok(res)
+                }
+                Err(e) => {
+                    self.cache.release(&key);
+                    Err(e)
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::time::{sleep, Millis};
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_response_cache() {
+        let counter = Rc::new(Cell::new(0));
+        let cache = ResponseCache::new().vary(header::ACCEPT_LANGUAGE);
+
+        let cnt = counter.clone();
+        let srv = init_service(
+            App::new()
+                .wrap(cache.clone())
+                .service(web::resource("/").to(move || {
+                    cnt.set(cnt.get() + 1);
+                    let n = cnt.get();
+                    async move { HttpResponse::Ok().body(format!("{}", n)) }
+                }))
+                .service(web::resource("/nostore").to(|| async {
+                    HttpResponse::Ok()
+                        .header(header::CACHE_CONTROL, "max-age=0, no-store")
+                        .body("nostore")
+                }))
+                .service(
+                    web::resource("/update")
+                        .route(web::post().to(|| async { HttpResponse::Ok().finish() })),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert!(resp.headers().get(header::AGE).is_none());
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"1"));
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.headers().get(header::AGE).unwrap(), "0");
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"1"));
+        assert_eq!(counter.get(), 1);
+
+        // vary header and query are part of the key
+        let req = TestRequest::with_uri("/")
+            .header(header::ACCEPT_LANGUAGE, "en")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"2"));
+        let req = TestRequest::with_uri("/?q=1").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"3"));
+        assert_eq!(cache.len(), 3);
+
+        let req = TestRequest::with_uri("/nostore").to_request();
+        let _ = call_service(&srv, req).await;
+        assert_eq!(cache.len(), 3);
+
+        // unsafe methods invalidate path
+        let req = TestRequest::with_uri("/update")
+            .method(Method::POST)
+            .to_request();
+        let _ = call_service(&srv, req).await;
+        assert_eq!(cache.len(), 3);
+
+        cache.invalidate("/");
+        assert!(cache.is_empty());
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"4"));
+        assert!(format!("{:?}", cache).contains("ResponseCache"));
+    }
+
+    #[crate::rt_test]
+    async fn test_response_cache_expire() {
+        let counter = Rc::new(Cell::new(0));
+        let cache = ResponseCache::new()
+            .ttl(Millis(50))
+            .stale_while_revalidate(Millis(500))
+            .max_entries(2);
+
+        let cnt = counter.clone();
+        let srv = init_service(App::new().wrap(cache.clone()).service(
+            web::resource("/{name}").to(move || {
+                cnt.set(cnt.get() + 1);
+                let n = cnt.get();
+                async move { HttpResponse::Ok().body(format!("{}", n)) }
+            }),
+        ))
+        .await;
+
+        let req = TestRequest::with_uri("/a").to_request();
+        assert_eq!(
+            read_body(call_service(&srv, req).await).await,
+            Bytes::from_static(b"1")
+        );
+
+        // first request after ttl revalidates
+        sleep(Millis(100)).await;
+        let req = TestRequest::with_uri("/a").to_request();
+        assert_eq!(
+            read_body(call_service(&srv, req).await).await,
+            Bytes::from_static(b"2")
+        );
+        let req = TestRequest::with_uri("/a").to_request();
+        assert_eq!(
+            read_body(call_service(&srv, req).await).await,
+            Bytes::from_static(b"2")
+        );
+
+        // concurrent requests get stale response during revalidation
+        sleep(Millis(100)).await;
+        assert!(matches!(cache.lookup("GET /a"), Lookup::Revalidate));
+        let req = TestRequest::with_uri("/a").to_request();
+        assert_eq!(
+            read_body(call_service(&srv, req).await).await,
+            Bytes::from_static(b"2")
+        );
+        assert_eq!(counter.get(), 2);
+
+        // lru eviction
+        cache.clear();
+        let req = TestRequest::with_uri("/a").to_request();
+        let _ = call_service(&srv, req).await;
+        let req = TestRequest::with_uri("/b").to_request();
+        let _ = call_service(&srv, req).await;
+        let req = TestRequest::with_uri("/a").to_request();
+        let _ = call_service(&srv, req).await;
+        let req = TestRequest::with_uri("/c").to_request();
+        let _ = call_service(&srv, req).await;
+        assert_eq!(cache.len(), 2);
+        assert!(matches!(cache.lookup("GET /a"), Lookup::Hit(_)));
+        assert!(matches!(cache.lookup("GET /b"), Lookup::Miss));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
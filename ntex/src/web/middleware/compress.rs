@@ -154,6 +154,10 @@ impl AcceptEncoding {
         encodings.sort();
 
         for enc in encodings.into_iter().flatten() {
+            // zstd encoder is not supported
+            if enc.encoding == ContentEncoding::Zstd {
+                continue;
+            }
             if encoding == ContentEncoding::Auto {
                 return enc.encoding;
             } else if encoding == enc.encoding {
@@ -163,3 +167,24 @@ impl AcceptEncoding {
         ContentEncoding::Identity
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_encoding() {
+        assert_eq!(
+            AcceptEncoding::parse("gzip, br;q=0.5", ContentEncoding::Auto),
+            ContentEncoding::Gzip
+        );
+        assert_eq!(
+            AcceptEncoding::parse("zstd;q=1.0, gzip;q=0.5", ContentEncoding::Auto),
+            ContentEncoding::Gzip
+        );
+        assert_eq!(
+            AcceptEncoding::parse("zstd", ContentEncoding::Auto),
+            ContentEncoding::Identity
+        );
+    }
+}
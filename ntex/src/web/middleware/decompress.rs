@@ -0,0 +1,205 @@
+//! `Middleware` for decompressing request body.
+use crate::http::encoding::Decoder;
+use crate::http::header::{ContentEncoding, CONTENT_ENCODING, CONTENT_LENGTH};
+use crate::http::Payload;
+use crate::service::{Middleware, Service, ServiceCtx};
+use crate::web::{WebRequest, WebResponse};
+
+#[derive(Debug, Clone)]
+/// `Middleware` for decompressing request body.
+///
+/// Request body with supported `Content-Encoding` (`gzip`, `deflate` and
+/// `br` if `brotli` feature is enabled) is decompressed before extractors
+/// run, `Content-Encoding` and `Content-Length` headers are removed from
+/// the request. Payloads with other encodings are passed as is.
+///
+/// Decompression stops with `PayloadError::Overflow` error before decompressed
+/// size exceeds size limit or expansion ratio of decompressed to compressed
+/// size exceeds max ratio.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(middleware::Decompress::default().max_size(1024 * 1024))
+///         .service(
+///             web::resource("/test")
+///                 .route(web::post().to(|body: String| async move { body }))
+///         );
+/// }
+/// ```
+pub struct Decompress {
+    max_size: usize,
+    max_ratio: usize,
+}
+
+impl Decompress {
+    /// Create new `Decompress` middleware.
+    pub fn new() -> Self {
+        Decompress::default()
+    }
+
+    /// Set max size of decompressed payload.
+    ///
+    /// By default max size is 8Mb.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    /// Set max expansion ratio of decompressed to compressed payload size.
+    ///
+    /// Ratio is not checked for payloads smaller than 64Kb.
+    /// By default max ratio is 100.
+    pub fn max_ratio(mut self, ratio: usize) -> Self {
+        self.max_ratio = ratio;
+        self
+    }
+}
+
+impl Default for Decompress {
+    fn default() -> Self {
+        Decompress {
+            max_size: 8 * 1024 * 1024,
+            max_ratio: 100,
+        }
+    }
+}
+
+impl<S> Middleware<S> for Decompress {
+    type Service = DecompressMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        DecompressMiddleware {
+            service,
+            max_size: self.max_size,
+            max_ratio: self.max_ratio,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DecompressMiddleware<S> {
+    service: S,
+    max_size: usize,
+    max_ratio: usize,
+}
+
+impl<S, E> Service<WebRequest<E>> for DecompressMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        mut req: WebRequest<E>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<WebResponse, S::Error> {
+        let encoding = req
+            .headers()
+            .get(&CONTENT_ENCODING)
+            .and_then(|val| val.to_str().ok())
+            .map(ContentEncoding::from)
+            .unwrap_or(ContentEncoding::Identity);
+
+        let supported = match encoding {
+            ContentEncoding::Gzip | ContentEncoding::Deflate => true,
+            #[cfg(feature = "brotli")]
+            ContentEncoding::Br => true,
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => true,
+            _ => false,
+        };
+
+        if supported {
+            let decoder = Decoder::new(req.take_payload(), encoding)
+                .max_size(self.max_size)
+                .max_ratio(self.max_ratio);
+            req.set_payload(Payload::from_stream(decoder));
+
+            let headers = req.headers_mut();
+            headers.remove(&CONTENT_ENCODING);
+            headers.remove(&CONTENT_LENGTH);
+        }
+
+        ctx.call(&self.service, req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use super::*;
+    use crate::http::{header, StatusCode};
+    use crate::util::Bytes;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App, HttpResponse};
+
+    fn gzip(data: &[u8]) -> Bytes {
+        let mut enc = GzEncoder::new(Vec::new(), Compression::best());
+        enc.write_all(data).unwrap();
+        Bytes::from(enc.finish().unwrap())
+    }
+
+    #[crate::rt_test]
+    async fn test_decompress() {
+        let srv = init_service(
+            App::new()
+                .wrap(Decompress::new().max_size(512 * 1024))
+                .service(web::resource("/").to(|req: web::HttpRequest, body: Bytes| {
+                    assert!(req.headers().get(header::CONTENT_ENCODING).is_none());
+                    async move { HttpResponse::Ok().body(body) }
+                })),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .set_payload(gzip(b"hello world"))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"hello world"));
+
+        // identity
+        let req = TestRequest::default()
+            .set_payload(Bytes::from_static(b"plain"))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"plain"));
+
+        // ratio limit
+        let req = TestRequest::default()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .set_payload(gzip(&[0; 256 * 1024]))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_ne!(resp.status(), StatusCode::OK);
+
+        // size limit
+        let srv = init_service(
+            App::new()
+                .wrap(Decompress::new().max_size(1024).max_ratio(100_000))
+                .service(
+                    web::resource("/")
+                        .to(|body: Bytes| async move { HttpResponse::Ok().body(body) }),
+                ),
+        )
+        .await;
+        let req = TestRequest::default()
+            .header(header::CONTENT_ENCODING, "gzip")
+            .set_payload(gzip(&[0; 4096]))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_ne!(resp.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,335 @@
+//! Middleware for enforcing response headers policy
+use std::rc::Rc;
+
+use crate::http::header::{self, Entry, HeaderMap, HeaderName, HeaderValue};
+use crate::http::StatusCode;
+use crate::service::{Middleware, Service, ServiceCtx};
+use crate::web::{WebRequest, WebResponse};
+
+/// `Middleware` for enforcing response headers policy.
+///
+/// Policy is applied to responses produced by handlers and inner middlewares:
+///
+/// - hop-by-hop headers (`Connection`, `Keep-Alive`, `Proxy-Connection`,
+///   `TE`, `Upgrade` and headers listed in `Connection` header) are removed,
+///   except for *101 Switching Protocols* responses
+/// - header values with control characters are removed
+/// - header values bigger than max size are removed
+/// - configured headers are removed
+/// - configured security headers are set, existing values are replaced
+///
+/// Middleware should be registered with last `wrap()` call, so it processes
+/// responses after all other middlewares.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(
+///             middleware::HeaderPolicy::new()
+///                 .hsts(31_536_000, true)
+///                 .content_security_policy("default-src 'self'")
+///                 .remove("x-powered-by")
+///                 .max_header_size(4096),
+///         )
+///         .service(web::resource("/test").to(|| async { HttpResponse::Ok() }));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct HeaderPolicy {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    hop_by_hop: bool,
+    max_size: Option<usize>,
+    remove: Vec<HeaderName>,
+    headers: HeaderMap,
+}
+
+impl Default for HeaderPolicy {
+    fn default() -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        );
+
+        HeaderPolicy {
+            inner: Rc::new(Inner {
+                headers,
+                hop_by_hop: true,
+                max_size: None,
+                remove: Vec::new(),
+            }),
+        }
+    }
+}
+
+impl HeaderPolicy {
+    /// Construct `HeaderPolicy` middleware.
+    ///
+    /// Default policy removes hop-by-hop headers and sets
+    /// `X-Content-Type-Options: nosniff` header.
+    pub fn new() -> Self {
+        HeaderPolicy::default()
+    }
+
+    /// Keep hop-by-hop headers.
+    pub fn keep_hop_by_hop(mut self) -> Self {
+        self.inner_mut().hop_by_hop = false;
+        self
+    }
+
+    /// Set `Strict-Transport-Security` header.
+    ///
+    /// `max_age` is in seconds.
+    pub fn hsts(self, max_age: u32, include_subdomains: bool) -> Self {
+        let value = if include_subdomains {
+            format!("max-age={}; includeSubDomains", max_age)
+        } else {
+            format!("max-age={}", max_age)
+        };
+        self.header(header::STRICT_TRANSPORT_SECURITY, value)
+    }
+
+    /// Set `Content-Security-Policy` header.
+    ///
+    /// Panics if policy is not a valid header value.
+    pub fn content_security_policy(self, policy: &str) -> Self {
+        self.header(header::CONTENT_SECURITY_POLICY, policy)
+    }
+
+    /// Do not set `X-Content-Type-Options` header.
+    pub fn disable_content_type_options(mut self) -> Self {
+        self.inner_mut()
+            .headers
+            .remove(header::X_CONTENT_TYPE_OPTIONS);
+        self
+    }
+
+    /// Set response header, existing value is replaced.
+    ///
+    /// Panics if name or value is not valid.
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        HeaderValue: TryFrom<V>,
+    {
+        let key = HeaderName::try_from(key)
+            .unwrap_or_else(|_| panic!("Cannot create header name"));
+        let value = HeaderValue::try_from(value)
+            .unwrap_or_else(|_| panic!("Cannot create header value"));
+        self.inner_mut().headers.insert(key, value);
+        self
+    }
+
+    /// Remove response header.
+    ///
+    /// Panics if name is not valid.
+    pub fn remove<K>(mut self, key: K) -> Self
+    where
+        HeaderName: TryFrom<K>,
+    {
+        let key = HeaderName::try_from(key)
+            .unwrap_or_else(|_| panic!("Cannot create header name"));
+        self.inner_mut().remove.push(key);
+        self
+    }
+
+    /// Set max size of response header value in bytes.
+    ///
+    /// Bigger values are removed from response. By default size is not limited.
+    pub fn max_header_size(mut self, size: usize) -> Self {
+        self.inner_mut().max_size = Some(size);
+        self
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Rc::get_mut(&mut self.inner).expect("Multiple copies exist")
+    }
+}
+
+impl Inner {
+    fn apply(&self, status: StatusCode, headers: &mut HeaderMap) {
+        if self.hop_by_hop && status != StatusCode::SWITCHING_PROTOCOLS {
+            let listed: Vec<_> = headers
+                .get_all(header::CONNECTION)
+                .filter_map(|val| val.to_str().ok())
+                .flat_map(|val| val.split(','))
+                .filter_map(|name| HeaderName::try_from(name.trim()).ok())
+                .filter(|name| {
+                    *name != header::CONTENT_LENGTH && *name != header::TRANSFER_ENCODING
+                })
+                .collect();
+            for name in listed {
+                headers.remove(name);
+            }
+            headers.remove(header::CONNECTION);
+            headers.remove("keep-alive");
+            headers.remove("proxy-connection");
+            headers.remove(header::TE);
+            headers.remove(header::UPGRADE);
+        }
+        for name in &self.remove {
+            headers.remove(name);
+        }
+
+        // sanitize values
+        let invalid: Vec<_> = headers
+            .iter()
+            .filter(|(_, val)| !self.is_valid(val))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in invalid {
+            log::warn!("Response header {:?} value is not allowed, removing", name);
+            if let Entry::Occupied(entry) = headers.entry(name) {
+                entry.retain(|val| self.is_valid(val));
+            }
+        }
+
+        for (name, value) in self.headers.iter() {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+
+    fn is_valid(&self, value: &HeaderValue) -> bool {
+        let bytes = value.as_bytes();
+        self.max_size.map(|max| bytes.len() <= max).unwrap_or(true)
+            && bytes
+                .iter()
+                .all(|b| *b == b'\t' || (*b >= 0x20 && *b != 0x7f))
+    }
+}
+
+impl<S> Middleware<S> for HeaderPolicy {
+    type Service = HeaderPolicyMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        HeaderPolicyMiddleware {
+            service,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HeaderPolicyMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, E> Service<WebRequest<E>> for HeaderPolicyMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<E>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let mut res = ctx.call(&self.service, req).await?;
+        let status = res.status();
+        self.inner.apply(status, res.headers_mut());
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::{IntoService, Pipeline};
+    use crate::util::Bytes;
+    use crate::web::test::TestRequest;
+    use crate::web::{DefaultError, Error, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_header_policy() {
+        let srv = |req: WebRequest<DefaultError>| async move {
+            Ok::<_, Error>(
+                req.into_response(
+                    HttpResponse::Ok()
+                        .header(header::CONNECTION, "keep-alive, x-internal")
+                        .header("keep-alive", "timeout=5")
+                        .header("x-internal", "1")
+                        .header("x-powered-by", "ntex")
+                        .header("x-long", "0123456789")
+                        .header("x-multi", "a")
+                        .header(header::CONTENT_SECURITY_POLICY, "none")
+                        .finish(),
+                ),
+            )
+        };
+        let mw = Pipeline::new(
+            HeaderPolicy::new()
+                .hsts(3600, true)
+                .content_security_policy("default-src 'self'")
+                .remove("x-powered-by")
+                .max_header_size(8)
+                .create(srv.into_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let mut resp = mw.call(req).await.unwrap();
+        let headers = resp.headers();
+        assert!(!headers.contains_key(header::CONNECTION));
+        assert!(!headers.contains_key("keep-alive"));
+        assert!(!headers.contains_key("x-internal"));
+        assert!(!headers.contains_key("x-powered-by"));
+        assert!(!headers.contains_key("x-long"));
+        assert_eq!(headers.get("x-multi").unwrap(), "a");
+        assert_eq!(
+            headers.get(header::STRICT_TRANSPORT_SECURITY).unwrap(),
+            "max-age=3600; includeSubDomains"
+        );
+        assert_eq!(
+            headers.get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'self'"
+        );
+        assert_eq!(
+            headers.get(header::X_CONTENT_TYPE_OPTIONS).unwrap(),
+            "nosniff"
+        );
+
+        resp.headers_mut()
+            .insert(HeaderName::from_static("x-ctl"), unsafe {
+                HeaderValue::from_shared_unchecked(Bytes::from_static(b"a\x01"))
+            });
+        HeaderPolicy::new()
+            .inner
+            .apply(StatusCode::OK, resp.headers_mut());
+        assert!(!resp.headers().contains_key("x-ctl"));
+    }
+
+    #[crate::rt_test]
+    async fn test_header_policy_upgrade() {
+        let srv = |req: WebRequest<DefaultError>| async move {
+            Ok::<_, Error>(
+                req.into_response(
+                    HttpResponse::build(StatusCode::SWITCHING_PROTOCOLS)
+                        .header(header::CONNECTION, "upgrade")
+                        .header(header::UPGRADE, "websocket")
+                        .finish(),
+                ),
+            )
+        };
+        let mw = Pipeline::new(
+            HeaderPolicy::new()
+                .disable_content_type_options()
+                .create(srv.into_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.headers().get(header::UPGRADE).unwrap(), "websocket");
+        assert!(!resp.headers().contains_key(header::X_CONTENT_TYPE_OPTIONS));
+    }
+}
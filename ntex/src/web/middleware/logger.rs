@@ -8,7 +8,7 @@ use crate::http::body::{Body, BodySize, MessageBody, ResponseBody};
 use crate::http::header::HeaderName;
 use crate::service::{Middleware, Service, ServiceCtx};
 use crate::util::{Bytes, HashSet};
-use crate::web::{HttpResponse, WebRequest, WebResponse};
+use crate::web::{error::ErrorCategory, HttpResponse, WebRequest, WebResponse};
 
 /// `Middleware` for logging request and response info to the terminal.
 ///
@@ -60,6 +60,9 @@ use crate::web::{HttpResponse, WebRequest, WebResponse};
 ///
 /// `%U`  Request URL
 ///
+/// `%E`  Error category, `handler`, `aborted` or `timeout`. `-` if response
+/// is not generated from the error
+///
 /// `%{FOO}i`  request.headers['FOO']
 ///
 /// `%{FOO}o`  response.headers['FOO']
@@ -233,7 +236,7 @@ impl Format {
     /// Returns `None` if the format string syntax is incorrect.
     fn new(s: &str) -> Format {
         log::trace!("Access log format: {}", s);
-        let fmt = Regex::new(r"%(\{([A-Za-z0-9\-_]+)\}([ioe])|[atPrUsbTD]?)").unwrap();
+        let fmt = Regex::new(r"%(\{([A-Za-z0-9\-_]+)\}([ioe])|[atPrUsbTDE]?)").unwrap();
 
         let mut idx = 0;
         let mut results = Vec::new();
@@ -268,6 +271,7 @@ impl Format {
                     "U" => FormatText::UrlPath,
                     "T" => FormatText::Time,
                     "D" => FormatText::TimeMillis,
+                    "E" => FormatText::ErrorCategory,
                     _ => FormatText::Str(m.as_str().to_owned()),
                 });
             }
@@ -295,6 +299,7 @@ enum FormatText {
     TimeMillis,
     RemoteAddr,
     UrlPath,
+    ErrorCategory,
     RequestHeader(HeaderName),
     ResponseHeader(HeaderName),
     EnvironHeader(String),
@@ -337,6 +342,13 @@ impl FormatText {
             FormatText::ResponseStatus => {
                 *self = FormatText::Str(format!("{}", res.status().as_u16()))
             }
+            FormatText::ErrorCategory => {
+                *self = if let Some(cat) = res.extensions().get::<ErrorCategory>() {
+                    FormatText::Str(cat.as_str().to_string())
+                } else {
+                    FormatText::Str("-".to_string())
+                };
+            }
             FormatText::ResponseHeader(ref name) => {
                 let s = if let Some(val) = res.headers().get(name) {
                     if let Ok(s) = val.to_str() {
@@ -575,4 +587,36 @@ mod tests {
         let s = format!("{}", FormatDisplay(&render));
         assert!(s.contains(&httpdate::HttpDate::from(now).to_string()));
     }
+
+    #[crate::rt_test]
+    async fn test_error_category_format() {
+        let mut format = Format::new("%s %E");
+        let now = time::SystemTime::now();
+
+        let resp = HttpResponse::build(StatusCode::OK).finish();
+        let mut fmt = format.clone();
+        for unit in &mut fmt.0 {
+            unit.render_response(&resp);
+        }
+        let render = |f: &mut fmt::Formatter<'_>| {
+            for unit in &fmt.0 {
+                unit.render(f, 0, now)?;
+            }
+            Ok(())
+        };
+        assert_eq!(format!("{}", FormatDisplay(&render)), "200 -");
+
+        let resp = HttpResponse::build(StatusCode::BAD_REQUEST).finish();
+        resp.extensions_mut().insert(ErrorCategory::Aborted);
+        for unit in &mut format.0 {
+            unit.render_response(&resp);
+        }
+        let render = |f: &mut fmt::Formatter<'_>| {
+            for unit in &format.0 {
+                unit.render(f, 0, now)?;
+            }
+            Ok(())
+        };
+        assert_eq!(format!("{}", FormatDisplay(&render)), "400 aborted");
+    }
 }
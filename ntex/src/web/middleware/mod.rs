@@ -5,8 +5,28 @@ mod compress;
 #[cfg(feature = "compress")]
 pub use self::compress::Compress;
 
+#[cfg(feature = "compress")]
+mod decompress;
+#[cfg(feature = "compress")]
+pub use self::decompress::Decompress;
+
+mod auth;
+pub use self::auth::HttpAuthentication;
+
+mod cache;
+pub use self::cache::ResponseCache;
+
 mod logger;
 pub use self::logger::Logger;
 
 mod defaultheaders;
 pub use self::defaultheaders::DefaultHeaders;
+
+mod headerpolicy;
+pub use self::headerpolicy::HeaderPolicy;
+
+mod normalize;
+pub use self::normalize::{NormalizePath, TrailingSlash};
+
+mod timing;
+pub use self::timing::{ServerTiming, Timed, Timings};
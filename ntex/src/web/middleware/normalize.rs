@@ -0,0 +1,338 @@
+//! Middleware for normalizing request path
+use crate::http::header::{HeaderValue, LOCATION};
+use crate::http::uri::{PathAndQuery, Uri};
+use crate::http::Response;
+use crate::service::{Middleware, Service, ServiceCtx};
+use crate::web::{WebRequest, WebResponse};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Trailing slash policy
+pub enum TrailingSlash {
+    /// Keep trailing slash as is
+    Keep,
+    /// Remove trailing slash, root path is not changed
+    Trim,
+    /// Always add trailing slash
+    Always,
+}
+
+/// `Middleware` for normalizing request path before routing.
+///
+/// Middleware performs following steps:
+///
+/// - percent-encoded unreserved characters are decoded, other
+///   percent-encoded characters use upper case hex digits
+/// - requests with percent-encoded `/`, `\` or `NUL` characters are
+///   rejected with *400 Bad Request* response
+/// - duplicate slashes are merged
+/// - `.` and `..` segments are resolved
+/// - trailing slash policy is applied
+///
+/// If path is changed, request is rewritten or redirected to normalized
+/// path. Middleware must be registered on application level, so proxy and
+/// application see the same path.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(
+///             middleware::NormalizePath::new()
+///                 .trailing_slash(middleware::TrailingSlash::Trim)
+///                 .redirect(),
+///         )
+///         .service(web::resource("/test").to(|| async { HttpResponse::Ok() }));
+/// }
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct NormalizePath {
+    trailing_slash: TrailingSlash,
+    redirect: bool,
+    reject_encoded_slash: bool,
+    reject_nul: bool,
+}
+
+impl Default for NormalizePath {
+    fn default() -> Self {
+        NormalizePath {
+            trailing_slash: TrailingSlash::Keep,
+            redirect: false,
+            reject_encoded_slash: true,
+            reject_nul: true,
+        }
+    }
+}
+
+impl NormalizePath {
+    /// Construct `NormalizePath` middleware.
+    pub fn new() -> Self {
+        NormalizePath::default()
+    }
+
+    /// Set trailing slash policy.
+    ///
+    /// By default trailing slash is kept as is.
+    pub fn trailing_slash(mut self, policy: TrailingSlash) -> Self {
+        self.trailing_slash = policy;
+        self
+    }
+
+    /// Redirect requests to normalized path instead of rewriting request path.
+    ///
+    /// Redirect uses *308 Permanent Redirect* response, so request method
+    /// and body are preserved.
+    pub fn redirect(mut self) -> Self {
+        self.redirect = true;
+        self
+    }
+
+    /// Reject requests with percent-encoded `/` or `\` characters.
+    ///
+    /// By default such requests are rejected.
+    pub fn reject_encoded_slash(mut self, reject: bool) -> Self {
+        self.reject_encoded_slash = reject;
+        self
+    }
+
+    /// Reject requests with percent-encoded `NUL` character.
+    ///
+    /// By default such requests are rejected.
+    pub fn reject_nul(mut self, reject: bool) -> Self {
+        self.reject_nul = reject;
+        self
+    }
+
+    /// Normalize path, returns `None` if path is already normalized
+    fn normalize(&self, path: &str) -> Result<Option<String>, ()> {
+        if !path.starts_with('/') {
+            return Ok(None);
+        }
+        let decoded = self.decode(path)?;
+
+        let mut segments: Vec<&str> = Vec::new();
+        let mut slash = false;
+        for seg in decoded.split('/').skip(1) {
+            slash = matches!(seg, "" | "." | "..");
+            match seg {
+                "" | "." => (),
+                ".." => {
+                    segments.pop();
+                }
+                seg => segments.push(seg),
+            }
+        }
+        let slash = match self.trailing_slash {
+            TrailingSlash::Keep => slash,
+            TrailingSlash::Trim => false,
+            TrailingSlash::Always => true,
+        };
+
+        let mut normalized = String::with_capacity(decoded.len() + 1);
+        for seg in &segments {
+            normalized.push('/');
+            normalized.push_str(seg);
+        }
+        if slash || segments.is_empty() {
+            normalized.push('/');
+        }
+
+        if normalized == path {
+            Ok(None)
+        } else {
+            Ok(Some(normalized))
+        }
+    }
+
+    /// Decode unreserved characters and check for rejected characters
+    fn decode(&self, path: &str) -> Result<String, ()> {
+        let bytes = path.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut idx = 0;
+        while idx < bytes.len() {
+            if bytes[idx] == b'%' && idx + 2 < bytes.len() {
+                if let (Some(d1), Some(d2)) =
+                    (from_hex(bytes[idx + 1]), from_hex(bytes[idx + 2]))
+                {
+                    let ch = (d1 << 4) | d2;
+                    if (ch == 0 && self.reject_nul)
+                        || ((ch == b'/' || ch == b'\\') && self.reject_encoded_slash)
+                    {
+                        return Err(());
+                    }
+                    if ch.is_ascii_alphanumeric() || matches!(ch, b'-' | b'.' | b'_' | b'~')
+                    {
+                        decoded.push(ch);
+                    } else {
+                        decoded.push(b'%');
+                        decoded.push(bytes[idx + 1].to_ascii_uppercase());
+                        decoded.push(bytes[idx + 2].to_ascii_uppercase());
+                    }
+                    idx += 3;
+                    continue;
+                }
+            }
+            decoded.push(bytes[idx]);
+            idx += 1;
+        }
+        String::from_utf8(decoded).map_err(|_| ())
+    }
+}
+
+fn from_hex(v: u8) -> Option<u8> {
+    (v as char).to_digit(16).map(|v| v as u8)
+}
+
+impl<S> Middleware<S> for NormalizePath {
+    type Service = NormalizePathMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        NormalizePathMiddleware {
+            service,
+            inner: *self,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct NormalizePathMiddleware<S> {
+    service: S,
+    inner: NormalizePath,
+}
+
+impl<S, E> Service<WebRequest<E>> for NormalizePathMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        mut req: WebRequest<E>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let path = match self.inner.normalize(req.path()) {
+            Ok(Some(path)) => path,
+            Ok(None) => return ctx.call(&self.service, req).await,
+            Err(_) => return Ok(req.into_response(Response::BadRequest().finish())),
+        };
+        let path = if let Some(query) = req.uri().query() {
+            format!("{}?{}", path, query)
+        } else {
+            path
+        };
+
+        if self.inner.redirect {
+            return Ok(match HeaderValue::try_from(path) {
+                Ok(location) => req.into_response(
+                    Response::PermanentRedirect()
+                        .header(LOCATION, location)
+                        .finish(),
+                ),
+                Err(_) => req.into_response(Response::BadRequest().finish()),
+            });
+        }
+
+        let mut parts = req.uri().clone().into_parts();
+        parts.path_and_query = match PathAndQuery::try_from(path) {
+            Ok(pq) => Some(pq),
+            Err(_) => return Ok(req.into_response(Response::BadRequest().finish())),
+        };
+        match Uri::from_parts(parts) {
+            Ok(uri) => {
+                *req.match_info_mut().get_mut() = uri.clone();
+                req.head_mut().uri = uri;
+                ctx.call(&self.service, req).await
+            }
+            Err(_) => Ok(req.into_response(Response::BadRequest().finish())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::util::Bytes;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App, HttpRequest};
+
+    #[test]
+    fn test_normalize() {
+        let norm = NormalizePath::new();
+        assert_eq!(norm.normalize("/"), Ok(None));
+        assert_eq!(norm.normalize("/a/b/"), Ok(None));
+        assert_eq!(norm.normalize("*"), Ok(None));
+        assert_eq!(norm.normalize("//a///b"), Ok(Some("/a/b".to_string())));
+        assert_eq!(norm.normalize("/a/./b/../c"), Ok(Some("/a/c".to_string())));
+        assert_eq!(norm.normalize("/../../a"), Ok(Some("/a".to_string())));
+        assert_eq!(norm.normalize("/a/b/.."), Ok(Some("/a/".to_string())));
+        assert_eq!(norm.normalize("/a/%2e%2E/b"), Ok(Some("/b".to_string())));
+        assert_eq!(norm.normalize("/%41%7e%3f"), Ok(Some("/A~%3F".to_string())));
+        assert_eq!(norm.normalize("/a%2fb"), Err(()));
+        assert_eq!(norm.normalize("/a%5Cb"), Err(()));
+        assert_eq!(norm.normalize("/a%00"), Err(()));
+        assert_eq!(norm.normalize("/a%2"), Ok(None));
+
+        let norm = NormalizePath::new()
+            .reject_encoded_slash(false)
+            .reject_nul(false);
+        assert_eq!(norm.normalize("/a%2fb"), Ok(Some("/a%2Fb".to_string())));
+        assert_eq!(norm.normalize("/a%00"), Ok(None));
+
+        let norm = NormalizePath::new().trailing_slash(TrailingSlash::Trim);
+        assert_eq!(norm.normalize("/"), Ok(None));
+        assert_eq!(norm.normalize("/a/b/"), Ok(Some("/a/b".to_string())));
+
+        let norm = NormalizePath::new().trailing_slash(TrailingSlash::Always);
+        assert_eq!(norm.normalize("/a/b"), Ok(Some("/a/b/".to_string())));
+        assert_eq!(norm.normalize("/a/b/"), Ok(None));
+    }
+
+    #[crate::rt_test]
+    async fn test_normalize_path() {
+        let srv = init_service(
+            App::new()
+                .wrap(NormalizePath::new().trailing_slash(TrailingSlash::Trim))
+                .service(
+                    web::resource("/v1/{name}").to(|req: HttpRequest| async move {
+                        format!("{}:{}", req.match_info()["name"], req.query_string())
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("//v1/./test/?q=1").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"test:q=1"));
+
+        let req = TestRequest::with_uri("/v1/a%2Fb").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let srv = init_service(
+            App::new()
+                .wrap(
+                    NormalizePath::new()
+                        .trailing_slash(TrailingSlash::Trim)
+                        .redirect(),
+                )
+                .service(web::resource("/v1/test").to(|| async { "test" })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/v1//test/?q=1").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(resp.headers().get(LOCATION).unwrap(), "/v1/test?q=1");
+
+        let req = TestRequest::with_uri("/v1/test").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,359 @@
+//! Per-request timings instrumentation
+use std::{cell::RefCell, fmt, rc::Rc, time::Duration, time::Instant};
+
+use crate::http::header::{HeaderName, HeaderValue};
+use crate::service::{Middleware, Service, ServiceCtx};
+use crate::web::{WebRequest, WebResponse};
+
+const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+/// `Middleware` for collecting per-request timings.
+///
+/// `ServerTiming` middleware stores `Timings` in request extensions.
+/// Middlewares wrapped with `Timed` record time spent in the middleware
+/// itself, excluding time spent in inner services. Handler execution time,
+/// including extraction of handler's parameters, is recorded as `handler`.
+/// Timings could be read or extended via `Timings` request extension.
+///
+/// By default middleware emits `Server-Timing` response header, with all
+/// recorded timings and total request processing time.
+///
+/// `ServerTiming` middleware must be registered as the most outer middleware,
+/// otherwise timings of middlewares registered outside of it are not recorded.
+///
+/// ```rust
+/// use ntex::web::{self, middleware, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .wrap(middleware::Timed::new("logger", middleware::Logger::default()))
+///         .wrap(middleware::ServerTiming::new())
+///         .service(web::resource("/").to(|| async { HttpResponse::Ok() }));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ServerTiming {
+    header: bool,
+}
+
+impl Default for ServerTiming {
+    fn default() -> Self {
+        ServerTiming { header: true }
+    }
+}
+
+impl ServerTiming {
+    /// Construct `ServerTiming` middleware.
+    pub fn new() -> ServerTiming {
+        ServerTiming::default()
+    }
+
+    /// Emit `Server-Timing` response header.
+    ///
+    /// By default header is enabled.
+    pub fn header(mut self, enabled: bool) -> Self {
+        self.header = enabled;
+        self
+    }
+}
+
+impl<S> Middleware<S> for ServerTiming {
+    type Service = ServerTimingMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        ServerTimingMiddleware {
+            service,
+            header: self.header,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ServerTimingMiddleware<S> {
+    service: S,
+    header: bool,
+}
+
+impl<S, E> Service<WebRequest<E>> for ServerTimingMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<E>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let start = Instant::now();
+        let timings = Timings::default();
+        req.extensions_mut().insert(timings.clone());
+
+        let mut res = ctx.call(&self.service, req).await?;
+
+        if self.header {
+            timings.record("total", start.elapsed());
+            if let Ok(val) = HeaderValue::try_from(timings.to_string()) {
+                res.headers_mut().append(SERVER_TIMING, val);
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// Records time spent in wrapped middleware.
+///
+/// Recorded time excludes time spent in inner services. If request
+/// does not contain `Timings` extension, timings are not recorded.
+pub struct Timed<M> {
+    name: Rc<str>,
+    middleware: M,
+}
+
+impl<M> Timed<M> {
+    /// Wrap middleware, record timings under specified name.
+    pub fn new(name: &str, middleware: M) -> Self {
+        Timed {
+            name: name.into(),
+            middleware,
+        }
+    }
+}
+
+impl<M> fmt::Debug for Timed<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timed").field("name", &self.name).finish()
+    }
+}
+
+impl<S, M> Middleware<S> for Timed<M>
+where
+    M: Middleware<TimedInner<S>>,
+{
+    type Service = TimedMiddleware<M::Service>;
+
+    fn create(&self, service: S) -> Self::Service {
+        TimedMiddleware {
+            name: self.name.clone(),
+            service: self.middleware.create(TimedInner { service }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TimedMiddleware<S> {
+    name: Rc<str>,
+    service: S,
+}
+
+impl<S, E> Service<WebRequest<E>> for TimedMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<E>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let timings = req.extensions().get::<Timings>().cloned();
+        let timings = if let Some(timings) = timings {
+            timings
+        } else {
+            return ctx.call(&self.service, req).await;
+        };
+
+        let start = Instant::now();
+        let depth = timings.0.borrow().inner.len();
+        let res = ctx.call(&self.service, req).await;
+        let elapsed = start.elapsed();
+
+        let mut inner = timings.0.borrow_mut();
+        let inner_elapsed = if inner.inner.len() > depth {
+            inner.inner.pop().unwrap_or_default()
+        } else {
+            Duration::ZERO
+        };
+        inner.push(&self.name, elapsed.saturating_sub(inner_elapsed));
+        res
+    }
+}
+
+#[derive(Debug)]
+pub struct TimedInner<S> {
+    service: S,
+}
+
+impl<S, E> Service<WebRequest<E>> for TimedInner<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    crate::forward_poll_ready!(service);
+    crate::forward_poll_shutdown!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<E>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let timings = req.extensions().get::<Timings>().cloned();
+        let start = Instant::now();
+        let res = ctx.call(&self.service, req).await;
+        if let Some(timings) = timings {
+            timings.0.borrow_mut().inner.push(start.elapsed());
+        }
+        res
+    }
+}
+
+/// Per-request timings.
+///
+/// Timings are stored in request extensions by `ServerTiming` middleware.
+#[derive(Clone, Default)]
+pub struct Timings(Rc<RefCell<TimingsInner>>);
+
+#[derive(Default)]
+struct TimingsInner {
+    entries: Vec<(Rc<str>, Duration)>,
+    inner: Vec<Duration>,
+}
+
+impl TimingsInner {
+    fn push(&mut self, name: &Rc<str>, dur: Duration) {
+        if let Some(item) = self.entries.iter_mut().find(|(n, _)| n == name) {
+            item.1 += dur;
+        } else {
+            self.entries.push((name.clone(), dur));
+        }
+    }
+}
+
+impl Timings {
+    /// Record timing.
+    ///
+    /// Durations of timings with the same name are accumulated.
+    pub fn record(&self, name: &str, dur: Duration) {
+        self.0.borrow_mut().push(&name.into(), dur)
+    }
+
+    /// Get recorded timing by name.
+    pub fn get(&self, name: &str) -> Option<Duration> {
+        self.0
+            .borrow()
+            .entries
+            .iter()
+            .find(|(n, _)| n.as_ref() == name)
+            .map(|(_, dur)| *dur)
+    }
+
+    /// Get all recorded timings, in order of completion.
+    pub fn entries(&self) -> Vec<(String, Duration)> {
+        self.0
+            .borrow()
+            .entries
+            .iter()
+            .map(|(n, dur)| (n.to_string(), *dur))
+            .collect()
+    }
+}
+
+impl fmt::Display for Timings {
+    /// Format timings as `Server-Timing` header value
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, (name, dur)) in self.0.borrow().entries.iter().enumerate() {
+            if idx != 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{};dur={:.3}", name, dur.as_secs_f64() * 1000.0)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timings")
+            .field("entries", &self.0.borrow().entries)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::time::{sleep, Millis};
+    use crate::web::test::{call_service, init_service, TestRequest};
+    use crate::web::{self, middleware::DefaultHeaders, App, HttpRequest, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_server_timing() {
+        let srv = init_service(
+            App::new()
+                .wrap(Timed::new(
+                    "hdrs",
+                    DefaultHeaders::new().header("x-test", "1"),
+                ))
+                .wrap(ServerTiming::new())
+                .service(web::resource("/").to(|req: HttpRequest| async move {
+                    sleep(Millis(20)).await;
+                    let timings = req.extensions().get::<Timings>().cloned().unwrap();
+                    timings.record("custom", Duration::from_millis(1));
+                    assert!(timings.get("custom").is_some());
+                    HttpResponse::Ok()
+                })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("x-test").unwrap(), "1");
+
+        let hdr = resp.headers().get(SERVER_TIMING).unwrap().to_str().unwrap();
+        let names: Vec<_> = hdr
+            .split(", ")
+            .map(|item| item.split(';').next().unwrap())
+            .collect();
+        assert_eq!(names, vec!["custom", "handler", "hdrs", "total"]);
+        assert!(hdr.contains("custom;dur=1.000"));
+    }
+
+    #[crate::rt_test]
+    async fn test_timings() {
+        let timings = Timings::default();
+        timings.record("db", Duration::from_millis(2));
+        timings.record("cache", Duration::from_millis(1));
+        timings.record("db", Duration::from_millis(3));
+        assert_eq!(timings.get("db"), Some(Duration::from_millis(5)));
+        assert_eq!(timings.get("unknown"), None);
+        assert_eq!(timings.entries().len(), 2);
+        assert_eq!(timings.to_string(), "db;dur=5.000, cache;dur=1.000");
+        assert!(format!("{:?}", timings).contains("Timings"));
+
+        // disabled header
+        let srv = init_service(
+            App::new()
+                .wrap(Timed::new("hdrs", DefaultHeaders::new()))
+                .wrap(ServerTiming::new().header(false))
+                .service(web::resource("/").to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert!(resp.headers().get(SERVER_TIMING).is_none());
+        assert!(format!("{:?}", Timed::new("t", ServerTiming::new())).contains("Timed"));
+    }
+}
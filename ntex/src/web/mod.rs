@@ -61,6 +61,7 @@
 //! * `compress` - enables content encoding compression support
 //! * `openssl` - enables ssl support via `openssl` crate
 //! * `rustls` - enables ssl support via `rustls` crate
+//! * `grpc` - enables grpc methods support
 
 mod app;
 mod app_service;
@@ -72,7 +73,9 @@ pub mod guard;
 mod handler;
 mod httprequest;
 mod info;
+pub mod introspect;
 pub mod middleware;
+mod policy;
 mod request;
 mod resource;
 mod responder;
@@ -81,14 +84,19 @@ mod rmap;
 mod route;
 mod scope;
 mod server;
+mod server_config;
 mod service;
 pub mod test;
 pub mod types;
 mod util;
+mod vhost;
 
 #[cfg(feature = "ws")]
 pub mod ws;
 
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
 // re-export proc macro
 pub use ntex_macros::web_connect as connect;
 pub use ntex_macros::web_delete as delete;
@@ -103,7 +111,7 @@ pub use ntex_macros::web_trace as trace;
 pub use crate::http::Response as HttpResponse;
 pub use crate::http::ResponseBuilder as HttpResponseBuilder;
 
-pub use self::app::App;
+pub use self::app::{App, StatePolicy};
 pub use self::config::ServiceConfig;
 pub use self::error::{
     DefaultError, Error, ErrorContainer, ErrorRenderer, WebResponseError,
@@ -111,6 +119,7 @@ pub use self::error::{
 pub use self::extract::FromRequest;
 pub use self::handler::Handler;
 pub use self::httprequest::HttpRequest;
+pub use self::policy::BodyPolicy;
 pub use self::request::WebRequest;
 pub use self::resource::Resource;
 pub use self::responder::Responder;
@@ -118,8 +127,12 @@ pub use self::response::WebResponse;
 pub use self::route::Route;
 pub use self::scope::Scope;
 pub use self::server::HttpServer;
+#[cfg(any(feature = "openssl", feature = "rustls"))]
+pub use self::server::TlsConfig;
+pub use self::server_config::{HttpServerConfig, TlsFiles};
 pub use self::service::WebServiceFactory;
 pub use self::util::*;
+pub use self::vhost::VirtualHosts;
 
 pub mod dev {
     //! The `ntex::web` prelude for library developers
@@ -128,7 +141,7 @@ pub mod dev {
     //! traits by adding a glob import to the top of ntex::web heavy modules:
 
     pub use crate::web::config::AppConfig;
-    pub use crate::web::info::ConnectionInfo;
+    pub use crate::web::info::{ConnectionInfo, TrustedProxies};
     pub use crate::web::rmap::ResourceMap;
     pub use crate::web::route::IntoRoutes;
     pub use crate::web::service::{WebServiceAdapter, WebServiceConfig, WebServiceFactory};
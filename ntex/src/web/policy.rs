@@ -0,0 +1,336 @@
+use std::{pin::Pin, task::Context, task::Poll};
+
+use mime::Mime;
+
+use crate::http::{error::PayloadError, header, HeaderMap, Payload, Response};
+use crate::util::{Bytes, Stream};
+
+use super::HttpRequest;
+
+#[derive(Clone, Debug, Default)]
+/// Route's body policy
+///
+/// Declares expected request content types, produced response content types
+/// and max request body size. Policy is enforced before extractors and handler
+/// run:
+///
+/// - request with unexpected content type gets *415 Unsupported Media Type*
+/// - request with payload bigger than max size gets *413 Payload Too Large*
+/// - request that does not accept any of produced content types gets
+///   *406 Not Acceptable*
+///
+/// Max body size is also used as size limit by `Json`, `Form`, `Bytes`
+/// and `String` extractors, so extractor configs are not needed.
+///
+/// ```rust
+/// use ntex::web::{self, App, BodyPolicy, HttpResponse};
+///
+/// fn main() {
+///     let json = BodyPolicy::new()
+///         .content_type(mime::APPLICATION_JSON)
+///         .max_size(65_536);
+///
+///     let app = App::new().service(
+///         web::resource("/users")
+///             .route(web::post().body(json.clone()).to(|| async { HttpResponse::Ok() }))
+///             .route(web::put().body(json).to(|| async { HttpResponse::Ok() }))
+///     );
+/// }
+/// ```
+pub struct BodyPolicy {
+    content_types: Vec<Mime>,
+    produces: Vec<Mime>,
+    max_size: Option<usize>,
+}
+
+impl BodyPolicy {
+    /// Create empty policy, all requests are allowed
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add expected request content type.
+    ///
+    /// Parameters are ignored, wildcard subtype like `image/*` is supported.
+    /// Requests without body are not checked.
+    pub fn content_type(mut self, mime: Mime) -> Self {
+        self.content_types.push(mime);
+        self
+    }
+
+    /// Add produced response content type.
+    ///
+    /// Request's `Accept` header must match at least one of produced
+    /// content types. Requests without `Accept` header are not checked.
+    pub fn produces(mut self, mime: Mime) -> Self {
+        self.produces.push(mime);
+        self
+    }
+
+    /// Set max request body size in bytes.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Check request head, returns error response if request violates policy
+    pub(super) fn check(&self, headers: &HeaderMap) -> Option<Response> {
+        if let Some(max) = self.max_size {
+            if content_length(headers)
+                .map(|len| len > max)
+                .unwrap_or(false)
+            {
+                return Some(Response::PayloadTooLarge().finish());
+            }
+        }
+
+        if !self.content_types.is_empty() && has_body(headers) {
+            let ctype = headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|val| val.to_str().ok())
+                .and_then(|val| val.parse::<Mime>().ok());
+            let allowed = ctype
+                .map(|ct| self.content_types.iter().any(|m| mime_matches(m, &ct)))
+                .unwrap_or(false);
+            if !allowed {
+                return Some(Response::UnsupportedMediaType().finish());
+            }
+        }
+
+        if !self.produces.is_empty() && !self.acceptable(headers) {
+            return Some(Response::NotAcceptable().finish());
+        }
+        None
+    }
+
+    /// Limit payload size
+    pub(super) fn payload(&self, payload: Payload) -> Payload {
+        match (self.max_size, payload) {
+            (_, Payload::None) => Payload::None,
+            (Some(limit), payload) => Payload::from_stream(Limited {
+                limit,
+                payload,
+                size: 0,
+            }),
+            (None, payload) => payload,
+        }
+    }
+
+    /// Max body size, if set
+    pub(super) fn max_body_size(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    fn acceptable(&self, headers: &HeaderMap) -> bool {
+        let mut found = false;
+        for val in headers.get_all(header::ACCEPT) {
+            let val = if let Ok(val) = val.to_str() {
+                val
+            } else {
+                continue;
+            };
+            found = true;
+            for item in val.split(',') {
+                let accept = if let Ok(accept) = item.trim().parse::<Mime>() {
+                    accept
+                } else {
+                    continue;
+                };
+                let rejected = accept
+                    .get_param("q")
+                    .and_then(|q| q.as_str().parse::<f32>().ok())
+                    .map(|q| q <= 0.0)
+                    .unwrap_or(false);
+                if !rejected
+                    && self
+                        .produces
+                        .iter()
+                        .any(|m| mime_matches(&accept, m) || mime_matches(m, &accept))
+                {
+                    return true;
+                }
+            }
+        }
+        !found
+    }
+}
+
+/// Route's max body size, used as extractors limit
+pub(crate) fn body_limit(req: &HttpRequest, default: usize) -> usize {
+    req.extensions()
+        .get::<BodyLimit>()
+        .map(|limit| limit.0)
+        .unwrap_or(default)
+}
+
+/// Request extension with route's max body size
+pub(super) struct BodyLimit(pub(super) usize);
+
+/// Check if `mime` matches `pattern`, pattern could have wildcards
+fn mime_matches(pattern: &Mime, mime: &Mime) -> bool {
+    (pattern.type_() == mime::STAR || pattern.type_() == mime.type_())
+        && (pattern.subtype() == mime::STAR || pattern.subtype() == mime.subtype())
+}
+
+fn content_length(headers: &HeaderMap) -> Option<usize> {
+    headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.parse::<usize>().ok())
+}
+
+fn has_body(headers: &HeaderMap) -> bool {
+    headers.contains_key(header::TRANSFER_ENCODING)
+        || content_length(headers).map(|len| len != 0).unwrap_or(false)
+}
+
+/// Payload stream with size limit
+struct Limited {
+    payload: Payload,
+    limit: usize,
+    size: usize,
+}
+
+impl Stream for Limited {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.size > this.limit {
+            return Poll::Ready(None);
+        }
+
+        match this.payload.poll_recv(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                this.size += chunk.len();
+                if this.size > this.limit {
+                    Poll::Ready(Some(Err(PayloadError::Overflow)))
+                } else {
+                    Poll::Ready(Some(Ok(chunk)))
+                }
+            }
+            res => res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{header, Method, StatusCode};
+    use crate::web::test::{call_service, init_service, TestRequest};
+    use crate::web::{self, App, HttpResponse};
+
+    #[test]
+    fn test_mime_matches() {
+        assert!(mime_matches(&mime::IMAGE_STAR, &mime::IMAGE_PNG));
+        assert!(mime_matches(&mime::STAR_STAR, &mime::APPLICATION_JSON));
+        assert!(mime_matches(
+            &mime::APPLICATION_JSON,
+            &mime::APPLICATION_JSON
+        ));
+        assert!(!mime_matches(&mime::APPLICATION_JSON, &mime::TEXT_PLAIN));
+        assert!(!mime_matches(&mime::IMAGE_PNG, &mime::IMAGE_STAR));
+    }
+
+    #[crate::rt_test]
+    async fn test_body_policy() {
+        let policy = BodyPolicy::new()
+            .content_type(mime::APPLICATION_JSON)
+            .produces(mime::APPLICATION_JSON)
+            .max_size(16);
+        let srv = init_service(
+            App::new().service(
+                web::resource("/test")
+                    .route(
+                        web::post()
+                            .body(policy)
+                            .to(|_: Bytes| async { HttpResponse::Ok() }),
+                    )
+                    .route(
+                        web::put()
+                            .max_body_size(300_000)
+                            .to(|body: Bytes| async move { body.len().to_string() }),
+                    ),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+            .header(header::ACCEPT, "text/html, */*;q=0.5")
+            .set_payload("{}")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, "text/plain")
+            .set_payload("{}")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::POST)
+            .set_payload("{}")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, "application/json")
+            .set_payload("{\"name\": \"01234567890\"}")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::POST)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT, "text/html, application/json;q=0")
+            .set_payload("{}")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+
+        // extractor uses route's limit instead of default one
+        let req = TestRequest::with_uri("/test")
+            .method(Method::PUT)
+            .set_payload(vec![b'x'; 270_000])
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::PUT)
+            .set_payload(vec![b'x'; 300_001])
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[crate::rt_test]
+    async fn test_limited_payload() {
+        let (mut sender, payload) = crate::http::h1::Payload::create(false);
+        sender.feed_data(Bytes::from_static(b"1234"));
+        sender.feed_data(Bytes::from_static(b"5678"));
+        drop(sender);
+
+        let mut payload = BodyPolicy::new()
+            .max_size(6)
+            .payload(Payload::from(payload));
+        assert_eq!(
+            payload.recv().await.unwrap().unwrap(),
+            Bytes::from_static(b"1234")
+        );
+        assert!(matches!(
+            payload.recv().await,
+            Some(Err(PayloadError::Overflow))
+        ));
+        assert!(payload.recv().await.is_none());
+    }
+}
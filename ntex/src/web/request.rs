@@ -1,11 +1,12 @@
 use std::{cell::Ref, cell::RefMut, fmt, marker::PhantomData, net, rc::Rc};
 
 use crate::http::{
-    header, HeaderMap, HttpMessage, Method, Payload, RequestHead, Response, Uri, Version,
+    header, HeaderMap, HttpMessage, Method, Payload, RequestHead, RequestTarget, Response,
+    Uri, Version,
 };
 use crate::io::{types, IoRef};
 use crate::router::{Path, Resource};
-use crate::util::Extensions;
+use crate::util::{timeout::CallDeadline, timeout::RequestDeadline, Extensions};
 
 use super::config::AppConfig;
 use super::error::{ErrorRenderer, WebResponseError};
@@ -152,6 +153,14 @@ impl<Err> WebRequest<Err> {
         }
     }
 
+    /// Parsed view of the request target.
+    ///
+    /// Path segments and query pairs are parsed once per request.
+    #[inline]
+    pub fn target(&self) -> Rc<RequestTarget> {
+        self.head().target()
+    }
+
     /// Peer socket address
     ///
     /// Peer address is actual socket address, if proxy is used in front of
@@ -242,6 +251,13 @@ impl<Err> WebRequest<Err> {
     }
 }
 
+/// Call deadline is taken from `CallDeadline` request extension
+impl<Err> RequestDeadline for WebRequest<Err> {
+    fn deadline(&self) -> Option<std::time::Instant> {
+        self.extensions().get::<CallDeadline>().map(|d| d.0)
+    }
+}
+
 impl<Err> Resource<Uri> for WebRequest<Err> {
     fn path(&self) -> &str {
         self.match_info().path()
@@ -305,6 +321,18 @@ mod tests {
     use crate::web::test::TestRequest;
     use crate::web::HttpResponse;
 
+    #[test]
+    fn test_deadline() {
+        use crate::util::timeout::{CallDeadline, RequestDeadline};
+
+        let req = TestRequest::default().to_srv_request();
+        assert_eq!(req.deadline(), None);
+
+        let deadline = std::time::Instant::now();
+        req.extensions_mut().insert(CallDeadline(deadline));
+        assert_eq!(req.deadline(), Some(deadline));
+    }
+
     #[test]
     fn test_request() {
         let mut req = TestRequest::default().to_srv_request();
@@ -1,7 +1,7 @@
 use std::{cell::RefCell, fmt, rc::Rc};
 
-use crate::http::Response;
-use crate::router::{IntoPattern, ResourceDef};
+use crate::http::{header, Method, Response};
+use crate::router::{IntoPattern, ResourceDef, Router};
 use crate::service::boxed::{self, BoxService, BoxServiceFactory};
 use crate::service::dev::{AndThen, ServiceChain, ServiceChainFactory};
 use crate::service::{chain, chain_factory, ServiceCtx};
@@ -12,11 +12,13 @@ use crate::util::Extensions;
 
 use super::dev::{insert_slash, WebServiceConfig, WebServiceFactory};
 use super::extract::FromRequest;
+use super::guard::{self, Guard};
 use super::handler::Handler;
+use super::introspect::ResourceInfo;
 use super::request::WebRequest;
 use super::response::WebResponse;
 use super::route::{IntoRoutes, Route, RouteService};
-use super::{app::Filter, error::ErrorRenderer, guard::Guard, service::AppState};
+use super::{app::Filter, error::ErrorRenderer, service::AppState};
 
 type HttpService<Err: ErrorRenderer> =
     BoxService<WebRequest<Err>, WebResponse, Err::Container>;
@@ -45,7 +47,8 @@ type ResourcePipeline<F, Err> =
 /// }
 /// ```
 ///
-/// If no matching route could be found, *405* response code get returned.
+/// If no matching route could be found, *405* response code with `Allow` header
+/// get returned.
 /// Default behavior could be overriden with `default_resource()` method.
 pub struct Resource<Err: ErrorRenderer, M = Identity, T = Filter<Err>> {
     middleware: M,
@@ -322,6 +325,11 @@ where
     Err: ErrorRenderer,
 {
     fn register(mut self, config: &mut WebServiceConfig<Err>) {
+        let info = ResourceInfo::new(
+            self.name.clone(),
+            &self.guards,
+            self.routes.iter().map(|route| route.info()).collect(),
+        );
         let guards = if self.guards.is_empty() {
             None
         } else {
@@ -348,6 +356,7 @@ where
             state,
             routes: self.routes,
             default: self.default.borrow_mut().take(),
+            options: config.method_not_allowed(),
         };
 
         config.register_service(
@@ -359,7 +368,8 @@ where
                 routing: router_factory,
             },
             None,
-        )
+        );
+        config.set_resource_info(info);
     }
 }
 
@@ -386,6 +396,7 @@ where
             state: None,
             routes: self.routes,
             default: self.default.borrow_mut().take(),
+            options: false,
         };
 
         ResourceServiceFactory {
@@ -431,6 +442,7 @@ struct ResourceRouterFactory<Err: ErrorRenderer> {
     routes: Vec<Route<Err>>,
     default: Option<Rc<HttpNewService<Err>>>,
     state: Option<AppState>,
+    options: bool,
 }
 
 impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for ResourceRouterFactory<Err> {
@@ -447,6 +459,7 @@ impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for ResourceRouterFacto
         };
         Ok(ResourceRouter {
             default,
+            options: self.options,
             state: self.state.clone(),
             routes: self.routes.iter().map(|route| route.service()).collect(),
         })
@@ -457,6 +470,7 @@ pub struct ResourceRouter<Err: ErrorRenderer> {
     state: Option<AppState>,
     routes: Vec<RouteService<Err>>,
     default: Option<HttpService<Err>>,
+    options: bool,
 }
 
 impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ResourceRouter<Err> {
@@ -479,14 +493,61 @@ impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ResourceRouter<Err> {
         if let Some(ref default) = self.default {
             ctx.call(default, req).await
         } else {
-            Ok(WebResponse::new(
-                Response::MethodNotAllowed().finish(),
-                req.into_parts().0,
-            ))
+            let mut methods = Vec::new();
+            for route in self.routes.iter() {
+                route.allowed_methods(req.head(), &mut methods);
+            }
+            Ok(method_not_allowed(req, methods, self.options))
         }
     }
 }
 
+/// Collect methods of resources that match request path but not request method
+pub(super) fn allowed_methods<T, Err: ErrorRenderer>(
+    router: &Router<T, Vec<Box<dyn Guard>>>,
+    req: &mut WebRequest<Err>,
+) -> Vec<Method> {
+    let methods = RefCell::new(Vec::new());
+    router.recognize_checked(req, |req, guards| {
+        if let Some(guards) = guards {
+            guard::allowed_methods(guards, req.head(), &mut methods.borrow_mut());
+        }
+        false
+    });
+    methods.into_inner()
+}
+
+/// Create *405 Method Not Allowed* response with `Allow` header.
+///
+/// If `options` is set, `OPTIONS` request get answered with *204 No Content*.
+pub(super) fn method_not_allowed<Err: ErrorRenderer>(
+    req: WebRequest<Err>,
+    mut methods: Vec<Method>,
+    options: bool,
+) -> WebResponse {
+    if methods.is_empty() {
+        return WebResponse::new(Response::MethodNotAllowed().finish(), req.into_parts().0);
+    }
+    if options && !methods.contains(&Method::OPTIONS) {
+        methods.push(Method::OPTIONS);
+    }
+
+    let mut res = if options && req.head().method == Method::OPTIONS {
+        Response::NoContent()
+    } else {
+        Response::MethodNotAllowed()
+    };
+    let allow = methods
+        .iter()
+        .map(|m| m.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    WebResponse::new(
+        res.header(header::ALLOW, allow).finish(),
+        req.into_parts().0,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::http::header::{self, HeaderValue};
@@ -589,6 +650,10 @@ mod tests {
             .to_request();
         let resp = call_service(&srv, req).await;
         assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            resp.headers().get(header::ALLOW).unwrap(),
+            HeaderValue::from_static("GET")
+        );
 
         let srv = init_service(
             App::new().service(
@@ -1,9 +1,10 @@
-use std::fmt;
+use std::{error::Error, fmt};
 
-use crate::http::body::{Body, MessageBody, ResponseBody};
+use crate::http::body::{Body, BodyMap, MessageBody, ResponseBody};
 use crate::http::{HeaderMap, Response, ResponseHead, StatusCode};
+use crate::util::Bytes;
 
-use super::error::{ErrorContainer, ErrorRenderer};
+use super::error::{ErrorCategory, ErrorContainer, ErrorRenderer};
 use super::httprequest::HttpRequest;
 
 /// An service http response
@@ -25,11 +26,19 @@ impl WebResponse {
     ) -> Self {
         let err = err.into();
         let res: Response = err.error_response(&request);
+        let category = err.category();
+        res.extensions_mut().insert(category);
 
-        if res.head().status == StatusCode::INTERNAL_SERVER_ERROR {
-            log::error!("Internal Server Error: {:?}", err);
-        } else {
-            log::debug!("Error in response: {:?}", err);
+        match category {
+            ErrorCategory::Aborted => log::debug!("Request aborted by client: {:?}", err),
+            ErrorCategory::Timeout => log::debug!("Request timed out: {:?}", err),
+            ErrorCategory::Handler => {
+                if res.head().status == StatusCode::INTERNAL_SERVER_ERROR {
+                    log::error!("Internal Server Error: {:?}", err);
+                } else {
+                    log::debug!("Error in response: {:?}", err);
+                }
+            }
         }
 
         WebResponse {
@@ -71,6 +80,12 @@ impl WebResponse {
         &mut self.response
     }
 
+    /// Get category of the error, if response is generated from the error
+    #[inline]
+    pub fn error_category(&self) -> Option<ErrorCategory> {
+        self.response.extensions().get::<ErrorCategory>().copied()
+    }
+
     /// Get the response status code
     #[inline]
     pub fn status(&self) -> StatusCode {
@@ -121,6 +136,29 @@ impl WebResponse {
             request: self.request,
         }
     }
+
+    /// Wrap response body
+    ///
+    /// Closure receives current body and returns any message body,
+    /// it allows to wrap streaming bodies without buffering.
+    pub fn wrap_body<F, B>(self, f: F) -> WebResponse
+    where
+        F: FnOnce(&mut ResponseHead, ResponseBody<Body>) -> B,
+        B: MessageBody,
+    {
+        self.map_body(|head, body| ResponseBody::Other(Body::from_message(f(head, body))))
+    }
+
+    /// Transform response body chunks
+    ///
+    /// Mapping function is called for every chunk of the response body,
+    /// returned error aborts response body stream. See `BodyMap` for details.
+    pub fn map_chunks<F>(self, f: F) -> WebResponse
+    where
+        F: FnMut(Bytes) -> Result<Bytes, Box<dyn Error>> + 'static,
+    {
+        self.wrap_body(|_, body| BodyMap::new(body, f))
+    }
 }
 
 impl From<WebResponse> for Response<Body> {
@@ -153,7 +191,11 @@ impl fmt::Debug for WebResponse {
 
 #[cfg(test)]
 mod tests {
+    use std::io;
+
     use crate::http::{self, StatusCode};
+    use crate::util::timeout::TimeoutError;
+    use crate::web::error::{ErrorCategory, JsonPayloadError};
     use crate::web::test::TestRequest;
     use crate::web::{DefaultError, HttpResponse};
 
@@ -176,4 +218,61 @@ mod tests {
         });
         assert_eq!(res.response().status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
+
+    #[crate::rt_test]
+    async fn test_map_chunks() {
+        use crate::http::body::{BodySize, MessageBody};
+        use crate::util::Bytes;
+
+        let mut res = TestRequest::default()
+            .to_srv_response(HttpResponse::Ok().body("test"))
+            .map_chunks(|chunk| Ok(Bytes::from(chunk.to_ascii_uppercase())));
+        assert_eq!(res.response().body().size(), BodySize::Stream);
+        let mut body = res.take_body();
+        let chunk = std::future::poll_fn(|cx| body.poll_next_chunk(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk, Bytes::from_static(b"TEST"));
+
+        let res = TestRequest::default()
+            .to_srv_response(HttpResponse::Ok().body("test"))
+            .wrap_body(|head, body| {
+                head.headers.insert(
+                    http::header::CONTENT_TYPE,
+                    http::header::HeaderValue::from_static("text/plain"),
+                );
+                body
+            });
+        assert_eq!(res.response().body().size(), BodySize::Sized(4));
+        assert!(res.headers().contains_key(http::header::CONTENT_TYPE));
+    }
+
+    #[test]
+    fn test_error_category() {
+        let res = TestRequest::default().to_srv_response(HttpResponse::Ok().finish());
+        assert_eq!(res.error_category(), None);
+
+        let res =
+            res.error_response::<DefaultError, _>(http::error::PayloadError::Overflow);
+        assert_eq!(res.error_category(), Some(ErrorCategory::Handler));
+
+        let res = res
+            .error_response::<DefaultError, _>(http::error::PayloadError::Incomplete(None));
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(res.error_category(), Some(ErrorCategory::Aborted));
+
+        let err = JsonPayloadError::Payload(http::error::PayloadError::Io(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "reset",
+        )));
+        let res = res.error_response::<DefaultError, _>(err);
+        assert_eq!(res.error_category(), Some(ErrorCategory::Aborted));
+
+        let err: TimeoutError<io::Error> = TimeoutError::Timeout;
+        let res = res.error_response::<DefaultError, _>(err);
+        assert_eq!(res.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(res.error_category(), Some(ErrorCategory::Timeout));
+        assert_eq!(ErrorCategory::Timeout.to_string(), "timeout");
+    }
 }
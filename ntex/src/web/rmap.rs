@@ -7,14 +7,14 @@ use crate::router::ResourceDef;
 use crate::util::HashMap;
 #[cfg(feature = "url")]
 use crate::web::httprequest::HttpRequest;
+use crate::web::introspect::ResourceInfo;
 
 #[derive(Clone, Debug)]
 pub struct ResourceMap {
-    #[allow(dead_code)]
     root: ResourceDef,
     parent: RefCell<Option<Rc<ResourceMap>>>,
     named: HashMap<String, ResourceDef>,
-    patterns: Vec<(ResourceDef, Option<Rc<ResourceMap>>)>,
+    patterns: Vec<(ResourceDef, Option<Rc<ResourceMap>>, Option<ResourceInfo>)>,
 }
 
 impl ResourceMap {
@@ -28,16 +28,45 @@ impl ResourceMap {
     }
 
     pub fn add(&mut self, pattern: &mut ResourceDef, nested: Option<Rc<ResourceMap>>) {
+        self.add_resource(pattern, nested, None)
+    }
+
+    pub(crate) fn add_resource(
+        &mut self,
+        pattern: &mut ResourceDef,
+        nested: Option<Rc<ResourceMap>>,
+        info: Option<ResourceInfo>,
+    ) {
         pattern.set_id(self.patterns.len() as u16);
-        self.patterns.push((pattern.clone(), nested));
+        self.patterns.push((pattern.clone(), nested, info));
         if !pattern.name().is_empty() {
             self.named
                 .insert(pattern.name().to_string(), pattern.clone());
         }
     }
 
+    /// List of registered resources, including resources of nested scopes
+    pub fn resources(&self) -> Vec<ResourceInfo> {
+        let mut resources = Vec::new();
+        self.collect_resources("", &mut resources);
+        resources
+    }
+
+    fn collect_resources(&self, prefix: &str, resources: &mut Vec<ResourceInfo>) {
+        let prefix = format!("{}{}", prefix, self.root.pattern());
+        for (pattern, nested, info) in &self.patterns {
+            if let Some(ref nested) = nested {
+                nested.collect_resources(&prefix, resources);
+            } else if let Some(ref info) = info {
+                let mut info = info.clone();
+                info.set_pattern(format!("{}{}", prefix, pattern.pattern()));
+                resources.push(info);
+            }
+        }
+    }
+
     pub(crate) fn finish(&self, current: Rc<ResourceMap>) {
-        for (_, nested) in &self.patterns {
+        for (_, nested, _) in &self.patterns {
             if let Some(ref nested) = nested {
                 *nested.parent.borrow_mut() = Some(current.clone());
                 nested.finish(nested.clone());
@@ -46,8 +75,31 @@ impl ResourceMap {
     }
 }
 
-#[cfg(feature = "url")]
 impl ResourceMap {
+    /// Generate path for named resource
+    ///
+    /// Check [`HttpRequest::path_for()`](../struct.HttpRequest.html#method.
+    /// path_for) for detailed information.
+    pub fn path_for<U, I>(
+        &self,
+        name: &str,
+        elements: U,
+    ) -> Result<String, super::error::UrlGenerationError>
+    where
+        U: IntoIterator<Item = I>,
+        I: AsRef<str>,
+    {
+        let mut path = String::new();
+        let mut elements = elements.into_iter();
+
+        if self.patterns_for(name, &mut path, &mut elements)?.is_some() {
+            Ok(path)
+        } else {
+            Err(super::error::UrlGenerationError::ResourceNotFound)
+        }
+    }
+
+    #[cfg(feature = "url")]
     /// Generate url for named resource
     ///
     /// Check [`HttpRequest::url_for()`](../struct.HttpRequest.html#method.
@@ -62,23 +114,22 @@ impl ResourceMap {
         U: IntoIterator<Item = I>,
         I: AsRef<str>,
     {
-        let mut path = String::new();
-        let mut elements = elements.into_iter();
+        let path = self.path_for(name, elements)?;
 
-        if self.patterns_for(name, &mut path, &mut elements)?.is_some() {
-            if path.starts_with('/') {
-                let conn = req.connection_info();
-                Ok(Url::parse(&format!(
-                    "{}://{}{}",
-                    conn.scheme(),
-                    conn.host(),
-                    path
-                ))?)
-            } else {
-                Ok(Url::parse(&path)?)
-            }
+        if path.starts_with("//") {
+            // scheme relative external resource
+            let conn = req.connection_info();
+            Ok(Url::parse(&format!("{}:{}", conn.scheme(), path))?)
+        } else if path.starts_with('/') {
+            let conn = req.connection_info();
+            Ok(Url::parse(&format!(
+                "{}://{}{}",
+                conn.scheme(),
+                conn.host(),
+                path
+            ))?)
         } else {
-            Err(super::error::UrlGenerationError::ResourceNotFound)
+            Ok(Url::parse(&path)?)
         }
     }
 
@@ -134,7 +185,7 @@ impl ResourceMap {
                 Err(super::error::UrlGenerationError::NotEnoughElements)
             }
         } else {
-            for (_, rmap) in &self.patterns {
+            for (_, rmap, _) in &self.patterns {
                 if let Some(ref rmap) = rmap {
                     if rmap.pattern_for(name, path, elements)?.is_some() {
                         return Ok(Some(()));
@@ -1,12 +1,15 @@
 use std::{fmt, mem, rc::Rc};
 
-use crate::{http::Method, service::Service, service::ServiceCtx, service::ServiceFactory};
+use crate::http::{Method, RequestHead};
+use crate::{service::Service, service::ServiceCtx, service::ServiceFactory};
 
 use super::error::ErrorRenderer;
 use super::error_default::DefaultError;
 use super::extract::FromRequest;
 use super::guard::{self, AllGuard, Guard};
 use super::handler::{Handler, HandlerFn, HandlerWrapper};
+use super::introspect::{Operation, RouteInfo};
+use super::policy::{BodyLimit, BodyPolicy};
 use super::request::WebRequest;
 use super::response::WebResponse;
 use super::HttpResponse;
@@ -19,6 +22,8 @@ pub struct Route<Err: ErrorRenderer = DefaultError> {
     handler: Rc<dyn HandlerFn<Err>>,
     methods: Vec<Method>,
     guards: Rc<AllGuard>,
+    operation: Option<Rc<Operation>>,
+    policy: Option<Rc<BodyPolicy>>,
 }
 
 impl<Err: ErrorRenderer> Route<Err> {
@@ -28,9 +33,15 @@ impl<Err: ErrorRenderer> Route<Err> {
             handler: Rc::new(HandlerWrapper::new(|| async { HttpResponse::NotFound() })),
             methods: Vec::new(),
             guards: Default::default(),
+            operation: None,
+            policy: None,
         }
     }
 
+    pub(super) fn info(&self) -> RouteInfo {
+        RouteInfo::new(self.methods.clone(), &self.guards.0, self.operation.clone())
+    }
+
     pub(super) fn take_guards(&mut self) -> Vec<Box<dyn Guard>> {
         for m in &self.methods {
             Rc::get_mut(&mut self.guards)
@@ -46,6 +57,7 @@ impl<Err: ErrorRenderer> Route<Err> {
             handler: self.handler.clone(),
             guards: self.guards.clone(),
             methods: self.methods.clone(),
+            policy: self.policy.clone(),
         }
     }
 }
@@ -56,6 +68,7 @@ impl<Err: ErrorRenderer> fmt::Debug for Route<Err> {
             .field("handler", &self.handler)
             .field("methods", &self.methods)
             .field("guards", &self.guards)
+            .field("policy", &self.policy)
             .finish()
     }
 }
@@ -75,6 +88,7 @@ pub struct RouteService<Err: ErrorRenderer> {
     handler: Rc<dyn HandlerFn<Err>>,
     methods: Vec<Method>,
     guards: Rc<AllGuard>,
+    policy: Option<Rc<BodyPolicy>>,
 }
 
 impl<Err: ErrorRenderer> RouteService<Err> {
@@ -85,6 +99,18 @@ impl<Err: ErrorRenderer> RouteService<Err> {
 
         self.guards.check(req.head())
     }
+
+    /// Collect route methods, if other route guards match request
+    pub(super) fn allowed_methods(&self, head: &RequestHead, methods: &mut Vec<Method>) {
+        let mut allowed = Vec::new();
+        if guard::allowed_methods(&self.guards.0, head, &mut allowed) {
+            for method in self.methods.iter().chain(allowed.iter()) {
+                if !methods.contains(method) {
+                    methods.push(method.clone());
+                }
+            }
+        }
+    }
 }
 
 impl<Err: ErrorRenderer> fmt::Debug for RouteService<Err> {
@@ -93,6 +119,7 @@ impl<Err: ErrorRenderer> fmt::Debug for RouteService<Err> {
             .field("handler", &self.handler)
             .field("methods", &self.methods)
             .field("guards", &self.guards)
+            .field("policy", &self.policy)
             .finish()
     }
 }
@@ -103,9 +130,19 @@ impl<Err: ErrorRenderer> Service<WebRequest<Err>> for RouteService<Err> {
 
     async fn call(
         &self,
-        req: WebRequest<Err>,
+        mut req: WebRequest<Err>,
         _: ServiceCtx<'_, Self>,
     ) -> Result<Self::Response, Self::Error> {
+        if let Some(ref policy) = self.policy {
+            if let Some(res) = policy.check(req.headers()) {
+                return Ok(req.into_response(res));
+            }
+            if let Some(limit) = policy.max_body_size() {
+                let payload = policy.payload(req.take_payload());
+                req.set_payload(payload);
+                req.extensions_mut().insert(BodyLimit(limit));
+            }
+        }
         self.handler.call(req).await
     }
 }
@@ -147,6 +184,76 @@ impl<Err: ErrorRenderer> Route<Err> {
         self
     }
 
+    /// Attach operation metadata to the route.
+    ///
+    /// Metadata is available via application resource map and is used
+    /// for OpenAPI document generation.
+    ///
+    /// ```rust
+    /// use ntex::web::{self, introspect::Operation, App, HttpResponse};
+    ///
+    /// fn main() {
+    ///     let app = App::new().service(web::resource("/users").route(
+    ///         web::get()
+    ///             .operation(Operation::new("list_users").summary("List users"))
+    ///             .to(|| async { HttpResponse::Ok() }))
+    ///     );
+    /// }
+    /// ```
+    pub fn operation(mut self, operation: Operation) -> Self {
+        self.operation = Some(Rc::new(operation));
+        self
+    }
+
+    /// Set route's body policy.
+    ///
+    /// Policy is checked before extractors and handler run.
+    /// See [`BodyPolicy`](super::BodyPolicy) for details.
+    ///
+    /// ```rust
+    /// use ntex::web::{self, App, BodyPolicy, HttpResponse};
+    ///
+    /// fn main() {
+    ///     let app = App::new().service(web::resource("/upload").route(
+    ///         web::post()
+    ///             .body(BodyPolicy::new().content_type(mime::IMAGE_STAR).max_size(1_048_576))
+    ///             .to(|| async { HttpResponse::Ok() }))
+    ///     );
+    /// }
+    /// ```
+    pub fn body(mut self, policy: BodyPolicy) -> Self {
+        self.policy = Some(Rc::new(policy));
+        self
+    }
+
+    /// Add expected request content type to route's body policy.
+    pub fn content_type(self, mime: mime::Mime) -> Self {
+        self.update_policy(|policy| policy.content_type(mime))
+    }
+
+    /// Add produced response content type to route's body policy.
+    pub fn produces(self, mime: mime::Mime) -> Self {
+        self.update_policy(|policy| policy.produces(mime))
+    }
+
+    /// Set max request body size for route's body policy.
+    pub fn max_body_size(self, size: usize) -> Self {
+        self.update_policy(|policy| policy.max_size(size))
+    }
+
+    fn update_policy<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(BodyPolicy) -> BodyPolicy,
+    {
+        let policy = self
+            .policy
+            .take()
+            .map(|policy| (*policy).clone())
+            .unwrap_or_default();
+        self.policy = Some(Rc::new(f(policy)));
+        self
+    }
+
     /// Set handler function, use request extractors for parameters.
     ///
     /// ```rust
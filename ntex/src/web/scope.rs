@@ -13,7 +13,7 @@ use super::dev::{WebServiceConfig, WebServiceFactory};
 use super::error::ErrorRenderer;
 use super::guard::Guard;
 use super::request::WebRequest;
-use super::resource::Resource;
+use super::resource::{self, Resource};
 use super::response::WebResponse;
 use super::rmap::ResourceMap;
 use super::route::Route;
@@ -64,6 +64,7 @@ pub struct Scope<Err: ErrorRenderer, M = Identity, T = Filter<Err>> {
     default: Rc<RefCell<Option<Rc<HttpNewService<Err>>>>>,
     external: Vec<ResourceDef>,
     case_insensitive: bool,
+    method_not_allowed: Option<bool>,
 }
 
 impl<Err: ErrorRenderer> Scope<Err> {
@@ -79,6 +80,7 @@ impl<Err: ErrorRenderer> Scope<Err> {
             default: Rc::new(RefCell::new(None)),
             external: Vec::new(),
             case_insensitive: false,
+            method_not_allowed: None,
         }
     }
 }
@@ -160,6 +162,37 @@ where
         self
     }
 
+    /// Respond with *405 Method Not Allowed* if request path matches
+    /// a resource but request method does not.
+    ///
+    /// Response contains `Allow` header with the list of methods supported
+    /// by the matched resources. `OPTIONS` requests are answered
+    /// automatically with *204 No Content* and `Allow` header.
+    ///
+    /// By default scope inherits setting from the parent scope or application.
+    pub fn method_not_allowed(mut self, enabled: bool) -> Self {
+        self.method_not_allowed = Some(enabled);
+        self
+    }
+
+    /// Register an external resource.
+    ///
+    /// External resources are useful for URL generation purposes only
+    /// and are never considered for matching at request time. Calls to
+    /// `HttpRequest::url_for()` will work as expected.
+    ///
+    /// This is same as `App::external_resource()` method.
+    pub fn external_resource<N, U>(mut self, name: N, url: U) -> Self
+    where
+        N: AsRef<str>,
+        U: AsRef<str>,
+    {
+        let mut rdef = ResourceDef::new(url.as_ref());
+        *rdef.name_mut() = name.as_ref().to_string();
+        self.external.push(rdef);
+        self
+    }
+
     /// Run external configuration as part of the scope building
     /// process
     ///
@@ -329,6 +362,7 @@ where
             default: self.default,
             external: self.external,
             case_insensitive: self.case_insensitive,
+            method_not_allowed: self.method_not_allowed,
         }
     }
 
@@ -353,6 +387,7 @@ where
             default: self.default,
             external: self.external,
             case_insensitive: self.case_insensitive,
+            method_not_allowed: self.method_not_allowed,
         }
     }
 }
@@ -384,7 +419,11 @@ where
         });
 
         // register nested services
+        let method_not_allowed = self
+            .method_not_allowed
+            .unwrap_or_else(|| config.method_not_allowed());
         let mut cfg = config.clone_config(state.clone());
+        cfg.set_method_not_allowed(method_not_allowed);
         self.services
             .into_iter()
             .for_each(|mut srv| srv.register(&mut cfg));
@@ -402,10 +441,11 @@ where
             state,
             default: self.default.borrow_mut().take(),
             case_insensitive: self.case_insensitive,
+            method_not_allowed,
             services: cfg
                 .into_services()
                 .into_iter()
-                .map(|(rdef, srv, guards, nested)| {
+                .map(|(rdef, srv, guards, nested, info)| {
                     // case for scope prefix ends with '/' and
                     // resource is empty pattern
                     let mut rdef = if slesh && rdef.pattern() == "" {
@@ -413,7 +453,7 @@ where
                     } else {
                         rdef
                     };
-                    rmap.add(&mut rdef, nested);
+                    rmap.add_resource(&mut rdef, nested, info);
                     (rdef, srv, RefCell::new(guards))
                 })
                 .collect(),
@@ -511,6 +551,7 @@ struct ScopeRouterFactory<Err: ErrorRenderer> {
     services: Vec<(ResourceDef, HttpNewService<Err>, RefCell<Option<Guards>>)>,
     default: Option<Rc<HttpNewService<Err>>>,
     case_insensitive: bool,
+    method_not_allowed: bool,
 }
 
 impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for ScopeRouterFactory<Err> {
@@ -539,6 +580,7 @@ impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for ScopeRouterFactory<
         Ok(ScopeRouter {
             default,
             router: router.finish(),
+            method_not_allowed: self.method_not_allowed,
             state: self.state.clone(),
         })
     }
@@ -548,6 +590,7 @@ struct ScopeRouter<Err: ErrorRenderer> {
     state: Option<AppState>,
     router: Router<HttpService<Err>, Vec<Box<dyn Guard>>>,
     default: Option<HttpService<Err>>,
+    method_not_allowed: bool,
 }
 
 impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ScopeRouter<Err> {
@@ -574,8 +617,17 @@ impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ScopeRouter<Err> {
             if let Some(ref state) = self.state {
                 req.set_state_container(state.clone());
             }
-            ctx.call(srv, req).await
-        } else if let Some(ref default) = self.default {
+            return ctx.call(srv, req).await;
+        }
+
+        if self.method_not_allowed {
+            let methods = resource::allowed_methods(&self.router, &mut req);
+            if !methods.is_empty() {
+                return Ok(resource::method_not_allowed(req, methods, true));
+            }
+        }
+
+        if let Some(ref default) = self.default {
             ctx.call(default, req).await
         } else {
             let req = req.into_parts().0;
@@ -587,7 +639,7 @@ impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ScopeRouter<Err> {
 #[cfg(test)]
 mod tests {
     use crate::http::body::{Body, ResponseBody};
-    use crate::http::header::{HeaderValue, CONTENT_TYPE};
+    use crate::http::header::{self, HeaderValue, CONTENT_TYPE};
     use crate::http::{Method, StatusCode};
     use crate::service::fn_service;
     use crate::util::{Bytes, Ready};
@@ -1233,4 +1285,69 @@ mod tests {
             Bytes::from_static(b"http://localhost:8080/a/b/c/12345")
         );
     }
+
+    #[crate::rt_test]
+    async fn test_path_for_nested() {
+        let srv = init_service(
+            App::new().service(
+                web::scope("/a").service(
+                    web::scope("/b")
+                        .external_resource("docs", "https://docs.example.com/{page}")
+                        .service(web::resource("/c/{stuff}").name("c").route(
+                            web::get().to(|req: HttpRequest| async move {
+                                HttpResponse::Ok().body(format!(
+                                    "{} {}",
+                                    req.path_for("c", ["12345"]).unwrap(),
+                                    req.path_for("docs", ["index.html"]).unwrap()
+                                ))
+                            }),
+                        )),
+                ),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/a/b/c/test").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        assert_eq!(
+            body,
+            Bytes::from_static(b"/a/b/c/12345 https://docs.example.com/index.html")
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_scope_method_not_allowed() {
+        let srv = init_service(
+            App::new()
+                .method_not_allowed(true)
+                .service(
+                    web::scope("/app1")
+                        .route("/test", web::get().to(|| async { HttpResponse::Ok() })),
+                )
+                .service(
+                    web::scope("/app2")
+                        .method_not_allowed(false)
+                        .route("/test", web::get().to(|| async { HttpResponse::Ok() })),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/app1/test")
+            .method(Method::POST)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            resp.headers().get(header::ALLOW).unwrap(),
+            HeaderValue::from_static("GET, OPTIONS")
+        );
+
+        let req = TestRequest::with_uri("/app2/test")
+            .method(Method::POST)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
 }
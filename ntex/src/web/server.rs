@@ -1,4 +1,4 @@
-use std::{fmt, io, marker::PhantomData, net, sync::Arc, sync::Mutex};
+use std::{fmt, future::Future, io, marker::PhantomData, net, sync::Arc, sync::Mutex};
 
 #[cfg(feature = "openssl")]
 use tls_openssl::ssl::{AlpnError, SslAcceptor, SslAcceptorBuilder};
@@ -8,11 +8,13 @@ use tls_rustls::ServerConfig as RustlsServerConfig;
 use crate::http::{
     self, body::MessageBody, HttpService, KeepAlive, Request, Response, ResponseError,
 };
-use crate::server::{Server, ServerBuilder};
+use crate::server::{Pressure, PressureStats, Server, ServerBuilder, Shutdown};
 use crate::service::{map_config, IntoServiceFactory, ServiceFactory};
-use crate::{time::Seconds, util::PoolId};
+use crate::{time::Millis, time::Seconds, util::PoolId};
 
 use super::config::AppConfig;
+use super::info::TrustedProxies;
+use super::server_config::{HttpServerConfig, TlsFiles};
 
 struct Config {
     host: Option<String>,
@@ -21,7 +23,22 @@ struct Config {
     ssl_handshake_timeout: Seconds,
     headers_read_rate: Option<ReadRate>,
     payload_read_rate: Option<ReadRate>,
+    payload_min_rate: Option<(u32, Seconds)>,
+    headers_timeout: Seconds,
+    h1_strict: http::h1::Strict,
+    max_headers: usize,
+    max_header_size: usize,
+    max_headers_size: usize,
+    max_uri_length: usize,
+    max_requests: usize,
+    max_lifetime: Seconds,
+    alt_svc: Option<http::header::HeaderValue>,
+    max_payload_size: u64,
+    lazy_continue: bool,
+    h1_flush: http::h1::FlushStrategy,
+    trusted_proxies: Option<TrustedProxies>,
     pool: PoolId,
+    h2: bool,
 }
 
 #[derive(Default, Copy, Clone)]
@@ -44,6 +61,24 @@ impl Config {
         if let Some(hdrs) = self.payload_read_rate {
             svc_cfg.payload_read_rate(hdrs.timeout, hdrs.max_timeout, hdrs.rate);
         }
+        if let Some((rate, window)) = self.payload_min_rate {
+            svc_cfg.payload_min_rate(rate, window);
+        }
+        svc_cfg.headers_timeout(self.headers_timeout);
+        svc_cfg
+            .h1_strict(self.h1_strict)
+            .max_headers(self.max_headers)
+            .max_header_size(self.max_header_size)
+            .max_headers_size(self.max_headers_size)
+            .max_uri_length(self.max_uri_length)
+            .keepalive_max_requests(self.max_requests)
+            .keepalive_max_lifetime(self.max_lifetime)
+            .max_payload_size(self.max_payload_size)
+            .h1_lazy_continue(self.lazy_continue)
+            .h1_flush_strategy(self.h1_flush);
+        if let Some(ref value) = self.alt_svc {
+            svc_cfg.alt_svc(value.clone());
+        }
         svc_cfg
     }
 }
@@ -107,7 +142,22 @@ where
                     max_timeout: Seconds(13),
                 }),
                 payload_read_rate: None,
+                payload_min_rate: None,
+                headers_timeout: Seconds::ZERO,
+                h1_strict: http::h1::Strict::empty(),
+                max_headers: 96,
+                max_header_size: 0,
+                max_headers_size: 32_768,
+                max_uri_length: 0,
+                max_requests: 0,
+                max_lifetime: Seconds::ZERO,
+                alt_svc: None,
+                max_payload_size: 0,
+                lazy_continue: false,
+                h1_flush: http::h1::FlushStrategy::Coalesce,
+                trusted_proxies: None,
                 pool: PoolId::P0,
+                h2: true,
             })),
             backlog: 1024,
             builder: ServerBuilder::default(),
@@ -115,6 +165,113 @@ where
         }
     }
 
+    /// Create new http server with application factory and configuration.
+    ///
+    /// Binds all configured addresses, see [`HttpServerConfig`](super::HttpServerConfig).
+    ///
+    /// ```rust,no_run
+    /// use ntex::web::{self, App, HttpResponse, HttpServer, HttpServerConfig};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let cfg = HttpServerConfig::from_env("APP_")?;
+    ///
+    ///     HttpServer::from_config(
+    ///         || App::new().service(web::resource("/").to(|| async { HttpResponse::Ok() })),
+    ///         &cfg,
+    ///     )?
+    ///     .run()
+    ///     .await
+    /// }
+    /// ```
+    pub fn from_config(factory: F, cfg: &HttpServerConfig) -> io::Result<Self> {
+        Self::new(factory).apply_config(cfg)
+    }
+
+    /// Apply configuration and bind configured addresses.
+    pub fn apply_config(mut self, cfg: &HttpServerConfig) -> io::Result<Self> {
+        if let Some(num) = cfg.workers {
+            self = self.workers(num);
+        }
+        if let Some(backlog) = cfg.backlog {
+            self = self.backlog(backlog);
+        }
+        if let Some(num) = cfg.maxconn {
+            self = self.maxconn(num);
+        }
+        if let Some(num) = cfg.maxconnrate {
+            self = self.maxconnrate(num);
+        }
+        if let Some(secs) = cfg.keep_alive {
+            self = self.keep_alive(if secs == 0 {
+                KeepAlive::Disabled
+            } else {
+                KeepAlive::Timeout(Seconds(secs))
+            });
+        }
+        if let Some(num) = cfg.keepalive_max_requests {
+            self = self.keepalive_max_requests(num);
+        }
+        if let Some(secs) = cfg.keepalive_max_lifetime {
+            self = self.keepalive_max_lifetime(Seconds(secs));
+        }
+        if let Some(secs) = cfg.client_timeout {
+            self = self.client_timeout(Seconds(secs));
+        }
+        if let Some(secs) = cfg.headers_timeout {
+            self = self.headers_timeout(Seconds(secs));
+        }
+        if let Some(secs) = cfg.disconnect_timeout {
+            self = self.disconnect_timeout(Seconds(secs));
+        }
+        if let Some(secs) = cfg.ssl_handshake_timeout {
+            self = self.ssl_handshake_timeout(Seconds(secs));
+        }
+        if let Some(secs) = cfg.shutdown_timeout {
+            self = self.shutdown_timeout(Seconds(secs));
+        }
+        if let Some(num) = cfg.max_headers {
+            self = self.max_headers(num);
+        }
+        if let Some(size) = cfg.max_header_size {
+            self = self.max_header_size(size);
+        }
+        if let Some(size) = cfg.max_headers_size {
+            self = self.max_headers_size(size);
+        }
+        if let Some(size) = cfg.max_uri_length {
+            self = self.max_uri_length(size);
+        }
+        if let Some(size) = cfg.max_payload_size {
+            self = self.max_payload_size(size);
+        }
+        if let Some(enabled) = cfg.http2 {
+            self = self.http2(enabled);
+        }
+        if let Some(ref host) = cfg.server_hostname {
+            self = self.server_hostname(host);
+        }
+
+        for addr in &cfg.bind {
+            self = if let Some(ref tls) = cfg.tls {
+                self.bind_config_tls(addr, tls)?
+            } else {
+                self.bind(addr.as_str())?
+            };
+        }
+        Ok(self)
+    }
+
+    #[cfg(any(feature = "openssl", feature = "rustls"))]
+    fn bind_config_tls(self, addr: &str, tls: &TlsFiles) -> io::Result<Self> {
+        self.bind_tls(addr, tls.load()?)
+    }
+
+    #[cfg(not(any(feature = "openssl", feature = "rustls")))]
+    fn bind_config_tls(self, _: &str, tls: &TlsFiles) -> io::Result<Self> {
+        tls.load().map(|_| self)
+    }
+
     /// Set number of workers to start.
     ///
     /// By default http server uses number of available logical cpu as threads
@@ -170,6 +327,45 @@ where
         self
     }
 
+    /// Set max number of requests served per connection.
+    ///
+    /// Once the limit is reached, http/1 connection responds with
+    /// `Connection: close` header and http/2 connection sends `GOAWAY` frame.
+    /// It could be used for connections rebalancing behind L4 load balancers.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn keepalive_max_requests(self, num: usize) -> Self {
+        self.config.lock().unwrap().max_requests = num;
+        self
+    }
+
+    /// Set max lifetime of a connection.
+    ///
+    /// Once connection's lifetime is exceeded, it gets closed same way
+    /// as with `keepalive_max_requests()` limit.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn keepalive_max_lifetime(self, lifetime: Seconds) -> Self {
+        self.config.lock().unwrap().max_lifetime = lifetime;
+        self
+    }
+
+    /// Set granularity of io timers.
+    ///
+    /// Keep-alive and read timeouts of all connections of a worker are
+    /// tracked by a shared timer wheel, timeouts are rounded up to the
+    /// granularity. Coarse granularity reduces timer wakeups for large
+    /// number of idle keep-alive connections.
+    ///
+    /// Granularity is applied to each worker thread on worker start.
+    /// By default granularity is set to 1 second.
+    pub fn timer_granularity(self, granularity: Seconds) -> Self {
+        self.on_worker_start(move || async move {
+            crate::io::set_timer_granularity(granularity);
+            Ok::<_, io::Error>(())
+        })
+    }
+
     /// Set request read timeout in seconds.
     ///
     /// Defines a timeout for reading client request headers. If a client does not transmit
@@ -268,6 +464,134 @@ where
         self
     }
 
+    /// Set max time for receiving complete request head.
+    ///
+    /// Timeout starts when first bytes of the request are received and
+    /// is enforced regardless of headers read rate. Connection is closed
+    /// with `408 Request Timeout` response.
+    ///
+    /// To disable timeout set value to 0. By default timeout is disabled.
+    pub fn headers_timeout(self, timeout: Seconds) -> Self {
+        self.config.lock().unwrap().headers_timeout = timeout;
+        self
+    }
+
+    /// Set minimum transfer rate for request's payload, in bytes per second.
+    ///
+    /// Rate is checked every `window` period, requests with slower payload
+    /// transfer are terminated with `408 Request Timeout` response. Overrides
+    /// `payload_read_rate()` setting.
+    ///
+    /// To disable check set rate to 0. By default check is disabled.
+    pub fn payload_min_rate(self, rate: u32, window: Seconds) -> Self {
+        self.config.lock().unwrap().payload_min_rate = if rate != 0 && !window.is_zero() {
+            Some((rate, window))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Set max number of request headers.
+    ///
+    /// Requests with more headers get rejected with
+    /// 431 (Request Header Fields Too Large) error.
+    ///
+    /// By default max number of headers is set to 96.
+    pub fn max_headers(self, num: usize) -> Self {
+        self.config.lock().unwrap().max_headers = num;
+        self
+    }
+
+    /// Set max size of individual request header (name and value).
+    ///
+    /// Requests with larger headers get rejected with
+    /// 431 (Request Header Fields Too Large) error.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn max_header_size(self, size: usize) -> Self {
+        self.config.lock().unwrap().max_header_size = size;
+        self
+    }
+
+    /// Set max total size of request head, including request line.
+    ///
+    /// Requests with larger head get rejected with
+    /// 431 (Request Header Fields Too Large) error.
+    ///
+    /// By default max size is set to 32Kb.
+    pub fn max_headers_size(self, size: usize) -> Self {
+        self.config.lock().unwrap().max_headers_size = size;
+        self
+    }
+
+    /// Set max length of request uri.
+    ///
+    /// Requests with longer uri get rejected with 414 (URI Too Long) error.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn max_uri_length(self, size: usize) -> Self {
+        self.config.lock().unwrap().max_uri_length = size;
+        self
+    }
+
+    /// Advertise alternative services.
+    ///
+    /// Value is sent with `Alt-Svc` header in all responses, unless
+    /// response already contains `Alt-Svc` header. For example
+    /// `h3=":443"; ma=86400` advertises http/3 endpoint.
+    ///
+    /// By default alternative services are not advertised.
+    pub fn alt_svc(self, value: http::header::HeaderValue) -> Self {
+        self.config.lock().unwrap().alt_svc = Some(value);
+        self
+    }
+
+    /// Set max size of request payload.
+    ///
+    /// Requests with larger `Content-Length` get rejected with
+    /// 413 (Payload Too Large) error before payload is read.
+    ///
+    /// To disable limit set value to 0. By default limit is disabled.
+    pub fn max_payload_size(self, size: u64) -> Self {
+        self.config.lock().unwrap().max_payload_size = size;
+        self
+    }
+
+    /// Defer `100 Continue` response until handler reads request payload.
+    ///
+    /// Allows guards and middlewares to reject `Expect: 100-continue`
+    /// requests before client sends payload.
+    ///
+    /// By default lazy continue is disabled.
+    pub fn h1_lazy_continue(self, enabled: bool) -> Self {
+        self.config.lock().unwrap().lazy_continue = enabled;
+        self
+    }
+
+    /// Set response write flush strategy for http/1 connections.
+    ///
+    /// `FlushStrategy::Response` flushes each response before next pipelined
+    /// request is read, `FlushStrategy::Coalesce` batches responses of
+    /// pipelined requests.
+    ///
+    /// By default responses are coalesced.
+    pub fn h1_flush_strategy(self, strategy: http::h1::FlushStrategy) -> Self {
+        self.config.lock().unwrap().h1_flush = strategy;
+        self
+    }
+
+    /// Set strict parsing checks for http/1 requests.
+    ///
+    /// Requests that violate any of enabled checks get rejected
+    /// with 400 (Bad Request) error.
+    ///
+    /// By default strict checks are disabled.
+    pub fn h1_strict(self, checks: http::h1::Strict) -> Self {
+        self.config.lock().unwrap().h1_strict = checks;
+        self
+    }
+
     /// Set server host name.
     ///
     /// Host name is used by application router as a hostname for url generation.
@@ -280,6 +604,18 @@ where
         self
     }
 
+    /// Set trusted proxies configuration.
+    ///
+    /// Forwarding headers (`Forwarded`, `X-Forwarded-For`, `X-Forwarded-Proto`,
+    /// `X-Forwarded-Host`) are used by [ConnectionInfo](./dev/struct.ConnectionInfo.html)
+    /// only if request is received from trusted proxy.
+    ///
+    /// By default forwarding headers of any request are trusted.
+    pub fn trusted_proxies(self, proxies: TrustedProxies) -> Self {
+        self.config.lock().unwrap().trusted_proxies = Some(proxies);
+        self
+    }
+
     /// Stop ntex runtime when server get dropped.
     ///
     /// By default "stop runtime" is disabled.
@@ -308,6 +644,89 @@ where
         self
     }
 
+    /// Register background task.
+    ///
+    /// Task is started in each worker, it receives shutdown signal once
+    /// worker starts graceful shutdown. Workers wait for completion of
+    /// background tasks, up to shutdown timeout.
+    ///
+    /// ```rust,no_run
+    /// use ntex::web::{self, App, HttpResponse, HttpServer};
+    /// use ntex::time::{sleep, Seconds};
+    /// use ntex::util::{select, Either};
+    ///
+    /// #[ntex::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     HttpServer::new(
+    ///         || App::new().service(web::resource("/").to(|| async { HttpResponse::Ok() })))
+    ///         .background(|shutdown| async move {
+    ///             loop {
+    ///                 // refresh caches
+    ///                 let res = select(sleep(Seconds(10)), shutdown.stopped()).await;
+    ///                 if let Either::Right(_) = res {
+    ///                     break;
+    ///                 }
+    ///             }
+    ///         })
+    ///         .bind("127.0.0.1:59090")?
+    ///         .run()
+    ///         .await
+    /// }
+    /// ```
+    pub fn background<T, R>(mut self, f: T) -> Self
+    where
+        T: Fn(Shutdown) -> R + Send + Clone + 'static,
+        R: Future<Output = ()> + 'static,
+    {
+        self.builder = self.builder.background(f);
+        self
+    }
+
+    /// Register global background task.
+    ///
+    /// Task is started once, in the thread that runs the server. Server
+    /// waits for completion of global tasks before workers get stopped.
+    pub fn background_global<T, R>(mut self, f: T) -> Self
+    where
+        T: Fn(Shutdown) -> R + Send + Clone + 'static,
+        R: Future<Output = ()> + 'static,
+    {
+        self.builder = self.builder.background_global(f);
+        self
+    }
+
+    /// Register async worker initialization function.
+    ///
+    /// Function runs in each worker thread before application gets
+    /// constructed, worker does not accept connections until function
+    /// completes. If function fails, worker does not start.
+    ///
+    /// Use `App::state_factory_with()` for per-worker application state.
+    pub fn on_worker_start<T, R, E>(mut self, f: T) -> Self
+    where
+        T: Fn() -> R + Send + Clone + 'static,
+        R: Future<Output = Result<(), E>> + 'static,
+        E: fmt::Display + 'static,
+    {
+        self.builder = self.builder.on_worker_start(f);
+        self
+    }
+
+    /// Register worker pressure detector.
+    ///
+    /// Detector get called in each worker every `interval` with worker's
+    /// connections, in-flight requests and memory pools statistics.
+    /// Depending on returned pressure level, worker stops accepting new
+    /// connections, responds with *503 Service Unavailable* to new requests
+    /// and closes idle keep-alive connections.
+    pub fn pressure<F>(mut self, interval: Millis, detector: F) -> Self
+    where
+        F: Fn(&PressureStats) -> Pressure + Send + Clone + 'static,
+    {
+        self.builder = self.builder.pressure(interval, detector);
+        self
+    }
+
     /// Set memory pool.
     ///
     /// Use specified memory pool for memory allocations.
@@ -316,11 +735,35 @@ where
         self
     }
 
+    /// Enable or disable http/2 support.
+    ///
+    /// Setting is used for alpn negotiation of tls listeners and is
+    /// validated by `bind_tls()` and `bind_h2c()` methods, so it must be
+    /// set before binding listeners.
+    ///
+    /// By default http/2 is enabled.
+    pub fn http2(self, enabled: bool) -> Self {
+        self.config.lock().unwrap().h2 = enabled;
+        self
+    }
+
     /// Use listener for accepting incoming connection requests
     ///
     /// HttpServer does not change any configuration for TcpListener,
     /// it needs to be configured before passing it to listen() method.
-    pub fn listen(mut self, lst: net::TcpListener) -> io::Result<Self> {
+    pub fn listen(self, lst: net::TcpListener) -> io::Result<Self> {
+        self.listen_inner(lst, false)
+    }
+
+    /// Use listener for accepting incoming cleartext http/1 and http/2 requests
+    ///
+    /// Http/2 connections are detected by connection preface (h2c with
+    /// prior knowledge), other connections are handled as http/1.
+    pub fn listen_h2c(self, lst: net::TcpListener) -> io::Result<Self> {
+        self.listen_inner(lst, true)
+    }
+
+    fn listen_inner(mut self, lst: net::TcpListener, h2c: bool) -> io::Result<Self> {
         let cfg = self.config.clone();
         let factory = self.factory.clone();
         let addr = lst.local_addr().unwrap();
@@ -333,10 +776,13 @@ where
                         false,
                         addr,
                         c.host.clone().unwrap_or_else(|| format!("{}", addr)),
-                    );
+                    )
+                    .with_trusted_proxies(c.trusted_proxies.clone());
                     r.memory_pool(c.pool);
 
-                    HttpService::build_with_config(c.into_cfg())
+                    let mut svc_cfg = c.into_cfg();
+                    svc_cfg.h2c(h2c);
+                    HttpService::build_with_config(svc_cfg)
                         .finish(map_config(factory(), move |_| cfg.clone()))
                 })?;
         Ok(self)
@@ -351,7 +797,8 @@ where
         lst: net::TcpListener,
         builder: SslAcceptorBuilder,
     ) -> io::Result<Self> {
-        self.listen_ssl_inner(lst, openssl_acceptor(builder)?)
+        let h2 = self.config.lock().unwrap().h2;
+        self.listen_ssl_inner(lst, openssl_acceptor(builder, h2)?)
     }
 
     #[cfg(feature = "openssl")]
@@ -372,7 +819,8 @@ where
                         true,
                         addr,
                         c.host.clone().unwrap_or_else(|| format!("{}", addr)),
-                    );
+                    )
+                    .with_trusted_proxies(c.trusted_proxies.clone());
                     r.memory_pool(c.pool);
 
                     HttpService::build_with_config(c.into_cfg())
@@ -389,8 +837,9 @@ where
     pub fn listen_rustls(
         self,
         lst: net::TcpListener,
-        config: RustlsServerConfig,
+        mut config: RustlsServerConfig,
     ) -> io::Result<Self> {
+        config.alpn_protocols = alpn_protocols(self.config.lock().unwrap().h2);
         self.listen_rustls_inner(lst, config)
     }
 
@@ -413,7 +862,8 @@ where
                     true,
                     addr,
                     c.host.clone().unwrap_or_else(|| format!("{}", addr)),
-                );
+                )
+                .with_trusted_proxies(c.trusted_proxies.clone());
                 r.memory_pool(c.pool);
 
                 HttpService::build_with_config(c.into_cfg())
@@ -437,6 +887,27 @@ where
         Ok(self)
     }
 
+    /// The socket address to bind for cleartext http/1 and http/2 requests
+    ///
+    /// Http/2 connections are detected by connection preface (h2c with
+    /// prior knowledge). It is useful for internal services (for example,
+    /// grpc services behind service mesh) where tls is handled externally.
+    pub fn bind_h2c<A: net::ToSocketAddrs>(mut self, addr: A) -> io::Result<Self> {
+        if !self.config.lock().unwrap().h2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot bind h2c listener, http/2 is disabled",
+            ));
+        }
+        let sockets = self.bind2(addr)?;
+
+        for lst in sockets {
+            self = self.listen_h2c(lst)?;
+        }
+
+        Ok(self)
+    }
+
     fn bind2<A: net::ToSocketAddrs>(&self, addr: A) -> io::Result<Vec<net::TcpListener>> {
         let mut err = None;
         let mut succ = false;
@@ -478,7 +949,7 @@ where
         A: net::ToSocketAddrs,
     {
         let sockets = self.bind2(addr)?;
-        let acceptor = openssl_acceptor(builder)?;
+        let acceptor = openssl_acceptor(builder, self.config.lock().unwrap().h2)?;
 
         for lst in sockets {
             self = self.listen_ssl_inner(lst, acceptor.clone())?;
@@ -494,8 +965,9 @@ where
     pub fn bind_rustls<A: net::ToSocketAddrs>(
         mut self,
         addr: A,
-        config: RustlsServerConfig,
+        mut config: RustlsServerConfig,
     ) -> io::Result<Self> {
+        config.alpn_protocols = alpn_protocols(self.config.lock().unwrap().h2);
         let sockets = self.bind2(addr)?;
         for lst in sockets {
             self = self.listen_rustls_inner(lst, config.clone())?;
@@ -503,6 +975,44 @@ where
         Ok(self)
     }
 
+    #[cfg(any(feature = "openssl", feature = "rustls"))]
+    /// Start listening for incoming tls connections.
+    ///
+    /// Tls acceptor is selected by config type. Alpn protocols are
+    /// set according to server's http/2 setting, "h2" and "http/1.1"
+    /// if http/2 is enabled and "http/1.1" otherwise. Alpn protocols
+    /// that are already set in rustls config are validated against
+    /// http/2 setting instead.
+    pub fn bind_tls<A, T>(mut self, addr: A, config: T) -> io::Result<Self>
+    where
+        A: net::ToSocketAddrs,
+        T: Into<TlsConfig>,
+    {
+        let h2 = self.config.lock().unwrap().h2;
+
+        match config.into() {
+            #[cfg(feature = "openssl")]
+            TlsConfig::Openssl(builder) => {
+                let acceptor = openssl_acceptor(builder, h2)?;
+                for lst in self.bind2(addr)? {
+                    self = self.listen_ssl_inner(lst, acceptor.clone())?;
+                }
+            }
+            #[cfg(feature = "rustls")]
+            TlsConfig::Rustls(mut config) => {
+                if config.alpn_protocols.is_empty() {
+                    config.alpn_protocols = alpn_protocols(h2);
+                } else {
+                    check_alpn_protocols(&config.alpn_protocols, h2)?;
+                }
+                for lst in self.bind2(addr)? {
+                    self = self.listen_rustls_inner(lst, config.clone())?;
+                }
+            }
+        }
+        Ok(self)
+    }
+
     #[cfg(unix)]
     /// Start listening for unix domain connections on existing listener.
     ///
@@ -521,7 +1031,8 @@ where
                 false,
                 socket_addr,
                 c.host.clone().unwrap_or_else(|| format!("{}", socket_addr)),
-            );
+            )
+            .with_trusted_proxies(c.trusted_proxies.clone());
             r.memory_pool(c.pool);
 
             HttpService::build_with_config(c.into_cfg())
@@ -552,7 +1063,8 @@ where
                     false,
                     socket_addr,
                     c.host.clone().unwrap_or_else(|| format!("{}", socket_addr)),
-                );
+                )
+                .with_trusted_proxies(c.trusted_proxies.clone());
                 r.memory_pool(c.pool);
 
                 HttpService::build_with_config(c.into_cfg())
@@ -603,11 +1115,11 @@ where
 
 #[cfg(feature = "openssl")]
 /// Configure `SslAcceptorBuilder` with custom server flags.
-fn openssl_acceptor(mut builder: SslAcceptorBuilder) -> io::Result<SslAcceptor> {
-    builder.set_alpn_select_callback(|_, protos| {
+fn openssl_acceptor(mut builder: SslAcceptorBuilder, h2: bool) -> io::Result<SslAcceptor> {
+    builder.set_alpn_select_callback(move |_, protos| {
         const H2: &[u8] = b"\x02h2";
         const H11: &[u8] = b"\x08http/1.1";
-        if protos.windows(3).any(|window| window == H2) {
+        if h2 && protos.windows(3).any(|window| window == H2) {
             Ok(b"h2")
         } else if protos.windows(9).any(|window| window == H11) {
             Ok(b"http/1.1")
@@ -615,7 +1127,81 @@ fn openssl_acceptor(mut builder: SslAcceptorBuilder) -> io::Result<SslAcceptor>
             Err(AlpnError::NOACK)
         }
     });
-    builder.set_alpn_protos(b"\x08http/1.1\x02h2")?;
+    if h2 {
+        builder.set_alpn_protos(b"\x08http/1.1\x02h2")?;
+    } else {
+        builder.set_alpn_protos(b"\x08http/1.1")?;
+    }
 
     Ok(builder.build())
 }
+
+#[cfg(any(feature = "openssl", feature = "rustls"))]
+/// Tls configuration for `HttpServer::bind_tls()`
+pub enum TlsConfig {
+    #[cfg(feature = "openssl")]
+    /// Openssl acceptor builder
+    Openssl(SslAcceptorBuilder),
+    #[cfg(feature = "rustls")]
+    /// Rustls server config
+    Rustls(RustlsServerConfig),
+}
+
+#[cfg(feature = "openssl")]
+impl From<SslAcceptorBuilder> for TlsConfig {
+    fn from(builder: SslAcceptorBuilder) -> Self {
+        TlsConfig::Openssl(builder)
+    }
+}
+
+#[cfg(feature = "rustls")]
+impl From<RustlsServerConfig> for TlsConfig {
+    fn from(config: RustlsServerConfig) -> Self {
+        TlsConfig::Rustls(config)
+    }
+}
+
+#[cfg(any(feature = "openssl", feature = "rustls"))]
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "openssl")]
+            TlsConfig::Openssl(_) => write!(f, "TlsConfig::Openssl"),
+            #[cfg(feature = "rustls")]
+            TlsConfig::Rustls(_) => write!(f, "TlsConfig::Rustls"),
+        }
+    }
+}
+
+#[cfg(feature = "rustls")]
+/// Alpn protocols for server's http/2 setting
+fn alpn_protocols(h2: bool) -> Vec<Vec<u8>> {
+    if h2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    }
+}
+
+#[cfg(feature = "rustls")]
+/// Validate configured alpn protocols against server's http/2 setting
+fn check_alpn_protocols(protos: &[Vec<u8>], h2: bool) -> io::Result<()> {
+    let err = |msg: String| Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+
+    if let Some(p) = protos.iter().find(|p| *p != b"h2" && *p != b"http/1.1") {
+        return err(format!(
+            "Unsupported alpn protocol {:?}",
+            String::from_utf8_lossy(p)
+        ));
+    }
+    let has_h2 = protos.iter().any(|p| p == b"h2");
+    if h2 && !has_h2 {
+        err("Http/2 is enabled but \"h2\" alpn protocol is not set".to_string())
+    } else if !h2 && has_h2 {
+        err("Http/2 is disabled but \"h2\" alpn protocol is set".to_string())
+    } else if !protos.iter().any(|p| p == b"http/1.1") {
+        err("\"http/1.1\" alpn protocol is not set".to_string())
+    } else {
+        Ok(())
+    }
+}
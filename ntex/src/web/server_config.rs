@@ -0,0 +1,242 @@
+use std::{env, io, path::PathBuf, str::FromStr};
+
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+/// Http server configuration
+///
+/// Configuration could be deserialized from any serde supported format
+/// (toml, yaml, json, etc), and overridden with environment variables.
+/// Unset options keep http server defaults. Timeouts are in seconds.
+///
+/// ```toml
+/// bind = ["0.0.0.0:8443"]
+/// workers = 4
+/// keep_alive = 30
+/// max_payload_size = 1048576
+///
+/// [tls]
+/// cert = "/etc/app/cert.pem"
+/// key = "/etc/app/key.pem"
+/// ```
+///
+/// Configuration could be re-read and applied to new server instance
+/// on reload, for example on `SIGHUP` signal.
+pub struct HttpServerConfig {
+    /// Socket addresses to bind, `host:port`
+    pub bind: Vec<String>,
+    /// Number of workers
+    pub workers: Option<usize>,
+    /// Listen backlog
+    pub backlog: Option<i32>,
+    /// Max number of concurrent connections per worker
+    pub maxconn: Option<usize>,
+    /// Max number of concurrent tls handshakes per worker
+    pub maxconnrate: Option<usize>,
+    /// Keep-alive timeout, `0` disables keep-alive
+    pub keep_alive: Option<u16>,
+    /// Max number of requests per connection
+    pub keepalive_max_requests: Option<usize>,
+    /// Max lifetime of connection
+    pub keepalive_max_lifetime: Option<u16>,
+    /// Timeout for reading request headers
+    pub client_timeout: Option<u16>,
+    /// Max time for receiving complete request head
+    pub headers_timeout: Option<u16>,
+    /// Connection disconnect timeout
+    pub disconnect_timeout: Option<u16>,
+    /// Tls handshake timeout
+    pub ssl_handshake_timeout: Option<u16>,
+    /// Graceful shutdown timeout
+    pub shutdown_timeout: Option<u16>,
+    /// Max number of request headers
+    pub max_headers: Option<usize>,
+    /// Max size of one request header
+    pub max_header_size: Option<usize>,
+    /// Max size of request headers
+    pub max_headers_size: Option<usize>,
+    /// Max request uri length
+    pub max_uri_length: Option<usize>,
+    /// Max request payload size
+    pub max_payload_size: Option<u64>,
+    /// Enable http/2 support
+    pub http2: Option<bool>,
+    /// Server host name
+    pub server_hostname: Option<String>,
+    /// Tls certificate and private key, all addresses use tls if set
+    pub tls: Option<TlsFiles>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+/// Paths to pem encoded tls certificate chain and private key
+pub struct TlsFiles {
+    /// Certificate chain file
+    pub cert: PathBuf,
+    /// Private key file
+    pub key: PathBuf,
+}
+
+impl HttpServerConfig {
+    /// Load configuration from environment variables.
+    ///
+    /// Variable name is option name in upper case with `prefix`,
+    /// for example `APP_WORKERS` or `APP_KEEP_ALIVE` for `APP_` prefix.
+    /// `BIND` variable is a comma separated list of addresses,
+    /// tls files are set with `TLS_CERT` and `TLS_KEY` variables.
+    pub fn from_env(prefix: &str) -> io::Result<Self> {
+        Self::default().env(prefix)
+    }
+
+    /// Override configuration with environment variables.
+    ///
+    /// See `HttpServerConfig::from_env()` for variables names.
+    pub fn env(mut self, prefix: &str) -> io::Result<Self> {
+        let var = |name: &str| env::var(format!("{}{}", prefix, name)).ok();
+
+        if let Some(val) = var("BIND") {
+            self.bind = val
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        parse(prefix, "WORKERS", &mut self.workers)?;
+        parse(prefix, "BACKLOG", &mut self.backlog)?;
+        parse(prefix, "MAXCONN", &mut self.maxconn)?;
+        parse(prefix, "MAXCONNRATE", &mut self.maxconnrate)?;
+        parse(prefix, "KEEP_ALIVE", &mut self.keep_alive)?;
+        parse(
+            prefix,
+            "KEEPALIVE_MAX_REQUESTS",
+            &mut self.keepalive_max_requests,
+        )?;
+        parse(
+            prefix,
+            "KEEPALIVE_MAX_LIFETIME",
+            &mut self.keepalive_max_lifetime,
+        )?;
+        parse(prefix, "CLIENT_TIMEOUT", &mut self.client_timeout)?;
+        parse(prefix, "HEADERS_TIMEOUT", &mut self.headers_timeout)?;
+        parse(prefix, "DISCONNECT_TIMEOUT", &mut self.disconnect_timeout)?;
+        parse(
+            prefix,
+            "SSL_HANDSHAKE_TIMEOUT",
+            &mut self.ssl_handshake_timeout,
+        )?;
+        parse(prefix, "SHUTDOWN_TIMEOUT", &mut self.shutdown_timeout)?;
+        parse(prefix, "MAX_HEADERS", &mut self.max_headers)?;
+        parse(prefix, "MAX_HEADER_SIZE", &mut self.max_header_size)?;
+        parse(prefix, "MAX_HEADERS_SIZE", &mut self.max_headers_size)?;
+        parse(prefix, "MAX_URI_LENGTH", &mut self.max_uri_length)?;
+        parse(prefix, "MAX_PAYLOAD_SIZE", &mut self.max_payload_size)?;
+        parse(prefix, "HTTP2", &mut self.http2)?;
+        if let Some(val) = var("SERVER_HOSTNAME") {
+            self.server_hostname = Some(val);
+        }
+
+        match (var("TLS_CERT"), var("TLS_KEY")) {
+            (Some(cert), Some(key)) => {
+                self.tls = Some(TlsFiles {
+                    cert: cert.into(),
+                    key: key.into(),
+                })
+            }
+            (None, None) => (),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Both {0}TLS_CERT and {0}TLS_KEY must be set", prefix),
+                ))
+            }
+        }
+        Ok(self)
+    }
+}
+
+fn parse<T: FromStr>(prefix: &str, name: &str, val: &mut Option<T>) -> io::Result<()> {
+    let name = format!("{}{}", prefix, name);
+    if let Ok(s) = env::var(&name) {
+        *val = Some(s.trim().parse().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot parse {} environment variable: {:?}", name, s),
+            )
+        })?);
+    }
+    Ok(())
+}
+
+impl TlsFiles {
+    #[cfg(feature = "openssl")]
+    /// Load tls config from files
+    pub(super) fn load(&self) -> io::Result<super::TlsConfig> {
+        use tls_openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
+
+        let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
+        builder.set_private_key_file(&self.key, SslFiletype::PEM)?;
+        builder.set_certificate_chain_file(&self.cert)?;
+        Ok(builder.into())
+    }
+
+    #[cfg(all(feature = "rustls", not(feature = "openssl")))]
+    /// Load tls config from files
+    pub(super) fn load(&self) -> io::Result<super::TlsConfig> {
+        use tls_rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+
+        let map_err = |e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}", e));
+        let certs = CertificateDer::pem_file_iter(&self.cert)
+            .map_err(map_err)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(map_err)?;
+        let key = PrivateKeyDer::from_pem_file(&self.key).map_err(map_err)?;
+        let config = tls_rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Ok(config.into())
+    }
+
+    #[cfg(not(any(feature = "openssl", feature = "rustls")))]
+    /// Load tls config from files
+    pub(super) fn load(&self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Tls support requires openssl or rustls feature",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_env() {
+        let cfg: HttpServerConfig = serde_json::from_str(
+            r#"{"bind": ["127.0.0.1:8080"], "workers": 2, "keep_alive": 0}"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.bind, vec!["127.0.0.1:8080".to_string()]);
+        assert_eq!(cfg.workers, Some(2));
+        assert_eq!(cfg.keep_alive, Some(0));
+        assert_eq!(cfg.backlog, None);
+        assert!(serde_json::from_str::<HttpServerConfig>(r#"{"unknown": 1}"#).is_err());
+
+        env::set_var("NTEX_TEST_CFG_WORKERS", "4");
+        env::set_var("NTEX_TEST_CFG_BIND", "127.0.0.1:8081, 127.0.0.1:8082");
+        env::set_var("NTEX_TEST_CFG_HTTP2", "false");
+        let cfg = cfg.env("NTEX_TEST_CFG_").unwrap();
+        assert_eq!(cfg.workers, Some(4));
+        assert_eq!(cfg.keep_alive, Some(0));
+        assert_eq!(cfg.http2, Some(false));
+        assert_eq!(cfg.bind.len(), 2);
+
+        env::set_var("NTEX_TEST_CFG_BACKLOG", "none");
+        assert!(HttpServerConfig::from_env("NTEX_TEST_CFG_").is_err());
+
+        env::set_var("NTEX_TEST_ERR_TLS_CERT", "cert.pem");
+        assert!(HttpServerConfig::from_env("NTEX_TEST_ERR_").is_err());
+    }
+}
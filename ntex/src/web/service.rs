@@ -8,7 +8,8 @@ use super::config::AppConfig;
 use super::dev::insert_slash;
 use super::error::ErrorRenderer;
 use super::guard::{AllGuard, Guard};
-use super::{request::WebRequest, response::WebResponse, rmap::ResourceMap};
+use super::rmap::ResourceMap;
+use super::{introspect::ResourceInfo, request::WebRequest, response::WebResponse};
 
 pub trait WebServiceFactory<Err: ErrorRenderer> {
     fn register(self, config: &mut WebServiceConfig<Err>);
@@ -99,12 +100,14 @@ impl AppState {
 pub struct WebServiceConfig<Err: ErrorRenderer> {
     state: AppState,
     root: bool,
+    method_not_allowed: bool,
     default: Rc<HttpServiceFactory<Err>>,
     services: Vec<(
         ResourceDef,
         HttpServiceFactory<Err>,
         Option<Guards>,
         Option<Rc<ResourceMap>>,
+        Option<ResourceInfo>,
     )>,
 }
 
@@ -115,6 +118,7 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
             state,
             default,
             root: true,
+            method_not_allowed: false,
             services: Vec::new(),
         }
     }
@@ -135,6 +139,7 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
         HttpServiceFactory<Err>,
         Option<Guards>,
         Option<Rc<ResourceMap>>,
+        Option<ResourceInfo>,
     )> {
         self.services
     }
@@ -145,9 +150,19 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
             default: self.default.clone(),
             services: Vec::new(),
             root: false,
+            method_not_allowed: self.method_not_allowed,
         }
     }
 
+    /// Check if *405 Method Not Allowed* responses are enabled
+    pub fn method_not_allowed(&self) -> bool {
+        self.method_not_allowed
+    }
+
+    pub(crate) fn set_method_not_allowed(&mut self, enabled: bool) {
+        self.method_not_allowed = enabled;
+    }
+
     /// Service configuration
     pub fn config(&self) -> &AppConfig {
         self.state.config()
@@ -174,8 +189,20 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
                 InitError = (),
             > + 'static,
     {
-        self.services
-            .push((rdef, boxed::factory(factory.into_factory()), guards, nested));
+        self.services.push((
+            rdef,
+            boxed::factory(factory.into_factory()),
+            guards,
+            nested,
+            None,
+        ));
+    }
+
+    /// Set introspection information for last registered service
+    pub(crate) fn set_resource_info(&mut self, info: ResourceInfo) {
+        if let Some(item) = self.services.last_mut() {
+            item.4 = Some(info);
+        }
     }
 }
 
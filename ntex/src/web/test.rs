@@ -1,5 +1,7 @@
 //! Various helpers for ntex applications to use during testing.
-use std::{fmt, net, net::SocketAddr, rc::Rc, sync::mpsc, thread};
+use std::task::{Context, Poll};
+use std::{collections::VecDeque, fmt, io, net, net::SocketAddr, pin::Pin, rc::Rc};
+use std::{sync::mpsc, thread};
 
 #[cfg(feature = "cookie")]
 use coo_kie::Cookie;
@@ -15,7 +17,8 @@ use crate::http::{HttpService, Method, Payload, Request, StatusCode, Uri, Versio
 use crate::io::Sealed;
 use crate::router::{Path, ResourceDef};
 use crate::service::{
-    map_config, IntoService, IntoServiceFactory, Pipeline, Service, ServiceFactory,
+    map_config, IntoService, IntoServiceFactory, Middleware, Pipeline, Service,
+    ServiceFactory,
 };
 use crate::time::{sleep, Millis, Seconds};
 use crate::util::{stream_recv, Bytes, BytesMut, Extensions, Ready, Stream};
@@ -82,6 +85,34 @@ where
     srv.pipeline(AppConfig::default()).await.unwrap()
 }
 
+/// Creates middleware service wrapping provided service.
+///
+/// Helper for testing middlewares in isolation, without application.
+///
+/// ```rust
+/// use ntex::http::{header, StatusCode};
+/// use ntex::web::{middleware::DefaultHeaders, test};
+///
+/// #[ntex::test]
+/// async fn test_middleware() {
+///     let srv = test::init_middleware(
+///         DefaultHeaders::new().header(header::CONTENT_TYPE, "text/plain"),
+///         test::ok_service(),
+///     );
+///
+///     let req = test::TestRequest::default().to_srv_request();
+///     let resp = test::call_service(&srv, req).await;
+///     assert_eq!(resp.status(), StatusCode::OK);
+///     assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "text/plain");
+/// }
+/// ```
+pub fn init_middleware<M, S>(mw: M, service: S) -> Pipeline<M::Service>
+where
+    M: Middleware<S>,
+{
+    Pipeline::new(mw.create(service))
+}
+
 /// Calls service and waits for response future completion.
 ///
 /// ```rust
@@ -190,6 +221,27 @@ pub async fn read_body(mut res: WebResponse) -> Bytes {
     bytes.freeze()
 }
 
+/// Helper function that returns a response body of a WebResponse,
+/// body size is limited by `limit` bytes.
+///
+/// Returns `PayloadError::Overflow` if body is larger than limit.
+pub async fn read_body_limit(
+    mut res: WebResponse,
+    limit: usize,
+) -> Result<Bytes, PayloadError> {
+    let mut body = res.take_body();
+    let mut bytes = BytesMut::new();
+    while let Some(item) = stream_recv(&mut body).await {
+        let chunk =
+            item.map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        if bytes.len() + chunk.len() > limit {
+            return Err(PayloadError::Overflow);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes.freeze())
+}
+
 /// Reads response's body and combines it to a Bytes objects
 pub async fn load_stream<S, E>(mut stream: S) -> Result<Bytes, E>
 where
@@ -420,6 +472,24 @@ impl TestRequest {
         self
     }
 
+    /// Set request payload stream
+    pub fn set_payload_stream<S>(mut self, stream: S) -> Self
+    where
+        S: Stream<Item = Result<Bytes, PayloadError>> + 'static,
+    {
+        self.req.set_payload_stream(stream);
+        self
+    }
+
+    /// Set request payload, payload is delivered by provided chunks
+    pub fn set_payload_chunks<I, B>(self, chunks: I) -> Self
+    where
+        I: IntoIterator<Item = B>,
+        B: Into<Bytes>,
+    {
+        self.set_payload_stream(Chunks(chunks.into_iter().map(|b| b.into()).collect()))
+    }
+
     /// Serialize `data` to a URL encoded form and set it as the request payload. The `Content-Type`
     /// header is set to `application/x-www-form-urlencoded`.
     pub fn set_form<T: Serialize>(mut self, data: &T) -> Self {
@@ -516,6 +586,20 @@ impl TestRequest {
     }
 }
 
+/// Payload stream of predefined chunks
+struct Chunks(VecDeque<Bytes>);
+
+impl Stream for Chunks {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.0.pop_front().map(Ok))
+    }
+}
+
 /// Start test server with default configuration
 ///
 /// Test server is very simple server that simplify process of writing
@@ -721,7 +805,17 @@ where
                     .openssl(builder.build())
                     .finish()
             }
-            #[cfg(not(feature = "openssl"))]
+            #[cfg(all(not(feature = "openssl"), feature = "rustls"))]
+            {
+                Connector::default()
+                    .lifetime(Seconds::ZERO)
+                    .keep_alive(Seconds(30))
+                    .timeout(Millis(30_000))
+                    .disconnect_timeout(Seconds(5))
+                    .rustls(tls::rustls_connector())
+                    .finish()
+            }
+            #[cfg(not(any(feature = "openssl", feature = "rustls")))]
             {
                 Connector::default()
                     .lifetime(Seconds::ZERO)
@@ -828,6 +922,18 @@ impl TestServerConfig {
         self
     }
 
+    /// Start openssl server with ephemeral self-signed certificate
+    #[cfg(all(feature = "openssl", feature = "test-certs"))]
+    pub fn openssl_self_signed(self) -> Self {
+        self.openssl(TestCert::generate().openssl_acceptor())
+    }
+
+    /// Start rustls server with ephemeral self-signed certificate
+    #[cfg(all(feature = "rustls", feature = "test-certs"))]
+    pub fn rustls_self_signed(self) -> Self {
+        self.rustls(TestCert::generate().rustls_config())
+    }
+
     /// Set server client timeout in seconds for first request.
     pub fn client_timeout(mut self, val: Seconds) -> Self {
         self.client_timeout = val;
@@ -835,6 +941,157 @@ impl TestServerConfig {
     }
 }
 
+#[cfg(all(feature = "test-certs", any(feature = "openssl", feature = "rustls")))]
+#[derive(Clone)]
+/// Ephemeral self-signed certificate for `localhost` and `127.0.0.1`
+pub struct TestCert {
+    cert: Vec<u8>,
+    key: Vec<u8>,
+}
+
+#[cfg(all(feature = "test-certs", any(feature = "openssl", feature = "rustls")))]
+impl TestCert {
+    /// Generate new self-signed certificate
+    pub fn generate() -> Self {
+        let names = vec!["localhost".to_string(), "127.0.0.1".to_string()];
+        let cert = rcgen::generate_simple_self_signed(names)
+            .expect("Cannot generate test certificate");
+        TestCert {
+            cert: cert.cert.der().to_vec(),
+            key: cert.key_pair.serialize_der(),
+        }
+    }
+
+    /// Returns DER encoded certificate
+    pub fn cert_der(&self) -> &[u8] {
+        &self.cert
+    }
+
+    /// Returns PKCS#8 DER encoded private key
+    pub fn key_der(&self) -> &[u8] {
+        &self.key
+    }
+
+    #[cfg(feature = "openssl")]
+    /// Create openssl acceptor with `h2` and `http/1.1` alpn protocols
+    pub fn openssl_acceptor(&self) -> tls_openssl::ssl::SslAcceptor {
+        use tls_openssl::ssl::{AlpnError, SslAcceptor, SslMethod};
+        use tls_openssl::{pkey::PKey, x509::X509};
+
+        let cert = X509::from_der(&self.cert).unwrap();
+        let key = PKey::private_key_from_pkcs8(&self.key).unwrap();
+
+        let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+        builder.set_certificate(&cert).unwrap();
+        builder.set_private_key(&key).unwrap();
+        builder.set_alpn_select_callback(|_, protos| {
+            const H2: &[u8] = b"\x02h2";
+            const H11: &[u8] = b"\x08http/1.1";
+            if protos.windows(3).any(|window| window == H2) {
+                Ok(b"h2")
+            } else if protos.windows(9).any(|window| window == H11) {
+                Ok(b"http/1.1")
+            } else {
+                Err(AlpnError::NOACK)
+            }
+        });
+        builder
+            .set_alpn_protos(b"\x08http/1.1\x02h2")
+            .expect("Cannot contrust SslAcceptor");
+        builder.build()
+    }
+
+    #[cfg(feature = "rustls")]
+    /// Create rustls server config with `h2` and `http/1.1` alpn protocols
+    pub fn rustls_config(&self) -> tls_rustls::ServerConfig {
+        use tls_rustls::pki_types::{CertificateDer, PrivatePkcs8KeyDer};
+
+        let mut config = tls_rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![CertificateDer::from(self.cert.clone())],
+                PrivatePkcs8KeyDer::from(self.key.clone()).into(),
+            )
+            .unwrap();
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        config
+    }
+}
+
+#[cfg(all(feature = "test-certs", any(feature = "openssl", feature = "rustls")))]
+impl fmt::Debug for TestCert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestCert").finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(feature = "rustls", not(feature = "openssl")))]
+mod tls {
+    use std::sync::Arc;
+
+    use tls_rustls::client::danger::{
+        HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+    };
+    use tls_rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use tls_rustls::{ClientConfig, DigitallySignedStruct, Error, SignatureScheme};
+
+    /// Rustls client config that accepts any server certificate
+    pub(super) fn rustls_connector() -> ClientConfig {
+        ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerification))
+            .with_no_client_auth()
+    }
+
+    #[derive(Debug)]
+    struct NoVerification;
+
+    impl ServerCertVerifier for NoVerification {
+        fn verify_server_cert(
+            &self,
+            _: &CertificateDer<'_>,
+            _: &[CertificateDer<'_>],
+            _: &ServerName<'_>,
+            _: &[u8],
+            _: UnixTime,
+        ) -> Result<ServerCertVerified, Error> {
+            Ok(ServerCertVerified::assertion())
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            _: &[u8],
+            _: &CertificateDer<'_>,
+            _: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            Ok(HandshakeSignatureValid::assertion())
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            _: &[u8],
+            _: &CertificateDer<'_>,
+            _: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, Error> {
+            Ok(HandshakeSignatureValid::assertion())
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            vec![
+                SignatureScheme::ECDSA_NISTP256_SHA256,
+                SignatureScheme::ECDSA_NISTP384_SHA384,
+                SignatureScheme::ED25519,
+                SignatureScheme::RSA_PSS_SHA256,
+                SignatureScheme::RSA_PSS_SHA384,
+                SignatureScheme::RSA_PSS_SHA512,
+                SignatureScheme::RSA_PKCS1_SHA256,
+                SignatureScheme::RSA_PKCS1_SHA384,
+                SignatureScheme::RSA_PKCS1_SHA512,
+            ]
+        }
+    }
+}
+
 #[derive(Debug)]
 /// Test server controller
 pub struct TestServer {
@@ -902,6 +1159,13 @@ impl TestServer {
         self.client.request(method, path.as_ref())
     }
 
+    /// Returns http client configured for test server
+    ///
+    /// Client does not verify server certificate.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
     /// Load response's body
     pub async fn load_body(
         &self,
@@ -935,9 +1199,25 @@ impl TestServer {
                     .await
                     .map(|ws| ws.seal())
             }
-            #[cfg(not(feature = "openssl"))]
+            #[cfg(all(not(feature = "openssl"), feature = "rustls"))]
             {
-                panic!("openssl feature is required")
+                let mut config = tls::rustls_connector();
+                config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+                WsClient::build(self.url(path))
+                    .address(self.addr)
+                    .timeout(Seconds(30))
+                    .rustls(std::sync::Arc::new(config))
+                    .take()
+                    .finish()
+                    .unwrap()
+                    .connect()
+                    .await
+                    .map(|ws| ws.seal())
+            }
+            #[cfg(not(any(feature = "openssl", feature = "rustls")))]
+            {
+                panic!("openssl or rustls feature is required")
             }
         } else {
             WsClient::build(self.url(path))
@@ -1005,6 +1285,57 @@ mod tests {
         assert_eq!(res, &b""[..]);
     }
 
+    #[crate::rt_test]
+    async fn test_init_middleware() {
+        let srv = init_middleware(
+            web::middleware::DefaultHeaders::new().header(header::CONTENT_TYPE, "0001"),
+            default_service::<DefaultError>(StatusCode::CREATED),
+        );
+        let res = call_service(&srv, TestRequest::default().to_srv_request()).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers().get(header::CONTENT_TYPE).unwrap(), "0001");
+    }
+
+    #[crate::rt_test]
+    async fn test_payload_builders() {
+        let mut req = TestRequest::default()
+            .set_payload_chunks(vec!["chunk1", "chunk2"])
+            .to_srv_request();
+        let mut pl = req.take_payload();
+        assert_eq!(
+            stream_recv(&mut pl).await.unwrap().unwrap(),
+            Bytes::from_static(b"chunk1")
+        );
+        assert_eq!(
+            stream_recv(&mut pl).await.unwrap().unwrap(),
+            Bytes::from_static(b"chunk2")
+        );
+        assert!(stream_recv(&mut pl).await.is_none());
+
+        let (req, mut pl) = TestRequest::default()
+            .set_payload_stream(Chunks(VecDeque::from(vec![Bytes::from_static(b"test")])))
+            .to_http_parts();
+        let body: Bytes = from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(body, Bytes::from_static(b"test"));
+    }
+
+    #[crate::rt_test]
+    async fn test_read_body_limit() {
+        let req = TestRequest::default().to_srv_request();
+        let res = req.into_response(HttpResponse::Ok().body("0123456789"));
+        assert_eq!(
+            read_body_limit(res, 10).await.unwrap(),
+            Bytes::from_static(b"0123456789")
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let res = req.into_response(HttpResponse::Ok().body("0123456789"));
+        assert!(matches!(
+            read_body_limit(res, 5).await,
+            Err(PayloadError::Overflow)
+        ));
+    }
+
     #[crate::rt_test]
     async fn test_request_methods() {
         let app = init_service(
@@ -1162,6 +1493,55 @@ mod tests {
         assert!(res.status().is_success());
     }
 
+    #[cfg(all(feature = "openssl", feature = "test-certs"))]
+    #[crate::rt_test]
+    async fn test_server_openssl() {
+        let srv = server_with(config().openssl_self_signed(), || {
+            App::new().service(web::resource("/").to(|req: HttpRequest| async move {
+                HttpResponse::Ok().body(format!("{:?}", req.version()))
+            }))
+        });
+        assert!(srv.url("/").starts_with("https://"));
+
+        let mut res = srv.get("/").send().await.unwrap();
+        assert!(res.status().is_success());
+        assert_eq!(res.version(), Version::HTTP_2);
+        assert_eq!(res.body().await.unwrap(), Bytes::from_static(b"HTTP/2.0"));
+
+        let srv = server_with(config().openssl_self_signed().h1(), || {
+            App::new().service(web::resource("/").to(|| async { HttpResponse::Ok() }))
+        });
+        let res = srv
+            .client()
+            .get(srv.url("/").as_str())
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+        assert_eq!(res.version(), Version::HTTP_11);
+    }
+
+    #[cfg(all(feature = "rustls", feature = "test-certs"))]
+    #[crate::rt_test]
+    async fn test_server_rustls() {
+        let srv = server_with(config().rustls_self_signed().h2(), || {
+            App::new().service(web::resource("/").to(|| async { HttpResponse::Ok() }))
+        });
+        let res = srv.get("/").send().await.unwrap();
+        assert!(res.status().is_success());
+        assert_eq!(res.version(), Version::HTTP_2);
+    }
+
+    #[cfg(all(feature = "test-certs", any(feature = "openssl", feature = "rustls")))]
+    #[test]
+    fn test_cert() {
+        let cert = TestCert::generate();
+        assert!(!cert.cert_der().is_empty());
+        assert!(!cert.key_der().is_empty());
+        assert_ne!(cert.cert_der(), TestCert::generate().cert_der());
+        assert!(format!("{:?}", cert).contains("TestCert"));
+    }
+
     #[crate::rt_test]
     async fn test_server_state() {
         async fn handler(data: web::types::State<usize>) -> crate::http::ResponseBuilder {
@@ -0,0 +1,315 @@
+//! Authorization header extractors
+use std::fmt;
+
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+
+use crate::http::header::{HeaderMap, AUTHORIZATION};
+use crate::http::Payload;
+use crate::web::error::{AuthError, AuthErrorKind, ErrorRenderer};
+use crate::web::{FromRequest, HttpRequest, WebRequest, WebResponse};
+
+/// Authorization credentials of specific scheme
+pub trait Credentials: Sized {
+    /// Authentication scheme name, for example `Basic`
+    const SCHEME: &'static str;
+
+    /// Parse credentials from the `Authorization` header value
+    fn parse(value: &str) -> Result<Self, AuthErrorKind>;
+
+    /// Parse credentials from the request headers
+    fn from_headers(headers: &HeaderMap) -> Result<Self, AuthErrorKind> {
+        let value = headers
+            .get(&AUTHORIZATION)
+            .ok_or(AuthErrorKind::Missing)?
+            .to_str()
+            .map_err(|_| AuthErrorKind::Invalid)?;
+
+        let (scheme, value) = value.split_once(' ').unwrap_or((value, ""));
+        if scheme.eq_ignore_ascii_case(Self::SCHEME) {
+            Self::parse(value.trim())
+        } else {
+            Err(AuthErrorKind::InvalidScheme)
+        }
+    }
+
+    /// Extract credentials from the request
+    ///
+    /// Authentication realm is taken from `AuthConfig` app state.
+    fn extract(req: &HttpRequest) -> Result<Self, AuthError> {
+        Self::from_headers(req.headers())
+            .map_err(|kind| Self::auth_error(kind, req.app_state::<AuthConfig>()))
+    }
+
+    /// Create authentication error for this scheme
+    fn auth_error(kind: AuthErrorKind, cfg: Option<&AuthConfig>) -> AuthError {
+        let err = AuthError::new(Self::SCHEME, kind);
+        if let Some(realm) = cfg.and_then(|cfg| cfg.realm.as_ref()) {
+            err.realm(realm.as_str())
+        } else {
+            err
+        }
+    }
+
+    /// Create *401 Unauthorized* response for rejected credentials
+    fn reject<Err>(req: WebRequest<Err>) -> WebResponse
+    where
+        Err: ErrorRenderer,
+        Err::Container: From<AuthError>,
+    {
+        let err = Self::auth_error(AuthErrorKind::Rejected, req.app_state::<AuthConfig>());
+        req.error_response(err)
+    }
+}
+
+/// Authorization extractors configuration
+///
+/// ```rust
+/// use ntex::web::{self, types::{AuthConfig, BasicAuth}, App};
+///
+/// async fn index(auth: BasicAuth) -> String {
+///     format!("Hello, {}!", auth.user_id())
+/// }
+///
+/// fn main() {
+///     let app = App::new()
+///         .state(AuthConfig::default().realm("Restricted area"))
+///         .service(web::resource("/index.html").route(web::get().to(index)));
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AuthConfig {
+    realm: Option<String>,
+}
+
+impl AuthConfig {
+    /// Set authentication realm, it is used in `WWW-Authenticate` challenge.
+    pub fn realm<T: Into<String>>(mut self, realm: T) -> Self {
+        self.realm = Some(realm.into());
+        self
+    }
+}
+
+/// Extract credentials of `Basic` authorization scheme.
+///
+/// If credentials are missing or malformed, extractor generates
+/// *401 Unauthorized* response with `WWW-Authenticate` header.
+///
+/// ```rust
+/// use ntex::web::{self, types::BasicAuth, App};
+///
+/// async fn index(auth: BasicAuth) -> String {
+///     format!("Hello, {}!", auth.user_id())
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(web::resource("/index.html").to(index));
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct BasicAuth {
+    user_id: String,
+    password: Option<String>,
+}
+
+impl BasicAuth {
+    /// User identifier
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    /// Password
+    pub fn password(&self) -> Option<&str> {
+        self.password.as_deref()
+    }
+}
+
+impl Credentials for BasicAuth {
+    const SCHEME: &'static str = "Basic";
+
+    fn parse(value: &str) -> Result<Self, AuthErrorKind> {
+        let decoded = base64.decode(value).map_err(|_| AuthErrorKind::Invalid)?;
+        let decoded = String::from_utf8(decoded).map_err(|_| AuthErrorKind::Invalid)?;
+
+        let (user_id, password) = if let Some((user_id, password)) = decoded.split_once(':')
+        {
+            (user_id.to_string(), Some(password.to_string()))
+        } else {
+            (decoded, None)
+        };
+        Ok(BasicAuth { user_id, password })
+    }
+}
+
+impl fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("user_id", &self.user_id)
+            .field("password", &self.password.as_ref().map(|_| "******"))
+            .finish()
+    }
+}
+
+impl<Err: ErrorRenderer> FromRequest<Err> for BasicAuth {
+    type Error = AuthError;
+
+    #[inline]
+    async fn from_request(req: &HttpRequest, _: &mut Payload) -> Result<Self, Self::Error> {
+        Self::extract(req)
+    }
+}
+
+/// Extract token of `Bearer` authorization scheme.
+///
+/// If token is missing or malformed, extractor generates
+/// *401 Unauthorized* response with `WWW-Authenticate` header.
+///
+/// ```rust
+/// use ntex::web::{self, types::BearerAuth, App};
+///
+/// async fn index(auth: BearerAuth) -> String {
+///     format!("Token: {}", auth.token())
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(web::resource("/index.html").to(index));
+/// }
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct BearerAuth {
+    token: String,
+}
+
+impl BearerAuth {
+    /// Bearer token
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl Credentials for BearerAuth {
+    const SCHEME: &'static str = "Bearer";
+
+    fn parse(value: &str) -> Result<Self, AuthErrorKind> {
+        // token68 syntax
+        let valid = !value.is_empty()
+            && value.trim_end_matches('=').bytes().all(|b| {
+                b.is_ascii_alphanumeric()
+                    || matches!(b, b'-' | b'.' | b'_' | b'~' | b'+' | b'/')
+            });
+        if valid {
+            Ok(BearerAuth {
+                token: value.to_string(),
+            })
+        } else {
+            Err(AuthErrorKind::Invalid)
+        }
+    }
+}
+
+impl fmt::Debug for BearerAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BearerAuth")
+            .field("token", &"******")
+            .finish()
+    }
+}
+
+impl<Err: ErrorRenderer> FromRequest<Err> for BearerAuth {
+    type Error = AuthError;
+
+    #[inline]
+    async fn from_request(req: &HttpRequest, _: &mut Payload) -> Result<Self, Self::Error> {
+        Self::extract(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{header, StatusCode};
+    use crate::web::test::{call_service, init_service, TestRequest};
+    use crate::web::{self, App, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_basic_auth() {
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Basic dXNlcjpwYXNz")
+            .to_http_request();
+        let auth = BasicAuth::extract(&req).unwrap();
+        assert_eq!(auth.user_id(), "user");
+        assert_eq!(auth.password(), Some("pass"));
+        assert!(!format!("{:?}", auth).contains("pass\""));
+
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "basic dXNlcg==")
+            .to_http_request();
+        let auth = BasicAuth::extract(&req).unwrap();
+        assert_eq!(auth.user_id(), "user");
+        assert_eq!(auth.password(), None);
+
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Basic !!!")
+            .to_http_request();
+        let err = BasicAuth::extract(&req).unwrap_err();
+        assert_eq!(err.kind(), AuthErrorKind::Invalid);
+
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Bearer token")
+            .to_http_request();
+        let err = BasicAuth::extract(&req).unwrap_err();
+        assert_eq!(err.kind(), AuthErrorKind::InvalidScheme);
+        assert_eq!(err.challenge(), "Basic");
+    }
+
+    #[crate::rt_test]
+    async fn test_bearer_auth() {
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Bearer mF_9.B5f-4.1JqM")
+            .to_http_request();
+        let auth = BearerAuth::extract(&req).unwrap();
+        assert_eq!(auth.token(), "mF_9.B5f-4.1JqM");
+        assert!(!format!("{:?}", auth).contains("mF_9"));
+
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Bearer in valid")
+            .state(AuthConfig::default().realm("api"))
+            .to_http_request();
+        let err = BearerAuth::extract(&req).unwrap_err();
+        assert_eq!(err.kind(), AuthErrorKind::Invalid);
+        assert_eq!(
+            err.challenge(),
+            "Bearer realm=\"api\", error=\"invalid_token\""
+        );
+
+        let req = TestRequest::default().to_http_request();
+        let err = BearerAuth::extract(&req).unwrap_err();
+        assert_eq!(err.kind(), AuthErrorKind::Missing);
+        assert_eq!(err.challenge(), "Bearer");
+    }
+
+    #[crate::rt_test]
+    async fn test_extractor() {
+        let srv = init_service(
+            App::new()
+                .state(AuthConfig::default().realm("test"))
+                .service(web::resource("/").to(|auth: BasicAuth| async move {
+                    HttpResponse::Ok().body(auth.user_id().to_string())
+                })),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Basic dXNlcjpwYXNz")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::default().to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            resp.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+            "Basic realm=\"test\""
+        );
+    }
+}
@@ -0,0 +1,740 @@
+//! File download responders
+use std::io::{self, Read, Seek, SeekFrom};
+use std::task::{Context, Poll};
+use std::{
+    error::Error, fmt, fs::File, future::Future, path::Path, path::PathBuf, pin::Pin,
+};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::http::body::{Body, SizedStream};
+use crate::http::header::{self, HeaderValue};
+use crate::http::{Method, Response, StatusCode};
+use crate::util::{ready, BoxFuture, Bytes, BytesMut, Stream};
+use crate::web::error::ErrorRenderer;
+use crate::web::httprequest::HttpRequest;
+use crate::web::responder::Responder;
+
+/// RFC 5987 `attr-char` set
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+const CHUNK_SIZE: u64 = 65_536;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Content disposition type
+pub enum DispositionType {
+    /// Content is displayed by the browser
+    Inline,
+    /// Content is downloaded and saved locally
+    Attachment,
+}
+
+/// File responder
+///
+/// Streams file content from blocking thread pool or from async reader.
+/// Response contains `Content-Type` guessed from file extension,
+/// `Content-Disposition` with file name and supports single `Range` requests.
+///
+/// ```rust,no_run
+/// use ntex::web::{self, types::NamedFile};
+///
+/// async fn download() -> std::io::Result<NamedFile> {
+///     Ok(NamedFile::open("report.pdf")?.attachment())
+/// }
+/// ```
+pub struct NamedFile {
+    path: Option<PathBuf>,
+    source: Source,
+    len: u64,
+    filename: String,
+    content_type: mime::Mime,
+    disposition: DispositionType,
+}
+
+impl NamedFile {
+    /// Open file in read-only mode.
+    ///
+    /// Content type is guessed from file extension, text, image, audio
+    /// and video files are served inline, other files as attachments.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<NamedFile> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        let md = file.metadata()?;
+        if md.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::Other, "Path is a directory"));
+        }
+
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid path"))?;
+        let content_type = guess_mime(&path);
+
+        Ok(NamedFile {
+            filename,
+            disposition: disposition(&content_type),
+            content_type,
+            path: Some(path),
+            source: Source::File(file),
+            len: md.len(),
+        })
+    }
+
+    /// Create file responder from async reader.
+    ///
+    /// Reader must produce exactly `len` bytes. Content type is guessed
+    /// from file name, range requests are served by skipping reader's
+    /// content up to range start.
+    pub fn from_reader<T, R>(filename: T, len: u64, reader: R) -> NamedFile
+    where
+        T: Into<String>,
+        R: futures_io::AsyncRead + Unpin + 'static,
+    {
+        let filename = filename.into();
+        let content_type = guess_mime(Path::new(&filename));
+
+        NamedFile {
+            len,
+            filename,
+            disposition: disposition(&content_type),
+            content_type,
+            path: None,
+            source: Source::Reader(Box::new(reader)),
+        }
+    }
+
+    /// Returns reference to file path, reader based files do not have path
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns file size
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Check if file is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Set content type of the response
+    pub fn set_content_type(mut self, content_type: mime::Mime) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// Set file name for `Content-Disposition` header
+    pub fn set_filename<T: Into<String>>(mut self, filename: T) -> Self {
+        self.filename = filename.into();
+        self
+    }
+
+    /// Serve file as attachment
+    pub fn attachment(mut self) -> Self {
+        self.disposition = DispositionType::Attachment;
+        self
+    }
+
+    /// Serve file inline
+    pub fn inline(mut self) -> Self {
+        self.disposition = DispositionType::Inline;
+        self
+    }
+}
+
+impl fmt::Debug for NamedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NamedFile")
+            .field("path", &self.path)
+            .field("len", &self.len)
+            .field("content_type", &self.content_type)
+            .field("disposition", &self.disposition)
+            .finish()
+    }
+}
+
+impl<Err: ErrorRenderer> Responder<Err> for NamedFile {
+    async fn respond_to(self, req: &HttpRequest) -> Response {
+        let (status, offset, length) = match range(req, self.len) {
+            Ok(Some((offset, length))) => (StatusCode::PARTIAL_CONTENT, offset, length),
+            Ok(None) => (StatusCode::OK, 0, self.len),
+            Err(_) => {
+                return Response::build(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, format!("bytes */{}", self.len))
+                    .finish()
+            }
+        };
+
+        let mut res = Response::build(status);
+        res.header(header::CONTENT_TYPE, self.content_type.to_string())
+            .header(
+                header::CONTENT_DISPOSITION,
+                content_disposition(self.disposition, &self.filename),
+            )
+            .header(header::ACCEPT_RANGES, "bytes");
+        if status == StatusCode::PARTIAL_CONTENT {
+            res.header(
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", offset, offset + length - 1, self.len),
+            );
+        }
+
+        if req.method() == Method::HEAD || length == 0 {
+            res.no_chunking().content_length(length).finish()
+        } else {
+            match self.source {
+                Source::File(file) => res.body(Body::from_message(SizedStream::new(
+                    length,
+                    ChunkedReadFile {
+                        offset,
+                        size: length,
+                        file: Some(file),
+                        fut: None,
+                    },
+                ))),
+                Source::Reader(reader) => res.body(Body::from_message(SizedStream::new(
+                    length,
+                    ChunkedReader {
+                        reader,
+                        skip: offset,
+                        size: length,
+                        buf: BytesMut::new(),
+                    },
+                ))),
+            }
+        }
+    }
+}
+
+/// Content source of file responder
+enum Source {
+    File(File),
+    Reader(Box<dyn futures_io::AsyncRead + Unpin>),
+}
+
+/// Streaming attachment responder
+///
+/// Sends stream of bytes with `Content-Disposition: attachment` header.
+/// If size of the stream is known, response contains `Content-Length` header.
+///
+/// ```rust
+/// use ntex::{util::Bytes, web::types::Attachment};
+///
+/// async fn export() -> Attachment<ntex::channel::mpsc::Receiver<Result<Bytes, std::io::Error>>> {
+///     let (tx, rx) = ntex::channel::mpsc::channel();
+///     let _ = tx.send(Ok(Bytes::from_static(b"id,name\n")));
+///     Attachment::new("export.csv", rx).content_type(mime::TEXT_CSV)
+/// }
+/// ```
+pub struct Attachment<S> {
+    stream: S,
+    size: Option<u64>,
+    filename: String,
+    content_type: mime::Mime,
+}
+
+impl<S, E> Attachment<S>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin + 'static,
+    E: Error + 'static,
+{
+    /// Create attachment from stream of bytes
+    pub fn new<T: Into<String>>(filename: T, stream: S) -> Self {
+        let filename = filename.into();
+        Attachment {
+            stream,
+            size: None,
+            content_type: guess_mime(Path::new(&filename)),
+            filename,
+        }
+    }
+
+    /// Set total size of the stream
+    pub fn size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Set content type of the response
+    pub fn content_type(mut self, content_type: mime::Mime) -> Self {
+        self.content_type = content_type;
+        self
+    }
+}
+
+impl<S> fmt::Debug for Attachment<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Attachment")
+            .field("filename", &self.filename)
+            .field("size", &self.size)
+            .field("content_type", &self.content_type)
+            .finish()
+    }
+}
+
+impl<S, E, Err> Responder<Err> for Attachment<S>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin + 'static,
+    E: Error + 'static,
+    Err: ErrorRenderer,
+{
+    async fn respond_to(self, _: &HttpRequest) -> Response {
+        let mut res = Response::Ok();
+        res.header(header::CONTENT_TYPE, self.content_type.to_string())
+            .header(
+                header::CONTENT_DISPOSITION,
+                content_disposition(DispositionType::Attachment, &self.filename),
+            );
+
+        if let Some(size) = self.size {
+            res.body(Body::from_message(SizedStream::new(
+                size,
+                BoxedErr(self.stream),
+            )))
+        } else {
+            res.streaming(self.stream)
+        }
+    }
+}
+
+/// Stream adapter, boxes stream errors
+struct BoxedErr<S>(S);
+
+impl<S, E> Stream for BoxedErr<S>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: Error + 'static,
+{
+    type Item = Result<Bytes, Box<dyn Error>>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.0)
+            .poll_next(cx)
+            .map(|item| item.map(|res| res.map_err(|e| Box::new(e) as Box<dyn Error>)))
+    }
+}
+
+/// Stream of file chunks, reads file on blocking thread pool
+struct ChunkedReadFile {
+    size: u64,
+    offset: u64,
+    file: Option<File>,
+    fut: Option<BoxFuture<'static, io::Result<(File, Bytes)>>>,
+}
+
+impl Stream for ChunkedReadFile {
+    type Item = Result<Bytes, Box<dyn Error>>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        if let Some(ref mut fut) = self.fut {
+            return match Pin::new(fut).poll(cx) {
+                Poll::Ready(Ok((file, bytes))) => {
+                    self.fut.take();
+                    self.file = Some(file);
+                    self.offset += bytes.len() as u64;
+                    self.size -= bytes.len() as u64;
+                    Poll::Ready(Some(Ok(bytes)))
+                }
+                Poll::Ready(Err(e)) => {
+                    self.fut.take();
+                    Poll::Ready(Some(Err(Box::new(e))))
+                }
+                Poll::Pending => Poll::Pending,
+            };
+        }
+
+        if self.size == 0 {
+            return Poll::Ready(None);
+        }
+
+        let (offset, size) = (self.offset, self.size);
+        let mut file = self.file.take().expect("Use after completion");
+        self.fut = Some(Box::pin(async move {
+            crate::web::block(move || {
+                let max = std::cmp::min(size, CHUNK_SIZE) as usize;
+                let mut buf = Vec::with_capacity(max);
+                file.seek(SeekFrom::Start(offset))?;
+                let n = file.by_ref().take(max as u64).read_to_end(&mut buf)?;
+                if n == 0 {
+                    Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+                } else {
+                    Ok((file, Bytes::from(buf)))
+                }
+            })
+            .await
+            .map_err(|e| match e {
+                crate::http::error::BlockingError::Error(e) => e,
+                crate::http::error::BlockingError::Canceled => {
+                    io::Error::new(io::ErrorKind::Other, "Thread pool is gone")
+                }
+            })
+        }));
+        self.poll_next(cx)
+    }
+}
+
+/// Stream of reader chunks, skips content before range offset
+struct ChunkedReader {
+    reader: Box<dyn futures_io::AsyncRead + Unpin>,
+    skip: u64,
+    size: u64,
+    buf: BytesMut,
+}
+
+impl Stream for ChunkedReader {
+    type Item = Result<Bytes, Box<dyn Error>>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.as_mut().get_mut();
+        loop {
+            if this.size == 0 {
+                return Poll::Ready(None);
+            }
+
+            let max = std::cmp::min(
+                if this.skip > 0 { this.skip } else { this.size },
+                CHUNK_SIZE,
+            ) as usize;
+            // read buffer is reused between reads, only consumed part
+            // of the buffer gets re-initialized
+            if this.buf.len() < max {
+                this.buf.resize(CHUNK_SIZE as usize, 0);
+            }
+
+            match ready!(Pin::new(&mut this.reader).poll_read(cx, &mut this.buf[..max])) {
+                Ok(0) => {
+                    this.size = 0;
+                    return Poll::Ready(Some(Err(Box::new(io::Error::from(
+                        io::ErrorKind::UnexpectedEof,
+                    )))));
+                }
+                Ok(n) if this.skip > 0 => this.skip -= n as u64,
+                Ok(n) => {
+                    this.size -= n as u64;
+                    return Poll::Ready(Some(Ok(this.buf.split_to(n).freeze())));
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Poll::Ready(Some(Err(Box::new(e)))),
+            }
+        }
+    }
+}
+
+/// Parse single `Range` header, returns offset and length
+fn range(req: &HttpRequest, len: u64) -> Result<Option<(u64, u64)>, ()> {
+    let value = if let Some(value) = req.headers().get(header::RANGE) {
+        value.to_str().map_err(|_| ())?
+    } else {
+        return Ok(None);
+    };
+
+    // multiple ranges are not supported, serve full content
+    let spec = match value.trim().strip_prefix("bytes=") {
+        Some(spec) if !spec.contains(',') => spec.trim(),
+        _ => return Ok(None),
+    };
+    let (start, end) = spec.split_once('-').ok_or(())?;
+
+    let (offset, last) = if start.is_empty() {
+        // suffix range
+        let suffix: u64 = end.parse().map_err(|_| ())?;
+        if suffix == 0 {
+            return Err(());
+        }
+        (len.saturating_sub(suffix), len.checked_sub(1).ok_or(())?)
+    } else {
+        let start: u64 = start.parse().map_err(|_| ())?;
+        let last = if end.is_empty() {
+            len.checked_sub(1).ok_or(())?
+        } else {
+            std::cmp::min(end.parse::<u64>().map_err(|_| ())?, len.saturating_sub(1))
+        };
+        (start, last)
+    };
+
+    if offset >= len || offset > last {
+        Err(())
+    } else {
+        Ok(Some((offset, last - offset + 1)))
+    }
+}
+
+/// Build `Content-Disposition` header value, non-ascii file names
+/// are encoded according to RFC 5987
+fn content_disposition(tp: DispositionType, filename: &str) -> HeaderValue {
+    let tp = match tp {
+        DispositionType::Inline => "inline",
+        DispositionType::Attachment => "attachment",
+    };
+    let simple = filename
+        .chars()
+        .all(|c| c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\');
+
+    let value = if simple {
+        format!("{}; filename=\"{}\"", tp, filename)
+    } else {
+        let fallback: String = filename
+            .chars()
+            .map(|c| {
+                if c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!(
+            "{}; filename=\"{}\"; filename*=UTF-8''{}",
+            tp,
+            fallback,
+            utf8_percent_encode(filename, ATTR_CHAR)
+        )
+    };
+    HeaderValue::try_from(value).unwrap_or_else(|_| HeaderValue::from_static("attachment"))
+}
+
+/// Text and media files are served inline, other files as attachments
+fn disposition(content_type: &mime::Mime) -> DispositionType {
+    let tp = content_type.type_();
+    if tp == mime::TEXT || tp == mime::IMAGE || tp == mime::AUDIO || tp == mime::VIDEO {
+        DispositionType::Inline
+    } else {
+        DispositionType::Attachment
+    }
+}
+
+/// Guess mime type from file extension
+fn guess_mime(path: &Path) -> mime::Mime {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "html" | "htm" => mime::TEXT_HTML_UTF_8,
+        "txt" | "text" => mime::TEXT_PLAIN_UTF_8,
+        "css" => mime::TEXT_CSS_UTF_8,
+        "csv" => mime::TEXT_CSV_UTF_8,
+        "js" | "mjs" => mime::APPLICATION_JAVASCRIPT_UTF_8,
+        "json" => mime::APPLICATION_JSON,
+        "xml" => mime::TEXT_XML,
+        "pdf" => mime::APPLICATION_PDF,
+        "png" => mime::IMAGE_PNG,
+        "jpg" | "jpeg" => mime::IMAGE_JPEG,
+        "gif" => mime::IMAGE_GIF,
+        "bmp" => mime::IMAGE_BMP,
+        "svg" => mime::IMAGE_SVG,
+        "webp" => "image/webp".parse().unwrap(),
+        "ico" => "image/x-icon".parse().unwrap(),
+        "mp3" => "audio/mpeg".parse().unwrap(),
+        "ogg" => "audio/ogg".parse().unwrap(),
+        "wav" => "audio/wav".parse().unwrap(),
+        "mp4" => "video/mp4".parse().unwrap(),
+        "webm" => "video/webm".parse().unwrap(),
+        "woff" => mime::FONT_WOFF,
+        "woff2" => mime::FONT_WOFF2,
+        "wasm" => "application/wasm".parse().unwrap(),
+        "zip" => "application/zip".parse().unwrap(),
+        "gz" => "application/gzip".parse().unwrap(),
+        _ => mime::APPLICATION_OCTET_STREAM,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+
+    use super::*;
+    use crate::http::body::MessageBody;
+    use crate::web::test::{read_body, respond_to, TestRequest};
+
+    #[test]
+    fn test_range() {
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(range(&req, 100), Ok(None));
+
+        let check = |value: &str, len| {
+            let req = TestRequest::default()
+                .header(header::RANGE, value)
+                .to_http_request();
+            range(&req, len)
+        };
+        assert_eq!(check("bytes=0-9", 100), Ok(Some((0, 10))));
+        assert_eq!(check("bytes=90-", 100), Ok(Some((90, 10))));
+        assert_eq!(check("bytes=-10", 100), Ok(Some((90, 10))));
+        assert_eq!(check("bytes=-200", 100), Ok(Some((0, 100))));
+        assert_eq!(check("bytes=50-200", 100), Ok(Some((50, 50))));
+        assert_eq!(check("bytes=0-1,5-6", 100), Ok(None));
+        assert_eq!(check("items=0-1", 100), Ok(None));
+        assert_eq!(check("bytes=100-", 100), Err(()));
+        assert_eq!(check("bytes=10-5", 100), Err(()));
+        assert_eq!(check("bytes=-0", 100), Err(()));
+        assert_eq!(check("bytes=a-b", 100), Err(()));
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        assert_eq!(
+            content_disposition(DispositionType::Attachment, "file.txt"),
+            "attachment; filename=\"file.txt\""
+        );
+        assert_eq!(
+            content_disposition(DispositionType::Inline, "€ rates.txt"),
+            "inline; filename=\"_ rates.txt\"; filename*=UTF-8''%E2%82%AC%20rates.txt"
+        );
+    }
+
+    #[test]
+    fn test_guess_mime() {
+        assert_eq!(guess_mime(Path::new("a/b.PNG")), mime::IMAGE_PNG);
+        assert_eq!(guess_mime(Path::new("a.html")), mime::TEXT_HTML_UTF_8);
+        assert_eq!(guess_mime(Path::new("a")), mime::APPLICATION_OCTET_STREAM);
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_head() {
+        let file = NamedFile::open("Cargo.toml").unwrap();
+        assert!(!file.is_empty());
+        let len = file.len();
+
+        let req = TestRequest::default()
+            .method(Method::HEAD)
+            .header(header::RANGE, "bytes=0-9")
+            .to_http_request();
+        let resp = respond_to(file.attachment(), &req).await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            &format!("bytes 0-9/{}", len)
+        );
+        assert_eq!(
+            resp.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            "attachment; filename=\"Cargo.toml\""
+        );
+
+        let req = TestRequest::default()
+            .header(header::RANGE, format!("bytes={}-", len))
+            .to_http_request();
+        let file = NamedFile::open("Cargo.toml").unwrap();
+        let resp = respond_to(file, &req).await;
+        assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        assert!(NamedFile::open("src").is_err());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[crate::rt_test]
+    async fn test_named_file_range() {
+        let req = TestRequest::default()
+            .header(header::RANGE, "bytes=1-6")
+            .to_http_request();
+        let file = NamedFile::open("Cargo.toml").unwrap();
+        let resp = respond_to(file, &req).await;
+        let resp = crate::web::WebResponse::new(resp, req);
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"packag"));
+    }
+
+    struct Reader(&'static [u8]);
+
+    impl futures_io::AsyncRead for Reader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let n = std::cmp::min(buf.len(), self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_reader() {
+        let file = NamedFile::from_reader("report.txt", 11, Reader(b"hello world"));
+        assert!(file.path().is_none());
+        assert_eq!(file.len(), 11);
+
+        let req = TestRequest::default().to_http_request();
+        let resp = respond_to(file, &req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(
+            resp.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+            "inline; filename=\"report.txt\""
+        );
+        let resp = crate::web::WebResponse::new(resp, req);
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"hello world"));
+
+        let req = TestRequest::default()
+            .header(header::RANGE, "bytes=6-9")
+            .to_http_request();
+        let file = NamedFile::from_reader("report.txt", 11, Reader(b"hello world"));
+        let resp = respond_to(file, &req).await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        let resp = crate::web::WebResponse::new(resp, req);
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"worl"));
+
+        // reader is shorter than declared length
+        let req = TestRequest::default().to_http_request();
+        let file = NamedFile::from_reader("report.txt", 20, Reader(b"hello world"))
+            .set_content_type(mime::APPLICATION_OCTET_STREAM)
+            .attachment();
+        let mut resp = respond_to(file, &req).await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/octet-stream"
+        );
+        let mut body = resp.take_body();
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from_static(b"hello world"))
+        );
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx))
+            .await
+            .unwrap()
+            .is_err());
+    }
+
+    #[crate::rt_test]
+    async fn test_attachment() {
+        let (tx, rx) = crate::channel::mpsc::channel::<Result<Bytes, io::Error>>();
+        let _ = tx.send(Ok(Bytes::from_static(b"data")));
+        drop(tx);
+
+        let req = TestRequest::default().to_http_request();
+        let resp = respond_to(Attachment::new("data.csv", rx).size(4), &req).await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/csv; charset=utf-8"
+        );
+        let resp = crate::web::WebResponse::new(resp, req);
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"data"));
+    }
+}
@@ -10,6 +10,7 @@ use crate::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
 use crate::http::{HttpMessage, Payload, Response, StatusCode};
 use crate::util::{stream_recv, BoxFuture, BytesMut};
 use crate::web::error::{ErrorRenderer, UrlencodedError, WebResponseError};
+use crate::web::policy::body_limit;
 use crate::web::{FromRequest, HttpRequest, Responder};
 
 /// Form data helper (`application/x-www-form-urlencoded`)
@@ -109,6 +110,7 @@ where
             .app_state::<FormConfig>()
             .map(|c| c.limit)
             .unwrap_or(16384);
+        let limit = body_limit(req, limit);
 
         match UrlEncoded::new(req, payload).limit(limit).await {
             Err(e) => Err(e),
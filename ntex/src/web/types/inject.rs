@@ -0,0 +1,231 @@
+use std::{fmt, future::Future, ops::Deref, rc::Rc};
+
+use crate::http::Payload;
+use crate::util::BoxFuture;
+use crate::web::error::{ErrorRenderer, StateExtractorError};
+use crate::web::extract::FromRequest;
+use crate::web::httprequest::HttpRequest;
+
+/// Request-scoped dependency.
+///
+/// `Inject<T>` extractor provides value of type `T` that is constructed
+/// lazily, once per request, by provider registered with `App::provide()`
+/// method. All extractors of the same request share the same instance.
+/// Value is dropped, and provider's teardown hook is called, when request
+/// processing is completed.
+///
+/// If provider is not registered for a type, using `Inject<T>` extractor
+/// would cause *Internal Server Error* response.
+///
+/// ```rust
+/// use ntex::web::{self, types::Inject, types::Provider, App, HttpRequest};
+///
+/// struct Principal {
+///     name: String,
+/// }
+///
+/// async fn index(user: Inject<Principal>) -> String {
+///     format!("Hello {}", user.name)
+/// }
+///
+/// fn main() {
+///     let app = App::new()
+///         .provide(Provider::new(|req: HttpRequest| async move {
+///             let name = req
+///                 .headers()
+///                 .get("x-user")
+///                 .and_then(|v| v.to_str().ok())
+///                 .ok_or_else(|| web::error::ErrorUnauthorized("Unauthorized"))?;
+///             Ok::<_, web::Error>(Principal { name: name.to_string() })
+///         }))
+///         .service(web::resource("/index.html").route(web::get().to(index)));
+/// }
+/// ```
+pub struct Inject<T>(Rc<T>);
+
+impl<T> Inject<T> {
+    /// Get reference to inner value.
+    pub fn get_ref(&self) -> &T {
+        self.0.as_ref()
+    }
+}
+
+impl<T> Deref for Inject<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0.as_ref()
+    }
+}
+
+impl<T> Clone for Inject<T> {
+    fn clone(&self) -> Inject<T> {
+        Inject(self.0.clone())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Inject<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Inject").field(&self.0).finish()
+    }
+}
+
+impl<T: 'static, Err> FromRequest<Err> for Inject<T>
+where
+    Err: ErrorRenderer,
+    Err::Container: From<StateExtractorError>,
+{
+    type Error = Err::Container;
+
+    async fn from_request(req: &HttpRequest, _: &mut Payload) -> Result<Self, Self::Error> {
+        if let Some(scoped) = req.extensions().get::<Scoped<T>>() {
+            return Ok(Inject(scoped.value.clone()));
+        }
+
+        if let Some(provider) = req.app_state::<Provider<T, Err>>() {
+            let value = Rc::new((provider.ctor)(req.clone()).await?);
+            req.extensions_mut().insert(Scoped {
+                value: value.clone(),
+                teardown: provider.teardown.clone(),
+            });
+            Ok(Inject(value))
+        } else {
+            log::debug!(
+                "Failed to construct request-scoped value, provider is not registered. \
+                 Request path: {:?}",
+                req.path()
+            );
+            Err(StateExtractorError::NotConfigured.into())
+        }
+    }
+}
+
+/// Request-scoped dependency provider.
+///
+/// Provider constructs values for `Inject<T>` extractor. Provider
+/// is registered with `App::provide()` method.
+pub struct Provider<T, Err: ErrorRenderer> {
+    ctor: Box<dyn Fn(HttpRequest) -> BoxFuture<'static, Result<T, Err::Container>>>,
+    teardown: Option<Rc<dyn Fn(&T)>>,
+}
+
+impl<T: 'static, Err: ErrorRenderer> Provider<T, Err> {
+    /// Create provider from async constructor.
+    pub fn new<F, R, E>(ctor: F) -> Self
+    where
+        F: Fn(HttpRequest) -> R + 'static,
+        R: Future<Output = Result<T, E>> + 'static,
+        E: Into<Err::Container>,
+    {
+        Provider {
+            ctor: Box::new(move |req| {
+                let fut = ctor(req);
+                Box::pin(async move { fut.await.map_err(Into::into) })
+            }),
+            teardown: None,
+        }
+    }
+
+    /// Set teardown hook.
+    ///
+    /// Hook is called with constructed value when request processing is completed.
+    pub fn teardown<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&T) + 'static,
+    {
+        self.teardown = Some(Rc::new(f));
+        self
+    }
+}
+
+impl<T, Err: ErrorRenderer> fmt::Debug for Provider<T, Err> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Provider")
+            .field("type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+/// Request-scoped value, stored in request extensions
+struct Scoped<T> {
+    value: Rc<T>,
+    teardown: Option<Rc<dyn Fn(&T)>>,
+}
+
+impl<T> Drop for Scoped<T> {
+    fn drop(&mut self) {
+        if let Some(ref teardown) = self.teardown {
+            (*teardown)(&self.value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::web::test::{init_service, TestRequest};
+    use crate::web::{self, error, App, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_inject() {
+        let created = Rc::new(Cell::new(0));
+        let dropped = Rc::new(Cell::new(0));
+        let (c, d) = (created.clone(), dropped.clone());
+
+        let srv = init_service(
+            App::new()
+                .provide(
+                    Provider::new(move |req: HttpRequest| {
+                        c.set(c.get() + 1);
+                        async move {
+                            if req.path() == "/fail" {
+                                Err(error::ErrorUnauthorized("unauthorized").into())
+                            } else {
+                                Ok::<_, web::Error>(10usize)
+                            }
+                        }
+                    })
+                    .teardown(move |val| {
+                        assert_eq!(*val, 10);
+                        d.set(d.get() + 1);
+                    }),
+                )
+                .service(web::resource("/{name}").to(
+                    |v1: Inject<usize>, v2: Inject<usize>| async move {
+                        assert!(Rc::ptr_eq(&v1.0, &v2.0));
+                        assert_eq!(*v1.get_ref(), 10);
+                        HttpResponse::Ok()
+                    },
+                )),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/test").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        drop(resp);
+        assert_eq!(created.get(), 1);
+        assert_eq!(dropped.get(), 1);
+
+        let req = TestRequest::with_uri("/fail").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(created.get(), 2);
+        assert_eq!(dropped.get(), 1);
+    }
+
+    #[crate::rt_test]
+    async fn test_inject_not_configured() {
+        let srv = init_service(App::new().service(
+            web::resource("/").to(|_: Inject<usize>| async { HttpResponse::Ok() }),
+        ))
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -9,6 +9,7 @@ use crate::http::header::CONTENT_LENGTH;
 use crate::http::{HttpMessage, Payload, Response, StatusCode};
 use crate::util::{stream_recv, BoxFuture, BytesMut};
 use crate::web::error::{ErrorRenderer, JsonError, JsonPayloadError, WebResponseError};
+use crate::web::policy::body_limit;
 use crate::web::{FromRequest, HttpRequest, Responder};
 
 /// Json helper
@@ -169,6 +170,7 @@ where
             .app_state::<JsonConfig>()
             .map(|c| (c.limit, c.content_type.clone()))
             .unwrap_or((32768, None));
+        let limit = body_limit(req, limit);
 
         match JsonBody::new(req, payload, ctype).limit(limit).await {
             Err(e) => {
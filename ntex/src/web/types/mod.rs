@@ -1,15 +1,24 @@
 //! Extractor types
 
+mod auth;
+mod file;
 pub(in crate::web) mod form;
+mod inject;
 pub(in crate::web) mod json;
+mod ndjson;
 mod path;
 pub(in crate::web) mod payload;
+mod qs;
 mod query;
 pub(in crate::web) mod state;
 
+pub use self::auth::{AuthConfig, BasicAuth, BearerAuth, Credentials};
+pub use self::file::{Attachment, DispositionType, NamedFile};
 pub use self::form::{Form, FormConfig};
+pub use self::inject::{Inject, Provider};
 pub use self::json::{Json, JsonConfig};
+pub use self::ndjson::{NdJson, NdJsonErrorPolicy};
 pub use self::path::Path;
 pub use self::payload::{Payload, PayloadConfig};
-pub use self::query::Query;
+pub use self::query::{Query, QueryConfig, QueryMode};
 pub use self::state::State;
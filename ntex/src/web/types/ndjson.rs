@@ -0,0 +1,299 @@
+use std::{error::Error, fmt, io::Write, pin::Pin, task::Context, task::Poll};
+
+use serde::Serialize;
+
+use crate::http::body::{Body, BodySize, MessageBody};
+use crate::http::helpers::Writer;
+use crate::http::{Response, StatusCode};
+use crate::util::{Bytes, BytesMut, Stream};
+use crate::web::{ErrorRenderer, HttpRequest, Responder};
+
+const DEFAULT_BUFFER: usize = 8 * 1024;
+
+/// Serialization error handling policy for `NdJson` responder.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NdJsonErrorPolicy {
+    /// Terminate response stream, connection is closed.
+    Abort,
+    /// Skip item, error is logged.
+    Skip,
+    /// Write `{"error": "..."}` line and continue.
+    Emit,
+}
+
+/// Newline-delimited json responder.
+///
+/// `NdJson` serializes stream of items as newline-delimited json
+/// (`application/x-ndjson`), one json value per line. Items that are
+/// ready are accumulated in a buffer, buffer is flushed once it reaches
+/// configured size or once stream is not ready to produce next item.
+///
+/// ```rust
+/// use futures_util::stream;
+/// use ntex::{util::Stream, web::{self, types::NdJson, App}};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Record {
+///     id: usize,
+/// }
+///
+/// async fn export() -> NdJson<impl Stream<Item = Record>> {
+///     NdJson::new(stream::iter((0..1000).map(|id| Record { id })))
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(web::resource("/export").to(export));
+/// }
+/// ```
+pub struct NdJson<S> {
+    stream: S,
+    policy: NdJsonErrorPolicy,
+    buffer: usize,
+}
+
+impl<S, T> NdJson<S>
+where
+    S: Stream<Item = T> + 'static,
+    T: Serialize,
+{
+    /// Create `NdJson` responder for stream of items.
+    pub fn new(stream: S) -> Self {
+        NdJson {
+            stream,
+            policy: NdJsonErrorPolicy::Abort,
+            buffer: DEFAULT_BUFFER,
+        }
+    }
+
+    /// Set serialization error handling policy.
+    ///
+    /// By default response stream is terminated.
+    pub fn on_error(mut self, policy: NdJsonErrorPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set max size of the write buffer.
+    ///
+    /// Zero value flushes each item separately. By default buffer size is 8Kb.
+    pub fn buffer(mut self, size: usize) -> Self {
+        self.buffer = size;
+        self
+    }
+}
+
+impl<S> fmt::Debug for NdJson<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NdJson")
+            .field("stream", &std::any::type_name::<S>())
+            .field("policy", &self.policy)
+            .field("buffer", &self.buffer)
+            .finish()
+    }
+}
+
+impl<S, T, Err: ErrorRenderer> Responder<Err> for NdJson<S>
+where
+    S: Stream<Item = T> + 'static,
+    T: Serialize + 'static,
+{
+    async fn respond_to(self, _: &HttpRequest) -> Response {
+        Response::build(StatusCode::OK)
+            .content_type("application/x-ndjson")
+            .body(Body::from_message(NdJsonBody {
+                stream: Box::pin(self.stream),
+                policy: self.policy,
+                max_buffer: self.buffer,
+                buf: BytesMut::new(),
+                err: None,
+                eof: false,
+            }))
+    }
+}
+
+struct NdJsonBody<S> {
+    stream: Pin<Box<S>>,
+    policy: NdJsonErrorPolicy,
+    max_buffer: usize,
+    buf: BytesMut,
+    err: Option<serde_json::Error>,
+    eof: bool,
+}
+
+impl<S, T> MessageBody for NdJsonBody<S>
+where
+    S: Stream<Item = T>,
+    T: Serialize,
+{
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        loop {
+            if self.eof || self.err.is_some() {
+                return if !self.buf.is_empty() {
+                    Poll::Ready(Some(Ok(self.buf.split().freeze())))
+                } else if let Some(err) = self.err.take() {
+                    self.eof = true;
+                    Poll::Ready(Some(Err(err.into())))
+                } else {
+                    Poll::Ready(None)
+                };
+            }
+
+            match self.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    let pos = self.buf.len();
+                    if let Err(e) = serde_json::to_writer(Writer(&mut self.buf), &item) {
+                        self.buf.truncate(pos);
+                        match self.policy {
+                            NdJsonErrorPolicy::Abort => {
+                                log::error!("Cannot serialize ndjson item: {}", e);
+                                self.err = Some(e);
+                                continue;
+                            }
+                            NdJsonErrorPolicy::Skip => {
+                                log::warn!("Cannot serialize ndjson item, skipping: {}", e);
+                                continue;
+                            }
+                            NdJsonErrorPolicy::Emit => {
+                                let err = serde_json::json!({ "error": e.to_string() });
+                                let _ = serde_json::to_writer(Writer(&mut self.buf), &err);
+                            }
+                        }
+                    }
+                    let _ = Writer(&mut self.buf).write_all(b"\n");
+
+                    if self.buf.len() >= self.max_buffer {
+                        return Poll::Ready(Some(Ok(self.buf.split().freeze())));
+                    }
+                }
+                Poll::Ready(None) => self.eof = true,
+                Poll::Pending => {
+                    return if self.buf.is_empty() {
+                        Poll::Pending
+                    } else {
+                        Poll::Ready(Some(Ok(self.buf.split().freeze())))
+                    };
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, future::poll_fn};
+
+    use futures_util::stream;
+
+    use super::*;
+    use crate::http::header;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App};
+
+    #[derive(Serialize)]
+    struct Item {
+        id: usize,
+    }
+
+    struct Invalid;
+
+    impl Serialize for Invalid {
+        fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("invalid"))
+        }
+    }
+
+    fn items(invalid: bool) -> impl Stream<Item = Result<Item, Invalid>> {
+        stream::iter((0..3).map(move |id| {
+            if invalid && id == 1 {
+                Err(Invalid)
+            } else {
+                Ok(Item { id })
+            }
+        }))
+    }
+
+    #[crate::rt_test]
+    async fn test_ndjson() {
+        let srv = init_service(
+            App::new()
+                .service(web::resource("/").to(|| async {
+                    NdJson::new(stream::iter((0..3).map(|id| Item { id }))).buffer(0)
+                }))
+                .service(web::resource("/skip").to(|| async {
+                    NdJson::new(items(true)).on_error(NdJsonErrorPolicy::Skip)
+                }))
+                .service(web::resource("/emit").to(|| async {
+                    NdJson::new(items(true)).on_error(NdJsonErrorPolicy::Emit)
+                }))
+                .service(web::resource("/map").to(|| async {
+                    let mut map = HashMap::new();
+                    map.insert("key", 1);
+                    NdJson::new(stream::iter(vec![map]))
+                })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/x-ndjson"
+        );
+        let body = read_body(resp).await;
+        assert_eq!(
+            body,
+            Bytes::from_static(b"{\"id\":0}\n{\"id\":1}\n{\"id\":2}\n")
+        );
+
+        let req = TestRequest::with_uri("/skip").to_request();
+        let body = read_body(call_service(&srv, req).await).await;
+        assert_eq!(
+            body,
+            Bytes::from_static(b"{\"Ok\":{\"id\":0}}\n{\"Ok\":{\"id\":2}}\n")
+        );
+
+        let req = TestRequest::with_uri("/emit").to_request();
+        let body = read_body(call_service(&srv, req).await).await;
+        assert_eq!(
+            body,
+            Bytes::from_static(
+                b"{\"Ok\":{\"id\":0}}\n{\"error\":\"invalid\"}\n{\"Ok\":{\"id\":2}}\n"
+            )
+        );
+
+        let req = TestRequest::with_uri("/map").to_request();
+        let body = read_body(call_service(&srv, req).await).await;
+        assert_eq!(body, Bytes::from_static(b"{\"key\":1}\n"));
+    }
+
+    #[crate::rt_test]
+    async fn test_ndjson_abort() {
+        let mut body = NdJsonBody {
+            stream: Box::pin(items(true)),
+            policy: NdJsonErrorPolicy::Abort,
+            max_buffer: DEFAULT_BUFFER,
+            buf: BytesMut::new(),
+            err: None,
+            eof: false,
+        };
+        let chunk = poll_fn(|cx| body.poll_next_chunk(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk, Bytes::from_static(b"{\"Ok\":{\"id\":0}}\n"));
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx))
+            .await
+            .unwrap()
+            .is_err());
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+        assert!(format!("{:?}", NdJson::new(items(false))).contains("NdJson"));
+    }
+}
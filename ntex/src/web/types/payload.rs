@@ -7,6 +7,7 @@ use mime::Mime;
 use crate::http::{error, header, HttpMessage};
 use crate::util::{stream_recv, BoxFuture, Bytes, BytesMut, Stream};
 use crate::web::error::{ErrorRenderer, PayloadError};
+use crate::web::policy::body_limit;
 use crate::web::{FromRequest, HttpRequest};
 
 /// Payload extractor returns request 's payload stream.
@@ -159,7 +160,7 @@ impl<Err: ErrorRenderer> FromRequest<Err> for Bytes {
         if let Err(e) = cfg.check_mimetype(req) {
             Err(e)
         } else {
-            let limit = cfg.limit;
+            let limit = body_limit(req, cfg.limit);
             HttpMessageBody::new(req, payload).limit(limit).await
         }
     }
@@ -215,7 +216,7 @@ impl<Err: ErrorRenderer> FromRequest<Err> for String {
             Ok(enc) => enc,
             Err(e) => return Err(PayloadError::from(e)),
         };
-        let limit = cfg.limit;
+        let limit = body_limit(req, cfg.limit);
         let body = HttpMessageBody::new(req, payload).limit(limit).await?;
 
         if encoding == UTF_8 {
@@ -0,0 +1,379 @@
+//! Nested query string deserializer
+use std::borrow::Cow;
+
+use percent_encoding::percent_decode_str;
+use serde::de::{
+    self, value::Error, Deserializer as _, Error as _, IntoDeserializer, Visitor,
+};
+
+/// Deserialize query string with arrays and bracketed nesting support.
+///
+/// * `a[]=1&a[]=2` and `a=1&a=2` are deserialized as sequence
+/// * `a[b][c]=1` is deserialized as nested map
+/// * `a[0]=1&a[1]=2` is deserialized as sequence or map
+/// * `true`, `on`, `yes`, `1` and `false`, `off`, `no`, `0` are coerced to booleans
+/// * empty value is deserialized as `None` for optional fields
+pub(super) fn from_str<T: de::DeserializeOwned>(query: &str) -> Result<T, Error> {
+    T::deserialize(parse(query)?)
+}
+
+#[derive(Debug, PartialEq)]
+enum Node {
+    Str(String),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+fn decode(s: &str) -> Result<String, Error> {
+    let s = if s.contains('+') {
+        Cow::Owned(s.replace('+', " "))
+    } else {
+        Cow::Borrowed(s)
+    };
+    percent_decode_str(&s)
+        .decode_utf8()
+        .map(|s| s.into_owned())
+        .map_err(|e| Error::custom(e.to_string()))
+}
+
+fn parse(query: &str) -> Result<Node, Error> {
+    let mut root = Node::Map(Vec::new());
+
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = decode(key)?;
+        let value = decode(value)?;
+
+        let (name, rest) = if let Some(pos) = key.find('[') {
+            (&key[..pos], &key[pos..])
+        } else {
+            (key.as_str(), "")
+        };
+        if name.is_empty() {
+            return Err(Error::custom(format!("Invalid query key: {:?}", key)));
+        }
+
+        let mut segments = vec![name];
+        let mut rest = rest;
+        while let Some(tail) = rest.strip_prefix('[') {
+            if let Some(pos) = tail.find(']') {
+                segments.push(&tail[..pos]);
+                rest = &tail[pos + 1..];
+            } else {
+                return Err(Error::custom(format!("Invalid query key: {:?}", key)));
+            }
+        }
+        if !rest.is_empty() {
+            return Err(Error::custom(format!("Invalid query key: {:?}", key)));
+        }
+
+        insert(&mut root, &segments, value)?;
+    }
+    Ok(root)
+}
+
+fn insert(node: &mut Node, segments: &[&str], value: String) -> Result<(), Error> {
+    let (seg, rest) = (segments[0], &segments[1..]);
+
+    match node {
+        Node::Map(items) if !seg.is_empty() => {
+            if let Some(idx) = items.iter().position(|(k, _)| k == seg) {
+                let item = &mut items[idx].1;
+                if rest.is_empty() {
+                    // repeated key
+                    match item {
+                        Node::Str(prev) => {
+                            let prev = std::mem::take(prev);
+                            *item = Node::Seq(vec![Node::Str(prev), Node::Str(value)]);
+                        }
+                        Node::Seq(seq) => seq.push(Node::Str(value)),
+                        Node::Map(_) => {
+                            return Err(Error::custom(format!(
+                                "Conflicting query key: {}",
+                                seg
+                            )))
+                        }
+                    }
+                    Ok(())
+                } else {
+                    insert(item, rest, value)
+                }
+            } else if rest.is_empty() {
+                items.push((seg.to_string(), Node::Str(value)));
+                Ok(())
+            } else {
+                let mut item = if rest[0].is_empty() {
+                    Node::Seq(Vec::new())
+                } else {
+                    Node::Map(Vec::new())
+                };
+                insert(&mut item, rest, value)?;
+                items.push((seg.to_string(), item));
+                Ok(())
+            }
+        }
+        Node::Seq(seq) if seg.is_empty() => {
+            if rest.is_empty() {
+                seq.push(Node::Str(value));
+                return Ok(());
+            }
+
+            // a[][x]=1&a[][y]=2 populates same element until key repeats
+            if let Some(Node::Map(items)) = seq.last() {
+                if !items.iter().any(|(k, _)| k == rest[0]) {
+                    return insert(seq.last_mut().unwrap(), rest, value);
+                }
+            }
+            let mut item = Node::Map(Vec::new());
+            insert(&mut item, rest, value)?;
+            seq.push(item);
+            Ok(())
+        }
+        _ => Err(Error::custom(format!("Conflicting query key: {:?}", seg))),
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Node {
+    type Deserializer = Node;
+
+    fn into_deserializer(self) -> Node {
+        self
+    }
+}
+
+impl Node {
+    fn into_seq(self) -> Result<Vec<Node>, Error> {
+        match self {
+            Node::Str(s) => Ok(vec![Node::Str(s)]),
+            Node::Seq(seq) => Ok(seq),
+            Node::Map(items) => {
+                // indexed sequence, a[0]=1&a[1]=2
+                let mut items = items
+                    .into_iter()
+                    .map(|(k, v)| {
+                        k.parse::<usize>().map(|idx| (idx, v)).map_err(|_| {
+                            Error::custom(format!("Invalid sequence index: {}", k))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                items.sort_by_key(|(idx, _)| *idx);
+                Ok(items.into_iter().map(|(_, v)| v).collect())
+            }
+        }
+    }
+}
+
+macro_rules! deserialize_parse {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                Node::Str(s) => match s.parse() {
+                    Ok(val) => visitor.$visit(val),
+                    Err(e) => Err(Error::custom(format!("{}: {:?}", e, s))),
+                },
+                node => node.deserialize_any(visitor),
+            }
+        }
+    )*}
+}
+
+impl<'de> de::Deserializer<'de> for Node {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Node::Str(s) => visitor.visit_string(s),
+            Node::Seq(seq) => {
+                visitor.visit_seq(de::value::SeqDeserializer::new(seq.into_iter()))
+            }
+            Node::Map(items) => visitor.visit_map(de::value::MapDeserializer::new(
+                items.into_iter().map(|(k, v)| (Node::Str(k), v)),
+            )),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Node::Str(s) => match s.as_str() {
+                "true" | "on" | "yes" | "1" => visitor.visit_bool(true),
+                "false" | "off" | "no" | "0" | "" => visitor.visit_bool(false),
+                _ => Err(Error::custom(format!("Invalid boolean value: {:?}", s))),
+            },
+            node => node.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_parse! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Node::Str(ref s) if s.is_empty() => visitor.visit_none(),
+            node => visitor.visit_some(node),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(
+            self.into_seq()?.into_iter(),
+        ))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            Node::Str(s) => visitor.visit_enum(s.into_deserializer()),
+            _ => Err(Error::custom("Only unit enum variants are supported")),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf map struct identifier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Filter {
+        name: String,
+        active: bool,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Order {
+        Asc,
+        Desc,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Params {
+        ids: Vec<u32>,
+        tags: Vec<String>,
+        filter: Filter,
+        order: Order,
+        page: Option<u32>,
+        limit: Option<u32>,
+        #[serde(default)]
+        items: Vec<Filter>,
+    }
+
+    #[test]
+    fn test_nested() {
+        let params: Params = from_str(
+            "ids[]=1&ids[]=2&tags=a&tags=b+c&filter[name]=test%20name&filter[active]=on\
+             &order=desc&page=&limit=10&items[][name]=x&items[][active]=0\
+             &items[][name]=y&items[][active]=true",
+        )
+        .unwrap();
+        assert_eq!(
+            params,
+            Params {
+                ids: vec![1, 2],
+                tags: vec!["a".to_string(), "b c".to_string()],
+                filter: Filter {
+                    name: "test name".to_string(),
+                    active: true
+                },
+                order: Order::Desc,
+                page: None,
+                limit: Some(10),
+                items: vec![
+                    Filter {
+                        name: "x".to_string(),
+                        active: false
+                    },
+                    Filter {
+                        name: "y".to_string(),
+                        active: true
+                    }
+                ],
+            }
+        );
+
+        let map: HashMap<u32, Vec<bool>> = from_str("1[1]=no&1[0]=yes&2[]=1").unwrap();
+        assert_eq!(map[&1], vec![true, false]);
+        assert_eq!(map[&2], vec![true]);
+
+        let map: HashMap<String, HashMap<String, String>> =
+            from_str("a[b]=1&a[c]=2").unwrap();
+        assert_eq!(map["a"]["c"], "2");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(from_str::<HashMap<String, String>>("[a]=1").is_err());
+        assert!(from_str::<HashMap<String, String>>("a[b=1").is_err());
+        assert!(from_str::<HashMap<String, String>>("a[b]c=1").is_err());
+        assert!(from_str::<HashMap<String, String>>("a=1&a[b]=2").is_err());
+        assert!(from_str::<HashMap<String, u32>>("a=b").is_err());
+        assert!(from_str::<HashMap<String, bool>>("a=maybe").is_err());
+        assert!(from_str::<HashMap<String, Vec<u32>>>("a[x]=1").is_err());
+    }
+}
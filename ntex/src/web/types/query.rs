@@ -58,7 +58,22 @@ impl<T> Query<T> {
     where
         T: de::DeserializeOwned,
     {
-        serde_urlencoded::from_str::<T>(query_str)
+        Self::from_query_with(query_str, QueryMode::Flat)
+    }
+
+    /// Get query parameters from the path, with specified deserialization mode
+    pub fn from_query_with(
+        query_str: &str,
+        mode: QueryMode,
+    ) -> Result<Self, QueryPayloadError>
+    where
+        T: de::DeserializeOwned,
+    {
+        let result = match mode {
+            QueryMode::Flat => serde_urlencoded::from_str::<T>(query_str),
+            QueryMode::Nested => super::qs::from_str::<T>(query_str),
+        };
+        result
             .map(|val| Ok(Query(val)))
             .unwrap_or_else(move |e| Err(QueryPayloadError::Deserialize(e)))
     }
@@ -131,18 +146,73 @@ where
 
     #[inline]
     async fn from_request(req: &HttpRequest, _: &mut Payload) -> Result<Self, Self::Error> {
-        serde_urlencoded::from_str::<T>(req.query_string())
-            .map(|val| Ok(Query(val)))
-            .unwrap_or_else(move |e| {
-                let e = QueryPayloadError::Deserialize(e);
+        let mode = req
+            .app_state::<QueryConfig>()
+            .map(|c| c.mode)
+            .unwrap_or(QueryMode::Flat);
 
-                log::debug!(
-                    "Failed during Query extractor deserialization. \
+        Query::from_query_with(req.query_string(), mode).map_err(move |e| {
+            log::debug!(
+                "Failed during Query extractor deserialization. \
                      Request path: {:?}",
-                    req.path()
-                );
-                Err(e)
-            })
+                req.path()
+            );
+            e
+        })
+    }
+}
+
+/// Query string deserialization mode
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum QueryMode {
+    /// Flat `application/x-www-form-urlencoded` semantics
+    #[default]
+    Flat,
+    /// Arrays and bracketed nesting support
+    ///
+    /// * `a[]=1&a[]=2` and `a=1&a=2` are deserialized as sequence
+    /// * `a[b][c]=1` is deserialized as nested struct or map
+    /// * `a[0]=1&a[1]=2` is deserialized as sequence or map
+    /// * `true`, `on`, `yes`, `1` and `false`, `off`, `no`, `0` values are
+    ///   coerced to booleans
+    /// * empty value is deserialized as `None` for optional fields
+    Nested,
+}
+
+/// Query extractor configuration
+///
+/// ```rust
+/// use ntex::web::{self, types::{Query, QueryConfig, QueryMode}, App};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Filter {
+///     ids: Vec<u64>,
+///     active: bool,
+/// }
+///
+/// /// `/index.html?ids[]=1&ids[]=2&active=on`
+/// async fn index(filter: Query<Filter>) -> String {
+///     format!("Ids: {:?}", filter.ids)
+/// }
+///
+/// fn main() {
+///     let app = App::new()
+///         .state(QueryConfig::default().mode(QueryMode::Nested))
+///         .service(web::resource("/index.html").route(web::get().to(index)));
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct QueryConfig {
+    mode: QueryMode,
+}
+
+impl QueryConfig {
+    /// Set query string deserialization mode.
+    ///
+    /// By default flat form-urlencoded semantics is used.
+    pub fn mode(mut self, mode: QueryMode) -> Self {
+        self.mode = mode;
+        self
     }
 }
 
@@ -191,4 +261,31 @@ mod tests {
         let s = s.into_inner();
         assert_eq!(s.id, "test1");
     }
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Filter {
+        ids: Vec<u32>,
+        active: bool,
+    }
+
+    #[crate::rt_test]
+    async fn test_nested_mode() {
+        let uri = "/?ids[]=1&ids[]=2&active=on";
+        let req = TestRequest::with_uri(uri).to_srv_request();
+        let (req, mut pl) = req.into_parts();
+        assert!(from_request::<Query<Filter>>(&req, &mut pl).await.is_err());
+
+        let req = TestRequest::with_uri(uri)
+            .state(QueryConfig::default().mode(QueryMode::Nested))
+            .to_srv_request();
+        let (req, mut pl) = req.into_parts();
+        let s = from_request::<Query<Filter>>(&req, &mut pl).await.unwrap();
+        assert_eq!(s.ids, vec![1, 2]);
+        assert!(s.active);
+
+        let s =
+            Query::<Filter>::from_query_with("ids=3&active=0", QueryMode::Nested).unwrap();
+        assert_eq!(s.ids, vec![3]);
+        assert!(!s.active);
+    }
 }
@@ -0,0 +1,345 @@
+use std::{fmt, task::Context, task::Poll};
+
+use crate::http::{header, Request, Response, StatusCode};
+use crate::service::boxed::{self, BoxService, BoxServiceFactory};
+use crate::service::{IntoServiceFactory, Service, ServiceCtx, ServiceFactory};
+use crate::tls::Servername;
+
+use super::config::AppConfig;
+use super::error::ErrorRenderer;
+use super::error_default::DefaultError;
+use super::guard::host_matches;
+use super::response::WebResponse;
+
+type HttpService<Err: ErrorRenderer> = BoxService<Request, WebResponse, Err::Container>;
+type HttpNewService<Err: ErrorRenderer> =
+    BoxServiceFactory<AppConfig, Request, WebResponse, Err::Container, ()>;
+
+/// Virtual hosts.
+///
+/// Virtual hosts allows to serve multiple domains with one server. Each
+/// host is served by separate application, with isolated state, middlewares
+/// and default service. Application is selected by request host name before
+/// path routing. Host name is taken from TLS SNI extension, `Host` header or
+/// from request uri authority, for example http/2 `:authority` pseudo header.
+/// If SNI host name does not match request host, *421 Misdirected Request*
+/// response get returned.
+///
+/// Host pattern could contain wildcard, `*.example.com` matches any
+/// subdomain of `example.com`. Hosts are checked in registration order.
+/// If none of hosts matches, default application is used, or *404 Not Found*
+/// response get returned.
+///
+/// ```rust,no_run
+/// use ntex::web::{self, App, HttpResponse, HttpServer, VirtualHosts};
+///
+/// #[ntex::main]
+/// async fn main() -> std::io::Result<()> {
+///     HttpServer::new(|| {
+///         VirtualHosts::new()
+///             .host("api.example.com", App::new().service(
+///                 web::resource("/").to(|| async { HttpResponse::Ok().body("api") })))
+///             .host("*.example.com", App::new().service(
+///                 web::resource("/").to(|| async { HttpResponse::Ok().body("site") })))
+///     })
+///     .bind("127.0.0.1:8080")?
+///     .run()
+///     .await
+/// }
+/// ```
+pub struct VirtualHosts<Err: ErrorRenderer = DefaultError> {
+    hosts: Vec<(String, HttpNewService<Err>)>,
+    default: Option<HttpNewService<Err>>,
+}
+
+impl VirtualHosts<DefaultError> {
+    /// Create virtual hosts builder
+    pub fn new() -> Self {
+        VirtualHosts {
+            hosts: Vec::new(),
+            default: None,
+        }
+    }
+}
+
+impl Default for VirtualHosts<DefaultError> {
+    fn default() -> Self {
+        VirtualHosts::new()
+    }
+}
+
+impl<Err: ErrorRenderer> VirtualHosts<Err> {
+    /// Create virtual hosts builder with custom error renderer.
+    pub fn with(_: Err) -> Self {
+        VirtualHosts {
+            hosts: Vec::new(),
+            default: None,
+        }
+    }
+
+    /// Register application for host pattern.
+    pub fn host<F, U>(mut self, host: &str, app: F) -> Self
+    where
+        F: IntoServiceFactory<U, Request, AppConfig>,
+        U: ServiceFactory<
+                Request,
+                AppConfig,
+                Response = WebResponse,
+                Error = Err::Container,
+                InitError = (),
+            > + 'static,
+    {
+        self.hosts
+            .push((host.to_string(), boxed::factory(app.into_factory())));
+        self
+    }
+
+    /// Default application to be used if no matching host could be found.
+    pub fn default_app<F, U>(mut self, app: F) -> Self
+    where
+        F: IntoServiceFactory<U, Request, AppConfig>,
+        U: ServiceFactory<
+                Request,
+                AppConfig,
+                Response = WebResponse,
+                Error = Err::Container,
+                InitError = (),
+            > + 'static,
+    {
+        self.default = Some(boxed::factory(app.into_factory()));
+        self
+    }
+}
+
+impl<Err: ErrorRenderer> fmt::Debug for VirtualHosts<Err> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualHosts")
+            .field(
+                "hosts",
+                &self
+                    .hosts
+                    .iter()
+                    .map(|(h, _)| h.as_str())
+                    .collect::<Vec<_>>(),
+            )
+            .field("default", &self.default.is_some())
+            .finish()
+    }
+}
+
+impl<Err: ErrorRenderer> ServiceFactory<Request, AppConfig> for VirtualHosts<Err> {
+    type Response = Response;
+    type Error = Err::Container;
+    type InitError = ();
+    type Service = VirtualHostsService<Err>;
+
+    async fn create(&self, cfg: AppConfig) -> Result<Self::Service, Self::InitError> {
+        let mut hosts = Vec::with_capacity(self.hosts.len());
+        for (host, factory) in &self.hosts {
+            hosts.push((host.clone(), factory.create(cfg.clone()).await?));
+        }
+        let default = if let Some(ref default) = self.default {
+            Some(default.create(cfg).await?)
+        } else {
+            None
+        };
+        Ok(VirtualHostsService { hosts, default })
+    }
+}
+
+/// Virtual hosts service
+pub struct VirtualHostsService<Err: ErrorRenderer> {
+    hosts: Vec<(String, HttpService<Err>)>,
+    default: Option<HttpService<Err>>,
+}
+
+impl<Err: ErrorRenderer> VirtualHostsService<Err> {
+    fn services(&self) -> impl Iterator<Item = &HttpService<Err>> {
+        self.hosts
+            .iter()
+            .map(|(_, srv)| srv)
+            .chain(self.default.iter())
+    }
+}
+
+impl<Err: ErrorRenderer> Service<Request> for VirtualHostsService<Err> {
+    type Response = Response;
+    type Error = Err::Container;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let mut ready = true;
+        for srv in self.services() {
+            ready &= srv.poll_ready(cx)?.is_ready();
+        }
+        if ready {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn poll_shutdown(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut ready = true;
+        for srv in self.services() {
+            ready &= srv.poll_shutdown(cx).is_ready();
+        }
+        if ready {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    async fn call(
+        &self,
+        req: Request,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let sni = req
+            .io()
+            .and_then(|io| io.query::<Servername>().as_ref().map(|name| name.0.clone()));
+        let host = match select_host(sni.as_deref(), request_host(&req)) {
+            Ok(host) => host,
+            Err(()) => return Ok(Response::new(StatusCode::MISDIRECTED_REQUEST)),
+        };
+
+        let srv = host.and_then(|host| {
+            self.hosts
+                .iter()
+                .find(|(pattern, _)| host_matches(pattern, host))
+                .map(|(_, srv)| srv)
+        });
+
+        if let Some(srv) = srv.or(self.default.as_ref()) {
+            ctx.call(srv, req).await.map(|res| res.into())
+        } else {
+            Ok(Response::NotFound().finish())
+        }
+    }
+}
+
+/// Select host name, SNI host name is preferred but it must match request host
+fn select_host<'a>(
+    sni: Option<&'a str>,
+    host: Option<&'a str>,
+) -> Result<Option<&'a str>, ()> {
+    match (sni, host) {
+        (Some(sni), Some(host)) if !sni.eq_ignore_ascii_case(host) => Err(()),
+        (Some(sni), _) => Ok(Some(sni)),
+        (None, host) => Ok(host),
+    }
+}
+
+/// Request host name, without port
+fn request_host(req: &Request) -> Option<&str> {
+    if let Some(host) = req
+        .headers()
+        .get(&header::HOST)
+        .and_then(|h| h.to_str().ok())
+    {
+        if let Some(rest) = host.strip_prefix('[') {
+            // ipv6 address
+            rest.split(']').next()
+        } else {
+            host.split(':').next()
+        }
+    } else {
+        req.uri().host()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::web::test::TestRequest;
+    use crate::web::{self, middleware::DefaultHeaders, App, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_vhosts() {
+        let srv = VirtualHosts::new()
+            .host(
+                "api.example.com",
+                App::new()
+                    .wrap(DefaultHeaders::new().header("x-app", "api"))
+                    .service(
+                        web::resource("/").to(|| async { HttpResponse::Ok().body("api") }),
+                    ),
+            )
+            .host(
+                "*.example.com",
+                App::new().service(
+                    web::resource("/").to(|| async { HttpResponse::Ok().body("site") }),
+                ),
+            )
+            .pipeline(AppConfig::default())
+            .await
+            .unwrap();
+
+        let req = TestRequest::with_uri("/")
+            .header(header::HOST, "api.example.com:8080")
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("x-app").unwrap(), "api");
+
+        let req = TestRequest::with_uri("/")
+            .header(header::HOST, "www.example.com")
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().get("x-app").is_none());
+
+        let req = TestRequest::with_uri("/")
+            .header(header::HOST, "example.org")
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        assert!(
+            format!("{:?}", VirtualHosts::new().host("example.com", App::new()))
+                .contains("example.com")
+        );
+    }
+
+    #[test]
+    fn test_select_host() {
+        assert_eq!(select_host(None, None), Ok(None));
+        assert_eq!(select_host(None, Some("a.com")), Ok(Some("a.com")));
+        assert_eq!(select_host(Some("a.com"), None), Ok(Some("a.com")));
+        assert_eq!(select_host(Some("a.com"), Some("A.com")), Ok(Some("a.com")));
+        assert_eq!(select_host(Some("a.com"), Some("b.com")), Err(()));
+    }
+
+    #[crate::rt_test]
+    async fn test_vhosts_default() {
+        let srv = VirtualHosts::new()
+            .host("api.example.com", App::new())
+            .default_app(App::new().service(
+                web::resource("/").to(|| async { HttpResponse::Ok().body("default") }),
+            ))
+            .pipeline(AppConfig::default())
+            .await
+            .unwrap();
+
+        let req = TestRequest::with_uri("/")
+            .header(header::HOST, "example.org")
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/")
+            .header(header::HOST, "api.example.com")
+            .to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
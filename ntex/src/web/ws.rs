@@ -13,6 +13,50 @@ use crate::{io::DispatchItem, rt, time::Seconds, util::Either, util::Ready};
 
 /// Do websocket handshake and start websockets service.
 pub async fn start<T, F, Err>(req: HttpRequest, factory: F) -> Result<HttpResponse, Err>
+where
+    T: ServiceFactory<Frame, WsSink, Response = Option<Message>> + 'static,
+    T::Error: fmt::Debug,
+    F: IntoServiceFactory<T, Frame, WsSink>,
+    Err: From<T::InitError> + From<HandshakeError>,
+{
+    start_with_codec(req, ws::Codec::new(), factory).await
+}
+
+/// Do websocket handshake and start websockets service with custom codec.
+///
+/// Codec defines frame and message size limits, fragments reassembly
+/// and protocol strictness.
+///
+/// ```rust
+/// use ntex::web::{self, ws, HttpRequest, HttpResponse};
+///
+/// async fn ws_index(req: HttpRequest) -> Result<HttpResponse, web::Error> {
+///     let codec = ntex::ws::Codec::new()
+///         .max_message_size(1024 * 1024)
+///         .max_fragments(64)
+///         .reassemble(true);
+///
+///     ws::start_with_codec::<_, _, web::Error>(
+///         req,
+///         codec,
+///         ntex::service::fn_factory_with_config(|_sink| async {
+///             Ok::<_, web::Error>(ntex::fn_service(|frame: ws::Frame| async move {
+///                 // fragmented messages are delivered as complete text or binary frames
+///                 Ok::<_, std::io::Error>(match frame {
+///                     ws::Frame::Text(text) => Some(ws::Message::Binary(text)),
+///                     _ => None,
+///                 })
+///             }))
+///         }),
+///     )
+///     .await
+/// }
+/// ```
+pub async fn start_with_codec<T, F, Err>(
+    req: HttpRequest,
+    codec: ws::Codec,
+    factory: F,
+) -> Result<HttpResponse, Err>
 where
     T: ServiceFactory<Frame, WsSink, Response = Option<Message>> + 'static,
     T::Error: fmt::Debug,
@@ -61,7 +105,7 @@ where
         }
     });
 
-    start_with(req, factory).await
+    start_inner(req, codec, factory).await
 }
 
 /// Do websocket handshake and start websockets service.
@@ -69,6 +113,21 @@ pub async fn start_with<T, F, Err>(
     req: HttpRequest,
     factory: F,
 ) -> Result<HttpResponse, Err>
+where
+    T: ServiceFactory<DispatchItem<ws::Codec>, WsSink, Response = Option<Message>>
+        + 'static,
+    T::Error: fmt::Debug,
+    F: IntoServiceFactory<T, DispatchItem<ws::Codec>, WsSink>,
+    Err: From<T::InitError> + From<HandshakeError>,
+{
+    start_inner(req, ws::Codec::new(), factory).await
+}
+
+async fn start_inner<T, F, Err>(
+    req: HttpRequest,
+    codec: ws::Codec,
+    factory: F,
+) -> Result<HttpResponse, Err>
 where
     T: ServiceFactory<DispatchItem<ws::Codec>, WsSink, Response = Option<Message>>
         + 'static,
@@ -87,14 +146,13 @@ where
         .take_io()
         .ok_or(HandshakeError::NoWebsocketUpgrade)?;
     let io = item.0;
-    let codec = item.1;
+    let h1_codec = item.1;
 
-    io.encode(h1::Message::Item((res, BodySize::Empty)), &codec)
+    io.encode(h1::Message::Item((res, BodySize::Empty)), &h1_codec)
         .map_err(|_| HandshakeError::NoWebsocketUpgrade)?;
     log::trace!("Ws handshake verification completed for {:?}", req.path());
 
     // create sink
-    let codec = ws::Codec::new();
     let sink = WsSink::new(io.get_ref(), codec.clone());
 
     // create ws service
@@ -31,6 +31,7 @@ pub struct WsClient<F, T> {
     head: Rc<RequestHead>,
     addr: Option<net::SocketAddr>,
     max_size: usize,
+    message: ws::MessageConfig,
     server_mode: bool,
     timeout: Millis,
     extra_headers: RefCell<Option<HeaderMap>>,
@@ -54,6 +55,7 @@ struct Inner<F, T> {
     pub(crate) head: RequestHead,
     addr: Option<net::SocketAddr>,
     max_size: usize,
+    message: ws::MessageConfig,
     server_mode: bool,
     timeout: Millis,
     config: DispatcherConfig,
@@ -137,6 +139,7 @@ where
     pub async fn connect(&self) -> Result<WsConnection<F>, WsClientError> {
         let head = self.head.clone();
         let max_size = self.max_size;
+        let message = self.message;
         let server_mode = self.server_mode;
         let to = self.timeout;
         let mut headers = self.extra_headers.borrow_mut().take().unwrap_or_default();
@@ -251,7 +254,8 @@ where
                 ws::Codec::new().max_size(max_size)
             } else {
                 ws::Codec::new().max_size(max_size).client_mode()
-            },
+            }
+            .message_config(message),
             self.config.clone(),
         ))
     }
@@ -300,6 +304,7 @@ impl WsClientBuilder<Base, ()> {
                 connector: Connector::<Uri>::default().tag("WS-CLIENT"),
                 addr: None,
                 max_size: 65_536,
+                message: ws::MessageConfig::default(),
                 server_mode: false,
                 timeout: Millis(5_000),
                 _t: marker::PhantomData,
@@ -378,6 +383,37 @@ where
         self
     }
 
+    /// Set max message size
+    ///
+    /// Limit applies to the total size of fragmented messages.
+    /// By default message size is not limited.
+    pub fn max_message_size(&mut self, size: usize) -> &mut Self {
+        if let Some(parts) = parts(&mut self.inner, &self.err) {
+            parts.message.max_size = size;
+        }
+        self
+    }
+
+    /// Set max number of fragments in one message
+    ///
+    /// By default number of fragments is not limited.
+    pub fn max_fragments(&mut self, num: usize) -> &mut Self {
+        if let Some(parts) = parts(&mut self.inner, &self.err) {
+            parts.message.max_fragments = num;
+        }
+        self
+    }
+
+    /// Reassemble continuation frames into complete messages
+    ///
+    /// By default reassembly is disabled.
+    pub fn reassemble(&mut self) -> &mut Self {
+        if let Some(parts) = parts(&mut self.inner, &self.err) {
+            parts.message.reassemble = true;
+        }
+        self
+    }
+
     /// Disable payload masking. By default ws client masks frame payload.
     pub fn server_mode(&mut self) -> &mut Self {
         if let Some(parts) = parts(&mut self.inner, &self.err) {
@@ -516,6 +552,7 @@ where
                 head: inner.head,
                 addr: inner.addr,
                 max_size: inner.max_size,
+                message: inner.message,
                 server_mode: inner.server_mode,
                 timeout: inner.timeout,
                 config: inner.config,
@@ -640,6 +677,7 @@ where
             head: Rc::new(inner.head),
             addr: inner.addr,
             max_size: inner.max_size,
+            message: inner.message,
             server_mode: inner.server_mode,
             timeout: inner.timeout,
             config: inner.config,
@@ -945,6 +983,9 @@ mod tests {
         let mut builder = WsClient::build("http://localhost/")
             .origin("test-origin")
             .max_frame_size(100)
+            .max_message_size(1000)
+            .max_fragments(10)
+            .reassemble()
             .server_mode()
             .protocols(["v1", "v2"])
             .set_header_if_none(header::CONTENT_TYPE, "json")
@@ -956,6 +997,9 @@ mod tests {
             "test-origin"
         );
         assert_eq!(builder.inner.as_ref().unwrap().max_size, 100);
+        assert_eq!(builder.inner.as_ref().unwrap().message.max_size, 1000);
+        assert_eq!(builder.inner.as_ref().unwrap().message.max_fragments, 10);
+        assert!(builder.inner.as_ref().unwrap().message.reassemble);
         assert!(builder.inner.as_ref().unwrap().server_mode);
         assert_eq!(builder.protocols, Some("v1,v2".to_string()));
 
@@ -1,4 +1,4 @@
-use std::cell::Cell;
+use std::{cell::Cell, cell::RefCell, cmp};
 
 use crate::codec::{Decoder, Encoder};
 use crate::util::{ByteString, Bytes, BytesMut};
@@ -55,6 +55,19 @@ pub enum Item {
 pub struct Codec {
     flags: Cell<Flags>,
     max_size: usize,
+    strict: Strict,
+    utf8: Cell<([u8; 4], u8)>,
+    message: MessageConfig,
+    // number of fragments and size of current message
+    fragments: Cell<(usize, usize)>,
+    assembly: RefCell<Option<(bool, BytesMut)>>,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct MessageConfig {
+    pub(crate) max_size: usize,
+    pub(crate) max_fragments: usize,
+    pub(crate) reassemble: bool,
 }
 
 bitflags::bitflags! {
@@ -64,6 +77,27 @@ bitflags::bitflags! {
         const R_CONTINUATION = 0b0000_0010;
         const W_CONTINUATION = 0b0000_0100;
         const CLOSED         = 0b0000_1000;
+        const R_TEXT         = 0b0001_0000;
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+    /// Strict websocket protocol checks
+    ///
+    /// All checks are required by RFC 6455, permissive mode
+    /// could be useful for legacy peers.
+    pub struct Strict: u8 {
+        /// Validate utf8 encoding of text messages, fragmented messages
+        /// and close reasons included
+        const UTF8           = 0b0000_0001;
+        /// Reject frames with reserved bits set
+        const RESERVED_BITS  = 0b0000_0010;
+        /// Reject close frames with invalid close code
+        const CLOSE_CODE     = 0b0000_0100;
+        /// Reject fragmented control frames and data frames
+        /// interleaved with fragmented message
+        const FRAGMENTATION  = 0b0000_1000;
     }
 }
 
@@ -73,6 +107,11 @@ impl Codec {
         Codec {
             max_size: 65_536,
             flags: Cell::new(Flags::SERVER),
+            strict: Strict::empty(),
+            utf8: Cell::new(([0; 4], 0)),
+            message: MessageConfig::default(),
+            fragments: Cell::new((0, 0)),
+            assembly: RefCell::new(None),
         }
     }
 
@@ -84,6 +123,47 @@ impl Codec {
         self
     }
 
+    /// Set max message size
+    ///
+    /// Limit applies to the total size of fragmented messages.
+    /// By default message size is not limited, 0 disables limit.
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.message.max_size = size;
+        self
+    }
+
+    /// Set max number of fragments in one message
+    ///
+    /// By default number of fragments is not limited, 0 disables limit.
+    pub fn max_fragments(mut self, num: usize) -> Self {
+        self.message.max_fragments = num;
+        self
+    }
+
+    /// Reassemble continuation frames into complete messages
+    ///
+    /// Decoder emits `Frame::Text` and `Frame::Binary` for fragmented messages,
+    /// interleaved control frames are emitted as is. It is recommended to set
+    /// max message size along with reassembly. By default reassembly is disabled.
+    pub fn reassemble(mut self, enabled: bool) -> Self {
+        self.message.reassemble = enabled;
+        self
+    }
+
+    pub(crate) fn message_config(mut self, cfg: MessageConfig) -> Self {
+        self.message = cfg;
+        self
+    }
+
+    /// Set strict protocol checks
+    ///
+    /// By default all checks are disabled. `Strict::all()` enables
+    /// full RFC 6455 compliance.
+    pub fn strict(mut self, checks: Strict) -> Self {
+        self.strict = checks;
+        self
+    }
+
     /// Set decoder to client mode.
     ///
     /// By default decoder works in server mode.
@@ -108,6 +188,116 @@ impl Codec {
         flags.remove(f);
         self.flags.set(flags);
     }
+
+    fn check_text(
+        &self,
+        data: &Option<Bytes>,
+        first: bool,
+        fin: bool,
+    ) -> Result<(), ProtocolError> {
+        if self.strict.contains(Strict::UTF8) {
+            let data = data.as_ref().map(|b| b.as_ref()).unwrap_or_default();
+            if first && fin {
+                std::str::from_utf8(data)
+                    .map(|_| ())
+                    .map_err(|_| ProtocolError::InvalidUtf8)
+            } else {
+                if first {
+                    self.utf8.set(([0; 4], 0));
+                }
+                self.validate_utf8(data, fin)
+            }
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Incremental utf8 validation, incomplete code point is kept between frames
+    fn validate_utf8(&self, mut data: &[u8], fin: bool) -> Result<(), ProtocolError> {
+        let (mut tail, len) = self.utf8.get();
+        let mut len = len as usize;
+
+        if len > 0 {
+            let width = match tail[0] {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                _ => 4,
+            };
+            let n = cmp::min(width - len, data.len());
+            tail[len..len + n].copy_from_slice(&data[..n]);
+            len += n;
+            data = &data[n..];
+
+            match std::str::from_utf8(&tail[..len]) {
+                Ok(_) => (),
+                Err(e) if e.error_len().is_none() && !fin && data.is_empty() => {
+                    self.utf8.set((tail, len as u8));
+                    return Ok(());
+                }
+                Err(_) => return Err(ProtocolError::InvalidUtf8),
+            }
+        }
+
+        match std::str::from_utf8(data) {
+            Ok(_) => {
+                self.utf8.set((tail, 0));
+                Ok(())
+            }
+            Err(e) if e.error_len().is_none() && !fin => {
+                let rest = &data[e.valid_up_to()..];
+                tail[..rest.len()].copy_from_slice(rest);
+                self.utf8.set((tail, rest.len() as u8));
+                Ok(())
+            }
+            Err(_) => Err(ProtocolError::InvalidUtf8),
+        }
+    }
+
+    fn check_message(&self, frame: &Frame) -> Result<(), ProtocolError> {
+        let (count, size) = match frame {
+            Frame::Text(data) | Frame::Binary(data) => (0, data.len()),
+            Frame::Continuation(Item::FirstText(data) | Item::FirstBinary(data)) => {
+                (1, data.len())
+            }
+            Frame::Continuation(Item::Continue(data) | Item::Last(data)) => {
+                let (count, size) = self.fragments.get();
+                (count + 1, size + data.len())
+            }
+            _ => return Ok(()),
+        };
+        if count != 0 {
+            self.fragments.set((count, size));
+        }
+
+        if self.message.max_fragments != 0 && count > self.message.max_fragments {
+            Err(ProtocolError::TooManyFragments)
+        } else if self.message.max_size != 0 && size > self.message.max_size {
+            Err(ProtocolError::Overflow)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_close(&self, pl: &[u8]) -> Result<(), ProtocolError> {
+        if self.strict.contains(Strict::CLOSE_CODE) {
+            if pl.len() == 1 {
+                return Err(ProtocolError::InvalidLength(1));
+            }
+            if pl.len() >= 2 {
+                let code = u16::from_be_bytes([pl[0], pl[1]]);
+                if !matches!(code, 1000..=1003 | 1007..=1014 | 3000..=4999) {
+                    return Err(ProtocolError::InvalidCloseCode(code));
+                }
+            }
+        }
+        if self.strict.contains(Strict::UTF8)
+            && pl.len() > 2
+            && std::str::from_utf8(&pl[2..]).is_err()
+        {
+            return Err(ProtocolError::InvalidUtf8);
+        }
+        Ok(())
+    }
 }
 
 impl Default for Codec {
@@ -221,6 +411,58 @@ impl Decoder for Codec {
     type Error = ProtocolError;
 
     fn decode(&self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            let frame = if let Some(frame) = self.decode_frame(src)? {
+                frame
+            } else {
+                return Ok(None);
+            };
+            self.check_message(&frame)?;
+
+            if !self.message.reassemble {
+                return Ok(Some(frame));
+            }
+
+            // collect fragments, decoder must not stop while src contains complete frames
+            match frame {
+                Frame::Continuation(Item::FirstText(data)) => {
+                    *self.assembly.borrow_mut() = Some((true, BytesMut::from(&data[..])));
+                }
+                Frame::Continuation(Item::FirstBinary(data)) => {
+                    *self.assembly.borrow_mut() = Some((false, BytesMut::from(&data[..])));
+                }
+                Frame::Continuation(Item::Continue(data)) => {
+                    if let Some((_, ref mut buf)) = *self.assembly.borrow_mut() {
+                        buf.extend_from_slice(&data);
+                    }
+                }
+                Frame::Continuation(Item::Last(data)) => {
+                    if let Some((text, mut buf)) = self.assembly.borrow_mut().take() {
+                        buf.extend_from_slice(&data);
+                        let buf = buf.freeze();
+                        return Ok(Some(if text {
+                            Frame::Text(buf)
+                        } else {
+                            Frame::Binary(buf)
+                        }));
+                    }
+                }
+                frame => return Ok(Some(frame)),
+            }
+        }
+    }
+}
+
+impl Codec {
+    fn decode_frame(&self, src: &mut BytesMut) -> Result<Option<Frame>, ProtocolError> {
+        // reserved bits are used by extensions only, none is supported
+        if self.strict.contains(Strict::RESERVED_BITS)
+            && !src.is_empty()
+            && src[0] & 0x70 != 0
+        {
+            return Err(ProtocolError::ReservedBits);
+        }
+
         match Parser::parse(src, self.flags.get().contains(Flags::SERVER), self.max_size) {
             Ok(Some((finished, opcode, payload))) => {
                 // handle continuation
@@ -228,6 +470,9 @@ impl Decoder for Codec {
                     match opcode {
                         OpCode::Continue => {
                             if self.flags.get().contains(Flags::R_CONTINUATION) {
+                                if self.flags.get().contains(Flags::R_TEXT) {
+                                    self.check_text(&payload, false, false)?;
+                                }
                                 Ok(Some(Frame::Continuation(Item::Continue(
                                     payload.unwrap_or_else(Bytes::new),
                                 ))))
@@ -247,7 +492,8 @@ impl Decoder for Codec {
                         }
                         OpCode::Text => {
                             if !self.flags.get().contains(Flags::R_CONTINUATION) {
-                                self.insert_flags(Flags::R_CONTINUATION);
+                                self.check_text(&payload, true, false)?;
+                                self.insert_flags(Flags::R_CONTINUATION | Flags::R_TEXT);
                                 Ok(Some(Frame::Continuation(Item::FirstText(
                                     payload.unwrap_or_else(Bytes::new),
                                 ))))
@@ -255,6 +501,11 @@ impl Decoder for Codec {
                                 Err(ProtocolError::ContinuationStarted)
                             }
                         }
+                        OpCode::Ping | OpCode::Pong
+                            if self.strict.contains(Strict::FRAGMENTATION) =>
+                        {
+                            Err(ProtocolError::ContinuationFragment(opcode))
+                        }
                         OpCode::Ping => {
                             Ok(Some(Frame::Ping(payload.unwrap_or_else(Bytes::new))))
                         }
@@ -271,7 +522,10 @@ impl Decoder for Codec {
                     match opcode {
                         OpCode::Continue => {
                             if self.flags.get().contains(Flags::R_CONTINUATION) {
-                                self.remove_flags(Flags::R_CONTINUATION);
+                                if self.flags.get().contains(Flags::R_TEXT) {
+                                    self.check_text(&payload, false, true)?;
+                                }
+                                self.remove_flags(Flags::R_CONTINUATION | Flags::R_TEXT);
                                 Ok(Some(Frame::Continuation(Item::Last(
                                     payload.unwrap_or_else(Bytes::new),
                                 ))))
@@ -282,6 +536,7 @@ impl Decoder for Codec {
                         OpCode::Bad => Err(ProtocolError::BadOpCode),
                         OpCode::Close => {
                             if let Some(ref pl) = payload {
+                                self.check_close(pl)?;
                                 let close_reason = Parser::parse_close_payload(pl);
                                 Ok(Some(Frame::Close(close_reason)))
                             } else {
@@ -294,10 +549,17 @@ impl Decoder for Codec {
                         OpCode::Pong => {
                             Ok(Some(Frame::Pong(payload.unwrap_or_else(Bytes::new))))
                         }
+                        OpCode::Text | OpCode::Binary
+                            if self.strict.contains(Strict::FRAGMENTATION)
+                                && self.flags.get().contains(Flags::R_CONTINUATION) =>
+                        {
+                            Err(ProtocolError::ContinuationStarted)
+                        }
                         OpCode::Binary => {
                             Ok(Some(Frame::Binary(payload.unwrap_or_else(Bytes::new))))
                         }
                         OpCode::Text => {
+                            self.check_text(&payload, true, true)?;
                             Ok(Some(Frame::Text(payload.unwrap_or_else(Bytes::new))))
                         }
                     }
@@ -308,3 +570,191 @@ impl Decoder for Codec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(op: OpCode, fin: bool, data: &[u8]) -> BytesMut {
+        let mut buf = BytesMut::new();
+        Parser::write_message(&mut buf, data, op, fin, true);
+        buf
+    }
+
+    #[test]
+    fn test_strict_utf8() {
+        let codec = Codec::new();
+        let mut buf = frame(OpCode::Text, true, b"\xff");
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Text(Bytes::from_static(b"\xff")))
+        );
+
+        let codec = Codec::new().strict(Strict::UTF8);
+        let mut buf = frame(OpCode::Text, true, b"\xff");
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+
+        // code point split between fragments
+        let codec = Codec::new().strict(Strict::UTF8);
+        let text = "κόσμε".as_bytes();
+        let mut buf = frame(OpCode::Text, false, &text[..1]);
+        buf.extend_from_slice(&frame(OpCode::Continue, false, &text[1..4]));
+        buf.extend_from_slice(&frame(OpCode::Continue, false, b""));
+        buf.extend_from_slice(&frame(OpCode::Continue, true, &text[4..]));
+        for _ in 0..4 {
+            assert!(codec.decode(&mut buf).unwrap().is_some());
+        }
+
+        // incomplete code point at the end of message
+        let mut buf = frame(OpCode::Text, false, b"a");
+        buf.extend_from_slice(&frame(OpCode::Continue, true, &text[..1]));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+
+        // binary messages are not validated
+        let codec = Codec::new().strict(Strict::UTF8);
+        let mut buf = frame(OpCode::Binary, false, b"\xff");
+        buf.extend_from_slice(&frame(OpCode::Continue, true, b"\xff"));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        let codec = Codec::new().strict(Strict::UTF8);
+        let mut buf = frame(OpCode::Close, true, b"\x03\xe8\xff");
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+    }
+
+    #[test]
+    fn test_strict_reserved_bits() {
+        let mut buf = frame(OpCode::Binary, true, b"data");
+        buf[0] |= 0x40;
+        let codec = Codec::new();
+        assert!(codec.decode(&mut buf.clone()).unwrap().is_some());
+
+        let codec = Codec::new().strict(Strict::RESERVED_BITS);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::ReservedBits)
+        ));
+    }
+
+    #[test]
+    fn test_strict_close_code() {
+        let codec = Codec::new().strict(Strict::CLOSE_CODE);
+        for code in [1000u16, 1003, 1007, 1011, 3000, 4999] {
+            let mut buf = frame(OpCode::Close, true, &code.to_be_bytes());
+            assert!(codec.decode(&mut buf).unwrap().is_some());
+        }
+        for code in [0u16, 999, 1004, 1005, 1006, 1015, 1016, 2999, 5000] {
+            let mut buf = frame(OpCode::Close, true, &code.to_be_bytes());
+            assert!(matches!(
+                codec.decode(&mut buf),
+                Err(ProtocolError::InvalidCloseCode(c)) if c == code
+            ));
+        }
+        let mut buf = frame(OpCode::Close, true, b"\x03");
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidLength(1))
+        ));
+
+        let codec = Codec::new();
+        let mut buf = frame(OpCode::Close, true, &5000u16.to_be_bytes());
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_reassemble() {
+        let codec = Codec::new().reassemble(true);
+        let mut buf = frame(OpCode::Text, false, b"te");
+        buf.extend_from_slice(&frame(OpCode::Ping, true, b"ping"));
+        buf.extend_from_slice(&frame(OpCode::Continue, false, b"x"));
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Ping(Bytes::from_static(b"ping")))
+        );
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(&frame(OpCode::Continue, true, b"t"));
+        buf.extend_from_slice(&frame(OpCode::Binary, true, b"bin"));
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Text(Bytes::from_static(b"text")))
+        );
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Binary(Bytes::from_static(b"bin")))
+        );
+    }
+
+    #[test]
+    fn test_message_limits() {
+        let codec = Codec::new().max_message_size(4);
+        let mut buf = frame(OpCode::Binary, true, b"1234");
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        let mut buf = frame(OpCode::Binary, true, b"12345");
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::Overflow)
+        ));
+
+        let mut buf = frame(OpCode::Binary, false, b"12");
+        buf.extend_from_slice(&frame(OpCode::Continue, false, b"34"));
+        buf.extend_from_slice(&frame(OpCode::Continue, true, b"5"));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::Overflow)
+        ));
+
+        let codec = Codec::new().max_fragments(2);
+        let mut buf = frame(OpCode::Binary, false, b"1");
+        buf.extend_from_slice(&frame(OpCode::Continue, true, b"2"));
+        buf.extend_from_slice(&frame(OpCode::Binary, false, b"1"));
+        buf.extend_from_slice(&frame(OpCode::Continue, false, b"2"));
+        buf.extend_from_slice(&frame(OpCode::Continue, true, b"3"));
+        for _ in 0..4 {
+            assert!(codec.decode(&mut buf).unwrap().is_some());
+        }
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::TooManyFragments)
+        ));
+    }
+
+    #[test]
+    fn test_strict_fragmentation() {
+        let codec = Codec::new().strict(Strict::FRAGMENTATION);
+        let mut buf = frame(OpCode::Ping, false, b"ping");
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::ContinuationFragment(OpCode::Ping))
+        ));
+
+        let mut buf = frame(OpCode::Text, false, b"a");
+        buf.extend_from_slice(&frame(OpCode::Ping, true, b"ping"));
+        buf.extend_from_slice(&frame(OpCode::Text, true, b"b"));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::ContinuationStarted)
+        ));
+
+        let codec = Codec::new();
+        let mut buf = frame(OpCode::Text, false, b"a");
+        buf.extend_from_slice(&frame(OpCode::Text, true, b"b"));
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+    }
+}
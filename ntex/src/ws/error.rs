@@ -58,6 +58,18 @@ pub enum ProtocolError {
     /// Unknown continuation fragment
     #[error("Unknown continuation fragment {0}")]
     ContinuationFragment(OpCode),
+    /// Received frame with reserved bits set
+    #[error("Received frame with reserved bits set")]
+    ReservedBits,
+    /// Text message is not valid utf8
+    #[error("Text message is not valid utf8")]
+    InvalidUtf8,
+    /// Fragmented message has too many fragments
+    #[error("Fragmented message has too many fragments")]
+    TooManyFragments,
+    /// Invalid close code
+    #[error("Invalid close code: {0}")]
+    InvalidCloseCode(u16),
 }
 
 /// Websocket client error
@@ -9,15 +9,19 @@ mod frame;
 mod handshake;
 mod mask;
 mod proto;
+mod session;
 mod sink;
 mod transport;
 
 pub mod error;
 
 pub use self::client::{WsClient, WsClientBuilder, WsConnection};
-pub use self::codec::{Codec, Frame, Item, Message};
+pub use self::codec::{Codec, Frame, Item, Message, Strict};
 pub use self::frame::Parser;
 pub use self::handshake::{handshake, handshake_response, verify_handshake};
 pub use self::proto::{hash_key, CloseCode, CloseReason, OpCode};
+pub use self::session::{WsEvent, WsSession, WsSessionSink};
 pub use self::sink::WsSink;
 pub use self::transport::{WsTransport, WsTransportService};
+
+pub(crate) use self::codec::MessageConfig;
@@ -0,0 +1,330 @@
+use std::time::{Duration, Instant};
+use std::{cell::Cell, cell::RefCell, fmt, future::Future, rc::Rc};
+
+use crate::connect::{Connect, ConnectError};
+use crate::http::Uri;
+use crate::io::{Filter, Io};
+use crate::service::{into_service, Service};
+use crate::time::{now, sleep, Millis, Seconds};
+use crate::util::{select, BoxFuture, Ready};
+use crate::{channel::mpsc, rt, ws};
+
+use super::client::WsClient;
+use super::error::WsError;
+
+type OnConnect = Rc<dyn Fn(ws::WsSink) -> BoxFuture<'static, ()>>;
+
+/// Websockets client session.
+///
+/// Session maintains connection to the websockets server. If connection
+/// is lost, session reconnects with exponential backoff. Session sends
+/// ping messages and closes connection if peer does not respond within
+/// pong timeout.
+///
+/// Session is started with `WsSession::start()` method, it returns sink
+/// and stream of session events. Both survive reconnects.
+///
+/// ```rust,no_run
+/// use ntex::ws::{WsClient, WsEvent, WsSession};
+/// use ntex::time::{Millis, Seconds};
+///
+/// #[ntex::main]
+/// async fn main() {
+///     let client = WsClient::build("http://localhost:8080/ws").finish().unwrap();
+///     let (sink, rx) = WsSession::new(client)
+///         .backoff(Millis(100), Millis(10_000))
+///         .heartbeat(Seconds(15), Seconds(30))
+///         .on_connect(|sink| async move {
+///             // restore subscriptions
+///             let _ = sink.send(ntex::ws::Message::Text("subscribe".into())).await;
+///         })
+///         .start();
+///
+///     while let Some(event) = rx.recv().await {
+///         if let WsEvent::Frame(frame) = event {
+///             println!("Received: {:?}", frame);
+///         }
+///     }
+/// }
+/// ```
+pub struct WsSession<F, T> {
+    client: WsClient<F, T>,
+    backoff: Millis,
+    max_backoff: Millis,
+    max_attempts: usize,
+    ping_interval: Seconds,
+    pong_timeout: Seconds,
+    on_connect: Option<OnConnect>,
+}
+
+/// Websockets session event
+#[derive(Debug)]
+pub enum WsEvent {
+    /// Connection to the server is established
+    Connected,
+    /// Frame is received, ping and pong frames are handled by session
+    Frame(ws::Frame),
+    /// Connection to the server is lost
+    Disconnected,
+}
+
+/// Websockets session sink
+#[derive(Clone)]
+pub struct WsSessionSink(Rc<SessionState>);
+
+struct SessionState {
+    sink: RefCell<Option<ws::WsSink>>,
+    closed: Cell<bool>,
+}
+
+impl<F, T> WsSession<F, T>
+where
+    F: Filter,
+    T: Service<Connect<Uri>, Response = Io<F>, Error = ConnectError> + 'static,
+{
+    /// Create new session for websockets client
+    pub fn new(client: WsClient<F, T>) -> Self {
+        WsSession {
+            client,
+            backoff: Millis(100),
+            max_backoff: Millis(30_000),
+            max_attempts: 0,
+            ping_interval: Seconds(30),
+            pong_timeout: Seconds(60),
+            on_connect: None,
+        }
+    }
+
+    /// Set reconnect backoff.
+    ///
+    /// Delay between reconnect attempts grows exponentially from `initial`
+    /// value up to `max` value. By default, from 100 millis to 30 seconds.
+    pub fn backoff<U: Into<Millis>, M: Into<Millis>>(mut self, initial: U, max: M) -> Self {
+        self.backoff = initial.into();
+        self.max_backoff = max.into();
+        self
+    }
+
+    /// Set max number of consecutive failed connect attempts.
+    ///
+    /// Session is closed if limit is reached. By default number of attempts
+    /// is not limited.
+    pub fn max_attempts(mut self, num: usize) -> Self {
+        self.max_attempts = num;
+        self
+    }
+
+    /// Set heartbeat settings.
+    ///
+    /// Session sends ping every `interval` and closes connection if nothing is
+    /// received from peer within `timeout`. To disable heartbeat set interval to 0.
+    /// By default interval is 30 seconds and timeout is 60 seconds.
+    pub fn heartbeat(mut self, interval: Seconds, timeout: Seconds) -> Self {
+        self.ping_interval = interval;
+        self.pong_timeout = timeout;
+        self
+    }
+
+    /// Set callback for established connections.
+    ///
+    /// Callback is called after every successful connect, before frames
+    /// are delivered. It could be used for restoring subscriptions.
+    pub fn on_connect<C, R>(mut self, f: C) -> Self
+    where
+        C: Fn(ws::WsSink) -> R + 'static,
+        R: Future<Output = ()> + 'static,
+    {
+        self.on_connect = Some(Rc::new(move |sink| Box::pin(f(sink))));
+        self
+    }
+
+    /// Start session.
+    ///
+    /// Returns sink and stream of session events. Stream terminates when
+    /// session is closed.
+    pub fn start(self) -> (WsSessionSink, mpsc::Receiver<WsEvent>) {
+        let (tx, rx) = mpsc::channel();
+        let state = Rc::new(SessionState {
+            sink: RefCell::new(None),
+            closed: Cell::new(false),
+        });
+        let sink = WsSessionSink(state.clone());
+
+        rt::spawn(async move {
+            let mut attempts = 0;
+            loop {
+                match self.client.connect().await {
+                    Ok(con) => {
+                        attempts = 0;
+                        let con = con.seal();
+                        let ws_sink = con.sink();
+                        *state.sink.borrow_mut() = Some(ws_sink.clone());
+                        if tx.send(WsEvent::Connected).is_err() {
+                            break;
+                        }
+                        if let Some(ref f) = self.on_connect {
+                            (*f)(ws_sink.clone()).await;
+                        }
+
+                        let tx2 = tx.clone();
+                        let io = ws_sink.io().clone();
+                        let codec = con.codec().clone();
+                        let last = Rc::new(Cell::new(now()));
+                        let last2 = last.clone();
+                        let service = into_service(move |frame: ws::Frame| {
+                            last2.set(now());
+                            let res = match frame {
+                                ws::Frame::Ping(msg) => Some(ws::Message::Pong(msg)),
+                                ws::Frame::Pong(_) => None,
+                                ws::Frame::Close(reason) => {
+                                    let _ = tx2.send(WsEvent::Frame(ws::Frame::Close(
+                                        reason.clone(),
+                                    )));
+                                    // respond and close connection
+                                    let _ = io.encode(ws::Message::Close(reason), &codec);
+                                    io.close();
+                                    None
+                                }
+                                frame => {
+                                    if tx2.send(WsEvent::Frame(frame)).is_err() {
+                                        io.close();
+                                    }
+                                    None
+                                }
+                            };
+                            Ready::Ok::<_, ()>(res)
+                        });
+
+                        let _ = select(
+                            con.start(service),
+                            heartbeat(ws_sink, last, self.ping_interval, self.pong_timeout),
+                        )
+                        .await;
+                        log::trace!("Ws session connection is closed");
+
+                        state.sink.borrow_mut().take();
+                        if tx.send(WsEvent::Disconnected).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        log::trace!("Ws session cannot connect: {:?}", err);
+                        attempts += 1;
+                    }
+                }
+
+                if state.closed.get()
+                    || tx.is_closed()
+                    || (self.max_attempts > 0 && attempts >= self.max_attempts)
+                {
+                    break;
+                }
+
+                // reconnect backoff
+                if attempts > 0 {
+                    let exp = (attempts - 1).min(16) as u32;
+                    let delay = Millis(
+                        self.backoff
+                            .0
+                            .saturating_mul(2u32.pow(exp))
+                            .min(self.max_backoff.0),
+                    );
+                    sleep(delay).await;
+                }
+            }
+            state.closed.set(true);
+            tx.close();
+        });
+
+        (sink, rx)
+    }
+}
+
+/// Send pings and check peer liveness
+async fn heartbeat(
+    sink: ws::WsSink,
+    last: Rc<Cell<Instant>>,
+    interval: Seconds,
+    timeout: Seconds,
+) {
+    if interval.is_zero() {
+        return std::future::pending().await;
+    }
+
+    loop {
+        sleep(interval).await;
+        if now() - last.get() > Duration::from(timeout) {
+            log::trace!("Ws session pong timeout, closing connection");
+            sink.io().close();
+            return std::future::pending().await;
+        }
+        if sink
+            .send(ws::Message::Ping(Default::default()))
+            .await
+            .is_err()
+        {
+            return std::future::pending().await;
+        }
+    }
+}
+
+impl WsSessionSink {
+    /// Check if session is connected to the server
+    pub fn is_connected(&self) -> bool {
+        self.0.sink.borrow().is_some()
+    }
+
+    /// Check if session is closed
+    pub fn is_closed(&self) -> bool {
+        self.0.closed.get()
+    }
+
+    /// Encode and send message to the peer.
+    ///
+    /// Returns `WsError::Disconnected` error if session is not connected.
+    pub async fn send(&self, item: ws::Message) -> Result<(), WsError<()>> {
+        let sink = self.0.sink.borrow().clone();
+        if let Some(sink) = sink {
+            sink.send(item).await.map_err(WsError::Protocol)
+        } else {
+            Err(WsError::Disconnected(None))
+        }
+    }
+
+    /// Close session.
+    ///
+    /// Close frame is sent to the peer, session does not reconnect.
+    pub fn close(&self) {
+        self.0.closed.set(true);
+        let sink = self.0.sink.borrow().clone();
+        if let Some(sink) = sink {
+            rt::spawn(async move {
+                let _ = sink
+                    .send(ws::Message::Close(Some(ws::CloseCode::Normal.into())))
+                    .await;
+            });
+        }
+    }
+}
+
+impl<F, T> fmt::Debug for WsSession<F, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WsSession")
+            .field("client", &self.client)
+            .field("backoff", &self.backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("max_attempts", &self.max_attempts)
+            .field("ping_interval", &self.ping_interval)
+            .field("pong_timeout", &self.pong_timeout)
+            .finish()
+    }
+}
+
+impl fmt::Debug for WsSessionSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WsSessionSink")
+            .field("connected", &self.is_connected())
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
@@ -273,3 +273,23 @@ async fn test_rustls_uri() {
     let io = conn.call(addr.into()).await.unwrap();
     assert_eq!(io.query::<PeerAddr>().get().unwrap(), srv.addr().into());
 }
+
+#[cfg(all(unix, feature = "tokio"))]
+#[ntex::test]
+async fn test_unix_peer_cred() {
+    use ntex::io::types::PeerCred;
+
+    let (sock, _peer) = std::os::unix::net::UnixStream::pair().unwrap();
+    let io = ntex::rt::from_unix_stream(sock).unwrap();
+
+    let cred = io.query::<PeerCred>().get().unwrap();
+    let meta = std::fs::metadata("/proc/self").ok();
+    if let Some(pid) = cred.pid {
+        assert_eq!(pid as u32, std::process::id());
+    }
+    if let Some(meta) = meta {
+        use std::os::unix::fs::MetadataExt;
+        assert_eq!(cred.uid, meta.uid());
+        assert_eq!(cred.gid, meta.gid());
+    }
+}
@@ -1,7 +1,7 @@
-use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use brotli2::write::BrotliEncoder;
 use coo_kie::Cookie;
@@ -506,6 +506,48 @@ async fn test_client_gzip_encoding_large() {
     assert_eq!(bytes, Bytes::from(STR.repeat(10)));
 }
 
+#[ntex::test]
+async fn test_client_decompress_limits() {
+    let srv = test::server(|| {
+        App::new().service(web::resource("/").route(web::to(|| async {
+            let mut e = GzEncoder::new(Vec::new(), Compression::default());
+            e.write_all(&[0; 1_048_576]).unwrap();
+            let data = e.finish().unwrap();
+
+            HttpResponse::Ok()
+                .header("content-encoding", "gzip")
+                .body(data)
+        })))
+    });
+
+    // size limit
+    let client = Client::build().response_decompress_limit(65_536).finish();
+    let mut response = client.get(srv.url("/")).send().await.unwrap();
+    assert!(response.status().is_success());
+    assert!(response.body().limit(2_097_152).await.is_err());
+
+    // ratio limit
+    let client = Client::build().response_decompress_ratio(100).finish();
+    let mut response = client.get(srv.url("/")).send().await.unwrap();
+    assert!(response.body().limit(2_097_152).await.is_err());
+
+    // raw payload
+    let client = Client::build()
+        .no_decompress()
+        .response_decompress_limit(65_536)
+        .finish();
+    let mut response = client.get(srv.url("/")).send().await.unwrap();
+    let bytes = response.body().await.unwrap();
+    let mut d = GzDecoder::new(&bytes[..]);
+    let mut data = Vec::new();
+    d.read_to_end(&mut data).unwrap();
+    assert_eq!(data.len(), 1_048_576);
+
+    // decompress specific request
+    let mut response = client.get(srv.url("/")).decompress().send().await.unwrap();
+    assert!(response.body().limit(2_097_152).await.is_err());
+}
+
 #[ntex::test]
 async fn test_client_gzip_encoding_large_random() {
     let data = rand::thread_rng()
@@ -825,6 +867,60 @@ async fn client_read_until_eof() {
     assert_eq!(bytes, Bytes::from_static(b"welcome!"));
 }
 
+#[ntex::test]
+async fn client_informational_and_upgrade() {
+    let addr = ntex::server::TestServer::unused_addr();
+
+    std::thread::spawn(move || {
+        let lst = std::net::TcpListener::bind(addr).unwrap();
+
+        if let Some(Ok(mut stream)) = lst.incoming().next() {
+            let mut b = [0; 1000];
+            let _ = stream.read(&mut b).unwrap();
+            let _ = stream.write_all(
+                b"HTTP/1.1 103 Early Hints\r\nlink: </style.css>\r\n\r\n\
+                  HTTP/1.1 101 Switching Protocols\r\nconnection: upgrade\r\n\
+                  upgrade: custom\r\n\r\nhello",
+            );
+            let n = stream.read(&mut b).unwrap();
+            assert_eq!(&b[..n], b"ping");
+            let _ = stream.write_all(b"pong");
+        }
+    });
+    sleep(Millis(300)).await;
+
+    let hints = Rc::new(RefCell::new(Vec::new()));
+    let hints2 = hints.clone();
+    let mut response = Client::build()
+        .timeout(Seconds(5))
+        .finish()
+        .get(format!("http://{}/", addr).as_str())
+        .set_connection_type(ntex::http::ConnectionType::Upgrade)
+        .header(header::UPGRADE, "custom")
+        .informational(move |head| hints2.borrow_mut().push(head.status))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        response.status(),
+        ntex::http::StatusCode::SWITCHING_PROTOCOLS
+    );
+    assert_eq!(
+        &*hints.borrow(),
+        &[ntex::http::StatusCode::from_u16(103).unwrap()]
+    );
+
+    let io = response.take_io().unwrap();
+    assert!(response.take_io().is_none());
+
+    let codec = ntex::codec::BytesCodec;
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, Bytes::from_static(b"hello"));
+    io.send(Bytes::from_static(b"ping"), &codec).await.unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, Bytes::from_static(b"pong"));
+}
+
 #[ntex::test]
 async fn client_basic_auth() {
     let srv = test::server(|| {
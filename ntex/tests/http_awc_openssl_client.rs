@@ -86,3 +86,104 @@ async fn test_connection_reuse_h2() {
     // one connection
     assert_eq!(num.load(Ordering::Relaxed), 1);
 }
+
+#[ntex::test]
+async fn test_proxy_tunnel() {
+    use ntex::codec::BytesCodec;
+    use ntex::http::client::{error::ConnectError, error::SendRequestError, Proxy};
+    use ntex::http::StatusCode;
+    use ntex::io::Io;
+    use ntex::service::fn_service;
+    use ntex::util::{select, Bytes, Either};
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .h2(map_config(
+                App::new().service(
+                    web::resource("/").route(web::to(|| async { HttpResponse::Ok() })),
+                ),
+                |_| AppConfig::default(),
+            ))
+            .openssl(ssl_acceptor())
+    });
+
+    // tunnel proxy
+    let addr = srv.addr();
+    let proxy = ntex::server::test_server(move || {
+        fn_service(move |io: Io| async move {
+            let req = io.recv(&BytesCodec).await.unwrap().unwrap();
+            let req = String::from_utf8(req.to_vec()).unwrap();
+            if !req.contains("Proxy-Authorization: Bearer token\r\n") {
+                io.send(
+                    Bytes::from_static(b"HTTP/1.1 407 Proxy Authentication Required\r\ncontent-length: 0\r\n\r\n"),
+                    &BytesCodec,
+                )
+                .await
+                .unwrap();
+                return Ok(());
+            }
+            assert!(
+                req.starts_with(&format!("CONNECT localhost:{} HTTP/1.1\r\n", addr.port()))
+            );
+
+            let target = ntex::rt::tcp_connect(addr).await.unwrap();
+            io.send(
+                Bytes::from_static(b"HTTP/1.1 200 Connection established\r\n\r\n"),
+                &BytesCodec,
+            )
+            .await
+            .unwrap();
+            loop {
+                match select(io.recv(&BytesCodec), target.recv(&BytesCodec)).await {
+                    Either::Left(Ok(Some(buf))) => {
+                        let _ = target.send(buf, &BytesCodec).await;
+                    }
+                    Either::Right(Ok(Some(buf))) => {
+                        let _ = io.send(buf, &BytesCodec).await;
+                    }
+                    _ => break,
+                }
+            }
+            Ok::<_, std::io::Error>(())
+        })
+    });
+
+    // disable ssl verification
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    let _ = builder
+        .set_alpn_protos(b"\x02h2\x08http/1.1")
+        .map_err(|e| log::error!("Cannot set alpn protocol: {:?}", e));
+    let ssl = builder.build();
+    let connector = || {
+        Connector::default()
+            .timeout(Seconds(30))
+            .openssl(ssl.clone())
+            .finish()
+    };
+
+    let client = Client::build()
+        .connector(connector())
+        .proxy(
+            Proxy::https(format!("http://{}", proxy.addr()))
+                .unwrap()
+                .bearer_auth("token"),
+        )
+        .finish();
+    let response = client.get(srv.surl("/")).send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.version(), Version::HTTP_2);
+
+    // proxy rejects tunnel
+    let client = Client::build()
+        .connector(connector())
+        .proxy(Proxy::https(format!("http://{}", proxy.addr())).unwrap())
+        .finish();
+    let err = client.get(srv.surl("/")).send().await.unwrap_err();
+    assert!(matches!(
+        err,
+        SendRequestError::Connect(ConnectError::ProxyTunnel(
+            StatusCode::PROXY_AUTHENTICATION_REQUIRED
+        ))
+    ));
+}
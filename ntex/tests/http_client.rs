@@ -1,9 +1,10 @@
 use std::io;
 
 use ntex::http::test::server as test_server;
-use ntex::http::{HttpService, Method, Request, Response};
+use ntex::http::{HttpService, Method, Request, Response, StatusCode};
 use ntex::service::ServiceFactory;
-use ntex::util::{Bytes, Ready};
+use ntex::time::Millis;
+use ntex::util::{stream_recv, Bytes, BytesMut, Ready};
 
 const STR: &str = "Hello World Hello World Hello World Hello World Hello World \
                    Hello World Hello World Hello World Hello World Hello World \
@@ -87,3 +88,580 @@ async fn test_with_query_parameter() {
     let response = request.send().await.unwrap();
     assert!(response.status().is_success());
 }
+
+#[ntex::test]
+async fn test_expect_continue() {
+    let srv = test_server(move || {
+        HttpService::build()
+            .h1_lazy_continue(true)
+            .finish(|mut req: Request| async move {
+                if req.path() == "/reject" {
+                    return Ok::<_, io::Error>(Response::ExpectationFailed().finish());
+                }
+                let mut pl = req.take_payload();
+                let mut body = BytesMut::new();
+                while let Some(chunk) = stream_recv(&mut pl).await {
+                    body.extend_from_slice(&chunk.unwrap());
+                }
+                Ok::<_, io::Error>(Response::Ok().body(body.freeze()))
+            })
+    });
+
+    let mut response = srv
+        .request(Method::POST, "/")
+        .expect_continue()
+        .send_body(STR)
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+
+    let response = srv
+        .request(Method::POST, "/reject")
+        .expect_continue()
+        .send_body(STR)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::EXPECTATION_FAILED);
+}
+
+#[ntex::test]
+async fn test_redirects() {
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(|mut req: Request| async move {
+                let res = match req.path() {
+                    "/found" => Response::Found().header("location", "/target").finish(),
+                    "/temporary" => Response::TemporaryRedirect()
+                        .header("location", "target")
+                        .finish(),
+                    "/loop" => Response::Found().header("location", "/loop").finish(),
+                    _ => {
+                        let mut pl = req.take_payload();
+                        let mut body = BytesMut::new();
+                        while let Some(Ok(chunk)) = stream_recv(&mut pl).await {
+                            body.extend_from_slice(&chunk);
+                        }
+                        Response::Ok().body(format!("{} {:?}", req.method(), body.freeze()))
+                    }
+                };
+                Ok::<_, io::Error>(res)
+            })
+            .map(|_| ())
+    });
+
+    let mut response = srv
+        .request(Method::POST, "/found")
+        .send_body("data")
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.url().path(), "/target");
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"GET b\"\""));
+
+    let mut response = srv
+        .request(Method::POST, "/temporary")
+        .send_body("data")
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"POST b\"data\""));
+
+    let res = srv.request(Method::GET, "/loop").send().await;
+    assert!(matches!(
+        res,
+        Err(ntex::http::client::error::SendRequestError::TooManyRedirects)
+    ));
+}
+
+#[ntex::test]
+async fn test_retry() {
+    use std::sync::{atomic::AtomicUsize, atomic::Ordering::Relaxed, Arc};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter2 = counter.clone();
+    let srv = test_server(move || {
+        let counter = counter2.clone();
+        HttpService::build()
+            .finish(move |_: Request| {
+                let res = if counter.fetch_add(1, Relaxed) % 3 < 2 {
+                    Response::ServiceUnavailable().finish()
+                } else {
+                    Response::Ok().finish()
+                };
+                Ready::Ok::<_, io::Error>(res)
+            })
+            .map(|_| ())
+    });
+
+    let policy = ntex::http::client::RetryPolicy::new().backoff(Millis(1), Millis(10));
+    let response = srv
+        .request(Method::GET, "/")
+        .retry(policy.clone())
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(counter.load(Relaxed), 3);
+
+    // retries are exhausted
+    let response = srv
+        .request(Method::GET, "/")
+        .retry(policy.clone().max_retries(1))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(counter.load(Relaxed), 5);
+
+    // POST is not retried
+    let response = srv
+        .request(Method::POST, "/")
+        .retry(policy)
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(counter.load(Relaxed), 6);
+}
+
+#[ntex::test]
+async fn test_timeouts() {
+    use ntex::http::client::error::{SendRequestError, TimeoutPhase};
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(|_: Request| async {
+                ntex::time::sleep(Millis(500)).await;
+                Ok::<_, io::Error>(Response::Ok().finish())
+            })
+            .map(|_| ())
+    });
+
+    let err = srv
+        .request(Method::GET, "/")
+        .timeout(Millis(100))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SendRequestError::Timeout));
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::FirstByte));
+
+    let err = srv
+        .request(Method::GET, "/")
+        .deadline(Millis(100))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SendRequestError::DeadlineExceeded));
+    assert_eq!(err.timeout_phase(), Some(TimeoutPhase::Deadline));
+
+    let response = srv
+        .request(Method::GET, "/")
+        .deadline(Millis(2_000))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+}
+
+#[ntex::test]
+async fn test_io_factory() {
+    use ntex::http::client::{Client, Connector};
+    use ntex::http::header;
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(|req: Request| {
+                let host = req.headers().get(header::HOST).unwrap().to_str().unwrap();
+                Ready::Ok::<_, io::Error>(Response::Ok().body(host.to_string()))
+            })
+            .map(|_| ())
+    });
+
+    let addr = srv.addr();
+    let client = Client::build()
+        .connector(
+            Connector::default()
+                .io_factory(move |_| ntex::rt::tcp_connect(addr))
+                .finish(),
+        )
+        .finish();
+
+    let mut response = client.get("http://docker/version").send().await.unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"docker"));
+}
+
+#[ntex::test]
+async fn test_client_middleware() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use ntex::http::client::error::SendRequestError;
+    use ntex::http::client::{Client, ClientMessage, ClientResponse, ClientService};
+    use ntex::http::header::{self, HeaderValue};
+    use ntex::service::{Middleware, Service, ServiceCtx};
+
+    struct Auth(&'static str, Rc<RefCell<Vec<&'static str>>>);
+
+    impl Middleware<ClientService> for Auth {
+        type Service = AuthService;
+
+        fn create(&self, service: ClientService) -> AuthService {
+            AuthService(service, self.0, self.1.clone())
+        }
+    }
+
+    struct AuthService(ClientService, &'static str, Rc<RefCell<Vec<&'static str>>>);
+
+    impl Service<ClientMessage> for AuthService {
+        type Response = ClientResponse;
+        type Error = SendRequestError;
+
+        async fn call(
+            &self,
+            mut msg: ClientMessage,
+            ctx: ServiceCtx<'_, Self>,
+        ) -> Result<ClientResponse, SendRequestError> {
+            self.2.borrow_mut().push(self.1);
+            msg.set_header(header::AUTHORIZATION, HeaderValue::from_static(self.1));
+            ctx.call(&self.0, msg).await
+        }
+    }
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(|req: Request| {
+                let auth = req
+                    .headers()
+                    .get(header::AUTHORIZATION)
+                    .map(|h| h.to_str().unwrap().to_string())
+                    .unwrap_or_default();
+                Ready::Ok::<_, io::Error>(Response::Ok().body(auth))
+            })
+            .map(|_| ())
+    });
+
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let client = Client::build()
+        .wrap(Auth("inner", calls.clone()))
+        .wrap(Auth("outer", calls.clone()))
+        .finish();
+
+    let mut response = client.get(srv.url("/")).send().await.unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"inner"));
+    assert_eq!(*calls.borrow(), vec!["outer", "inner"]);
+
+    // frozen request
+    let request = client.get(srv.url("/")).freeze().unwrap();
+    let mut response = request.send().await.unwrap();
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"inner"));
+    assert_eq!(calls.borrow().len(), 4);
+}
+
+#[ntex::test]
+async fn test_proxy() {
+    use ntex::http::client::{Client, Proxy};
+    use ntex::http::header;
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(|req: Request| {
+                let auth = req
+                    .headers()
+                    .get(header::PROXY_AUTHORIZATION)
+                    .map(|h| h.to_str().unwrap().to_string())
+                    .unwrap_or_default();
+                Ready::Ok::<_, io::Error>(Response::Ok().body(format!(
+                    "{} {}",
+                    req.uri(),
+                    auth
+                )))
+            })
+            .map(|_| ())
+    });
+
+    // plain requests are sent in absolute form
+    let client = Client::build()
+        .proxy(
+            Proxy::http(srv.url("/"))
+                .unwrap()
+                .basic_auth("user", Some("pass")),
+        )
+        .finish();
+    let mut response = client
+        .get("http://example.com/path?q=1")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(
+        bytes,
+        Bytes::from_static(b"http://example.com/path?q=1 Basic dXNlcjpwYXNz")
+    );
+
+    // bypass proxy
+    let client = Client::build()
+        .proxy(
+            Proxy::all("http://127.0.0.1:1")
+                .unwrap()
+                .no_proxy("localhost, 10.0.0.0/8"),
+        )
+        .finish();
+    let mut response = client.get(srv.url("/test")).send().await.unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"/test "));
+}
+
+#[ntex::test]
+async fn test_client_cache() {
+    use std::sync::{atomic::AtomicUsize, atomic::Ordering::Relaxed, Arc};
+
+    use ntex::http::client::{Client, ClientCache, MemoryStore};
+    use ntex::http::header::{self, HeaderValue};
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter2 = counter.clone();
+    let srv = test_server(move || {
+        let counter = counter2.clone();
+        HttpService::build()
+            .finish(move |req: Request| {
+                counter.fetch_add(1, Relaxed);
+                let res = match req.path() {
+                    "/fresh" => Response::Ok()
+                        .header(header::CACHE_CONTROL, "max-age=60")
+                        .body("fresh"),
+                    "/etag" => {
+                        if req.headers().get(header::IF_NONE_MATCH)
+                            == Some(&HeaderValue::from_static("\"v1\""))
+                        {
+                            Response::NotModified()
+                                .header(header::ETAG, "\"v1\"")
+                                .finish()
+                        } else {
+                            Response::Ok()
+                                .header(header::CACHE_CONTROL, "no-cache")
+                                .header(header::ETAG, "\"v1\"")
+                                .body("etag")
+                        }
+                    }
+                    _ => Response::Ok().body("no-cache"),
+                };
+                Ready::Ok::<_, io::Error>(res)
+            })
+            .map(|_| ())
+    });
+
+    let store = MemoryStore::new(16);
+    let client = Client::build()
+        .wrap(ClientCache::new(store.clone()))
+        .finish();
+
+    // fresh response is served from cache
+    for _ in 0..2 {
+        let mut response = client.get(srv.url("/fresh")).send().await.unwrap();
+        assert!(response.status().is_success());
+        let bytes = response.body().await.unwrap();
+        assert_eq!(bytes, Bytes::from_static(b"fresh"));
+    }
+    assert_eq!(counter.load(Relaxed), 1);
+
+    // no-store request bypasses cache
+    let mut response = client
+        .get(srv.url("/fresh"))
+        .header(header::CACHE_CONTROL, "no-store")
+        .send()
+        .await
+        .unwrap();
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"fresh"));
+    assert_eq!(counter.load(Relaxed), 2);
+
+    // stale response is revalidated
+    for _ in 0..2 {
+        let mut response = client.get(srv.url("/etag")).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.body().await.unwrap();
+        assert_eq!(bytes, Bytes::from_static(b"etag"));
+    }
+    assert_eq!(counter.load(Relaxed), 4);
+
+    // response without validators is not cached
+    let mut response = client.get(srv.url("/other")).send().await.unwrap();
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"no-cache"));
+    assert_eq!(store.len(), 2);
+
+    // unsafe method invalidates cached response
+    let response = client.post(srv.url("/fresh")).send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(store.len(), 1);
+}
+
+#[cfg(feature = "digest-auth")]
+#[ntex::test]
+async fn test_digest_auth() {
+    use std::sync::{atomic::AtomicUsize, atomic::Ordering::Relaxed, Arc};
+
+    use ntex::http::client::{Client, DigestAuth};
+    use ntex::http::header;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter2 = counter.clone();
+    let srv = test_server(move || {
+        let counter = counter2.clone();
+        HttpService::build()
+            .finish(move |req: Request| {
+                counter.fetch_add(1, Relaxed);
+                let auth = req
+                    .headers()
+                    .get(header::AUTHORIZATION)
+                    .map(|h| h.to_str().unwrap().to_string())
+                    .unwrap_or_default();
+                let res = if auth.starts_with("Digest ")
+                    && auth.contains("username=\"user\"")
+                    && auth.contains("nonce=\"abc\"")
+                    && auth.contains("qop=auth")
+                {
+                    Response::Ok().body(auth)
+                } else {
+                    Response::Unauthorized()
+                        .header(
+                            header::WWW_AUTHENTICATE,
+                            "Digest realm=\"test\", qop=\"auth\", nonce=\"abc\"",
+                        )
+                        .finish()
+                };
+                Ready::Ok::<_, io::Error>(res)
+            })
+            .map(|_| ())
+    });
+
+    let client = Client::build()
+        .wrap(DigestAuth::new("user", "pass"))
+        .finish();
+
+    let mut response = client.get(srv.url("/")).send().await.unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert!(bytes.starts_with(b"Digest "));
+    assert_eq!(counter.load(Relaxed), 2);
+
+    // challenge is reused
+    let response = client.get(srv.url("/")).send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(counter.load(Relaxed), 3);
+
+    // explicit authorization is not replaced
+    let response = client
+        .get(srv.url("/"))
+        .basic_auth("user", Some("pass"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[ntex::test]
+async fn test_local_address() {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use ntex::http::client::Client;
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(|req: Request| {
+                let peer = req
+                    .peer_addr()
+                    .map(|a| a.ip().to_string())
+                    .unwrap_or_default();
+                Ready::Ok::<_, io::Error>(Response::Ok().body(peer))
+            })
+            .map(|_| ())
+    });
+
+    let local = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let client = Client::build().local_address(local).finish();
+    let mut response = client.get(srv.url("/")).send().await.unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"127.0.0.1"));
+
+    // per-request binding
+    let client = Client::new();
+    let mut response = client
+        .get(srv.url("/"))
+        .local_address(local)
+        .send()
+        .await
+        .unwrap();
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"127.0.0.1"));
+
+    // address of other family could not be bound
+    let response = client
+        .get(srv.url("/"))
+        .local_address("::1".parse().unwrap())
+        .send()
+        .await;
+    assert!(response.is_err());
+}
+
+#[ntex::test]
+async fn test_progress() {
+    use std::{cell::RefCell, rc::Rc};
+
+    use ntex::http::client::{Client, Progress};
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(|mut req: Request| async move {
+                let mut pl = req.take_payload();
+                let mut body = BytesMut::new();
+                while let Some(item) = stream_recv(&mut pl).await {
+                    body.extend_from_slice(&item.unwrap());
+                }
+                Ok::<_, io::Error>(Response::Ok().body(body.freeze()))
+            })
+            .map(|_| ())
+    });
+
+    let upload = Rc::new(RefCell::new(Vec::<Progress>::new()));
+    let download = Rc::new(RefCell::new(Vec::<Progress>::new()));
+    let (up, down) = (upload.clone(), download.clone());
+
+    let data = STR.repeat(100);
+    let mut response = Client::new()
+        .post(srv.url("/"))
+        .upload_progress(move |p| up.borrow_mut().push(p))
+        .download_progress(move |p| down.borrow_mut().push(p))
+        .send_body(data.clone())
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().limit(1024 * 1024).await.unwrap();
+    assert_eq!(bytes, Bytes::from(data.clone()));
+
+    let size = data.len() as u64;
+    let upload = upload.borrow();
+    assert!(upload.len() > 1);
+    let last = upload.last().unwrap();
+    assert!(last.is_complete());
+    assert_eq!(last.transferred(), size);
+    assert_eq!(last.total(), Some(size));
+
+    let download = download.borrow();
+    let last = download.last().unwrap();
+    assert!(last.is_complete());
+    assert_eq!(last.transferred(), size);
+    assert_eq!(last.total(), Some(size));
+}
@@ -11,10 +11,42 @@ use ntex::http::test::server as test_server;
 use ntex::http::{
     body, HttpService, KeepAlive, Method, Request, Response, StatusCode, Version,
 };
-use ntex::service::fn_service;
+use ntex::service::{fn_service, ServiceFactory};
 use ntex::time::{sleep, timeout, Millis, Seconds};
 use ntex::{util::Bytes, util::Ready, web::error};
 
+#[ntex::test]
+async fn test_h2c_prior_knowledge() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .h2c(true)
+            .finish(|_| Ready::Ok::<_, io::Error>(Response::Ok().finish()))
+    });
+
+    // connection preface and empty settings frame
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0");
+    let mut data = [0; 9];
+    let _ = stream.read_exact(&mut data[..]);
+    // server's settings frame
+    assert_eq!(data[3], 0x04);
+
+    // regular http/1 requests
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nconnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"));
+}
+
+#[ntex::test]
+async fn test_h1_h2c_not_supported() {
+    let factory: ntex::http::h1::H1Service<ntex::io::Base, _, _, _> = HttpService::build()
+        .h2c(true)
+        .h1(|_: Request| Ready::Ok::<_, io::Error>(Response::Ok().finish()));
+    assert!(factory.create(()).await.is_err());
+}
+
 #[ntex::test]
 async fn test_h1() {
     let srv = test_server(|| {
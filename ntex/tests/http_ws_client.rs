@@ -221,3 +221,92 @@ async fn test_upgrade_handler_with_await() {
         .await
         .unwrap();
 }
+
+#[ntex::test]
+async fn test_session() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1_control(|req: h1::Control<_, _>| async move {
+                let ack = if let h1::Control::Upgrade(upg) = req {
+                    upg.handle(|req, io, codec| async move {
+                        let res = handshake_response(req.head()).finish();
+
+                        // send handshake respone
+                        io.encode(
+                            h1::Message::Item((res.drop_body(), BodySize::None)),
+                            &codec,
+                        )
+                        .unwrap();
+
+                        // start websocket service
+                        Dispatcher::new(
+                            io.seal(),
+                            ws::Codec::default(),
+                            ws_service,
+                            &Default::default(),
+                        )
+                        .await
+                    })
+                } else {
+                    req.ack()
+                };
+                Ok::<_, io::Error>(ack)
+            })
+            .finish(|_| Ready::Ok::<_, io::Error>(Response::NotFound()))
+    });
+
+    let client = ws::WsClient::build(srv.url("/"))
+        .address(srv.addr())
+        .finish()
+        .unwrap();
+    let (sink, rx) = ws::WsSession::new(client)
+        .backoff(ntex::time::Millis(10), ntex::time::Millis(100))
+        .heartbeat(Seconds(1), Seconds(5))
+        .on_connect(|sink| async move {
+            sink.send(ws::Message::Text(ByteString::from_static("hello")))
+                .await
+                .unwrap();
+        })
+        .start();
+
+    assert!(matches!(rx.recv().await.unwrap(), ws::WsEvent::Connected));
+    assert!(sink.is_connected());
+    let item = rx.recv().await.unwrap();
+    assert!(
+        matches!(item, ws::WsEvent::Frame(ws::Frame::Text(ref t)) if t == &Bytes::from_static(b"hello"))
+    );
+
+    sink.send(ws::Message::Binary(Bytes::from_static(b"text")))
+        .await
+        .unwrap();
+    let item = rx.recv().await.unwrap();
+    assert!(
+        matches!(item, ws::WsEvent::Frame(ws::Frame::Binary(ref t)) if t == &Bytes::from_static(b"text"))
+    );
+
+    // server closes connection, session reconnects
+    sink.send(ws::Message::Close(None)).await.unwrap();
+    assert!(matches!(
+        rx.recv().await.unwrap(),
+        ws::WsEvent::Frame(ws::Frame::Close(None))
+    ));
+    assert!(matches!(
+        rx.recv().await.unwrap(),
+        ws::WsEvent::Disconnected
+    ));
+    assert!(matches!(rx.recv().await.unwrap(), ws::WsEvent::Connected));
+    let item = rx.recv().await.unwrap();
+    assert!(matches!(item, ws::WsEvent::Frame(ws::Frame::Text(_))));
+
+    // close session
+    sink.close();
+    loop {
+        match rx.recv().await {
+            Some(ws::WsEvent::Connected) => panic!(),
+            Some(_) => (),
+            None => break,
+        }
+    }
+    assert!(sink.is_closed());
+    assert!(!sink.is_connected());
+}
@@ -129,6 +129,61 @@ async fn test_run() {
     let _ = h.join();
 }
 
+#[ntex::test]
+async fn test_background() {
+    let addr = TestServer::unused_addr();
+    let (tx, rx) = mpsc::channel();
+    let started = Arc::new(AtomicUsize::new(0));
+    let stopped = Arc::new(AtomicUsize::new(0));
+    let (started2, stopped2) = (started.clone(), stopped.clone());
+
+    let h = thread::spawn(move || {
+        let sys = ntex::rt::System::new("test");
+        let _ = sys.run(move || {
+            let (started, stopped) = (started2.clone(), stopped2.clone());
+            let srv = build()
+                .disable_signals()
+                .workers(2)
+                .bind("test", addr, move |_| {
+                    fn_service(|_| Ready::Ok::<_, ()>(()))
+                })
+                .unwrap()
+                .background(move |shutdown| {
+                    let (started, stopped) = (started.clone(), stopped.clone());
+                    async move {
+                        let _ = started.fetch_add(1, Relaxed);
+                        let token = shutdown.token();
+                        shutdown.stopped().await;
+                        assert!(shutdown.is_stopped());
+                        token.cancelled().await;
+                        let _ = stopped.fetch_add(1, Relaxed);
+                    }
+                })
+                .background_global(move |shutdown| {
+                    let (started, stopped) = (started2.clone(), stopped2.clone());
+                    async move {
+                        let _ = started.fetch_add(10, Relaxed);
+                        shutdown.stopped().await;
+                        let _ = stopped.fetch_add(10, Relaxed);
+                    }
+                })
+                .run();
+            let _ = tx.send((srv, ntex::rt::System::current()));
+            Ok(())
+        });
+    });
+    let (srv, sys) = rx.recv().unwrap();
+
+    thread::sleep(time::Duration::from_millis(500));
+    assert_eq!(started.load(Relaxed), 12);
+    assert_eq!(stopped.load(Relaxed), 0);
+
+    srv.stop(true).await;
+    assert_eq!(stopped.load(Relaxed), 12);
+    sys.stop();
+    let _ = h.join();
+}
+
 #[test]
 #[cfg(feature = "tokio")]
 fn test_on_worker_start() {
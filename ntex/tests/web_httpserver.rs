@@ -191,6 +191,40 @@ async fn test_rustls() {
     sys.stop();
 }
 
+#[test]
+#[cfg(feature = "rustls")]
+fn test_bind_tls_alpn_validation() {
+    let app = || {
+        App::new().service(
+            web::resource("/").route(web::to(|| async { HttpResponse::Ok().body("test") })),
+        )
+    };
+
+    let mut config = rustls_utils::tls_acceptor();
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let err = HttpServer::new(app)
+        .bind_tls("127.0.0.1:0", config)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let mut config = rustls_utils::tls_acceptor();
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    let err = HttpServer::new(app)
+        .http2(false)
+        .bind_tls("127.0.0.1:0", config)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let err = HttpServer::new(app)
+        .http2(false)
+        .bind_h2c("127.0.0.1:0")
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
 #[ntex::test]
 #[cfg(unix)]
 async fn test_bind_uds() {
@@ -66,6 +66,69 @@ async fn web_ws() {
     assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Away.into())));
 }
 
+#[ntex::test]
+async fn web_ws_reassemble() {
+    let srv = test::server(|| {
+        App::new().service(web::resource("/").route(web::to(
+            |req: HttpRequest| async move {
+                ws::start_with_codec::<_, _, web::Error>(
+                    req,
+                    ntex::ws::Codec::new()
+                        .max_message_size(8)
+                        .max_fragments(4)
+                        .reassemble(true),
+                    fn_factory_with_config(|_| async {
+                        Ok::<_, web::Error>(fn_service(service))
+                    }),
+                )
+                .await
+            },
+        )))
+    });
+
+    // fragmented message is delivered as one frame
+    let (io, codec, _) = srv.ws().await.unwrap().into_inner();
+    for msg in [
+        ws::Message::Continuation(ntex::ws::Item::FirstText("te".into())),
+        ws::Message::Ping("ping".into()),
+        ws::Message::Continuation(ntex::ws::Item::Continue("x".into())),
+        ws::Message::Continuation(ntex::ws::Item::Last("t".into())),
+    ] {
+        io.send(msg, &codec).await.unwrap();
+    }
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Pong(Bytes::from_static(b"ping")));
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Text(Bytes::from_static(b"text")));
+
+    // message size limit
+    for msg in [
+        ws::Message::Continuation(ntex::ws::Item::FirstBinary("12345".into())),
+        ws::Message::Continuation(ntex::ws::Item::Last("6789".into())),
+    ] {
+        io.send(msg, &codec).await.unwrap();
+    }
+    assert!(!matches!(io.recv(&codec).await, Ok(Some(_))));
+
+    // fragments limit
+    let (io, codec, _) = srv.ws().await.unwrap().into_inner();
+    io.send(
+        ws::Message::Continuation(ntex::ws::Item::FirstBinary("1".into())),
+        &codec,
+    )
+    .await
+    .unwrap();
+    for _ in 0..4 {
+        io.send(
+            ws::Message::Continuation(ntex::ws::Item::Continue("1".into())),
+            &codec,
+        )
+        .await
+        .unwrap();
+    }
+    assert!(!matches!(io.recv(&codec).await, Ok(Some(_))));
+}
+
 #[ntex::test]
 async fn web_no_ws() {
     let srv = test::server(|| {